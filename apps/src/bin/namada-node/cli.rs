@@ -33,11 +33,29 @@ pub fn main() -> Result<()> {
                 let chain_ctx = ctx.take_chain_or_exit();
                 ledger::dump_db(chain_ctx.config.ledger, args);
             }
-            cmds::Ledger::RollBack(_) => {
+            cmds::Ledger::RollBack(cmds::LedgerRollBack(args)) => {
                 let chain_ctx = ctx.take_chain_or_exit();
-                ledger::rollback(chain_ctx.config.ledger)
+                ledger::rollback(chain_ctx.config.ledger, args)
                     .wrap_err("Failed to rollback the Namada node")?;
             }
+            cmds::Ledger::ExportState(cmds::LedgerExportState(args)) => {
+                let chain_ctx = ctx.take_chain_or_exit();
+                ledger::export_state(chain_ctx.config.ledger, args)
+                    .wrap_err("Failed to export the Namada state")?;
+            }
+            cmds::Ledger::ImportState(cmds::LedgerImportState(args)) => {
+                let chain_ctx = ctx.take_chain_or_exit();
+                ledger::import_state(chain_ctx.config.ledger, args)
+                    .wrap_err("Failed to import the Namada state")?;
+            }
+        },
+        cmds::NamadaNode::Utils(sub) => match sub {
+            cmds::NodeUtils::PrecompileWasm(cmds::PrecompileWasm) => {
+                let chain_ctx = ctx.take_chain_or_exit();
+                let wasm_dir = chain_ctx.wasm_dir();
+                ledger::precompile_wasm(chain_ctx.config.ledger, wasm_dir)
+                    .wrap_err("Failed to pre-compile the WASMs")?;
+            }
         },
         cmds::NamadaNode::Config(sub) => match sub {
             cmds::Config::Gen(cmds::ConfigGen) => {
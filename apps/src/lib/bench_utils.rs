@@ -52,7 +52,6 @@ use namada::ibc::core::Msg;
 use namada::ibc::Height as IbcHeight;
 use namada::ibc_proto::google::protobuf::Any;
 use namada::ibc_proto::protobuf::Protobuf;
-use namada::ledger::dry_run_tx;
 use namada::ledger::gas::TxGasMeter;
 use namada::ledger::ibc::storage::{channel_key, connection_key};
 use namada::ledger::native_vp::ibc::get_dummy_header;
@@ -60,6 +59,7 @@ use namada::ledger::queries::{
     Client, EncodedResponseQuery, RequestCtx, RequestQuery, Router, RPC,
 };
 use namada::ledger::storage_api::StorageRead;
+use namada::ledger::{dry_run_tx, estimate_fee, simulate_tx};
 use namada::proto::{Code, Data, Section, Signature, Tx};
 use namada::tendermint::Hash;
 use namada::tendermint_rpc::{self};
@@ -164,6 +164,7 @@ impl Default for BenchShell {
             sender,
             None,
             None,
+            None,
             50 * 1024 * 1024, // 50 kiB
             50 * 1024 * 1024, // 50 kiB
         );
@@ -707,6 +708,7 @@ impl Client for BenchShell {
         let ctx = RequestCtx {
             wl_storage: &self.wl_storage,
             event_log: self.event_log(),
+            key_change_log: self.key_change_log(),
             vp_wasm_cache: self.vp_wasm_cache.read_only(),
             tx_wasm_cache: self.tx_wasm_cache.read_only(),
             storage_read_past_height_limit: None,
@@ -714,6 +716,10 @@ impl Client for BenchShell {
 
         if request.path == "/shell/dry_run_tx" {
             dry_run_tx(ctx, &request)
+        } else if request.path == "/shell/simulate_tx" {
+            simulate_tx(ctx, &request)
+        } else if request.path == "/shell/estimate_fee" {
+            estimate_fee(ctx, &request)
         } else {
             RPC.handle(ctx, &request)
         }
@@ -123,17 +123,21 @@ pub mod cmds {
     pub enum NamadaNode {
         Ledger(Ledger),
         Config(Config),
+        Utils(NodeUtils),
     }
 
     impl Cmd for NamadaNode {
         fn add_sub(app: App) -> App {
-            app.subcommand(Ledger::def()).subcommand(Config::def())
+            app.subcommand(Ledger::def())
+                .subcommand(Config::def())
+                .subcommand(NodeUtils::def())
         }
 
         fn parse(matches: &ArgMatches) -> Option<Self> {
             let ledger = SubCmd::parse(matches).map(Self::Ledger);
             let config = SubCmd::parse(matches).map(Self::Config);
-            ledger.or(config)
+            let utils = SubCmd::parse(matches).map(Self::Utils);
+            ledger.or(config).or(utils)
         }
     }
     impl SubCmd for NamadaNode {
@@ -222,6 +226,8 @@ pub mod cmds {
                 // Governance transactions
                 .subcommand(TxInitProposal::def().display_order(1))
                 .subcommand(TxVoteProposal::def().display_order(1))
+                .subcommand(TxDelegateVotes::def().display_order(1))
+                .subcommand(TxDepositProposal::def().display_order(1))
                 // PoS transactions
                 .subcommand(TxInitValidator::def().display_order(2))
                 .subcommand(TxUnjailValidator::def().display_order(2))
@@ -240,6 +246,13 @@ pub mod cmds {
                 // PGF transactions
                 .subcommand(TxUpdateStewardCommission::def().display_order(4))
                 .subcommand(TxResignSteward::def().display_order(4))
+                .subcommand(TxPausePgfFunding::def().display_order(4))
+                // Scheduled transactions
+                .subcommand(TxScheduleTx::def().display_order(4))
+                .subcommand(TxCancelScheduledTx::def().display_order(4))
+                // Fee allowances
+                .subcommand(TxGrantFeeAllowance::def().display_order(4))
+                .subcommand(TxRevokeFeeAllowance::def().display_order(4))
                 // Queries
                 .subcommand(QueryEpoch::def().display_order(5))
                 .subcommand(QueryAccount::def().display_order(5))
@@ -258,6 +271,9 @@ pub mod cmds {
                 .subcommand(QueryProposalResult::def().display_order(5))
                 .subcommand(QueryProtocolParameters::def().display_order(5))
                 .subcommand(QueryPgf::def().display_order(5))
+                .subcommand(QueryScheduledTxs::def().display_order(5))
+                .subcommand(QueryFeeAllowances::def().display_order(5))
+                .subcommand(QueryVoteDelegations::def().display_order(5))
                 .subcommand(QueryValidatorState::def().display_order(5))
                 .subcommand(QueryCommissionRate::def().display_order(5))
                 .subcommand(QueryMetaData::def().display_order(5))
@@ -289,10 +305,23 @@ pub mod cmds {
                 Self::parse_with_ctx(matches, TxInitProposal);
             let tx_vote_proposal =
                 Self::parse_with_ctx(matches, TxVoteProposal);
+            let tx_delegate_votes =
+                Self::parse_with_ctx(matches, TxDelegateVotes);
+            let tx_deposit_proposal =
+                Self::parse_with_ctx(matches, TxDepositProposal);
             let tx_update_steward_commission =
                 Self::parse_with_ctx(matches, TxUpdateStewardCommission);
             let tx_resign_steward =
                 Self::parse_with_ctx(matches, TxResignSteward);
+            let tx_pause_pgf_funding =
+                Self::parse_with_ctx(matches, TxPausePgfFunding);
+            let tx_schedule_tx = Self::parse_with_ctx(matches, TxScheduleTx);
+            let tx_cancel_scheduled_tx =
+                Self::parse_with_ctx(matches, TxCancelScheduledTx);
+            let tx_grant_fee_allowance =
+                Self::parse_with_ctx(matches, TxGrantFeeAllowance);
+            let tx_revoke_fee_allowance =
+                Self::parse_with_ctx(matches, TxRevokeFeeAllowance);
             let tx_commission_rate_change =
                 Self::parse_with_ctx(matches, TxCommissionRateChange);
             let tx_change_consensus_key =
@@ -327,6 +356,12 @@ pub mod cmds {
             let query_protocol_parameters =
                 Self::parse_with_ctx(matches, QueryProtocolParameters);
             let query_pgf = Self::parse_with_ctx(matches, QueryPgf);
+            let query_scheduled_txs =
+                Self::parse_with_ctx(matches, QueryScheduledTxs);
+            let query_fee_allowances =
+                Self::parse_with_ctx(matches, QueryFeeAllowances);
+            let query_vote_delegations =
+                Self::parse_with_ctx(matches, QueryVoteDelegations);
             let query_validator_state =
                 Self::parse_with_ctx(matches, QueryValidatorState);
             let query_commission =
@@ -346,6 +381,8 @@ pub mod cmds {
                 .or(tx_reveal_pk)
                 .or(tx_init_proposal)
                 .or(tx_vote_proposal)
+                .or(tx_delegate_votes)
+                .or(tx_deposit_proposal)
                 .or(tx_init_validator)
                 .or(tx_commission_rate_change)
                 .or(tx_change_consensus_key)
@@ -361,6 +398,11 @@ pub mod cmds {
                 .or(add_to_eth_bridge_pool)
                 .or(tx_update_steward_commission)
                 .or(tx_resign_steward)
+                .or(tx_pause_pgf_funding)
+                .or(tx_schedule_tx)
+                .or(tx_cancel_scheduled_tx)
+                .or(tx_grant_fee_allowance)
+                .or(tx_revoke_fee_allowance)
                 .or(query_epoch)
                 .or(query_transfers)
                 .or(query_conversions)
@@ -377,6 +419,9 @@ pub mod cmds {
                 .or(query_proposal_result)
                 .or(query_protocol_parameters)
                 .or(query_pgf)
+                .or(query_scheduled_txs)
+                .or(query_fee_allowances)
+                .or(query_vote_delegations)
                 .or(query_validator_state)
                 .or(query_commission)
                 .or(query_metadata)
@@ -435,6 +480,8 @@ pub mod cmds {
         TxReactivateValidator(TxReactivateValidator),
         TxInitProposal(TxInitProposal),
         TxVoteProposal(TxVoteProposal),
+        TxDelegateVotes(TxDelegateVotes),
+        TxDepositProposal(TxDepositProposal),
         TxRevealPk(TxRevealPk),
         Bond(Bond),
         Unbond(Unbond),
@@ -444,6 +491,11 @@ pub mod cmds {
         AddToEthBridgePool(AddToEthBridgePool),
         TxUpdateStewardCommission(TxUpdateStewardCommission),
         TxResignSteward(TxResignSteward),
+        TxPausePgfFunding(TxPausePgfFunding),
+        TxScheduleTx(TxScheduleTx),
+        TxCancelScheduledTx(TxCancelScheduledTx),
+        TxGrantFeeAllowance(TxGrantFeeAllowance),
+        TxRevokeFeeAllowance(TxRevokeFeeAllowance),
         QueryEpoch(QueryEpoch),
         QueryAccount(QueryAccount),
         QueryTransfers(QueryTransfers),
@@ -462,6 +514,9 @@ pub mod cmds {
         QueryProposalResult(QueryProposalResult),
         QueryProtocolParameters(QueryProtocolParameters),
         QueryPgf(QueryPgf),
+        QueryScheduledTxs(QueryScheduledTxs),
+        QueryFeeAllowances(QueryFeeAllowances),
+        QueryVoteDelegations(QueryVoteDelegations),
         QueryValidatorState(QueryValidatorState),
         SignTx(SignTx),
         GenIbcShieldedTransafer(GenIbcShieldedTransafer),
@@ -983,6 +1038,8 @@ pub mod cmds {
         Reset(LedgerReset),
         DumpDb(LedgerDumpDb),
         RollBack(LedgerRollBack),
+        ExportState(LedgerExportState),
+        ImportState(LedgerImportState),
     }
 
     impl SubCmd for Ledger {
@@ -995,10 +1052,16 @@ pub mod cmds {
                 let dump_db = SubCmd::parse(matches).map(Self::DumpDb);
                 let rollback = SubCmd::parse(matches).map(Self::RollBack);
                 let run_until = SubCmd::parse(matches).map(Self::RunUntil);
+                let export_state =
+                    SubCmd::parse(matches).map(Self::ExportState);
+                let import_state =
+                    SubCmd::parse(matches).map(Self::ImportState);
                 run.or(reset)
                     .or(dump_db)
                     .or(rollback)
                     .or(run_until)
+                    .or(export_state)
+                    .or(import_state)
                     // The `run` command is the default if no sub-command given
                     .or(Some(Self::Run(LedgerRun(args::LedgerRun {
                         start_time: None,
@@ -1017,6 +1080,8 @@ pub mod cmds {
                 .subcommand(LedgerReset::def())
                 .subcommand(LedgerDumpDb::def())
                 .subcommand(LedgerRollBack::def())
+                .subcommand(LedgerExportState::def())
+                .subcommand(LedgerImportState::def())
         }
     }
 
@@ -1099,22 +1164,77 @@ pub mod cmds {
     }
 
     #[derive(Clone, Debug)]
-    pub struct LedgerRollBack;
+    pub struct LedgerRollBack(pub args::LedgerRollBack);
 
     impl SubCmd for LedgerRollBack {
         const CMD: &'static str = "rollback";
 
         fn parse(matches: &ArgMatches) -> Option<Self> {
-            matches.subcommand_matches(Self::CMD).map(|_matches| Self)
+            matches
+                .subcommand_matches(Self::CMD)
+                .map(|matches| Self(args::LedgerRollBack::parse(matches)))
         }
 
         fn def() -> App {
-            App::new(Self::CMD).about(
-                "Roll Namada state back to the previous height. This command \
-                 does not create a backup of neither the Namada nor the \
-                 Tendermint state before execution: for extra safety, it is \
-                 recommended to make a backup in advance.",
-            )
+            App::new(Self::CMD)
+                .about(
+                    "Roll Namada state back to the previous height, or to the \
+                     given height. This command does not create a backup of \
+                     neither the Namada nor the Tendermint state before \
+                     execution: for extra safety, it is recommended to make a \
+                     backup in advance. The Tendermint state is rolled back \
+                     in place one height at a time, so a rollback to a given \
+                     height that fails may leave it between the two heights, \
+                     to be restored from the backup.",
+                )
+                .add_args::<args::LedgerRollBack>()
+        }
+    }
+
+    #[derive(Clone, Debug)]
+    pub struct LedgerExportState(pub args::LedgerExportState);
+
+    impl SubCmd for LedgerExportState {
+        const CMD: &'static str = "export-state";
+
+        fn parse(matches: &ArgMatches) -> Option<Self> {
+            matches
+                .subcommand_matches(Self::CMD)
+                .map(|matches| Self(args::LedgerExportState::parse(matches)))
+        }
+
+        fn def() -> App {
+            App::new(Self::CMD)
+                .about(
+                    "Export Namada ledger node's state at a given height into \
+                     a file, from which a new chain can be initialized. The \
+                     node must not be running.",
+                )
+                .add_args::<args::LedgerExportState>()
+        }
+    }
+
+    #[derive(Clone, Debug)]
+    pub struct LedgerImportState(pub args::LedgerImportState);
+
+    impl SubCmd for LedgerImportState {
+        const CMD: &'static str = "import-state";
+
+        fn parse(matches: &ArgMatches) -> Option<Self> {
+            matches
+                .subcommand_matches(Self::CMD)
+                .map(|matches| Self(args::LedgerImportState::parse(matches)))
+        }
+
+        fn def() -> App {
+            App::new(Self::CMD)
+                .about(
+                    "Import a state exported from another chain. The chain \
+                     will be initialized from this state instead of its \
+                     genesis files. Its CometBFT genesis must set the initial \
+                     height to the height following the exported block.",
+                )
+                .add_args::<args::LedgerImportState>()
         }
     }
 
@@ -1181,6 +1301,48 @@ pub mod cmds {
         }
     }
 
+    #[derive(Clone, Debug)]
+    pub enum NodeUtils {
+        PrecompileWasm(PrecompileWasm),
+    }
+
+    impl SubCmd for NodeUtils {
+        const CMD: &'static str = "utils";
+
+        fn parse(matches: &ArgMatches) -> Option<Self> {
+            matches.subcommand_matches(Self::CMD).and_then(|matches| {
+                SubCmd::parse(matches).map(Self::PrecompileWasm)
+            })
+        }
+
+        fn def() -> App {
+            App::new(Self::CMD)
+                .subcommand_required(true)
+                .arg_required_else_help(true)
+                .about("Node utilities.")
+                .subcommand(PrecompileWasm::def())
+        }
+    }
+
+    #[derive(Clone, Debug)]
+    pub struct PrecompileWasm;
+
+    impl SubCmd for PrecompileWasm {
+        const CMD: &'static str = "precompile-wasm";
+
+        fn parse(matches: &ArgMatches) -> Option<Self> {
+            matches.subcommand_matches(Self::CMD).map(|_matches| Self)
+        }
+
+        fn def() -> App {
+            App::new(Self::CMD).about(
+                "Compile the WASMs listed in the checksums file of the WASM \
+                 directory into the node's compilation cache, so that they \
+                 don't have to be compiled when they're first used.",
+            )
+        }
+    }
+
     #[derive(Clone, Debug)]
     pub struct QueryResult(pub args::QueryResult<args::CliTypes>);
 
@@ -1294,6 +1456,77 @@ pub mod cmds {
         }
     }
 
+    #[derive(Clone, Debug)]
+    pub struct QueryScheduledTxs(pub args::QueryScheduledTxs<args::CliTypes>);
+
+    impl SubCmd for QueryScheduledTxs {
+        const CMD: &'static str = "query-scheduled-txs";
+
+        fn parse(matches: &ArgMatches) -> Option<Self>
+        where
+            Self: Sized,
+        {
+            matches.subcommand_matches(Self::CMD).map(|matches| {
+                QueryScheduledTxs(args::QueryScheduledTxs::parse(matches))
+            })
+        }
+
+        fn def() -> App {
+            App::new(Self::CMD)
+                .about("Query the pending scheduled transactions of an owner.")
+                .add_args::<args::QueryScheduledTxs<args::CliTypes>>()
+        }
+    }
+
+    #[derive(Clone, Debug)]
+    pub struct QueryFeeAllowances(pub args::QueryFeeAllowances<args::CliTypes>);
+
+    impl SubCmd for QueryFeeAllowances {
+        const CMD: &'static str = "query-fee-allowances";
+
+        fn parse(matches: &ArgMatches) -> Option<Self>
+        where
+            Self: Sized,
+        {
+            matches.subcommand_matches(Self::CMD).map(|matches| {
+                QueryFeeAllowances(args::QueryFeeAllowances::parse(matches))
+            })
+        }
+
+        fn def() -> App {
+            App::new(Self::CMD)
+                .about("Query the fee allowances granted by an address.")
+                .add_args::<args::QueryFeeAllowances<args::CliTypes>>()
+        }
+    }
+
+    #[derive(Clone, Debug)]
+    pub struct QueryVoteDelegations(
+        pub args::QueryVoteDelegations<args::CliTypes>,
+    );
+
+    impl SubCmd for QueryVoteDelegations {
+        const CMD: &'static str = "query-vote-delegations";
+
+        fn parse(matches: &ArgMatches) -> Option<Self>
+        where
+            Self: Sized,
+        {
+            matches.subcommand_matches(Self::CMD).map(|matches| {
+                QueryVoteDelegations(args::QueryVoteDelegations::parse(matches))
+            })
+        }
+
+        fn def() -> App {
+            App::new(Self::CMD)
+                .about(
+                    "Query the governance delegates of an address and the \
+                     addresses delegating their votes to it.",
+                )
+                .add_args::<args::QueryVoteDelegations<args::CliTypes>>()
+        }
+    }
+
     #[derive(Clone, Debug)]
     pub struct TxCustom(pub args::TxCustom<args::CliTypes>);
 
@@ -1977,6 +2210,130 @@ pub mod cmds {
         }
     }
 
+    #[derive(Clone, Debug)]
+    pub struct TxPausePgfFunding(pub args::PausePgfFunding<args::CliTypes>);
+
+    impl SubCmd for TxPausePgfFunding {
+        const CMD: &'static str = "pause-pgf-funding";
+
+        fn parse(matches: &ArgMatches) -> Option<Self>
+        where
+            Self: Sized,
+        {
+            matches.subcommand_matches(Self::CMD).map(|matches| {
+                TxPausePgfFunding(args::PausePgfFunding::parse(matches))
+            })
+        }
+
+        fn def() -> App {
+            App::new(Self::CMD)
+                .about(
+                    "Craft a transaction for a steward to pause a continuous \
+                     PGF funding until governance sets or removes it again.",
+                )
+                .add_args::<args::PausePgfFunding<args::CliTypes>>()
+        }
+    }
+
+    #[derive(Clone, Debug)]
+    pub struct TxScheduleTx(pub args::ScheduleTx<args::CliTypes>);
+
+    impl SubCmd for TxScheduleTx {
+        const CMD: &'static str = "schedule-tx";
+
+        fn parse(matches: &ArgMatches) -> Option<Self>
+        where
+            Self: Sized,
+        {
+            matches
+                .subcommand_matches(Self::CMD)
+                .map(|matches| TxScheduleTx(args::ScheduleTx::parse(matches)))
+        }
+
+        fn def() -> App {
+            App::new(Self::CMD)
+                .about(
+                    "Schedule a signed transaction for execution at a block \
+                     height or epoch, escrowing the fees for it.",
+                )
+                .add_args::<args::ScheduleTx<args::CliTypes>>()
+        }
+    }
+
+    #[derive(Clone, Debug)]
+    pub struct TxCancelScheduledTx(pub args::CancelScheduledTx<args::CliTypes>);
+
+    impl SubCmd for TxCancelScheduledTx {
+        const CMD: &'static str = "cancel-scheduled-tx";
+
+        fn parse(matches: &ArgMatches) -> Option<Self>
+        where
+            Self: Sized,
+        {
+            matches.subcommand_matches(Self::CMD).map(|matches| {
+                TxCancelScheduledTx(args::CancelScheduledTx::parse(matches))
+            })
+        }
+
+        fn def() -> App {
+            App::new(Self::CMD)
+                .about(
+                    "Cancel a pending scheduled transaction and get the \
+                     escrowed fees refunded.",
+                )
+                .add_args::<args::CancelScheduledTx<args::CliTypes>>()
+        }
+    }
+
+    #[derive(Clone, Debug)]
+    pub struct TxGrantFeeAllowance(pub args::GrantFeeAllowance<args::CliTypes>);
+
+    impl SubCmd for TxGrantFeeAllowance {
+        const CMD: &'static str = "grant-fee-allowance";
+
+        fn parse(matches: &ArgMatches) -> Option<Self>
+        where
+            Self: Sized,
+        {
+            matches.subcommand_matches(Self::CMD).map(|matches| {
+                TxGrantFeeAllowance(args::GrantFeeAllowance::parse(matches))
+            })
+        }
+
+        fn def() -> App {
+            App::new(Self::CMD)
+                .about(
+                    "Let an address pay the transaction fees of another one, \
+                     up to a spend limit in a token.",
+                )
+                .add_args::<args::GrantFeeAllowance<args::CliTypes>>()
+        }
+    }
+
+    #[derive(Clone, Debug)]
+    pub struct TxRevokeFeeAllowance(
+        pub args::RevokeFeeAllowance<args::CliTypes>,
+    );
+
+    impl SubCmd for TxRevokeFeeAllowance {
+        const CMD: &'static str = "revoke-fee-allowance";
+
+        fn parse(matches: &ArgMatches) -> Option<Self>
+        where
+            Self: Sized,
+        {
+            matches.subcommand_matches(Self::CMD).map(|matches| {
+                TxRevokeFeeAllowance(args::RevokeFeeAllowance::parse(matches))
+            })
+        }
+
+        fn def() -> App {
+            App::new(Self::CMD)
+                .about("Revoke a fee allowance granted to an address.")
+                .add_args::<args::RevokeFeeAllowance<args::CliTypes>>()
+        }
+    }
+
     #[derive(Clone, Debug)]
     pub struct TxCommissionRateChange(
         pub args::CommissionRateChange<args::CliTypes>,
@@ -2074,6 +2431,57 @@ pub mod cmds {
         }
     }
 
+    #[derive(Clone, Debug)]
+    pub struct TxDelegateVotes(pub args::DelegateVotes<args::CliTypes>);
+
+    impl SubCmd for TxDelegateVotes {
+        const CMD: &'static str = "delegate-votes";
+
+        fn parse(matches: &ArgMatches) -> Option<Self>
+        where
+            Self: Sized,
+        {
+            matches.subcommand_matches(Self::CMD).map(|matches| {
+                TxDelegateVotes(args::DelegateVotes::parse(matches))
+            })
+        }
+
+        fn def() -> App {
+            App::new(Self::CMD)
+                .about(
+                    "Let another address vote on the governance proposals on \
+                     behalf of an address that doesn't vote itself, or revoke \
+                     the delegation.",
+                )
+                .add_args::<args::DelegateVotes<args::CliTypes>>()
+        }
+    }
+
+    #[derive(Clone, Debug)]
+    pub struct TxDepositProposal(pub args::DepositProposal<args::CliTypes>);
+
+    impl SubCmd for TxDepositProposal {
+        const CMD: &'static str = "deposit-proposal";
+
+        fn parse(matches: &ArgMatches) -> Option<Self>
+        where
+            Self: Sized,
+        {
+            matches.subcommand_matches(Self::CMD).map(|matches| {
+                TxDepositProposal(args::DepositProposal::parse(matches))
+            })
+        }
+
+        fn def() -> App {
+            App::new(Self::CMD)
+                .about(
+                    "Top up the deposit of a proposal before its voting \
+                     period starts.",
+                )
+                .add_args::<args::DepositProposal<args::CliTypes>>()
+        }
+    }
+
     #[derive(Clone, Debug)]
     pub struct TxRevealPk(pub args::RevealPk<args::CliTypes>);
 
@@ -2818,11 +3226,14 @@ pub mod args {
     use std::path::PathBuf;
     use std::str::FromStr;
 
+    use namada::core::ledger::governance::storage::proposal::ProposalKind;
     use namada::ibc::core::ics24_host::identifier::{ChannelId, PortId};
+    use namada::proto::MAX_MEMO_LEN;
     use namada::types::address::Address;
     use namada::types::chain::{ChainId, ChainIdPrefix};
     use namada::types::dec::Dec;
     use namada::types::ethereum_events::EthAddress;
+    use namada::types::hash::Hash;
     use namada::types::keccak::KeccakHash;
     use namada::types::key::*;
     use namada::types::masp::MaspValue;
@@ -2831,17 +3242,23 @@ pub mod args {
     use namada::types::token;
     use namada::types::token::NATIVE_MAX_DECIMAL_PLACES;
     use namada::types::transaction::GasLimit;
+    use namada::types::transaction::scheduler::Schedule;
+    use namada::types::vesting::VestingSchedule;
     pub use namada_sdk::args::*;
+    use namada_sdk::signing::remote::ExternalSigner;
     pub use namada_sdk::tx::{
-        TX_BOND_WASM, TX_BRIDGE_POOL_WASM, TX_CHANGE_COMMISSION_WASM,
-        TX_CHANGE_CONSENSUS_KEY_WASM, TX_CHANGE_METADATA_WASM,
-        TX_CLAIM_REWARDS_WASM, TX_DEACTIVATE_VALIDATOR_WASM, TX_IBC_WASM,
+        TX_BOND_WASM, TX_BRIDGE_POOL_WASM, TX_CANCEL_SCHEDULED_TX_WASM,
+        TX_CHANGE_COMMISSION_WASM, TX_CHANGE_CONSENSUS_KEY_WASM,
+        TX_CHANGE_METADATA_WASM, TX_CLAIM_REWARDS_WASM,
+        TX_DEACTIVATE_VALIDATOR_WASM, TX_DELEGATE_VOTES_WASM,
+        TX_DEPOSIT_PROPOSAL_WASM, TX_GRANT_FEE_ALLOWANCE_WASM, TX_IBC_WASM,
         TX_INIT_ACCOUNT_WASM, TX_INIT_PROPOSAL, TX_INIT_VALIDATOR_WASM,
-        TX_REACTIVATE_VALIDATOR_WASM, TX_REDELEGATE_WASM, TX_RESIGN_STEWARD,
-        TX_REVEAL_PK, TX_TRANSFER_WASM, TX_UNBOND_WASM,
-        TX_UNJAIL_VALIDATOR_WASM, TX_UPDATE_ACCOUNT_WASM,
+        TX_PAUSE_PGF_FUNDING_WASM, TX_REACTIVATE_VALIDATOR_WASM,
+        TX_REDELEGATE_WASM, TX_RESIGN_STEWARD, TX_REVEAL_PK,
+        TX_REVOKE_FEE_ALLOWANCE_WASM, TX_SCHEDULE_TX_WASM, TX_TRANSFER_WASM,
+        TX_UNBOND_WASM, TX_UNJAIL_VALIDATOR_WASM, TX_UPDATE_ACCOUNT_WASM,
         TX_UPDATE_STEWARD_COMMISSION, TX_VOTE_PROPOSAL, TX_WITHDRAW_WASM,
-        VP_USER_WASM,
+        VP_USER_WASM, VP_VESTING_WASM,
     };
 
     use super::context::*;
@@ -2858,6 +3275,7 @@ pub mod args {
     pub const ALIAS_FORCE: ArgFlag = flag("alias-force");
     pub const ALLOW_DUPLICATE_IP: ArgFlag = flag("allow-duplicate-ip");
     pub const AMOUNT: Arg<token::DenominatedAmount> = arg("amount");
+    pub const APP_HASH: Arg<Hash> = arg("app-hash");
     pub const ARCHIVE_DIR: ArgOpt<PathBuf> = arg_opt("archive-dir");
     pub const BALANCE_OWNER: ArgOpt<WalletBalanceOwner> = arg_opt("owner");
     pub const BASE_DIR: ArgDefault<PathBuf> = arg_default(
@@ -2906,6 +3324,9 @@ pub mod args {
     pub const DATA_PATH_OPT: ArgOpt<PathBuf> = arg_opt("data-path");
     pub const DATA_PATH: Arg<PathBuf> = arg("data-path");
     pub const DECRYPT: ArgFlag = flag("decrypt");
+    pub const DELEGATE_OPT: ArgOpt<WalletAddress> = arg_opt("delegate");
+    pub const DELEGATOR: Arg<WalletAddress> = arg("delegator");
+    pub const DEPOSITOR: Arg<WalletAddress> = arg("depositor");
     pub const DESCRIPTION_OPT: ArgOpt<String> = arg_opt("description");
     pub const DISPOSABLE_SIGNING_KEY: ArgFlag = flag("disposable-gas-payer");
     pub const DESTINATION_VALIDATOR: Arg<WalletAddress> =
@@ -2929,6 +3350,7 @@ pub mod args {
     );
     pub const ETH_SYNC: ArgFlag = flag("sync");
     pub const EXPIRATION_OPT: ArgOpt<DateTimeUtc> = arg_opt("expiration");
+    pub const EXPIRATION_EPOCH: ArgOpt<Epoch> = arg_opt("expiration-epoch");
     pub const EMAIL: Arg<String> = arg("email");
     pub const EMAIL_OPT: ArgOpt<String> = EMAIL.opt();
     pub const FEE_UNSHIELD_SPENDING_KEY: ArgOpt<WalletTransferSource> =
@@ -2936,9 +3358,10 @@ pub mod args {
     pub const FEE_AMOUNT_OPT: ArgOpt<token::DenominatedAmount> =
         arg_opt("gas-price");
     pub const FEE_PAYER_OPT: ArgOpt<WalletKeypair> = arg_opt("gas-payer");
+    pub const FEE_GRANTER_OPT: ArgOpt<WalletAddress> = arg_opt("gas-granter");
     pub const FORCE: ArgFlag = flag("force");
-    pub const GAS_LIMIT: ArgDefault<GasLimit> =
-        arg_default("gas-limit", DefaultFn(|| GasLimit::from(25_000)));
+    pub const GAS_LIMIT_OPT: ArgOpt<GasLimit> = arg_opt("gas-limit");
+    pub const GAS_PROFILE: ArgFlag = flag("gas-profile");
     pub const FEE_TOKEN: ArgDefaultFromCtx<WalletAddress> =
         arg_default_from_ctx("gas-token", DefaultFn(|| "NAM".parse().unwrap()));
     pub const FEE_PAYER: Arg<WalletAddress> = arg("fee-payer");
@@ -2953,12 +3376,17 @@ pub mod args {
     pub const GENESIS_TIME: Arg<DateTimeUtc> = arg("genesis-time");
     pub const GENESIS_VALIDATOR: ArgOpt<String> =
         arg("genesis-validator").opt();
+    pub const GRANTEE: Arg<WalletAddress> = arg("grantee");
+    pub const GRANTEE_OPT: ArgOpt<WalletAddress> = GRANTEE.opt();
+    pub const GRANTER: Arg<WalletAddress> = arg("granter");
     pub const HALT_ACTION: ArgFlag = flag("halt");
     pub const HASH_LIST: Arg<String> = arg("hash-list");
     pub const HD_WALLET_DERIVATION_PATH: ArgDefault<String> =
         arg_default("hd-path", DefaultFn(|| "default".to_string()));
     pub const HISTORIC: ArgFlag = flag("historic");
     pub const IBC_TRANSFER_MEMO_PATH: ArgOpt<PathBuf> = arg_opt("memo-path");
+    pub const IN_FILE_PATH: Arg<PathBuf> = arg("in-file-path");
+    pub const KEY_POLICIES: ArgOpt<PathBuf> = arg_opt("key-policies");
     pub const LEDGER_ADDRESS_ABOUT: &str =
         "Address of a ledger node as \"{scheme}://{host}:{port}\". If the \
          scheme is not supplied, it is assumed to be TCP.";
@@ -2974,6 +3402,7 @@ pub mod args {
     pub const MAX_COMMISSION_RATE_CHANGE: Arg<Dec> =
         arg("max-commission-rate-change");
     pub const MAX_ETH_GAS: ArgOpt<u64> = arg_opt("max_eth-gas");
+    pub const MEMO: ArgOpt<String> = arg_opt("memo");
     pub const MODE: ArgOpt<String> = arg_opt("mode");
     pub const NET_ADDRESS: Arg<SocketAddr> = arg("net-address");
     pub const NAMADA_START_TIME: ArgOpt<DateTimeUtc> = arg_opt("time");
@@ -2986,6 +3415,7 @@ pub mod args {
     pub const OWNER: Arg<WalletAddress> = arg("owner");
     pub const OWNER_OPT: ArgOpt<WalletAddress> = OWNER.opt();
     pub const PATH: Arg<PathBuf> = arg("path");
+    pub const PGF_FUNDING: Arg<String> = arg("funding");
     pub const PIN: ArgFlag = flag("pin");
     pub const PORT_ID: ArgDefault<PortId> = arg_default(
         "port-id",
@@ -2993,15 +3423,19 @@ pub mod args {
     );
     pub const PRE_GENESIS: ArgFlag = flag("pre-genesis");
     pub const PROPOSAL_ETH: ArgFlag = flag("eth");
+    pub const PROPOSAL_PARAMETER_CHANGE: ArgFlag = flag("parameter-change");
     pub const PROPOSAL_PGF_STEWARD: ArgFlag = flag("pgf-stewards");
     pub const PROPOSAL_PGF_FUNDING: ArgFlag = flag("pgf-funding");
     pub const PROPOSAL_OFFLINE: ArgFlag = flag("offline");
+    pub const PROPOSAL_UPGRADE: ArgFlag = flag("upgrade");
     pub const PROTOCOL_KEY: ArgOpt<WalletPublicKey> = arg_opt("protocol-key");
     pub const PRE_GENESIS_PATH: ArgOpt<PathBuf> = arg_opt("pre-genesis-path");
     pub const PUBLIC_KEY: Arg<WalletPublicKey> = arg("public-key");
     pub const PUBLIC_KEYS: ArgMulti<WalletPublicKey> = arg_multi("public-keys");
     pub const PROPOSAL_ID: Arg<u64> = arg("proposal-id");
     pub const PROPOSAL_ID_OPT: ArgOpt<u64> = arg_opt("proposal-id");
+    pub const PROPOSAL_KIND_OPT: ArgOpt<ProposalKind> =
+        arg_opt("proposal-type");
     pub const PROPOSAL_VOTE_PGF_OPT: ArgOpt<String> = arg_opt("pgf");
     pub const PROPOSAL_VOTE_ETH_OPT: ArgOpt<String> = arg_opt("eth");
     pub const PROPOSAL_VOTE: Arg<String> = arg("vote");
@@ -3014,12 +3448,16 @@ pub mod args {
     pub const RECEIVER: Arg<String> = arg("receiver");
     pub const RELAYER: Arg<Address> = arg("relayer");
     pub const SAFE_MODE: ArgFlag = flag("safe-mode");
+    pub const SCHEDULED_GAS_LIMIT: Arg<GasLimit> = arg("scheduled-gas-limit");
+    pub const SCHEDULED_SIGNATURES: ArgMulti<PathBuf> =
+        arg_multi("scheduled-signatures");
+    pub const SCHEDULED_TX_ID: Arg<u64> = arg("scheduled-tx-id");
     pub const SCHEME: ArgDefault<SchemeType> =
         arg_default("scheme", DefaultFn(|| SchemeType::Ed25519));
     pub const SELF_BOND_AMOUNT: Arg<token::DenominatedAmount> =
         arg("self-bond-amount");
     pub const SENDER: Arg<String> = arg("sender");
-    pub const SIGNER: ArgOpt<WalletAddress> = arg_opt("signer");
+    pub const SIGNER: ArgOpt<ExternalSigner> = arg_opt("signer");
     pub const SIGNING_KEY_OPT: ArgOpt<WalletKeypair> = SIGNING_KEY.opt();
     pub const SIGNING_KEY: Arg<WalletKeypair> = arg("signing-key");
     pub const SIGNING_KEYS: ArgMulti<WalletKeypair> = arg_multi("signing-keys");
@@ -3034,6 +3472,7 @@ pub mod args {
     pub const TIMEOUT_HEIGHT: ArgOpt<u64> = arg_opt("timeout-height");
     pub const TIMEOUT_SEC_OFFSET: ArgOpt<u64> = arg_opt("timeout-sec-offset");
     pub const TM_ADDRESS: Arg<String> = arg("tm-address");
+    pub const TO_HEIGHT_OPT: ArgOpt<BlockHeight> = arg_opt("to-height");
     pub const TOKEN_OPT: ArgOpt<WalletAddress> = TOKEN.opt();
     pub const TOKEN: Arg<WalletAddress> = arg("token");
     pub const TRANSFER_FROM_SOURCE_AMOUNT: Arg<token::DenominatedAmount> =
@@ -3062,6 +3501,11 @@ pub mod args {
     pub const VALUE: ArgOpt<String> = arg_opt("value");
     pub const VERIFICATION_KEY: ArgOpt<WalletPublicKey> =
         arg_opt("verification-key");
+    pub const VESTING_AMOUNT: ArgOpt<token::DenominatedAmount> =
+        arg_opt("vesting-amount");
+    pub const VESTING_END: ArgOpt<DateTimeUtc> = arg_opt("vesting-end");
+    pub const VESTING_START: ArgOpt<DateTimeUtc> = arg_opt("vesting-start");
+    pub const VESTING_TOKEN: ArgOpt<WalletAddress> = arg_opt("vesting-token");
     pub const VIEWING_KEY: Arg<WalletViewingKey> = arg("key");
     pub const WALLET_ALIAS_FORCE: ArgFlag = flag("wallet-alias-force");
     pub const WASM_CHECKSUMS_PATH: Arg<PathBuf> = arg("wasm-checksums-path");
@@ -3221,24 +3665,118 @@ pub mod args {
     }
 
     #[derive(Clone, Debug)]
-    pub struct UpdateLocalConfig {
-        pub config_path: PathBuf,
+    pub struct LedgerRollBack {
+        pub to_height: Option<BlockHeight>,
+        pub dry_run: bool,
     }
 
-    impl Args for UpdateLocalConfig {
+    impl Args for LedgerRollBack {
         fn parse(matches: &ArgMatches) -> Self {
-            let config_path = DATA_PATH.parse(matches);
-            Self { config_path }
+            let to_height = TO_HEIGHT_OPT.parse(matches);
+            let dry_run = DRY_RUN_TX.parse(matches);
+            Self { to_height, dry_run }
         }
 
         fn def(app: App) -> App {
-            app.arg(DATA_PATH.def().help(
-                "The path to the toml file containing the updated local \
-                 configuration.",
+            app.arg(TO_HEIGHT_OPT.def().help(
+                "The block height to roll back to. It cannot be older than \
+                 the diffs retained by the node, as set by the \
+                 `storage_read_past_height_limit` config. Defaults to the \
+                 previous height.",
             ))
-        }
-    }
-
+            .arg(
+                DRY_RUN_TX
+                    .def()
+                    .help(
+                        "Only print the storage keys that would change, \
+                         without rolling back.",
+                    )
+                    .requires(TO_HEIGHT_OPT.name),
+            )
+        }
+    }
+
+    #[derive(Clone, Debug)]
+    pub struct LedgerExportState {
+        pub block_height: Option<BlockHeight>,
+        pub out_file_path: PathBuf,
+    }
+
+    impl Args for LedgerExportState {
+        fn parse(matches: &ArgMatches) -> Self {
+            let block_height = BLOCK_HEIGHT_OPT.parse(matches);
+            let out_file_path = OUT_FILE_PATH_OPT
+                .parse(matches)
+                .unwrap_or_else(|| PathBuf::from("state_export".to_string()));
+
+            Self {
+                block_height,
+                out_file_path,
+            }
+        }
+
+        fn def(app: App) -> App {
+            app.arg(BLOCK_HEIGHT_OPT.def().help(
+                "The block height to export. A past height is exported from a \
+                 checkpoint of the DB rolled back to it, so it must still be \
+                 retained by the node. Defaults to the last committed block.",
+            ))
+            .arg(OUT_FILE_PATH_OPT.def().help(
+                "Path for the output file. Defaults to \"state_export\" in \
+                 the current working directory.",
+            ))
+        }
+    }
+
+    #[derive(Clone, Debug)]
+    pub struct LedgerImportState {
+        pub in_file_path: PathBuf,
+        pub app_hash: Hash,
+    }
+
+    impl Args for LedgerImportState {
+        fn parse(matches: &ArgMatches) -> Self {
+            let in_file_path = IN_FILE_PATH.parse(matches);
+            let app_hash = APP_HASH.parse(matches);
+            Self {
+                in_file_path,
+                app_hash,
+            }
+        }
+
+        fn def(app: App) -> App {
+            app.arg(
+                IN_FILE_PATH.def().help(
+                    "Path to the file produced by `ledger export-state`.",
+                ),
+            )
+            .arg(APP_HASH.def().help(
+                "The app hash of the exported block, against which the \
+                 exported state is verified. It must be obtained from a \
+                 trusted source, e.g. the header of the following block.",
+            ))
+        }
+    }
+
+    #[derive(Clone, Debug)]
+    pub struct UpdateLocalConfig {
+        pub config_path: PathBuf,
+    }
+
+    impl Args for UpdateLocalConfig {
+        fn parse(matches: &ArgMatches) -> Self {
+            let config_path = DATA_PATH.parse(matches);
+            Self { config_path }
+        }
+
+        fn def(app: App) -> App {
+            app.arg(DATA_PATH.def().help(
+                "The path to the toml file containing the updated local \
+                 configuration.",
+            ))
+        }
+    }
+
     /// Convert CLI args to SDK args, with contextual data.
     pub trait CliToSdk<SDK>: Args {
         /// Convert CLI args to SDK args, with contextual data.
@@ -3847,6 +4385,7 @@ pub mod args {
                 token: chain_ctx.get(&self.token),
                 amount: self.amount,
                 native_token: chain_ctx.native_token.clone(),
+                memo: self.memo,
                 tx_code_path: self.tx_code_path.to_path_buf(),
             }
         }
@@ -3859,6 +4398,7 @@ pub mod args {
             let target = TRANSFER_TARGET.parse(matches);
             let token = TOKEN.parse(matches);
             let amount = InputAmount::Unvalidated(AMOUNT.parse(matches));
+            let memo = MEMO.parse(matches);
             let tx_code_path = PathBuf::from(TX_TRANSFER_WASM);
             Self {
                 tx,
@@ -3866,6 +4406,7 @@ pub mod args {
                 target,
                 token,
                 amount,
+                memo,
                 tx_code_path,
                 native_token: (),
             }
@@ -3883,6 +4424,10 @@ pub mod args {
                 ))
                 .arg(TOKEN.def().help("The transfer token."))
                 .arg(AMOUNT.def().help("The amount to transfer in decimal."))
+                .arg(MEMO.def().help(
+                    "An optional memo attached to the transaction, e.g. to \
+                     identify a deposit.",
+                ))
         }
     }
 
@@ -3917,9 +4462,22 @@ pub mod args {
             let channel_id = CHANNEL_ID.parse(matches);
             let timeout_height = TIMEOUT_HEIGHT.parse(matches);
             let timeout_sec_offset = TIMEOUT_SEC_OFFSET.parse(matches);
-            let memo = IBC_TRANSFER_MEMO_PATH.parse(matches).map(|path| {
-                std::fs::read_to_string(path)
-                    .expect("Expected a file at given path")
+            let memo = MEMO.parse(matches);
+            if let Some(memo) = &memo {
+                if memo.len() > MAX_MEMO_LEN {
+                    eprintln!(
+                        "The memo is {} bytes long, the maximum is {} bytes",
+                        memo.len(),
+                        MAX_MEMO_LEN
+                    );
+                    safe_exit(1);
+                }
+            }
+            let memo = memo.or_else(|| {
+                IBC_TRANSFER_MEMO_PATH.parse(matches).map(|path| {
+                    std::fs::read_to_string(path)
+                        .expect("Expected a file at given path")
+                })
             });
             let tx_code_path = PathBuf::from(TX_IBC_WASM);
             Self {
@@ -3956,6 +4514,11 @@ pub mod args {
                         .help("The timeout height of the destination chain."),
                 )
                 .arg(TIMEOUT_SEC_OFFSET.def().help("The timeout as seconds."))
+                .arg(
+                    MEMO.def()
+                        .help("The memo field of the ICS20 transfer.")
+                        .conflicts_with(IBC_TRANSFER_MEMO_PATH.name),
+                )
                 .arg(
                     IBC_TRANSFER_MEMO_PATH
                         .def()
@@ -3978,6 +4541,11 @@ pub mod args {
                     .map(|pk| chain_ctx.get(pk))
                     .collect(),
                 threshold: self.threshold,
+                vesting: self.vesting.map(|vesting| AccountVesting {
+                    token: chain_ctx.get(&vesting.token),
+                    amount: vesting.amount,
+                    schedule: vesting.schedule,
+                }),
             }
         }
     }
@@ -3985,17 +4553,37 @@ pub mod args {
     impl Args for TxInitAccount<CliTypes> {
         fn parse(matches: &ArgMatches) -> Self {
             let tx = Tx::parse(matches);
-            let vp_code_path = CODE_PATH_OPT
-                .parse(matches)
-                .unwrap_or_else(|| PathBuf::from(VP_USER_WASM));
             let tx_code_path = PathBuf::from(TX_INIT_ACCOUNT_WASM);
             let public_keys = PUBLIC_KEYS.parse(matches);
             let threshold = THRESOLD.parse(matches);
+            let vesting = VESTING_TOKEN.parse(matches).map(|token| {
+                // The other vesting args are required with the token
+                let amount = VESTING_AMOUNT.parse(matches).unwrap();
+                let end = VESTING_END.parse(matches).unwrap();
+                let schedule = match VESTING_START.parse(matches) {
+                    Some(start) => VestingSchedule::Linear { start, end },
+                    None => VestingSchedule::Cliff { unlock: end },
+                };
+                AccountVesting {
+                    token,
+                    amount: InputAmount::Unvalidated(amount),
+                    schedule,
+                }
+            });
+            let vp_code_path =
+                CODE_PATH_OPT.parse(matches).unwrap_or_else(|| {
+                    if vesting.is_some() {
+                        PathBuf::from(VP_VESTING_WASM)
+                    } else {
+                        PathBuf::from(VP_USER_WASM)
+                    }
+                });
             Self {
                 tx,
                 vp_code_path,
                 public_keys,
                 threshold,
+                vesting,
                 tx_code_path,
             }
         }
@@ -4016,6 +4604,40 @@ pub mod args {
                      authorization. Must be less then the maximum number of \
                      public keys provided.",
                 ))
+                .arg(
+                    VESTING_TOKEN
+                        .def()
+                        .help(
+                            "Lock an amount of the given token in the new \
+                             account until it vests. Uses the vesting VP if \
+                             no VP code path is specified.",
+                        )
+                        .requires(VESTING_AMOUNT.name)
+                        .requires(VESTING_END.name),
+                )
+                .arg(
+                    VESTING_AMOUNT
+                        .def()
+                        .help("The total amount of the token that vests.")
+                        .requires(VESTING_TOKEN.name),
+                )
+                .arg(
+                    VESTING_START
+                        .def()
+                        .help(
+                            "The time from which the tokens start to vest \
+                             linearly until the vesting end. If none is \
+                             provided, all the tokens vest at once at the \
+                             vesting end.",
+                        )
+                        .requires(VESTING_TOKEN.name),
+                )
+                .arg(
+                    VESTING_END
+                        .def()
+                        .help("The time from which all the tokens are vested.")
+                        .requires(VESTING_TOKEN.name),
+                )
         }
     }
 
@@ -4189,6 +4811,16 @@ pub mod args {
                     .map(|pk| chain_ctx.get(pk))
                     .collect(),
                 threshold: self.threshold,
+                key_policies: self.key_policies.map(|path| {
+                    let file = std::io::BufReader::new(
+                        std::fs::File::open(path).expect(
+                            "Failed to open the provided file to the key \
+                             policies",
+                        ),
+                    );
+                    serde_json::from_reader(file)
+                        .expect("Failed to parse the key policies")
+                }),
             }
         }
     }
@@ -4201,6 +4833,7 @@ pub mod args {
             let tx_code_path = PathBuf::from(TX_UPDATE_ACCOUNT_WASM);
             let public_keys = PUBLIC_KEYS.parse(matches);
             let threshold = THRESOLD.parse(matches);
+            let key_policies = KEY_POLICIES.parse(matches);
             Self {
                 tx,
                 vp_code_path,
@@ -4208,6 +4841,7 @@ pub mod args {
                 tx_code_path,
                 public_keys,
                 threshold,
+                key_policies,
             }
         }
 
@@ -4231,6 +4865,16 @@ pub mod args {
                      authorization. Must be less then the maximum number of \
                      public keys provided.",
                 ))
+                .arg(KEY_POLICIES.def().help(
+                    "The path to a JSON file mapping public keys of the \
+                     account to their policy, replacing all the existing \
+                     policies. A policy lists the \"permissions\" of the key, \
+                     out of \"Transfer\", \"PoS\", \"Governance\" and \
+                     \"Admin\", and its \"spending_limits\", mapping token \
+                     addresses to the maximum raw amount debited from the \
+                     account per epoch. Keys without a policy are \
+                     unrestricted.",
+                ))
         }
     }
 
@@ -4408,6 +5052,258 @@ pub mod args {
         }
     }
 
+    impl CliToSdk<PausePgfFunding<SdkTypes>> for PausePgfFunding<CliTypes> {
+        fn to_sdk(self, ctx: &mut Context) -> PausePgfFunding<SdkTypes> {
+            PausePgfFunding::<SdkTypes> {
+                tx: self.tx.to_sdk(ctx),
+                steward: ctx.borrow_chain_or_exit().get(&self.steward),
+                funding: self.funding,
+                tx_code_path: self.tx_code_path.to_path_buf(),
+            }
+        }
+    }
+
+    impl Args for PausePgfFunding<CliTypes> {
+        fn parse(matches: &ArgMatches) -> Self {
+            let tx = Tx::parse(matches);
+            let steward = STEWARD.parse(matches);
+            let funding = PGF_FUNDING.parse(matches);
+            let tx_code_path = PathBuf::from(TX_PAUSE_PGF_FUNDING_WASM);
+            Self {
+                tx,
+                steward,
+                funding,
+                tx_code_path,
+            }
+        }
+
+        fn def(app: App) -> App {
+            app.add_args::<Tx<CliTypes>>()
+                .arg(STEWARD.def().help("Steward address."))
+                .arg(PGF_FUNDING.def().help(
+                    "The funding key of the continuous PGF funding to pause: \
+                     the address of an internal recipient or the key shown by \
+                     `query-pgf` for a recipient on another chain.",
+                ))
+        }
+    }
+
+    impl CliToSdk<ScheduleTx<SdkTypes>> for ScheduleTx<CliTypes> {
+        fn to_sdk(self, ctx: &mut Context) -> ScheduleTx<SdkTypes> {
+            ScheduleTx::<SdkTypes> {
+                tx: self.tx.to_sdk(ctx),
+                owner: ctx.borrow_chain_or_exit().get(&self.owner),
+                scheduled_tx: std::fs::read(self.scheduled_tx)
+                    .expect("Expected a file at given path"),
+                scheduled_signatures: self
+                    .scheduled_signatures
+                    .iter()
+                    .map(|path| {
+                        std::fs::read(path)
+                            .expect("Expected a file at given path")
+                    })
+                    .collect(),
+                schedule: self.schedule,
+                scheduled_gas_limit: self.scheduled_gas_limit,
+                tx_code_path: self.tx_code_path.to_path_buf(),
+            }
+        }
+    }
+
+    impl Args for ScheduleTx<CliTypes> {
+        fn parse(matches: &ArgMatches) -> Self {
+            let tx = Tx::parse(matches);
+            let owner = OWNER.parse(matches);
+            let scheduled_tx = TX_PATH.parse(matches);
+            let scheduled_signatures = SCHEDULED_SIGNATURES.parse(matches);
+            let schedule =
+                match (BLOCK_HEIGHT_OPT.parse(matches), EPOCH.parse(matches)) {
+                    (Some(height), None) => Schedule::AtHeight(height),
+                    (None, Some(epoch)) => Schedule::AtEpoch(epoch),
+                    _ => unreachable!(
+                        "Exactly one of the height or the epoch is required"
+                    ),
+                };
+            let scheduled_gas_limit = SCHEDULED_GAS_LIMIT.parse(matches);
+            let tx_code_path = PathBuf::from(TX_SCHEDULE_TX_WASM);
+            Self {
+                tx,
+                owner,
+                scheduled_tx,
+                scheduled_signatures,
+                schedule,
+                scheduled_gas_limit,
+                tx_code_path,
+            }
+        }
+
+        fn def(app: App) -> App {
+            app.add_args::<Tx<CliTypes>>()
+                .arg(OWNER.def().help(
+                    "The address paying the fees of the scheduled transaction \
+                     and allowed to cancel it.",
+                ))
+                .arg(TX_PATH.def().help(
+                    "The path to the serialized transaction to schedule, as \
+                     dumped with --dump-tx.",
+                ))
+                .arg(SCHEDULED_SIGNATURES.def().help(
+                    "List of file paths containing the serialized signatures \
+                     of the transaction to schedule.",
+                ))
+                .arg(BLOCK_HEIGHT_OPT.def().help(
+                    "The block height at the end of which to execute the \
+                     scheduled transaction.",
+                ))
+                .arg(EPOCH.def().help(
+                    "The epoch at the end of whose first block to execute the \
+                     scheduled transaction.",
+                ))
+                .group(
+                    ArgGroup::new("schedule")
+                        .args([BLOCK_HEIGHT_OPT.name, EPOCH.name])
+                        .required(true),
+                )
+                .arg(SCHEDULED_GAS_LIMIT.def().help(
+                    "The maximum amount of gas the scheduled transaction may \
+                     use. The fees for it are escrowed until the execution.",
+                ))
+        }
+    }
+
+    impl CliToSdk<CancelScheduledTx<SdkTypes>> for CancelScheduledTx<CliTypes> {
+        fn to_sdk(self, ctx: &mut Context) -> CancelScheduledTx<SdkTypes> {
+            CancelScheduledTx::<SdkTypes> {
+                tx: self.tx.to_sdk(ctx),
+                id: self.id,
+                owner: ctx.borrow_chain_or_exit().get(&self.owner),
+                tx_code_path: self.tx_code_path.to_path_buf(),
+            }
+        }
+    }
+
+    impl Args for CancelScheduledTx<CliTypes> {
+        fn parse(matches: &ArgMatches) -> Self {
+            let tx = Tx::parse(matches);
+            let id = SCHEDULED_TX_ID.parse(matches);
+            let owner = OWNER.parse(matches);
+            let tx_code_path = PathBuf::from(TX_CANCEL_SCHEDULED_TX_WASM);
+            Self {
+                tx,
+                id,
+                owner,
+                tx_code_path,
+            }
+        }
+
+        fn def(app: App) -> App {
+            app.add_args::<Tx<CliTypes>>()
+                .arg(
+                    SCHEDULED_TX_ID
+                        .def()
+                        .help("The id of the scheduled transaction."),
+                )
+                .arg(
+                    OWNER.def().help("The owner of the scheduled transaction."),
+                )
+        }
+    }
+
+    impl CliToSdk<GrantFeeAllowance<SdkTypes>> for GrantFeeAllowance<CliTypes> {
+        fn to_sdk(self, ctx: &mut Context) -> GrantFeeAllowance<SdkTypes> {
+            let tx = self.tx.to_sdk(ctx);
+            let chain_ctx = ctx.borrow_chain_or_exit();
+            GrantFeeAllowance::<SdkTypes> {
+                tx,
+                granter: chain_ctx.get(&self.granter),
+                grantee: chain_ctx.get(&self.grantee),
+                token: chain_ctx.get(&self.token),
+                spend_limit: self.spend_limit,
+                expiration: self.expiration,
+                tx_code_path: self.tx_code_path.to_path_buf(),
+            }
+        }
+    }
+
+    impl Args for GrantFeeAllowance<CliTypes> {
+        fn parse(matches: &ArgMatches) -> Self {
+            let tx = Tx::parse(matches);
+            let granter = GRANTER.parse(matches);
+            let grantee = GRANTEE.parse(matches);
+            let token = TOKEN.parse(matches);
+            let spend_limit = InputAmount::Unvalidated(AMOUNT.parse(matches));
+            let expiration = EXPIRATION_EPOCH.parse(matches);
+            let tx_code_path = PathBuf::from(TX_GRANT_FEE_ALLOWANCE_WASM);
+            Self {
+                tx,
+                granter,
+                grantee,
+                token,
+                spend_limit,
+                expiration,
+                tx_code_path,
+            }
+        }
+
+        fn def(app: App) -> App {
+            app.add_args::<Tx<CliTypes>>()
+                .arg(GRANTER.def().help("The address paying the fees."))
+                .arg(
+                    GRANTEE
+                        .def()
+                        .help("The address whose transaction fees are paid."),
+                )
+                .arg(TOKEN.def().help("The token the fees are paid in."))
+                .arg(AMOUNT.def().help(
+                    "The maximum amount of fees paid in the token. It \
+                     replaces any spend limit previously granted in the same \
+                     token.",
+                ))
+                .arg(EXPIRATION_EPOCH.def().help(
+                    "The first epoch in which the allowance can no longer be \
+                     used. If none is provided, the allowance doesn't expire.",
+                ))
+        }
+    }
+
+    impl CliToSdk<RevokeFeeAllowance<SdkTypes>> for RevokeFeeAllowance<CliTypes> {
+        fn to_sdk(self, ctx: &mut Context) -> RevokeFeeAllowance<SdkTypes> {
+            let tx = self.tx.to_sdk(ctx);
+            let chain_ctx = ctx.borrow_chain_or_exit();
+            RevokeFeeAllowance::<SdkTypes> {
+                tx,
+                granter: chain_ctx.get(&self.granter),
+                grantee: chain_ctx.get(&self.grantee),
+                tx_code_path: self.tx_code_path.to_path_buf(),
+            }
+        }
+    }
+
+    impl Args for RevokeFeeAllowance<CliTypes> {
+        fn parse(matches: &ArgMatches) -> Self {
+            let tx = Tx::parse(matches);
+            let granter = GRANTER.parse(matches);
+            let grantee = GRANTEE.parse(matches);
+            let tx_code_path = PathBuf::from(TX_REVOKE_FEE_ALLOWANCE_WASM);
+            Self {
+                tx,
+                granter,
+                grantee,
+                tx_code_path,
+            }
+        }
+
+        fn def(app: App) -> App {
+            app.add_args::<Tx<CliTypes>>()
+                .arg(GRANTER.def().help("The address paying the fees."))
+                .arg(
+                    GRANTEE
+                        .def()
+                        .help("The address whose transaction fees are paid."),
+                )
+        }
+    }
+
     impl CliToSdk<Redelegate<SdkTypes>> for Redelegate<CliTypes> {
         fn to_sdk(self, ctx: &mut Context) -> Redelegate<SdkTypes> {
             let tx = self.tx.to_sdk(ctx);
@@ -4475,6 +5371,8 @@ pub mod args {
                 is_offline: self.is_offline,
                 is_pgf_stewards: self.is_pgf_stewards,
                 is_pgf_funding: self.is_pgf_funding,
+                is_parameter_change: self.is_parameter_change,
+                is_upgrade: self.is_upgrade,
                 native_token: ctx.borrow_chain_or_exit().native_token.clone(),
                 tx_code_path: self.tx_code_path,
             }
@@ -4488,6 +5386,8 @@ pub mod args {
             let is_offline = PROPOSAL_OFFLINE.parse(matches);
             let is_pgf_stewards = PROPOSAL_PGF_STEWARD.parse(matches);
             let is_pgf_funding = PROPOSAL_PGF_FUNDING.parse(matches);
+            let is_parameter_change = PROPOSAL_PARAMETER_CHANGE.parse(matches);
+            let is_upgrade = PROPOSAL_UPGRADE.parse(matches);
             let tx_code_path = PathBuf::from(TX_INIT_PROPOSAL);
 
             Self {
@@ -4498,6 +5398,8 @@ pub mod args {
                 is_offline,
                 is_pgf_stewards,
                 is_pgf_funding,
+                is_parameter_change,
+                is_upgrade,
             }
         }
 
@@ -4517,6 +5419,8 @@ pub mod args {
                             PROPOSAL_PGF_FUNDING.name,
                             PROPOSAL_PGF_STEWARD.name,
                             PROPOSAL_ETH.name,
+                            PROPOSAL_PARAMETER_CHANGE.name,
+                            PROPOSAL_UPGRADE.name,
                         ]),
                 )
                 .arg(
@@ -4526,6 +5430,8 @@ pub mod args {
                         .conflicts_with_all([
                             PROPOSAL_PGF_FUNDING.name,
                             PROPOSAL_PGF_STEWARD.name,
+                            PROPOSAL_PARAMETER_CHANGE.name,
+                            PROPOSAL_UPGRADE.name,
                         ]),
                 )
                 .arg(
@@ -4538,6 +5444,8 @@ pub mod args {
                         .conflicts_with_all([
                             PROPOSAL_ETH.name,
                             PROPOSAL_PGF_FUNDING.name,
+                            PROPOSAL_PARAMETER_CHANGE.name,
+                            PROPOSAL_UPGRADE.name,
                         ]),
                 )
                 .arg(
@@ -4550,6 +5458,37 @@ pub mod args {
                         .conflicts_with_all([
                             PROPOSAL_ETH.name,
                             PROPOSAL_PGF_STEWARD.name,
+                            PROPOSAL_PARAMETER_CHANGE.name,
+                            PROPOSAL_UPGRADE.name,
+                        ]),
+                )
+                .arg(
+                    PROPOSAL_PARAMETER_CHANGE
+                        .def()
+                        .help(
+                            "Flag if the proposal is of type \
+                             parameter-change. Used to update protocol, PoS, \
+                             governance and pgf parameters.",
+                        )
+                        .conflicts_with_all([
+                            PROPOSAL_ETH.name,
+                            PROPOSAL_PGF_STEWARD.name,
+                            PROPOSAL_PGF_FUNDING.name,
+                            PROPOSAL_UPGRADE.name,
+                        ]),
+                )
+                .arg(
+                    PROPOSAL_UPGRADE
+                        .def()
+                        .help(
+                            "Flag if the proposal is of type upgrade. Used to \
+                             schedule a software upgrade at a block height.",
+                        )
+                        .conflicts_with_all([
+                            PROPOSAL_ETH.name,
+                            PROPOSAL_PGF_STEWARD.name,
+                            PROPOSAL_PGF_FUNDING.name,
+                            PROPOSAL_PARAMETER_CHANGE.name,
                         ]),
                 )
         }
@@ -4604,11 +5543,10 @@ pub mod args {
                             DATA_PATH_OPT.name,
                         ]),
                 )
-                .arg(
-                    PROPOSAL_VOTE
-                        .def()
-                        .help("The vote for the proposal. Either yay or nay."),
-                )
+                .arg(PROPOSAL_VOTE.def().help(
+                    "The vote for the proposal. Either yay, nay, abstain or \
+                     no-with-veto.",
+                ))
                 .arg(
                     PROPOSAL_OFFLINE
                         .def()
@@ -4629,6 +5567,109 @@ pub mod args {
         }
     }
 
+    impl CliToSdk<DepositProposal<SdkTypes>> for DepositProposal<CliTypes> {
+        fn to_sdk(self, ctx: &mut Context) -> DepositProposal<SdkTypes> {
+            let tx = self.tx.to_sdk(ctx);
+            let chain_ctx = ctx.borrow_chain_or_exit();
+            DepositProposal::<SdkTypes> {
+                tx,
+                proposal_id: self.proposal_id,
+                depositor: chain_ctx.get(&self.depositor),
+                amount: self.amount,
+                native_token: chain_ctx.native_token.clone(),
+                tx_code_path: self.tx_code_path.to_path_buf(),
+            }
+        }
+    }
+
+    impl Args for DepositProposal<CliTypes> {
+        fn parse(matches: &ArgMatches) -> Self {
+            let tx = Tx::parse(matches);
+            let proposal_id = PROPOSAL_ID.parse(matches);
+            let depositor = DEPOSITOR.parse(matches);
+            let amount = AMOUNT.parse(matches);
+            let amount = amount
+                .canonical()
+                .increase_precision(NATIVE_MAX_DECIMAL_PLACES.into())
+                .unwrap_or_else(|e| {
+                    println!("Could not parse deposit amount: {:?}", e);
+                    safe_exit(1);
+                })
+                .amount;
+            let tx_code_path = PathBuf::from(TX_DEPOSIT_PROPOSAL_WASM);
+            Self {
+                tx,
+                proposal_id,
+                depositor,
+                amount,
+                native_token: (),
+                tx_code_path,
+            }
+        }
+
+        fn def(app: App) -> App {
+            app.add_args::<Tx<CliTypes>>()
+                .arg(PROPOSAL_ID.def().help("The proposal identifier."))
+                .arg(DEPOSITOR.def().help("The address depositing the tokens."))
+                .arg(
+                    AMOUNT
+                        .def()
+                        .help("The amount of native tokens to deposit."),
+                )
+        }
+    }
+
+    impl CliToSdk<DelegateVotes<SdkTypes>> for DelegateVotes<CliTypes> {
+        fn to_sdk(self, ctx: &mut Context) -> DelegateVotes<SdkTypes> {
+            let tx = self.tx.to_sdk(ctx);
+            let chain_ctx = ctx.borrow_chain_or_exit();
+            DelegateVotes::<SdkTypes> {
+                tx,
+                delegator: chain_ctx.get(&self.delegator),
+                delegate: self.delegate.map(|x| chain_ctx.get(&x)),
+                proposal_kind: self.proposal_kind,
+                tx_code_path: self.tx_code_path.to_path_buf(),
+            }
+        }
+    }
+
+    impl Args for DelegateVotes<CliTypes> {
+        fn parse(matches: &ArgMatches) -> Self {
+            let tx = Tx::parse(matches);
+            let delegator = DELEGATOR.parse(matches);
+            let delegate = DELEGATE_OPT.parse(matches);
+            let proposal_kind = PROPOSAL_KIND_OPT.parse(matches);
+            let tx_code_path = PathBuf::from(TX_DELEGATE_VOTES_WASM);
+            Self {
+                tx,
+                delegator,
+                delegate,
+                proposal_kind,
+                tx_code_path,
+            }
+        }
+
+        fn def(app: App) -> App {
+            app.add_args::<Tx<CliTypes>>()
+                .arg(
+                    DELEGATOR
+                        .def()
+                        .help("The address delegating its governance votes."),
+                )
+                .arg(DELEGATE_OPT.def().help(
+                    "The address voting on behalf of the delegator. Omit it \
+                     to revoke the delegation.",
+                ))
+                .arg(PROPOSAL_KIND_OPT.def().help(
+                    "Restrict the delegation to the proposals of a type, \
+                     either \"default\", \"pgf-steward\", \"pgf-payment\", \
+                     \"parameter-change\" or \"upgrade\". A delegation \
+                     restricted to the type of a proposal takes precedence \
+                     over the delegation for all the types.",
+                ))
+        }
+    }
+
     impl CliToSdk<RevealPk<SdkTypes>> for RevealPk<CliTypes> {
         fn to_sdk(self, ctx: &mut Context) -> RevealPk<SdkTypes> {
             let tx = self.tx.to_sdk(ctx);
@@ -4749,6 +5790,92 @@ pub mod args {
         }
     }
 
+    impl CliToSdk<QueryScheduledTxs<SdkTypes>> for QueryScheduledTxs<CliTypes> {
+        fn to_sdk(self, ctx: &mut Context) -> QueryScheduledTxs<SdkTypes> {
+            QueryScheduledTxs::<SdkTypes> {
+                query: self.query.to_sdk(ctx),
+                owner: ctx.borrow_chain_or_exit().get(&self.owner),
+            }
+        }
+    }
+
+    impl Args for QueryScheduledTxs<CliTypes> {
+        fn parse(matches: &ArgMatches) -> Self {
+            let query = Query::parse(matches);
+            let owner = OWNER.parse(matches);
+            Self { query, owner }
+        }
+
+        fn def(app: App) -> App {
+            app.add_args::<Query<CliTypes>>().arg(
+                OWNER.def().help("The owner of the scheduled transactions."),
+            )
+        }
+    }
+
+    impl CliToSdk<QueryVoteDelegations<SdkTypes>>
+        for QueryVoteDelegations<CliTypes>
+    {
+        fn to_sdk(self, ctx: &mut Context) -> QueryVoteDelegations<SdkTypes> {
+            let query = self.query.to_sdk(ctx);
+            let chain_ctx = ctx.borrow_chain_or_exit();
+            QueryVoteDelegations::<SdkTypes> {
+                query,
+                address: chain_ctx.get(&self.address),
+            }
+        }
+    }
+
+    impl Args for QueryVoteDelegations<CliTypes> {
+        fn parse(matches: &ArgMatches) -> Self {
+            let query = Query::parse(matches);
+            let address = ADDRESS.parse(matches);
+            Self { query, address }
+        }
+
+        fn def(app: App) -> App {
+            app.add_args::<Query<CliTypes>>().arg(
+                ADDRESS
+                    .def()
+                    .help("The address delegating or receiving the votes."),
+            )
+        }
+    }
+
+    impl CliToSdk<QueryFeeAllowances<SdkTypes>> for QueryFeeAllowances<CliTypes> {
+        fn to_sdk(self, ctx: &mut Context) -> QueryFeeAllowances<SdkTypes> {
+            let query = self.query.to_sdk(ctx);
+            let chain_ctx = ctx.borrow_chain_or_exit();
+            QueryFeeAllowances::<SdkTypes> {
+                query,
+                granter: chain_ctx.get(&self.granter),
+                grantee: self.grantee.map(|x| chain_ctx.get(&x)),
+            }
+        }
+    }
+
+    impl Args for QueryFeeAllowances<CliTypes> {
+        fn parse(matches: &ArgMatches) -> Self {
+            let query = Query::parse(matches);
+            let granter = GRANTER.parse(matches);
+            let grantee = GRANTEE_OPT.parse(matches);
+            Self {
+                query,
+                granter,
+                grantee,
+            }
+        }
+
+        fn def(app: App) -> App {
+            app.add_args::<Query<CliTypes>>()
+                .arg(GRANTER.def().help("The address paying the fees."))
+                .arg(GRANTEE_OPT.def().help(
+                    "The address whose transaction fees are paid. If none is \
+                     provided, the allowances of all the grantees are shown.",
+                ))
+        }
+    }
+
     impl CliToSdk<QueryProtocolParameters<SdkTypes>>
         for QueryProtocolParameters<CliTypes>
     {
@@ -5634,6 +6761,7 @@ pub mod args {
         type BpConversionTable = PathBuf;
         type Data = PathBuf;
         type EthereumAddress = String;
+        type KeyPolicies = PathBuf;
         type Keypair = WalletKeypair;
         type NativeAddress = ();
         type PublicKey = WalletPublicKey;
@@ -5649,6 +6777,7 @@ pub mod args {
             Tx::<SdkTypes> {
                 dry_run: self.dry_run,
                 dry_run_wrapper: self.dry_run_wrapper,
+                gas_profile: self.gas_profile,
                 dump_tx: self.dump_tx,
                 output_folder: self.output_folder,
                 force: self.force,
@@ -5661,6 +6790,7 @@ pub mod args {
                 fee_unshield: self
                     .fee_unshield
                     .map(|ref fee_unshield| ctx.get_cached(fee_unshield)),
+                fee_granter: self.fee_granter.map(|x| ctx.get(&x)),
                 gas_limit: self.gas_limit,
                 signing_keys: self
                     .signing_keys
@@ -5686,6 +6816,7 @@ pub mod args {
                     .wrapper_fee_payer
                     .map(|x| ctx.get_cached(&x)),
                 use_device: self.use_device,
+                signer: self.signer,
             }
         }
     }
@@ -5707,6 +6838,10 @@ pub mod args {
                     )
                     .conflicts_with(DRY_RUN_TX.name),
             )
+            .arg(GAS_PROFILE.def().help(
+                "Print the gas used per host function and per validity \
+                 predicate when dry running the transaction.",
+            ))
             .arg(DUMP_TX.def().help("Dump transaction bytes to a file."))
             .arg(FORCE.def().help(
                 "Submit the transaction even if it doesn't pass client checks.",
@@ -5730,16 +6865,27 @@ pub mod args {
             ))
             .arg(FEE_AMOUNT_OPT.def().help(
                 "The amount being paid, per gas unit, for the inclusion of \
-                 this transaction",
+                 this transaction. If none is provided, it is estimated from \
+                 the gas utilization of the recent blocks.",
             ))
             .arg(FEE_TOKEN.def().help("The token for paying the gas"))
             .arg(FEE_UNSHIELD_SPENDING_KEY.def().help(
                 "The spending key to be used for fee unshielding. If none is \
                  provided, fee will be payed from the unshielded balance only.",
             ))
-            .arg(GAS_LIMIT.def().help(
+            .arg(
+                FEE_GRANTER_OPT
+                    .def()
+                    .help(
+                        "The address paying the fees out of the fee allowance \
+                         it granted to the fee payer.",
+                    )
+                    .conflicts_with(FEE_UNSHIELD_SPENDING_KEY.name),
+            )
+            .arg(GAS_LIMIT_OPT.def().help(
                 "The multiplier of the gas limit resolution defining the \
-                 maximum amount of gas needed to run transaction.",
+                 maximum amount of gas needed to run transaction. If none is \
+                 provided, it is estimated from a dry run of the transaction.",
             ))
             .arg(WALLET_ALIAS_FORCE.def().help(
                 "Override the alias without confirmation if it already exists.",
@@ -5815,11 +6961,24 @@ pub mod args {
                 "Use an attached hardware wallet device to sign the \
                  transaction.",
             ))
+            .arg(
+                SIGNER
+                    .def()
+                    .help(
+                        "Use an external signer to sign the transaction with \
+                         the keys that are not in the wallet. Either \
+                         \"exec:<program> [args]\" to spawn a program for \
+                         every signature or \"unix:<socket path>\" to connect \
+                         to a signer listening on a Unix socket.",
+                    )
+                    .conflicts_with(USE_DEVICE.name),
+            )
         }
 
         fn parse(matches: &ArgMatches) -> Self {
             let dry_run = DRY_RUN_TX.parse(matches);
             let dry_run_wrapper = DRY_RUN_WRAPPER_TX.parse(matches);
+            let gas_profile = GAS_PROFILE.parse(matches);
             let dump_tx = DUMP_TX.parse(matches);
             let force = FORCE.parse(matches);
             let broadcast_only = BROADCAST_ONLY.parse(matches);
@@ -5829,8 +6988,9 @@ pub mod args {
                 FEE_AMOUNT_OPT.parse(matches).map(InputAmount::Unvalidated);
             let fee_token = FEE_TOKEN.parse(matches);
             let fee_unshield = FEE_UNSHIELD_SPENDING_KEY.parse(matches);
+            let fee_granter = FEE_GRANTER_OPT.parse(matches);
             let _wallet_alias_force = WALLET_ALIAS_FORCE.parse(matches);
-            let gas_limit = GAS_LIMIT.parse(matches);
+            let gas_limit = GAS_LIMIT_OPT.parse(matches);
             let wallet_alias_force = WALLET_ALIAS_FORCE.parse(matches);
             let expiration = EXPIRATION_OPT.parse(matches);
             let disposable_signing_key = DISPOSABLE_SIGNING_KEY.parse(matches);
@@ -5843,9 +7003,11 @@ pub mod args {
             let wrapper_fee_payer = FEE_PAYER_OPT.parse(matches);
             let output_folder = OUTPUT_FOLDER_PATH.parse(matches);
             let use_device = USE_DEVICE.parse(matches);
+            let signer = SIGNER.parse(matches);
             Self {
                 dry_run,
                 dry_run_wrapper,
+                gas_profile,
                 dump_tx,
                 force,
                 broadcast_only,
@@ -5855,6 +7017,7 @@ pub mod args {
                 fee_amount,
                 fee_token,
                 fee_unshield,
+                fee_granter,
                 gas_limit,
                 expiration,
                 disposable_signing_key,
@@ -5867,6 +7030,7 @@ pub mod args {
                 wrapper_fee_payer,
                 output_folder,
                 use_device,
+                signer,
             }
         }
     }
@@ -152,6 +152,28 @@ impl CliApi {
                         let namada = ctx.to_sdk(&client, io);
                         tx::submit_vote_proposal(&namada, args).await?;
                     }
+                    Sub::TxDelegateVotes(TxDelegateVotes(mut args)) => {
+                        let client = client.unwrap_or_else(|| {
+                            C::from_tendermint_address(
+                                &mut args.tx.ledger_address,
+                            )
+                        });
+                        client.wait_until_node_is_synced(io).await?;
+                        let args = args.to_sdk(&mut ctx);
+                        let namada = ctx.to_sdk(&client, io);
+                        tx::submit_delegate_votes(&namada, args).await?;
+                    }
+                    Sub::TxDepositProposal(TxDepositProposal(mut args)) => {
+                        let client = client.unwrap_or_else(|| {
+                            C::from_tendermint_address(
+                                &mut args.tx.ledger_address,
+                            )
+                        });
+                        client.wait_until_node_is_synced(io).await?;
+                        let args = args.to_sdk(&mut ctx);
+                        let namada = ctx.to_sdk(&client, io);
+                        tx::submit_deposit_proposal(&namada, args).await?;
+                    }
                     Sub::TxRevealPk(TxRevealPk(mut args)) => {
                         let client = client.unwrap_or_else(|| {
                             C::from_tendermint_address(
@@ -349,6 +371,63 @@ impl CliApi {
                         let namada = ctx.to_sdk(&client, io);
                         tx::submit_resign_steward(&namada, args).await?;
                     }
+                    Sub::TxPausePgfFunding(TxPausePgfFunding(mut args)) => {
+                        let client = client.unwrap_or_else(|| {
+                            C::from_tendermint_address(
+                                &mut args.tx.ledger_address,
+                            )
+                        });
+                        client.wait_until_node_is_synced(io).await?;
+                        let args = args.to_sdk(&mut ctx);
+                        let namada = ctx.to_sdk(&client, io);
+                        tx::submit_pause_pgf_funding(&namada, args).await?;
+                    }
+                    Sub::TxScheduleTx(TxScheduleTx(mut args)) => {
+                        let client = client.unwrap_or_else(|| {
+                            C::from_tendermint_address(
+                                &mut args.tx.ledger_address,
+                            )
+                        });
+                        client.wait_until_node_is_synced(io).await?;
+                        let args = args.to_sdk(&mut ctx);
+                        let namada = ctx.to_sdk(&client, io);
+                        tx::submit_schedule_tx(&namada, args).await?;
+                    }
+                    Sub::TxCancelScheduledTx(TxCancelScheduledTx(mut args)) => {
+                        let client = client.unwrap_or_else(|| {
+                            C::from_tendermint_address(
+                                &mut args.tx.ledger_address,
+                            )
+                        });
+                        client.wait_until_node_is_synced(io).await?;
+                        let args = args.to_sdk(&mut ctx);
+                        let namada = ctx.to_sdk(&client, io);
+                        tx::submit_cancel_scheduled_tx(&namada, args).await?;
+                    }
+                    Sub::TxGrantFeeAllowance(TxGrantFeeAllowance(mut args)) => {
+                        let client = client.unwrap_or_else(|| {
+                            C::from_tendermint_address(
+                                &mut args.tx.ledger_address,
+                            )
+                        });
+                        client.wait_until_node_is_synced(io).await?;
+                        let args = args.to_sdk(&mut ctx);
+                        let namada = ctx.to_sdk(&client, io);
+                        tx::submit_grant_fee_allowance(&namada, args).await?;
+                    }
+                    Sub::TxRevokeFeeAllowance(TxRevokeFeeAllowance(
+                        mut args,
+                    )) => {
+                        let client = client.unwrap_or_else(|| {
+                            C::from_tendermint_address(
+                                &mut args.tx.ledger_address,
+                            )
+                        });
+                        client.wait_until_node_is_synced(io).await?;
+                        let args = args.to_sdk(&mut ctx);
+                        let namada = ctx.to_sdk(&client, io);
+                        tx::submit_revoke_fee_allowance(&namada, args).await?;
+                    }
                     // Ledger queries
                     Sub::QueryEpoch(QueryEpoch(mut args)) => {
                         let client = client.unwrap_or_else(|| {
@@ -559,6 +638,41 @@ impl CliApi {
                         let namada = ctx.to_sdk(&client, io);
                         rpc::query_pgf(&namada, args).await;
                     }
+                    Sub::QueryScheduledTxs(QueryScheduledTxs(mut args)) => {
+                        let client = client.unwrap_or_else(|| {
+                            C::from_tendermint_address(
+                                &mut args.query.ledger_address,
+                            )
+                        });
+                        client.wait_until_node_is_synced(io).await?;
+                        let args = args.to_sdk(&mut ctx);
+                        let namada = ctx.to_sdk(&client, io);
+                        rpc::query_scheduled_txs(&namada, args).await;
+                    }
+                    Sub::QueryFeeAllowances(QueryFeeAllowances(mut args)) => {
+                        let client = client.unwrap_or_else(|| {
+                            C::from_tendermint_address(
+                                &mut args.query.ledger_address,
+                            )
+                        });
+                        client.wait_until_node_is_synced(io).await?;
+                        let args = args.to_sdk(&mut ctx);
+                        let namada = ctx.to_sdk(&client, io);
+                        rpc::query_fee_allowances(&namada, args).await;
+                    }
+                    Sub::QueryVoteDelegations(QueryVoteDelegations(
+                        mut args,
+                    )) => {
+                        let client = client.unwrap_or_else(|| {
+                            C::from_tendermint_address(
+                                &mut args.query.ledger_address,
+                            )
+                        });
+                        client.wait_until_node_is_synced(io).await?;
+                        let args = args.to_sdk(&mut ctx);
+                        let namada = ctx.to_sdk(&client, io);
+                        rpc::query_vote_delegations(&namada, args).await;
+                    }
                     Sub::QueryAccount(QueryAccount(mut args)) => {
                         let client = client.unwrap_or_else(|| {
                             C::from_tendermint_address(
@@ -143,6 +143,8 @@ pub async fn query_transfers<'a>(
         .values()
         .map(|fvk| (ExtendedFullViewingKey::from(*fvk).fvk.vk, fvk))
         .collect();
+    // The memos of the transactions of the last block queried
+    let mut block_memos = (None, BTreeMap::new());
     // Now display historical shielded and transparent transactions
     for ((height, idx), (epoch, tfer_delta, tx_delta)) in transfers {
         // Check if this transfer pertains to the supplied owner
@@ -200,6 +202,16 @@ pub async fn query_transfers<'a>(
             height,
             idx
         );
+        // Fetch the memos of a block once for all of its transfers
+        if block_memos.0 != Some(height) {
+            let memos = rpc::query_block_memos(context.client(), height)
+                .await
+                .unwrap_or_default();
+            block_memos = (Some(height), memos);
+        }
+        if let Some(memo) = block_memos.1.get(&idx) {
+            display_line!(context.io(), "  Memo: {}", memo);
+        }
         // Display the transparent changes first
         for (account, MaspChange { ref asset, change }) in tfer_delta {
             if account != MASP {
@@ -1153,11 +1165,14 @@ pub async fn query_proposal_result<'a>(
                         proposal.voting_end_epoch,
                     )
                     .await;
+                    let governance_parameters =
+                        query_governance_parameters(context.client()).await;
 
                     compute_proposal_result(
                         votes,
                         total_voting_power,
                         tally_type,
+                        governance_parameters.veto_threshold,
                     )
                 }
             };
@@ -1233,11 +1248,14 @@ pub async fn query_proposal_result<'a>(
             proposal.proposal.tally_epoch,
         )
         .await;
+        let governance_parameters =
+            query_governance_parameters(context.client()).await;
 
         let proposal_result = compute_proposal_result(
             proposal_votes,
             total_voting_power,
             TallyType::TwoThirds,
+            governance_parameters.veto_threshold,
         );
 
         display_line!(
@@ -1263,6 +1281,21 @@ pub async fn query_account<'a>(
         display_line!(context.io(), "Public keys:");
         for (public_key, _) in account.public_keys_map.pk_to_idx {
             display_line!(context.io(), "- {}", public_key);
+            if let Some(policy) = account.key_policies.get(&public_key) {
+                display_line!(
+                    context.io(),
+                    "  Permissions: {:?}",
+                    policy.permissions
+                );
+                for (token, limit) in &policy.spending_limits {
+                    display_line!(
+                        context.io(),
+                        "  Spending limit per epoch: {} {}",
+                        context.format_amount(token, *limit).await,
+                        token
+                    );
+                }
+            }
         }
     } else {
         display_line!(context.io(), "No account exists for {}", args.owner);
@@ -1308,18 +1341,232 @@ pub async fn query_pgf<'a>(context: &impl Namada<'a>, _args: args::QueryPgf) {
         false => {
             display_line!(context.io(), "Pgf fundings:");
             for funding in fundings {
+                let funding_key = funding.detail.recipient.funding_key();
+                let is_paused =
+                    rpc::is_pgf_funding_paused(context.client(), &funding_key)
+                        .await
+                        .unwrap_or_default();
                 display_line!(
                     context.io(),
                     "{:4}- {} for {}",
                     "",
-                    funding.detail.target,
+                    funding.detail.recipient,
                     funding.detail.amount.to_string_native()
                 );
+                display_line!(
+                    context.io(),
+                    "{:4}  Funding: {}",
+                    "",
+                    funding_key
+                );
+                display_line!(
+                    context.io(),
+                    "{:4}  Proposal id: {}",
+                    "",
+                    funding.id
+                );
+                display_line!(
+                    context.io(),
+                    "{:4}  Start epoch: {}",
+                    "",
+                    funding.detail.start_epoch
+                );
+                if let Some(end_epoch) = funding.detail.end_epoch {
+                    display_line!(
+                        context.io(),
+                        "{:4}  End epoch: {}",
+                        "",
+                        end_epoch
+                    );
+                }
+                match funding.detail.cap {
+                    Some(cap) => display_line!(
+                        context.io(),
+                        "{:4}  Paid: {} out of {}",
+                        "",
+                        funding.paid.to_string_native(),
+                        cap.to_string_native()
+                    ),
+                    None => display_line!(
+                        context.io(),
+                        "{:4}  Paid: {}",
+                        "",
+                        funding.paid.to_string_native()
+                    ),
+                }
+                if is_paused {
+                    display_line!(
+                        context.io(),
+                        "{:4}  Paused by a steward",
+                        ""
+                    );
+                }
             }
         }
     }
 }
 
+/// Query and print the pending scheduled txs of an owner
+pub async fn query_scheduled_txs<'a>(
+    context: &impl Namada<'a>,
+    args: args::QueryScheduledTxs,
+) {
+    let scheduled_txs = rpc::query_scheduled_txs(context.client(), &args.owner)
+        .await
+        .unwrap();
+    if scheduled_txs.is_empty() {
+        display_line!(
+            context.io(),
+            "No pending scheduled transactions found for {}",
+            args.owner
+        );
+        return;
+    }
+    display_line!(context.io(), "Scheduled transactions of {}:", args.owner);
+    for (id, scheduled) in scheduled_txs {
+        display_line!(context.io(), "{:4}- Id: {}", "", id);
+        display_line!(
+            context.io(),
+            "{:6}Tx hash: {}",
+            "",
+            scheduled.tx.header_hash()
+        );
+        display_line!(context.io(), "{:6}Due: {}", "", scheduled.schedule);
+        display_line!(
+            context.io(),
+            "{:6}Gas limit: {}",
+            "",
+            u64::from(scheduled.gas_limit)
+        );
+        display_line!(
+            context.io(),
+            "{:6}Fee per gas unit: {} {}",
+            "",
+            scheduled.fee.amount_per_gas_unit.to_string_native(),
+            scheduled.fee.token
+        );
+    }
+}
+
+/// Query and print the fee allowances granted by a granter
+pub async fn query_fee_allowances<'a>(
+    context: &impl Namada<'a>,
+    args: args::QueryFeeAllowances,
+) {
+    let allowances = match &args.grantee {
+        Some(grantee) => {
+            rpc::query_fee_allowance(context.client(), &args.granter, grantee)
+                .await
+                .unwrap()
+                .map(|allowance| vec![(grantee.clone(), allowance)])
+                .unwrap_or_default()
+        }
+        None => rpc::query_fee_allowances(context.client(), &args.granter)
+            .await
+            .unwrap(),
+    };
+    if allowances.is_empty() {
+        display_line!(
+            context.io(),
+            "No fee allowances granted by {}",
+            args.granter
+        );
+        return;
+    }
+    display_line!(context.io(), "Fee allowances granted by {}:", args.granter);
+    for (grantee, allowance) in allowances {
+        display_line!(context.io(), "{:4}- Grantee: {}", "", grantee);
+        for (token, remaining) in &allowance.spend_limit {
+            let remaining = context.format_amount(token, *remaining).await;
+            display_line!(
+                context.io(),
+                "{:6}Remaining: {} {}",
+                "",
+                remaining,
+                token
+            );
+        }
+        match allowance.expiration {
+            Some(epoch) => {
+                display_line!(
+                    context.io(),
+                    "{:6}Expires at epoch: {}",
+                    "",
+                    epoch
+                )
+            }
+            None => display_line!(context.io(), "{:6}Doesn't expire", ""),
+        }
+    }
+}
+
+pub async fn query_vote_delegations<'a>(
+    context: &impl Namada<'a>,
+    args: args::QueryVoteDelegations,
+) {
+    let delegations =
+        rpc::query_vote_delegations(context.client(), &args.address)
+            .await
+            .unwrap();
+    if delegations.is_empty() {
+        display_line!(
+            context.io(),
+            "{} doesn't delegate its governance votes",
+            args.address
+        );
+    } else {
+        display_line!(
+            context.io(),
+            "Governance delegates of {}:",
+            args.address
+        );
+        for (kind, delegate) in delegations {
+            let kind = kind.map_or("all".to_string(), |kind| kind.to_string());
+            display_line!(
+                context.io(),
+                "{:4}- Proposal type: {}, delegate: {}",
+                "",
+                kind,
+                delegate
+            );
+        }
+    }
+
+    let delegators =
+        rpc::query_vote_delegators(context.client(), &args.address)
+            .await
+            .unwrap();
+    if delegators.is_empty() {
+        display_line!(
+            context.io(),
+            "No address delegates its governance votes to {}",
+            args.address
+        );
+    } else {
+        display_line!(
+            context.io(),
+            "Addresses delegating their governance votes to {}:",
+            args.address
+        );
+        for (delegator, kinds) in delegators {
+            let kinds = kinds
+                .into_iter()
+                .map(|kind| {
+                    kind.map_or("all".to_string(), |kind| kind.to_string())
+                })
+                .collect::<Vec<_>>()
+                .join(", ");
+            display_line!(
+                context.io(),
+                "{:4}- {} (proposal types: {})",
+                "",
+                delegator,
+                kinds
+            );
+        }
+    }
+}
+
 pub async fn query_protocol_parameters<'a>(
     context: &impl Namada<'a>,
     _args: args::QueryProtocolParameters,
@@ -1363,6 +1610,12 @@ pub async fn query_protocol_parameters<'a>(
         "",
         governance_parameters.min_proposal_grace_epochs
     );
+    display_line!(
+        context.io(),
+        "{:4}Veto threshold: {}",
+        "",
+        governance_parameters.veto_threshold
+    );
 
     let pgf_parameters = query_pgf_parameters(context.client()).await;
     display_line!(context.io(), "Public Goods Funding Parameters\n");
@@ -2704,9 +2957,15 @@ pub async fn compute_proposal_votes<
     proposal_id: u64,
     epoch: Epoch,
 ) -> ProposalVotes {
-    let votes = namada_sdk::rpc::query_proposal_votes(client, proposal_id)
+    let mut votes = namada_sdk::rpc::query_proposal_votes(client, proposal_id)
         .await
         .unwrap();
+    // The addresses that didn't vote follow the votes of their delegates
+    votes.extend(
+        namada_sdk::rpc::query_proposal_delegated_votes(client, proposal_id)
+            .await
+            .unwrap(),
+    );
 
     let mut validators_vote: HashMap<Address, TallyVote> = HashMap::default();
     let mut validator_voting_power: HashMap<Address, VotePower> =
@@ -11,7 +11,8 @@ use namada::core::ledger::governance::cli::offline::{
     OfflineProposal, OfflineSignedProposal, OfflineVote,
 };
 use namada::core::ledger::governance::cli::onchain::{
-    DefaultProposal, PgfFundingProposal, PgfStewardProposal, ProposalVote,
+    DefaultProposal, ParameterChangeProposal, PgfFundingProposal,
+    PgfStewardProposal, ProposalVote, UpgradeProposal,
 };
 use namada::ibc::applications::transfer::Memo;
 use namada::proto::{CompressedSignature, Section, Signer, Tx};
@@ -19,6 +20,7 @@ use namada::types::address::{Address, ImplicitAddress};
 use namada::types::dec::Dec;
 use namada::types::io::Io;
 use namada::types::key::{self, *};
+use namada::types::storage::Epoch;
 use namada::types::transaction::pos::{ConsensusKeyChange, InitValidator};
 use namada_sdk::rpc::{TxBroadcastData, TxResponse};
 use namada_sdk::wallet::alias::validator_consensus_key;
@@ -68,7 +70,8 @@ pub async fn aux_signing_data<'a>(
     Ok(signing_data)
 }
 
-// Sign the given transaction using a hardware wallet as a backup
+// Sign the given transaction using a hardware wallet or an external signer as
+// a backup
 pub async fn sign<'a>(
     context: &impl Namada<'a>,
     tx: &mut Tx,
@@ -192,6 +195,14 @@ pub async fn sign<'a>(
             };
         // Finally, begin the signing with the Ledger as backup
         context.sign(tx, args, signing_data, with_hw).await?;
+    } else if let Some(signer) = &args.signer {
+        // Sign with the keys missing from the wallet using the external signer
+        let with_remote = |tx, pubkey, parts| {
+            signing::remote::sign_with_remote_signer(
+                context, signer, tx, pubkey, parts,
+            )
+        };
+        context.sign(tx, args, signing_data, with_remote).await?;
     } else {
         // Otherwise sign without a backup procedure
         context.sign(tx, args, signing_data, default_sign).await?;
@@ -199,6 +210,67 @@ pub async fn sign<'a>(
     Ok(())
 }
 
+// Sign the given batch of transactions using an external signer as a backup.
+// A batch made of a single transaction is signed like any other transaction.
+pub async fn sign_batch<'a>(
+    context: &impl Namada<'a>,
+    tx: &mut Tx,
+    args: &args::Tx,
+    mut signing_data: Vec<SigningTxData>,
+) -> Result<(), error::Error> {
+    if signing_data.len() == 1 {
+        return sign(context, tx, args, signing_data.remove(0)).await;
+    }
+    if args.use_device {
+        return Err(error::Error::Other(
+            "Batches of transactions cannot be signed with a hardware wallet"
+                .to_string(),
+        ));
+    } else if let Some(signer) = &args.signer {
+        // Sign with the keys missing from the wallet using the external signer
+        let with_remote = |tx, pubkey, parts| {
+            signing::remote::sign_with_remote_signer(
+                context, signer, tx, pubkey, parts,
+            )
+        };
+        context.sign_batch(tx, args, signing_data, with_remote).await?;
+    } else {
+        // Otherwise sign without a backup procedure
+        context.sign_batch(tx, args, signing_data, default_sign).await?;
+    }
+    Ok(())
+}
+
+// Build a transaction batched after a transaction revealing the public key of
+// the given address, if needed. The hardware wallet cannot sign batches, so
+// the public key is then revealed by a separate transaction.
+async fn build_with_reveal_aux<'a, F>(
+    context: &impl Namada<'a>,
+    args: &args::Tx,
+    address: &Address,
+    build: F,
+) -> Result<(Tx, Vec<SigningTxData>, Option<Epoch>), error::Error>
+where
+    F: std::future::Future<
+            Output = Result<(Tx, SigningTxData, Option<Epoch>), error::Error>,
+        >,
+{
+    if args.dump_tx || args.use_device {
+        submit_reveal_aux(context, args.clone(), address).await?;
+        let (tx, signing_data, epoch) = build.await?;
+        return Ok((tx, vec![signing_data], epoch));
+    }
+    let (tx, signing_data, epoch) = build.await?;
+    let (tx, signing_data) = tx::build_batch_with_reveal_pk(
+        context,
+        args,
+        address,
+        (tx, signing_data),
+    )
+    .await?;
+    Ok((tx, signing_data, epoch))
+}
+
 // Build a transaction to reveal the signer of the given transaction.
 pub async fn submit_reveal_aux<'a>(
     context: &impl Namada<'a>,
@@ -436,14 +508,7 @@ pub async fn submit_change_consensus_key<'a>(
     .add_data(data);
     let signing_data = aux_signing_data(namada, &tx_args, None, None).await?;
 
-    tx::prepare_tx(
-        namada,
-        &tx_args,
-        &mut tx,
-        signing_data.fee_payer.clone(),
-        None,
-    )
-    .await?;
+    tx::prepare_tx(namada, &tx_args, &mut tx, &signing_data, None).await?;
 
     signing::generate_test_vector(namada, &tx).await?;
 
@@ -740,14 +805,7 @@ pub async fn submit_init_validator<'a>(
     let signing_data =
         init_validator_signing_data(namada, &tx_args, all_pks).await?;
 
-    tx::prepare_tx(
-        namada,
-        &tx_args,
-        &mut tx,
-        signing_data.fee_payer.clone(),
-        None,
-    )
-    .await?;
+    tx::prepare_tx(namada, &tx_args, &mut tx, &signing_data, None).await?;
 
     signing::generate_test_vector(namada, &tx).await?;
 
@@ -862,22 +920,20 @@ pub async fn submit_transfer<'a>(
     args: args::TxTransfer,
 ) -> Result<(), error::Error> {
     for _ in 0..2 {
-        submit_reveal_aux(
+        let (mut tx, signing_data, tx_epoch) = build_with_reveal_aux(
             namada,
-            args.tx.clone(),
+            &args.tx,
             &args.source.effective_address(),
+            args.clone().build(namada),
         )
         .await?;
-
-        let (mut tx, signing_data, tx_epoch) =
-            args.clone().build(namada).await?;
         signing::generate_test_vector(namada, &tx).await?;
 
         if args.tx.dump_tx {
             tx::dump_tx(namada.io(), &args.tx, tx);
             break;
         } else {
-            sign(namada, &mut tx, &args.tx, signing_data).await?;
+            sign_batch(namada, &mut tx, &args.tx, signing_data).await?;
 
             signing::generate_test_vector(namada, &tx).await?;
 
@@ -918,14 +974,19 @@ pub async fn submit_ibc_transfer<'a, N: Namada<'a>>(
 where
     <N::Client as namada::ledger::queries::Client>::Error: std::fmt::Display,
 {
-    submit_reveal_aux(namada, args.tx.clone(), &args.source).await?;
-    let (mut tx, signing_data, _epoch) = args.build(namada).await?;
+    let (mut tx, signing_data, _epoch) = build_with_reveal_aux(
+        namada,
+        &args.tx,
+        &args.source,
+        args.build(namada),
+    )
+    .await?;
     signing::generate_test_vector(namada, &tx).await?;
 
     if args.tx.dump_tx {
         tx::dump_tx(namada.io(), &args.tx, tx);
     } else {
-        sign(namada, &mut tx, &args.tx, signing_data).await?;
+        sign_batch(namada, &mut tx, &args.tx, signing_data).await?;
 
         signing::generate_test_vector(namada, &tx).await?;
 
@@ -1024,6 +1085,62 @@ where
             .await?;
 
         tx::build_pgf_stewards_proposal(namada, &args, proposal).await?
+    } else if args.is_parameter_change {
+        let proposal =
+            ParameterChangeProposal::try_from(args.proposal_data.as_ref())
+                .map_err(|e| {
+                    error::TxError::FailedGovernaneProposalDeserialize(
+                        e.to_string(),
+                    )
+                })?;
+        let author_balance = rpc::get_token_balance(
+            namada.client(),
+            &namada.native_token(),
+            &proposal.proposal.author,
+        )
+        .await;
+        let proposal = proposal
+            .validate(
+                &governance_parameters,
+                current_epoch,
+                author_balance,
+                args.tx.force,
+            )
+            .map_err(|e| error::TxError::InvalidProposal(e.to_string()))?;
+
+        submit_reveal_aux(namada, args.tx.clone(), &proposal.proposal.author)
+            .await?;
+
+        tx::build_parameter_change_proposal(namada, &args, proposal).await?
+    } else if args.is_upgrade {
+        let proposal = UpgradeProposal::try_from(args.proposal_data.as_ref())
+            .map_err(|e| {
+            error::TxError::FailedGovernaneProposalDeserialize(e.to_string())
+        })?;
+        let current_height = namada_sdk::rpc::query_block(namada.client())
+            .await?
+            .map(|block| block.height)
+            .unwrap_or_default();
+        let author_balance = rpc::get_token_balance(
+            namada.client(),
+            &namada.native_token(),
+            &proposal.proposal.author,
+        )
+        .await;
+        let proposal = proposal
+            .validate(
+                &governance_parameters,
+                current_epoch,
+                current_height,
+                author_balance,
+                args.tx.force,
+            )
+            .map_err(|e| error::TxError::InvalidProposal(e.to_string()))?;
+
+        submit_reveal_aux(namada, args.tx.clone(), &proposal.proposal.author)
+            .await?;
+
+        tx::build_upgrade_proposal(namada, &args, proposal).await?
     } else {
         let proposal = DefaultProposal::try_from(args.proposal_data.as_ref())
             .map_err(|e| {
@@ -1144,6 +1261,54 @@ where
     Ok(())
 }
 
+pub async fn submit_delegate_votes<'a, N: Namada<'a>>(
+    namada: &N,
+    args: args::DelegateVotes,
+) -> Result<(), error::Error>
+where
+    <N::Client as namada::ledger::queries::Client>::Error: std::fmt::Display,
+{
+    let (mut tx, signing_data, _epoch) = args.build(namada).await?;
+
+    signing::generate_test_vector(namada, &tx).await?;
+
+    if args.tx.dump_tx {
+        tx::dump_tx(namada.io(), &args.tx, tx);
+    } else {
+        sign(namada, &mut tx, &args.tx, signing_data).await?;
+
+        signing::generate_test_vector(namada, &tx).await?;
+
+        namada.submit(tx, &args.tx).await?;
+    }
+
+    Ok(())
+}
+
+pub async fn submit_deposit_proposal<'a, N: Namada<'a>>(
+    namada: &N,
+    args: args::DepositProposal,
+) -> Result<(), error::Error>
+where
+    <N::Client as namada::ledger::queries::Client>::Error: std::fmt::Display,
+{
+    let (mut tx, signing_data, _epoch) = args.build(namada).await?;
+
+    signing::generate_test_vector(namada, &tx).await?;
+
+    if args.tx.dump_tx {
+        tx::dump_tx(namada.io(), &args.tx, tx);
+    } else {
+        sign(namada, &mut tx, &args.tx, signing_data).await?;
+
+        signing::generate_test_vector(namada, &tx).await?;
+
+        namada.submit(tx, &args.tx).await?;
+    }
+
+    Ok(())
+}
+
 pub async fn sign_tx<'a, N: Namada<'a>>(
     namada: &N,
     args::SignTx {
@@ -1245,16 +1410,19 @@ where
     <N::Client as namada::ledger::queries::Client>::Error: std::fmt::Display,
 {
     let default_address = args.source.clone().unwrap_or(args.validator.clone());
-    submit_reveal_aux(namada, args.tx.clone(), &default_address).await?;
-
-    let (mut tx, signing_data, _fee_unshield_epoch) =
-        args.build(namada).await?;
+    let (mut tx, signing_data, _fee_unshield_epoch) = build_with_reveal_aux(
+        namada,
+        &args.tx,
+        &default_address,
+        args.build(namada),
+    )
+    .await?;
     signing::generate_test_vector(namada, &tx).await?;
 
     if args.tx.dump_tx {
         tx::dump_tx(namada.io(), &args.tx, tx);
     } else {
-        sign(namada, &mut tx, &args.tx, signing_data).await?;
+        sign_batch(namada, &mut tx, &args.tx, signing_data).await?;
 
         signing::generate_test_vector(namada, &tx).await?;
 
@@ -1552,6 +1720,126 @@ where
     Ok(())
 }
 
+pub async fn submit_pause_pgf_funding<'a, N: Namada<'a>>(
+    namada: &N,
+    args: args::PausePgfFunding,
+) -> Result<(), error::Error>
+where
+    <N::Client as namada::ledger::queries::Client>::Error: std::fmt::Display,
+{
+    let (mut tx, signing_data, _epoch) = args.build(namada).await?;
+
+    signing::generate_test_vector(namada, &tx).await?;
+
+    if args.tx.dump_tx {
+        tx::dump_tx(namada.io(), &args.tx, tx);
+    } else {
+        sign(namada, &mut tx, &args.tx, signing_data).await?;
+
+        signing::generate_test_vector(namada, &tx).await?;
+
+        namada.submit(tx, &args.tx).await?;
+    }
+
+    Ok(())
+}
+
+pub async fn submit_schedule_tx<'a, N: Namada<'a>>(
+    namada: &N,
+    args: args::ScheduleTx,
+) -> Result<(), error::Error>
+where
+    <N::Client as namada::ledger::queries::Client>::Error: std::fmt::Display,
+{
+    let (mut tx, signing_data, _epoch) = args.build(namada).await?;
+
+    signing::generate_test_vector(namada, &tx).await?;
+
+    if args.tx.dump_tx {
+        tx::dump_tx(namada.io(), &args.tx, tx);
+    } else {
+        sign(namada, &mut tx, &args.tx, signing_data).await?;
+
+        signing::generate_test_vector(namada, &tx).await?;
+
+        namada.submit(tx, &args.tx).await?;
+    }
+
+    Ok(())
+}
+
+pub async fn submit_cancel_scheduled_tx<'a, N: Namada<'a>>(
+    namada: &N,
+    args: args::CancelScheduledTx,
+) -> Result<(), error::Error>
+where
+    <N::Client as namada::ledger::queries::Client>::Error: std::fmt::Display,
+{
+    let (mut tx, signing_data, _epoch) = args.build(namada).await?;
+
+    signing::generate_test_vector(namada, &tx).await?;
+
+    if args.tx.dump_tx {
+        tx::dump_tx(namada.io(), &args.tx, tx);
+    } else {
+        sign(namada, &mut tx, &args.tx, signing_data).await?;
+
+        signing::generate_test_vector(namada, &tx).await?;
+
+        namada.submit(tx, &args.tx).await?;
+    }
+
+    Ok(())
+}
+
+pub async fn submit_grant_fee_allowance<'a, N: Namada<'a>>(
+    namada: &N,
+    args: args::GrantFeeAllowance,
+) -> Result<(), error::Error>
+where
+    <N::Client as namada::ledger::queries::Client>::Error: std::fmt::Display,
+{
+    let (mut tx, signing_data, _epoch) = args.build(namada).await?;
+
+    signing::generate_test_vector(namada, &tx).await?;
+
+    if args.tx.dump_tx {
+        tx::dump_tx(namada.io(), &args.tx, tx);
+    } else {
+        sign(namada, &mut tx, &args.tx, signing_data).await?;
+
+        signing::generate_test_vector(namada, &tx).await?;
+
+        namada.submit(tx, &args.tx).await?;
+    }
+
+    Ok(())
+}
+
+pub async fn submit_revoke_fee_allowance<'a, N: Namada<'a>>(
+    namada: &N,
+    args: args::RevokeFeeAllowance,
+) -> Result<(), error::Error>
+where
+    <N::Client as namada::ledger::queries::Client>::Error: std::fmt::Display,
+{
+    let (mut tx, signing_data, _epoch) = args.build(namada).await?;
+
+    signing::generate_test_vector(namada, &tx).await?;
+
+    if args.tx.dump_tx {
+        tx::dump_tx(namada.io(), &args.tx, tx);
+    } else {
+        sign(namada, &mut tx, &args.tx, signing_data).await?;
+
+        signing::generate_test_vector(namada, &tx).await?;
+
+        namada.submit(tx, &args.tx).await?;
+    }
+
+    Ok(())
+}
+
 /// Save accounts initialized from a tx into the wallet, if any.
 pub async fn save_initialized_accounts<'a>(
     namada: &impl Namada<'a>,
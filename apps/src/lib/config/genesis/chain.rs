@@ -372,6 +372,7 @@ impl Finalized {
             max_proposal_period,
             max_proposal_content_size,
             min_proposal_grace_epochs,
+            veto_threshold,
         } = self.parameters.gov_params.clone();
         namada::core::ledger::governance::parameters::GovernanceParameters {
             min_proposal_fund: Amount::native_whole(min_proposal_fund),
@@ -380,6 +381,7 @@ impl Finalized {
             max_proposal_content_size,
             min_proposal_grace_epochs,
             min_proposal_voting_period,
+            veto_threshold,
         }
     }
 
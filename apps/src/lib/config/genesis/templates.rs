@@ -436,6 +436,9 @@ pub struct GovernanceParams {
     pub max_proposal_content_size: u64,
     /// Minimum number of epoch between end and grace epoch
     pub min_proposal_grace_epochs: u64,
+    /// Minimum fraction of the cast voting power voting no with veto to veto
+    /// a proposal
+    pub veto_threshold: Dec,
 }
 
 #[derive(
@@ -8,6 +8,7 @@ pub mod utils;
 use std::collections::HashMap;
 use std::fs::{create_dir_all, File};
 use std::io::Write;
+use std::net::SocketAddr;
 use std::path::{Path, PathBuf};
 
 use directories::ProjectDirs;
@@ -35,6 +36,10 @@ pub const FILENAME: &str = "config.toml";
 pub const COMETBFT_DIR: &str = "cometbft";
 /// Chain-specific Namada DB. Nested in chain dirs.
 pub const DB_DIR: &str = "db";
+/// Chain-specific state-sync snapshots. Nested in chain dirs.
+pub const SNAPSHOTS_DIR: &str = "snapshots";
+/// WASM compilation caches shared by all the chains. Nested in the base dir.
+pub const WASM_CACHE_DIR: &str = "wasm_cache";
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Config {
@@ -65,6 +70,20 @@ impl TendermintMode {
     }
 }
 
+/// The storage backend of the ledger's DB
+#[derive(
+    Clone, Copy, Debug, Default, Serialize, Deserialize, PartialEq, Eq,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum StorageBackend {
+    /// RocksDB, an LSM-tree store optimized for write throughput
+    #[default]
+    RocksDB,
+    /// redb, a copy-on-write B-tree store with lower read latency at the cost
+    /// of a higher write amplification
+    Redb,
+}
+
 /// An action to be performed at a
 /// certain block height.
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -99,7 +118,11 @@ pub struct Ledger {
 pub struct Shell {
     pub base_dir: PathBuf,
     // pub ledger_address: SocketAddr,
-    /// RocksDB block cache maximum size in bytes.
+    /// The DB's storage backend. A DB created with one backend cannot be
+    /// opened with another one.
+    #[serde(default)]
+    pub storage_backend: StorageBackend,
+    /// DB block cache maximum size in bytes.
     /// When not set, defaults to 1/3 of the available memory.
     pub block_cache_bytes: Option<u64>,
     /// VP WASM compilation cache maximum size in bytes.
@@ -108,8 +131,15 @@ pub struct Shell {
     /// Tx WASM compilation in-memory cache maximum size in bytes.
     /// When not set, defaults to 1/6 of the available memory.
     pub tx_wasm_compilation_cache_bytes: Option<u64>,
+    /// Maximum size in bytes of the compiled modules files of each of the VP
+    /// and Tx WASM compilation caches. When exceeded, the least recently used
+    /// files are removed. When not set, the files are never removed.
+    #[serde(default)]
+    pub wasm_compilation_disk_cache_bytes: Option<u64>,
     /// When set, will limit the how many block heights in the past can the
-    /// storage be queried for reading values.
+    /// storage be queried for reading values. The subspace diffs and Merkle
+    /// tree stores that are older than this limit are pruned. When not set,
+    /// the node retains all the past states (archive mode).
     pub storage_read_past_height_limit: Option<u64>,
     /// Use the [`Ledger::db_dir()`] method to read the value.
     db_dir: PathBuf,
@@ -117,6 +147,24 @@ pub struct Shell {
     cometbft_dir: PathBuf,
     /// An optional action to take when a given blockheight is reached.
     pub action_at_height: Option<ActionAtHeight>,
+    /// When set, a state-sync snapshot is taken at the start of every epoch
+    /// that is a multiple of this interval. When not set, the node doesn't
+    /// take any snapshots.
+    #[serde(default)]
+    pub snapshot_epoch_interval: Option<u64>,
+    /// How many of the most recent snapshots are kept on disk. When not set,
+    /// defaults to 2.
+    #[serde(default)]
+    pub snapshots_to_keep: Option<u64>,
+    /// When set, the node serves websocket subscriptions to the storage key
+    /// changes of every block on this address.
+    #[serde(default)]
+    pub key_changes_ws_address: Option<SocketAddr>,
+    /// When set, the events emitted in the blocks that are older than this
+    /// many blocks are pruned from the persistent event store. When not set,
+    /// the node retains the events of all the blocks.
+    #[serde(default)]
+    pub events_retention_blocks: Option<u64>,
     /// Specify if tendermint is started as validator, fullnode or seednode
     pub tendermint_mode: TendermintMode,
 }
@@ -138,14 +186,20 @@ impl Ledger {
             chain_id,
             shell: Shell {
                 base_dir: base_dir.as_ref().to_owned(),
+                storage_backend: StorageBackend::default(),
                 block_cache_bytes: None,
                 vp_wasm_compilation_cache_bytes: None,
                 tx_wasm_compilation_cache_bytes: None,
+                wasm_compilation_disk_cache_bytes: None,
                 // Default corresponds to 1 hour of past blocks at 1 block/sec
                 storage_read_past_height_limit: Some(3600),
                 db_dir: DB_DIR.into(),
                 cometbft_dir: COMETBFT_DIR.into(),
                 action_at_height: None,
+                snapshot_epoch_interval: None,
+                snapshots_to_keep: None,
+                key_changes_ws_address: None,
+                events_retention_blocks: None,
                 tendermint_mode: mode,
             },
             cometbft: tendermint_config,
@@ -181,6 +235,21 @@ impl Shell {
             .join(chain_id.as_str())
             .join(&self.cometbft_dir)
     }
+
+    /// Get the directory path to the VP WASM compilation cache
+    pub fn vp_wasm_cache_dir(&self) -> PathBuf {
+        self.base_dir.join(WASM_CACHE_DIR).join("vp")
+    }
+
+    /// Get the directory path to the Tx WASM compilation cache
+    pub fn tx_wasm_cache_dir(&self) -> PathBuf {
+        self.base_dir.join(WASM_CACHE_DIR).join("tx")
+    }
+
+    /// Get the directory path to the state-sync snapshots
+    pub fn snapshots_dir(&self, chain_id: &ChainId) -> PathBuf {
+        self.base_dir.join(chain_id.as_str()).join(SNAPSHOTS_DIR)
+    }
 }
 
 #[derive(Error, Debug)]
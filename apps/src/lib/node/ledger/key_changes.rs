@@ -0,0 +1,226 @@
+//! A websocket endpoint to subscribe to the storage key changes of every
+//! block finalized by the ledger.
+//!
+//! A client registers the key prefixes it is interested in by sending a JSON
+//! message `{"prefixes": ["<key prefix>", ..]}` and then receives, for every
+//! new block with matching changes, a message with the changed keys and
+//! their new hex-encoded values (`null` for deleted keys). Sending another
+//! `prefixes` message replaces the previous subscription. Clients that
+//! connect late or fall behind can fetch the missed blocks with the
+//! `/shell/key_changes` query.
+
+use std::net::SocketAddr;
+use std::sync::Arc;
+
+use data_encoding::HEXLOWER;
+use futures::{SinkExt, StreamExt};
+use namada::ledger::key_changes::BlockKeyChanges;
+use namada::types::storage::Key;
+use serde::{Deserialize, Serialize};
+use tokio::sync::{broadcast, oneshot};
+use warp::ws::{Message, WebSocket};
+use warp::Filter;
+
+/// The path of the websocket endpoint.
+const KEY_CHANGES_ENDPOINT: &str = "key_changes";
+
+/// The number of blocks buffered for a subscriber before it lags behind.
+const CHANNEL_CAPACITY: usize = 16;
+
+/// The sending half of the channel over which the shell publishes the key
+/// changes of every finalized block.
+pub type Sender = broadcast::Sender<Arc<BlockKeyChanges>>;
+
+/// Create a new channel for publishing key changes.
+pub fn channel() -> Sender {
+    let (sender, _) = broadcast::channel(CHANNEL_CAPACITY);
+    sender
+}
+
+/// A subscription request sent by a client.
+#[derive(Debug, Deserialize)]
+struct Subscribe {
+    /// The key prefixes to receive the changes of
+    prefixes: Vec<String>,
+}
+
+/// A message sent to a client.
+#[derive(Debug, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum Notification {
+    /// The subscription was updated
+    Subscribed { prefixes: Vec<String> },
+    /// The matching changes of a block
+    Changes {
+        height: u64,
+        changes: Vec<KeyChange>,
+    },
+    /// The last request was invalid, or the client fell behind
+    Error { message: String },
+}
+
+/// A changed key with its new hex-encoded value, if any.
+#[derive(Debug, Serialize)]
+struct KeyChange {
+    key: String,
+    value: Option<String>,
+}
+
+impl From<BlockKeyChanges> for Notification {
+    fn from(changes: BlockKeyChanges) -> Self {
+        Self::Changes {
+            height: changes.height.0,
+            changes: changes
+                .changes
+                .into_iter()
+                .map(|change| KeyChange {
+                    key: change.key.to_string(),
+                    value: change.value.map(|value| HEXLOWER.encode(&value)),
+                })
+                .collect(),
+        }
+    }
+}
+
+/// Starts a [`warp::Server`] that accepts websocket subscriptions to the key
+/// changes published on `sender`. It shuts down if a signal is sent on the
+/// `abort_recv` channel.
+pub async fn serve(
+    listen_addr: SocketAddr,
+    sender: Sender,
+    abort_recv: oneshot::Receiver<()>,
+) {
+    let subscriptions = warp::path(KEY_CHANGES_ENDPOINT).and(warp::ws()).map(
+        move |ws: warp::ws::Ws| {
+            let receiver = sender.subscribe();
+            ws.on_upgrade(move |socket| subscription(socket, receiver))
+        },
+    );
+
+    let (_, future) = warp::serve(subscriptions).bind_with_graceful_shutdown(
+        listen_addr,
+        async move {
+            tracing::info!(
+                ?listen_addr,
+                "Starting to serve key change subscriptions"
+            );
+            if abort_recv.await.is_err() {
+                tracing::warn!(
+                    "Channel for receiving signal to abort was closed \
+                     abruptly, will abort now"
+                );
+            }
+            tracing::info!(
+                ?listen_addr,
+                "Stopping serving key change subscriptions"
+            );
+        },
+    );
+    future.await
+}
+
+/// Drive a single client subscription until the client disconnects or falls
+/// behind.
+async fn subscription(
+    socket: WebSocket,
+    mut receiver: broadcast::Receiver<Arc<BlockKeyChanges>>,
+) {
+    let (mut ws_sender, mut ws_receiver) = socket.split();
+    // Nothing is sent until the client subscribes to some prefixes
+    let mut prefixes: Vec<Key> = vec![];
+
+    loop {
+        let notification = tokio::select! {
+            msg = ws_receiver.next() => match msg {
+                Some(Ok(msg)) if msg.is_close() => break,
+                Some(Ok(msg)) => match msg.to_str() {
+                    Ok(text) => match parse_subscription(text) {
+                        Ok(new_prefixes) => {
+                            let subscribed = Notification::Subscribed {
+                                prefixes: new_prefixes
+                                    .iter()
+                                    .map(Key::to_string)
+                                    .collect(),
+                            };
+                            prefixes = new_prefixes;
+                            subscribed
+                        }
+                        Err(message) => Notification::Error { message },
+                    },
+                    // Pings and pongs are handled by warp
+                    Err(()) => continue,
+                },
+                Some(Err(err)) => {
+                    tracing::debug!(%err, "Key change subscriber error");
+                    break;
+                }
+                None => break,
+            },
+            changes = receiver.recv() => match changes {
+                Ok(changes) => match changes.filter_prefixes(&prefixes) {
+                    Some(matching) => Notification::from(matching),
+                    None => continue,
+                },
+                Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                    let message = format!(
+                        "The subscriber fell behind by {skipped} blocks, use \
+                         the key_changes query to catch up"
+                    );
+                    let _ = send(&mut ws_sender, Notification::Error {
+                        message,
+                    })
+                    .await;
+                    break;
+                }
+                Err(broadcast::error::RecvError::Closed) => break,
+            },
+        };
+        if send(&mut ws_sender, notification).await.is_err() {
+            break;
+        }
+    }
+    let _ = ws_sender.close().await;
+}
+
+/// Parse the key prefixes of a subscription request.
+fn parse_subscription(text: &str) -> Result<Vec<Key>, String> {
+    let Subscribe { prefixes } = serde_json::from_str(text)
+        .map_err(|err| format!("Invalid subscription request: {err}"))?;
+    prefixes
+        .iter()
+        .map(|prefix| {
+            Key::parse(prefix)
+                .map_err(|err| format!("Invalid key prefix {prefix}: {err}"))
+        })
+        .collect()
+}
+
+/// Send a JSON-encoded notification to a client.
+async fn send<S>(
+    ws_sender: &mut S,
+    notification: Notification,
+) -> Result<(), ()>
+where
+    S: futures::Sink<Message> + Unpin,
+{
+    let json = serde_json::to_string(&notification)
+        .expect("Serializing a notification shouldn't fail");
+    ws_sender.send(Message::text(json)).await.map_err(|_| ())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Test that the subscription requests are parsed into keys.
+    #[test]
+    fn test_parse_subscription() {
+        let key = Key::parse("balance").unwrap();
+        let prefixes =
+            parse_subscription(&format!(r#"{{"prefixes": ["{key}"]}}"#))
+                .unwrap();
+        assert_eq!(prefixes, vec![key]);
+
+        assert!(parse_subscription(r#"{"keys": []}"#).is_err());
+    }
+}
@@ -1,22 +1,32 @@
 mod abortable;
 mod broadcaster;
 pub mod ethereum_oracle;
+mod key_changes;
 pub mod shell;
 pub mod shims;
 pub mod storage;
 pub mod tendermint_node;
 
+use std::collections::BTreeMap;
 use std::convert::TryInto;
 use std::net::SocketAddr;
 use std::path::PathBuf;
 use std::str::FromStr;
 use std::thread;
 
+use borsh::BorshDeserialize;
 use byte_unit::Byte;
 use futures::future::TryFutureExt;
+use namada::core::ledger::gas::GasCostTable;
 use namada::core::ledger::governance::storage::keys as governance_storage;
 use namada::eth_bridge::ethers::providers::{Http, Provider};
-use namada::types::storage::Key;
+use namada::ledger::parameters;
+use namada::ledger::storage::{DBIter, DB};
+use namada::ledger::storage_api;
+use namada::types::hash::Hash;
+use namada::types::storage::{BlockHeight, Key};
+use namada::vm::wasm::{TxCache, VpCache};
+use namada::vm::WasmCacheRwAccess;
 use namada_sdk::tendermint::abci::request::CheckTxKind;
 use once_cell::unsync::Lazy;
 use sysinfo::{RefreshKind, System, SystemExt};
@@ -30,7 +40,7 @@ use self::shell::EthereumOracleChannels;
 use self::shims::abcipp_shim::AbciService;
 use crate::cli::args;
 use crate::config::utils::{convert_tm_addr_to_socket_addr, num_of_threads};
-use crate::config::{ethereum_bridge, TendermintMode};
+use crate::config::{ethereum_bridge, StorageBackend, TendermintMode};
 use crate::facade::tendermint::v0_37::abci::response;
 use crate::facade::tower_abci::{split, Server};
 use crate::node::ledger::broadcaster::Broadcaster;
@@ -62,7 +72,10 @@ const ENV_VAR_RAYON_THREADS: &str = "NAMADA_RAYON_THREADS";
 //         Poll::Ready(Ok(()))
 //     }
 //```
-impl Shell {
+impl<D> Shell<D>
+where
+    D: DB + for<'iter> DBIter<'iter> + Sync + 'static,
+{
     fn load_proposals(&mut self) {
         let proposals_key = governance_storage::get_commiting_proposals_prefix(
             self.wl_storage.storage.last_epoch.0,
@@ -147,16 +160,18 @@ impl Shell {
                 Ok(Response::CheckTx(self.mempool_validate(&tx.tx, r#type)))
             }
             Request::ListSnapshots => {
-                Ok(Response::ListSnapshots(Default::default()))
+                Ok(Response::ListSnapshots(self.list_snapshots()))
             }
-            Request::OfferSnapshot(_) => {
-                Ok(Response::OfferSnapshot(Default::default()))
+            Request::OfferSnapshot(req) => {
+                tracing::debug!("Request OfferSnapshot");
+                Ok(Response::OfferSnapshot(self.offer_snapshot(req)))
             }
-            Request::LoadSnapshotChunk(_) => {
-                Ok(Response::LoadSnapshotChunk(Default::default()))
+            Request::LoadSnapshotChunk(req) => {
+                Ok(Response::LoadSnapshotChunk(self.load_snapshot_chunk(req)))
             }
-            Request::ApplySnapshotChunk(_) => {
-                Ok(Response::ApplySnapshotChunk(Default::default()))
+            Request::ApplySnapshotChunk(req) => {
+                tracing::debug!("Request ApplySnapshotChunk");
+                Ok(Response::ApplySnapshotChunk(self.apply_snapshot_chunk(req)))
             }
         }
     }
@@ -214,18 +229,144 @@ pub fn dump_db(
         historic,
     }: args::LedgerDumpDb,
 ) {
-    use namada::ledger::storage::DB;
-
     let chain_id = config.chain_id;
     let db_path = config.shell.db_dir(&chain_id);
 
-    let db = storage::PersistentDB::open(db_path, None);
-    db.dump_block(out_file_path, historic, block_height);
+    match config.shell.storage_backend {
+        StorageBackend::RocksDB => {
+            let db = storage::PersistentDB::open(db_path, None);
+            db.dump_block(out_file_path, historic, block_height);
+        }
+        StorageBackend::Redb => {
+            let db = storage::PersistentRedbDB::open(db_path, None);
+            db.dump_block(out_file_path, historic, block_height);
+        }
+    }
+}
+
+/// Roll Namada state back to the previous height, or to the given height
+pub fn rollback(
+    config: config::Ledger,
+    args: args::LedgerRollBack,
+) -> Result<(), shell::Error> {
+    shell::rollback(config, args)
+}
+
+/// Export Namada ledger node's state at a given height into a file
+pub fn export_state(
+    config: config::Ledger,
+    args: args::LedgerExportState,
+) -> Result<(), shell::Error> {
+    shell::state_migration::export_state(config, args)
+}
+
+/// Prepare the initialization of a new chain from an exported state
+pub fn import_state(
+    config: config::Ledger,
+    args: args::LedgerImportState,
+) -> Result<(), shell::Error> {
+    shell::state_migration::import_state(config, args)
+}
+
+/// Compile the WASMs listed in the checksums file of the WASM directory into
+/// the node's compilation caches, so that the node doesn't have to compile
+/// them when they're first used. They're compiled with the gas costs in force
+/// at the next block of the chain.
+pub fn precompile_wasm(
+    config: config::Ledger,
+    wasm_dir: PathBuf,
+) -> Result<(), shell::Error> {
+    let chain_dir = config.shell.base_dir.join(config.chain_id.as_str());
+    shell::remove_chain_wasm_caches(&chain_dir);
+
+    let db_path = config.shell.db_dir(&config.chain_id);
+    let gas_costs = match config.shell.storage_backend {
+        StorageBackend::RocksDB => {
+            read_next_gas_costs(&storage::PersistentDB::open(db_path, None))
+        }
+        StorageBackend::Redb => {
+            read_next_gas_costs(&storage::PersistentRedbDB::open(db_path, None))
+        }
+    }?;
+
+    let disk_budget = config.shell.wasm_compilation_disk_cache_bytes;
+    // The modules only have to be written to files, so the in-memory caches
+    // are left empty
+    let mut vp_wasm_cache = VpCache::<WasmCacheRwAccess>::new_with_disk_budget(
+        config.shell.vp_wasm_cache_dir(),
+        1,
+        disk_budget,
+    );
+    let mut tx_wasm_cache = TxCache::<WasmCacheRwAccess>::new_with_disk_budget(
+        config.shell.tx_wasm_cache_dir(),
+        1,
+        disk_budget,
+    );
+    vp_wasm_cache.set_gas_costs(gas_costs);
+    tx_wasm_cache.set_gas_costs(gas_costs);
+
+    let checksums = wasm_loader::Checksums::read_checksums(&wasm_dir);
+    for (name, full_name) in checksums.0.iter() {
+        let code = wasm_loader::read_wasm(&wasm_dir, name)
+            .map_err(shell::Error::ReadingWasm)?;
+        let code_hash = Hash::sha256(&code);
+        let checksum = full_name.split('.').nth(1).ok_or_else(|| {
+            shell::Error::LoadingWasm(format!(
+                "invalid full name: {}",
+                full_name
+            ))
+        })?;
+        if code_hash.to_string() != checksum.to_uppercase() {
+            return Err(shell::Error::LoadingWasm(format!(
+                "Invalid wasm code sha256 hash for {}",
+                name
+            )));
+        }
+
+        let compiled = if name.starts_with("tx_") {
+            match tx_wasm_cache.fetch(&code_hash) {
+                Ok(Some(_)) => continue,
+                _ => tx_wasm_cache.compile_or_fetch(&code),
+            }
+        } else if name.starts_with("vp_") {
+            match vp_wasm_cache.fetch(&code_hash) {
+                Ok(Some(_)) => continue,
+                _ => vp_wasm_cache.compile_or_fetch(&code),
+            }
+        } else {
+            continue;
+        };
+        compiled.map_err(|err| {
+            shell::Error::LoadingWasm(format!(
+                "Failed to compile {}: {}",
+                name, err
+            ))
+        })?;
+        tracing::info!("Compiled {}.", name);
+    }
+    Ok(())
 }
 
-/// Roll Namada state back to the previous height
-pub fn rollback(config: config::Ledger) -> Result<(), shell::Error> {
-    shell::rollback(config)
+/// Read the gas costs in force at the next block of the chain. Before the
+/// chain is initialized, these are the default costs.
+fn read_next_gas_costs<D>(db: &D) -> Result<GasCostTable, shell::Error>
+where
+    D: DB + for<'iter> DBIter<'iter>,
+{
+    let storage_error =
+        |e| shell::Error::StorageApi(storage_api::Error::new(e));
+    let next_height = match db.read_last_block().map_err(storage_error)? {
+        Some(last_block) => last_block.height.next_height(),
+        None => return Ok(GasCostTable::default()),
+    };
+    let tables: BTreeMap<BlockHeight, GasCostTable> = db
+        .read_subspace_val(&parameters::storage::get_gas_cost_tables_key())
+        .map_err(storage_error)?
+        .map(|bytes| BTreeMap::try_from_slice(&bytes))
+        .transpose()
+        .map_err(|e| shell::Error::StorageApi(storage_api::Error::new(e)))?
+        .unwrap_or_default();
+    Ok(parameters::gas_cost_table_at(&tables, next_height))
 }
 
 /// Runs and monitors a few concurrent tasks.
@@ -259,24 +400,31 @@ async fn run_aux(config: config::Ledger, wasm_dir: PathBuf) {
             }
         };
 
-    // Start ABCI server and broadcaster (the latter only if we are a validator
-    // node)
-    let (abci, broadcaster, shell_handler) = start_abci_broadcaster_shell(
-        &mut spawner,
-        eth_oracle_channels,
-        wasm_dir,
-        setup_data,
-        config,
-    );
+    // Start ABCI server, broadcaster (only if we are a validator node) and
+    // key changes server (only if enabled)
+    let (abci, broadcaster, key_changes, shell_handler) =
+        start_abci_broadcaster_shell(
+            &mut spawner,
+            eth_oracle_channels,
+            wasm_dir,
+            setup_data,
+            config,
+        );
 
     // Wait for interrupt signal or abort message
     let aborted = spawner.wait_for_abort().await.child_terminated();
 
     // Wait for all managed tasks to finish.
-    let res = tokio::try_join!(tendermint_node, abci, eth_oracle, broadcaster);
+    let res = tokio::try_join!(
+        tendermint_node,
+        abci,
+        eth_oracle,
+        broadcaster,
+        key_changes
+    );
 
     match res {
-        Ok((tendermint_res, abci_res, _, _)) => {
+        Ok((tendermint_res, abci_res, _, _, _)) => {
             // we ignore errors on user-initiated shutdown
             if aborted {
                 if let Err(err) = tendermint_res {
@@ -378,7 +526,7 @@ async fn run_aux_setup(
             .get_appropriate_unit(true)
     );
 
-    // Find the RocksDB block cache size
+    // Find the DB block cache size
     let db_block_cache_size_bytes = match config.shell.block_cache_bytes {
         Some(block_cache_bytes) => {
             tracing::info!("Block cache set from the configuration.");
@@ -393,7 +541,7 @@ async fn run_aux_setup(
         }
     };
     tracing::info!(
-        "RocksDB block cache size: {}",
+        "DB block cache size: {}",
         Byte::from_bytes(db_block_cache_size_bytes as u128)
             .get_appropriate_unit(true)
     );
@@ -405,9 +553,9 @@ async fn run_aux_setup(
     }
 }
 
-/// This function spawns an ABCI server and a [`Broadcaster`] into the
-/// asynchronous runtime. Additionally, it executes a shell in
-/// a new OS thread, to drive the ABCI server.
+/// This function spawns an ABCI server, a [`Broadcaster`] and the key changes
+/// websocket server into the asynchronous runtime. Additionally, it executes a
+/// shell in a new OS thread, to drive the ABCI server.
 fn start_abci_broadcaster_shell(
     spawner: &mut AbortableSpawner,
     eth_oracle: Option<EthereumOracleChannels>,
@@ -417,6 +565,7 @@ fn start_abci_broadcaster_shell(
 ) -> (
     task::JoinHandle<shell::Result<()>>,
     task::JoinHandle<()>,
+    task::JoinHandle<()>,
     thread::JoinHandle<()>,
 ) {
     let rpc_address =
@@ -457,24 +606,70 @@ fn start_abci_broadcaster_shell(
         spawn_dummy_task(())
     };
 
-    // Setup DB cache, it must outlive the DB instance that's in the shell
-    let db_cache =
-        rocksdb::Cache::new_lru_cache(db_block_cache_size_bytes as usize);
+    // Start the key changes websocket server
+    let (key_changes_sender, key_changes) = match config
+        .shell
+        .key_changes_ws_address
+    {
+        Some(listen_addr) => {
+            let sender = key_changes::channel();
+            let server_sender = sender.clone();
+            let (kc_abort_send, kc_abort_recv) =
+                tokio::sync::oneshot::channel::<()>();
+            let handle = spawner
+                .spawn_abortable("Key changes", move |aborter| async move {
+                    key_changes::serve(
+                        listen_addr,
+                        server_sender,
+                        kc_abort_recv,
+                    )
+                    .await;
+                    tracing::info!("Key changes server is no longer running.");
+
+                    drop(aborter);
+                })
+                .with_cleanup(async move {
+                    let _ = kc_abort_send.send(());
+                });
+            (Some(sender), handle)
+        }
+        None => (None, spawn_dummy_task(())),
+    };
 
     // Construct our ABCI application.
-    let tendermint_mode = config.shell.tendermint_mode.clone();
     let proxy_app_address =
         convert_tm_addr_to_socket_addr(&config.cometbft.proxy_app);
 
-    let (shell, abci_service, service_handle) = AbcippShim::new(
-        config,
-        wasm_dir,
-        broadcaster_sender,
-        eth_oracle,
-        &db_cache,
-        vp_wasm_compilation_cache,
-        tx_wasm_compilation_cache,
-    );
+    let (abci_service, service_handle, shell_handler) =
+        match config.shell.storage_backend {
+            StorageBackend::RocksDB => {
+                // Setup DB cache, it must outlive the DB instance that's in
+                // the shell
+                let db_cache = rocksdb::Cache::new_lru_cache(
+                    db_block_cache_size_bytes as usize,
+                );
+                start_shell::<storage::PersistentDB>(
+                    config,
+                    wasm_dir,
+                    broadcaster_sender,
+                    key_changes_sender,
+                    eth_oracle,
+                    &db_cache,
+                    vp_wasm_compilation_cache,
+                    tx_wasm_compilation_cache,
+                )
+            }
+            StorageBackend::Redb => start_shell::<storage::PersistentRedbDB>(
+                config,
+                wasm_dir,
+                broadcaster_sender,
+                key_changes_sender,
+                eth_oracle,
+                &(db_block_cache_size_bytes as usize),
+                vp_wasm_compilation_cache,
+                tx_wasm_compilation_cache,
+            ),
+        };
 
     // Channel for signalling shut down to ABCI server
     let (abci_abort_send, abci_abort_recv) = tokio::sync::oneshot::channel();
@@ -497,6 +692,41 @@ fn start_abci_broadcaster_shell(
             let _ = abci_abort_send.send(());
         });
 
+    (abci, broadcaster, key_changes, shell_handler)
+}
+
+/// Construct the shell over the given storage backend and run it in a new OS
+/// thread. Returns the ABCI service that forwards requests to the shell.
+#[allow(clippy::too_many_arguments)]
+fn start_shell<D>(
+    config: config::Ledger,
+    wasm_dir: PathBuf,
+    broadcaster_sender: mpsc::UnboundedSender<Vec<u8>>,
+    key_changes_sender: Option<key_changes::Sender>,
+    eth_oracle: Option<EthereumOracleChannels>,
+    db_cache: &D::Cache,
+    vp_wasm_compilation_cache: u64,
+    tx_wasm_compilation_cache: u64,
+) -> (
+    AbciService,
+    tokio::sync::broadcast::Sender<()>,
+    thread::JoinHandle<()>,
+)
+where
+    D: DB + for<'iter> DBIter<'iter> + Send + Sync + 'static,
+{
+    let tendermint_mode = config.shell.tendermint_mode.clone();
+    let (shell, abci_service, service_handle) = AbcippShim::<D>::new(
+        config,
+        wasm_dir,
+        broadcaster_sender,
+        key_changes_sender,
+        eth_oracle,
+        db_cache,
+        vp_wasm_compilation_cache,
+        tx_wasm_compilation_cache,
+    );
+
     // Start the shell in a new OS thread
     let thread_builder = thread::Builder::new().name("ledger-shell".into());
     let shell_handler = thread_builder
@@ -514,7 +744,7 @@ fn start_abci_broadcaster_shell(
         })
         .expect("Must be able to start a thread for the shell");
 
-    (abci, broadcaster, shell_handler)
+    (abci_service, service_handle, shell_handler)
 }
 
 /// Runs the an asynchronous ABCI server with four sub-components for consensus,
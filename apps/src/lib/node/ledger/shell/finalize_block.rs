@@ -7,6 +7,7 @@ use namada::core::ledger::pgf::ADDRESS as pgf_address;
 use namada::ledger::events::EventType;
 use namada::ledger::gas::{GasMetering, TxGasMeter};
 use namada::ledger::parameters::storage as params_storage;
+use namada::ledger::pgf::utils::ProposalEvent;
 use namada::ledger::pos::{namada_proof_of_stake, staking_token_address};
 use namada::ledger::protocol;
 use namada::ledger::storage::wl_storage::WriteLogAndStorage;
@@ -26,6 +27,11 @@ use namada::types::transaction::protocol::{
 use namada::types::vote_extensions::ethereum_events::MultiSignedEthEvent;
 
 use super::governance::execute_governance_proposals;
+use super::pgf_fundings::pay_pgf_fundings;
+use super::scheduler::execute_scheduled_txs;
+use super::upgrades::{
+    check_scheduled_upgrades, remove_applied_upgrades, IMPLEMENTED_UPGRADES,
+};
 use super::*;
 use crate::facade::tendermint::abci::types::{Misbehavior, VoteInfo};
 use crate::node::ledger::shell::stats::InternalStats;
@@ -61,9 +67,22 @@ where
     ) -> Result<shim::response::FinalizeBlock> {
         let mut response = shim::response::FinalizeBlock::default();
 
+        // Halt the chain at the height of a software upgrade that this binary
+        // doesn't implement, before any change is made to the state
+        check_scheduled_upgrades(
+            &self.wl_storage,
+            self.wl_storage.storage.get_last_block_height() + 1,
+            IMPLEMENTED_UPGRADES,
+        )?;
+
+        let block_time = req.header.time;
         // Begin the new block and check if a new epoch has begun
         let (height, new_epoch) =
             self.update_state(req.header, req.hash, req.byzantine_validators);
+        self.update_gas_costs(height)?;
+        // The upgrades scheduled at this height are implemented by this
+        // binary, as checked above
+        remove_applied_upgrades(&mut self.wl_storage, height)?;
 
         let (current_epoch, _gas) = self.wl_storage.storage.get_current_epoch();
         let update_for_tendermint = matches!(
@@ -95,6 +114,10 @@ where
         if new_epoch {
             update_allowed_conversions(&mut self.wl_storage)?;
 
+            // The fundings written by older versions must be migrated before
+            // the proposals can remove them
+            pgf::migrate_legacy_payments(&mut self.wl_storage)?;
+
             execute_governance_proposals(self, &mut response)?;
 
             // Copy the new_epoch + pipeline_len - 1 validator set into
@@ -138,7 +161,7 @@ where
             // Invariant: Process slashes before inflation as they may affect
             // the rewards in the current epoch.
             self.process_slashes();
-            self.apply_inflation(current_epoch)?;
+            self.apply_inflation(current_epoch, height, &mut response)?;
         }
 
         // Consensus set liveness check
@@ -184,6 +207,9 @@ where
         }
 
         let mut stats = InternalStats::default();
+        // The gas limits of the wrappers of the block, which count towards
+        // the max block gas
+        let mut block_gas: u64 = 0;
 
         let native_block_proposer_address = {
             let tm_raw_hash_string =
@@ -272,6 +298,8 @@ where
                 match &tx_header.tx_type {
                     TxType::Wrapper(wrapper) => {
                         stats.increment_wrapper_txs();
+                        block_gas = block_gas
+                            .saturating_add(u64::from(wrapper.gas_limit));
                         let tx_event = Event::new_tx_event(&tx, height.0);
                         let gas_meter = TxGasMeter::new(wrapper.gas_limit);
                         (tx_event, None, gas_meter, Some(tx.clone()))
@@ -545,6 +573,17 @@ where
             response.events.push(tx_event);
         }
 
+        execute_scheduled_txs(
+            self,
+            &mut response,
+            height,
+            new_epoch.then_some(current_epoch),
+            block_time,
+            &native_block_proposer_address,
+            block_gas,
+            req.txs.len(),
+        );
+
         stats.set_tx_cache_size(
             self.tx_wasm_cache.get_size(),
             self.tx_wasm_cache.get_cache_size(),
@@ -569,7 +608,9 @@ where
             native_block_proposer_address,
         )?;
 
+        self.persist_events(height, &response.events);
         self.event_log_mut().log_events(response.events.clone());
+        self.log_key_changes(height);
         tracing::debug!("End finalize_block {height} of epoch {current_epoch}");
 
         Ok(response)
@@ -631,7 +672,12 @@ where
     /// account, then update the reward products of the validators. This is
     /// executed while finalizing the first block of a new epoch and is applied
     /// with respect to the previous epoch.
-    fn apply_inflation(&mut self, current_epoch: Epoch) -> Result<()> {
+    fn apply_inflation(
+        &mut self,
+        current_epoch: Epoch,
+        height: BlockHeight,
+        response: &mut shim::response::FinalizeBlock,
+    ) -> Result<()> {
         let last_epoch = current_epoch.prev();
         // Get input values needed for the PD controller for PoS.
         // Run the PD controllers to calculate new rates.
@@ -746,33 +792,13 @@ where
             pgf_inflation_amount.to_string_native()
         );
 
-        let mut pgf_fundings = pgf::get_payments(&self.wl_storage)?;
-        // we want to pay first the oldest fundings
-        pgf_fundings.sort_by(|a, b| a.id.cmp(&b.id));
-
-        for funding in pgf_fundings {
-            if storage_api::token::transfer(
-                &mut self.wl_storage,
-                &staking_token,
-                &pgf_address,
-                &funding.detail.target,
-                funding.detail.amount,
-            )
-            .is_ok()
-            {
-                tracing::info!(
-                    "Paying {} tokens for {} project.",
-                    funding.detail.amount.to_string_native(),
-                    &funding.detail.target,
-                );
-            } else {
-                tracing::warn!(
-                    "Failed to pay {} tokens for {} project.",
-                    funding.detail.amount.to_string_native(),
-                    &funding.detail.target,
-                );
-            }
-        }
+        pay_pgf_fundings(
+            &mut self.wl_storage,
+            response,
+            &staking_token,
+            current_epoch,
+            height,
+        )?;
 
         // Pgf steward inflation
         let stewards = pgf::get_stewards(&self.wl_storage)?;
@@ -788,26 +814,38 @@ where
                     .unwrap_or_default();
                 let reward_amount = token::Amount::from(pgf_steward_reward);
 
-                if credit_tokens(
+                let error = match credit_tokens(
                     &mut self.wl_storage,
                     &staking_token,
                     &address,
                     reward_amount,
-                )
-                .is_ok()
-                {
-                    tracing::info!(
-                        "Minting {} tokens for steward {}.",
-                        reward_amount.to_string_native(),
-                        address,
-                    );
-                } else {
-                    tracing::warn!(
-                        "Failed minting {} tokens for steward {}.",
-                        reward_amount.to_string_native(),
+                ) {
+                    Ok(()) => {
+                        tracing::info!(
+                            "Minting {} tokens for steward {}.",
+                            reward_amount.to_string_native(),
+                            address,
+                        );
+                        None
+                    }
+                    Err(err) => {
+                        tracing::warn!(
+                            "Failed minting {} tokens for steward {}: {}",
+                            reward_amount.to_string_native(),
+                            address,
+                            err,
+                        );
+                        Some(err.to_string())
+                    }
+                };
+                response.events.push(
+                    ProposalEvent::pgf_steward_payment(
                         address,
-                    );
-                }
+                        reward_amount,
+                        error,
+                    )
+                    .into(),
+                );
             }
         }
 
@@ -1,8 +1,10 @@
 use std::collections::HashMap;
 
+use namada::core::ledger::governance::cli::is_valid_parameter_change_data;
 use namada::core::ledger::governance::storage::keys as gov_storage;
 use namada::core::ledger::governance::storage::proposal::{
-    AddRemove, PGFAction, ProposalType, StoragePgfFunding,
+    AddRemove, PGFAction, ParameterChange, ProposalKind, ProposalType,
+    StoragePgfFunding,
 };
 use namada::core::ledger::governance::utils::{
     compute_proposal_result, ProposalVotes, TallyResult, TallyType, TallyVote,
@@ -14,17 +16,23 @@ use namada::core::ledger::pgf::storage::steward::StewardDetail;
 use namada::core::ledger::pgf::ADDRESS;
 use namada::core::ledger::storage_api::governance as gov_api;
 use namada::ledger::governance::utils::ProposalEvent;
+use namada::ledger::parameters::EpochDuration;
+use namada::ledger::pgf::utils::ProposalEvent as PgfPaymentEvent;
 use namada::ledger::pos::BondId;
 use namada::ledger::protocol;
 use namada::ledger::storage::types::encode;
 use namada::ledger::storage::{DBIter, StorageHasher, DB};
 use namada::ledger::storage_api::{pgf, token, StorageWrite};
 use namada::proof_of_stake::parameters::PosParams;
-use namada::proof_of_stake::{bond_amount, read_total_stake};
+use namada::proof_of_stake::{
+    bond_amount, find_delegation_validators, read_total_stake, write_pos_params,
+};
 use namada::proto::{Code, Data};
 use namada::types::address::Address;
+use namada::types::dec::Dec;
 use namada::types::storage::Epoch;
 
+use super::upgrades::execute_upgrade_proposal;
 use super::utils::force_read;
 use super::*;
 
@@ -46,12 +54,16 @@ where
 
     for id in std::mem::take(&mut shell.proposal_data) {
         let proposal_funds_key = gov_storage::get_funds_key(id);
+        let proposal_start_epoch_key =
+            gov_storage::get_voting_start_epoch_key(id);
         let proposal_end_epoch_key = gov_storage::get_voting_end_epoch_key(id);
         let proposal_type_key = gov_storage::get_proposal_type_key(id);
         let proposal_author_key = gov_storage::get_author_key(id);
 
         let funds: token::Amount =
             force_read(&shell.wl_storage, &proposal_funds_key)?;
+        let proposal_start_epoch: Epoch =
+            force_read(&shell.wl_storage, &proposal_start_epoch_key)?;
         let proposal_end_epoch: Epoch =
             force_read(&shell.wl_storage, &proposal_end_epoch_key)?;
         let proposal_type: ProposalType =
@@ -70,16 +82,23 @@ where
             &shell.wl_storage,
             &params,
             id,
+            proposal_type.kind(),
+            proposal_start_epoch,
             proposal_end_epoch,
         )?;
-        let proposal_result =
-            compute_proposal_result(votes, total_voting_power, tally_type);
+        let gov_params = gov_api::get_parameters(&shell.wl_storage)?;
+        let proposal_result = compute_proposal_result(
+            votes,
+            total_voting_power,
+            tally_type,
+            gov_params.veto_threshold,
+        );
         let proposal_result_key = gov_storage::get_proposal_result_key(id);
         shell
             .wl_storage
             .write(&proposal_result_key, proposal_result)?;
 
-        let transfer_address = match proposal_result.result {
+        let is_vetoed = match proposal_result.result {
             TallyResult::Passed => {
                 let proposal_event = match proposal_type {
                     ProposalType::Default(_) => {
@@ -125,6 +144,7 @@ where
                             &shell.wl_storage.get_native_token()?;
                         let result = execute_pgf_payment_proposal(
                             &mut shell.wl_storage,
+                            response,
                             native_token,
                             payments,
                             id,
@@ -138,14 +158,45 @@ where
                         ProposalEvent::pgf_payments_proposal_event(id, result)
                             .into()
                     }
+                    ProposalType::ParameterChange(changes) => {
+                        let result = execute_parameter_change_proposal(
+                            &mut shell.wl_storage,
+                            changes,
+                        )?;
+                        tracing::info!(
+                            "Governance proposal (parameter change) {} has \
+                             been executed ({}) and passed.",
+                            id,
+                            result
+                        );
+
+                        ProposalEvent::parameter_change_proposal_event(
+                            id, result,
+                        )
+                        .into()
+                    }
+                    ProposalType::Upgrade(plan) => {
+                        let result = execute_upgrade_proposal(
+                            &mut shell.wl_storage,
+                            id,
+                            plan,
+                        )?;
+                        tracing::info!(
+                            "Governance proposal (upgrade) {} has been \
+                             executed ({}) and passed.",
+                            id,
+                            result
+                        );
+
+                        ProposalEvent::upgrade_proposal_event(id, result).into()
+                    }
                 };
                 response.events.push(proposal_event);
                 proposals_result.passed.push(id);
 
-                let proposal_author_key = gov_storage::get_author_key(id);
-                shell.wl_storage.read::<Address>(&proposal_author_key)?
+                false
             }
-            TallyResult::Rejected => {
+            TallyResult::Rejected | TallyResult::Vetoed => {
                 if let ProposalType::PGFPayment(_) = proposal_type {
                     if proposal_result.two_thirds_nay_over_two_thirds_total() {
                         pgf::remove_steward(
@@ -163,34 +214,41 @@ where
                         );
                     }
                 }
-                let proposal_event =
-                    ProposalEvent::rejected_proposal_event(id).into();
+                let is_vetoed =
+                    matches!(proposal_result.result, TallyResult::Vetoed);
+                let proposal_event = if is_vetoed {
+                    ProposalEvent::vetoed_proposal_event(id).into()
+                } else {
+                    ProposalEvent::rejected_proposal_event(id).into()
+                };
                 response.events.push(proposal_event);
                 proposals_result.rejected.push(id);
 
                 tracing::info!(
-                    "Governance proposal {} has been executed and rejected.",
-                    id
+                    "Governance proposal {} has been executed and {}.",
+                    id,
+                    proposal_result.result
                 );
 
-                None
+                is_vetoed
             }
         };
 
+        // The deposits are refunded, unless the proposal was vetoed
         let native_token = shell.wl_storage.storage.native_token.clone();
-        if let Some(address) = transfer_address {
-            token::transfer(
+        if is_vetoed {
+            token::burn(
                 &mut shell.wl_storage,
                 &native_token,
                 &gov_address,
-                &address,
                 funds,
             )?;
         } else {
-            token::burn(
+            refund_proposal_deposits(
                 &mut shell.wl_storage,
                 &native_token,
-                &gov_address,
+                id,
+                &proposal_author,
                 funds,
             )?;
         }
@@ -199,10 +257,41 @@ where
     Ok(proposals_result)
 }
 
+/// Refund the deposits made on a proposal out of its funds. The funds not
+/// covered by a deposit go back to the author.
+fn refund_proposal_deposits<S>(
+    storage: &mut S,
+    native_token: &Address,
+    proposal_id: u64,
+    author: &Address,
+    funds: token::Amount,
+) -> storage_api::Result<()>
+where
+    S: StorageRead + StorageWrite,
+{
+    let mut refunded = token::Amount::zero();
+    for (depositor, deposit) in
+        gov_api::get_proposal_deposits(storage, proposal_id)?
+    {
+        token::transfer(
+            storage,
+            native_token,
+            &gov_address,
+            &depositor,
+            deposit,
+        )?;
+        refunded += deposit;
+    }
+    let remaining = funds.checked_sub(refunded).unwrap_or_default();
+    token::transfer(storage, native_token, &gov_address, author, remaining)
+}
+
 fn compute_proposal_votes<S>(
     storage: &S,
     params: &PosParams,
     proposal_id: u64,
+    proposal_kind: ProposalKind,
+    start_epoch: Epoch,
     epoch: Epoch,
 ) -> storage_api::Result<ProposalVotes>
 where
@@ -249,6 +338,35 @@ where
         }
     }
 
+    // The addresses that didn't vote follow the vote of the delegate they had
+    // when the voting period started with all their bonds, still overriding
+    // the votes of their validators
+    let delegated_votes = gov_api::get_delegated_votes(
+        storage,
+        proposal_id,
+        proposal_kind,
+        start_epoch,
+    )?;
+    for (delegator, (_delegate, vote_data)) in delegated_votes {
+        for validator in find_delegation_validators(storage, &delegator)? {
+            if validator == delegator {
+                continue;
+            }
+            let bond_id = BondId {
+                source: delegator.clone(),
+                validator: validator.clone(),
+            };
+            let delegator_stake =
+                bond_amount(storage, &bond_id, epoch).unwrap_or_default();
+
+            delegators_vote.insert(delegator.clone(), vote_data.clone().into());
+            delegator_voting_power
+                .entry(delegator.clone())
+                .or_default()
+                .insert(validator, delegator_stake);
+        }
+    }
+
     Ok(ProposalVotes {
         validators_vote,
         validator_voting_power,
@@ -340,8 +458,191 @@ where
     Ok(true)
 }
 
+/// Execute the changes of a parameter change proposal. Nothing is written if
+/// any of the new values is out of its range. The PoS and governance
+/// parameters are then updated in memory and nothing is written unless
+/// they're still consistent once all the changes are applied, nor if new gas
+/// costs would come in force at a past height.
+fn execute_parameter_change_proposal<S>(
+    storage: &mut S,
+    changes: Vec<ParameterChange>,
+) -> Result<bool>
+where
+    S: StorageRead + StorageWrite,
+{
+    if let Err(err) = is_valid_parameter_change_data(&changes) {
+        tracing::info!(
+            "The parameter changes are invalid, none of them are applied: {}",
+            err
+        );
+        return Ok(false);
+    }
+
+    let mut pos_params = read_pos_params(storage)?.owned;
+    let mut gov_params = gov_api::get_parameters(storage)?;
+    let mut is_pos_changed = false;
+    let mut is_gas_costs_consistent = true;
+    let current_height = storage.get_block_height()?;
+    for change in &changes {
+        match change {
+            ParameterChange::BlockProposerReward(value) => {
+                pos_params.block_proposer_reward = *value;
+                is_pos_changed = true;
+            }
+            ParameterChange::BlockVoteReward(value) => {
+                pos_params.block_vote_reward = *value;
+                is_pos_changed = true;
+            }
+            ParameterChange::MaxInflationRate(value) => {
+                pos_params.max_inflation_rate = *value;
+                is_pos_changed = true;
+            }
+            ParameterChange::TargetStakedRatio(value) => {
+                pos_params.target_staked_ratio = *value;
+                is_pos_changed = true;
+            }
+            ParameterChange::DuplicateVoteMinSlashRate(value) => {
+                pos_params.duplicate_vote_min_slash_rate = *value;
+                is_pos_changed = true;
+            }
+            ParameterChange::LightClientAttackMinSlashRate(value) => {
+                pos_params.light_client_attack_min_slash_rate = *value;
+                is_pos_changed = true;
+            }
+            ParameterChange::ValidatorStakeThreshold(value) => {
+                pos_params.validator_stake_threshold = *value;
+                is_pos_changed = true;
+            }
+            ParameterChange::LivenessThreshold(value) => {
+                pos_params.liveness_threshold = *value;
+                is_pos_changed = true;
+            }
+            ParameterChange::MinProposalVotingPeriod(value) => {
+                gov_params.min_proposal_voting_period = *value
+            }
+            ParameterChange::MaxProposalPeriod(value) => {
+                gov_params.max_proposal_period = *value
+            }
+            ParameterChange::MinProposalGraceEpochs(value) => {
+                gov_params.min_proposal_grace_epochs = *value
+            }
+            ParameterChange::GasCosts {
+                activation_height, ..
+            } => is_gas_costs_consistent = *activation_height > current_height,
+            _ => {}
+        }
+    }
+
+    let is_pos_consistent = pos_params.validate().is_empty()
+        && pos_params.block_proposer_reward + pos_params.block_vote_reward
+            <= Dec::one();
+    // Otherwise, no proposal could ever be submitted again
+    let is_gov_consistent = gov_params.min_proposal_voting_period
+        + gov_params.min_proposal_grace_epochs
+        <= gov_params.max_proposal_period;
+    if !is_pos_consistent || !is_gov_consistent {
+        tracing::info!(
+            "The parameter changes would make the PoS or governance \
+             parameters inconsistent, none of them are applied."
+        );
+        return Ok(false);
+    }
+    if !is_gas_costs_consistent {
+        tracing::info!(
+            "The new gas costs would come in force by the current block \
+             height {}, none of the parameter changes are applied.",
+            current_height
+        );
+        return Ok(false);
+    }
+
+    for change in changes {
+        match change {
+            ParameterChange::EpochDuration {
+                min_num_of_blocks,
+                min_duration,
+            } => parameters::update_epoch_parameter(
+                storage,
+                &EpochDuration {
+                    min_num_of_blocks,
+                    min_duration,
+                },
+            )?,
+            ParameterChange::MaxExpectedTimePerBlock(value) => {
+                parameters::update_max_expected_time_per_block_parameter(
+                    storage, &value,
+                )?
+            }
+            ParameterChange::VpWhitelist(value) => {
+                parameters::update_vp_whitelist_parameter(storage, value)?
+            }
+            ParameterChange::TxWhitelist(value) => {
+                parameters::update_tx_whitelist_parameter(storage, value)?
+            }
+            ParameterChange::EpochsPerYear(value) => {
+                parameters::update_epochs_per_year_parameter(storage, &value)?
+            }
+            ParameterChange::MaxSignaturesPerTransaction(value) => {
+                parameters::update_max_signature_per_tx(storage, value)?
+            }
+            ParameterChange::PosGainP(value) => {
+                parameters::update_pos_gain_p_parameter(storage, &value)?
+            }
+            ParameterChange::PosGainD(value) => {
+                parameters::update_pos_gain_d_parameter(storage, &value)?
+            }
+            ParameterChange::MinProposalFund(value) => storage
+                .write(&gov_storage::get_min_proposal_fund_key(), value)?,
+            ParameterChange::MaxProposalCodeSize(value) => storage
+                .write(&gov_storage::get_max_proposal_code_size_key(), value)?,
+            ParameterChange::MinProposalVotingPeriod(value) => storage.write(
+                &gov_storage::get_min_proposal_voting_period_key(),
+                value,
+            )?,
+            ParameterChange::MaxProposalPeriod(value) => storage
+                .write(&gov_storage::get_max_proposal_period_key(), value)?,
+            ParameterChange::MaxProposalContentSize(value) => storage
+                .write(&gov_storage::get_max_proposal_content_key(), value)?,
+            ParameterChange::MinProposalGraceEpochs(value) => storage.write(
+                &gov_storage::get_min_proposal_grace_epoch_key(),
+                value,
+            )?,
+            ParameterChange::VetoThreshold(value) => {
+                storage.write(&gov_storage::get_veto_threshold_key(), value)?
+            }
+            ParameterChange::PgfInflationRate(value) => storage
+                .write(&pgf_storage::get_pgf_inflation_rate_key(), value)?,
+            ParameterChange::StewardsInflationRate(value) => storage
+                .write(&pgf_storage::get_steward_inflation_rate_key(), value)?,
+            ParameterChange::GasCosts {
+                activation_height,
+                table,
+            } => parameters::update_gas_cost_table(
+                storage,
+                activation_height,
+                table,
+            )?,
+            // The PoS parameters are written at once below
+            ParameterChange::BlockProposerReward(_)
+            | ParameterChange::BlockVoteReward(_)
+            | ParameterChange::MaxInflationRate(_)
+            | ParameterChange::TargetStakedRatio(_)
+            | ParameterChange::DuplicateVoteMinSlashRate(_)
+            | ParameterChange::LightClientAttackMinSlashRate(_)
+            | ParameterChange::ValidatorStakeThreshold(_)
+            | ParameterChange::LivenessThreshold(_) => {}
+        }
+    }
+    if is_pos_changed {
+        write_pos_params(storage, &pos_params)?;
+    }
+
+    Ok(true)
+}
+
 fn execute_pgf_payment_proposal<S>(
     storage: &mut S,
+    response: &mut shim::response::FinalizeBlock,
     token: &Address,
     payments: Vec<PGFAction>,
     proposal_id: u64,
@@ -353,57 +654,174 @@ where
         match payment {
             PGFAction::Continuous(action) => match action {
                 AddRemove::Add(target) => {
+                    let funding_key = target.recipient.funding_key();
                     pgf_storage::fundings_handle().insert(
                         storage,
-                        target.target.clone(),
+                        funding_key.clone(),
                         StoragePgfFunding::new(target.clone(), proposal_id),
                     )?;
+                    // A funding set again by governance is resumed
+                    pgf::clear_payment_pauses(storage, &funding_key)?;
                     tracing::info!(
                         "Execute ContinousPgf from proposal id {}: set {} to \
                          {}.",
                         proposal_id,
                         target.amount.to_string_native(),
-                        target.target
+                        target.recipient
                     );
                 }
                 AddRemove::Remove(target) => {
+                    let funding_key = target.recipient.funding_key();
                     pgf_storage::fundings_handle()
-                        .remove(storage, &target.target)?;
+                        .remove(storage, &funding_key)?;
+                    pgf::clear_payment_pauses(storage, &funding_key)?;
                     tracing::info!(
-                        "Execute ContinousPgf from proposal id {}: set {} to \
-                         {}.",
+                        "Execute ContinousPgf from proposal id {}: removed \
+                         funding for {}.",
                         proposal_id,
-                        target.amount.to_string_native(),
-                        target.target
+                        target.recipient
                     );
                 }
             },
             PGFAction::Retro(target) => {
-                match token::transfer(
+                let error = match token::transfer(
                     storage,
                     token,
                     &ADDRESS,
                     &target.target,
                     target.amount,
                 ) {
-                    Ok(()) => tracing::info!(
-                        "Execute RetroPgf from proposal id {}: sent {} to {}.",
-                        proposal_id,
-                        target.amount.to_string_native(),
-                        target.target
-                    ),
-                    Err(e) => tracing::warn!(
-                        "Error in RetroPgf transfer from proposal id {}, \
-                         amount {} to {}: {}",
+                    Ok(()) => {
+                        tracing::info!(
+                            "Execute RetroPgf from proposal id {}: sent {} to \
+                             {}.",
+                            proposal_id,
+                            target.amount.to_string_native(),
+                            target.target
+                        );
+                        None
+                    }
+                    Err(e) => {
+                        tracing::warn!(
+                            "Error in RetroPgf transfer from proposal id {}, \
+                             amount {} to {}: {}",
+                            proposal_id,
+                            target.amount.to_string_native(),
+                            target.target,
+                            e
+                        );
+                        Some(e.to_string())
+                    }
+                };
+                response.events.push(
+                    PgfPaymentEvent::pgf_funding_payment(
                         proposal_id,
-                        target.amount.to_string_native(),
-                        target.target,
-                        e
-                    ),
-                }
+                        target.target.to_string(),
+                        target.amount,
+                        error,
+                    )
+                    .into(),
+                );
             }
         }
     }
 
     Ok(true)
 }
+
+#[cfg(test)]
+mod test_governance {
+    use namada::core::ledger::gas::GasCostTable;
+    use namada::core::ledger::governance::parameters::GovernanceParameters;
+    use namada::ledger::storage::testing::TestWlStorage;
+    use namada::proof_of_stake::parameters::OwnedPosParams;
+    use namada::types::storage::BlockHeight;
+
+    use super::*;
+
+    /// Test that the gas costs set by a parameter change proposal come in
+    /// force at their activation height, which can't be in the past
+    #[test]
+    fn test_gas_costs_parameter_change() {
+        let mut storage = TestWlStorage::default();
+        GovernanceParameters::default()
+            .init_storage(&mut storage)
+            .unwrap();
+        write_pos_params(&mut storage, &OwnedPosParams::default()).unwrap();
+        storage.storage.block.height = BlockHeight(10);
+
+        let table = GasCostTable {
+            wasm_instruction: 2,
+            ..GasCostTable::default()
+        };
+        let change = |height: u64| ParameterChange::GasCosts {
+            activation_height: BlockHeight(height),
+            table,
+        };
+
+        // An activation height that isn't after the current height is
+        // rejected without writing anything
+        let result =
+            execute_parameter_change_proposal(&mut storage, vec![change(10)])
+                .unwrap();
+        assert!(!result);
+        assert_eq!(
+            parameters::read_gas_cost_table(&storage, BlockHeight(20)).unwrap(),
+            GasCostTable::default()
+        );
+
+        let result =
+            execute_parameter_change_proposal(&mut storage, vec![change(12)])
+                .unwrap();
+        assert!(result);
+        assert_eq!(
+            parameters::read_gas_cost_table(&storage, BlockHeight(11)).unwrap(),
+            GasCostTable::default()
+        );
+        assert_eq!(
+            parameters::read_gas_cost_table(&storage, BlockHeight(12)).unwrap(),
+            table
+        );
+    }
+
+    /// Test that a parameter change proposal with a rate out of its range
+    /// isn't applied
+    #[test]
+    fn test_invalid_rate_parameter_change() {
+        let mut storage = TestWlStorage::default();
+        let gov_params = GovernanceParameters::default();
+        gov_params.init_storage(&mut storage).unwrap();
+        write_pos_params(&mut storage, &OwnedPosParams::default()).unwrap();
+
+        for change in [
+            ParameterChange::VetoThreshold(Dec::two()),
+            ParameterChange::PgfInflationRate(-Dec::one()),
+            ParameterChange::StewardsInflationRate(Dec::two()),
+        ] {
+            let result =
+                execute_parameter_change_proposal(&mut storage, vec![change])
+                    .unwrap();
+            assert!(!result);
+        }
+        assert_eq!(
+            gov_api::get_parameters(&storage).unwrap().veto_threshold,
+            gov_params.veto_threshold
+        );
+        assert!(
+            !storage
+                .has_key(&pgf_storage::get_pgf_inflation_rate_key())
+                .unwrap()
+        );
+
+        let result = execute_parameter_change_proposal(
+            &mut storage,
+            vec![ParameterChange::VetoThreshold(Dec::one())],
+        )
+        .unwrap();
+        assert!(result);
+        assert_eq!(
+            gov_api::get_parameters(&storage).unwrap().veto_threshold,
+            Dec::one()
+        );
+    }
+}
@@ -58,6 +58,25 @@ where
             )));
         }
 
+        // A chain migrated from an exported state is initialized from it
+        // instead of the genesis files
+        let imported_state_path = self
+            .base_dir
+            .join(chain_id)
+            .join(state_migration::IMPORTED_STATE_FILE);
+        if imported_state_path.exists() {
+            let initial_height = init
+                .initial_height
+                .try_into()
+                .expect("Unexpected block height");
+            self.init_chain_from_imported_state(
+                &imported_state_path,
+                initial_height,
+            )?;
+            response.validators = self.initial_validator_set();
+            return Ok(response);
+        }
+
         // Read the genesis files
         #[cfg(any(
             feature = "integration",
@@ -173,7 +192,14 @@ where
         ibc::init_genesis_storage(&mut self.wl_storage);
 
         // Set the initial validator set
-        response.validators = self
+        response.validators = self.initial_validator_set();
+        Ok(response)
+    }
+
+    /// Get the consensus validator set of the current epoch, to be sent to
+    /// CometBFT at the chain's initialization
+    pub(super) fn initial_validator_set(&self) -> Vec<validator::Update> {
+        let validators = self
             .get_abci_validator_updates(true, |pk, power| {
                 let pub_key: crate::facade::tendermint::PublicKey = pk.into();
                 let power =
@@ -182,8 +208,8 @@ where
                 validator::Update { pub_key, power }
             })
             .expect("Must be able to set genesis validator set");
-        debug_assert!(!response.validators.is_empty());
-        Ok(response)
+        debug_assert!(!validators.is_empty());
+        validators
     }
 
     /// Look-up WASM code of a genesis VP by its name
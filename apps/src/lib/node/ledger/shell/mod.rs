@@ -9,13 +9,18 @@ pub mod block_alloc;
 mod finalize_block;
 mod governance;
 mod init_chain;
+mod pgf_fundings;
 pub mod prepare_proposal;
 pub mod process_proposal;
 pub(super) mod queries;
+mod scheduler;
+mod snapshots;
+pub mod state_migration;
 mod stats;
 #[cfg(any(test, feature = "testing"))]
 #[allow(dead_code)]
 pub mod testing;
+mod upgrades;
 pub mod utils;
 mod vote_extensions;
 
@@ -25,15 +30,18 @@ use std::mem;
 use std::path::{Path, PathBuf};
 #[allow(unused_imports)]
 use std::rc::Rc;
+use std::sync::Arc;
 
 use borsh::BorshDeserialize;
 use borsh_ext::BorshSerializeExt;
 use masp_primitives::transaction::Transaction;
 use namada::core::hints;
 use namada::core::ledger::eth_bridge;
+use namada::core::ledger::governance::storage::proposal::UpgradePlan;
 use namada::ledger::events::log::EventLog;
 use namada::ledger::events::Event;
 use namada::ledger::gas::{Gas, TxGasMeter};
+use namada::ledger::key_changes::{BlockKeyChanges, KeyChangeLog};
 use namada::ledger::pos::into_tm_voting_power;
 use namada::ledger::pos::namada_proof_of_stake::types::{
     ConsensusValidator, ValidatorSetUpdate,
@@ -73,7 +81,10 @@ use thiserror::Error;
 use tokio::sync::mpsc::{Receiver, UnboundedSender};
 
 use super::ethereum_oracle::{self as oracle, last_processed_block};
-use crate::config::{self, genesis, TendermintMode, ValidatorLocalConfig};
+use crate::cli::args;
+use crate::config::{
+    self, genesis, StorageBackend, TendermintMode, ValidatorLocalConfig,
+};
 use crate::facade::tendermint::abci::types::{Misbehavior, MisbehaviorKind};
 use crate::facade::tendermint::v0_37::abci::{request, response};
 use crate::facade::tendermint::{self, validator};
@@ -81,7 +92,7 @@ use crate::facade::tendermint_proto::google::protobuf::Timestamp;
 use crate::facade::tendermint_proto::v0_37::crypto::public_key;
 use crate::node::ledger::shims::abcipp_shim_types::shim;
 use crate::node::ledger::shims::abcipp_shim_types::shim::response::TxResult;
-use crate::node::ledger::{storage, tendermint_node};
+use crate::node::ledger::{key_changes, storage, tendermint_node};
 use crate::wallet::{ValidatorData, ValidatorKeys};
 
 fn key_to_tendermint(
@@ -125,6 +136,13 @@ pub enum Error {
     StorageApi(#[from] storage_api::Error),
     #[error("Transaction replay attempt: {0}")]
     ReplayAttempt(String),
+    #[error("{0}")]
+    StateMigration(state_migration::StateMigrationError),
+    #[error(
+        "Upgrade required: the software upgrade {1} scheduled by proposal {0} \
+         is not implemented by this binary"
+    )]
+    UpgradeRequired(u64, UpgradePlan),
 }
 
 impl From<Error> for TxResult {
@@ -195,6 +213,24 @@ impl From<ErrorCodes> for crate::facade::tendermint::abci::Code {
 
 pub type Result<T> = std::result::Result<T, Error>;
 
+/// Remove the WASM compilation caches kept in the chain directory before they
+/// were shared by all the chains
+pub fn remove_chain_wasm_caches(chain_dir: &Path) {
+    for cache_dir in ["vp_wasm_cache", "tx_wasm_cache"] {
+        match std::fs::remove_dir_all(chain_dir.join(cache_dir)) {
+            Ok(()) => {
+                tracing::info!("Removed the old WASM cache {}", cache_dir)
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => (),
+            Err(e) => tracing::warn!(
+                "Failed to remove the old WASM cache {}: {}",
+                cache_dir,
+                e
+            ),
+        }
+    }
+}
+
 pub fn reset(config: config::Ledger) -> Result<()> {
     // simply nuke the DB files
     let db_path = &config.db_dir();
@@ -207,20 +243,81 @@ pub fn reset(config: config::Ledger) -> Result<()> {
     Ok(())
 }
 
-pub fn rollback(config: config::Ledger) -> Result<()> {
-    // Rollback Tendermint state
-    tracing::info!("Rollback Tendermint state");
-    let tendermint_block_height =
-        tendermint_node::rollback(config.cometbft_dir())
-            .map_err(Error::Tendermint)?;
-
-    // Rollback Namada state
+pub fn rollback(
+    config: config::Ledger,
+    args::LedgerRollBack { to_height, dry_run }: args::LedgerRollBack,
+) -> Result<()> {
     let db_path = config.shell.db_dir(&config.chain_id);
-    let mut db = storage::PersistentDB::open(db_path, None);
-    tracing::info!("Rollback Namada state");
+    match config.shell.storage_backend {
+        StorageBackend::RocksDB => rollback_db(
+            storage::PersistentDB::open(db_path, None),
+            &config,
+            to_height,
+            dry_run,
+        ),
+        StorageBackend::Redb => rollback_db(
+            storage::PersistentRedbDB::open(db_path, None),
+            &config,
+            to_height,
+            dry_run,
+        ),
+    }
+}
 
-    db.rollback(tendermint_block_height)
-        .map_err(|e| Error::StorageApi(storage_api::Error::new(e)))
+/// Roll back the Namada state together with the Tendermint state
+fn rollback_db<D>(
+    mut db: D,
+    config: &config::Ledger,
+    to_height: Option<BlockHeight>,
+    dry_run: bool,
+) -> Result<()>
+where
+    D: DB + storage::DBRollback,
+{
+    let storage_error = |e: namada::ledger::storage::Error| {
+        Error::StorageApi(storage_api::Error::new(e))
+    };
+    let plan = match to_height {
+        Some(target_height) => {
+            // Plan the rollback before touching the Tendermint state, so that
+            // a height that isn't retained is rejected
+            let plan =
+                db.plan_rollback(target_height).map_err(storage_error)?;
+            if dry_run {
+                return plan.print(&db).map_err(storage_error);
+            }
+            if !plan.is_empty() {
+                tracing::info!(
+                    "Rollback Tendermint state to height {target_height}"
+                );
+                // Tendermint may have stored one more block than the last one
+                // committed by Namada
+                let max_rollbacks =
+                    plan.last_height.0 - plan.target_height.0 + 1;
+                tendermint_node::rollback_to(
+                    config.cometbft_dir(),
+                    target_height,
+                    max_rollbacks,
+                )
+                .map_err(Error::Tendermint)?;
+            }
+            plan
+        }
+        None => {
+            tracing::info!("Rollback Tendermint state");
+            let tendermint_block_height =
+                tendermint_node::rollback(config.cometbft_dir())
+                    .map_err(Error::Tendermint)?;
+            db.plan_rollback(tendermint_block_height)
+                .map_err(storage_error)?
+        }
+    };
+    tracing::info!(
+        "Rollback Namada state from height {} to height {}",
+        plan.last_height,
+        plan.target_height
+    );
+    db.apply_rollback(plan).map_err(storage_error)
 }
 
 #[derive(Debug)]
@@ -400,6 +497,26 @@ where
     pub proposal_data: HashSet<u64>,
     /// Log of events emitted by `FinalizeBlock` ABCI calls.
     event_log: EventLog,
+    /// Log of storage keys changed by `FinalizeBlock` ABCI calls.
+    key_change_log: KeyChangeLog,
+    /// Notifies the key change subscribers of every finalized block, when
+    /// the key changes websocket endpoint is enabled.
+    key_changes_sender: Option<key_changes::Sender>,
+    /// Taken from config `events_retention_blocks`. When set, the events
+    /// older than this many blocks are pruned from the persistent event
+    /// store.
+    events_retention_blocks: Option<u64>,
+    /// Taken from config `snapshot_epoch_interval`. When set, a state-sync
+    /// snapshot is taken every this many epochs.
+    snapshot_epoch_interval: Option<u64>,
+    /// How many of the most recent snapshots are kept on disk
+    snapshots_to_keep: u64,
+    /// Path to the directory with the state-sync snapshots
+    snapshots_dir: PathBuf,
+    /// The snapshot being restored, if any
+    snapshot_restore: Option<snapshots::SnapshotRestore<H>>,
+    /// The background thread writing the last snapshot, if any
+    snapshot_task: Option<std::thread::JoinHandle<()>>,
 }
 
 /// Channels for communicating with an Ethereum oracle.
@@ -436,6 +553,7 @@ where
         config: config::Ledger,
         wasm_dir: PathBuf,
         broadcast_sender: UnboundedSender<Vec<u8>>,
+        key_changes_sender: Option<key_changes::Sender>,
         eth_oracle: Option<EthereumOracleChannels>,
         db_cache: Option<&D::Cache>,
         vp_wasm_compilation_cache: u64,
@@ -443,14 +561,25 @@ where
     ) -> Self {
         let chain_id = config.chain_id;
         let db_path = config.shell.db_dir(&chain_id);
+        let snapshots_dir = config.shell.snapshots_dir(&chain_id);
+        let vp_wasm_cache_dir = config.shell.vp_wasm_cache_dir();
+        let tx_wasm_cache_dir = config.shell.tx_wasm_cache_dir();
+        let wasm_disk_cache = config.shell.wasm_compilation_disk_cache_bytes;
         let base_dir = config.shell.base_dir;
         let mode = config.shell.tendermint_mode;
         let storage_read_past_height_limit =
             config.shell.storage_read_past_height_limit;
+        let events_retention_blocks = config.shell.events_retention_blocks;
+        let snapshot_epoch_interval = config.shell.snapshot_epoch_interval;
+        let snapshots_to_keep = config
+            .shell
+            .snapshots_to_keep
+            .unwrap_or(snapshots::DEFAULT_SNAPSHOTS_TO_KEEP);
         if !Path::new(&base_dir).is_dir() {
             std::fs::create_dir(&base_dir)
                 .expect("Creating directory for Namada should not fail");
         }
+        remove_chain_wasm_caches(&base_dir.join(chain_id.as_str()));
         let native_token = if cfg!(feature = "integration")
             || (!cfg!(test) && !cfg!(feature = "benches"))
         {
@@ -477,10 +606,6 @@ where
                 tracing::error!("Cannot load the last state from the DB {}", e);
             })
             .expect("PersistentStorage cannot be initialized");
-        let vp_wasm_cache_dir =
-            base_dir.join(chain_id.as_str()).join("vp_wasm_cache");
-        let tx_wasm_cache_dir =
-            base_dir.join(chain_id.as_str()).join("tx_wasm_cache");
         // load in keys and address from wallet if mode is set to `Validator`
         let mode = match mode {
             TendermintMode::Validator => {
@@ -556,24 +681,52 @@ where
             base_dir,
             wasm_dir,
             mode,
-            vp_wasm_cache: VpCache::new(
+            vp_wasm_cache: VpCache::new_with_disk_budget(
                 vp_wasm_cache_dir,
                 vp_wasm_compilation_cache as usize,
+                wasm_disk_cache,
             ),
-            tx_wasm_cache: TxCache::new(
+            tx_wasm_cache: TxCache::new_with_disk_budget(
                 tx_wasm_cache_dir,
                 tx_wasm_compilation_cache as usize,
+                wasm_disk_cache,
             ),
             storage_read_past_height_limit,
             proposal_data: HashSet::new(),
             // TODO: config event log params
             event_log: EventLog::default(),
+            key_change_log: KeyChangeLog::default(),
+            key_changes_sender,
+            events_retention_blocks,
+            snapshot_epoch_interval,
+            snapshots_to_keep,
+            snapshots_dir,
+            snapshot_restore: None,
+            snapshot_task: None,
         };
 
         shell.update_eth_oracle();
+        let next_height = shell
+            .wl_storage
+            .storage
+            .get_last_block_height()
+            .next_height();
+        // The costs are read again when the block is finalized
+        if let Err(e) = shell.update_gas_costs(next_height) {
+            tracing::error!("Failed to read the gas cost table: {}", e);
+        }
         shell
     }
 
+    /// Charge the gas costs in force at the given block height from now on,
+    /// so that the blocks are replayed with the costs they were executed with
+    fn update_gas_costs(&mut self, height: BlockHeight) -> Result<()> {
+        let gas_costs =
+            parameters::read_gas_cost_table(&self.wl_storage, height)?;
+        self.wl_storage.set_gas_costs(gas_costs);
+        Ok(())
+    }
+
     /// Return a reference to the [`EventLog`].
     #[inline]
     pub fn event_log(&self) -> &EventLog {
@@ -586,6 +739,38 @@ where
         &mut self.event_log
     }
 
+    /// Return a reference to the [`KeyChangeLog`].
+    #[inline]
+    pub fn key_change_log(&self) -> &KeyChangeLog {
+        &self.key_change_log
+    }
+
+    /// Log the storage keys changed in the block at the given height and
+    /// notify the subscribers, if any.
+    fn log_key_changes(&mut self, height: BlockHeight) {
+        let changes = BlockKeyChanges::new(
+            height,
+            self.wl_storage.write_log.get_block_changes(),
+        );
+        if let Some(sender) = &self.key_changes_sender {
+            // sending only fails when there are no subscribers
+            let _ = sender.send(Arc::new(changes.clone()));
+        }
+        self.key_change_log.log_changes(changes);
+    }
+
+    /// Set the events emitted in the block at the given height to be
+    /// persisted in the event store with the block, together with the pruning
+    /// of the ones that are no longer retained.
+    fn persist_events(&mut self, height: BlockHeight, events: &[Event]) {
+        let storage = &mut self.wl_storage.storage;
+        storage.block_events = events.iter().map(Event::to_indexed).collect();
+        storage.events_prune_below = self
+            .events_retention_blocks
+            .and_then(|retention| height.0.checked_sub(retention))
+            .map(BlockHeight);
+    }
+
     /// Iterate over the wrapper txs in order
     #[allow(dead_code)]
     fn iter_tx_queue(&mut self) -> impl Iterator<Item = &TxInQueue> {
@@ -824,6 +1009,8 @@ where
             retain_height: tendermint::block::Height::from(0_u32),
             ..Default::default()
         };
+        let is_new_epoch = self.wl_storage.storage.last_epoch
+            != self.wl_storage.storage.block.epoch;
         // commit block's data from write log and store the in DB
         self.wl_storage.commit_block().unwrap_or_else(|e| {
             tracing::error!(
@@ -833,13 +1020,25 @@ where
         });
 
         let root = self.wl_storage.storage.merkle_root();
+        let last_height = self.wl_storage.storage.get_last_block_height();
         tracing::info!(
             "Committed block hash: {}, height: {}",
             root,
-            self.wl_storage.storage.get_last_block_height(),
+            last_height,
         );
         response.data = root.0.to_vec().into();
 
+        // Charge the txs proposed for the next block with its gas costs. The
+        // costs are read again when the block is finalized.
+        if let Err(e) = self.update_gas_costs(last_height.next_height()) {
+            tracing::error!("Failed to read the gas cost table: {}", e);
+        }
+
+        // The Merkle tree stores are only fully persisted on a new epoch
+        if is_new_epoch {
+            self.maybe_take_snapshot();
+        }
+
         self.bump_last_processed_eth_block();
         self.broadcast_queued_txs();
 
@@ -1130,6 +1329,13 @@ where
             }
         }
 
+        // Tx memo check
+        if let Err(msg) = tx.validate_memo() {
+            response.code = ErrorCodes::InvalidTx.into();
+            response.log = format!("{INVALID_MSG}: {msg}");
+            return response;
+        }
+
         // Tx signature check
         let tx_type = match tx.validate_tx() {
             Ok(_) => tx.header(),
@@ -1249,7 +1455,13 @@ where
             TxType::Wrapper(wrapper) => {
                 // Tx gas limit
                 let mut gas_meter = TxGasMeter::new(wrapper.gas_limit);
-                if gas_meter.add_wrapper_gas(tx_bytes).is_err() {
+                if gas_meter
+                    .add_wrapper_gas(
+                        tx_bytes,
+                        &self.wl_storage.storage.gas_costs,
+                    )
+                    .is_err()
+                {
                     response.code = ErrorCodes::TxGasLimit.into();
                     response.log = "{INVALID_MSG}: Wrapper transactions \
                                     exceeds its gas limit"
@@ -1312,6 +1524,7 @@ where
                 // Validate wrapper fees
                 if let Err(e) = self.wrapper_fee_check(
                     &wrapper,
+                    tx.fee_granter().as_ref(),
                     get_fee_unshielding_transaction(&tx, &wrapper),
                     &mut TempWlStorage::new(&self.wl_storage.storage),
                     &mut self.vp_wasm_cache.clone(),
@@ -1351,6 +1564,7 @@ where
     pub fn wrapper_fee_check<CA>(
         &self,
         wrapper: &WrapperTx,
+        fee_granter: Option<&Address>,
         masp_transaction: Option<Transaction>,
         temp_wl_storage: &mut TempWlStorage<D, H>,
         vp_wasm_cache: &mut VpCache<CA>,
@@ -1480,10 +1694,13 @@ where
         }
 
         let result = match block_proposer {
-            Some(proposer) => {
-                protocol::transfer_fee(temp_wl_storage, proposer, wrapper)
-            }
-            None => protocol::check_fees(temp_wl_storage, wrapper),
+            Some(proposer) => protocol::transfer_fee(
+                temp_wl_storage,
+                proposer,
+                wrapper,
+                fee_granter,
+            ),
+            None => protocol::check_fees(temp_wl_storage, wrapper, fee_granter),
         };
 
         result.map_err(Error::TxApply)
@@ -1742,6 +1959,7 @@ mod test_utils {
                 ),
                 top_level_directory().join("wasm"),
                 sender,
+                None,
                 Some(eth_oracle),
                 None,
                 vp_wasm_compilation_cache,
@@ -2071,6 +2289,7 @@ mod test_utils {
             ),
             top_level_directory().join("wasm"),
             sender.clone(),
+            None,
             Some(eth_oracle),
             None,
             vp_wasm_compilation_cache,
@@ -2204,6 +2423,7 @@ mod test_utils {
             ),
             top_level_directory().join("wasm"),
             sender,
+            None,
             Some(eth_oracle),
             None,
             vp_wasm_compilation_cache,
@@ -0,0 +1,334 @@
+//! Payment of the continous PGF fundings at the start of each epoch
+
+use namada::core::ledger::governance::storage::proposal::PGFRecipient;
+use namada::core::ledger::pgf::storage::keys as pgf_storage;
+use namada::core::ledger::pgf::ADDRESS as pgf_address;
+use namada::ledger::ibc::transfer_over_ibc;
+use namada::ledger::pgf::utils::ProposalEvent;
+use namada::ledger::storage_api::{pgf, StorageWrite};
+use namada::types::ibc::IbcEvent;
+use namada::types::storage::Epoch;
+
+use super::*;
+
+/// Pay `amount` of `token` from the PGF account to a recipient. A recipient
+/// on another chain is paid with an ICS-20 transfer, whose IBC events are
+/// returned.
+pub fn pay_pgf_recipient<D, H>(
+    wl_storage: &mut WlStorage<D, H>,
+    token: &Address,
+    recipient: &PGFRecipient,
+    amount: token::Amount,
+) -> storage_api::Result<BTreeSet<IbcEvent>>
+where
+    D: DB + for<'iter> DBIter<'iter> + Sync + 'static,
+    H: StorageHasher + Sync + 'static,
+{
+    match recipient {
+        PGFRecipient::Internal(target) => {
+            storage_api::token::transfer(
+                wl_storage,
+                token,
+                &pgf_address,
+                target,
+                amount,
+            )?;
+            Ok(BTreeSet::new())
+        }
+        PGFRecipient::Ibc(target) => {
+            transfer_over_ibc(wl_storage, token, &pgf_address, target, amount)
+        }
+    }
+}
+
+/// Pay the continous PGF fundings due in the current epoch, the oldest
+/// fundings first.
+///
+/// The fundings that ran past their end epoch or paid their cap are removed
+/// together with their pauses. The fundings paused by a steward are skipped.
+/// A `PgfPayment` event is emitted for every payment, successful or not, and
+/// only a successful payment counts towards the cap of a funding.
+pub fn pay_pgf_fundings<D, H>(
+    wl_storage: &mut WlStorage<D, H>,
+    response: &mut shim::response::FinalizeBlock,
+    token: &Address,
+    current_epoch: Epoch,
+    height: BlockHeight,
+) -> Result<()>
+where
+    D: DB + for<'iter> DBIter<'iter> + Sync + 'static,
+    H: StorageHasher + Sync + 'static,
+{
+    let mut pgf_fundings = pgf::get_payments(wl_storage)?;
+    // we want to pay first the oldest fundings
+    pgf_fundings.sort_by(|a, b| a.id.cmp(&b.id));
+
+    for mut funding in pgf_fundings {
+        let funding_key = funding.detail.recipient.funding_key();
+        if funding.is_expired(current_epoch) {
+            remove_pgf_funding(wl_storage, &funding_key)?;
+            tracing::info!(
+                "PGF funding for {} from proposal id {} has expired after \
+                 paying {} tokens.",
+                funding.detail.recipient,
+                funding.id,
+                funding.paid.to_string_native(),
+            );
+            continue;
+        }
+        if pgf::is_payment_paused(wl_storage, &funding_key)? {
+            tracing::info!(
+                "Skipping the paused PGF funding for {} from proposal id {}.",
+                funding.detail.recipient,
+                funding.id,
+            );
+            continue;
+        }
+        let amount = funding.due_at(current_epoch);
+        if amount.is_zero() {
+            continue;
+        }
+
+        let error = match pay_pgf_recipient(
+            wl_storage,
+            token,
+            &funding.detail.recipient,
+            amount,
+        ) {
+            Ok(ibc_events) => {
+                tracing::info!(
+                    "Paying {} tokens for {} project.",
+                    amount.to_string_native(),
+                    funding.detail.recipient,
+                );
+                for ibc_event in ibc_events {
+                    let mut event = Event::from(ibc_event);
+                    // Add the height for IBC event query
+                    event["height"] = height.to_string();
+                    response.events.push(event);
+                }
+                funding.paid = funding
+                    .paid
+                    .checked_add(amount)
+                    .unwrap_or_else(token::Amount::max);
+                // Don't keep a funding that has nothing left to pay
+                if funding.is_expired(current_epoch.next()) {
+                    remove_pgf_funding(wl_storage, &funding_key)?;
+                } else {
+                    pgf_storage::fundings_handle().insert(
+                        wl_storage,
+                        funding_key,
+                        funding.clone(),
+                    )?;
+                }
+                None
+            }
+            Err(err) => {
+                tracing::warn!(
+                    "Failed to pay {} tokens for {} project: {}",
+                    amount.to_string_native(),
+                    funding.detail.recipient,
+                    err,
+                );
+                Some(err.to_string())
+            }
+        };
+        response.events.push(
+            ProposalEvent::pgf_funding_payment(
+                funding.id,
+                funding.detail.recipient.to_string(),
+                amount,
+                error,
+            )
+            .into(),
+        );
+    }
+
+    Ok(())
+}
+
+/// Remove a continous PGF funding and the pauses set on it by the stewards
+fn remove_pgf_funding<S>(storage: &mut S, funding_key: &str) -> Result<()>
+where
+    S: StorageRead + StorageWrite,
+{
+    pgf_storage::fundings_handle().remove(storage, &funding_key.to_owned())?;
+    pgf::clear_payment_pauses(storage, funding_key)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod test_pgf_fundings {
+    use namada::core::ledger::governance::storage::proposal::{
+        LegacyStoragePgfFunding, PGFContinuous, PGFTarget, StoragePgfFunding,
+    };
+    use namada::ledger::events::EventType;
+    use namada::ledger::storage::testing::TestWlStorage;
+    use namada::ledger::storage_api::collections::{
+        lazy_map, LazyCollection, LazyMap,
+    };
+    use namada::ledger::storage_api::token::{credit_tokens, read_balance};
+    use namada::types::address::nam;
+    use namada::types::address::testing::{
+        established_address_1, established_address_2, established_address_3,
+    };
+
+    use super::*;
+
+    /// Test that a continous funding is paid from its start epoch until its
+    /// cap, except while it's paused by a steward
+    #[test]
+    fn test_pay_pgf_fundings() {
+        let mut storage = TestWlStorage::default();
+        let mut response = shim::response::FinalizeBlock::default();
+        let token = nam();
+        let recipient = established_address_1();
+        let steward = established_address_2();
+        credit_tokens(
+            &mut storage,
+            &token,
+            &pgf_address,
+            token::Amount::from_u64(1000),
+        )
+        .unwrap();
+
+        let funding = PGFContinuous {
+            recipient: PGFRecipient::Internal(recipient.clone()),
+            amount: token::Amount::from_u64(40),
+            start_epoch: Epoch(1),
+            end_epoch: None,
+            cap: Some(token::Amount::from_u64(100)),
+        };
+        let funding_key = funding.recipient.funding_key();
+        pgf_storage::fundings_handle()
+            .insert(
+                &mut storage,
+                funding_key.clone(),
+                StoragePgfFunding::new(funding, 0),
+            )
+            .unwrap();
+
+        let mut pay_and_check =
+            |storage: &mut TestWlStorage, epoch: u64, expected_balance: u64| {
+                pay_pgf_fundings(
+                    storage,
+                    &mut response,
+                    &token,
+                    Epoch(epoch),
+                    BlockHeight(1),
+                )
+                .unwrap();
+                let balance =
+                    read_balance(storage, &token, &recipient).unwrap();
+                assert_eq!(balance, token::Amount::from_u64(expected_balance));
+            };
+
+        // Nothing is paid before the start epoch
+        pay_and_check(&mut storage, 0, 0);
+        pay_and_check(&mut storage, 1, 40);
+
+        // A paused funding is skipped until governance resumes it
+        pgf::pause_payment(&mut storage, &steward, &funding_key).unwrap();
+        pay_and_check(&mut storage, 2, 40);
+        pgf::clear_payment_pauses(&mut storage, &funding_key).unwrap();
+        pay_and_check(&mut storage, 3, 80);
+
+        // The last payment is capped and the funding is then removed
+        pay_and_check(&mut storage, 4, 100);
+        assert!(pgf::get_payment(&storage, &funding_key).unwrap().is_none());
+        pay_and_check(&mut storage, 5, 100);
+
+        let payment_events = response
+            .events
+            .iter()
+            .filter(|event| event.event_type == EventType::PgfPayment)
+            .count();
+        assert_eq!(payment_events, 3);
+    }
+
+    /// Test that the fundings stored by older versions are migrated and paid
+    /// every epoch, and that an undecodable funding doesn't prevent the
+    /// payment of the others
+    #[test]
+    fn test_pay_legacy_pgf_fundings() {
+        let mut storage = TestWlStorage::default();
+        let mut response = shim::response::FinalizeBlock::default();
+        let token = nam();
+        let target = established_address_1();
+        let corrupted = established_address_2();
+        let recipient = established_address_3();
+        credit_tokens(
+            &mut storage,
+            &token,
+            &pgf_address,
+            token::Amount::from_u64(1000),
+        )
+        .unwrap();
+
+        let legacy_fundings: LazyMap<Address, LegacyStoragePgfFunding> =
+            LazyMap::open(pgf_storage::fundings_key_prefix());
+        legacy_fundings
+            .insert(
+                &mut storage,
+                target.clone(),
+                LegacyStoragePgfFunding {
+                    detail: PGFTarget {
+                        target: target.clone(),
+                        amount: token::Amount::from_u64(10),
+                    },
+                    id: 0,
+                },
+            )
+            .unwrap();
+        let corrupted_key = pgf_storage::fundings_key_prefix()
+            .push(&lazy_map::DATA_SUBKEY.to_owned())
+            .unwrap()
+            .push(&corrupted)
+            .unwrap();
+        storage.write_bytes(&corrupted_key, [0xff]).unwrap();
+        let funding = PGFContinuous {
+            recipient: PGFRecipient::Internal(recipient.clone()),
+            amount: token::Amount::from_u64(20),
+            start_epoch: Epoch(0),
+            end_epoch: None,
+            cap: None,
+        };
+        pgf_storage::fundings_handle()
+            .insert(
+                &mut storage,
+                funding.recipient.funding_key(),
+                StoragePgfFunding::new(funding, 1),
+            )
+            .unwrap();
+
+        pgf::migrate_legacy_payments(&mut storage).unwrap();
+        assert!(legacy_fundings.get(&storage, &target).unwrap().is_none());
+        let migrated = pgf::get_payment(&storage, &target.encode())
+            .unwrap()
+            .expect("The legacy funding must be migrated");
+        assert_eq!(migrated.detail.start_epoch, Epoch(0));
+        assert_eq!(migrated.detail.end_epoch, None);
+        assert_eq!(migrated.detail.cap, None);
+        // The undecodable funding is left as it is
+        assert!(storage.has_key(&corrupted_key).unwrap());
+
+        for epoch in 1..=2 {
+            pay_pgf_fundings(
+                &mut storage,
+                &mut response,
+                &token,
+                Epoch(epoch),
+                BlockHeight(1),
+            )
+            .unwrap();
+        }
+        let target_balance = read_balance(&storage, &token, &target).unwrap();
+        assert_eq!(target_balance, token::Amount::from_u64(20));
+        let recipient_balance =
+            read_balance(&storage, &token, &recipient).unwrap();
+        assert_eq!(recipient_balance, token::Amount::from_u64(40));
+        let corrupted_balance =
+            read_balance(&storage, &token, &corrupted).unwrap();
+        assert!(corrupted_balance.is_zero());
+    }
+}
@@ -224,7 +224,9 @@ where
         if let TxType::Wrapper(wrapper) = tx.header().tx_type {
             // Check tx gas limit for tx size
             let mut tx_gas_meter = TxGasMeter::new(wrapper.gas_limit);
-            tx_gas_meter.add_wrapper_gas(tx_bytes).map_err(|_| ())?;
+            tx_gas_meter
+                .add_wrapper_gas(tx_bytes, &temp_wl_storage.storage.gas_costs)
+                .map_err(|_| ())?;
 
             self.replay_protection_checks(&tx, temp_wl_storage)
                 .map_err(|_| ())?;
@@ -232,6 +234,7 @@ where
             // Check fees
             match self.wrapper_fee_check(
                 &wrapper,
+                tx.fee_granter().as_ref(),
                 get_fee_unshielding_transaction(&tx, &wrapper),
                 temp_wl_storage,
                 vp_wasm_cache,
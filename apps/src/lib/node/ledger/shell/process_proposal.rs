@@ -335,6 +335,12 @@ where
                 info: err.to_string(),
             };
         }
+        if let Err(err) = tx.validate_memo() {
+            return TxResult {
+                code: ErrorCodes::InvalidTx.into(),
+                info: err.to_string(),
+            };
+        }
         match tx.header().tx_type {
             // If it is a raw transaction, we do no further validation
             TxType::Raw => TxResult {
@@ -568,7 +574,13 @@ where
                 // valid transaction and avoid wasting block
                 // resources (ABCI only)
                 let mut tx_gas_meter = TxGasMeter::new(wrapper.gas_limit);
-                if tx_gas_meter.add_wrapper_gas(tx_bytes).is_err() {
+                if tx_gas_meter
+                    .add_wrapper_gas(
+                        tx_bytes,
+                        &temp_wl_storage.storage.gas_costs,
+                    )
+                    .is_err()
+                {
                     // Account for the tx's resources even in case of an error.
                     // Ignore any allocation error
                     let _ = metadata
@@ -648,6 +660,7 @@ where
                 // Check that the fee payer has sufficient balance.
                 match self.wrapper_fee_check(
                     &wrapper,
+                    tx.fee_granter().as_ref(),
                     get_fee_unshielding_transaction(&tx, &wrapper),
                     temp_wl_storage,
                     vp_wasm_cache,
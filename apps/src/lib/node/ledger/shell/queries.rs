@@ -1,8 +1,8 @@
 //! Shell methods for querying state
 
-use namada::ledger::dry_run_tx;
 use namada::ledger::queries::{RequestCtx, ResponseQuery};
 use namada::ledger::storage_api::token;
+use namada::ledger::{dry_run_tx, estimate_fee, simulate_tx};
 use namada::types::address::Address;
 
 use super::*;
@@ -21,6 +21,7 @@ where
         let ctx = RequestCtx {
             wl_storage: &self.wl_storage,
             event_log: self.event_log(),
+            key_change_log: self.key_change_log(),
             vp_wasm_cache: self.vp_wasm_cache.read_only(),
             tx_wasm_cache: self.tx_wasm_cache.read_only(),
             storage_read_past_height_limit: self.storage_read_past_height_limit,
@@ -29,6 +30,10 @@ where
         // Invoke the root RPC handler - returns borsh-encoded data on success
         let result = if query.path == "/shell/dry_run_tx" {
             dry_run_tx(ctx, &query)
+        } else if query.path == "/shell/simulate_tx" {
+            simulate_tx(ctx, &query)
+        } else if query.path == "/shell/estimate_fee" {
+            estimate_fee(ctx, &query)
         } else {
             namada::ledger::queries::handle_path(ctx, &query)
         };
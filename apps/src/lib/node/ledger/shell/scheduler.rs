@@ -0,0 +1,341 @@
+//! Execution of the scheduled txs that are due in a block
+
+use namada::ledger::gas::{get_max_block_gas, GasMetering};
+use namada::ledger::storage_api::scheduler as scheduler_api;
+use namada::types::storage::Epoch;
+use namada::types::transaction::scheduler::Schedule;
+
+use super::*;
+
+/// Execute the txs scheduled at the given block height and, if a new epoch
+/// has just started, at the given epoch, in the order they were scheduled.
+///
+/// The scheduled txs share the max block gas with the txs of the block, which
+/// used the given amount of gas. A due tx whose gas limit exceeds the gas left
+/// in the block is postponed to the next block height.
+///
+/// The scheduled txs are indexed after the `block_txs` txs of the block, so
+/// that their MASP transfers don't collide with the ones of the block's txs.
+/// Every executed tx is removed from storage beforehand, regardless of the
+/// outcome, and gets the gas limit it was scheduled with. The fees for the gas
+/// used are paid from the escrow to the block proposer and the rest of the
+/// escrow is refunded to the owner. A scheduled tx whose storage can't be
+/// handled is logged and skipped, so that it can't halt the chain.
+pub fn execute_scheduled_txs<D, H>(
+    shell: &mut Shell<D, H>,
+    response: &mut shim::response::FinalizeBlock,
+    height: BlockHeight,
+    new_epoch: Option<Epoch>,
+    block_time: DateTimeUtc,
+    block_proposer: &Address,
+    block_gas: u64,
+    block_txs: usize,
+) where
+    D: DB + for<'iter> DBIter<'iter> + Sync + 'static,
+    H: StorageHasher + Sync + 'static,
+{
+    let due_txs = match scheduler_api::due_scheduled_txs(
+        &shell.wl_storage,
+        height,
+        new_epoch,
+    ) {
+        Ok(due_txs) => due_txs,
+        Err(err) => {
+            tracing::error!("Failed to read the due scheduled txs: {err}");
+            return;
+        }
+    };
+    let max_block_gas = match get_max_block_gas(&shell.wl_storage) {
+        Ok(max_block_gas) => max_block_gas,
+        Err(err) => {
+            tracing::error!("Failed to read the max block gas: {err}");
+            return;
+        }
+    };
+    let mut gas_left = max_block_gas.saturating_sub(block_gas);
+
+    for (position, id) in due_txs.into_iter().enumerate() {
+        let tx_index = TxIndex(
+            (block_txs + position)
+                .try_into()
+                .expect("transaction index out of bounds"),
+        );
+        let scheduled =
+            match scheduler_api::read_scheduled_tx(&shell.wl_storage, id) {
+                Ok(Some(scheduled)) => scheduled,
+                Ok(None) => continue,
+                Err(err) => {
+                    tracing::error!("Failed to read scheduled tx {id}: {err}");
+                    continue;
+                }
+            };
+        if u64::from(scheduled.gas_limit) > gas_left {
+            match scheduler_api::reschedule_tx(
+                &mut shell.wl_storage,
+                id,
+                Schedule::AtHeight(height.next_height()),
+            ) {
+                Ok(()) => {
+                    tracing::info!(
+                        "Postponed scheduled tx {id} to the next block for \
+                         lack of gas"
+                    );
+                    shell.wl_storage.commit_tx();
+                }
+                Err(err) => {
+                    tracing::error!(
+                        "Failed to postpone scheduled tx {id}: {err}"
+                    );
+                    shell.wl_storage.drop_tx();
+                }
+            }
+            continue;
+        }
+        if let Err(err) =
+            scheduler_api::remove_scheduled_tx(&mut shell.wl_storage, id)
+        {
+            tracing::error!("Failed to remove scheduled tx {id}: {err}");
+            shell.wl_storage.drop_tx();
+            continue;
+        }
+        shell.wl_storage.commit_tx();
+
+        let mut tx = scheduled.tx.clone();
+        tx.update_header(TxType::Decrypted(DecryptedTx::Decrypted));
+        let tx_hash = tx.raw_header_hash();
+        let mut tx_event = Event::new_tx_event(&tx, height.0);
+        tx_event["scheduled_tx_id"] = id.to_string();
+        // The tx isn't in the block, so its index can't be told from it
+        tx_event["tx_index"] = tx_index.0.to_string();
+        let mut tx_gas_meter = TxGasMeter::new(scheduled.gas_limit);
+
+        if let Some(exp) = tx.header.expiration {
+            if block_time > exp {
+                tx_event["code"] = ErrorCodes::ExpiredTx.into();
+                tx_event["info"] = format!(
+                    "Scheduled tx expired at {:#?}, block time: {:#?}",
+                    exp, block_time
+                );
+            }
+        }
+        if !tx_event.contains_key("code")
+            && shell
+                .wl_storage
+                .has_replay_protection_entry(&tx_hash)
+                .unwrap_or_else(|err| {
+                    tracing::error!(
+                        "Failed to check the replay protection of scheduled \
+                         tx {id}: {err}"
+                    );
+                    // Don't risk a replay
+                    true
+                })
+        {
+            tx_event["code"] = ErrorCodes::ReplayTx.into();
+            tx_event["info"] =
+                format!("Scheduled tx {} was already applied", tx_hash);
+        }
+
+        if !tx_event.contains_key("code") {
+            match protocol::dispatch_tx(
+                tx,
+                &[],
+                tx_index,
+                &mut tx_gas_meter,
+                &mut shell.wl_storage,
+                &mut shell.vp_wasm_cache,
+                &mut shell.tx_wasm_cache,
+                None,
+            ) {
+                Ok(result) => {
+                    if result.is_accepted() {
+                        tracing::trace!(
+                            "all VPs accepted scheduled tx {id} storage \
+                             modification {:#?}",
+                            result
+                        );
+                        shell.wl_storage.commit_tx();
+                        tx_event["code"] = ErrorCodes::Ok.into();
+                    } else {
+                        tracing::trace!(
+                            "some VPs rejected scheduled tx {id} storage \
+                             modification {:#?}",
+                            result.vps_result.rejected_vps
+                        );
+                        shell.wl_storage.drop_tx();
+                        tx_event["code"] = ErrorCodes::InvalidTx.into();
+                    }
+                    tx_event["info"] = result.to_string();
+                }
+                Err(msg) => {
+                    tracing::info!("Scheduled tx {id} failed with: {msg}");
+                    shell.wl_storage.drop_tx();
+                    tx_event["code"] = ErrorCodes::WasmRuntimeError.into();
+                    tx_event["info"] = msg.to_string();
+                }
+            }
+            // The hash is committed whatever the outcome, so that the signed
+            // tx can't be replayed in a wrapper
+            shell
+                .wl_storage
+                .write_tx_hash(tx_hash)
+                .expect("Error while writing tx hash to storage");
+        }
+
+        let gas_used = tx_gas_meter.get_tx_consumed_gas();
+        gas_left = gas_left.saturating_sub(gas_used.get_whole_gas_units());
+        match scheduler_api::settle_fees(
+            &mut shell.wl_storage,
+            &scheduled,
+            gas_used.get_whole_gas_units(),
+            block_proposer,
+        ) {
+            Ok(()) => shell.wl_storage.commit_tx(),
+            Err(err) => {
+                tracing::error!(
+                    "Failed to settle the fees of scheduled tx {id}: {err}"
+                );
+                shell.wl_storage.drop_tx();
+            }
+        }
+
+        tx_event["gas_used"] = gas_used.to_string();
+        response.events.push(tx_event);
+    }
+}
+
+#[cfg(test)]
+mod test_scheduler {
+    use namada::ledger::storage_api::token::credit_tokens;
+    use namada::proto::Tx;
+    use namada::types::address::testing::{
+        established_address_1, established_address_2,
+    };
+    use namada::types::transaction::scheduler::ScheduleTx;
+    use namada::types::transaction::{Fee, GasLimit};
+
+    use super::*;
+    use crate::node::ledger::shell::test_utils::setup;
+
+    /// Test that a due scheduled tx whose gas limit exceeds the gas left in
+    /// the block is postponed to the next block height
+    #[test]
+    fn test_postpone_scheduled_tx() {
+        let (mut shell, _recv, _, _) = setup();
+        let owner = established_address_1();
+        let native_token = shell.wl_storage.storage.native_token.clone();
+        credit_tokens(
+            &mut shell.wl_storage,
+            &native_token,
+            &owner,
+            token::Amount::from_u64(1000),
+        )
+        .unwrap();
+        let height = BlockHeight(10);
+        let id = scheduler_api::schedule_tx(
+            &mut shell.wl_storage,
+            ScheduleTx {
+                owner,
+                schedule: Schedule::AtHeight(height),
+                fee: Fee {
+                    amount_per_gas_unit: token::Amount::from_u64(1),
+                    token: native_token,
+                },
+                gas_limit: GasLimit::from(100),
+                tx: Tx::from_type(TxType::Raw),
+            },
+        )
+        .unwrap();
+        shell.wl_storage.commit_tx();
+
+        let max_block_gas = get_max_block_gas(&shell.wl_storage).unwrap();
+        let mut response = shim::response::FinalizeBlock::default();
+        execute_scheduled_txs(
+            &mut shell,
+            &mut response,
+            height,
+            None,
+            DateTimeUtc::now(),
+            &established_address_2(),
+            max_block_gas - 50,
+            0,
+        );
+        assert!(response.events.is_empty());
+
+        let scheduled = scheduler_api::read_scheduled_tx(&shell.wl_storage, id)
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            scheduled.schedule,
+            Schedule::AtHeight(height.next_height())
+        );
+        assert!(
+            scheduler_api::due_scheduled_txs(&shell.wl_storage, height, None)
+                .unwrap()
+                .is_empty()
+        );
+        assert_eq!(
+            scheduler_api::due_scheduled_txs(
+                &shell.wl_storage,
+                height.next_height(),
+                None
+            )
+            .unwrap(),
+            vec![id]
+        );
+    }
+
+    /// Test that the scheduled txs of a block get distinct indices following
+    /// the ones of the block's txs
+    #[test]
+    fn test_scheduled_tx_indices() {
+        let (mut shell, _recv, _, _) = setup();
+        let owner = established_address_1();
+        let native_token = shell.wl_storage.storage.native_token.clone();
+        credit_tokens(
+            &mut shell.wl_storage,
+            &native_token,
+            &owner,
+            token::Amount::from_u64(1000),
+        )
+        .unwrap();
+        let height = BlockHeight(10);
+        for memo in ["first", "second"] {
+            let mut tx = Tx::from_type(TxType::Raw);
+            tx.add_memo(memo);
+            scheduler_api::schedule_tx(
+                &mut shell.wl_storage,
+                ScheduleTx {
+                    owner: owner.clone(),
+                    schedule: Schedule::AtHeight(height),
+                    fee: Fee {
+                        amount_per_gas_unit: token::Amount::from_u64(1),
+                        token: native_token.clone(),
+                    },
+                    gas_limit: GasLimit::from(100),
+                    tx,
+                },
+            )
+            .unwrap();
+        }
+        shell.wl_storage.commit_tx();
+
+        let mut response = shim::response::FinalizeBlock::default();
+        execute_scheduled_txs(
+            &mut shell,
+            &mut response,
+            height,
+            None,
+            DateTimeUtc::now(),
+            &established_address_2(),
+            0,
+            3,
+        );
+        let indices: Vec<_> = response
+            .events
+            .iter()
+            .map(|event| event["tx_index"].clone())
+            .collect();
+        assert_eq!(indices, vec!["3".to_string(), "4".to_string()]);
+    }
+}
@@ -0,0 +1,987 @@
+//! Shell methods for state-sync snapshots.
+//!
+//! A snapshot is taken right after committing the first block of an epoch,
+//! which is when the Merkle tree stores are fully persisted. It contains the
+//! state of the committed block, the Merkle tree stores, the subspace and the
+//! replay protection entries, split into chunks of borsh encoded
+//! [`SnapshotItem`]s. The snapshot's metadata is the list of the chunks'
+//! hashes.
+//!
+//! A node restoring a snapshot first restores the Merkle tree from the stores
+//! and checks its root against the trusted app hash. Every subspace entry of
+//! the following chunks must then be committed in this tree. Once all the
+//! chunks are applied, the tree rebuilt from the received entries must have
+//! the same root, which ensures that no entry is missing.
+//!
+//! The replay protection entries are not in the tree, so they can't be
+//! verified against the app hash and are trusted from the peer serving the
+//! snapshot, like the chunk hashes of its metadata. A peer omitting some of
+//! them could have a restored node accept the replay of a tx already applied
+//! in the last blocks, so a node should only restore snapshots from peers
+//! it trusts.
+//!
+//! The snapshot is written on a background thread from a checkpoint of the
+//! DB, so that the node doesn't wait for it to commit the next blocks.
+
+use std::path::Path;
+use std::{fs, thread};
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use namada::core::ledger::eth_bridge::storage::bridge_pool::is_pending_transfer_key;
+use namada::ledger::replay_protection::{
+    get_replay_protection_all_subkey, get_replay_protection_last_subkey,
+};
+use namada::ledger::storage::merkle_tree::Error as MerkleTreeError;
+use namada::ledger::storage::{
+    epoch_merkle_tree_entries, BlockStateWrite, ConversionState,
+    Error as StorageError, MerkleTree, MerkleTreeStoresRead, StoreType,
+};
+use namada::types::address::EstablishedAddressGen;
+use namada::types::ethereum_structs;
+use namada::types::hash::Hash;
+use namada::types::internal::TxQueue;
+use namada::types::storage::{
+    BlockHash, BlockResults, Epoch, Epochs, EthEventsQueue, Header,
+};
+
+use super::*;
+use crate::facade::tendermint::abci::response::ApplySnapshotChunkResult;
+use crate::facade::tendermint::abci::types::Snapshot;
+
+/// The only snapshot format produced and accepted by this version
+pub const SNAPSHOT_FORMAT: u32 = 1;
+
+/// The size in bytes above which the items are split into a new chunk. It is
+/// kept well below the maximum size of a CometBFT chunk message.
+const CHUNK_SIZE_LIMIT: usize = 10 * 1024 * 1024;
+
+/// The number of snapshots kept on disk, when not configured
+pub const DEFAULT_SNAPSHOTS_TO_KEEP: u64 = 2;
+
+/// The name of the metadata file in a snapshot directory
+const METADATA_FILE: &str = "metadata";
+
+#[derive(Error, Debug)]
+pub enum SnapshotError {
+    #[error("Snapshot I/O error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Error decoding a snapshot chunk: {0}")]
+    Decoding(std::io::Error),
+    #[error("Storage error: {0}")]
+    Storage(#[from] StorageError),
+    #[error("Merkle tree error: {0}")]
+    MerkleTree(#[from] MerkleTreeError),
+    #[error("Invalid snapshot: {0}")]
+    Invalid(String),
+}
+
+/// The state of the committed block at the height of a snapshot
+#[derive(Debug, BorshSerialize, BorshDeserialize)]
+pub struct SnapshotBlock {
+    pub header: Option<Header>,
+    pub hash: BlockHash,
+    pub height: BlockHeight,
+    pub time: DateTimeUtc,
+    pub epoch: Epoch,
+    pub pred_epochs: Epochs,
+    pub next_epoch_min_start_height: BlockHeight,
+    pub next_epoch_min_start_time: DateTimeUtc,
+    pub update_epoch_blocks_delay: Option<u32>,
+    pub address_gen: EstablishedAddressGen,
+    pub results: BlockResults,
+    pub conversion_state: ConversionState,
+    pub tx_queue: TxQueue,
+    pub ethereum_height: Option<ethereum_structs::BlockHeight>,
+    pub eth_events_queue: EthEventsQueue,
+}
+
+/// An entry of a snapshot chunk
+#[derive(Debug, BorshSerialize, BorshDeserialize)]
+pub enum SnapshotItem {
+    /// The block state, always the first item of the first chunk
+    Block(Box<SnapshotBlock>),
+    /// A part of the encoded store of a Merkle tree. A store too large for a
+    /// single chunk is split into consecutive parts.
+    MerkleTreeStore {
+        store_type: StoreType,
+        root: Hash,
+        part: Vec<u8>,
+    },
+    /// A subspace key and its value
+    Subspace { key: Key, value: Vec<u8> },
+    /// A replay protection entry of the last block
+    ReplayProtectionLast(Hash),
+    /// A replay protection entry of the previous blocks
+    ReplayProtectionAll(Hash),
+}
+
+/// The metadata of a snapshot stored on disk
+#[derive(Debug, BorshSerialize, BorshDeserialize)]
+pub struct SnapshotMetadata {
+    pub height: BlockHeight,
+    pub format: u32,
+    pub chunk_hashes: Vec<Hash>,
+}
+
+impl SnapshotMetadata {
+    /// Describe the snapshot to CometBFT. The ABCI metadata is the list of
+    /// the chunks' hashes and the snapshot's hash is the hash of that list.
+    fn to_abci(&self) -> Option<Snapshot> {
+        let metadata = self.chunk_hashes.serialize_to_vec();
+        Some(Snapshot {
+            height: self.height.0.try_into().ok()?,
+            format: self.format,
+            chunks: self.chunk_hashes.len().try_into().ok()?,
+            hash: Hash::sha256(&metadata).0.to_vec().into(),
+            metadata: metadata.into(),
+        })
+    }
+}
+
+/// Splits the snapshot items into chunks written to the snapshot's directory
+struct ChunkWriter<'a> {
+    dir: &'a Path,
+    items: Vec<SnapshotItem>,
+    size: usize,
+    chunk_hashes: Vec<Hash>,
+}
+
+impl<'a> ChunkWriter<'a> {
+    fn new(dir: &'a Path) -> Self {
+        Self {
+            dir,
+            items: vec![],
+            size: 0,
+            chunk_hashes: vec![],
+        }
+    }
+
+    /// Add an item of approximately the given size in bytes
+    fn push(&mut self, item: SnapshotItem, size: usize) -> std::io::Result<()> {
+        if !self.items.is_empty() && self.size + size > CHUNK_SIZE_LIMIT {
+            self.flush()?;
+        }
+        self.items.push(item);
+        self.size += size;
+        Ok(())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        let chunk = mem::take(&mut self.items).serialize_to_vec();
+        let path = self.dir.join(chunk_file(self.chunk_hashes.len()));
+        fs::write(path, &chunk)?;
+        self.chunk_hashes.push(Hash::sha256(&chunk));
+        self.size = 0;
+        Ok(())
+    }
+
+    /// Write the remaining items and return the hashes of all the chunks
+    fn finish(mut self) -> std::io::Result<Vec<Hash>> {
+        if !self.items.is_empty() {
+            self.flush()?;
+        }
+        Ok(self.chunk_hashes)
+    }
+}
+
+fn chunk_file(index: usize) -> String {
+    format!("chunk_{index}")
+}
+
+/// Read the metadata of all the snapshots found in the given directory,
+/// ordered by their heights
+fn read_snapshots(dir: &Path) -> std::io::Result<Vec<SnapshotMetadata>> {
+    let mut snapshots = vec![];
+    if !dir.is_dir() {
+        return Ok(snapshots);
+    }
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        // Skip the snapshots that are still being written
+        let is_snapshot = entry
+            .file_name()
+            .to_str()
+            .map(|name| name.parse::<u64>().is_ok())
+            .unwrap_or_default();
+        if !is_snapshot {
+            continue;
+        }
+        let bytes = fs::read(entry.path().join(METADATA_FILE))?;
+        snapshots.push(SnapshotMetadata::try_from_slice(&bytes)?);
+    }
+    snapshots.sort_by_key(|snapshot| snapshot.height);
+    Ok(snapshots)
+}
+
+/// The state of a snapshot being restored
+#[derive(Debug)]
+pub struct SnapshotRestore<H: StorageHasher> {
+    /// The trusted app hash at the snapshot's height
+    app_hash: Vec<u8>,
+    height: BlockHeight,
+    chunk_hashes: Vec<Hash>,
+    next_chunk: u32,
+    block: Option<SnapshotBlock>,
+    stores: Vec<(StoreType, Hash, Vec<u8>)>,
+    /// The tree restored from the stores, once verified
+    tree: Option<MerkleTree<H>>,
+    /// The tree rebuilt from the restored subspace entries
+    rebuilt_tree: MerkleTree<H>,
+}
+
+impl<H> SnapshotRestore<H>
+where
+    H: StorageHasher,
+{
+    fn new(
+        app_hash: Vec<u8>,
+        height: BlockHeight,
+        chunk_hashes: Vec<Hash>,
+    ) -> Self {
+        Self {
+            app_hash,
+            height,
+            chunk_hashes,
+            next_chunk: 0,
+            block: None,
+            stores: vec![],
+            tree: None,
+            rebuilt_tree: MerkleTree::default(),
+        }
+    }
+
+    fn is_complete(&self) -> bool {
+        self.next_chunk as usize == self.chunk_hashes.len()
+    }
+
+    /// Restore the tree from the received stores and check it against the
+    /// trusted app hash
+    fn restore_tree(&self) -> Result<MerkleTree<H>, SnapshotError> {
+        match &self.block {
+            Some(block) if block.height == self.height => {}
+            _ => {
+                return Err(SnapshotError::Invalid(format!(
+                    "Missing the block state at height {}",
+                    self.height
+                )));
+            }
+        }
+        let mut stores = MerkleTreeStoresRead::default();
+        for st in StoreType::iter() {
+            let (_, root, bytes) = self
+                .stores
+                .iter()
+                .find(|(store_type, _, _)| store_type == st)
+                .ok_or_else(|| {
+                    SnapshotError::Invalid(format!("Missing the {st} store"))
+                })?;
+            stores.set_root(st, *root);
+            stores.set_store(st.decode_store(bytes)?);
+        }
+        let tree = MerkleTree::new(stores)?;
+        if tree.root().0.as_slice() != self.app_hash.as_slice() {
+            return Err(SnapshotError::Invalid(format!(
+                "The Merkle tree root {} doesn't match the app hash",
+                tree.root()
+            )));
+        }
+        Ok(tree)
+    }
+
+    /// Verify the items of a chunk and write them to the DB
+    fn apply_items<D>(
+        &mut self,
+        db: &mut D,
+        items: Vec<SnapshotItem>,
+    ) -> Result<(), SnapshotError>
+    where
+        D: DB,
+    {
+        let mut batch = D::batch();
+        for item in items {
+            match item {
+                SnapshotItem::Block(block) => {
+                    if self.block.is_some() {
+                        return Err(SnapshotError::Invalid(
+                            "Duplicate block state".to_string(),
+                        ));
+                    }
+                    self.block = Some(*block);
+                }
+                SnapshotItem::MerkleTreeStore {
+                    store_type,
+                    root,
+                    mut part,
+                } => {
+                    if self.tree.is_some() {
+                        return Err(SnapshotError::Invalid(format!(
+                            "Unexpected {store_type} store after the subspace"
+                        )));
+                    }
+                    match self.stores.last_mut() {
+                        Some((last_type, last_root, bytes))
+                            if *last_type == store_type
+                                && *last_root == root =>
+                        {
+                            bytes.append(&mut part)
+                        }
+                        _ => self.stores.push((store_type, root, part)),
+                    }
+                }
+                SnapshotItem::Subspace { key, value } => {
+                    if self.tree.is_none() {
+                        self.tree = Some(self.restore_tree()?);
+                    }
+                    let tree = self.tree.as_ref().expect("The tree must exist");
+                    if !tree.has_value(&key, &value)? {
+                        return Err(SnapshotError::Invalid(format!(
+                            "The value of {key} is not committed in the tree"
+                        )));
+                    }
+                    // The bridge pool tree commits the height at which a
+                    // transfer was added instead of its value
+                    if is_pending_transfer_key(&key) {
+                        self.rebuilt_tree.update(&key, tree.get(&key)?)?;
+                    } else {
+                        self.rebuilt_tree.update(&key, &value)?;
+                    }
+                    db.batch_write_subspace_val(
+                        &mut batch,
+                        BlockHeight(0),
+                        &key,
+                        value,
+                    )?;
+                }
+                SnapshotItem::ReplayProtectionLast(hash) => db
+                    .write_replay_protection_entry(
+                        &mut batch,
+                        &get_replay_protection_last_subkey(&hash),
+                    )?,
+                SnapshotItem::ReplayProtectionAll(hash) => db
+                    .write_replay_protection_entry(
+                        &mut batch,
+                        &get_replay_protection_all_subkey(&hash),
+                    )?,
+            }
+        }
+        db.exec_batch(batch)?;
+        Ok(())
+    }
+
+    /// Check that no entry is missing or added and write the block state
+    fn finish<D>(mut self, db: &mut D) -> Result<(), SnapshotError>
+    where
+        D: DB,
+    {
+        let tree = match self.tree.take() {
+            Some(tree) => tree,
+            None => self.restore_tree()?,
+        };
+        let block = self.block.expect("The block must have been restored");
+        for (key, value) in epoch_merkle_tree_entries(
+            block.next_epoch_min_start_height,
+            block.next_epoch_min_start_time,
+            block.epoch,
+        )? {
+            self.rebuilt_tree.update(&key, value)?;
+        }
+        if self.rebuilt_tree.root() != tree.root() {
+            return Err(SnapshotError::Invalid(
+                "Some of the subspace entries are missing".to_string(),
+            ));
+        }
+        let mut batch = D::batch();
+        let state = BlockStateWrite {
+            merkle_tree_stores: tree.stores(),
+            header: block.header.as_ref(),
+            hash: &block.hash,
+            height: block.height,
+            time: block.time,
+            epoch: block.epoch,
+            results: &block.results,
+            pred_epochs: &block.pred_epochs,
+            next_epoch_min_start_height: block.next_epoch_min_start_height,
+            next_epoch_min_start_time: block.next_epoch_min_start_time,
+            update_epoch_blocks_delay: block.update_epoch_blocks_delay,
+            address_gen: &block.address_gen,
+            conversion_state: &block.conversion_state,
+            tx_queue: &block.tx_queue,
+            ethereum_height: block.ethereum_height.as_ref(),
+            eth_events_queue: &block.eth_events_queue,
+        };
+        db.add_block_to_batch(state, &mut batch, true)?;
+        // The subspace is restored without any history
+        db.prune_subspace_diffs(&mut batch, BlockHeight(0))?;
+        db.exec_batch(batch)?;
+        Ok(())
+    }
+}
+
+impl<D, H> Shell<D, H>
+where
+    D: DB + for<'iter> DBIter<'iter> + Sync + 'static,
+    H: StorageHasher + Sync + 'static,
+{
+    /// List the snapshots available on disk
+    pub fn list_snapshots(&self) -> response::ListSnapshots {
+        let snapshots = read_snapshots(&self.snapshots_dir)
+            .unwrap_or_else(|err| {
+                tracing::error!("Failed to read the snapshots: {}", err);
+                vec![]
+            })
+            .iter()
+            .filter_map(SnapshotMetadata::to_abci)
+            .collect();
+        response::ListSnapshots { snapshots }
+    }
+
+    /// Load a chunk of a snapshot from disk
+    pub fn load_snapshot_chunk(
+        &self,
+        req: request::LoadSnapshotChunk,
+    ) -> response::LoadSnapshotChunk {
+        if req.format != SNAPSHOT_FORMAT {
+            return Default::default();
+        }
+        let path = self
+            .snapshots_dir
+            .join(req.height.value().to_string())
+            .join(chunk_file(req.chunk as usize));
+        match fs::read(&path) {
+            Ok(chunk) => response::LoadSnapshotChunk {
+                chunk: chunk.into(),
+            },
+            Err(err) => {
+                tracing::error!(
+                    "Failed to read the snapshot chunk {}: {}",
+                    path.to_string_lossy(),
+                    err
+                );
+                Default::default()
+            }
+        }
+    }
+
+    /// Decide whether to restore the offered snapshot
+    pub fn offer_snapshot(
+        &mut self,
+        req: request::OfferSnapshot,
+    ) -> response::OfferSnapshot {
+        if self.wl_storage.storage.last_block.is_some() {
+            tracing::warn!(
+                "Rejecting a snapshot offer, the chain's state already exists"
+            );
+            return response::OfferSnapshot::Reject;
+        }
+        let snapshot = req.snapshot;
+        if snapshot.format != SNAPSHOT_FORMAT {
+            return response::OfferSnapshot::RejectFormat;
+        }
+        let chunk_hashes = match Vec::<Hash>::try_from_slice(&snapshot.metadata)
+        {
+            Ok(chunk_hashes)
+                if !chunk_hashes.is_empty()
+                    && chunk_hashes.len() == snapshot.chunks as usize
+                    && Hash::sha256(&snapshot.metadata).0.as_slice()
+                        == snapshot.hash.as_ref() =>
+            {
+                chunk_hashes
+            }
+            _ => return response::OfferSnapshot::Reject,
+        };
+        // Clean up what may be left from a previous restoration
+        if let Err(err) = self.clear_restored_state() {
+            tracing::error!(
+                "Failed to clear a partially restored state: {}",
+                err
+            );
+            return response::OfferSnapshot::Abort;
+        }
+        let height = BlockHeight(snapshot.height.value());
+        tracing::info!("Restoring a snapshot at height {}", height);
+        self.snapshot_restore = Some(SnapshotRestore::new(
+            req.app_hash.as_bytes().to_vec(),
+            height,
+            chunk_hashes,
+        ));
+        response::OfferSnapshot::Accept
+    }
+
+    /// Verify a chunk of the snapshot being restored and apply it
+    pub fn apply_snapshot_chunk(
+        &mut self,
+        req: request::ApplySnapshotChunk,
+    ) -> response::ApplySnapshotChunk {
+        let mut restore = match self.snapshot_restore.take() {
+            Some(restore) => restore,
+            None => {
+                return response::ApplySnapshotChunk {
+                    result: ApplySnapshotChunkResult::Abort,
+                    ..Default::default()
+                };
+            }
+        };
+        let reject = |err: SnapshotError| {
+            tracing::error!("Rejecting the snapshot: {}", err);
+            response::ApplySnapshotChunk {
+                result: ApplySnapshotChunkResult::RejectSnapshot,
+                ..Default::default()
+            }
+        };
+        if req.index != restore.next_chunk {
+            return reject(SnapshotError::Invalid(format!(
+                "Expected chunk {}, got chunk {}",
+                restore.next_chunk, req.index
+            )));
+        }
+        // A chunk that doesn't match the metadata comes from a faulty peer
+        if Hash::sha256(&req.chunk) != restore.chunk_hashes[req.index as usize]
+        {
+            tracing::warn!(
+                "Chunk {} from {} doesn't match its hash",
+                req.index,
+                req.sender
+            );
+            self.snapshot_restore = Some(restore);
+            return response::ApplySnapshotChunk {
+                result: ApplySnapshotChunkResult::Retry,
+                refetch_chunks: vec![req.index],
+                reject_senders: vec![req.sender],
+            };
+        }
+        let applied = Vec::<SnapshotItem>::try_from_slice(&req.chunk)
+            .map_err(SnapshotError::Decoding)
+            .and_then(|items| {
+                restore.apply_items(&mut self.wl_storage.storage.db, items)
+            });
+        if let Err(err) = applied {
+            return reject(err);
+        }
+        restore.next_chunk += 1;
+        if !restore.is_complete() {
+            self.snapshot_restore = Some(restore);
+            return response::ApplySnapshotChunk {
+                result: ApplySnapshotChunkResult::Accept,
+                ..Default::default()
+            };
+        }
+
+        let height = restore.height;
+        if let Err(err) = restore.finish(&mut self.wl_storage.storage.db) {
+            return reject(err);
+        }
+        self.wl_storage
+            .storage
+            .load_last_state()
+            .expect("The restored state must be loadable");
+        tracing::info!("Restored the snapshot at height {}", height);
+        self.update_eth_oracle();
+        response::ApplySnapshotChunk {
+            result: ApplySnapshotChunkResult::Accept,
+            ..Default::default()
+        }
+    }
+
+    /// Delete the subspace and replay protection entries written by a
+    /// restoration that didn't complete
+    fn clear_restored_state(&mut self) -> Result<(), SnapshotError> {
+        let storage = &mut self.wl_storage.storage;
+        let keys: Vec<String> = storage
+            .db
+            .iter_prefix(None)
+            .map(|(key, _, _)| key)
+            .collect();
+        let last_hashes: Vec<Hash> = storage.iter_replay_protection().collect();
+        let all_hashes: Vec<Hash> =
+            storage.iter_all_replay_protection().collect();
+        let mut batch = D::batch();
+        for key in keys {
+            let key = Key::parse(key).map_err(StorageError::KeyError)?;
+            storage.db.batch_delete_subspace_val(
+                &mut batch,
+                BlockHeight(0),
+                &key,
+            )?;
+        }
+        for hash in last_hashes {
+            storage.db.delete_replay_protection_entry(
+                &mut batch,
+                &get_replay_protection_last_subkey(&hash),
+            )?;
+        }
+        for hash in all_hashes {
+            storage.db.delete_replay_protection_entry(
+                &mut batch,
+                &get_replay_protection_all_subkey(&hash),
+            )?;
+        }
+        storage
+            .db
+            .prune_subspace_diffs(&mut batch, BlockHeight(0))?;
+        storage.db.exec_batch(batch)?;
+        Ok(())
+    }
+
+    /// Take a snapshot of the last committed block, if the configured epoch
+    /// interval is reached. Must be called right after committing the first
+    /// block of an epoch.
+    ///
+    /// The DB is checkpointed and the snapshot is written from the checkpoint
+    /// on a background thread. A snapshot is skipped if the previous one is
+    /// still being written.
+    pub(super) fn maybe_take_snapshot(&mut self) {
+        let interval = match self.snapshot_epoch_interval {
+            Some(interval) => interval,
+            None => return,
+        };
+        let epoch = self.wl_storage.storage.last_epoch;
+        if interval == 0 || epoch.0 % interval != 0 {
+            return;
+        }
+        if let Some(task) = &self.snapshot_task {
+            if !task.is_finished() {
+                tracing::warn!(
+                    "Skipping a snapshot, the previous one is still being \
+                     written"
+                );
+                return;
+            }
+        }
+        let height = self.wl_storage.storage.get_last_block_height();
+        let checkpoint_dir =
+            self.snapshots_dir.join(format!("checkpoint_{height}"));
+        let checkpoint = || -> Result<(), SnapshotError> {
+            if checkpoint_dir.exists() {
+                fs::remove_dir_all(&checkpoint_dir)?;
+            }
+            fs::create_dir_all(&self.snapshots_dir)?;
+            self.wl_storage.storage.db.checkpoint(&checkpoint_dir)?;
+            Ok(())
+        };
+        if let Err(err) = checkpoint() {
+            tracing::error!("Failed to checkpoint the DB: {}", err);
+            return;
+        }
+
+        let snapshots_dir = self.snapshots_dir.clone();
+        let snapshots_to_keep = self.snapshots_to_keep;
+        self.snapshot_task = Some(thread::spawn(move || {
+            let db = D::open(&checkpoint_dir, None);
+            match write_snapshot(&db, &snapshots_dir) {
+                Ok(metadata) => tracing::info!(
+                    "Took a snapshot at height {} with {} chunks",
+                    metadata.height,
+                    metadata.chunk_hashes.len()
+                ),
+                Err(err) => {
+                    tracing::error!("Failed to take a snapshot: {}", err)
+                }
+            }
+            drop(db);
+            if let Err(err) = fs::remove_dir_all(&checkpoint_dir) {
+                tracing::error!("Failed to remove the DB checkpoint: {}", err);
+            }
+            if let Err(err) = prune_snapshots(&snapshots_dir, snapshots_to_keep)
+            {
+                tracing::error!("Failed to prune the old snapshots: {}", err);
+            }
+        }));
+    }
+}
+
+/// Write a snapshot of the last committed block in the DB to the snapshots
+/// directory
+fn write_snapshot<D>(
+    db: &D,
+    snapshots_dir: &Path,
+) -> Result<SnapshotMetadata, SnapshotError>
+where
+    D: DB + for<'iter> DBIter<'iter>,
+{
+    let state = db.read_last_block()?.ok_or_else(|| {
+        SnapshotError::Invalid("No block has been committed".to_string())
+    })?;
+    let height = state.height;
+    let header = db.read_block_header(height)?;
+
+    let tmp_dir = snapshots_dir.join(format!("tmp_{height}"));
+    if tmp_dir.exists() {
+        fs::remove_dir_all(&tmp_dir)?;
+    }
+    fs::create_dir_all(&tmp_dir)?;
+    let mut writer = ChunkWriter::new(&tmp_dir);
+
+    let block = SnapshotBlock {
+        header,
+        hash: state.hash,
+        height,
+        time: state.time,
+        epoch: state.epoch,
+        pred_epochs: state.pred_epochs,
+        next_epoch_min_start_height: state.next_epoch_min_start_height,
+        next_epoch_min_start_time: state.next_epoch_min_start_time,
+        update_epoch_blocks_delay: state.update_epoch_blocks_delay,
+        address_gen: state.address_gen,
+        results: state.results,
+        conversion_state: state.conversion_state,
+        tx_queue: state.tx_queue,
+        ethereum_height: state.ethereum_height,
+        eth_events_queue: state.eth_events_queue,
+    };
+    let item = SnapshotItem::Block(Box::new(block));
+    let size = item.serialize_to_vec().len();
+    writer.push(item, size)?;
+
+    let stores = &state.merkle_tree_stores;
+    for st in StoreType::iter() {
+        let root = stores.get_root(*st);
+        for part in stores.get_store(*st).encode().chunks(CHUNK_SIZE_LIMIT) {
+            let item = SnapshotItem::MerkleTreeStore {
+                store_type: *st,
+                root,
+                part: part.to_vec(),
+            };
+            writer.push(item, part.len())?;
+        }
+    }
+
+    for (key, value, _) in db.iter_prefix(None) {
+        let size = key.len() + value.len();
+        let key = Key::parse(key).map_err(StorageError::KeyError)?;
+        writer.push(SnapshotItem::Subspace { key, value }, size)?;
+    }
+    for (key, _, _) in db.iter_replay_protection() {
+        let hash = replay_protection_hash(&key)?;
+        writer.push(SnapshotItem::ReplayProtectionLast(hash), 32)?;
+    }
+    for (key, _, _) in db.iter_all_replay_protection() {
+        let hash = replay_protection_hash(&key)?;
+        writer.push(SnapshotItem::ReplayProtectionAll(hash), 32)?;
+    }
+
+    let metadata = SnapshotMetadata {
+        height,
+        format: SNAPSHOT_FORMAT,
+        chunk_hashes: writer.finish()?,
+    };
+    fs::write(tmp_dir.join(METADATA_FILE), metadata.serialize_to_vec())?;
+    let dir = snapshots_dir.join(height.0.to_string());
+    if dir.exists() {
+        fs::remove_dir_all(&dir)?;
+    }
+    fs::rename(tmp_dir, dir)?;
+    Ok(metadata)
+}
+
+/// Parse the tx hash from the key of a replay protection entry
+fn replay_protection_hash(key: &str) -> Result<Hash, SnapshotError> {
+    key.rsplit_once('/')
+        .and_then(|(_, hash)| hash.parse().ok())
+        .ok_or_else(|| {
+            SnapshotError::Invalid(format!(
+                "Invalid replay protection key {key}"
+            ))
+        })
+}
+
+/// Remove the oldest snapshots above the number of snapshots to keep
+fn prune_snapshots(
+    snapshots_dir: &Path,
+    snapshots_to_keep: u64,
+) -> std::io::Result<()> {
+    let snapshots = read_snapshots(snapshots_dir)?;
+    let to_prune = snapshots.len().saturating_sub(snapshots_to_keep as usize);
+    for snapshot in &snapshots[..to_prune] {
+        fs::remove_dir_all(snapshots_dir.join(snapshot.height.0.to_string()))?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test_snapshots {
+    use namada::ledger::storage::mockdb::MockDB;
+    use namada::ledger::storage_api::StorageWrite;
+
+    use super::*;
+    use crate::node::ledger::shell::test_utils::{self, TestShell};
+
+    /// Write some state and commit the first block of a new epoch
+    fn commit_new_epoch(shell: &mut TestShell) {
+        for i in 0..10_u64 {
+            let key = Key::parse(format!("snapshot_test/{i}")).unwrap();
+            shell
+                .wl_storage
+                .write_bytes(&key, i.serialize_to_vec())
+                .unwrap();
+            shell
+                .wl_storage
+                .write_log
+                .write_tx_hash(Hash::sha256(key.to_string()))
+                .unwrap();
+        }
+        let storage = &mut shell.wl_storage.storage;
+        let height = storage.get_last_block_height().next_height();
+        let epoch = storage.block.epoch.next();
+        storage.block.height = height;
+        storage.block.epoch = epoch;
+        storage.block.pred_epochs.new_epoch(height);
+        shell.wl_storage.commit_block().unwrap();
+        assert_eq!(shell.wl_storage.storage.last_epoch, epoch);
+    }
+
+    /// Offer the snapshot to the restoring shell and feed it all the chunks
+    fn restore(
+        shell: &TestShell,
+        restored: &mut TestShell,
+        snapshot: Snapshot,
+    ) -> Vec<response::ApplySnapshotChunk> {
+        let app_hash = AppHash::try_from(
+            shell.wl_storage.storage.merkle_root().0.to_vec(),
+        )
+        .unwrap();
+        let height = snapshot.height;
+        let chunks = snapshot.chunks;
+        let offer = restored
+            .offer_snapshot(request::OfferSnapshot { snapshot, app_hash });
+        assert_eq!(offer, response::OfferSnapshot::Accept);
+        (0..chunks)
+            .map(|index| {
+                let chunk = shell
+                    .load_snapshot_chunk(request::LoadSnapshotChunk {
+                        height,
+                        format: SNAPSHOT_FORMAT,
+                        chunk: index,
+                    })
+                    .chunk;
+                restored.apply_snapshot_chunk(request::ApplySnapshotChunk {
+                    index,
+                    chunk,
+                    sender: "peer".to_string(),
+                })
+            })
+            .collect()
+    }
+
+    /// Test that a snapshot restores the same state in a new node
+    #[test]
+    fn test_snapshot_restore() {
+        let (mut shell, _recv, _eth_sender, _control_receiver) =
+            test_utils::setup();
+        commit_new_epoch(&mut shell);
+        let metadata =
+            write_snapshot(&shell.wl_storage.storage.db, &shell.snapshots_dir)
+                .unwrap();
+        assert_eq!(
+            metadata.height,
+            shell.wl_storage.storage.get_last_block_height()
+        );
+        let mut snapshots = shell.list_snapshots().snapshots;
+        assert_eq!(snapshots.len(), 1);
+        let snapshot = snapshots.remove(0);
+
+        let (mut restored, _recv, _eth_sender, _control_receiver) =
+            TestShell::new();
+        let responses = restore(&shell, &mut restored, snapshot.clone());
+        assert!(responses
+            .iter()
+            .all(|resp| resp.result == ApplySnapshotChunkResult::Accept));
+        assert_eq!(
+            restored.wl_storage.storage.merkle_root(),
+            shell.wl_storage.storage.merkle_root()
+        );
+        assert_eq!(
+            restored.wl_storage.storage.get_last_block_height(),
+            metadata.height
+        );
+        let key = Key::parse("snapshot_test/3").unwrap();
+        assert_eq!(
+            restored.wl_storage.storage.read(&key).unwrap().0,
+            Some(3_u64.serialize_to_vec())
+        );
+        assert!(
+            restored
+                .wl_storage
+                .storage
+                .has_replay_protection_entry(&Hash::sha256(key.to_string()))
+                .unwrap()
+        );
+
+        // A node with a state doesn't accept snapshots
+        let offer = restored.offer_snapshot(request::OfferSnapshot {
+            snapshot,
+            app_hash: AppHash::default(),
+        });
+        assert_eq!(offer, response::OfferSnapshot::Reject);
+    }
+
+    /// Test that a snapshot which doesn't match the app hash is rejected
+    #[test]
+    fn test_snapshot_wrong_app_hash() {
+        let (mut shell, _recv, _eth_sender, _control_receiver) =
+            test_utils::setup();
+        commit_new_epoch(&mut shell);
+        write_snapshot(&shell.wl_storage.storage.db, &shell.snapshots_dir)
+            .unwrap();
+        let snapshot = shell.list_snapshots().snapshots.remove(0);
+        let height = snapshot.height;
+
+        let (mut restored, _recv, _eth_sender, _control_receiver) =
+            TestShell::new();
+        let offer = restored.offer_snapshot(request::OfferSnapshot {
+            snapshot,
+            app_hash: AppHash::try_from(vec![1u8; 32]).unwrap(),
+        });
+        assert_eq!(offer, response::OfferSnapshot::Accept);
+        let chunk = shell
+            .load_snapshot_chunk(request::LoadSnapshotChunk {
+                height,
+                format: SNAPSHOT_FORMAT,
+                chunk: 0,
+            })
+            .chunk;
+
+        // A corrupted chunk is fetched again from another peer
+        let mut corrupted = chunk.to_vec();
+        corrupted[0] ^= 1;
+        let resp = restored.apply_snapshot_chunk(request::ApplySnapshotChunk {
+            index: 0,
+            chunk: corrupted.into(),
+            sender: "peer".to_string(),
+        });
+        assert_eq!(resp.result, ApplySnapshotChunkResult::Retry);
+        assert_eq!(resp.refetch_chunks, vec![0]);
+        assert_eq!(resp.reject_senders, vec!["peer".to_string()]);
+
+        let resp = restored.apply_snapshot_chunk(request::ApplySnapshotChunk {
+            index: 0,
+            chunk,
+            sender: "other_peer".to_string(),
+        });
+        assert_eq!(resp.result, ApplySnapshotChunkResult::RejectSnapshot);
+        assert!(restored.snapshot_restore.is_none());
+    }
+
+    /// Test that only the configured number of snapshots is kept
+    #[test]
+    fn test_prune_snapshots() {
+        let (shell, _recv, _eth_sender, _control_receiver) = TestShell::new();
+        for height in 1..=3_u64 {
+            let dir = shell.snapshots_dir.join(height.to_string());
+            fs::create_dir_all(&dir).unwrap();
+            let metadata = SnapshotMetadata {
+                height: BlockHeight(height),
+                format: SNAPSHOT_FORMAT,
+                chunk_hashes: vec![],
+            };
+            fs::write(dir.join(METADATA_FILE), metadata.serialize_to_vec())
+                .unwrap();
+        }
+        prune_snapshots(&shell.snapshots_dir, shell.snapshots_to_keep).unwrap();
+        let heights: Vec<_> = read_snapshots(&shell.snapshots_dir)
+            .unwrap()
+            .into_iter()
+            .map(|snapshot| snapshot.height)
+            .collect();
+        assert_eq!(heights, vec![BlockHeight(2), BlockHeight(3)]);
+    }
+}
@@ -0,0 +1,690 @@
+//! Export and import of the ledger's state, for hard-fork style chain
+//! migrations.
+//!
+//! The state of a halted chain is exported at a committed block into a
+//! versioned file, which contains the subspace entries, the block state that
+//! is not part of the subspace (epochs, address generator, conversion state,
+//! ...) and the Merkle roots of every [`StoreType`]. The file's content only
+//! depends on the state, so every node of the old chain exports the same
+//! file. A block older than the last committed one is exported from a
+//! checkpoint of the DB rolled back to it, as far back as the diffs are
+//! retained.
+//!
+//! Importing the file into a new chain checks the entries against the app hash
+//! of the exported block, which must be taken from a trusted source (e.g. the
+//! header of the following block), applies the [`StateTransformation`]s
+//! returned by [`transformations`] and places the result in the chain's
+//! directory. The new chain is then initialized from it instead of its
+//! genesis files. It continues from the exported height, so its CometBFT
+//! genesis must set the initial height to the height following the exported
+//! block.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use namada::core::ledger::eth_bridge::storage::bridge_pool::is_pending_transfer_key;
+use namada::ledger::storage::merkle_tree::Error as MerkleTreeError;
+use namada::ledger::storage::{
+    epoch_merkle_tree_entries, ConversionState, Error as StorageError,
+    MerkleTree, StoreType,
+};
+use namada::ledger::storage_api::StorageWrite;
+use namada::types::address::EstablishedAddressGen;
+use namada::types::ethereum_structs;
+use namada::types::hash::Hash;
+use namada::types::storage::{Epoch, Epochs, EthEventsQueue};
+
+use super::*;
+use crate::cli::args;
+use crate::node::ledger::storage::PersistentStorageHasher;
+
+/// The version of the state export format produced and accepted by this
+/// version
+pub const STATE_EXPORT_VERSION: u32 = 1;
+
+/// The name of the file in the chain directory holding the state that the
+/// chain is initialized from
+pub const IMPORTED_STATE_FILE: &str = "imported_state";
+
+#[derive(Error, Debug)]
+pub enum StateMigrationError {
+    #[error("State file I/O error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Error decoding the state file: {0}")]
+    Decoding(std::io::Error),
+    #[error("Storage error: {0}")]
+    Storage(#[from] StorageError),
+    #[error("Merkle tree error: {0}")]
+    MerkleTree(#[from] MerkleTreeError),
+    #[error("Invalid state: {0}")]
+    Invalid(String),
+}
+
+impl From<StateMigrationError> for Error {
+    fn from(err: StateMigrationError) -> Self {
+        Error::StateMigration(err)
+    }
+}
+
+/// The state of a chain at a committed block
+#[derive(Debug, BorshSerialize, BorshDeserialize)]
+pub struct StateExport {
+    /// The chain the state was exported from
+    pub chain_id: ChainId,
+    /// Height of the exported block
+    pub height: BlockHeight,
+    /// Epoch of the exported block
+    pub epoch: Epoch,
+    /// Predecessor block epochs
+    pub pred_epochs: Epochs,
+    /// Minimum block height at which the next epoch may start
+    pub next_epoch_min_start_height: BlockHeight,
+    /// Minimum block time at which the next epoch may start
+    pub next_epoch_min_start_time: DateTimeUtc,
+    /// Update epoch delay
+    pub update_epoch_blocks_delay: Option<u32>,
+    /// Established address generator
+    pub address_gen: EstablishedAddressGen,
+    /// The conversion state
+    pub conversion_state: ConversionState,
+    /// The latest block height on Ethereum processed, if the bridge is
+    /// enabled
+    pub ethereum_height: Option<ethereum_structs::BlockHeight>,
+    /// The queue of Ethereum events to be processed in order
+    pub eth_events_queue: EthEventsQueue,
+    /// The Merkle roots of the exported block, for every store type
+    pub merkle_roots: Vec<(StoreType, Hash)>,
+    /// The subspace entries
+    pub entries: Vec<(Key, Vec<u8>)>,
+    /// The values committed in the bridge pool tree for the pending
+    /// transfers, which are the heights at which they were added instead of
+    /// the transfers
+    pub bridge_pool_heights: Vec<(Key, Vec<u8>)>,
+}
+
+/// A transformation of an exported state, applied when it's imported into a
+/// new chain
+pub trait StateTransformation {
+    /// Transform an entry of the state. Returning `None` removes the entry.
+    fn transform(&self, key: Key, value: Vec<u8>) -> Option<(Key, Vec<u8>)>;
+
+    /// Entries to add to the state once all the existing entries have been
+    /// transformed. They overwrite any entry with the same key.
+    fn new_entries(&self) -> Vec<(Key, Vec<u8>)> {
+        vec![]
+    }
+}
+
+/// The transformations applied, in order, to a state imported with
+/// `ledger import-state`. The changes of the state required by a chain
+/// migration are added here.
+pub fn transformations() -> Vec<Box<dyn StateTransformation>> {
+    vec![]
+}
+
+impl StateExport {
+    /// Export the state of the last committed block
+    pub fn from_storage<D, H>(
+        storage: &Storage<D, H>,
+    ) -> std::result::Result<Self, StateMigrationError>
+    where
+        D: DB + for<'iter> DBIter<'iter>,
+        H: StorageHasher,
+    {
+        let state = storage.db.read_last_block()?.ok_or_else(|| {
+            StateMigrationError::Invalid(
+                "No block has been committed".to_string(),
+            )
+        })?;
+        if !state.tx_queue.is_empty() {
+            tracing::warn!(
+                "The wrapper txs in the queue at height {} are not exported",
+                state.height
+            );
+        }
+
+        let merkle_roots = StoreType::iter()
+            .map(|st| (*st, storage.block.tree.sub_root(st).into()))
+            .collect();
+        let entries: Vec<(Key, Vec<u8>)> = storage
+            .db
+            .iter_prefix(None)
+            .map(|(key, value, _gas)| {
+                let key = Key::parse(key).map_err(StorageError::KeyError)?;
+                Ok((key, value))
+            })
+            .collect::<std::result::Result<_, StateMigrationError>>()?;
+        let bridge_pool_heights = entries
+            .iter()
+            .filter(|(key, _)| is_pending_transfer_key(key))
+            .map(|(key, _)| Ok((key.clone(), storage.block.tree.get(key)?)))
+            .collect::<std::result::Result<_, StateMigrationError>>()?;
+
+        Ok(Self {
+            chain_id: storage.chain_id.clone(),
+            height: state.height,
+            epoch: state.epoch,
+            pred_epochs: state.pred_epochs,
+            next_epoch_min_start_height: state.next_epoch_min_start_height,
+            next_epoch_min_start_time: state.next_epoch_min_start_time,
+            update_epoch_blocks_delay: state.update_epoch_blocks_delay,
+            address_gen: state.address_gen,
+            conversion_state: state.conversion_state,
+            ethereum_height: state.ethereum_height,
+            eth_events_queue: state.eth_events_queue,
+            merkle_roots,
+            entries,
+            bridge_pool_heights,
+        })
+    }
+
+    /// Read a state file, checking its version
+    pub fn read(
+        path: impl AsRef<Path>,
+    ) -> std::result::Result<Self, StateMigrationError> {
+        let bytes = fs::read(path)?;
+        let mut reader = bytes.as_slice();
+        let version = u32::deserialize(&mut reader)
+            .map_err(StateMigrationError::Decoding)?;
+        if version != STATE_EXPORT_VERSION {
+            return Err(StateMigrationError::Invalid(format!(
+                "Unsupported state export version {version}, expected \
+                 {STATE_EXPORT_VERSION}"
+            )));
+        }
+        let state = Self::deserialize(&mut reader)
+            .map_err(StateMigrationError::Decoding)?;
+        if !reader.is_empty() {
+            return Err(StateMigrationError::Invalid(
+                "Unexpected trailing bytes".to_string(),
+            ));
+        }
+        Ok(state)
+    }
+
+    /// Write the state into a file, prefixed with the format version
+    pub fn write(
+        &self,
+        path: impl AsRef<Path>,
+    ) -> std::result::Result<(), StateMigrationError> {
+        let mut bytes = STATE_EXPORT_VERSION.serialize_to_vec();
+        bytes.extend(self.serialize_to_vec());
+        fs::write(path, bytes)?;
+        Ok(())
+    }
+
+    /// Check that the entries are the ones committed in the trusted app hash
+    /// of the exported block
+    pub fn verify<H>(
+        &self,
+        app_hash: &Hash,
+    ) -> std::result::Result<(), StateMigrationError>
+    where
+        H: StorageHasher + Default,
+    {
+        let mut tree = MerkleTree::<H>::default();
+        for (key, value) in &self.entries {
+            // The bridge pool tree commits the heights at which the
+            // transfers were added instead of their values
+            if !is_pending_transfer_key(key) {
+                tree.update(key, value)?;
+            }
+        }
+        for (key, height) in &self.bridge_pool_heights {
+            if !self.entries.iter().any(|(entry_key, _)| entry_key == key) {
+                return Err(StateMigrationError::Invalid(format!(
+                    "No pending transfer for the bridge pool key {key}"
+                )));
+            }
+            tree.update(key, height)?;
+        }
+        for (key, value) in epoch_merkle_tree_entries(
+            self.next_epoch_min_start_height,
+            self.next_epoch_min_start_time,
+            self.epoch,
+        )? {
+            tree.update(&key, value)?;
+        }
+        for st in StoreType::iter() {
+            let root = self
+                .merkle_roots
+                .iter()
+                .find_map(|(store_type, root)| {
+                    (store_type == st).then_some(root)
+                })
+                .ok_or_else(|| {
+                    StateMigrationError::Invalid(format!(
+                        "Missing the {st} root"
+                    ))
+                })?;
+            if Hash::from(tree.sub_root(st)) != *root {
+                return Err(StateMigrationError::Invalid(format!(
+                    "The entries don't match the {st} root"
+                )));
+            }
+        }
+        if Hash::from(tree.root()) != *app_hash {
+            return Err(StateMigrationError::Invalid(format!(
+                "The Merkle root {} doesn't match the app hash {app_hash}",
+                tree.root()
+            )));
+        }
+        Ok(())
+    }
+
+    /// Apply the transformations to the entries
+    pub fn transform(
+        mut self,
+        transformations: &[Box<dyn StateTransformation>],
+    ) -> Self {
+        let mut entries = BTreeMap::new();
+        for (key, value) in self.entries {
+            let entry = transformations.iter().try_fold(
+                (key, value),
+                |(key, value), transformation| {
+                    transformation.transform(key, value)
+                },
+            );
+            if let Some((key, value)) = entry {
+                entries.insert(key, value);
+            }
+        }
+        for transformation in transformations {
+            entries.extend(transformation.new_entries());
+        }
+        self.entries = entries.into_iter().collect();
+        self
+    }
+}
+
+/// Export the state at the given height, or at the last committed block, into
+/// a file
+pub fn export_state(
+    config: config::Ledger,
+    args::LedgerExportState {
+        block_height,
+        out_file_path,
+    }: args::LedgerExportState,
+) -> Result<()> {
+    let chain_dir = config.shell.base_dir.join(config.chain_id.as_str());
+    let genesis = genesis::chain::Finalized::read_toml_files(&chain_dir)
+        .expect("Missing genesis files");
+    let native_token = genesis.get_native_token().clone();
+    let db_path = config.shell.db_dir(&config.chain_id);
+
+    let state = match config.shell.storage_backend {
+        StorageBackend::RocksDB => export_db::<storage::PersistentDB>(
+            &db_path,
+            config.chain_id,
+            native_token,
+            block_height,
+        )?,
+        StorageBackend::Redb => export_db::<storage::PersistentRedbDB>(
+            &db_path,
+            config.chain_id,
+            native_token,
+            block_height,
+        )?,
+    };
+    state.write(&out_file_path)?;
+    tracing::info!(
+        "Exported the state of chain {} at height {} to {}",
+        state.chain_id,
+        state.height,
+        out_file_path.to_string_lossy()
+    );
+    Ok(())
+}
+
+/// Export the state of a DB at the given height, or at the last committed
+/// block. A given height is exported from a checkpoint of the DB rolled back
+/// to it, so that the DB is left untouched.
+fn export_db<D>(
+    db_path: &Path,
+    chain_id: ChainId,
+    native_token: Address,
+    height: Option<BlockHeight>,
+) -> std::result::Result<StateExport, StateMigrationError>
+where
+    D: DB + for<'iter> DBIter<'iter> + storage::DBRollback,
+{
+    // Keep the checkpoint on the same file system as the DB
+    let checkpoint_dir =
+        tempfile::tempdir_in(db_path.parent().unwrap_or(db_path))?;
+    let db_path = match height {
+        Some(height) => {
+            let checkpoint_path = checkpoint_dir.path().join("db");
+            D::open(db_path, None).checkpoint(&checkpoint_path)?;
+            let mut checkpoint = D::open(&checkpoint_path, None);
+            checkpoint.rollback(height)?;
+            checkpoint_path
+        }
+        None => db_path.to_owned(),
+    };
+    let mut storage = Storage::<D, PersistentStorageHasher>::open(
+        db_path,
+        chain_id,
+        native_token,
+        None,
+        None,
+    );
+    storage.load_last_state()?;
+    StateExport::from_storage(&storage)
+}
+
+/// Check and transform an exported state, and place it in the chain directory
+/// for the chain to be initialized from it
+pub fn import_state(
+    config: config::Ledger,
+    args::LedgerImportState {
+        in_file_path,
+        app_hash,
+    }: args::LedgerImportState,
+) -> Result<()> {
+    let state = StateExport::read(&in_file_path)?;
+    if state.chain_id == config.chain_id {
+        return Err(StateMigrationError::Invalid(format!(
+            "The state of chain {} must be imported into a new chain",
+            state.chain_id
+        ))
+        .into());
+    }
+    state.verify::<PersistentStorageHasher>(&app_hash)?;
+
+    let db_path = config.shell.db_dir(&config.chain_id);
+    let last_block = match config.shell.storage_backend {
+        StorageBackend::RocksDB => {
+            storage::PersistentDB::open(db_path, None).read_last_block()
+        }
+        StorageBackend::Redb => {
+            storage::PersistentRedbDB::open(db_path, None).read_last_block()
+        }
+    }
+    .map_err(StateMigrationError::from)?;
+    if last_block.is_some() {
+        return Err(StateMigrationError::Invalid(format!(
+            "Chain {} has already been initialized",
+            config.chain_id
+        ))
+        .into());
+    }
+
+    let state = state.transform(&transformations());
+    let chain_dir = config.shell.base_dir.join(config.chain_id.as_str());
+    state.write(chain_dir.join(IMPORTED_STATE_FILE))?;
+    tracing::info!(
+        "Chain {} will be initialized from the state of chain {} at height \
+         {}. Its initial height must be {}.",
+        config.chain_id,
+        state.chain_id,
+        state.height,
+        state.height.next_height()
+    );
+    Ok(())
+}
+
+impl<D, H> Shell<D, H>
+where
+    D: DB + for<'iter> DBIter<'iter> + Sync + 'static,
+    H: StorageHasher + Sync + 'static,
+{
+    /// Initialize the chain from an imported state, instead of the genesis
+    /// files.
+    ///
+    /// INVARIANT: This method must not commit the state changes to DB.
+    pub(super) fn init_chain_from_imported_state(
+        &mut self,
+        path: &Path,
+        initial_height: BlockHeight,
+    ) -> Result<()> {
+        let state = StateExport::read(path)?;
+        self.apply_imported_state(state, initial_height)
+    }
+
+    /// Write the imported state. The chain continues from the exported
+    /// block, with the same epochs. The last epoch is left unset, so that the
+    /// first block is fully committed.
+    fn apply_imported_state(
+        &mut self,
+        state: StateExport,
+        initial_height: BlockHeight,
+    ) -> Result<()> {
+        // The epoch of the chain's first block must differ from its last
+        // epoch for all of the block state and the epoch's Merkle tree entries
+        // to be committed
+        if state.epoch == Epoch::default() {
+            return Err(StateMigrationError::Invalid(
+                "A state exported in the first epoch cannot be imported"
+                    .to_string(),
+            )
+            .into());
+        }
+        if initial_height != state.height.next_height() {
+            return Err(StateMigrationError::Invalid(format!(
+                "The initial height must be {}, following the exported block, \
+                 got {initial_height}",
+                state.height.next_height()
+            ))
+            .into());
+        }
+        tracing::info!(
+            "Initializing the chain from the state of chain {} at height {}",
+            state.chain_id,
+            state.height
+        );
+
+        for (key, value) in state.entries {
+            self.wl_storage.write_bytes(&key, value)?;
+        }
+        let storage = &mut self.wl_storage.storage;
+        storage.block.epoch = state.epoch;
+        storage.block.pred_epochs = state.pred_epochs;
+        storage.next_epoch_min_start_height = state.next_epoch_min_start_height;
+        storage.next_epoch_min_start_time = state.next_epoch_min_start_time;
+        storage.update_epoch_blocks_delay = state.update_epoch_blocks_delay;
+        storage.address_gen = state.address_gen;
+        storage.conversion_state = state.conversion_state;
+        storage.ethereum_height = state.ethereum_height;
+        storage.eth_events_queue = state.eth_events_queue;
+
+        self.update_eth_oracle();
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test_state_migration {
+    use namada::ledger::storage::Sha256Hasher;
+    use namada::types::storage::BlockHash;
+
+    use super::*;
+    use crate::node::ledger::shell::test_utils::{self, TestShell};
+
+    /// Removes the first test entry, renames the second and adds a new one
+    struct TestTransformation;
+
+    impl StateTransformation for TestTransformation {
+        fn transform(
+            &self,
+            key: Key,
+            value: Vec<u8>,
+        ) -> Option<(Key, Vec<u8>)> {
+            match key.to_string().as_str() {
+                "migration_test/0" => None,
+                "migration_test/1" => {
+                    Some((Key::parse("migration_test/renamed").unwrap(), value))
+                }
+                _ => Some((key, value)),
+            }
+        }
+
+        fn new_entries(&self) -> Vec<(Key, Vec<u8>)> {
+            vec![(
+                Key::parse("migration_test/new").unwrap(),
+                42_u64.serialize_to_vec(),
+            )]
+        }
+    }
+
+    /// Write some state and commit the first block of a new epoch
+    fn commit_test_entries(shell: &mut TestShell) {
+        for i in 0..5_u64 {
+            let key = Key::parse(format!("migration_test/{i}")).unwrap();
+            shell
+                .wl_storage
+                .write_bytes(&key, i.serialize_to_vec())
+                .unwrap();
+        }
+        let storage = &mut shell.wl_storage.storage;
+        let height = storage.get_last_block_height().next_height();
+        storage.block.height = height;
+        storage.block.epoch = storage.block.epoch.next();
+        storage.block.pred_epochs.new_epoch(height);
+        shell.wl_storage.commit_block().unwrap();
+    }
+
+    /// Test that an exported state initializes a new chain with the
+    /// transformed entries
+    #[test]
+    fn test_export_import_state() {
+        let (mut shell, _recv, _eth_sender, _control_receiver) =
+            test_utils::setup();
+        commit_test_entries(&mut shell);
+        let storage = &shell.wl_storage.storage;
+        let height = storage.get_last_block_height();
+        let app_hash = Hash::from(storage.merkle_root());
+
+        let state = StateExport::from_storage(storage).unwrap();
+        assert_eq!(state.height, height);
+        state.verify::<Sha256Hasher>(&app_hash).unwrap();
+        // The app hash must be the one of the exported block
+        assert!(state.verify::<Sha256Hasher>(&Hash::zero()).is_err());
+
+        // The state file is deterministic
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("state");
+        state.write(&path).unwrap();
+        let read_back = StateExport::read(&path).unwrap();
+        assert_eq!(read_back.serialize_to_vec(), state.serialize_to_vec());
+        let exported_again = StateExport::from_storage(storage).unwrap();
+        assert_eq!(exported_again.serialize_to_vec(), state.serialize_to_vec());
+
+        let state = state.transform(&[Box::new(TestTransformation)]);
+        let imported_path = dir.path().join(IMPORTED_STATE_FILE);
+        state.write(&imported_path).unwrap();
+
+        let (mut imported, _recv, _eth_sender, _imported_control_receiver) =
+            TestShell::new();
+        // The chain must start right after the exported block
+        assert!(imported
+            .init_chain_from_imported_state(&imported_path, height)
+            .is_err());
+        let initial_height = height.next_height();
+        imported
+            .init_chain_from_imported_state(&imported_path, initial_height)
+            .unwrap();
+        assert_eq!(
+            imported.initial_validator_set(),
+            shell.initial_validator_set()
+        );
+        imported.wl_storage.storage.block.height = initial_height;
+        imported.wl_storage.commit_block().unwrap();
+
+        let storage = &imported.wl_storage.storage;
+        assert_eq!(storage.get_last_block_height(), initial_height);
+        assert_eq!(storage.last_epoch, shell.wl_storage.storage.last_epoch);
+        assert_eq!(storage.address_gen, shell.wl_storage.storage.address_gen);
+        let read =
+            |key: &str| storage.read(&Key::parse(key).unwrap()).unwrap().0;
+        assert_eq!(read("migration_test/0"), None);
+        assert_eq!(read("migration_test/1"), None);
+        assert_eq!(
+            read("migration_test/renamed"),
+            Some(1_u64.serialize_to_vec())
+        );
+        assert_eq!(read("migration_test/2"), Some(2_u64.serialize_to_vec()));
+        assert_eq!(read("migration_test/new"), Some(42_u64.serialize_to_vec()));
+    }
+
+    /// Test that a state whose entries were modified without transformations
+    /// is rejected
+    #[test]
+    fn test_verify_tampered_state() {
+        let (mut shell, _recv, _eth_sender, _control_receiver) =
+            test_utils::setup();
+        commit_test_entries(&mut shell);
+        let storage = &shell.wl_storage.storage;
+        let app_hash = Hash::from(storage.merkle_root());
+        let mut state = StateExport::from_storage(storage).unwrap();
+        let key = Key::parse("migration_test/3").unwrap();
+        let entry = state.entries.iter_mut().find(|(k, _)| *k == key).unwrap();
+        entry.1 = 4_u64.serialize_to_vec();
+        assert!(matches!(
+            state.verify::<Sha256Hasher>(&app_hash),
+            Err(StateMigrationError::Invalid(_))
+        ));
+    }
+
+    /// Test the export of a block older than the last committed one, from a
+    /// persistent DB
+    #[test]
+    fn test_export_past_height() {
+        let db_dir = tempfile::tempdir().unwrap();
+        let db_path = db_dir.path().join("db");
+        let chain_id = ChainId::default();
+        let key = Key::parse("migration_test/0").unwrap();
+        let storage = storage::PersistentStorage::open(
+            &db_path,
+            chain_id.clone(),
+            address::nam(),
+            None,
+            None,
+        );
+        let mut wl_storage = WlStorage::new(WriteLog::default(), storage);
+        let mut app_hash = Hash::zero();
+        for height in 1..=2_u64 {
+            let height = BlockHeight(height);
+            wl_storage
+                .storage
+                .begin_block(BlockHash::default(), height)
+                .unwrap();
+            if height == BlockHeight(1) {
+                // Start a new epoch, as the exported state can't be in the
+                // first one
+                wl_storage.storage.block.epoch = Epoch(1);
+                wl_storage.storage.block.pred_epochs.new_epoch(height);
+            }
+            wl_storage
+                .write_bytes(&key, height.0.serialize_to_vec())
+                .unwrap();
+            wl_storage.commit_block().unwrap();
+            if height == BlockHeight(1) {
+                app_hash = Hash::from(wl_storage.storage.merkle_root());
+            }
+        }
+        drop(wl_storage);
+
+        let state = export_db::<storage::PersistentDB>(
+            &db_path,
+            chain_id,
+            address::nam(),
+            Some(BlockHeight(1)),
+        )
+        .unwrap();
+        assert_eq!(state.height, BlockHeight(1));
+        assert_eq!(state.epoch, Epoch(1));
+        assert!(state.entries.contains(&(key, 1_u64.serialize_to_vec())));
+        state
+            .verify::<storage::PersistentStorageHasher>(&app_hash)
+            .unwrap();
+
+        // The DB itself is not rolled back
+        let last_block = storage::PersistentDB::open(&db_path, None)
+            .read_last_block()
+            .unwrap()
+            .unwrap();
+        assert_eq!(last_block.height, BlockHeight(2));
+    }
+}
@@ -11,7 +11,6 @@ use itertools::Either;
 use lazy_static::lazy_static;
 use namada::core::types::ethereum_structs;
 use namada::eth_bridge::oracle::config::Config as OracleConfig;
-use namada::ledger::dry_run_tx;
 use namada::ledger::events::log::dumb_queries;
 use namada::ledger::queries::{
     EncodedResponseQuery, RequestCtx, RequestQuery, Router, RPC,
@@ -19,6 +18,7 @@ use namada::ledger::queries::{
 use namada::ledger::storage::{
     LastBlock, Sha256Hasher, EPOCH_SWITCH_BLOCKS_DELAY,
 };
+use namada::ledger::{dry_run_tx, estimate_fee, simulate_tx};
 use namada::proof_of_stake::pos_queries::PosQueries;
 use namada::proof_of_stake::types::WeightedValidator;
 use namada::proof_of_stake::{
@@ -592,12 +592,17 @@ impl<'a> Client for &'a MockNode {
         let ctx = RequestCtx {
             wl_storage: &borrowed.wl_storage,
             event_log: borrowed.event_log(),
+            key_change_log: borrowed.key_change_log(),
             vp_wasm_cache: borrowed.vp_wasm_cache.read_only(),
             tx_wasm_cache: borrowed.tx_wasm_cache.read_only(),
             storage_read_past_height_limit: None,
         };
         if request.path == "/shell/dry_run_tx" {
             dry_run_tx(ctx, &request)
+        } else if request.path == "/shell/simulate_tx" {
+            simulate_tx(ctx, &request)
+        } else if request.path == "/shell/estimate_fee" {
+            estimate_fee(ctx, &request)
         } else {
             rpc.handle(ctx, &request)
         }
@@ -0,0 +1,197 @@
+//! Software upgrades scheduled by governance
+//!
+//! An accepted upgrade proposal schedules an [`UpgradePlan`] at a block
+//! height. A node refuses to process the block at that height, halting the
+//! chain, unless its binary declares that it implements the upgrade in
+//! [`IMPLEMENTED_UPGRADES`].
+
+use namada::ledger::storage_api::governance as gov_api;
+use namada::types::hash::Hash;
+
+use super::*;
+
+/// A software upgrade scheduled by governance that a binary implements
+#[derive(Debug, Clone, Copy)]
+pub struct ImplementedUpgrade {
+    /// The name of the upgrade
+    pub name: &'static str,
+    /// The hash of the wasm code or state migration applied by the upgrade,
+    /// which must match the one of the upgrade plan
+    pub migration: Option<Hash>,
+}
+
+impl ImplementedUpgrade {
+    /// Check if this is the implementation of the given upgrade plan
+    fn implements(&self, plan: &UpgradePlan) -> bool {
+        self.name == plan.name && self.migration == plan.migration
+    }
+}
+
+/// The software upgrades implemented by this binary. The upgrades of the
+/// previous versions must be kept, so that a node can process the blocks
+/// from their height when syncing the chain.
+pub const IMPLEMENTED_UPGRADES: &[ImplementedUpgrade] = &[];
+
+/// Check that the given upgrades implement every upgrade scheduled at the
+/// given block height, before the block is processed
+pub fn check_scheduled_upgrades<S>(
+    storage: &S,
+    height: BlockHeight,
+    implemented: &[ImplementedUpgrade],
+) -> Result<()>
+where
+    S: StorageRead,
+{
+    for (id, plan) in gov_api::get_upgrade_plans(storage)? {
+        if plan.height != height
+            || implemented.iter().any(|upgrade| upgrade.implements(&plan))
+        {
+            continue;
+        }
+        tracing::error!(
+            "Upgrade required: the software upgrade {} scheduled by proposal \
+             {} isn't implemented by this binary.",
+            plan,
+            id
+        );
+        return Err(Error::UpgradeRequired(id, plan));
+    }
+    Ok(())
+}
+
+/// Remove the software upgrades scheduled up to the given block height, once
+/// the block has passed the check of [`check_scheduled_upgrades`], so that
+/// their names can be used by new upgrades
+pub fn remove_applied_upgrades<S>(
+    storage: &mut S,
+    height: BlockHeight,
+) -> Result<()>
+where
+    S: StorageRead + StorageWrite,
+{
+    for (id, plan) in gov_api::get_upgrade_plans(storage)? {
+        if plan.height <= height {
+            tracing::info!(
+                "Applied the software upgrade {} scheduled by proposal {}.",
+                plan,
+                id
+            );
+            gov_api::remove_upgrade(storage, id)?;
+        }
+    }
+    Ok(())
+}
+
+/// Schedule the software upgrade of an accepted proposal. The upgrade is
+/// rejected if its height has already been reached or if an upgrade with the
+/// same name is already scheduled.
+pub fn execute_upgrade_proposal<S>(
+    storage: &mut S,
+    id: u64,
+    plan: UpgradePlan,
+) -> Result<bool>
+where
+    S: StorageRead + StorageWrite,
+{
+    let current_height = storage.get_block_height()?;
+    if plan.height <= current_height {
+        tracing::info!(
+            "The software upgrade {} of proposal {} is past the current \
+             height {}.",
+            plan,
+            id,
+            current_height
+        );
+        return Ok(false);
+    }
+    let is_duplicate = gov_api::get_upgrade_plans(storage)?
+        .values()
+        .any(|scheduled| scheduled.name == plan.name);
+    if is_duplicate {
+        tracing::info!(
+            "A software upgrade named {} is already scheduled, the one of \
+             proposal {} is ignored.",
+            plan.name,
+            id
+        );
+        return Ok(false);
+    }
+
+    gov_api::schedule_upgrade(storage, id, &plan)?;
+    Ok(true)
+}
+
+#[cfg(test)]
+mod test_upgrades {
+    use namada::ledger::storage::testing::TestWlStorage;
+
+    use super::*;
+
+    #[test]
+    fn test_check_scheduled_upgrades() {
+        let mut storage = TestWlStorage::default();
+        let plan = UpgradePlan {
+            name: "v2".to_string(),
+            height: BlockHeight(100),
+            migration: Some(Hash([1; 32])),
+        };
+        assert!(
+            execute_upgrade_proposal(&mut storage, 0, plan.clone()).unwrap()
+        );
+        // The same upgrade can't be scheduled twice
+        assert!(
+            !execute_upgrade_proposal(&mut storage, 1, plan.clone()).unwrap()
+        );
+
+        let implemented = ImplementedUpgrade {
+            name: "v2",
+            migration: Some(Hash([1; 32])),
+        };
+        assert!(
+            check_scheduled_upgrades(&storage, BlockHeight(99), &[]).is_ok()
+        );
+        assert!(matches!(
+            check_scheduled_upgrades(&storage, BlockHeight(100), &[]),
+            Err(Error::UpgradeRequired(0, _))
+        ));
+        assert!(
+            check_scheduled_upgrades(
+                &storage,
+                BlockHeight(100),
+                &[implemented]
+            )
+            .is_ok()
+        );
+        // An implementation with another migration is a different upgrade
+        let other_migration = ImplementedUpgrade {
+            migration: None,
+            ..implemented
+        };
+        assert!(
+            check_scheduled_upgrades(
+                &storage,
+                BlockHeight(100),
+                &[other_migration]
+            )
+            .is_err()
+        );
+
+        // An upgrade can't be scheduled at a height already reached
+        storage.storage.block.height = BlockHeight(150);
+        let late = UpgradePlan {
+            name: "v3".to_string(),
+            height: BlockHeight(150),
+            migration: None,
+        };
+        assert!(!execute_upgrade_proposal(&mut storage, 2, late).unwrap());
+
+        // Once applied, an upgrade doesn't block its name anymore
+        remove_applied_upgrades(&mut storage, BlockHeight(150)).unwrap();
+        assert!(gov_api::get_upgrade_plans(&storage).unwrap().is_empty());
+        let again = UpgradePlan {
+            height: BlockHeight(200),
+            ..plan
+        };
+        assert!(execute_upgrade_proposal(&mut storage, 3, again).unwrap());
+    }
+}
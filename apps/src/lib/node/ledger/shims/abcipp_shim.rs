@@ -5,6 +5,7 @@ use std::pin::Pin;
 use std::task::{Context, Poll};
 
 use futures::future::FutureExt;
+use namada::ledger::storage::{DBIter, DB};
 use namada::proof_of_stake::find_validator_by_raw_hash;
 use namada::proto::Tx;
 use namada::types::hash::Hash;
@@ -26,13 +27,17 @@ use crate::facade::tendermint::v0_37::abci::{
 use crate::facade::tendermint_proto::v0_37::abci::ResponseDeliverTx;
 use crate::facade::tower_abci::BoxError;
 use crate::node::ledger::shell::{EthereumOracleChannels, Shell};
+use crate::node::ledger::{key_changes, storage};
 
 /// The shim wraps the shell, which implements ABCI++.
 /// The shim makes a crude translation between the ABCI interface currently used
 /// by tendermint and the shell's interface.
 #[derive(Debug)]
-pub struct AbcippShim {
-    service: Shell,
+pub struct AbcippShim<D = storage::PersistentDB>
+where
+    D: DB + for<'iter> DBIter<'iter> + Sync + 'static,
+{
+    service: Shell<D>,
     begin_block_request: Option<request::BeginBlock>,
     delivered_txs: Vec<TxBytes>,
     shell_recv: std::sync::mpsc::Receiver<(
@@ -41,7 +46,10 @@ pub struct AbcippShim {
     )>,
 }
 
-impl AbcippShim {
+impl<D> AbcippShim<D>
+where
+    D: DB + for<'iter> DBIter<'iter> + Sync + 'static,
+{
     /// Create a shell with a ABCI service that passes messages to and from the
     /// shell.
     #[allow(clippy::too_many_arguments)]
@@ -49,8 +57,9 @@ impl AbcippShim {
         config: config::Ledger,
         wasm_dir: PathBuf,
         broadcast_sender: UnboundedSender<Vec<u8>>,
+        key_changes_sender: Option<key_changes::Sender>,
         eth_oracle: Option<EthereumOracleChannels>,
-        db_cache: &rocksdb::Cache,
+        db_cache: &D::Cache,
         vp_wasm_compilation_cache: u64,
         tx_wasm_compilation_cache: u64,
     ) -> (Self, AbciService, broadcast::Sender<()>) {
@@ -66,6 +75,7 @@ impl AbcippShim {
                     config,
                     wasm_dir,
                     broadcast_sender,
+                    key_changes_sender,
                     eth_oracle,
                     Some(db_cache),
                     vp_wasm_compilation_cache,
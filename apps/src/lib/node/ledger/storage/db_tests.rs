@@ -0,0 +1,458 @@
+//! The test suite of the DB backends. Every backend runs these tests from its
+//! own test module, so that they all behave the same behind the `DB` trait.
+
+use std::path::Path;
+
+use namada::core::ledger::masp_conversions::ConversionState;
+use namada::ledger::storage::{
+    types, BlockStateWrite, DBIter, IndexedEvent, MerkleTree, Result,
+    Sha256Hasher, DB,
+};
+use namada::types::address::{gen_established_address, EstablishedAddressGen};
+use namada::types::internal::TxQueue;
+use namada::types::storage::{
+    BlockHash, BlockHeight, BlockResults, Epoch, Epochs, EthEventsQueue, Key,
+};
+use namada::types::time::DateTimeUtc;
+
+use super::DBRollback;
+
+/// Test that a block written can be loaded back from DB.
+pub fn test_load_state<D>(db: &mut D)
+where
+    D: DB + for<'iter> DBIter<'iter>,
+{
+    let mut batch = D::batch();
+    let last_height = BlockHeight::default();
+    db.batch_write_subspace_val(
+        &mut batch,
+        last_height,
+        &Key::parse("test").unwrap(),
+        vec![1_u8, 1, 1, 1],
+    )
+    .unwrap();
+
+    add_block_to_batch(
+        db,
+        &mut batch,
+        BlockHeight::default(),
+        Epoch::default(),
+        Epochs::default(),
+        &ConversionState::default(),
+    )
+    .unwrap();
+    db.exec_batch(batch).unwrap();
+
+    let _state = db
+        .read_last_block()
+        .expect("Should be able to read last block")
+        .expect("Block should have been written");
+}
+
+/// Test the reads of the latest values and of the values at past heights.
+pub fn test_read<D>(db: &mut D)
+where
+    D: DB + for<'iter> DBIter<'iter>,
+{
+    let key = Key::parse("test").unwrap();
+    let batch_key = Key::parse("batch").unwrap();
+
+    let mut batch = D::batch();
+    let last_height = BlockHeight(100);
+    db.batch_write_subspace_val(
+        &mut batch,
+        last_height,
+        &batch_key,
+        vec![1_u8, 1, 1, 1],
+    )
+    .unwrap();
+    db.exec_batch(batch).unwrap();
+
+    db.write_subspace_val(last_height, &key, vec![1_u8, 1, 1, 0])
+        .unwrap();
+
+    let mut batch = D::batch();
+    let last_height = BlockHeight(111);
+    db.batch_write_subspace_val(
+        &mut batch,
+        last_height,
+        &batch_key,
+        vec![2_u8, 2, 2, 2],
+    )
+    .unwrap();
+    db.exec_batch(batch).unwrap();
+
+    db.write_subspace_val(last_height, &key, vec![2_u8, 2, 2, 0])
+        .unwrap();
+
+    let prev_value = db
+        .read_subspace_val_with_height(
+            &batch_key,
+            BlockHeight(100),
+            last_height,
+        )
+        .expect("read should succeed");
+    assert_eq!(prev_value, Some(vec![1_u8, 1, 1, 1]));
+    let prev_value = db
+        .read_subspace_val_with_height(&key, BlockHeight(100), last_height)
+        .expect("read should succeed");
+    assert_eq!(prev_value, Some(vec![1_u8, 1, 1, 0]));
+
+    let updated_value = db
+        .read_subspace_val_with_height(
+            &batch_key,
+            BlockHeight(111),
+            last_height,
+        )
+        .expect("read should succeed");
+    assert_eq!(updated_value, Some(vec![2_u8, 2, 2, 2]));
+    let updated_value = db
+        .read_subspace_val_with_height(&key, BlockHeight(111), last_height)
+        .expect("read should succeed");
+    assert_eq!(updated_value, Some(vec![2_u8, 2, 2, 0]));
+
+    let latest_value = db
+        .read_subspace_val(&batch_key)
+        .expect("read should succeed");
+    assert_eq!(latest_value, Some(vec![2_u8, 2, 2, 2]));
+    let latest_value = db.read_subspace_val(&key).expect("read should succeed");
+    assert_eq!(latest_value, Some(vec![2_u8, 2, 2, 0]));
+
+    let mut batch = D::batch();
+    let last_height = BlockHeight(222);
+    db.batch_delete_subspace_val(&mut batch, last_height, &batch_key)
+        .unwrap();
+    db.exec_batch(batch).unwrap();
+
+    db.delete_subspace_val(last_height, &key).unwrap();
+
+    let deleted_value = db
+        .read_subspace_val_with_height(
+            &batch_key,
+            BlockHeight(222),
+            last_height,
+        )
+        .expect("read should succeed");
+    assert_eq!(deleted_value, None);
+    let deleted_value = db
+        .read_subspace_val_with_height(&key, BlockHeight(222), last_height)
+        .expect("read should succeed");
+    assert_eq!(deleted_value, None);
+
+    let latest_value = db
+        .read_subspace_val(&batch_key)
+        .expect("read should succeed");
+    assert_eq!(latest_value, None);
+    let latest_value = db.read_subspace_val(&key).expect("read should succeed");
+    assert_eq!(latest_value, None);
+}
+
+/// Test that the prefix iterator only yields the keys under the prefix, in
+/// order.
+pub fn test_prefix_iter<D>(db: &mut D)
+where
+    D: DB + for<'iter> DBIter<'iter>,
+{
+    let prefix_0 = Key::parse("0").unwrap();
+    let key_0_a = prefix_0.push(&"a".to_string()).unwrap();
+    let key_0_b = prefix_0.push(&"b".to_string()).unwrap();
+    let key_0_c = prefix_0.push(&"c".to_string()).unwrap();
+    let prefix_1 = Key::parse("1").unwrap();
+    let key_1_a = prefix_1.push(&"a".to_string()).unwrap();
+    let key_1_b = prefix_1.push(&"b".to_string()).unwrap();
+    let key_1_c = prefix_1.push(&"c".to_string()).unwrap();
+    let prefix_01 = Key::parse("01").unwrap();
+    let key_01_a = prefix_01.push(&"a".to_string()).unwrap();
+
+    let keys_0 = vec![key_0_a, key_0_b, key_0_c];
+    let keys_1 = vec![key_1_a, key_1_b, key_1_c];
+    let keys_01 = vec![key_01_a];
+    let all_keys = vec![keys_0.clone(), keys_01, keys_1.clone()].concat();
+
+    // Write the keys
+    let mut batch = D::batch();
+    let height = BlockHeight(1);
+    for key in &all_keys {
+        db.batch_write_subspace_val(&mut batch, height, key, [0_u8])
+            .unwrap();
+    }
+    db.exec_batch(batch).unwrap();
+
+    // Prefix "0" shouldn't match prefix "01"
+    let itered_keys: Vec<Key> = db
+        .iter_prefix(Some(&prefix_0))
+        .map(|(key, _val, _)| Key::parse(key).unwrap())
+        .collect();
+    itertools::assert_equal(keys_0, itered_keys);
+
+    let itered_keys: Vec<Key> = db
+        .iter_prefix(Some(&prefix_1))
+        .map(|(key, _val, _)| Key::parse(key).unwrap())
+        .collect();
+    itertools::assert_equal(keys_1, itered_keys);
+
+    let itered_keys: Vec<Key> = db
+        .iter_prefix(None)
+        .map(|(key, _val, _)| Key::parse(key).unwrap())
+        .collect();
+    itertools::assert_equal(all_keys, itered_keys);
+}
+
+/// Test that only the diffs of the pruned height are removed.
+pub fn test_prune_subspace_diffs<D>(db: &mut D)
+where
+    D: DB + for<'iter> DBIter<'iter>,
+{
+    let key = Key::parse("test").unwrap();
+    for height in [1_u64, 10, 11] {
+        db.write_subspace_val(BlockHeight(height), &key, [height as u8])
+            .unwrap();
+    }
+
+    let mut batch = D::batch();
+    db.prune_subspace_diffs(&mut batch, BlockHeight(1)).unwrap();
+    db.exec_batch(batch).unwrap();
+
+    // Only the diffs at height 1 are removed, not the ones at height 10 or 11
+    // that share the same digit prefix
+    assert_eq!(db.iter_new_diffs(BlockHeight(1), None).count(), 0);
+    assert_eq!(db.iter_new_diffs(BlockHeight(10), None).count(), 1);
+    assert_eq!(db.iter_new_diffs(BlockHeight(11), None).count(), 1);
+}
+
+/// Test that the events are read back by height and by index key, and that
+/// they are replaced and pruned together with their index entries.
+pub fn test_events<D>(db: &mut D)
+where
+    D: DB + for<'iter> DBIter<'iter>,
+{
+    let write_events = |db: &mut D, height: u64, hashes: &[&str]| {
+        let events: Vec<_> = hashes
+            .iter()
+            .map(|hash| IndexedEvent {
+                index_keys: vec![
+                    "applied/code/0".to_string(),
+                    format!("applied/hash/{hash}"),
+                ],
+                event: format!("{height}/{hash}").into_bytes(),
+            })
+            .collect();
+        let mut batch = D::batch();
+        db.batch_write_events(&mut batch, BlockHeight(height), &events)
+            .unwrap();
+        db.exec_batch(batch).unwrap();
+    };
+    let read_indexed = |db: &D, index_key: &str| -> Vec<String> {
+        db.read_indexed_events(index_key)
+            .unwrap()
+            .into_iter()
+            .map(|event| String::from_utf8(event).unwrap())
+            .collect()
+    };
+
+    write_events(db, 1, &["a", "b"]);
+    write_events(db, 2, &["c"]);
+    write_events(db, 3, &["a"]);
+
+    assert_eq!(
+        db.read_events(BlockHeight(1)).unwrap(),
+        vec![b"1/a".to_vec(), b"1/b".to_vec()]
+    );
+    // The most recent events come first
+    assert_eq!(read_indexed(db, "applied/hash/a"), vec!["3/a", "1/a"]);
+    assert_eq!(
+        read_indexed(db, "applied/code/0"),
+        vec!["3/a", "2/c", "1/b", "1/a"]
+    );
+    // A prefix of the index keys doesn't match any event
+    assert!(read_indexed(db, "applied/hash").is_empty());
+
+    // Writing the events of a height again replaces them
+    write_events(db, 3, &["d"]);
+    assert_eq!(read_indexed(db, "applied/hash/a"), vec!["1/a"]);
+    assert_eq!(read_indexed(db, "applied/hash/d"), vec!["3/d"]);
+
+    let mut batch = D::batch();
+    db.prune_events(&mut batch, BlockHeight(2)).unwrap();
+    db.exec_batch(batch).unwrap();
+    assert!(db.read_events(BlockHeight(1)).unwrap().is_empty());
+    assert!(read_indexed(db, "applied/hash/a").is_empty());
+    assert_eq!(read_indexed(db, "applied/code/0"), vec!["3/d", "2/c"]);
+}
+
+/// Test that a rollback restores the subspace and the block state of the
+/// target height.
+pub fn test_rollback<D>(db: &mut D)
+where
+    D: DB + DBRollback + for<'iter> DBIter<'iter>,
+{
+    // A key that's gonna be added on a second block
+    let add_key = Key::parse("add").unwrap();
+    // A key that's gonna be deleted on a second block
+    let delete_key = Key::parse("delete").unwrap();
+    // A key that's gonna be overwritten on a second block
+    let overwrite_key = Key::parse("overwrite").unwrap();
+
+    // Write first block
+    let mut batch = D::batch();
+    let height_0 = BlockHeight(100);
+    let mut pred_epochs = Epochs::default();
+    pred_epochs.new_epoch(height_0);
+    let mut conversion_state_0 = ConversionState::default();
+    conversion_state_0
+        .tokens
+        .insert("dummy1".to_string(), gen_established_address("test"));
+    let to_delete_val = vec![1_u8, 1, 0, 0];
+    let to_overwrite_val = vec![1_u8, 1, 1, 0];
+    db.batch_write_subspace_val(
+        &mut batch,
+        height_0,
+        &delete_key,
+        &to_delete_val,
+    )
+    .unwrap();
+    db.batch_write_subspace_val(
+        &mut batch,
+        height_0,
+        &overwrite_key,
+        &to_overwrite_val,
+    )
+    .unwrap();
+
+    add_block_to_batch(
+        db,
+        &mut batch,
+        height_0,
+        Epoch(1),
+        pred_epochs.clone(),
+        &conversion_state_0,
+    )
+    .unwrap();
+    db.exec_batch(batch).unwrap();
+
+    // Write second block
+    let mut batch = D::batch();
+    let height_1 = BlockHeight(101);
+    pred_epochs.new_epoch(height_1);
+    let mut conversion_state_1 = ConversionState::default();
+    conversion_state_1
+        .tokens
+        .insert("dummy2".to_string(), gen_established_address("test"));
+    let add_val = vec![1_u8, 0, 0, 0];
+    let overwrite_val = vec![1_u8, 1, 1, 1];
+    db.batch_write_subspace_val(&mut batch, height_1, &add_key, &add_val)
+        .unwrap();
+    db.batch_write_subspace_val(
+        &mut batch,
+        height_1,
+        &overwrite_key,
+        &overwrite_val,
+    )
+    .unwrap();
+    db.batch_delete_subspace_val(&mut batch, height_1, &delete_key)
+        .unwrap();
+
+    add_block_to_batch(
+        db,
+        &mut batch,
+        height_1,
+        Epoch(2),
+        pred_epochs,
+        &conversion_state_1,
+    )
+    .unwrap();
+    db.exec_batch(batch).unwrap();
+
+    // Check that the values are as expected from second block
+    let added = db.read_subspace_val(&add_key).unwrap();
+    assert_eq!(added, Some(add_val));
+    let overwritten = db.read_subspace_val(&overwrite_key).unwrap();
+    assert_eq!(overwritten, Some(overwrite_val));
+    let deleted = db.read_subspace_val(&delete_key).unwrap();
+    assert_eq!(deleted, None);
+
+    // Rollback to the first block height
+    db.rollback(height_0).unwrap();
+
+    // Check that the values are back to the state at the first block
+    let added = db.read_subspace_val(&add_key).unwrap();
+    assert_eq!(added, None);
+    let overwritten = db.read_subspace_val(&overwrite_key).unwrap();
+    assert_eq!(overwritten, Some(to_overwrite_val));
+    let deleted = db.read_subspace_val(&delete_key).unwrap();
+    assert_eq!(deleted, Some(to_delete_val));
+    // Check the block state
+    let last_block = db.read_last_block().unwrap().unwrap();
+    assert_eq!(last_block.height, height_0);
+    assert_eq!(
+        types::encode(&last_block.conversion_state),
+        types::encode(&conversion_state_0)
+    );
+    // Check that the last block's diffs have been removed
+    assert_eq!(db.iter_new_diffs(height_1, None).count(), 0);
+}
+
+/// Test that a checkpoint keeps the state from which it was taken.
+pub fn test_checkpoint<D>(db: &mut D, checkpoint_path: &Path)
+where
+    D: DB + for<'iter> DBIter<'iter>,
+{
+    let key = Key::parse("test").unwrap();
+    db.write_subspace_val(BlockHeight(1), &key, vec![1_u8])
+        .unwrap();
+    db.checkpoint(checkpoint_path).unwrap();
+    db.write_subspace_val(BlockHeight(2), &key, vec![2_u8])
+        .unwrap();
+
+    let checkpoint = D::open(checkpoint_path, None);
+    assert_eq!(
+        checkpoint.read_subspace_val(&key).unwrap(),
+        Some(vec![1_u8])
+    );
+    assert_eq!(db.read_subspace_val(&key).unwrap(), Some(vec![2_u8]));
+}
+
+/// A test helper to write a block
+pub fn add_block_to_batch<D>(
+    db: &D,
+    batch: &mut D::WriteBatch,
+    height: BlockHeight,
+    epoch: Epoch,
+    pred_epochs: Epochs,
+    conversion_state: &ConversionState,
+) -> Result<()>
+where
+    D: DB,
+{
+    let merkle_tree = MerkleTree::<Sha256Hasher>::default();
+    let merkle_tree_stores = merkle_tree.stores();
+    let hash = BlockHash::default();
+    let time = DateTimeUtc::now();
+    let next_epoch_min_start_height = BlockHeight::default();
+    let next_epoch_min_start_time = DateTimeUtc::now();
+    let update_epoch_blocks_delay = None;
+    let address_gen = EstablishedAddressGen::new("whatever");
+    let tx_queue = TxQueue::default();
+    let results = BlockResults::default();
+    let eth_events_queue = EthEventsQueue::default();
+    let block = BlockStateWrite {
+        merkle_tree_stores,
+        header: None,
+        hash: &hash,
+        height,
+        time,
+        epoch,
+        results: &results,
+        conversion_state,
+        pred_epochs: &pred_epochs,
+        next_epoch_min_start_height,
+        next_epoch_min_start_time,
+        update_epoch_blocks_delay,
+        address_gen: &address_gen,
+        tx_queue: &tx_queue,
+        ethereum_height: None,
+        eth_events_queue: &eth_events_queue,
+    };
+
+    db.add_block_to_batch(block, batch, true)
+}
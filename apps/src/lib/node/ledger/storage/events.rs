@@ -0,0 +1,67 @@
+//! The layout of the persistent event store, shared by the DB backends:
+//! - `height/{h}/{n}`: the `n`-th event emitted in the block at height `h`,
+//!   with the keys it's indexed by
+//! - `index/{index_key}/{h}/{n}`: an empty entry for every index key of the
+//!   `n`-th event emitted in the block at height `h`
+
+use namada::types::storage::{BlockHeight, KeySeg};
+
+/// The prefix of the events of all the heights
+pub(super) const EVENTS_HEIGHT_PREFIX: &str = "height/";
+
+/// The key of the `n`-th event emitted in the block at the given height
+pub(super) fn event_key(height: BlockHeight, n: u64) -> String {
+    format!("{}{}", events_height_prefix(height), n.raw())
+}
+
+/// The prefix of the events emitted in the block at the given height
+pub(super) fn events_height_prefix(height: BlockHeight) -> String {
+    format!("{EVENTS_HEIGHT_PREFIX}{}/", height.raw())
+}
+
+/// The key of an index entry of the `n`-th event emitted in the block at the
+/// given height
+pub(super) fn event_index_key(
+    index_key: &str,
+    height: BlockHeight,
+    n: u64,
+) -> String {
+    format!(
+        "{}{}/{}",
+        event_index_prefix(index_key),
+        height.raw(),
+        n.raw()
+    )
+}
+
+/// The prefix of the index entries of the given index key
+pub(super) fn event_index_prefix(index_key: &str) -> String {
+    format!("index/{index_key}/")
+}
+
+/// Parse the height and the index within the block of an event from the key
+/// of its entry
+pub(super) fn parse_event_key(key: &str) -> Option<(BlockHeight, u64)> {
+    let (height, n) =
+        key.strip_prefix(EVENTS_HEIGHT_PREFIX)?.split_once('/')?;
+    parse_height_and_index(height, n)
+}
+
+/// Parse the height and the index within the block of an event from the key
+/// of its index entry without the [`event_index_prefix`]. Returns `None` for
+/// the entries of longer index keys that share the same prefix.
+pub(super) fn parse_event_index_entry(
+    entry: &str,
+) -> Option<(BlockHeight, u64)> {
+    let (height, n) = entry.split_once('/')?;
+    if n.contains('/') {
+        return None;
+    }
+    parse_height_and_index(height, n)
+}
+
+fn parse_height_and_index(height: &str, n: &str) -> Option<(BlockHeight, u64)> {
+    let height = u64::parse(height.to_owned()).ok()?;
+    let n = u64::parse(n.to_owned()).ok()?;
+    Some((BlockHeight(height), n))
+}
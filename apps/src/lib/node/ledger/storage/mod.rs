@@ -1,7 +1,12 @@
 //! The storage module handles both the current state in-memory and the stored
 //! state in DB.
 
+#[cfg(test)]
+mod db_tests;
+mod events;
+mod redb;
 mod rocksdb;
+mod rollback;
 
 use std::fmt;
 
@@ -11,12 +16,16 @@ use arse_merkle_tree::H256;
 use blake2b_rs::{Blake2b, Blake2bBuilder};
 use namada::ledger::storage::traits::StorageHasher;
 use namada::ledger::storage::Storage;
+pub use rollback::{DBRollback, RollbackPlan};
 
 #[derive(Default)]
 pub struct PersistentStorageHasher(Blake2bHasher);
 
 pub type PersistentDB = rocksdb::RocksDB;
 
+/// The alternative DB backend, selected with the `storage_backend` config
+pub type PersistentRedbDB = redb::RedbDB;
+
 pub type PersistentStorage = Storage<PersistentDB, PersistentStorageHasher>;
 
 impl Hasher for PersistentStorageHasher {
@@ -55,11 +64,13 @@ mod tests {
 
     use itertools::Itertools;
     use namada::core::ledger::masp_conversions::update_allowed_conversions;
+    use namada::ledger::events::{Event, EventLevel, EventType};
     use namada::ledger::gas::STORAGE_ACCESS_GAS_PER_BYTE;
     use namada::ledger::ibc::storage::ibc_key;
     use namada::ledger::parameters::{EpochDuration, Parameters};
+    use namada::ledger::replay_protection;
     use namada::ledger::storage::write_log::WriteLog;
-    use namada::ledger::storage::{types, StoreType, WlStorage};
+    use namada::ledger::storage::{types, DBIter, StoreType, WlStorage};
     use namada::ledger::storage_api::{self, StorageWrite};
     use namada::types::chain::ChainId;
     use namada::types::hash::Hash;
@@ -631,6 +642,332 @@ mod tests {
         );
         let result = storage.get_merkle_tree(6.into(), Some(StoreType::Ibc));
         assert!(result.is_ok(), "The tree should be restored");
+
+        // The diffs of the pruned epoch should be pruned too
+        assert!(
+            storage.db.iter_new_diffs(1.into(), None).next().is_none(),
+            "The diffs at Height 1 should be pruned"
+        );
+        assert!(
+            storage.db.iter_new_diffs(6.into(), None).next().is_some(),
+            "The diffs at Height 6 should be kept"
+        );
+    }
+
+    /// Test that the stores of every epoch crossed by the minimum height since
+    /// the previous full commit are pruned, when the epochs are uneven
+    #[test]
+    fn test_prune_merkle_tree_stores_uneven_epochs() {
+        let db_path =
+            TempDir::new().expect("Unable to create a temporary DB directory");
+        let mut storage = PersistentStorage::open(
+            db_path.path(),
+            ChainId::default(),
+            address::nam(),
+            None,
+            Some(5),
+        );
+        // The epochs 1 and 2 are shorter than the limit, the epoch 3 is longer
+        for (height, key) in
+            [(1, "key1"), (3, "key2"), (5, "key3"), (20, "key4")]
+        {
+            let height = BlockHeight(height);
+            storage
+                .begin_block(BlockHash::default(), height)
+                .expect("begin_block failed");
+            let key = ibc_key(key).unwrap();
+            storage
+                .write(&key, types::encode(&height.0))
+                .expect("write failed");
+            storage.block.epoch = storage.block.epoch.next();
+            storage.block.pred_epochs.new_epoch(height);
+            let batch = PersistentStorage::batch();
+            storage.commit_block(batch).expect("commit failed");
+        }
+
+        for height in [1, 3] {
+            let result =
+                storage.get_merkle_tree(height.into(), Some(StoreType::Ibc));
+            assert!(result.is_err(), "The tree at Height {height} is pruned");
+            assert!(
+                storage
+                    .db
+                    .iter_new_diffs(height.into(), None)
+                    .next()
+                    .is_none(),
+                "The diffs at Height {height} should be pruned"
+            );
+        }
+        let result = storage.get_merkle_tree(5.into(), Some(StoreType::Ibc));
+        assert!(result.is_ok(), "The tree at Height 5 should be restored");
+        assert!(
+            storage.db.iter_new_diffs(5.into(), None).next().is_some(),
+            "The diffs at Height 5 should be kept"
+        );
+    }
+
+    /// Test that the events of a block are persisted only when it's committed
+    #[test]
+    fn test_commit_block_events() {
+        let db_path =
+            TempDir::new().expect("Unable to create a temporary DB directory");
+        let mut storage = PersistentStorage::open(
+            db_path.path(),
+            ChainId::default(),
+            address::nam(),
+            None,
+            None,
+        );
+        storage
+            .begin_block(BlockHash::default(), BlockHeight(1))
+            .expect("begin_block failed");
+        let event = Event {
+            event_type: EventType::Applied,
+            level: EventLevel::Tx,
+            attributes: HashMap::from([(
+                "hash".to_string(),
+                "DEADBEEF".to_string(),
+            )]),
+        };
+        storage.block_events = vec![event.to_indexed()];
+        assert!(storage.db.read_events(BlockHeight(1)).unwrap().is_empty());
+
+        let batch = PersistentStorage::batch();
+        storage.commit_block(batch).expect("commit failed");
+        assert_eq!(storage.db.read_events(BlockHeight(1)).unwrap().len(), 1);
+        assert!(storage.block_events.is_empty());
+    }
+
+    /// Test that no merkle tree store is pruned without a limit
+    #[test]
+    fn test_merkle_tree_stores_archive() {
+        let db_path =
+            TempDir::new().expect("Unable to create a temporary DB directory");
+        let mut storage = PersistentStorage::open(
+            db_path.path(),
+            ChainId::default(),
+            address::nam(),
+            None,
+            None,
+        );
+        for (height, key) in [(1, "key1"), (6, "key2"), (11, "key3")] {
+            let height = BlockHeight(height);
+            storage
+                .begin_block(BlockHash::default(), height)
+                .expect("begin_block failed");
+            let key = ibc_key(key).unwrap();
+            storage
+                .write(&key, types::encode(&height.0))
+                .expect("write failed");
+            storage.block.epoch = storage.block.epoch.next();
+            storage.block.pred_epochs.new_epoch(height);
+            let batch = PersistentStorage::batch();
+            storage.commit_block(batch).expect("commit failed");
+        }
+
+        for height in [1, 6, 11] {
+            let result = storage.get_merkle_tree(height.into(), None);
+            assert!(
+                result.is_ok(),
+                "The whole tree at Height {height} should be restored"
+            );
+        }
+    }
+
+    /// Test reads of a prefix at past block heights.
+    #[test]
+    fn test_read_prefix_with_height() {
+        let db_path =
+            TempDir::new().expect("Unable to create a temporary DB directory");
+        let mut storage = PersistentStorage::open(
+            db_path.path(),
+            ChainId::default(),
+            address::nam(),
+            None,
+            None,
+        );
+
+        let prefix = Key::parse("prefix").unwrap();
+        let key_a = prefix.push(&"a".to_string()).unwrap();
+        let key_b = prefix.push(&"b".to_string()).unwrap();
+        let key_c = prefix.push(&"c".to_string()).unwrap();
+        let mismatched_key = Key::parse("different/a").unwrap();
+
+        // Height 1: write `a` and `b`
+        storage
+            .begin_block(BlockHash::default(), BlockHeight(1))
+            .expect("begin_block failed");
+        storage.write(&key_a, types::encode(&1_u64)).unwrap();
+        storage.write(&key_b, types::encode(&1_u64)).unwrap();
+        storage
+            .write(&mismatched_key, types::encode(&1_u64))
+            .unwrap();
+        storage.commit_block(PersistentStorage::batch()).unwrap();
+
+        // Height 2: delete `a`, update `b` and write `c`
+        storage
+            .begin_block(BlockHash::default(), BlockHeight(2))
+            .expect("begin_block failed");
+        storage.delete(&key_a).unwrap();
+        storage.write(&key_b, types::encode(&2_u64)).unwrap();
+        storage.write(&key_c, types::encode(&2_u64)).unwrap();
+        storage.commit_block(PersistentStorage::batch()).unwrap();
+
+        // Height 3: write `a` again
+        storage
+            .begin_block(BlockHash::default(), BlockHeight(3))
+            .expect("begin_block failed");
+        storage.write(&key_a, types::encode(&3_u64)).unwrap();
+        storage.commit_block(PersistentStorage::batch()).unwrap();
+
+        let read_prefix = |height: u64| -> Vec<(Key, u64)> {
+            storage
+                .read_prefix_with_height(&prefix, BlockHeight(height))
+                .unwrap()
+                .into_iter()
+                .map(|storage::PrefixValue { key, value }| {
+                    (key, types::decode(value).unwrap())
+                })
+                .collect()
+        };
+        assert_eq!(
+            read_prefix(1),
+            vec![(key_a.clone(), 1), (key_b.clone(), 1)]
+        );
+        assert_eq!(
+            read_prefix(2),
+            vec![(key_b.clone(), 2), (key_c.clone(), 2)]
+        );
+        let expected = vec![(key_a, 3), (key_b, 2), (key_c, 2)];
+        assert_eq!(read_prefix(3), expected);
+        // `0` means the last committed height
+        assert_eq!(read_prefix(0), expected);
+    }
+
+    /// Test a rollback of multiple blocks, restoring the subspace, the
+    /// replay protection entries and the merkle tree.
+    #[test]
+    fn test_rollback_multiple_blocks() {
+        /// Commit the block with a tx hash, moving the tx hashes of the
+        /// previous block to the replay protection entries of all the blocks
+        fn commit_block(storage: &mut PersistentStorage, tx_hash: &Hash) {
+            let mut batch = PersistentStorage::batch();
+            let last_hashes: Vec<Hash> =
+                storage.iter_replay_protection().collect();
+            for hash in last_hashes {
+                storage
+                    .write_replay_protection_entry(
+                        &mut batch,
+                        &replay_protection::get_replay_protection_all_subkey(
+                            &hash,
+                        ),
+                    )
+                    .unwrap();
+                storage
+                    .delete_replay_protection_entry(
+                        &mut batch,
+                        &replay_protection::get_replay_protection_last_subkey(
+                            &hash,
+                        ),
+                    )
+                    .unwrap();
+            }
+            storage
+                .write_replay_protection_entry(
+                    &mut batch,
+                    &replay_protection::get_replay_protection_last_subkey(
+                        tx_hash,
+                    ),
+                )
+                .unwrap();
+            storage.commit_block(batch).expect("commit failed");
+        }
+
+        let db_path =
+            TempDir::new().expect("Unable to create a temporary DB directory");
+        let mut storage = PersistentStorage::open(
+            db_path.path(),
+            ChainId::default(),
+            address::nam(),
+            None,
+            None,
+        );
+        let key_a = Key::parse("a").unwrap();
+        let key_b = Key::parse("b").unwrap();
+        let tx_hashes: Vec<Hash> = (1..=3_u8).map(|i| Hash([i; 32])).collect();
+
+        // Height 1: write `a`
+        storage
+            .begin_block(BlockHash::default(), BlockHeight(1))
+            .expect("begin_block failed");
+        storage.write(&key_a, types::encode(&1_u64)).unwrap();
+        commit_block(&mut storage, &tx_hashes[0]);
+        let root = storage.merkle_root();
+
+        // Height 2: update `a` and write `b`
+        storage
+            .begin_block(BlockHash::default(), BlockHeight(2))
+            .expect("begin_block failed");
+        storage.write(&key_a, types::encode(&2_u64)).unwrap();
+        storage.write(&key_b, types::encode(&2_u64)).unwrap();
+        commit_block(&mut storage, &tx_hashes[1]);
+
+        // Height 3: delete `a` and update `b`
+        storage
+            .begin_block(BlockHash::default(), BlockHeight(3))
+            .expect("begin_block failed");
+        storage.delete(&key_a).unwrap();
+        storage.write(&key_b, types::encode(&3_u64)).unwrap();
+        commit_block(&mut storage, &tx_hashes[2]);
+
+        // A height above the last one cannot be rolled back to
+        assert!(storage.db.plan_rollback(BlockHeight(4)).is_err());
+
+        let plan = storage.db.plan_rollback(BlockHeight(1)).unwrap();
+        assert_eq!(plan.last_height, BlockHeight(3));
+        assert_eq!(
+            plan.subspace.into_iter().collect::<Vec<_>>(),
+            vec![
+                (key_a.to_string(), Some(types::encode(&1_u64))),
+                (key_b.to_string(), None)
+            ]
+        );
+        assert_eq!(plan.last_tx_hashes, vec![tx_hashes[0].to_string()]);
+        assert_eq!(
+            plan.removed_tx_hashes,
+            vec![tx_hashes[0].to_string(), tx_hashes[1].to_string()]
+        );
+
+        storage.db.rollback(BlockHeight(1)).unwrap();
+        drop(storage);
+
+        // Load the rolled back state
+        let mut storage = PersistentStorage::open(
+            db_path.path(),
+            ChainId::default(),
+            address::nam(),
+            None,
+            None,
+        );
+        storage
+            .load_last_state()
+            .expect("loading the last state failed");
+        let (loaded_root, height) =
+            storage.get_state().expect("no block exists");
+        assert_eq!(loaded_root.0, root.0);
+        assert_eq!(height, 1);
+        let (val, _) = storage.read(&key_a).expect("read failed");
+        assert_eq!(val, Some(types::encode(&1_u64)));
+        let (val, _) = storage.read(&key_b).expect("read failed");
+        assert_eq!(val, None);
+        assert_eq!(
+            storage.iter_replay_protection().collect::<Vec<_>>(),
+            vec![tx_hashes[0]]
+        );
+        assert_eq!(storage.iter_all_replay_protection().count(), 0);
+        // The diffs after the target height are removed
+        assert!(storage.db.iter_old_diffs(3.into(), None).next().is_none());
+        assert!(storage.db.iter_new_diffs(2.into(), None).next().is_none());
     }
 
     /// Test the prefix iterator with RocksDB.
@@ -0,0 +1,1641 @@
+//! The persistent storage in redb.
+//!
+//! redb is an embedded copy-on-write B-tree store. Compared to RocksDB, it
+//! has no write-ahead log or background compactions, which trades a higher
+//! write amplification for lower and more predictable read latency.
+//!
+//! The storage layout is the same as the one used for RocksDB (see the
+//! `rocksdb` module), with every column family mapped onto a redb table:
+//! - `state`: the latest ledger state
+//! - `subspace`: accounts sub-spaces
+//! - `diffs`: diffs in account subspaces' key-vals
+//! - `block`: block state
+//! - `replay_protection`: hashes of processed tx
+//! - `events`: events emitted in the blocks
+
+use std::collections::{BTreeMap, VecDeque};
+use std::fmt::Display;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::ops::Bound;
+use std::path::Path;
+use std::str::FromStr;
+
+use borsh::BorshDeserialize;
+use borsh_ext::BorshSerializeExt;
+use data_encoding::HEXLOWER;
+use itertools::Either;
+use namada::core::ledger::masp_conversions::ConversionState;
+use namada::core::types::ethereum_structs;
+use namada::ledger::storage::merkle_tree::{
+    base_tree_key_prefix, subtree_key_prefix,
+};
+use namada::ledger::storage::{
+    types, BlockStateRead, BlockStateWrite, DBIter, DBWriteBatch, Error,
+    IndexedEvent, MerkleTreeStoresRead, Result, StoreType, DB,
+};
+use namada::types::internal::TxQueue;
+use namada::types::storage::{
+    BlockHeight, BlockResults, Epoch, EthEventsQueue, Header, Key, KeySeg,
+    KEY_SEGMENT_SEPARATOR,
+};
+use namada::types::time::DateTimeUtc;
+use rayon::prelude::*;
+use redb::{ReadableTable, TableDefinition};
+
+use super::events::{
+    event_index_key, event_index_prefix, event_key, events_height_prefix,
+    parse_event_index_entry, parse_event_key, EVENTS_HEIGHT_PREFIX,
+};
+use super::rollback::{
+    block_keys, check_rollback_target, legacy_pred_key, not_retained_error,
+    read_legacy_state, replay_protection_diff_prefix, rollback_subspace,
+    state_diff_key, DBRollback, RollbackPlan, CONVERSION_STATE_KEY,
+    LEGACY_PRED_STATE_KEYS, ROLLBACK_STATE_KEYS,
+};
+
+/// The name of the database file inside of the DB directory
+const DB_FILE_NAME: &str = "namada.redb";
+
+/// The number of entries that a prefix iterator reads from the DB at once
+const ITER_CHUNK_SIZE: usize = 1024;
+
+/// The tables of the DB, equivalent to RocksDB's column families
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Table {
+    Subspace,
+    Diffs,
+    State,
+    Block,
+    ReplayProtection,
+    Events,
+}
+
+impl Table {
+    /// All the tables, in the order in which the batch writes are applied
+    const ALL: [Table; 6] = [
+        Table::Subspace,
+        Table::Diffs,
+        Table::State,
+        Table::Block,
+        Table::ReplayProtection,
+        Table::Events,
+    ];
+
+    fn definition(
+        self,
+    ) -> TableDefinition<'static, &'static str, &'static [u8]> {
+        let name = match self {
+            Table::Subspace => "subspace",
+            Table::Diffs => "diffs",
+            Table::State => "state",
+            Table::Block => "block",
+            Table::ReplayProtection => "replay_protection",
+            Table::Events => "events",
+        };
+        TableDefinition::new(name)
+    }
+}
+
+/// redb handle
+#[derive(Debug)]
+pub struct RedbDB(redb::Database);
+
+/// DB Handle for batch writes. The operations are applied in a single write
+/// transaction on [`DB::exec_batch`].
+#[derive(Debug, Default)]
+pub struct RedbWriteBatch(Vec<BatchOp>);
+
+#[derive(Debug)]
+enum BatchOp {
+    Put {
+        table: Table,
+        key: String,
+        value: Vec<u8>,
+    },
+    Delete {
+        table: Table,
+        key: String,
+    },
+    /// Delete all the keys starting with the prefix
+    DeletePrefix {
+        table: Table,
+        prefix: String,
+    },
+}
+
+impl RedbWriteBatch {
+    fn put(
+        &mut self,
+        table: Table,
+        key: impl Into<String>,
+        value: impl Into<Vec<u8>>,
+    ) {
+        self.0.push(BatchOp::Put {
+            table,
+            key: key.into(),
+            value: value.into(),
+        })
+    }
+
+    fn delete(&mut self, table: Table, key: impl Into<String>) {
+        self.0.push(BatchOp::Delete {
+            table,
+            key: key.into(),
+        })
+    }
+
+    fn delete_prefix(&mut self, table: Table, prefix: impl Into<String>) {
+        self.0.push(BatchOp::DeletePrefix {
+            table,
+            prefix: prefix.into(),
+        })
+    }
+}
+
+/// Open redb for the DB. The `cache` is the size of the page cache in bytes.
+pub fn open(path: impl AsRef<Path>, cache: Option<&usize>) -> Result<RedbDB> {
+    let path = path.as_ref();
+    std::fs::create_dir_all(path).map_err(|e| {
+        Error::DBError(format!(
+            "Cannot create the DB directory {}: {e}",
+            path.to_string_lossy()
+        ))
+    })?;
+
+    let mut builder = redb::Database::builder();
+    if let Some(cache) = cache {
+        builder.set_cache_size(*cache);
+    }
+    let db = builder.create(path.join(DB_FILE_NAME)).map_err(db_error)?;
+
+    // Read transactions fail on tables that don't exist, so we create all of
+    // them up-front
+    let txn = db.begin_write().map_err(db_error)?;
+    for table in Table::ALL {
+        txn.open_table(table.definition()).map_err(db_error)?;
+    }
+    txn.commit().map_err(db_error)?;
+
+    Ok(RedbDB(db))
+}
+
+fn db_error(error: impl Display) -> Error {
+    Error::DBError(error.to_string())
+}
+
+impl RedbDB {
+    /// Read a value from the given table
+    fn read_value(&self, table: Table, key: &str) -> Result<Option<Vec<u8>>> {
+        let read = || -> std::result::Result<_, redb::Error> {
+            let txn = self.0.begin_read()?;
+            let table = txn.open_table(table.definition())?;
+            let value = table.get(key)?.map(|value| value.value().to_vec());
+            Ok(value)
+        };
+        read().map_err(db_error)
+    }
+
+    /// Read at most `limit` entries of the given table whose keys start with
+    /// the `prefix`, from the `from` bound on.
+    fn read_prefix_chunk(
+        &self,
+        table: Table,
+        prefix: &str,
+        from: Bound<&str>,
+        limit: usize,
+    ) -> Result<Vec<(String, Vec<u8>)>> {
+        let read = || -> std::result::Result<_, redb::Error> {
+            let txn = self.0.begin_read()?;
+            let table = txn.open_table(table.definition())?;
+            let mut entries = Vec::new();
+            for entry in table.range::<&str>((from, Bound::Unbounded))? {
+                let (key, value) = entry?;
+                let key = key.value();
+                if !key.starts_with(prefix) || entries.len() == limit {
+                    break;
+                }
+                entries.push((key.to_owned(), value.value().to_vec()));
+            }
+            Ok(entries)
+        };
+        read().map_err(db_error)
+    }
+
+    /// Write the given ops in a single transaction
+    fn write_ops(&self, ops: Vec<BatchOp>) -> Result<()> {
+        let write = || -> std::result::Result<_, redb::Error> {
+            let txn = self.0.begin_write()?;
+            // A table can only be opened once at a time in a transaction. The
+            // ops on different tables are independent, so we apply them table
+            // by table, preserving the order within each table.
+            for current in Table::ALL {
+                let mut table = txn.open_table(current.definition())?;
+                for op in &ops {
+                    match op {
+                        BatchOp::Put {
+                            table: t,
+                            key,
+                            value,
+                        } if *t == current => {
+                            table.insert(key.as_str(), value.as_slice())?;
+                        }
+                        BatchOp::Delete { table: t, key } if *t == current => {
+                            table.remove(key.as_str())?;
+                        }
+                        BatchOp::DeletePrefix { table: t, prefix }
+                            if *t == current =>
+                        {
+                            match prefix_upper_bound(prefix) {
+                                Some(upper) => table
+                                    .drain::<&str>(
+                                        prefix.as_str()..upper.as_str(),
+                                    )?
+                                    .for_each(drop),
+                                None => table.drain::<&str>(..)?.for_each(drop),
+                            }
+                        }
+                        _ => {}
+                    }
+                }
+            }
+            txn.commit()?;
+            Ok(())
+        };
+        write().map_err(db_error)
+    }
+
+    /// Persist the diff of an account subspace key-val under the height where
+    /// it was changed in a batch write.
+    fn batch_write_subspace_diff(
+        &self,
+        batch: &mut RedbWriteBatch,
+        height: BlockHeight,
+        key: &Key,
+        old_value: Option<&[u8]>,
+        new_value: Option<&[u8]>,
+    ) -> Result<()> {
+        if let Some(old_value) = old_value {
+            batch.put(Table::Diffs, diff_key(height, "old", key)?, old_value);
+        }
+        if let Some(new_value) = new_value {
+            batch.put(Table::Diffs, diff_key(height, "new", key)?, new_value);
+        }
+        Ok(())
+    }
+
+    /// Dump last known block
+    pub fn dump_block(
+        &self,
+        out_file_path: std::path::PathBuf,
+        historic: bool,
+        height: Option<BlockHeight>,
+    ) {
+        // Find the last block height
+        let last_height: BlockHeight = types::decode(
+            self.read_value(Table::State, "height")
+                .expect("Unable to read DB")
+                .expect("No block height found"),
+        )
+        .expect("Unable to decode block height");
+
+        let height = height.unwrap_or(last_height);
+
+        let full_path = out_file_path
+            .with_file_name(format!(
+                "{}_{height}",
+                out_file_path
+                    .file_name()
+                    .map(|name| name.to_string_lossy().into_owned())
+                    .unwrap_or_else(|| "dump_db".to_string())
+            ))
+            .with_extension("toml");
+
+        let mut file = File::options()
+            .append(true)
+            .create_new(true)
+            .open(&full_path)
+            .expect("Cannot open the output file");
+
+        println!("Will write to {} ...", full_path.to_string_lossy());
+
+        if historic {
+            // Dump the keys prepended with the selected block height (includes
+            // subspace diff keys)
+            let prefix = format!("{}/", height.raw());
+            self.dump_it(Table::Diffs, prefix.clone(), &mut file);
+            self.dump_it(Table::Block, prefix, &mut file);
+        }
+
+        // subspace
+        if height != last_height {
+            // Restoring subspace at specified height
+            let restored_subspace = self
+                .iter_prefix(None)
+                .par_bridge()
+                .fold(
+                    || "".to_string(),
+                    |mut cur, (key, _value, _gas)| match self
+                        .read_subspace_val_with_height(
+                            &Key::from(key.to_db_key()),
+                            height,
+                            last_height,
+                        )
+                        .expect("Unable to find subspace key")
+                    {
+                        Some(value) => {
+                            let val = HEXLOWER.encode(&value);
+                            let new_line = format!("\"{key}\" = \"{val}\"\n");
+                            cur.push_str(new_line.as_str());
+                            cur
+                        }
+                        None => cur,
+                    },
+                )
+                .reduce(
+                    || "".to_string(),
+                    |mut a: String, b: String| {
+                        a.push_str(&b);
+                        a
+                    },
+                );
+            file.write_all(restored_subspace.as_bytes())
+                .expect("Unable to write to output file");
+        } else {
+            // Just dump the current subspace
+            self.dump_it(Table::Subspace, String::default(), &mut file);
+        }
+
+        // replay protection
+        // Dump of replay protection keys is possible only at the last height or
+        // the previous one
+        if height == last_height {
+            self.dump_it(Table::ReplayProtection, String::default(), &mut file);
+        } else if height == last_height - 1 {
+            self.dump_it(Table::ReplayProtection, "all".to_string(), &mut file);
+        }
+
+        println!("Done writing to {}", full_path.to_string_lossy());
+    }
+
+    /// Dump data
+    fn dump_it(&self, table: Table, prefix: String, file: &mut File) {
+        let mut buf = BufWriter::new(file);
+        // Empty string to prevent prefix stripping
+        for (key, raw_val, _gas) in
+            RedbPrefixIterator::new(self, table, prefix, String::default())
+        {
+            let val = HEXLOWER.encode(&raw_val);
+            let bytes = format!("\"{key}\" = \"{val}\"\n");
+            buf.write_all(bytes.as_bytes())
+                .expect("Unable to write to buffer");
+        }
+        buf.flush().expect("Unable to write to output file");
+    }
+
+    /// Read the hashes of the txs included in the block at the given height
+    /// that have been recorded for rollback
+    fn read_recorded_tx_hashes(&self, height: BlockHeight) -> Vec<String> {
+        let prefix = replay_protection_diff_prefix(height);
+        RedbPrefixIterator::new(self, Table::Diffs, prefix.clone(), prefix)
+            .map(|(hash, _, _)| hash)
+            .collect()
+    }
+
+    /// Batch delete the given entries of events with their index entries
+    fn batch_delete_events(
+        &self,
+        batch: &mut RedbWriteBatch,
+        events: impl IntoIterator<Item = (String, Vec<u8>, u64)>,
+    ) -> Result<()> {
+        for (key, value, _) in events {
+            let event: IndexedEvent =
+                types::decode(value).map_err(Error::CodingError)?;
+            let (height, n) = parse_event_key(&key).ok_or_else(|| {
+                Error::DBError(format!("Invalid event key {key}"))
+            })?;
+            for index_key in event.index_keys {
+                batch.delete(
+                    Table::Events,
+                    event_index_key(&index_key, height, n),
+                );
+            }
+            batch.delete(Table::Events, key);
+        }
+        Ok(())
+    }
+}
+
+impl DBRollback for RedbDB {
+    fn plan_rollback(
+        &self,
+        target_height: BlockHeight,
+    ) -> Result<RollbackPlan> {
+        let last_block = self.read_last_block()?.ok_or(Error::DBError(
+            "Missing last block in storage".to_string(),
+        ))?;
+        check_rollback_target(target_height, last_block.height)?;
+        let target_epoch = last_block
+            .pred_epochs
+            .get_epoch(target_height)
+            .ok_or_else(|| not_retained_error(target_height, "epoch"))?;
+        let mut plan = RollbackPlan {
+            last_height: last_block.height,
+            last_epoch: last_block.epoch,
+            target_height,
+            target_epoch,
+            state: vec![],
+            subspace: BTreeMap::new(),
+            last_tx_hashes: vec![],
+            removed_tx_hashes: vec![],
+        };
+        if plan.is_empty() {
+            return Ok(plan);
+        }
+
+        let read_legacy = |state_key: &str| {
+            read_legacy_state(
+                target_height,
+                last_block.height,
+                state_key,
+                |key| self.read_value(Table::State, key),
+            )
+        };
+        for state_key in ROLLBACK_STATE_KEYS {
+            let value = match self.read_value(
+                Table::Diffs,
+                &state_diff_key(target_height, state_key),
+            )? {
+                Some(value) => Some(value),
+                None => read_legacy(state_key)?,
+            }
+            .ok_or_else(|| not_retained_error(target_height, "block state"))?;
+            plan.state.push((state_key, value));
+        }
+        if target_epoch != last_block.epoch {
+            // The conversion state is written on the full commit at the
+            // start of an epoch
+            let epoch_start = last_block
+                .pred_epochs
+                .get_start_height_of_epoch(target_epoch)
+                .unwrap_or_default();
+            let conversion_state = (epoch_start.0..=target_height.0)
+                .rev()
+                .find_map(|raw_height| {
+                    self.read_value(
+                        Table::Diffs,
+                        &state_diff_key(
+                            BlockHeight(raw_height),
+                            CONVERSION_STATE_KEY,
+                        ),
+                    )
+                    .transpose()
+                })
+                .transpose()?;
+            let conversion_state = match conversion_state {
+                Some(value) => Some(value),
+                None => read_legacy(CONVERSION_STATE_KEY)?,
+            }
+            .ok_or_else(|| {
+                not_retained_error(target_height, "conversion state")
+            })?;
+            plan.state.push((CONVERSION_STATE_KEY, conversion_state));
+
+            // The merkle tree at the target height is restored from the
+            // subtree stores of its epoch
+            for st in StoreType::iter_subtrees() {
+                let root_key = subtree_key_prefix(st, target_epoch)
+                    .with_segment("root".to_owned());
+                if self
+                    .read_value(Table::Block, &root_key.to_string())?
+                    .is_none()
+                {
+                    return Err(not_retained_error(
+                        target_height,
+                        "merkle tree",
+                    ));
+                }
+            }
+        }
+
+        plan.subspace =
+            rollback_subspace(self, target_height, last_block.height)?;
+        plan.last_tx_hashes = self.read_recorded_tx_hashes(target_height);
+        for raw_height in target_height.0..last_block.height.0 {
+            plan.removed_tx_hashes
+                .extend(self.read_recorded_tx_hashes(BlockHeight(raw_height)));
+        }
+        Ok(plan)
+    }
+
+    fn apply_rollback(&mut self, plan: RollbackPlan) -> Result<()> {
+        if plan.is_empty() {
+            tracing::info!(
+                "Namada height already matches the rollback height, no need \
+                 to rollback."
+            );
+            return Ok(());
+        }
+        let RollbackPlan {
+            last_height,
+            last_epoch,
+            target_height,
+            target_epoch,
+            state,
+            subspace,
+            last_tx_hashes,
+            removed_tx_hashes,
+        } = plan;
+        let mut batch = RedbWriteBatch::default();
+
+        tracing::info!("Restoring the block state of height {target_height}");
+        batch.put(Table::State, "height", types::encode(&target_height));
+        for (key, value) in state {
+            batch.put(Table::State, key, value);
+        }
+        for state_key in LEGACY_PRED_STATE_KEYS {
+            batch.delete(Table::State, legacy_pred_key(state_key));
+        }
+
+        tracing::info!("Restoring {} subspace keys", subspace.len());
+        for (key, value) in subspace {
+            match value {
+                Some(value) => batch.put(Table::Subspace, key, value),
+                None => batch.delete(Table::Subspace, key),
+            }
+        }
+
+        tracing::info!("Restoring replay protection hashes");
+        batch.delete_prefix(Table::ReplayProtection, "last/");
+        for hash in removed_tx_hashes {
+            batch.delete(Table::ReplayProtection, format!("all/{hash}"));
+        }
+        let recorded_prefix = replay_protection_diff_prefix(target_height);
+        for hash in last_tx_hashes {
+            batch.put(Table::ReplayProtection, format!("last/{hash}"), vec![]);
+            batch.delete(Table::Diffs, format!("{recorded_prefix}{hash}"));
+        }
+
+        tracing::info!("Deleting the blocks after height {target_height}");
+        for raw_height in target_height.0 + 1..=last_height.0 {
+            let height = BlockHeight(raw_height);
+            for key in block_keys(height) {
+                batch.delete(Table::Block, key);
+            }
+            self.prune_subspace_diffs(&mut batch, height)?;
+        }
+        for epoch in
+            Epoch::iter_bounds_inclusive(target_epoch.next(), last_epoch)
+        {
+            for st in StoreType::iter_subtrees() {
+                self.prune_merkle_tree_store(&mut batch, st, epoch)?;
+            }
+        }
+
+        // Write the batch and persist changes to disk
+        tracing::info!("Flushing restored state to disk");
+        self.write_ops(batch.0)
+    }
+}
+
+impl DB for RedbDB {
+    type Cache = usize;
+    type WriteBatch = RedbWriteBatch;
+
+    fn open(
+        db_path: impl AsRef<std::path::Path>,
+        cache: Option<&Self::Cache>,
+    ) -> Self {
+        open(db_path, cache).expect("cannot open the DB")
+    }
+
+    fn read_last_block(&self) -> Result<Option<BlockStateRead>> {
+        /// Read and decode a value from the state table, logging an error if
+        /// it's missing
+        fn read_state<T: BorshDeserialize>(
+            db: &RedbDB,
+            key: &str,
+            what: &str,
+        ) -> Result<Option<T>> {
+            match db.read_value(Table::State, key)? {
+                Some(bytes) => {
+                    types::decode(bytes).map(Some).map_err(Error::CodingError)
+                }
+                None => {
+                    tracing::error!("Couldn't load {what} from the DB");
+                    Ok(None)
+                }
+            }
+        }
+
+        // Block height
+        let height: BlockHeight = match self
+            .read_value(Table::State, "height")?
+        {
+            Some(bytes) => types::decode(bytes).map_err(Error::CodingError)?,
+            None => return Ok(None),
+        };
+
+        // Block results
+        let results_path = format!("results/{}", height.raw());
+        let results: BlockResults = match self
+            .read_value(Table::Block, &results_path)?
+        {
+            Some(bytes) => types::decode(bytes).map_err(Error::CodingError)?,
+            None => return Ok(None),
+        };
+
+        // Epoch start height and time
+        let Some(next_epoch_min_start_height) = read_state::<BlockHeight>(
+            self,
+            "next_epoch_min_start_height",
+            "next epoch start height",
+        )?
+        else {
+            return Ok(None);
+        };
+        let Some(next_epoch_min_start_time) = read_state::<DateTimeUtc>(
+            self,
+            "next_epoch_min_start_time",
+            "next epoch start time",
+        )?
+        else {
+            return Ok(None);
+        };
+        let Some(update_epoch_blocks_delay) = read_state::<Option<u32>>(
+            self,
+            "update_epoch_blocks_delay",
+            "epoch update block delay",
+        )?
+        else {
+            return Ok(None);
+        };
+        let Some(conversion_state) = read_state::<ConversionState>(
+            self,
+            "conversion_state",
+            "conversion state",
+        )?
+        else {
+            return Ok(None);
+        };
+        let Some(tx_queue) =
+            read_state::<TxQueue>(self, "tx_queue", "tx queue")?
+        else {
+            return Ok(None);
+        };
+        let Some(ethereum_height) =
+            read_state::<Option<ethereum_structs::BlockHeight>>(
+                self,
+                "ethereum_height",
+                "ethereum height",
+            )?
+        else {
+            return Ok(None);
+        };
+        let Some(eth_events_queue) = read_state::<EthEventsQueue>(
+            self,
+            "eth_events_queue",
+            "the eth events queue",
+        )?
+        else {
+            return Ok(None);
+        };
+
+        // Load data at the height
+        let prefix = format!("{}/", height.raw());
+        let mut merkle_tree_stores = MerkleTreeStoresRead::default();
+        let mut hash = None;
+        let mut time = None;
+        let mut epoch: Option<Epoch> = None;
+        let mut pred_epochs = None;
+        let mut address_gen = None;
+        for (path, bytes, _gas) in RedbPrefixIterator::new(
+            self,
+            Table::Block,
+            prefix,
+            String::default(),
+        ) {
+            let path = &path;
+            let segments: Vec<&str> =
+                path.split(KEY_SEGMENT_SEPARATOR).collect();
+            match segments.get(1) {
+                Some(prefix) => match *prefix {
+                    // Restore the base tree of Merkle tree
+                    "tree" => match segments.get(2) {
+                        Some(s) => {
+                            let st = StoreType::from_str(s)?;
+                            match segments.get(3) {
+                                Some(&"root") => merkle_tree_stores.set_root(
+                                    &st,
+                                    types::decode(bytes)
+                                        .map_err(Error::CodingError)?,
+                                ),
+                                Some(&"store") => merkle_tree_stores
+                                    .set_store(st.decode_store(bytes)?),
+                                _ => unknown_key_error(path)?,
+                            }
+                        }
+                        None => unknown_key_error(path)?,
+                    },
+                    "header" => {
+                        // the block header doesn't have to be restored
+                    }
+                    "hash" => {
+                        hash = Some(
+                            types::decode(bytes).map_err(Error::CodingError)?,
+                        )
+                    }
+                    "time" => {
+                        time = Some(
+                            types::decode(bytes).map_err(Error::CodingError)?,
+                        )
+                    }
+                    "epoch" => {
+                        epoch = Some(
+                            types::decode(bytes).map_err(Error::CodingError)?,
+                        )
+                    }
+                    "pred_epochs" => {
+                        pred_epochs = Some(
+                            types::decode(bytes).map_err(Error::CodingError)?,
+                        )
+                    }
+                    "address_gen" => {
+                        address_gen = Some(
+                            types::decode(bytes).map_err(Error::CodingError)?,
+                        );
+                    }
+                    _ => unknown_key_error(path)?,
+                },
+                None => unknown_key_error(path)?,
+            }
+        }
+        // Restore subtrees of Merkle tree
+        if let Some(epoch) = epoch {
+            for st in StoreType::iter_subtrees() {
+                let key_prefix = subtree_key_prefix(st, epoch);
+                let root_key =
+                    key_prefix.clone().with_segment("root".to_owned());
+                if let Some(bytes) =
+                    self.read_value(Table::Block, &root_key.to_string())?
+                {
+                    merkle_tree_stores.set_root(
+                        st,
+                        types::decode(bytes).map_err(Error::CodingError)?,
+                    );
+                }
+                let store_key = key_prefix.with_segment("store".to_owned());
+                if let Some(bytes) =
+                    self.read_value(Table::Block, &store_key.to_string())?
+                {
+                    merkle_tree_stores.set_store(st.decode_store(bytes)?);
+                }
+            }
+        }
+        match (hash, time, epoch, pred_epochs, address_gen) {
+            (
+                Some(hash),
+                Some(time),
+                Some(epoch),
+                Some(pred_epochs),
+                Some(address_gen),
+            ) => Ok(Some(BlockStateRead {
+                merkle_tree_stores,
+                hash,
+                height,
+                time,
+                epoch,
+                pred_epochs,
+                results,
+                conversion_state,
+                next_epoch_min_start_height,
+                next_epoch_min_start_time,
+                update_epoch_blocks_delay,
+                address_gen,
+                tx_queue,
+                ethereum_height,
+                eth_events_queue,
+            })),
+            _ => Err(Error::Temporary {
+                error: "Essential data couldn't be read from the DB"
+                    .to_string(),
+            }),
+        }
+    }
+
+    fn add_block_to_batch(
+        &self,
+        state: BlockStateWrite,
+        batch: &mut Self::WriteBatch,
+        is_full_commit: bool,
+    ) -> Result<()> {
+        let BlockStateWrite {
+            merkle_tree_stores,
+            header,
+            hash,
+            height,
+            time,
+            epoch,
+            pred_epochs,
+            next_epoch_min_start_height,
+            next_epoch_min_start_time,
+            update_epoch_blocks_delay,
+            address_gen,
+            results,
+            conversion_state,
+            tx_queue,
+            ethereum_height,
+            eth_events_queue,
+        }: BlockStateWrite = state;
+
+        // Record the tx hashes of the last committed block before its
+        // replay protection entries are moved from "last" to "all"
+        if let Some(bytes) = self.read_value(Table::State, "height")? {
+            let last_height: BlockHeight =
+                types::decode(bytes).map_err(Error::CodingError)?;
+            let prefix = replay_protection_diff_prefix(last_height);
+            for (key, _, _) in self.iter_replay_protection() {
+                if let Some((_, hash)) = key.rsplit_once('/') {
+                    batch.put(Table::Diffs, format!("{prefix}{hash}"), vec![]);
+                }
+            }
+        }
+
+        // The predecessor values written by older versions are stale once a
+        // block is committed
+        for state_key in LEGACY_PRED_STATE_KEYS {
+            batch.delete(Table::State, legacy_pred_key(state_key));
+        }
+
+        // Write the block state, also recording it in the diffs at this
+        // height for rollback
+        let mut put_state = |key: &str, value: Vec<u8>| {
+            batch.put(Table::Diffs, state_diff_key(height, key), value.clone());
+            batch.put(Table::State, key, value);
+        };
+        // Epoch start height and time
+        put_state(
+            "next_epoch_min_start_height",
+            types::encode(&next_epoch_min_start_height),
+        );
+        put_state(
+            "next_epoch_min_start_time",
+            types::encode(&next_epoch_min_start_time),
+        );
+        put_state(
+            "update_epoch_blocks_delay",
+            types::encode(&update_epoch_blocks_delay),
+        );
+        // Save the conversion state when the epoch is updated
+        if is_full_commit {
+            put_state(CONVERSION_STATE_KEY, types::encode(conversion_state));
+        }
+        // Tx queue
+        put_state("tx_queue", types::encode(&tx_queue));
+        put_state("ethereum_height", types::encode(&ethereum_height));
+        put_state("eth_events_queue", types::encode(&eth_events_queue));
+
+        let prefix_key = Key::from(height.to_db_key());
+        // Merkle tree
+        {
+            for st in StoreType::iter() {
+                if *st == StoreType::Base || is_full_commit {
+                    let key_prefix = if *st == StoreType::Base {
+                        base_tree_key_prefix(height)
+                    } else {
+                        subtree_key_prefix(st, epoch)
+                    };
+                    let root_key =
+                        key_prefix.clone().with_segment("root".to_owned());
+                    batch.put(
+                        Table::Block,
+                        root_key.to_string(),
+                        types::encode(merkle_tree_stores.root(st)),
+                    );
+                    let store_key = key_prefix.with_segment("store".to_owned());
+                    batch.put(
+                        Table::Block,
+                        store_key.to_string(),
+                        merkle_tree_stores.store(st).encode(),
+                    );
+                }
+            }
+        }
+        // Block header
+        if let Some(h) = header {
+            let key = prefix_key
+                .push(&"header".to_owned())
+                .map_err(Error::KeyError)?;
+            batch.put(Table::Block, key.to_string(), h.serialize_to_vec());
+        }
+        // Block hash
+        {
+            let key = prefix_key
+                .push(&"hash".to_owned())
+                .map_err(Error::KeyError)?;
+            batch.put(Table::Block, key.to_string(), types::encode(&hash));
+        }
+        // Block time
+        {
+            let key = prefix_key
+                .push(&"time".to_owned())
+                .map_err(Error::KeyError)?;
+            batch.put(Table::Block, key.to_string(), types::encode(&time));
+        }
+        // Block epoch
+        {
+            let key = prefix_key
+                .push(&"epoch".to_owned())
+                .map_err(Error::KeyError)?;
+            batch.put(Table::Block, key.to_string(), types::encode(&epoch));
+        }
+        // Block results
+        {
+            let results_path = format!("results/{}", height.raw());
+            batch.put(Table::Block, results_path, types::encode(&results));
+        }
+        // Predecessor block epochs
+        {
+            let key = prefix_key
+                .push(&"pred_epochs".to_owned())
+                .map_err(Error::KeyError)?;
+            batch.put(
+                Table::Block,
+                key.to_string(),
+                types::encode(&pred_epochs),
+            );
+        }
+        // Address gen
+        {
+            let key = prefix_key
+                .push(&"address_gen".to_owned())
+                .map_err(Error::KeyError)?;
+            batch.put(
+                Table::Block,
+                key.to_string(),
+                types::encode(&address_gen),
+            );
+        }
+
+        // Block height
+        batch.put(Table::State, "height", types::encode(&height));
+
+        Ok(())
+    }
+
+    fn read_block_header(&self, height: BlockHeight) -> Result<Option<Header>> {
+        let prefix_key = Key::from(height.to_db_key());
+        let key = prefix_key
+            .push(&"header".to_owned())
+            .map_err(Error::KeyError)?;
+        match self.read_value(Table::Block, &key.to_string())? {
+            Some(v) => Ok(Some(
+                Header::try_from_slice(&v[..])
+                    .map_err(Error::BorshCodingError)?,
+            )),
+            None => Ok(None),
+        }
+    }
+
+    fn read_merkle_tree_stores(
+        &self,
+        epoch: Epoch,
+        base_height: BlockHeight,
+        store_type: Option<StoreType>,
+    ) -> Result<Option<MerkleTreeStoresRead>> {
+        let mut merkle_tree_stores = MerkleTreeStoresRead::default();
+        let store_types = store_type
+            .as_ref()
+            .map(|st| Either::Left(std::iter::once(st)))
+            .unwrap_or_else(|| Either::Right(StoreType::iter()));
+        for st in store_types {
+            let key_prefix = if *st == StoreType::Base {
+                base_tree_key_prefix(base_height)
+            } else {
+                subtree_key_prefix(st, epoch)
+            };
+            let root_key = key_prefix.clone().with_segment("root".to_owned());
+            match self.read_value(Table::Block, &root_key.to_string())? {
+                Some(b) => {
+                    let root = types::decode(b).map_err(Error::CodingError)?;
+                    merkle_tree_stores.set_root(st, root);
+                }
+                None => return Ok(None),
+            }
+
+            let store_key = key_prefix.with_segment("store".to_owned());
+            match self.read_value(Table::Block, &store_key.to_string())? {
+                Some(b) => {
+                    merkle_tree_stores.set_store(st.decode_store(b)?);
+                }
+                None => return Ok(None),
+            }
+        }
+        Ok(Some(merkle_tree_stores))
+    }
+
+    fn has_replay_protection_entry(
+        &self,
+        hash: &namada::types::hash::Hash,
+    ) -> Result<bool> {
+        for prefix in ["last", "all"] {
+            let key = Key::parse(prefix)
+                .map_err(Error::KeyError)?
+                .push(&hash.to_string())
+                .map_err(Error::KeyError)?;
+            if self
+                .read_value(Table::ReplayProtection, &key.to_string())?
+                .is_some()
+            {
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+
+    fn read_subspace_val(&self, key: &Key) -> Result<Option<Vec<u8>>> {
+        self.read_value(Table::Subspace, &key.to_string())
+    }
+
+    fn read_subspace_val_with_height(
+        &self,
+        key: &Key,
+        height: BlockHeight,
+        last_height: BlockHeight,
+    ) -> Result<Option<Vec<u8>>> {
+        // If it has a "new" val, it was written at this height
+        if let Some(new_val) =
+            self.read_value(Table::Diffs, &diff_key(height, "new", key)?)?
+        {
+            return Ok(Some(new_val));
+        }
+        // If it has an "old" val, it was deleted at this height
+        if self
+            .read_value(Table::Diffs, &diff_key(height, "old", key)?)?
+            .is_some()
+        {
+            return Ok(None);
+        }
+
+        // If the value didn't change at the given height, we try to look for it
+        // at successor heights, up to the `last_height`
+        let mut raw_height = height.0 + 1;
+        loop {
+            let height = BlockHeight(raw_height);
+            // If it has an "old" val, it's the one we're looking for
+            if let Some(old_val) =
+                self.read_value(Table::Diffs, &diff_key(height, "old", key)?)?
+            {
+                return Ok(Some(old_val));
+            }
+            // Check if the value was created at this height instead, which
+            // would mean that it wasn't present before
+            if self
+                .read_value(Table::Diffs, &diff_key(height, "new", key)?)?
+                .is_some()
+            {
+                return Ok(None);
+            }
+
+            if raw_height >= last_height.0 {
+                // Read from latest height
+                return self.read_subspace_val(key);
+            } else {
+                raw_height += 1
+            }
+        }
+    }
+
+    fn write_subspace_val(
+        &mut self,
+        height: BlockHeight,
+        key: &Key,
+        value: impl AsRef<[u8]>,
+    ) -> Result<i64> {
+        let mut batch = Self::batch();
+        let size_diff =
+            self.batch_write_subspace_val(&mut batch, height, key, value)?;
+        self.write_ops(batch.0)?;
+        Ok(size_diff)
+    }
+
+    fn delete_subspace_val(
+        &mut self,
+        height: BlockHeight,
+        key: &Key,
+    ) -> Result<i64> {
+        let mut batch = Self::batch();
+        let prev_len =
+            self.batch_delete_subspace_val(&mut batch, height, key)?;
+        self.write_ops(batch.0)?;
+        Ok(prev_len)
+    }
+
+    fn batch() -> Self::WriteBatch {
+        RedbWriteBatch::default()
+    }
+
+    fn exec_batch(&mut self, batch: Self::WriteBatch) -> Result<()> {
+        self.write_ops(batch.0)
+    }
+
+    fn batch_write_subspace_val(
+        &self,
+        batch: &mut Self::WriteBatch,
+        height: BlockHeight,
+        key: &Key,
+        value: impl AsRef<[u8]>,
+    ) -> Result<i64> {
+        let value = value.as_ref();
+        let size_diff = match self.read_subspace_val(key)? {
+            Some(old_value) => {
+                let size_diff = value.len() as i64 - old_value.len() as i64;
+                // Persist the previous value
+                self.batch_write_subspace_diff(
+                    batch,
+                    height,
+                    key,
+                    Some(&old_value),
+                    Some(value),
+                )?;
+                size_diff
+            }
+            None => {
+                self.batch_write_subspace_diff(
+                    batch,
+                    height,
+                    key,
+                    None,
+                    Some(value),
+                )?;
+                value.len() as i64
+            }
+        };
+
+        // Write the new key-val
+        batch.put(Table::Subspace, key.to_string(), value);
+
+        Ok(size_diff)
+    }
+
+    fn batch_delete_subspace_val(
+        &self,
+        batch: &mut Self::WriteBatch,
+        height: BlockHeight,
+        key: &Key,
+    ) -> Result<i64> {
+        // Check the length of previous value, if any
+        let prev_len = match self.read_subspace_val(key)? {
+            Some(prev_value) => {
+                let prev_len = prev_value.len() as i64;
+                // Persist the previous value
+                self.batch_write_subspace_diff(
+                    batch,
+                    height,
+                    key,
+                    Some(&prev_value),
+                    None,
+                )?;
+                prev_len
+            }
+            None => 0,
+        };
+
+        // Delete the key-val
+        batch.delete(Table::Subspace, key.to_string());
+
+        Ok(prev_len)
+    }
+
+    fn prune_merkle_tree_store(
+        &mut self,
+        batch: &mut Self::WriteBatch,
+        store_type: &StoreType,
+        epoch: Epoch,
+    ) -> Result<()> {
+        let key_prefix = subtree_key_prefix(store_type, epoch);
+        let root_key = key_prefix.clone().with_segment("root".to_owned());
+        batch.delete(Table::Block, root_key.to_string());
+        let store_key = key_prefix.with_segment("store".to_owned());
+        batch.delete(Table::Block, store_key.to_string());
+        Ok(())
+    }
+
+    fn prune_subspace_diffs(
+        &mut self,
+        batch: &mut Self::WriteBatch,
+        height: BlockHeight,
+    ) -> Result<()> {
+        batch.delete_prefix(Table::Diffs, format!("{}/", height.raw()));
+        Ok(())
+    }
+
+    fn write_replay_protection_entry(
+        &mut self,
+        batch: &mut Self::WriteBatch,
+        key: &Key,
+    ) -> Result<()> {
+        batch.put(Table::ReplayProtection, key.to_string(), vec![]);
+        Ok(())
+    }
+
+    fn delete_replay_protection_entry(
+        &mut self,
+        batch: &mut Self::WriteBatch,
+        key: &Key,
+    ) -> Result<()> {
+        batch.delete(Table::ReplayProtection, key.to_string());
+        Ok(())
+    }
+
+    fn batch_write_events(
+        &self,
+        batch: &mut Self::WriteBatch,
+        height: BlockHeight,
+        events: &[IndexedEvent],
+    ) -> Result<()> {
+        // The events of a replayed block replace the ones written before
+        let prefix = events_height_prefix(height);
+        self.batch_delete_events(
+            batch,
+            RedbPrefixIterator::new(
+                self,
+                Table::Events,
+                prefix,
+                String::default(),
+            ),
+        )?;
+        for (n, event) in events.iter().enumerate() {
+            let n = n as u64;
+            for index_key in &event.index_keys {
+                batch.put(
+                    Table::Events,
+                    event_index_key(index_key, height, n),
+                    vec![],
+                );
+            }
+            batch.put(
+                Table::Events,
+                event_key(height, n),
+                types::encode(event),
+            );
+        }
+        Ok(())
+    }
+
+    fn read_events(&self, height: BlockHeight) -> Result<Vec<Vec<u8>>> {
+        let prefix = events_height_prefix(height);
+        RedbPrefixIterator::new(self, Table::Events, prefix.clone(), prefix)
+            .map(|(_, value, _)| {
+                types::decode::<IndexedEvent>(value)
+                    .map(|indexed| indexed.event)
+                    .map_err(Error::CodingError)
+            })
+            .collect()
+    }
+
+    fn read_indexed_events(&self, index_key: &str) -> Result<Vec<Vec<u8>>> {
+        let prefix = event_index_prefix(index_key);
+        let entries: Vec<String> = RedbPrefixIterator::new(
+            self,
+            Table::Events,
+            prefix.clone(),
+            prefix,
+        )
+        .filter_map(|(entry, _, _)| {
+            parse_event_index_entry(&entry)
+                .map(|(height, n)| event_key(height, n))
+        })
+        .collect();
+        let mut events = Vec::with_capacity(entries.len());
+        for key in entries.into_iter().rev() {
+            if let Some(value) = self.read_value(Table::Events, &key)? {
+                let indexed: IndexedEvent =
+                    types::decode(value).map_err(Error::CodingError)?;
+                events.push(indexed.event);
+            }
+        }
+        Ok(events)
+    }
+
+    fn prune_events(
+        &mut self,
+        batch: &mut Self::WriteBatch,
+        below_height: BlockHeight,
+    ) -> Result<()> {
+        let pruned = RedbPrefixIterator::new(
+            self,
+            Table::Events,
+            EVENTS_HEIGHT_PREFIX.to_string(),
+            String::default(),
+        )
+        .take_while(|(key, _, _)| {
+            parse_event_key(key)
+                .map(|(height, _)| height < below_height)
+                .unwrap_or_default()
+        });
+        self.batch_delete_events(batch, pruned)
+    }
+
+    fn checkpoint(&self, path: &Path) -> Result<()> {
+        // redb has no checkpoints, so the tables are copied from a single read
+        // transaction, which sees a consistent state
+        let checkpoint = open(path, None)?;
+        let copy = || -> std::result::Result<_, redb::Error> {
+            let read_txn = self.0.begin_read()?;
+            let write_txn = checkpoint.0.begin_write()?;
+            for current in Table::ALL {
+                let source = read_txn.open_table(current.definition())?;
+                let mut target = write_txn.open_table(current.definition())?;
+                for entry in source.iter()? {
+                    let (key, value) = entry?;
+                    target.insert(key.value(), value.value())?;
+                }
+            }
+            write_txn.commit()?;
+            Ok(())
+        };
+        copy().map_err(db_error)
+    }
+}
+
+impl<'iter> DBIter<'iter> for RedbDB {
+    type PrefixIter = RedbPrefixIterator<'iter>;
+
+    fn iter_prefix(
+        &'iter self,
+        prefix: Option<&Key>,
+    ) -> RedbPrefixIterator<'iter> {
+        let prefix = prefix
+            .map(|k| {
+                if k == &Key::default() {
+                    k.to_string()
+                } else {
+                    format!("{k}/")
+                }
+            })
+            .unwrap_or_default();
+        RedbPrefixIterator::new(
+            self,
+            Table::Subspace,
+            prefix,
+            String::default(),
+        )
+    }
+
+    fn iter_results(&'iter self) -> RedbPrefixIterator<'iter> {
+        let db_prefix = "results/".to_owned();
+        RedbPrefixIterator::new(
+            self,
+            Table::Block,
+            db_prefix.clone(),
+            db_prefix,
+        )
+    }
+
+    fn iter_old_diffs(
+        &'iter self,
+        height: BlockHeight,
+        prefix: Option<&'iter Key>,
+    ) -> RedbPrefixIterator<'iter> {
+        iter_diffs_prefix(self, height, prefix, true)
+    }
+
+    fn iter_new_diffs(
+        &'iter self,
+        height: BlockHeight,
+        prefix: Option<&'iter Key>,
+    ) -> RedbPrefixIterator<'iter> {
+        iter_diffs_prefix(self, height, prefix, false)
+    }
+
+    fn iter_replay_protection(&'iter self) -> Self::PrefixIter {
+        let db_prefix = "last".to_owned();
+        RedbPrefixIterator::new(
+            self,
+            Table::ReplayProtection,
+            db_prefix.clone(),
+            db_prefix,
+        )
+    }
+
+    fn iter_all_replay_protection(&'iter self) -> Self::PrefixIter {
+        RedbPrefixIterator::new(
+            self,
+            Table::ReplayProtection,
+            "all/".to_owned(),
+            "all".to_owned(),
+        )
+    }
+}
+
+fn iter_diffs_prefix<'a>(
+    db: &'a RedbDB,
+    height: BlockHeight,
+    prefix: Option<&'a Key>,
+    is_old: bool,
+) -> RedbPrefixIterator<'a> {
+    let kind = if is_old { "old" } else { "new" };
+    let db_prefix = format!("{}/{}/", height.0.raw(), kind);
+    let prefix = match prefix {
+        Some(k) if k != &Key::default() => format!("{db_prefix}{k}/"),
+        _ => db_prefix.clone(),
+    };
+    // get keys without a prefix
+    RedbPrefixIterator::new(db, Table::Diffs, prefix, db_prefix)
+}
+
+/// A prefix iterator that reads the matching entries from the DB in chunks of
+/// [`ITER_CHUNK_SIZE`], each in its own read transaction.
+#[derive(Debug)]
+pub struct RedbPrefixIterator<'a> {
+    db: &'a RedbDB,
+    table: Table,
+    /// Only the keys starting with this prefix are iterated
+    prefix: String,
+    /// The prefix that is stripped from the iterated keys
+    db_prefix: String,
+    /// The entries read from the DB that haven't been yielded yet
+    chunk: VecDeque<(String, Vec<u8>)>,
+    /// The last key read from the DB, from which the next chunk starts
+    last_key: Option<String>,
+    /// Set when there are no more entries to read from the DB
+    exhausted: bool,
+}
+
+impl<'a> RedbPrefixIterator<'a> {
+    fn new(
+        db: &'a RedbDB,
+        table: Table,
+        prefix: String,
+        db_prefix: String,
+    ) -> Self {
+        Self {
+            db,
+            table,
+            prefix,
+            db_prefix,
+            chunk: VecDeque::new(),
+            last_key: None,
+            exhausted: false,
+        }
+    }
+
+    fn read_next_chunk(&mut self) {
+        let from = match &self.last_key {
+            Some(key) => Bound::Excluded(key.as_str()),
+            None => Bound::Included(self.prefix.as_str()),
+        };
+        let chunk = self
+            .db
+            .read_prefix_chunk(self.table, &self.prefix, from, ITER_CHUNK_SIZE)
+            .expect("Prefix iterator shouldn't fail");
+        self.exhausted = chunk.len() < ITER_CHUNK_SIZE;
+        if let Some((key, _)) = chunk.last() {
+            self.last_key = Some(key.clone());
+        }
+        self.chunk = chunk.into();
+    }
+}
+
+impl<'a> Iterator for RedbPrefixIterator<'a> {
+    type Item = (String, Vec<u8>, u64);
+
+    /// Returns the next pair and the gas cost
+    fn next(&mut self) -> Option<(String, Vec<u8>, u64)> {
+        loop {
+            if self.chunk.is_empty() {
+                if self.exhausted {
+                    return None;
+                }
+                self.read_next_chunk();
+            }
+            let (key, val) = self.chunk.pop_front()?;
+            if let Some(k) = key.strip_prefix(&self.db_prefix) {
+                let gas = k.len() + val.len();
+                return Some((k.to_owned(), val, gas as _));
+            }
+        }
+    }
+}
+
+impl DBWriteBatch for RedbWriteBatch {}
+
+/// The key of a subspace diff written at the given height. The `kind` is
+/// either "old" or "new".
+fn diff_key(height: BlockHeight, kind: &str, key: &Key) -> Result<String> {
+    Ok(Key::from(height.to_db_key())
+        .push(&kind.to_owned())
+        .map_err(Error::KeyError)?
+        .join(key)
+        .to_string())
+}
+
+/// The smallest key greater than all the keys starting with the `prefix`, if
+/// any.
+fn prefix_upper_bound(prefix: &str) -> Option<String> {
+    let mut upper = prefix.to_owned();
+    while let Some(last) = upper.pop() {
+        if let Some(next) = char::from_u32(last as u32 + 1) {
+            upper.push(next);
+            return Some(upper);
+        }
+    }
+    None
+}
+
+fn unknown_key_error(key: &str) -> Result<()> {
+    Err(Error::UnknownKey {
+        key: key.to_owned(),
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use tempfile::tempdir;
+    use test_log::test;
+
+    use super::super::db_tests;
+    use super::*;
+
+    #[test]
+    fn test_load_state() {
+        let dir = tempdir().unwrap();
+        let mut db = open(dir.path(), None).unwrap();
+        db_tests::test_load_state(&mut db);
+    }
+
+    #[test]
+    fn test_read() {
+        let dir = tempdir().unwrap();
+        let mut db = open(dir.path(), None).unwrap();
+        db_tests::test_read(&mut db);
+    }
+
+    #[test]
+    fn test_prefix_iter() {
+        let dir = tempdir().unwrap();
+        let mut db = open(dir.path(), None).unwrap();
+        db_tests::test_prefix_iter(&mut db);
+    }
+
+    /// Test that the prefix iterator yields all the entries when they span
+    /// more than one chunk.
+    #[test]
+    fn test_prefix_iter_chunks() {
+        let dir = tempdir().unwrap();
+        let mut db = open(dir.path(), None).unwrap();
+
+        let prefix = Key::parse("prefix").unwrap();
+        let mut keys: Vec<Key> = (0..ITER_CHUNK_SIZE * 2 + 1)
+            .map(|i| prefix.push(&format!("{i:05}")).unwrap())
+            .collect();
+        keys.sort();
+
+        let mut batch = RedbDB::batch();
+        for key in &keys {
+            db.batch_write_subspace_val(
+                &mut batch,
+                BlockHeight(1),
+                key,
+                [0_u8],
+            )
+            .unwrap();
+        }
+        db.exec_batch(batch).unwrap();
+
+        let itered_keys: Vec<Key> = db
+            .iter_prefix(Some(&prefix))
+            .map(|(key, _val, _)| Key::parse(key).unwrap())
+            .collect();
+        itertools::assert_equal(keys, itered_keys);
+    }
+
+    #[test]
+    fn test_prune_subspace_diffs() {
+        let dir = tempdir().unwrap();
+        let mut db = open(dir.path(), None).unwrap();
+        db_tests::test_prune_subspace_diffs(&mut db);
+    }
+
+    #[test]
+    fn test_events() {
+        let dir = tempdir().unwrap();
+        let mut db = open(dir.path(), None).unwrap();
+        db_tests::test_events(&mut db);
+    }
+
+    #[test]
+    fn test_rollback() {
+        let dir = tempdir().unwrap();
+        let mut db = open(dir.path(), None).unwrap();
+        db_tests::test_rollback(&mut db);
+    }
+
+    #[test]
+    fn test_checkpoint() {
+        let dir = tempdir().unwrap();
+        let mut db = open(dir.path().join("db"), None).unwrap();
+        db_tests::test_checkpoint(&mut db, &dir.path().join("checkpoint"));
+    }
+}
@@ -13,16 +13,16 @@
 //!   - `next_epoch_min_start_time`: minimum block time from which the next
 //!     epoch can start
 //!   - `replay_protection`: hashes of the processed transactions
-//!   - `pred`: predecessor values of the top-level keys of the same name
-//!     - `tx_queue`
-//!     - `next_epoch_min_start_height`
-//!     - `next_epoch_min_start_time`
 //!   - `conversion_state`: MASP conversion state
 //! - `subspace`: accounts sub-spaces
 //!   - `{address}/{dyn}`: any byte data associated with accounts
 //! - `diffs`: diffs in account subspaces' key-vals
-//!   - `new/{dyn}`: value set in block height `h`
-//!   - `old/{dyn}`: value from predecessor block height
+//!   - `h`: for each block at height `h`:
+//!     - `new/{dyn}`: value set in block height `h`
+//!     - `old/{dyn}`: value from predecessor block height
+//!     - `state/{key}`: value of the `state` key written in block height `h`
+//!     - `replay_protection/{hash}`: hashes of the txs included in block height
+//!       `h`
 //! - `block`: block state
 //!   - `results/{h}`: block results at height `h`
 //!   - `h`: for each block at height `h`:
@@ -37,12 +37,16 @@
 //! - `replay_protection`: hashes of processed tx
 //!     - `all`: the hashes included up to the last block
 //!     - `last`: the hashes included in the last block
+//! - `events`: events emitted in the blocks
+//!   - `height/{h}/{n}`: the `n`-th event emitted in block height `h`, with its
+//!     index keys
+//!   - `index/{index_key}/{h}/{n}`: the index entries of the events
 
+use std::collections::BTreeMap;
 use std::fs::File;
 use std::io::{BufWriter, Write};
 use std::path::Path;
 use std::str::FromStr;
-use std::sync::Mutex;
 
 use borsh::BorshDeserialize;
 use borsh_ext::BorshSerializeExt;
@@ -56,7 +60,7 @@ use namada::ledger::storage::merkle_tree::{
 use namada::ledger::storage::types::PrefixIterator;
 use namada::ledger::storage::{
     types, BlockStateRead, BlockStateWrite, DBIter, DBWriteBatch, Error,
-    MerkleTreeStoresRead, Result, StoreType, DB,
+    IndexedEvent, MerkleTreeStoresRead, Result, StoreType, DB,
 };
 use namada::types::internal::TxQueue;
 use namada::types::storage::{
@@ -65,11 +69,22 @@ use namada::types::storage::{
 };
 use namada::types::time::DateTimeUtc;
 use rayon::prelude::*;
+use rocksdb::checkpoint::Checkpoint;
 use rocksdb::{
     BlockBasedOptions, ColumnFamily, ColumnFamilyDescriptor, Direction,
     FlushOptions, IteratorMode, Options, ReadOptions, WriteBatch,
 };
 
+use super::events::{
+    event_index_key, event_index_prefix, event_key, events_height_prefix,
+    parse_event_index_entry, parse_event_key, EVENTS_HEIGHT_PREFIX,
+};
+use super::rollback::{
+    block_keys, check_rollback_target, legacy_pred_key, not_retained_error,
+    read_legacy_state, replay_protection_diff_prefix, rollback_subspace,
+    state_diff_key, DBRollback, RollbackPlan, CONVERSION_STATE_KEY,
+    LEGACY_PRED_STATE_KEYS, ROLLBACK_STATE_KEYS,
+};
 use crate::config::utils::num_of_threads;
 
 // TODO the DB schema will probably need some kind of versioning
@@ -84,6 +99,7 @@ const DIFFS_CF: &str = "diffs";
 const STATE_CF: &str = "state";
 const BLOCK_CF: &str = "block";
 const REPLAY_PROTECTION_CF: &str = "replay_protection";
+const EVENTS_CF: &str = "events";
 
 /// RocksDB handle
 #[derive(Debug)]
@@ -186,6 +202,14 @@ pub fn open(
         replay_protection_cf_opts,
     ));
 
+    // for events (insert-intensive)
+    let mut events_cf_opts = Options::default();
+    events_cf_opts.set_compression_type(rocksdb::DBCompressionType::Zstd);
+    events_cf_opts.set_compression_options(0, 0, 0, 1024 * 1024);
+    events_cf_opts.set_compaction_style(rocksdb::DBCompactionStyle::Universal);
+    events_cf_opts.set_block_based_table_factory(&table_opts);
+    cfs.push(ColumnFamilyDescriptor::new(EVENTS_CF, events_cf_opts));
+
     rocksdb::DB::open_cf_descriptors(&db_opts, path, cfs)
         .map(RocksDB)
         .map_err(|e| Error::DBError(e.into_string()))
@@ -198,6 +222,16 @@ impl Drop for RocksDB {
 }
 
 impl RocksDB {
+    /// Flush the memtables to the SST files. The committed write batches are
+    /// already durable through the write-ahead log.
+    fn flush(&self, wait: bool) -> Result<()> {
+        let mut flush_opts = FlushOptions::default();
+        flush_opts.set_wait(wait);
+        self.0
+            .flush_opt(&flush_opts)
+            .map_err(|e| Error::DBError(e.into_string()))
+    }
+
     fn get_column_family(&self, cf_name: &str) -> Result<&ColumnFamily> {
         self.0
             .cf_handle(cf_name)
@@ -429,160 +463,243 @@ impl RocksDB {
         buf.flush().expect("Unable to write to output file");
     }
 
-    /// Rollback to previous block. Given the inner working of tendermint
-    /// rollback and of the key structure of Namada, calling rollback more than
-    /// once without restarting the chain results in a single rollback.
-    pub fn rollback(
-        &mut self,
-        tendermint_block_height: BlockHeight,
+    /// Read the hashes of the txs included in the block at the given height
+    /// that have been recorded for rollback
+    fn read_recorded_tx_hashes(
+        &self,
+        height: BlockHeight,
+    ) -> Result<Vec<String>> {
+        let diffs_cf = self.get_column_family(DIFFS_CF)?;
+        let prefix = replay_protection_diff_prefix(height);
+        Ok(iter_prefix(self, diffs_cf, prefix.clone(), Some(prefix))
+            .map(|(hash, _, _)| hash)
+            .collect())
+    }
+
+    /// Batch delete the given entries of events with their index entries
+    fn batch_delete_events(
+        &self,
+        batch: &mut RocksDBWriteBatch,
+        events: impl IntoIterator<Item = (String, Vec<u8>, u64)>,
     ) -> Result<()> {
+        let events_cf = self.get_column_family(EVENTS_CF)?;
+        for (key, value, _) in events {
+            let event: IndexedEvent =
+                types::decode(value).map_err(Error::CodingError)?;
+            let (height, n) = parse_event_key(&key).ok_or_else(|| {
+                Error::DBError(format!("Invalid event key {key}"))
+            })?;
+            for index_key in event.index_keys {
+                batch.0.delete_cf(
+                    events_cf,
+                    event_index_key(&index_key, height, n),
+                );
+            }
+            batch.0.delete_cf(events_cf, key);
+        }
+        Ok(())
+    }
+}
+
+impl DBRollback for RocksDB {
+    fn plan_rollback(
+        &self,
+        target_height: BlockHeight,
+    ) -> Result<RollbackPlan> {
         let last_block = self.read_last_block()?.ok_or(Error::DBError(
             "Missing last block in storage".to_string(),
         ))?;
-        tracing::info!(
-            "Namada last block height: {}, Tendermint last block height: {}",
-            last_block.height,
-            tendermint_block_height
-        );
+        check_rollback_target(target_height, last_block.height)?;
+        let target_epoch = last_block
+            .pred_epochs
+            .get_epoch(target_height)
+            .ok_or_else(|| not_retained_error(target_height, "epoch"))?;
+        let mut plan = RollbackPlan {
+            last_height: last_block.height,
+            last_epoch: last_block.epoch,
+            target_height,
+            target_epoch,
+            state: vec![],
+            subspace: BTreeMap::new(),
+            last_tx_hashes: vec![],
+            removed_tx_hashes: vec![],
+        };
+        if plan.is_empty() {
+            return Ok(plan);
+        }
 
-        // If the block height to which tendermint rolled back matches the
-        // Namada height, there's no need to rollback
-        if tendermint_block_height == last_block.height {
+        let diffs_cf = self.get_column_family(DIFFS_CF)?;
+        let read_diff = |key: String| {
+            self.0
+                .get_cf(diffs_cf, key)
+                .map_err(|e| Error::DBError(e.into_string()))
+        };
+        let state_cf = self.get_column_family(STATE_CF)?;
+        let read_legacy = |state_key: &str| {
+            read_legacy_state(
+                target_height,
+                last_block.height,
+                state_key,
+                |key| {
+                    self.0
+                        .get_cf(state_cf, key)
+                        .map_err(|e| Error::DBError(e.into_string()))
+                },
+            )
+        };
+        for state_key in ROLLBACK_STATE_KEYS {
+            let value = match read_diff(state_diff_key(
+                target_height,
+                state_key,
+            ))? {
+                Some(value) => Some(value),
+                None => read_legacy(state_key)?,
+            }
+            .ok_or_else(|| not_retained_error(target_height, "block state"))?;
+            plan.state.push((state_key, value));
+        }
+        if target_epoch != last_block.epoch {
+            // The conversion state is written on the full commit at the
+            // start of an epoch
+            let epoch_start = last_block
+                .pred_epochs
+                .get_start_height_of_epoch(target_epoch)
+                .unwrap_or_default();
+            let conversion_state = (epoch_start.0..=target_height.0)
+                .rev()
+                .find_map(|raw_height| {
+                    read_diff(state_diff_key(
+                        BlockHeight(raw_height),
+                        CONVERSION_STATE_KEY,
+                    ))
+                    .transpose()
+                })
+                .transpose()?;
+            let conversion_state = match conversion_state {
+                Some(value) => Some(value),
+                None => read_legacy(CONVERSION_STATE_KEY)?,
+            }
+            .ok_or_else(|| {
+                not_retained_error(target_height, "conversion state")
+            })?;
+            plan.state.push((CONVERSION_STATE_KEY, conversion_state));
+
+            // The merkle tree at the target height is restored from the
+            // subtree stores of its epoch
+            let block_cf = self.get_column_family(BLOCK_CF)?;
+            for st in StoreType::iter_subtrees() {
+                let root_key = subtree_key_prefix(st, target_epoch)
+                    .with_segment("root".to_owned());
+                if self
+                    .0
+                    .get_cf(block_cf, root_key.to_string())
+                    .map_err(|e| Error::DBError(e.into_string()))?
+                    .is_none()
+                {
+                    return Err(not_retained_error(
+                        target_height,
+                        "merkle tree",
+                    ));
+                }
+            }
+        }
+
+        plan.subspace =
+            rollback_subspace(self, target_height, last_block.height)?;
+        plan.last_tx_hashes = self.read_recorded_tx_hashes(target_height)?;
+        for raw_height in target_height.0..last_block.height.0 {
+            plan.removed_tx_hashes
+                .extend(self.read_recorded_tx_hashes(BlockHeight(raw_height))?);
+        }
+        Ok(plan)
+    }
+
+    fn apply_rollback(&mut self, plan: RollbackPlan) -> Result<()> {
+        if plan.is_empty() {
             tracing::info!(
-                "Namada height already matches the rollback Tendermint \
-                 height, no need to rollback."
+                "Namada height already matches the rollback height, no need \
+                 to rollback."
             );
             return Ok(());
         }
+        let RollbackPlan {
+            last_height,
+            last_epoch,
+            target_height,
+            target_epoch,
+            state,
+            subspace,
+            last_tx_hashes,
+            removed_tx_hashes,
+        } = plan;
+        let mut batch = RocksDBWriteBatch::default();
+
+        // The diffs and the merkle tree stores written after the target height
+        for raw_height in target_height.0 + 1..=last_height.0 {
+            self.prune_subspace_diffs(&mut batch, BlockHeight(raw_height))?;
+        }
+        for epoch in
+            Epoch::iter_bounds_inclusive(target_epoch.next(), last_epoch)
+        {
+            for st in StoreType::iter_subtrees() {
+                self.prune_merkle_tree_store(&mut batch, st, epoch)?;
+            }
+        }
 
-        let mut batch = WriteBatch::default();
-        let previous_height =
-            BlockHeight::from(u64::from(last_block.height) - 1);
-
+        tracing::info!("Restoring the block state of height {target_height}");
         let state_cf = self.get_column_family(STATE_CF)?;
-        // Revert the non-height-prepended metadata storage keys which get
-        // updated with every block. Because of the way we save these
-        // three keys in storage we can only perform one rollback before
-        // restarting the chain
-        tracing::info!("Reverting non-height-prepended metadata keys");
-        batch.put_cf(state_cf, "height", types::encode(&previous_height));
-        for metadata_key in [
-            "next_epoch_min_start_height",
-            "next_epoch_min_start_time",
-            "tx_queue",
-        ] {
-            let previous_key = format!("pred/{}", metadata_key);
-            let previous_value = self
-                .0
-                .get_cf(state_cf, previous_key.as_bytes())
-                .map_err(|e| Error::DBError(e.to_string()))?
-                .ok_or(Error::UnknownKey { key: previous_key })?;
-
-            batch.put_cf(state_cf, metadata_key, previous_value);
-            // NOTE: we cannot restore the "pred/" keys themselves since we
-            // don't have their predecessors in storage, but there's no need to
-            // since we cannot do more than one rollback anyway because of
-            // Tendermint.
+        batch
+            .0
+            .put_cf(state_cf, "height", types::encode(&target_height));
+        for (key, value) in state {
+            batch.0.put_cf(state_cf, key, value);
         }
-
-        // Revert conversion state if the epoch had been changed
-        if last_block.pred_epochs.get_epoch(previous_height)
-            != Some(last_block.epoch)
-        {
-            let previous_key = "pred/conversion_state".to_string();
-            let previous_value = self
-                .0
-                .get_cf(state_cf, previous_key.as_bytes())
-                .map_err(|e| Error::DBError(e.to_string()))?
-                .ok_or(Error::UnknownKey { key: previous_key })?;
-            batch.put_cf(state_cf, "conversion_state", previous_value);
+        for state_key in LEGACY_PRED_STATE_KEYS {
+            batch.0.delete_cf(state_cf, legacy_pred_key(state_key));
         }
 
-        // Delete block results for the last block
-        let block_cf = self.get_column_family(BLOCK_CF)?;
-        tracing::info!("Removing last block results");
-        batch.delete_cf(block_cf, format!("results/{}", last_block.height));
+        tracing::info!("Restoring {} subspace keys", subspace.len());
+        let subspace_cf = self.get_column_family(SUBSPACE_CF)?;
+        for (key, value) in subspace {
+            match value {
+                Some(value) => batch.0.put_cf(subspace_cf, key, value),
+                None => batch.0.delete_cf(subspace_cf, key),
+            }
+        }
 
-        // Delete the tx hashes included in the last block
+        tracing::info!("Restoring replay protection hashes");
         let reprot_cf = self.get_column_family(REPLAY_PROTECTION_CF)?;
-        tracing::info!("Removing replay protection hashes");
-        batch.delete_cf(reprot_cf, "last");
-
-        // Execute next step in parallel
-        let batch = Mutex::new(batch);
-
-        tracing::info!("Restoring previous height subspace diffs");
-        self.iter_prefix(None).par_bridge().try_for_each(
-            |(key, _value, _gas)| -> Result<()> {
-                // Restore previous height diff if present, otherwise delete the
-                // subspace key
-                let subspace_cf = self.get_column_family(SUBSPACE_CF)?;
-                match self.read_subspace_val_with_height(
-                    &Key::from(key.to_db_key()),
-                    previous_height,
-                    last_block.height,
-                )? {
-                    Some(previous_value) => batch.lock().unwrap().put_cf(
-                        subspace_cf,
-                        &key,
-                        previous_value,
-                    ),
-                    None => batch.lock().unwrap().delete_cf(subspace_cf, &key),
-                }
-
-                Ok(())
-            },
-        )?;
-
-        // Look for diffs in this block to find what has been deleted
-        let diff_new_key_prefix = Key {
-            segments: vec![
-                last_block.height.to_db_key(),
-                "new".to_string().to_db_key(),
-            ],
-        };
-        {
-            let mut batch_guard = batch.lock().unwrap();
-            let subspace_cf = self.get_column_family(SUBSPACE_CF)?;
-            for (key, val, _) in
-                iter_diffs_prefix(self, last_block.height, None, true)
-            {
-                let key = Key::parse(key).unwrap();
-                let diff_new_key = diff_new_key_prefix.join(&key);
-                if self.read_subspace_val(&diff_new_key)?.is_none() {
-                    // If there is no new value, it has been deleted in this
-                    // block and we have to restore it
-                    batch_guard.put_cf(subspace_cf, key.to_string(), val)
-                }
-            }
+        for (key, _, _) in iter_prefix(
+            self,
+            reprot_cf,
+            String::default(),
+            Some("last/".to_string()),
+        ) {
+            batch.0.delete_cf(reprot_cf, key);
+        }
+        for hash in removed_tx_hashes {
+            batch.0.delete_cf(reprot_cf, format!("all/{hash}"));
+        }
+        let diffs_cf = self.get_column_family(DIFFS_CF)?;
+        let recorded_prefix = replay_protection_diff_prefix(target_height);
+        for hash in last_tx_hashes {
+            batch.0.put_cf(reprot_cf, format!("last/{hash}"), vec![]);
+            batch
+                .0
+                .delete_cf(diffs_cf, format!("{recorded_prefix}{hash}"));
         }
 
-        tracing::info!("Deleting keys prepended with the last height");
-        let mut batch = batch.into_inner().unwrap();
-        let prefix = last_block.height.to_string();
-        let mut delete_keys = |cf: &ColumnFamily| {
-            let read_opts = make_iter_read_opts(Some(prefix.clone()));
-            let iter = self.0.iterator_cf_opt(
-                cf,
-                read_opts,
-                IteratorMode::From(prefix.as_bytes(), Direction::Forward),
-            );
-            for (key, _value, _gas) in PersistentPrefixIterator(
-                // Empty prefix string to prevent stripping
-                PrefixIterator::new(iter, String::default()),
-            ) {
-                batch.delete_cf(cf, key);
+        tracing::info!("Deleting the blocks after height {target_height}");
+        let block_cf = self.get_column_family(BLOCK_CF)?;
+        for raw_height in target_height.0 + 1..=last_height.0 {
+            for key in block_keys(BlockHeight(raw_height)) {
+                batch.0.delete_cf(block_cf, key);
             }
-        };
-        // Delete any height-prepended key in subspace diffs
-        let diffs_cf = self.get_column_family(DIFFS_CF)?;
-        delete_keys(diffs_cf);
-        // Delete any height-prepended key in the block
-        delete_keys(block_cf);
+        }
 
         // Write the batch and persist changes to disk
         tracing::info!("Flushing restored state to disk");
-        self.exec_batch(batch)
+        DB::exec_batch(self, batch)
     }
 }
 
@@ -597,14 +714,6 @@ impl DB for RocksDB {
         open(db_path, cache).expect("cannot open the DB")
     }
 
-    fn flush(&self, wait: bool) -> Result<()> {
-        let mut flush_opts = FlushOptions::default();
-        flush_opts.set_wait(wait);
-        self.0
-            .flush_opt(&flush_opts)
-            .map_err(|e| Error::DBError(e.into_string()))
-    }
-
     fn read_last_block(&self) -> Result<Option<BlockStateRead>> {
         // Block height
         let state_cf = self.get_column_family(STATE_CF)?;
@@ -887,104 +996,61 @@ impl DB for RocksDB {
             eth_events_queue,
         }: BlockStateWrite = state;
 
-        // Epoch start height and time
         let state_cf = self.get_column_family(STATE_CF)?;
-        if let Some(current_value) = self
+        let diffs_cf = self.get_column_family(DIFFS_CF)?;
+
+        // Record the tx hashes of the last committed block before its
+        // replay protection entries are moved from "last" to "all"
+        if let Some(bytes) = self
             .0
-            .get_cf(state_cf, "next_epoch_min_start_height")
+            .get_cf(state_cf, "height")
             .map_err(|e| Error::DBError(e.into_string()))?
         {
-            // Write the predecessor value for rollback
-            batch.0.put_cf(
-                state_cf,
-                "pred/next_epoch_min_start_height",
-                current_value,
-            );
+            let last_height: BlockHeight =
+                types::decode(bytes).map_err(Error::CodingError)?;
+            let prefix = replay_protection_diff_prefix(last_height);
+            for (key, _, _) in self.iter_replay_protection() {
+                if let Some((_, hash)) = key.rsplit_once('/') {
+                    batch.0.put_cf(diffs_cf, format!("{prefix}{hash}"), vec![]);
+                }
+            }
+        }
+
+        // The predecessor values written by older versions are stale once a
+        // block is committed
+        for state_key in LEGACY_PRED_STATE_KEYS {
+            batch.0.delete_cf(state_cf, legacy_pred_key(state_key));
         }
-        batch.0.put_cf(
-            state_cf,
+
+        // Write the block state, also recording it in the diffs at this
+        // height for rollback
+        let mut put_state = |key: &str, value: Vec<u8>| {
+            batch
+                .0
+                .put_cf(diffs_cf, state_diff_key(height, key), &value);
+            batch.0.put_cf(state_cf, key, value);
+        };
+        // Epoch start height and time
+        put_state(
             "next_epoch_min_start_height",
             types::encode(&next_epoch_min_start_height),
         );
-
-        if let Some(current_value) = self
-            .0
-            .get_cf(state_cf, "next_epoch_min_start_time")
-            .map_err(|e| Error::DBError(e.into_string()))?
-        {
-            // Write the predecessor value for rollback
-            batch.0.put_cf(
-                state_cf,
-                "pred/next_epoch_min_start_time",
-                current_value,
-            );
-        }
-        batch.0.put_cf(
-            state_cf,
+        put_state(
             "next_epoch_min_start_time",
             types::encode(&next_epoch_min_start_time),
         );
-        if let Some(current_value) = self
-            .0
-            .get_cf(state_cf, "update_epoch_blocks_delay")
-            .map_err(|e| Error::DBError(e.into_string()))?
-        {
-            // Write the predecessor value for rollback
-            batch.0.put_cf(
-                state_cf,
-                "pred/update_epoch_blocks_delay",
-                current_value,
-            );
-        }
-        batch.0.put_cf(
-            state_cf,
+        put_state(
             "update_epoch_blocks_delay",
             types::encode(&update_epoch_blocks_delay),
         );
-
         // Save the conversion state when the epoch is updated
         if is_full_commit {
-            if let Some(current_value) = self
-                .0
-                .get_cf(state_cf, "conversion_state")
-                .map_err(|e| Error::DBError(e.into_string()))?
-            {
-                // Write the predecessor value for rollback
-                batch.0.put_cf(
-                    state_cf,
-                    "pred/conversion_state",
-                    current_value,
-                );
-            }
-            batch.0.put_cf(
-                state_cf,
-                "conversion_state",
-                types::encode(conversion_state),
-            );
+            put_state(CONVERSION_STATE_KEY, types::encode(conversion_state));
         }
-
         // Tx queue
-        if let Some(pred_tx_queue) = self
-            .0
-            .get_cf(state_cf, "tx_queue")
-            .map_err(|e| Error::DBError(e.into_string()))?
-        {
-            // Write the predecessor value for rollback
-            batch.0.put_cf(state_cf, "pred/tx_queue", pred_tx_queue);
-        }
-        batch
-            .0
-            .put_cf(state_cf, "tx_queue", types::encode(&tx_queue));
-        batch.0.put_cf(
-            state_cf,
-            "ethereum_height",
-            types::encode(&ethereum_height),
-        );
-        batch.0.put_cf(
-            state_cf,
-            "eth_events_queue",
-            types::encode(&eth_events_queue),
-        );
+        put_state("tx_queue", types::encode(&tx_queue));
+        put_state("ethereum_height", types::encode(&ethereum_height));
+        put_state("eth_events_queue", types::encode(&eth_events_queue));
 
         let block_cf = self.get_column_family(BLOCK_CF)?;
         let prefix_key = Key::from(height.to_db_key());
@@ -1446,6 +1512,20 @@ impl DB for RocksDB {
         Ok(())
     }
 
+    fn prune_subspace_diffs(
+        &mut self,
+        batch: &mut Self::WriteBatch,
+        height: BlockHeight,
+    ) -> Result<()> {
+        let diffs_cf = self.get_column_family(DIFFS_CF)?;
+        // Both the "old" and "new" diffs are prepended with the height. The
+        // upper bound is the prefix with the trailing separator incremented.
+        let from = format!("{}/", height.raw());
+        let to = format!("{}0", height.raw());
+        batch.0.delete_range_cf(diffs_cf, from, to);
+        Ok(())
+    }
+
     fn write_replay_protection_entry(
         &mut self,
         batch: &mut Self::WriteBatch,
@@ -1473,6 +1553,101 @@ impl DB for RocksDB {
 
         Ok(())
     }
+
+    fn batch_write_events(
+        &self,
+        batch: &mut Self::WriteBatch,
+        height: BlockHeight,
+        events: &[IndexedEvent],
+    ) -> Result<()> {
+        let events_cf = self.get_column_family(EVENTS_CF)?;
+        // The events of a replayed block replace the ones written before
+        let prefix = events_height_prefix(height);
+        self.batch_delete_events(
+            batch,
+            iter_prefix(self, events_cf, String::default(), Some(prefix)),
+        )?;
+        for (n, event) in events.iter().enumerate() {
+            let n = n as u64;
+            for index_key in &event.index_keys {
+                batch.0.put_cf(
+                    events_cf,
+                    event_index_key(index_key, height, n),
+                    vec![],
+                );
+            }
+            batch.0.put_cf(
+                events_cf,
+                event_key(height, n),
+                types::encode(event),
+            );
+        }
+        Ok(())
+    }
+
+    fn read_events(&self, height: BlockHeight) -> Result<Vec<Vec<u8>>> {
+        let events_cf = self.get_column_family(EVENTS_CF)?;
+        let prefix = events_height_prefix(height);
+        iter_prefix(self, events_cf, prefix.clone(), Some(prefix))
+            .map(|(_, value, _)| {
+                types::decode::<IndexedEvent>(value)
+                    .map(|indexed| indexed.event)
+                    .map_err(Error::CodingError)
+            })
+            .collect()
+    }
+
+    fn read_indexed_events(&self, index_key: &str) -> Result<Vec<Vec<u8>>> {
+        let events_cf = self.get_column_family(EVENTS_CF)?;
+        let prefix = event_index_prefix(index_key);
+        let entries: Vec<String> =
+            iter_prefix(self, events_cf, prefix.clone(), Some(prefix))
+                .filter_map(|(entry, _, _)| {
+                    parse_event_index_entry(&entry)
+                        .map(|(height, n)| event_key(height, n))
+                })
+                .collect();
+        let mut events = Vec::with_capacity(entries.len());
+        for key in entries.into_iter().rev() {
+            if let Some(value) = self
+                .0
+                .get_cf(events_cf, key)
+                .map_err(|e| Error::DBError(e.into_string()))?
+            {
+                let indexed: IndexedEvent =
+                    types::decode(value).map_err(Error::CodingError)?;
+                events.push(indexed.event);
+            }
+        }
+        Ok(events)
+    }
+
+    fn prune_events(
+        &mut self,
+        batch: &mut Self::WriteBatch,
+        below_height: BlockHeight,
+    ) -> Result<()> {
+        let events_cf = self.get_column_family(EVENTS_CF)?;
+        let pruned = iter_prefix(
+            self,
+            events_cf,
+            String::default(),
+            Some(EVENTS_HEIGHT_PREFIX.to_string()),
+        )
+        .take_while(|(key, _, _)| {
+            parse_event_key(key)
+                .map(|(height, _)| height < below_height)
+                .unwrap_or_default()
+        });
+        self.batch_delete_events(batch, pruned)
+    }
+
+    fn checkpoint(&self, path: &Path) -> Result<()> {
+        // The SST files are hard-linked, so this is cheap
+        Checkpoint::new(&self.0)
+            .and_then(|checkpoint| checkpoint.create_checkpoint(path))
+            .map_err(|e| Error::DBError(e.into_string()))
+    }
 }
 
 impl<'iter> DBIter<'iter> for RocksDB {
@@ -1524,6 +1699,19 @@ impl<'iter> DBIter<'iter> for RocksDB {
 
         iter_prefix(self, replay_protection_cf, "last".to_string(), None)
     }
+
+    fn iter_all_replay_protection(&'iter self) -> Self::PrefixIter {
+        let replay_protection_cf = self
+            .get_column_family(REPLAY_PROTECTION_CF)
+            .expect("{REPLAY_PROTECTION_CF} column family should exist");
+
+        iter_prefix(
+            self,
+            replay_protection_cf,
+            "all".to_string(),
+            Some("all/".to_string()),
+        )
+    }
 }
 
 fn iter_subspace_prefix<'iter>(
@@ -1703,347 +1891,124 @@ mod imp {
 
 #[cfg(test)]
 mod test {
-    use namada::ledger::storage::{MerkleTree, Sha256Hasher};
-    use namada::types::address::{
-        gen_established_address, EstablishedAddressGen,
-    };
-    use namada::types::storage::{BlockHash, Epoch, Epochs};
+    use namada::types::address::gen_established_address;
+    use namada::types::storage::Epochs;
     use tempfile::tempdir;
     use test_log::test;
 
+    use super::super::db_tests;
     use super::*;
 
-    /// Test that a block written can be loaded back from DB.
     #[test]
     fn test_load_state() {
         let dir = tempdir().unwrap();
         let mut db = open(dir.path(), None).unwrap();
-
-        let mut batch = RocksDB::batch();
-        let last_height = BlockHeight::default();
-        db.batch_write_subspace_val(
-            &mut batch,
-            last_height,
-            &Key::parse("test").unwrap(),
-            vec![1_u8, 1, 1, 1],
-        )
-        .unwrap();
-
-        add_block_to_batch(
-            &db,
-            &mut batch,
-            BlockHeight::default(),
-            Epoch::default(),
-            Epochs::default(),
-            &ConversionState::default(),
-        )
-        .unwrap();
-        db.exec_batch(batch.0).unwrap();
-
-        let _state = db
-            .read_last_block()
-            .expect("Should be able to read last block")
-            .expect("Block should have been written");
+        db_tests::test_load_state(&mut db);
     }
 
     #[test]
     fn test_read() {
         let dir = tempdir().unwrap();
         let mut db = open(dir.path(), None).unwrap();
-
-        let key = Key::parse("test").unwrap();
-        let batch_key = Key::parse("batch").unwrap();
-
-        let mut batch = RocksDB::batch();
-        let last_height = BlockHeight(100);
-        db.batch_write_subspace_val(
-            &mut batch,
-            last_height,
-            &batch_key,
-            vec![1_u8, 1, 1, 1],
-        )
-        .unwrap();
-        db.exec_batch(batch.0).unwrap();
-
-        db.write_subspace_val(last_height, &key, vec![1_u8, 1, 1, 0])
-            .unwrap();
-
-        let mut batch = RocksDB::batch();
-        let last_height = BlockHeight(111);
-        db.batch_write_subspace_val(
-            &mut batch,
-            last_height,
-            &batch_key,
-            vec![2_u8, 2, 2, 2],
-        )
-        .unwrap();
-        db.exec_batch(batch.0).unwrap();
-
-        db.write_subspace_val(last_height, &key, vec![2_u8, 2, 2, 0])
-            .unwrap();
-
-        let prev_value = db
-            .read_subspace_val_with_height(
-                &batch_key,
-                BlockHeight(100),
-                last_height,
-            )
-            .expect("read should succeed");
-        assert_eq!(prev_value, Some(vec![1_u8, 1, 1, 1]));
-        let prev_value = db
-            .read_subspace_val_with_height(&key, BlockHeight(100), last_height)
-            .expect("read should succeed");
-        assert_eq!(prev_value, Some(vec![1_u8, 1, 1, 0]));
-
-        let updated_value = db
-            .read_subspace_val_with_height(
-                &batch_key,
-                BlockHeight(111),
-                last_height,
-            )
-            .expect("read should succeed");
-        assert_eq!(updated_value, Some(vec![2_u8, 2, 2, 2]));
-        let updated_value = db
-            .read_subspace_val_with_height(&key, BlockHeight(111), last_height)
-            .expect("read should succeed");
-        assert_eq!(updated_value, Some(vec![2_u8, 2, 2, 0]));
-
-        let latest_value = db
-            .read_subspace_val(&batch_key)
-            .expect("read should succeed");
-        assert_eq!(latest_value, Some(vec![2_u8, 2, 2, 2]));
-        let latest_value =
-            db.read_subspace_val(&key).expect("read should succeed");
-        assert_eq!(latest_value, Some(vec![2_u8, 2, 2, 0]));
-
-        let mut batch = RocksDB::batch();
-        let last_height = BlockHeight(222);
-        db.batch_delete_subspace_val(&mut batch, last_height, &batch_key)
-            .unwrap();
-        db.exec_batch(batch.0).unwrap();
-
-        db.delete_subspace_val(last_height, &key).unwrap();
-
-        let deleted_value = db
-            .read_subspace_val_with_height(
-                &batch_key,
-                BlockHeight(222),
-                last_height,
-            )
-            .expect("read should succeed");
-        assert_eq!(deleted_value, None);
-        let deleted_value = db
-            .read_subspace_val_with_height(&key, BlockHeight(222), last_height)
-            .expect("read should succeed");
-        assert_eq!(deleted_value, None);
-
-        let latest_value = db
-            .read_subspace_val(&batch_key)
-            .expect("read should succeed");
-        assert_eq!(latest_value, None);
-        let latest_value =
-            db.read_subspace_val(&key).expect("read should succeed");
-        assert_eq!(latest_value, None);
+        db_tests::test_read(&mut db);
     }
 
     #[test]
     fn test_prefix_iter() {
         let dir = tempdir().unwrap();
         let mut db = open(dir.path(), None).unwrap();
+        db_tests::test_prefix_iter(&mut db);
+    }
 
-        let prefix_0 = Key::parse("0").unwrap();
-        let key_0_a = prefix_0.push(&"a".to_string()).unwrap();
-        let key_0_b = prefix_0.push(&"b".to_string()).unwrap();
-        let key_0_c = prefix_0.push(&"c".to_string()).unwrap();
-        let prefix_1 = Key::parse("1").unwrap();
-        let key_1_a = prefix_1.push(&"a".to_string()).unwrap();
-        let key_1_b = prefix_1.push(&"b".to_string()).unwrap();
-        let key_1_c = prefix_1.push(&"c".to_string()).unwrap();
-        let prefix_01 = Key::parse("01").unwrap();
-        let key_01_a = prefix_01.push(&"a".to_string()).unwrap();
-
-        let keys_0 = vec![key_0_a, key_0_b, key_0_c];
-        let keys_1 = vec![key_1_a, key_1_b, key_1_c];
-        let keys_01 = vec![key_01_a];
-        let all_keys = vec![keys_0.clone(), keys_01, keys_1.clone()].concat();
-
-        // Write the keys
-        let mut batch = RocksDB::batch();
-        let height = BlockHeight(1);
-        for key in &all_keys {
-            db.batch_write_subspace_val(&mut batch, height, key, [0_u8])
-                .unwrap();
-        }
-        db.exec_batch(batch.0).unwrap();
-
-        // Prefix "0" shouldn't match prefix "01"
-        let itered_keys: Vec<Key> = db
-            .iter_prefix(Some(&prefix_0))
-            .map(|(key, _val, _)| Key::parse(key).unwrap())
-            .collect();
-        itertools::assert_equal(keys_0, itered_keys);
-
-        let itered_keys: Vec<Key> = db
-            .iter_prefix(Some(&prefix_1))
-            .map(|(key, _val, _)| Key::parse(key).unwrap())
-            .collect();
-        itertools::assert_equal(keys_1, itered_keys);
-
-        let itered_keys: Vec<Key> = db
-            .iter_prefix(None)
-            .map(|(key, _val, _)| Key::parse(key).unwrap())
-            .collect();
-        itertools::assert_equal(all_keys, itered_keys);
+    #[test]
+    fn test_prune_subspace_diffs() {
+        let dir = tempdir().unwrap();
+        let mut db = open(dir.path(), None).unwrap();
+        db_tests::test_prune_subspace_diffs(&mut db);
+    }
+
+    #[test]
+    fn test_events() {
+        let dir = tempdir().unwrap();
+        let mut db = open(dir.path(), None).unwrap();
+        db_tests::test_events(&mut db);
     }
 
     #[test]
     fn test_rollback() {
         let dir = tempdir().unwrap();
         let mut db = open(dir.path(), None).unwrap();
+        db_tests::test_rollback(&mut db);
+    }
 
-        // A key that's gonna be added on a second block
-        let add_key = Key::parse("add").unwrap();
-        // A key that's gonna be deleted on a second block
-        let delete_key = Key::parse("delete").unwrap();
-        // A key that's gonna be overwritten on a second block
-        let overwrite_key = Key::parse("overwrite").unwrap();
+    #[test]
+    fn test_checkpoint() {
+        let dir = tempdir().unwrap();
+        let mut db = open(dir.path().join("db"), None).unwrap();
+        db_tests::test_checkpoint(&mut db, &dir.path().join("checkpoint"));
+    }
 
-        // Write first block
-        let mut batch = RocksDB::batch();
-        let height_0 = BlockHeight(100);
+    /// Test that the last block of a DB written by a version that kept the
+    /// predecessor block state under `pred/` can still be rolled back
+    #[test]
+    fn test_rollback_legacy_pred_state() {
+        let dir = tempdir().unwrap();
+        let mut db = open(dir.path(), None).unwrap();
         let mut pred_epochs = Epochs::default();
-        pred_epochs.new_epoch(height_0);
         let mut conversion_state_0 = ConversionState::default();
         conversion_state_0
             .tokens
-            .insert("dummy1".to_string(), gen_established_address("test"));
-        let to_delete_val = vec![1_u8, 1, 0, 0];
-        let to_overwrite_val = vec![1_u8, 1, 1, 0];
-        db.batch_write_subspace_val(
-            &mut batch,
-            height_0,
-            &delete_key,
-            &to_delete_val,
-        )
-        .unwrap();
-        db.batch_write_subspace_val(
-            &mut batch,
-            height_0,
-            &overwrite_key,
-            &to_overwrite_val,
-        )
-        .unwrap();
-
-        add_block_to_batch(
-            &db,
-            &mut batch,
-            height_0,
-            Epoch(1),
-            pred_epochs.clone(),
-            &conversion_state_0,
-        )
-        .unwrap();
-        db.exec_batch(batch.0).unwrap();
-
-        // Write second block
-        let mut batch = RocksDB::batch();
-        let height_1 = BlockHeight(101);
-        pred_epochs.new_epoch(height_1);
-        let mut conversion_state_1 = ConversionState::default();
-        conversion_state_1
-            .tokens
-            .insert("dummy2".to_string(), gen_established_address("test"));
-        let add_val = vec![1_u8, 0, 0, 0];
-        let overwrite_val = vec![1_u8, 1, 1, 1];
-        db.batch_write_subspace_val(&mut batch, height_1, &add_key, &add_val)
-            .unwrap();
-        db.batch_write_subspace_val(
-            &mut batch,
-            height_1,
-            &overwrite_key,
-            &overwrite_val,
-        )
-        .unwrap();
-        db.batch_delete_subspace_val(&mut batch, height_1, &delete_key)
+            .insert("dummy".to_string(), gen_established_address("test"));
+        for (height, epoch, conversion_state) in [
+            (BlockHeight(100), Epoch(1), &conversion_state_0),
+            (BlockHeight(101), Epoch(2), &ConversionState::default()),
+        ] {
+            pred_epochs.new_epoch(height);
+            let mut batch = RocksDB::batch();
+            db_tests::add_block_to_batch(
+                &db,
+                &mut batch,
+                height,
+                epoch,
+                pred_epochs.clone(),
+                conversion_state,
+            )
             .unwrap();
+            DB::exec_batch(&mut db, batch).unwrap();
+        }
 
-        add_block_to_batch(
-            &db,
-            &mut batch,
-            height_1,
-            Epoch(2),
-            pred_epochs,
-            &conversion_state_1,
-        )
-        .unwrap();
-        db.exec_batch(batch.0).unwrap();
-
-        // Check that the values are as expected from second block
-        let added = db.read_subspace_val(&add_key).unwrap();
-        assert_eq!(added, Some(add_val));
-        let overwritten = db.read_subspace_val(&overwrite_key).unwrap();
-        assert_eq!(overwritten, Some(overwrite_val));
-        let deleted = db.read_subspace_val(&delete_key).unwrap();
-        assert_eq!(deleted, None);
-
-        // Rollback to the first block height
-        db.rollback(height_0).unwrap();
-
-        // Check that the values are back to the state at the first block
-        let added = db.read_subspace_val(&add_key).unwrap();
-        assert_eq!(added, None);
-        let overwritten = db.read_subspace_val(&overwrite_key).unwrap();
-        assert_eq!(overwritten, Some(to_overwrite_val));
-        let deleted = db.read_subspace_val(&delete_key).unwrap();
-        assert_eq!(deleted, Some(to_delete_val));
-        // Check the conversion state
+        // Replace the block state recorded in the diffs at the first height
+        // with the predecessor values
         let state_cf = db.get_column_family(STATE_CF).unwrap();
-        let conversion_state =
-            db.0.get_cf(state_cf, "conversion_state".as_bytes())
-                .unwrap()
-                .unwrap();
-        assert_eq!(conversion_state, types::encode(&conversion_state_0));
-    }
-
-    /// A test helper to write a block
-    fn add_block_to_batch(
-        db: &RocksDB,
-        batch: &mut RocksDBWriteBatch,
-        height: BlockHeight,
-        epoch: Epoch,
-        pred_epochs: Epochs,
-        conversion_state: &ConversionState,
-    ) -> Result<()> {
-        let merkle_tree = MerkleTree::<Sha256Hasher>::default();
-        let merkle_tree_stores = merkle_tree.stores();
-        let hash = BlockHash::default();
-        let time = DateTimeUtc::now();
-        let next_epoch_min_start_height = BlockHeight::default();
-        let next_epoch_min_start_time = DateTimeUtc::now();
-        let update_epoch_blocks_delay = None;
-        let address_gen = EstablishedAddressGen::new("whatever");
-        let tx_queue = TxQueue::default();
-        let results = BlockResults::default();
-        let eth_events_queue = EthEventsQueue::default();
-        let block = BlockStateWrite {
-            merkle_tree_stores,
-            header: None,
-            hash: &hash,
-            height,
-            time,
-            epoch,
-            results: &results,
-            conversion_state,
-            pred_epochs: &pred_epochs,
-            next_epoch_min_start_height,
-            next_epoch_min_start_time,
-            update_epoch_blocks_delay,
-            address_gen: &address_gen,
-            tx_queue: &tx_queue,
-            ethereum_height: None,
-            eth_events_queue: &eth_events_queue,
-        };
-
-        db.add_block_to_batch(block, batch, true)
+        let diffs_cf = db.get_column_family(DIFFS_CF).unwrap();
+        let mut batch = RocksDB::batch();
+        for state_key in ROLLBACK_STATE_KEYS
+            .into_iter()
+            .chain([CONVERSION_STATE_KEY])
+        {
+            let diff_key = state_diff_key(BlockHeight(100), state_key);
+            let value = db.0.get_cf(diffs_cf, &diff_key).unwrap().unwrap();
+            batch.0.delete_cf(diffs_cf, diff_key);
+            if LEGACY_PRED_STATE_KEYS.contains(&state_key) {
+                batch.0.put_cf(state_cf, legacy_pred_key(state_key), value);
+            }
+        }
+        DB::exec_batch(&mut db, batch).unwrap();
+
+        db.rollback(BlockHeight(100)).unwrap();
+        let last_block = db.read_last_block().unwrap().unwrap();
+        assert_eq!(last_block.height, BlockHeight(100));
+        assert_eq!(
+            types::encode(&last_block.conversion_state),
+            types::encode(&conversion_state_0)
+        );
+        // The stale predecessor values are removed
+        for state_key in LEGACY_PRED_STATE_KEYS {
+            assert!(
+                db.0.get_cf(state_cf, legacy_pred_key(state_key))
+                    .unwrap()
+                    .is_none()
+            );
+        }
     }
 }
@@ -0,0 +1,247 @@
+//! Rollback of the persistent storage to a past block height, using the
+//! subspace diffs and the block state recorded at every height.
+
+use std::collections::BTreeMap;
+
+use namada::ledger::storage::merkle_tree::base_tree_key_prefix;
+use namada::ledger::storage::{DBIter, Error, Result, DB};
+use namada::types::storage::{BlockHeight, Epoch, Key, KeySeg};
+
+/// The block state keys whose value is recorded in the diffs at every height,
+/// so that they can be restored by a rollback. The conversion state is only
+/// recorded on a full commit, under [`CONVERSION_STATE_KEY`].
+pub(super) const ROLLBACK_STATE_KEYS: [&str; 6] = [
+    "next_epoch_min_start_height",
+    "next_epoch_min_start_time",
+    "update_epoch_blocks_delay",
+    "tx_queue",
+    "ethereum_height",
+    "eth_events_queue",
+];
+
+/// The block state key of the MASP conversion state
+pub(super) const CONVERSION_STATE_KEY: &str = "conversion_state";
+
+/// The block state keys whose predecessor value was written under
+/// `pred/{key}` by the versions that didn't record the block state in the
+/// diffs. They're only read to roll back the last block of a DB written by
+/// such a version, and are removed on the next commit.
+pub(super) const LEGACY_PRED_STATE_KEYS: [&str; 5] = [
+    "next_epoch_min_start_height",
+    "next_epoch_min_start_time",
+    "update_epoch_blocks_delay",
+    "tx_queue",
+    CONVERSION_STATE_KEY,
+];
+
+/// The state key of the predecessor value of a legacy block state key
+pub(super) fn legacy_pred_key(state_key: &str) -> String {
+    format!("pred/{state_key}")
+}
+
+/// Read the value of a block state key at the target height from a DB
+/// written by a version that didn't record the block state in the diffs.
+/// Only the last block can be rolled back, using the predecessor values. The
+/// keys without a predecessor value are left as they are, like these versions
+/// did.
+pub(super) fn read_legacy_state(
+    target_height: BlockHeight,
+    last_height: BlockHeight,
+    state_key: &str,
+    read_state: impl Fn(&str) -> Result<Option<Vec<u8>>>,
+) -> Result<Option<Vec<u8>>> {
+    if target_height.next_height() != last_height {
+        return Ok(None);
+    }
+    if LEGACY_PRED_STATE_KEYS.contains(&state_key) {
+        read_state(&legacy_pred_key(state_key))
+    } else {
+        read_state(state_key)
+    }
+}
+
+/// The diffs key of the value of a block state key at the given height
+pub(super) fn state_diff_key(height: BlockHeight, state_key: &str) -> String {
+    format!("{}/state/{state_key}", height.raw())
+}
+
+/// The diffs key prefix of the hashes of the txs included in the block at the
+/// given height
+pub(super) fn replay_protection_diff_prefix(height: BlockHeight) -> String {
+    format!("{}/replay_protection/", height.raw())
+}
+
+/// The keys of the block table written for the block at the given height.
+/// The subtree stores are excluded, they're written per epoch.
+pub(super) fn block_keys(height: BlockHeight) -> Vec<String> {
+    let base_prefix = base_tree_key_prefix(height);
+    let mut keys = vec![
+        base_prefix
+            .clone()
+            .with_segment("root".to_owned())
+            .to_string(),
+        base_prefix.with_segment("store".to_owned()).to_string(),
+        format!("results/{}", height.raw()),
+    ];
+    for segment in [
+        "header",
+        "hash",
+        "time",
+        "epoch",
+        "pred_epochs",
+        "address_gen",
+    ] {
+        keys.push(format!("{}/{segment}", height.raw()));
+    }
+    keys
+}
+
+/// A rollback of the DB to a past block height. It's planned from the
+/// retained diffs before anything is written, so that an invalid target
+/// height is rejected without side-effects and a dry-run can show the
+/// changes.
+#[derive(Debug)]
+pub struct RollbackPlan {
+    /// The height of the last committed block
+    pub last_height: BlockHeight,
+    /// The epoch of the last committed block
+    pub last_epoch: Epoch,
+    /// The height to roll back to
+    pub target_height: BlockHeight,
+    /// The epoch of the target height
+    pub target_epoch: Epoch,
+    /// The values of the block state keys at the target height
+    pub state: Vec<(&'static str, Vec<u8>)>,
+    /// The value at the target height of every subspace key changed in the
+    /// blocks after it. `None` if the key didn't exist.
+    pub subspace: BTreeMap<String, Option<Vec<u8>>>,
+    /// The hashes of the txs included in the block at the target height,
+    /// which become again the replay protection entries of the last block
+    pub last_tx_hashes: Vec<String>,
+    /// The hashes of the txs included in the blocks from the target height
+    /// to the one preceding the last block, to be removed from the replay
+    /// protection entries of all the committed blocks
+    pub removed_tx_hashes: Vec<String>,
+}
+
+impl RollbackPlan {
+    /// Check if there's nothing to roll back
+    pub fn is_empty(&self) -> bool {
+        self.target_height == self.last_height
+    }
+
+    /// Print the changes of the rollback to stdout
+    pub fn print<D>(&self, db: &D) -> Result<()>
+    where
+        D: DB,
+    {
+        println!(
+            "Rolling back from height {} (epoch {}) to height {} (epoch {})",
+            self.last_height,
+            self.last_epoch,
+            self.target_height,
+            self.target_epoch
+        );
+        let mut changed = 0_usize;
+        for (key, value) in &self.subspace {
+            let current = db.read_subspace_val(
+                &Key::parse(key).map_err(Error::KeyError)?,
+            )?;
+            let change = match (current, value) {
+                (None, None) => continue,
+                (Some(_), None) => "delete",
+                (None, Some(_)) => "restore",
+                (Some(current), Some(value)) if &current == value => continue,
+                (Some(_), Some(_)) => "revert",
+            };
+            println!("  {change} {key}");
+            changed += 1;
+        }
+        println!(
+            "{changed} subspace keys would change, {} blocks would be removed \
+             and {} tx hashes would be removed from the replay protection",
+            self.last_height.0 - self.target_height.0,
+            self.removed_tx_hashes.len(),
+        );
+        Ok(())
+    }
+}
+
+/// A DB that can be rolled back to a past block height, as long as the diffs
+/// of the heights after it are retained
+pub trait DBRollback {
+    /// Plan a rollback to the given height without writing anything
+    fn plan_rollback(&self, target_height: BlockHeight)
+        -> Result<RollbackPlan>;
+
+    /// Write a planned rollback
+    fn apply_rollback(&mut self, plan: RollbackPlan) -> Result<()>;
+
+    /// Roll back to the given height
+    fn rollback(&mut self, target_height: BlockHeight) -> Result<()> {
+        let plan = self.plan_rollback(target_height)?;
+        self.apply_rollback(plan)
+    }
+}
+
+/// Find the value at the target height of every subspace key that has been
+/// changed in the blocks after it, up to the last height
+pub(super) fn rollback_subspace<D>(
+    db: &D,
+    target_height: BlockHeight,
+    last_height: BlockHeight,
+) -> Result<BTreeMap<String, Option<Vec<u8>>>>
+where
+    D: DB + for<'iter> DBIter<'iter>,
+{
+    let mut subspace = BTreeMap::new();
+    for raw_height in target_height.0 + 1..=last_height.0 {
+        let height = BlockHeight(raw_height);
+        let changed_keys = db
+            .iter_old_diffs(height, None)
+            .chain(db.iter_new_diffs(height, None))
+            .map(|(key, _, _)| key);
+        for key in changed_keys {
+            if subspace.contains_key(&key) {
+                continue;
+            }
+            let value = db.read_subspace_val_with_height(
+                &Key::parse(&key).map_err(Error::KeyError)?,
+                target_height,
+                last_height,
+            )?;
+            subspace.insert(key, value);
+        }
+    }
+    Ok(subspace)
+}
+
+/// Check the target height of a rollback against the last committed block
+pub(super) fn check_rollback_target(
+    target_height: BlockHeight,
+    last_height: BlockHeight,
+) -> Result<()> {
+    if target_height > last_height {
+        return Err(Error::DBError(format!(
+            "Cannot roll back to height {target_height} above the last \
+             committed height {last_height}"
+        )));
+    }
+    if target_height.0 == 0 {
+        return Err(Error::DBError(
+            "Cannot roll back to before the first block".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// The error of a rollback target height whose state isn't retained
+pub(super) fn not_retained_error(
+    target_height: BlockHeight,
+    what: &str,
+) -> Error {
+    Error::DBError(format!(
+        "The {what} at height {target_height} is not retained in the DB, the \
+         rollback cannot go further back than the retained diffs"
+    ))
+}
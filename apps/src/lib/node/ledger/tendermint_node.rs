@@ -173,20 +173,60 @@ pub fn reset(tendermint_dir: impl AsRef<Path>) -> Result<()> {
 }
 
 pub fn rollback(tendermint_dir: impl AsRef<Path>) -> Result<BlockHeight> {
+    rollback_once(tendermint_dir.as_ref(), false)
+}
+
+/// Roll back the CometBFT state and blocks to the given height. CometBFT only
+/// rolls back one height at a time, so its hard rollbacks are repeated in
+/// place, at most `max_rollbacks` times. They can't be undone if the target
+/// height isn't reached: the CometBFT directory should be backed up
+/// beforehand, as advised by the rollback command, to recover from a failure.
+pub fn rollback_to(
+    tendermint_dir: impl AsRef<Path>,
+    target_height: BlockHeight,
+    max_rollbacks: u64,
+) -> Result<BlockHeight> {
+    let tendermint_dir = tendermint_dir.as_ref();
+    for _ in 0..max_rollbacks {
+        // A hard rollback also removes the last block, so that it can be
+        // repeated to go further back
+        let height = rollback_once(tendermint_dir, true)?;
+        tracing::info!("Rolled back CometBFT state to height {height}");
+        if height < target_height {
+            return Err(Error::RollBack(format!(
+                "CometBFT state rolled back to height {height}, below the \
+                 target height {target_height}"
+            )));
+        }
+        if height == target_height {
+            return Ok(height);
+        }
+    }
+    Err(Error::RollBack(format!(
+        "CometBFT state is still above the target height {target_height} \
+         after {max_rollbacks} rollbacks"
+    )))
+}
+
+fn rollback_once(tendermint_dir: &Path, hard: bool) -> Result<BlockHeight> {
     let tendermint_path = from_env_or_default()?;
-    let tendermint_dir = tendermint_dir.as_ref().to_string_lossy();
+    let tendermint_dir = tendermint_dir.to_string_lossy();
 
     // Rollback tendermint state, see https://github.com/tendermint/tendermint/blob/main/cmd/tendermint/commands/rollback.go for details
     // on how the tendermint rollback behaves
-    let output = std::process::Command::new(tendermint_path)
-        .args([
-            "rollback",
-            "unsafe-all",
-            // NOTE: log config: https://docs.tendermint.com/master/nodes/logging.html#configuring-log-levels
-            // "--log-level=\"*debug\"",
-            "--home",
-            &tendermint_dir,
-        ])
+    let mut command = std::process::Command::new(tendermint_path);
+    command.args([
+        "rollback",
+        "unsafe-all",
+        // NOTE: log config: https://docs.tendermint.com/master/nodes/logging.html#configuring-log-levels
+        // "--log-level=\"*debug\"",
+        "--home",
+        &tendermint_dir,
+    ]);
+    if hard {
+        command.arg("--hard");
+    }
+    let output = command
         .output()
         .map_err(|e| Error::RollBack(e.to_string()))?;
 
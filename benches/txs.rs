@@ -385,6 +385,7 @@ fn update_account(c: &mut Criterion) {
         )),
         public_keys: vec![defaults::albert_keypair().ref_to()],
         threshold: None,
+        key_policies: None,
     };
     let vp = shell.generate_tx(
         TX_UPDATE_ACCOUNT_WASM,
@@ -428,6 +429,7 @@ fn init_account(c: &mut Criterion) {
         public_keys: vec![new_account.to_public()],
         vp_code_hash: extra_hash,
         threshold: 1,
+        vesting: None,
     };
     let tx = shell.generate_tx(
         TX_INIT_ACCOUNT_WASM,
@@ -86,6 +86,7 @@ fn vp_user(c: &mut Criterion) {
         )),
         public_keys: vec![defaults::albert_keypair().to_public()],
         threshold: None,
+        key_policies: None,
     };
     let vp = shell.generate_tx(
         TX_UPDATE_ACCOUNT_WASM,
@@ -373,6 +374,7 @@ fn vp_validator(c: &mut Criterion) {
         )),
         public_keys: vec![defaults::validator_keypair().to_public()],
         threshold: None,
+        key_policies: None,
     };
     let vp = shell.generate_tx(
         TX_UPDATE_ACCOUNT_WASM,
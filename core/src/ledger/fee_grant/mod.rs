@@ -0,0 +1,9 @@
+//! Fee grants library code
+
+use crate::types::address::{Address, InternalAddress};
+
+/// Fee grants storage
+pub mod storage;
+
+/// The fee grants internal address
+pub const ADDRESS: Address = Address::Internal(InternalAddress::FeeGrant);
@@ -0,0 +1,52 @@
+//! The storage layout of the fee grants:
+//! - `#feegrant/allowance/{granter}/{grantee}`: the fee allowance granted by a
+//!   granter to a grantee
+
+use namada_macros::StorageKeys;
+
+use crate::ledger::fee_grant::ADDRESS;
+use crate::types::address::Address;
+use crate::types::storage::{DbKeySeg, Key, KeySeg};
+
+/// Storage keys for the fee grants internal address.
+#[derive(StorageKeys)]
+struct Keys {
+    allowance: &'static str,
+}
+
+/// Check if key is inside the fee grants address space
+pub fn is_fee_grant_key(key: &Key) -> bool {
+    matches!(&key.segments[0], DbKeySeg::AddressSeg(addr) if addr == &ADDRESS)
+}
+
+/// Check if key is a fee allowance key. If it is, returns the granter and
+/// the grantee.
+pub fn is_allowance_key(key: &Key) -> Option<(&Address, &Address)> {
+    match &key.segments[..] {
+        [
+            DbKeySeg::AddressSeg(addr),
+            DbKeySeg::StringSeg(prefix),
+            DbKeySeg::AddressSeg(granter),
+            DbKeySeg::AddressSeg(grantee),
+        ] if addr == &ADDRESS && prefix == Keys::VALUES.allowance => {
+            Some((granter, grantee))
+        }
+        _ => None,
+    }
+}
+
+/// Get the prefix of the fee allowances granted by the given granter
+pub fn get_allowances_prefix(granter: &Address) -> Key {
+    Key::from(ADDRESS.to_db_key())
+        .push(&Keys::VALUES.allowance.to_owned())
+        .expect("Cannot obtain a storage key")
+        .push(granter)
+        .expect("Cannot obtain a storage key")
+}
+
+/// Get the key of the fee allowance granted by a granter to a grantee
+pub fn get_allowance_key(granter: &Address, grantee: &Address) -> Key {
+    get_allowances_prefix(granter)
+        .push(grantee)
+        .expect("Cannot obtain a storage key")
+}
@@ -0,0 +1,2 @@
+/// Fee grants storage keys
+pub mod keys;
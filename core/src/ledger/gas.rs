@@ -1,10 +1,12 @@
 //! Gas accounting module to track the gas usage in a block for transactions and
 //! validity predicates triggered by transactions.
 
+use std::collections::BTreeMap;
 use std::fmt::Display;
 use std::ops::Div;
 
 use borsh::{BorshDeserialize, BorshSchema, BorshSerialize};
+use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
 use super::parameters;
@@ -56,6 +58,86 @@ pub const IBC_ACTION_EXECUTE_GAS: u64 = 47_452;
 /// The cost to execute a masp tx verification
 pub const MASP_VERIFY_SHIELDED_TX_GAS: u64 = 62_381_957;
 
+/// The cost of every wasm instruction executed. Set to 0 by default, in which
+/// case no gas metering is injected for the instructions.
+pub const WASM_INSTRUCTION_GAS: u32 = 0;
+
+/// The gas costs charged by the host functions and injected into the wasm
+/// code. The table in force at a given block height is governed by the
+/// [`parameters::read_gas_cost_table`] protocol parameter and defaults to the
+/// constants defined in this module.
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    BorshSerialize,
+    BorshDeserialize,
+    BorshSchema,
+    Serialize,
+    Deserialize,
+)]
+pub struct GasCostTable {
+    /// The cost of compiling wasm code, per byte
+    pub compile_per_byte: u64,
+    /// The cost of validating untrusted wasm code, per byte
+    pub wasm_code_validation_per_byte: u64,
+    /// The cost of validating a wrapper transaction
+    pub wrapper_tx_validation: u64,
+    /// The cost of the space occupied in a block or in storage, per byte
+    pub storage_occupation_per_byte: u64,
+    /// The cost of accessing data from memory, per byte
+    pub memory_access_per_byte: u64,
+    /// The cost of accessing data from storage, per byte
+    pub storage_access_per_byte: u64,
+    /// The cost of writing data to storage, per byte
+    pub storage_write_per_byte: u64,
+    /// The cost of verifying a single signature of a transaction
+    pub verify_tx_sig: u64,
+    /// The cost to validate an Ibc action
+    pub ibc_action_validate: u64,
+    /// The cost to execute an Ibc action
+    pub ibc_action_execute: u64,
+    /// The cost to execute a masp tx verification
+    pub masp_verify_shielded_tx: u64,
+    /// The cost of every wasm instruction executed
+    pub wasm_instruction: u32,
+    /// The cost for requesting one more page in wasm (64KiB)
+    pub wasm_memory_page: u32,
+}
+
+impl Default for GasCostTable {
+    fn default() -> Self {
+        Self {
+            compile_per_byte: COMPILE_GAS_PER_BYTE,
+            wasm_code_validation_per_byte: WASM_CODE_VALIDATION_GAS_PER_BYTE,
+            wrapper_tx_validation: WRAPPER_TX_VALIDATION_GAS,
+            storage_occupation_per_byte: STORAGE_OCCUPATION_GAS_PER_BYTE,
+            memory_access_per_byte: MEMORY_ACCESS_GAS_PER_BYTE,
+            storage_access_per_byte: STORAGE_ACCESS_GAS_PER_BYTE,
+            storage_write_per_byte: STORAGE_WRITE_GAS_PER_BYTE,
+            verify_tx_sig: VERIFY_TX_SIG_GAS,
+            ibc_action_validate: IBC_ACTION_VALIDATE_GAS,
+            ibc_action_execute: IBC_ACTION_EXECUTE_GAS,
+            masp_verify_shielded_tx: MASP_VERIFY_SHIELDED_TX_GAS,
+            wasm_instruction: WASM_INSTRUCTION_GAS,
+            wasm_memory_page: WASM_MEMORY_PAGE_GAS,
+        }
+    }
+}
+
+impl GasCostTable {
+    /// Check if the costs injected into the wasm code at compilation differ
+    /// from the default ones, in which case the compiled modules can't be
+    /// shared with the default table.
+    pub fn has_custom_wasm_rules(&self) -> bool {
+        let default = Self::default();
+        self.wasm_instruction != default.wasm_instruction
+            || self.wasm_memory_page != default.wasm_memory_page
+    }
+}
+
 /// Gas module result for functions that may fail
 pub type Result<T> = std::result::Result<T, Error>;
 
@@ -105,7 +187,7 @@ impl Gas {
 
     /// Converts the sub gas units to whole ones. If the sub units are not a
     /// multiple of the `SCALE` than ceil the quotient
-    fn get_whole_gas_units(&self) -> u64 {
+    pub fn get_whole_gas_units(&self) -> u64 {
         let quotient = self.sub / SCALE;
         if self.sub % SCALE == 0 {
             quotient
@@ -167,28 +249,40 @@ pub trait GasMetering {
     fn consume(&mut self, gas: u64) -> Result<()>;
 
     /// Add the compiling cost proportionate to the code length
-    fn add_compiling_gas(&mut self, bytes_len: u64) -> Result<()> {
+    fn add_compiling_gas(
+        &mut self,
+        bytes_len: u64,
+        costs: &GasCostTable,
+    ) -> Result<()> {
         self.consume(
             bytes_len
-                .checked_mul(COMPILE_GAS_PER_BYTE)
+                .checked_mul(costs.compile_per_byte)
                 .ok_or(Error::GasOverflow)?,
         )
     }
 
     /// Add the gas for loading the wasm code from storage
-    fn add_wasm_load_from_storage_gas(&mut self, bytes_len: u64) -> Result<()> {
+    fn add_wasm_load_from_storage_gas(
+        &mut self,
+        bytes_len: u64,
+        costs: &GasCostTable,
+    ) -> Result<()> {
         self.consume(
             bytes_len
-                .checked_mul(STORAGE_ACCESS_GAS_PER_BYTE)
+                .checked_mul(costs.storage_access_per_byte)
                 .ok_or(Error::GasOverflow)?,
         )
     }
 
     /// Add the gas for validating untrusted wasm code
-    fn add_wasm_validation_gas(&mut self, bytes_len: u64) -> Result<()> {
+    fn add_wasm_validation_gas(
+        &mut self,
+        bytes_len: u64,
+        costs: &GasCostTable,
+    ) -> Result<()> {
         self.consume(
             bytes_len
-                .checked_mul(WASM_CODE_VALIDATION_GAS_PER_BYTE)
+                .checked_mul(costs.wasm_code_validation_per_byte)
                 .ok_or(Error::GasOverflow)?,
         )
     }
@@ -206,6 +300,8 @@ pub struct TxGasMeter {
     /// The gas limit for a transaction
     pub tx_gas_limit: Gas,
     transaction_gas: Gas,
+    profiler: Option<GasProfiler>,
+    dummy_signatures: bool,
 }
 
 /// Gas metering in a validity predicate
@@ -217,6 +313,99 @@ pub struct VpGasMeter {
     initial_gas: Gas,
     /// The current gas usage in the VP
     current_gas: Gas,
+    profiler: Option<GasProfiler>,
+    dummy_signatures: bool,
+}
+
+/// The section of a profiled execution that the gas used before entering any
+/// other section is attributed to, e.g. loading and compiling the wasm code
+pub const SETUP_GAS_SECTION: &str = "setup";
+/// The section of a profiled execution that the gas charged by the wasm code
+/// itself is attributed to, i.e. the gas of its instructions
+pub const WASM_GAS_SECTION: &str = "wasm";
+/// The section of a profiled transaction that the gas used by its validity
+/// predicates is attributed to
+pub const VPS_GAS_SECTION: &str = "vps";
+
+/// The gas used by a section of a profiled execution
+#[derive(
+    Clone,
+    Copy,
+    Debug,
+    Default,
+    PartialEq,
+    BorshSerialize,
+    BorshDeserialize,
+    BorshSchema,
+)]
+pub struct GasProfileEntry {
+    /// The number of times the section was entered, e.g. the number of calls
+    /// of a host function
+    pub calls: u64,
+    /// The gas used in the section
+    pub gas: Gas,
+}
+
+/// The gas used by an execution, per section. The sections are the host
+/// functions called by the wasm code, named after them (e.g. `tx_read`), and
+/// the [`SETUP_GAS_SECTION`], [`WASM_GAS_SECTION`] and [`VPS_GAS_SECTION`].
+#[derive(
+    Clone, Debug, Default, PartialEq, BorshSerialize, BorshDeserialize,
+)]
+pub struct GasProfile(pub BTreeMap<String, GasProfileEntry>);
+
+impl GasProfile {
+    /// Get the total gas used by all the sections
+    pub fn total_gas(&self) -> Gas {
+        self.0.values().fold(Gas::default(), |acc, entry| {
+            acc.checked_add(entry.gas).unwrap_or(acc)
+        })
+    }
+}
+
+impl Display for GasProfile {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        // Sort the sections by decreasing gas
+        let mut entries: Vec<_> = self.0.iter().collect();
+        entries
+            .sort_by(|(_, a), (_, b)| u64::from(b.gas).cmp(&u64::from(a.gas)));
+        for (section, entry) in entries {
+            writeln!(
+                f,
+                "  {section:<40} {:>8} calls {:>14} gas",
+                entry.calls,
+                entry.gas.to_string()
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Records the gas used per section of an execution
+#[derive(Debug, Clone, Default)]
+struct GasProfiler {
+    /// The section the gas is currently attributed to
+    section: &'static str,
+    profile: GasProfile,
+}
+
+impl GasProfiler {
+    fn new() -> Self {
+        Self {
+            section: SETUP_GAS_SECTION,
+            profile: GasProfile::default(),
+        }
+    }
+
+    fn enter_section(&mut self, section: &'static str) {
+        self.section = section;
+        self.profile.0.entry(section.to_string()).or_default().calls += 1;
+    }
+
+    fn record(&mut self, gas: u64) {
+        let entry = self.profile.0.entry(self.section.to_string()).or_default();
+        entry.gas = entry.gas.checked_add(gas.into()).unwrap_or(entry.gas);
+    }
 }
 
 /// Gas meter for VPs parallel runs
@@ -230,6 +419,9 @@ pub struct VpsGas {
 
 impl GasMetering for TxGasMeter {
     fn consume(&mut self, gas: u64) -> Result<()> {
+        if let Some(profiler) = self.profiler.as_mut() {
+            profiler.record(gas);
+        }
         self.transaction_gas = self
             .transaction_gas
             .checked_add(gas.into())
@@ -258,6 +450,8 @@ impl TxGasMeter {
         Self {
             tx_gas_limit: tx_gas_limit.into(),
             transaction_gas: Gas::default(),
+            profiler: None,
+            dummy_signatures: false,
         }
     }
 
@@ -267,19 +461,57 @@ impl TxGasMeter {
         Self {
             tx_gas_limit,
             transaction_gas: Gas::default(),
+            profiler: None,
+            dummy_signatures: false,
         }
     }
 
+    /// Record the gas used per section of the execution from now on. The
+    /// gas meters of the VPs triggered by the transaction are profiled too.
+    pub fn enable_profiling(&mut self) {
+        self.profiler = Some(GasProfiler::new());
+    }
+
+    /// Attribute the gas consumed from now on to the given section of the
+    /// execution, if it's profiled
+    pub fn enter_section(&mut self, section: &'static str) {
+        if let Some(profiler) = self.profiler.as_mut() {
+            profiler.enter_section(section);
+        }
+    }
+
+    /// Get the gas used per section of the execution, if it's profiled
+    pub fn gas_profile(&self) -> Option<&GasProfile> {
+        self.profiler.as_ref().map(|profiler| &profiler.profile)
+    }
+
+    /// Charge the gas of the signature checks from now on without verifying
+    /// the signatures, to estimate the gas of a transaction that carries dummy
+    /// signatures. The VPs triggered by the transaction accept them too. Must
+    /// never be enabled for a transaction applied to the chain.
+    pub fn accept_dummy_signatures(&mut self) {
+        self.dummy_signatures = true;
+    }
+
+    /// Whether the signatures are charged without being verified
+    pub fn accepts_dummy_signatures(&self) -> bool {
+        self.dummy_signatures
+    }
+
     /// Add the gas required by a wrapper transaction which is comprised of:
     ///  - cost of validating the wrapper tx
     ///  - space that the transaction requires in the block
-    pub fn add_wrapper_gas(&mut self, tx_bytes: &[u8]) -> Result<()> {
-        self.consume(WRAPPER_TX_VALIDATION_GAS)?;
+    pub fn add_wrapper_gas(
+        &mut self,
+        tx_bytes: &[u8],
+        costs: &GasCostTable,
+    ) -> Result<()> {
+        self.consume(costs.wrapper_tx_validation)?;
 
         let bytes_len = tx_bytes.len() as u64;
         self.consume(
             bytes_len
-                .checked_mul(STORAGE_OCCUPATION_GAS_PER_BYTE)
+                .checked_mul(costs.storage_occupation_per_byte)
                 .ok_or(Error::GasOverflow)?,
         )
     }
@@ -299,6 +531,9 @@ impl TxGasMeter {
 
 impl GasMetering for VpGasMeter {
     fn consume(&mut self, gas: u64) -> Result<()> {
+        if let Some(profiler) = self.profiler.as_mut() {
+            profiler.record(gas);
+        }
         self.current_gas = self
             .current_gas
             .checked_add(gas.into())
@@ -326,14 +561,43 @@ impl GasMetering for VpGasMeter {
 }
 
 impl VpGasMeter {
+    /// Get the gas consumed by the VP alone
+    pub fn get_vp_consumed_gas(&self) -> Gas {
+        self.current_gas
+    }
+
     /// Initialize a new VP gas meter from the `TxGasMeter`
     pub fn new_from_tx_meter(tx_gas_meter: &TxGasMeter) -> Self {
         Self {
             tx_gas_limit: tx_gas_meter.tx_gas_limit,
             initial_gas: tx_gas_meter.transaction_gas,
             current_gas: Gas::default(),
+            profiler: tx_gas_meter
+                .profiler
+                .as_ref()
+                .map(|_| GasProfiler::new()),
+            dummy_signatures: tx_gas_meter.dummy_signatures,
         }
     }
+
+    /// Attribute the gas consumed from now on to the given section of the
+    /// execution, if it's profiled
+    pub fn enter_section(&mut self, section: &'static str) {
+        if let Some(profiler) = self.profiler.as_mut() {
+            profiler.enter_section(section);
+        }
+    }
+
+    /// Get the gas used per section of the execution, if it's profiled
+    pub fn gas_profile(&self) -> Option<&GasProfile> {
+        self.profiler.as_ref().map(|profiler| &profiler.profile)
+    }
+
+    /// Whether the signatures are charged without being verified, as in the
+    /// transaction that triggered the VP
+    pub fn accepts_dummy_signatures(&self) -> bool {
+        self.dummy_signatures
+    }
 }
 
 impl VpsGas {
@@ -405,6 +669,8 @@ mod tests {
         let tx_gas_meter = TxGasMeter {
             tx_gas_limit: BLOCK_GAS_LIMIT.into(),
             transaction_gas: Gas::default(),
+            profiler: None,
+            dummy_signatures: false,
         };
             let mut meter = VpGasMeter::new_from_tx_meter(&tx_gas_meter);
             meter.consume(gas).expect("cannot add the gas");
@@ -417,6 +683,8 @@ mod tests {
         let tx_gas_meter = TxGasMeter {
             tx_gas_limit: BLOCK_GAS_LIMIT.into(),
             transaction_gas: (TX_GAS_LIMIT - 1).into(),
+            profiler: None,
+            dummy_signatures: false,
         };
         let mut meter = VpGasMeter::new_from_tx_meter(&tx_gas_meter);
         assert_matches!(
@@ -430,6 +698,8 @@ mod tests {
         let tx_gas_meter = TxGasMeter {
             tx_gas_limit: TX_GAS_LIMIT.into(),
             transaction_gas: (TX_GAS_LIMIT - 1).into(),
+            profiler: None,
+            dummy_signatures: false,
         };
         let mut meter = VpGasMeter::new_from_tx_meter(&tx_gas_meter);
         assert_matches!(
@@ -450,6 +720,77 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_wrapper_gas_with_cost_table() {
+        let tx_bytes = [0_u8; 10];
+        let mut default_meter =
+            TxGasMeter::new_from_sub_limit(BLOCK_GAS_LIMIT.into());
+        default_meter
+            .add_wrapper_gas(&tx_bytes, &GasCostTable::default())
+            .expect("cannot add the gas");
+        assert_eq!(
+            u64::from(default_meter.get_tx_consumed_gas()),
+            WRAPPER_TX_VALIDATION_GAS + 10 * STORAGE_OCCUPATION_GAS_PER_BYTE
+        );
+
+        let costs = GasCostTable {
+            wrapper_tx_validation: 1_000,
+            storage_occupation_per_byte: 5,
+            ..Default::default()
+        };
+        let mut meter = TxGasMeter::new_from_sub_limit(BLOCK_GAS_LIMIT.into());
+        meter
+            .add_wrapper_gas(&tx_bytes, &costs)
+            .expect("cannot add the gas");
+        assert_eq!(u64::from(meter.get_tx_consumed_gas()), 1_050);
+        assert!(!costs.has_custom_wasm_rules());
+
+        let costs = GasCostTable {
+            wasm_instruction: 1,
+            ..Default::default()
+        };
+        assert!(costs.has_custom_wasm_rules());
+    }
+
+    #[test]
+    fn test_gas_profile() {
+        let mut meter = TxGasMeter::new_from_sub_limit(TX_GAS_LIMIT.into());
+        meter.consume(10).expect("cannot add the gas");
+        assert!(meter.gas_profile().is_none());
+
+        meter.enable_profiling();
+        meter.consume(5).expect("cannot add the gas");
+        meter.enter_section("tx_read");
+        meter.consume(7).expect("cannot add the gas");
+        meter.enter_section(WASM_GAS_SECTION);
+        meter.consume(1).expect("cannot add the gas");
+        meter.enter_section("tx_read");
+        meter.consume(3).expect("cannot add the gas");
+
+        let profile = meter.gas_profile().unwrap();
+        assert_eq!(
+            profile.0.get(SETUP_GAS_SECTION),
+            Some(&GasProfileEntry {
+                calls: 0,
+                gas: 5.into()
+            })
+        );
+        assert_eq!(
+            profile.0.get("tx_read"),
+            Some(&GasProfileEntry {
+                calls: 2,
+                gas: 10.into()
+            })
+        );
+        assert_eq!(u64::from(profile.total_gas()), 16);
+
+        // The VPs triggered by a profiled tx are profiled too
+        let mut vp_meter = VpGasMeter::new_from_tx_meter(&meter);
+        vp_meter.enter_section("vp_read_pre");
+        vp_meter.consume(2).expect("cannot add the gas");
+        assert_eq!(u64::from(vp_meter.gas_profile().unwrap().total_gas()), 2);
+    }
+
     #[test]
     fn test_tx_gas_limit() {
         let mut meter = TxGasMeter::new_from_sub_limit(TX_GAS_LIMIT.into());
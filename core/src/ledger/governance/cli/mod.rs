@@ -4,3 +4,5 @@ pub mod offline;
 pub mod onchain;
 /// CLi governance validation
 mod validation;
+
+pub use validation::is_valid_parameter_change_data;
@@ -5,14 +5,18 @@ use serde::{Deserialize, Serialize};
 
 use super::validation::{
     is_valid_author_balance, is_valid_content, is_valid_default_proposal_data,
-    is_valid_end_epoch, is_valid_grace_epoch, is_valid_pgf_funding_data,
-    is_valid_pgf_stewards_data, is_valid_proposal_period, is_valid_start_epoch,
+    is_valid_end_epoch, is_valid_grace_epoch, is_valid_parameter_change_data,
+    is_valid_pgf_funding_data, is_valid_pgf_stewards_data,
+    is_valid_proposal_period, is_valid_start_epoch, is_valid_upgrade_data,
     ProposalValidation,
 };
 use crate::ledger::governance::parameters::GovernanceParameters;
+use crate::ledger::governance::storage::proposal::{
+    PGFIbcTarget, PGFRecipient, ParameterChange,
+};
 use crate::ledger::storage_api::token;
 use crate::types::address::Address;
-use crate::types::storage::Epoch;
+use crate::types::storage::{BlockHeight, Epoch};
 
 #[derive(
     Debug, Clone, BorshSerialize, BorshDeserialize, Serialize, Deserialize,
@@ -228,7 +232,7 @@ impl PgfFundingProposal {
             &self.proposal.content,
             governance_parameters.max_proposal_content_size,
         )?;
-        is_valid_pgf_funding_data(&self.data)?;
+        is_valid_pgf_funding_data(&self.data, self.proposal.grace_epoch)?;
 
         Ok(self)
     }
@@ -242,6 +246,158 @@ impl TryFrom<&[u8]> for PgfFundingProposal {
     }
 }
 
+/// Parameter change proposal
+#[derive(
+    Debug, Clone, BorshSerialize, BorshDeserialize, Serialize, Deserialize,
+)]
+pub struct ParameterChangeProposal {
+    /// The proposal data
+    pub proposal: OnChainProposal,
+    /// The parameters changed by the proposal
+    pub data: Vec<ParameterChange>,
+}
+
+impl ParameterChangeProposal {
+    /// Validate a parameter change proposal
+    pub fn validate(
+        self,
+        governance_parameters: &GovernanceParameters,
+        current_epoch: Epoch,
+        balance: token::Amount,
+        force: bool,
+    ) -> Result<Self, ProposalValidation> {
+        if force {
+            return Ok(self);
+        }
+        is_valid_start_epoch(
+            self.proposal.voting_start_epoch,
+            current_epoch,
+            governance_parameters.min_proposal_voting_period,
+        )?;
+        is_valid_end_epoch(
+            self.proposal.voting_start_epoch,
+            self.proposal.voting_end_epoch,
+            current_epoch,
+            governance_parameters.min_proposal_voting_period,
+            governance_parameters.min_proposal_voting_period,
+            governance_parameters.max_proposal_period,
+        )?;
+        is_valid_grace_epoch(
+            self.proposal.grace_epoch,
+            self.proposal.voting_end_epoch,
+            governance_parameters.min_proposal_grace_epochs,
+        )?;
+        is_valid_proposal_period(
+            self.proposal.voting_start_epoch,
+            self.proposal.grace_epoch,
+            governance_parameters.max_proposal_period,
+        )?;
+        is_valid_author_balance(
+            balance,
+            governance_parameters.min_proposal_fund,
+        )?;
+        is_valid_content(
+            &self.proposal.content,
+            governance_parameters.max_proposal_content_size,
+        )?;
+        is_valid_parameter_change_data(&self.data)?;
+
+        Ok(self)
+    }
+}
+
+impl TryFrom<&[u8]> for ParameterChangeProposal {
+    type Error = serde_json::Error;
+
+    fn try_from(value: &[u8]) -> Result<Self, Self::Error> {
+        serde_json::from_slice(value)
+    }
+}
+
+/// Software upgrade proposal
+#[derive(
+    Debug, Clone, BorshSerialize, BorshDeserialize, Serialize, Deserialize,
+)]
+pub struct UpgradeProposal {
+    /// The proposal data
+    pub proposal: OnChainProposal,
+    /// The upgrade scheduled by the proposal
+    pub data: SoftwareUpgrade,
+}
+
+impl UpgradeProposal {
+    /// Validate a software upgrade proposal
+    pub fn validate(
+        self,
+        governance_parameters: &GovernanceParameters,
+        current_epoch: Epoch,
+        current_height: BlockHeight,
+        balance: token::Amount,
+        force: bool,
+    ) -> Result<Self, ProposalValidation> {
+        if force {
+            return Ok(self);
+        }
+        is_valid_start_epoch(
+            self.proposal.voting_start_epoch,
+            current_epoch,
+            governance_parameters.min_proposal_voting_period,
+        )?;
+        is_valid_end_epoch(
+            self.proposal.voting_start_epoch,
+            self.proposal.voting_end_epoch,
+            current_epoch,
+            governance_parameters.min_proposal_voting_period,
+            governance_parameters.min_proposal_voting_period,
+            governance_parameters.max_proposal_period,
+        )?;
+        is_valid_grace_epoch(
+            self.proposal.grace_epoch,
+            self.proposal.voting_end_epoch,
+            governance_parameters.min_proposal_grace_epochs,
+        )?;
+        is_valid_proposal_period(
+            self.proposal.voting_start_epoch,
+            self.proposal.grace_epoch,
+            governance_parameters.max_proposal_period,
+        )?;
+        is_valid_author_balance(
+            balance,
+            governance_parameters.min_proposal_fund,
+        )?;
+        is_valid_content(
+            &self.proposal.content,
+            governance_parameters.max_proposal_content_size,
+        )?;
+        is_valid_upgrade_data(&self.data, current_height)?;
+
+        Ok(self)
+    }
+}
+
+impl TryFrom<&[u8]> for UpgradeProposal {
+    type Error = serde_json::Error;
+
+    fn try_from(value: &[u8]) -> Result<Self, Self::Error> {
+        serde_json::from_slice(value)
+    }
+}
+
+/// A software upgrade
+#[derive(
+    Debug, Clone, BorshSerialize, BorshDeserialize, Serialize, Deserialize,
+)]
+pub struct SoftwareUpgrade {
+    /// The name of the upgrade
+    pub name: String,
+    /// The height from which the chain must be processed by an upgraded
+    /// binary
+    pub height: BlockHeight,
+    /// The hex-encoded hash of the wasm code or state migration applied by
+    /// the upgrade
+    pub migration: Option<String>,
+}
+
 /// Pgf stewards
 #[derive(
     Debug, Clone, BorshSerialize, BorshDeserialize, Serialize, Deserialize,
@@ -277,7 +433,7 @@ impl PgfAction {
 )]
 pub struct PgfFunding {
     /// Pgf continous funding
-    pub continous: Vec<PgfFundingTarget>,
+    pub continous: Vec<PgfContinousTarget>,
     /// pgf retro fundings
     pub retro: Vec<PgfFundingTarget>,
 }
@@ -313,6 +469,58 @@ pub struct PgfFundingTarget {
     pub address: Address,
 }
 
+/// Pgf continous funding target
+#[derive(
+    Debug, Clone, BorshSerialize, BorshDeserialize, Serialize, Deserialize,
+)]
+pub struct PgfContinousTarget {
+    /// Amount paid every epoch
+    pub amount: token::Amount,
+    /// Target address, or the receiver on the counterparty chain when the
+    /// target is paid over IBC
+    pub address: String,
+    /// The IBC port and channel to pay the target over
+    #[serde(default)]
+    pub ibc: Option<PgfIbcChannel>,
+    /// The first epoch in which the target is paid, by default the grace
+    /// epoch of the proposal
+    #[serde(default)]
+    pub start_epoch: Option<Epoch>,
+    /// The first epoch in which the target is no longer paid
+    #[serde(default)]
+    pub end_epoch: Option<Epoch>,
+    /// The maximum total amount paid to the target
+    #[serde(default)]
+    pub cap: Option<token::Amount>,
+}
+
+impl PgfContinousTarget {
+    /// The recipient of the funding, if the target address is valid
+    pub fn recipient(&self) -> Option<PGFRecipient> {
+        match &self.ibc {
+            Some(channel) => Some(PGFRecipient::Ibc(PGFIbcTarget {
+                receiver: self.address.clone(),
+                port_id: channel.port_id.clone(),
+                channel_id: channel.channel_id.clone(),
+            })),
+            None => Address::decode(&self.address)
+                .ok()
+                .map(PGFRecipient::Internal),
+        }
+    }
+}
+
+/// The IBC port and channel of a pgf funding target
+#[derive(
+    Debug, Clone, BorshSerialize, BorshDeserialize, Serialize, Deserialize,
+)]
+pub struct PgfIbcChannel {
+    /// The IBC port
+    pub port_id: String,
+    /// The IBC channel
+    pub channel_id: String,
+}
+
 /// Represent an proposal vote
 #[derive(
     Debug,
@@ -330,6 +538,8 @@ pub enum ProposalVote {
     Nay,
     /// Represent an abstain proposal vote
     Abstain,
+    /// Represent a nay proposal vote that vetoes the proposal
+    NoWithVeto,
 }
 
 impl TryFrom<String> for ProposalVote {
@@ -340,6 +550,7 @@ impl TryFrom<String> for ProposalVote {
             "yay" => Ok(ProposalVote::Yay),
             "nay" => Ok(ProposalVote::Nay),
             "abstain" => Ok(ProposalVote::Abstain),
+            "no-with-veto" => Ok(ProposalVote::NoWithVeto),
             _ => Err("invalid vote".to_string()),
         }
     }
@@ -361,6 +572,11 @@ impl ProposalVote {
         matches!(self, ProposalVote::Abstain)
     }
 
+    /// Check if the vote type is no with veto
+    pub fn is_veto(&self) -> bool {
+        matches!(self, ProposalVote::NoWithVeto)
+    }
+
     /// Check if two votes are equal
     pub fn is_same_side(&self, other: &Self) -> bool {
         std::mem::discriminant(self) == std::mem::discriminant(other)
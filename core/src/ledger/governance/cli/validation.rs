@@ -1,10 +1,16 @@
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, HashSet};
+use std::str::FromStr;
 
 use thiserror::Error;
 
-use super::onchain::{PgfFunding, StewardsUpdate};
+use super::onchain::{PgfFunding, SoftwareUpgrade, StewardsUpdate};
+use crate::ledger::governance::storage::proposal::{
+    PGFContinuous, PGFRecipient, ParameterChange,
+};
 use crate::types::address::Address;
-use crate::types::storage::Epoch;
+use crate::types::dec::Dec;
+use crate::types::hash::Hash;
+use crate::types::storage::{BlockHeight, Epoch};
 use crate::types::token;
 
 /// This enum raprresent a proposal data
@@ -69,6 +75,37 @@ pub enum ProposalValidation {
     /// The pgf funding data is not valid
     #[error("invalid proposal extra data: cannot be empty.")]
     InvalidPgfFundingExtraData,
+    /// The target of a continous pgf funding is not valid
+    #[error("Invalid pgf funding target: {0}")]
+    InvalidPgfFundingTarget(String),
+    /// The schedule of a continous pgf funding is empty
+    #[error(
+        "Invalid pgf funding schedule for {0}: the end epoch must be greater          than the start epoch and the cap must be positive"
+    )]
+    InvalidPgfFundingSchedule(String),
+    /// The parameter change data is empty
+    #[error("Invalid proposal extra data: cannot be empty.")]
+    InvalidParameterChangeExtraData,
+    /// The same parameter is changed more than once
+    #[error(
+        "Invalid parameter change: parameter {0} is changed more than once"
+    )]
+    DuplicateParameterChange(String),
+    /// The new value of a parameter is out of its range
+    #[error("Invalid parameter change: the value of {0} {1}")]
+    InvalidParameterChangeValue(String, String),
+    /// The upgrade name is empty
+    #[error("Invalid upgrade: the name cannot be empty.")]
+    InvalidUpgradeName,
+    /// The upgrade height is not in the future
+    #[error(
+        "Invalid upgrade height: {0} must be greater than the current height \
+         {1}"
+    )]
+    InvalidUpgradeHeight(BlockHeight, BlockHeight),
+    /// The upgrade migration hash is not valid
+    #[error("Invalid upgrade migration hash: {0}")]
+    InvalidUpgradeMigrationHash(String),
 }
 
 pub fn is_valid_author_balance(
@@ -243,10 +280,256 @@ pub fn is_valid_pgf_stewards_data(
 
 pub fn is_valid_pgf_funding_data(
     data: &PgfFunding,
+    grace_epoch: Epoch,
 ) -> Result<(), ProposalValidation> {
-    if !data.continous.is_empty() || !data.retro.is_empty() {
-        Ok(())
-    } else {
-        Err(ProposalValidation::InvalidPgfFundingExtraData)
+    if data.continous.is_empty() && data.retro.is_empty() {
+        return Err(ProposalValidation::InvalidPgfFundingExtraData);
+    }
+    for target in &data.continous {
+        let recipient = target
+            .recipient()
+            .filter(PGFRecipient::is_valid)
+            .ok_or_else(|| {
+                ProposalValidation::InvalidPgfFundingTarget(
+                    target.address.clone(),
+                )
+            })?;
+        // A zero amount removes the funding, regardless of its schedule
+        if target.amount.is_zero() {
+            continue;
+        }
+        let funding = PGFContinuous {
+            recipient,
+            amount: target.amount,
+            start_epoch: target.start_epoch.unwrap_or(grace_epoch),
+            end_epoch: target.end_epoch,
+            cap: target.cap,
+        };
+        if !funding.is_valid() {
+            return Err(ProposalValidation::InvalidPgfFundingSchedule(
+                target.address.clone(),
+            ));
+        }
+    }
+    Ok(())
+}
+
+pub fn is_valid_upgrade_data(
+    data: &SoftwareUpgrade,
+    current_height: BlockHeight,
+) -> Result<(), ProposalValidation> {
+    if data.name.trim().is_empty() {
+        return Err(ProposalValidation::InvalidUpgradeName);
+    }
+    if data.height <= current_height {
+        return Err(ProposalValidation::InvalidUpgradeHeight(
+            data.height,
+            current_height,
+        ));
+    }
+    match &data.migration {
+        Some(hash) if Hash::from_str(hash).is_err() => Err(
+            ProposalValidation::InvalidUpgradeMigrationHash(hash.clone()),
+        ),
+        _ => Ok(()),
+    }
+}
+
+/// Check that a parameter change proposal changes at least one parameter,
+/// each at most once and to a value in its range
+pub fn is_valid_parameter_change_data(
+    data: &[ParameterChange],
+) -> Result<(), ProposalValidation> {
+    if data.is_empty() {
+        return Err(ProposalValidation::InvalidParameterChangeExtraData);
+    }
+
+    let mut names = HashSet::new();
+    for change in data {
+        if !names.insert(change.name()) {
+            return Err(ProposalValidation::DuplicateParameterChange(
+                change.name().to_string(),
+            ));
+        }
+        is_valid_parameter_change_value(change).map_err(|reason| {
+            ProposalValidation::InvalidParameterChangeValue(
+                change.name().to_string(),
+                reason.to_string(),
+            )
+        })?;
+    }
+    Ok(())
+}
+
+/// Check the range of the new value of a parameter, returning the reason why
+/// it's invalid otherwise
+fn is_valid_parameter_change_value(
+    change: &ParameterChange,
+) -> Result<(), &'static str> {
+    let is_rate = |value: &Dec| {
+        if value.is_negative() || *value > Dec::one() {
+            Err("must be between 0 and 1")
+        } else {
+            Ok(())
+        }
+    };
+    let is_positive = |value: u64| {
+        if value == 0 {
+            Err("must be greater than 0")
+        } else {
+            Ok(())
+        }
+    };
+
+    match change {
+        ParameterChange::EpochDuration {
+            min_num_of_blocks,
+            min_duration,
+        } => {
+            is_positive(*min_num_of_blocks)?;
+            is_positive(min_duration.0)
+        }
+        ParameterChange::MaxExpectedTimePerBlock(value) => is_positive(value.0),
+        ParameterChange::VpWhitelist(hashes)
+        | ParameterChange::TxWhitelist(hashes) => {
+            if hashes.iter().all(|hash| Hash::from_str(hash).is_ok()) {
+                Ok(())
+            } else {
+                Err("must only contain code hashes")
+            }
+        }
+        ParameterChange::EpochsPerYear(value)
+        | ParameterChange::MaxProposalCodeSize(value)
+        | ParameterChange::MinProposalVotingPeriod(value)
+        | ParameterChange::MaxProposalPeriod(value)
+        | ParameterChange::MaxProposalContentSize(value) => is_positive(*value),
+        ParameterChange::MaxSignaturesPerTransaction(value) => {
+            is_positive(*value as u64)
+        }
+        ParameterChange::PosGainP(value) | ParameterChange::PosGainD(value) => {
+            if value.is_negative() {
+                Err("must not be negative")
+            } else {
+                Ok(())
+            }
+        }
+        ParameterChange::BlockProposerReward(value)
+        | ParameterChange::BlockVoteReward(value)
+        | ParameterChange::MaxInflationRate(value)
+        | ParameterChange::TargetStakedRatio(value)
+        | ParameterChange::DuplicateVoteMinSlashRate(value)
+        | ParameterChange::LightClientAttackMinSlashRate(value)
+        | ParameterChange::LivenessThreshold(value)
+        | ParameterChange::VetoThreshold(value)
+        | ParameterChange::PgfInflationRate(value)
+        | ParameterChange::StewardsInflationRate(value) => is_rate(value),
+        ParameterChange::ValidatorStakeThreshold(_)
+        | ParameterChange::MinProposalFund(_)
+        | ParameterChange::MinProposalGraceEpochs(_) => Ok(()),
+        // The activation height is checked against the current height when
+        // the proposal is executed
+        ParameterChange::GasCosts {
+            activation_height, ..
+        } => is_positive(activation_height.0),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pgf_funding_data() {
+        let mut data: PgfFunding = serde_json::from_str(
+            r#"{
+                "continous": [
+                    {
+                        "amount": "1000",
+                        "address": "cosmos1receiver",
+                        "ibc": {"port_id": "transfer", "channel_id": "channel-0"},
+                        "end_epoch": 20,
+                        "cap": "5000"
+                    }
+                ],
+                "retro": []
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(is_valid_pgf_funding_data(&data, Epoch(10)), Ok(()));
+        // The funding would start after it ends
+        assert_eq!(
+            is_valid_pgf_funding_data(&data, Epoch(20)),
+            Err(ProposalValidation::InvalidPgfFundingSchedule(
+                "cosmos1receiver".to_string()
+            ))
+        );
+
+        // A target on this chain must be a valid address
+        data.continous[0].ibc = None;
+        assert_eq!(
+            is_valid_pgf_funding_data(&data, Epoch(10)),
+            Err(ProposalValidation::InvalidPgfFundingTarget(
+                "cosmos1receiver".to_string()
+            ))
+        );
+
+        data.continous.clear();
+        assert_eq!(
+            is_valid_pgf_funding_data(&data, Epoch(10)),
+            Err(ProposalValidation::InvalidPgfFundingExtraData)
+        );
+    }
+
+    #[test]
+    fn test_parameter_change_data() {
+        let data: Vec<ParameterChange> = serde_json::from_str(
+            r#"[
+                {"parameter": "epochs_per_year", "value": 365},
+                {"parameter": "veto_threshold", "value": "0.4"},
+                {
+                    "parameter": "epoch_duration",
+                    "value": {"min_num_of_blocks": 10, "min_duration": 60}
+                }
+            ]"#,
+        )
+        .unwrap();
+        assert_eq!(
+            data,
+            vec![
+                ParameterChange::EpochsPerYear(365),
+                ParameterChange::VetoThreshold(Dec::new(4, 1).unwrap()),
+                ParameterChange::EpochDuration {
+                    min_num_of_blocks: 10,
+                    min_duration: crate::types::time::DurationSecs(60),
+                },
+            ]
+        );
+        assert_eq!(is_valid_parameter_change_data(&data), Ok(()));
+
+        assert_eq!(
+            is_valid_parameter_change_data(&[]),
+            Err(ProposalValidation::InvalidParameterChangeExtraData)
+        );
+        assert_eq!(
+            is_valid_parameter_change_data(&[
+                ParameterChange::EpochsPerYear(365),
+                ParameterChange::EpochsPerYear(366),
+            ]),
+            Err(ProposalValidation::DuplicateParameterChange(
+                "epochs_per_year".to_string()
+            ))
+        );
+        assert!(matches!(
+            is_valid_parameter_change_data(&[ParameterChange::VetoThreshold(
+                Dec::new(11, 1).unwrap()
+            )]),
+            Err(ProposalValidation::InvalidParameterChangeValue(_, _))
+        ));
+        assert!(matches!(
+            is_valid_parameter_change_data(&[ParameterChange::VpWhitelist(
+                vec!["not a hash".to_string()]
+            )]),
+            Err(ProposalValidation::InvalidParameterChangeValue(_, _))
+        ));
     }
 }
@@ -2,6 +2,7 @@ use borsh::{BorshDeserialize, BorshSerialize};
 
 use super::storage::keys as goverance_storage;
 use crate::ledger::storage_api::{self, StorageRead, StorageWrite};
+use crate::types::dec::Dec;
 use crate::types::token;
 
 #[derive(
@@ -29,6 +30,9 @@ pub struct GovernanceParameters {
     pub max_proposal_content_size: u64,
     /// Minimum epochs between end and grace epochs
     pub min_proposal_grace_epochs: u64,
+    /// Minimum fraction of the voting power cast on a proposal that has to
+    /// vote no with veto for the proposal to be vetoed
+    pub veto_threshold: Dec,
 }
 
 impl Default for GovernanceParameters {
@@ -40,6 +44,7 @@ impl Default for GovernanceParameters {
             max_proposal_period: 27,
             max_proposal_content_size: 10_000,
             min_proposal_grace_epochs: 6,
+            veto_threshold: Dec::new(334, 3).expect("Cannot fail"),
         }
     }
 }
@@ -57,6 +62,7 @@ impl GovernanceParameters {
             max_proposal_period,
             max_proposal_content_size,
             min_proposal_grace_epochs,
+            veto_threshold,
         } = self;
 
         let min_proposal_fund_key =
@@ -88,6 +94,9 @@ impl GovernanceParameters {
         storage
             .write(&min_proposal_grace_epoch_key, min_proposal_grace_epochs)?;
 
+        let veto_threshold_key = goverance_storage::get_veto_threshold_key();
+        storage.write(&veto_threshold_key, veto_threshold)?;
+
         let counter_key = goverance_storage::get_counter_key();
         storage.write(&counter_key, u64::MIN)
     }
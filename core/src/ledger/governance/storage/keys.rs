@@ -1,5 +1,6 @@
 use namada_macros::StorageKeys;
 
+use crate::ledger::governance::storage::proposal::ProposalKind;
 use crate::ledger::governance::ADDRESS;
 use crate::types::address::Address;
 use crate::types::storage::{DbKeySeg, Key, KeySeg};
@@ -24,11 +25,21 @@ struct Keys {
     max_period: &'static str,
     max_content: &'static str,
     min_grace_epoch: &'static str,
+    veto_threshold: &'static str,
     counter: &'static str,
     pending: &'static str,
     result: &'static str,
+    delegate_vote: &'static str,
+    vote_delegation: &'static str,
+    vote_delegator: &'static str,
+    deposit: &'static str,
+    upgrade: &'static str,
 }
 
+/// The storage key segment of a vote delegation that applies to all the
+/// proposal kinds
+const ALL_PROPOSAL_KINDS: &str = "all";
+
 /// Check if key is inside governance address space
 pub fn is_governance_key(key: &Key) -> bool {
     matches!(&key.segments[0], DbKeySeg::AddressSeg(addr) if addr == &ADDRESS)
@@ -54,6 +65,103 @@ pub fn is_vote_key(key: &Key) -> bool {
     }
 }
 
+/// Check if a key is the key of the vote cast by an address that may be
+/// followed by the addresses delegating their votes to it. If it is, returns
+/// the voter.
+pub fn is_delegate_vote_key(key: &Key) -> Option<&Address> {
+    match &key.segments[..] {
+        [
+            DbKeySeg::AddressSeg(addr),
+            DbKeySeg::StringSeg(prefix),
+            DbKeySeg::StringSeg(id),
+            DbKeySeg::StringSeg(delegate_vote),
+            DbKeySeg::AddressSeg(voter),
+        ] if addr == &ADDRESS
+            && prefix == Keys::VALUES.proposal
+            && delegate_vote == Keys::VALUES.delegate_vote
+            && id.parse::<u64>().is_ok() =>
+        {
+            Some(voter)
+        }
+        _ => None,
+    }
+}
+
+/// Check if a key is a vote delegation key. If it is, returns the delegating
+/// address and the proposal kind the delegation is restricted to, if any.
+pub fn is_vote_delegation_key(
+    key: &Key,
+) -> Option<(&Address, Option<ProposalKind>)> {
+    match &key.segments[..] {
+        [
+            DbKeySeg::AddressSeg(addr),
+            DbKeySeg::StringSeg(prefix),
+            DbKeySeg::AddressSeg(delegator),
+            DbKeySeg::StringSeg(kind),
+        ] if addr == &ADDRESS && prefix == Keys::VALUES.vote_delegation => {
+            parse_proposal_kind(kind).map(|kind| (delegator, kind))
+        }
+        _ => None,
+    }
+}
+
+/// Check if a key is the key indexing a vote delegation by one of its
+/// delegates. If it is, returns the delegate, the delegating address and the
+/// proposal kind the delegation is restricted to, if any.
+pub fn is_vote_delegator_key(
+    key: &Key,
+) -> Option<(&Address, &Address, Option<ProposalKind>)> {
+    match &key.segments[..] {
+        [
+            DbKeySeg::AddressSeg(addr),
+            DbKeySeg::StringSeg(prefix),
+            DbKeySeg::AddressSeg(delegate),
+            DbKeySeg::AddressSeg(delegator),
+            DbKeySeg::StringSeg(kind),
+        ] if addr == &ADDRESS && prefix == Keys::VALUES.vote_delegator => {
+            parse_proposal_kind(kind).map(|kind| (delegate, delegator, kind))
+        }
+        _ => None,
+    }
+}
+
+/// Parse the proposal kind segment of a vote delegation key, `None` for a
+/// delegation that applies to all the proposal kinds
+fn parse_proposal_kind(kind: &str) -> Option<Option<ProposalKind>> {
+    if kind == ALL_PROPOSAL_KINDS {
+        Some(None)
+    } else {
+        kind.parse().ok().map(Some)
+    }
+}
+
+/// Get the storage key segment of the proposal kind of a vote delegation
+fn proposal_kind_segment(kind: Option<ProposalKind>) -> String {
+    kind.as_ref()
+        .map_or(ALL_PROPOSAL_KINDS, ProposalKind::as_str)
+        .to_owned()
+}
+
+/// Check if a key is the key of the deposit made by an address on a
+/// proposal. If it is, returns the proposal id and the depositor.
+pub fn is_deposit_key(key: &Key) -> Option<(u64, &Address)> {
+    match &key.segments[..] {
+        [
+            DbKeySeg::AddressSeg(addr),
+            DbKeySeg::StringSeg(prefix),
+            DbKeySeg::StringSeg(id),
+            DbKeySeg::StringSeg(deposit),
+            DbKeySeg::AddressSeg(depositor),
+        ] if addr == &ADDRESS
+            && prefix == Keys::VALUES.proposal
+            && deposit == Keys::VALUES.deposit =>
+        {
+            id.parse::<u64>().ok().map(|id| (id, depositor))
+        }
+        _ => None,
+    }
+}
+
 /// Check if key is author key
 pub fn is_author_key(key: &Key) -> bool {
     match &key.segments[..] {
@@ -270,6 +378,15 @@ pub fn is_min_grace_epoch_key(key: &Key) -> bool {
                     && min_grace_epoch_param == Keys::VALUES.min_grace_epoch)
 }
 
+/// Check if key is a veto threshold param key
+pub fn is_veto_threshold_key(key: &Key) -> bool {
+    matches!(&key.segments[..], [
+             DbKeySeg::AddressSeg(addr),
+             DbKeySeg::StringSeg(veto_threshold_param),
+         ] if addr == &ADDRESS
+             && veto_threshold_param == Keys::VALUES.veto_threshold)
+}
+
 /// Check if key is parameter key
 pub fn is_parameter_key(key: &Key) -> bool {
     is_min_proposal_fund_key(key)
@@ -278,6 +395,7 @@ pub fn is_parameter_key(key: &Key) -> bool {
         || is_min_proposal_voting_period_key(key)
         || is_max_proposal_period_key(key)
         || is_min_grace_epoch_key(key)
+        || is_veto_threshold_key(key)
 }
 
 /// Check if key is start epoch or end epoch key
@@ -334,6 +452,13 @@ pub fn get_min_proposal_grace_epoch_key() -> Key {
         .expect("Cannot obtain a storage key")
 }
 
+/// Get veto threshold key
+pub fn get_veto_threshold_key() -> Key {
+    Key::from(ADDRESS.to_db_key())
+        .push(&Keys::VALUES.veto_threshold.to_owned())
+        .expect("Cannot obtain a storage key")
+}
+
 /// Get key of proposal ids counter
 pub fn get_counter_key() -> Key {
     Key::from(ADDRESS.to_db_key())
@@ -395,6 +520,22 @@ pub fn get_funds_key(id: u64) -> Key {
         .expect("Cannot obtain a storage key")
 }
 
+/// Get the prefix of the deposits made on a proposal
+pub fn get_deposits_prefix(id: u64) -> Key {
+    proposal_prefix()
+        .push(&id.to_string())
+        .expect("Cannot obtain a storage key")
+        .push(&Keys::VALUES.deposit.to_owned())
+        .expect("Cannot obtain a storage key")
+}
+
+/// Get the key of the deposit made by an address on a proposal
+pub fn get_deposit_key(id: u64, depositor: &Address) -> Key {
+    get_deposits_prefix(id)
+        .push(depositor)
+        .expect("Cannot obtain a storage key")
+}
+
 /// Get proposal grace epoch key
 pub fn get_grace_epoch_key(id: u64) -> Key {
     proposal_prefix()
@@ -451,6 +592,103 @@ pub fn get_vote_proposal_key(
         .expect("Cannot obtain a storage key")
 }
 
+/// Get the key of the vote cast by an address on a proposal, that may be
+/// followed by the addresses delegating their votes to it
+pub fn get_delegate_vote_key(id: u64, voter: &Address) -> Key {
+    get_delegate_votes_prefix(id)
+        .push(voter)
+        .expect("Cannot obtain a storage key")
+}
+
+/// Get the prefix of the votes cast on a proposal that may be followed by the
+/// addresses delegating their votes to the voters
+pub fn get_delegate_votes_prefix(id: u64) -> Key {
+    proposal_prefix()
+        .push(&id.to_string())
+        .expect("Cannot obtain a storage key")
+        .push(&Keys::VALUES.delegate_vote.to_owned())
+        .expect("Cannot obtain a storage key")
+}
+
+/// Get the prefix of all the vote delegations
+pub fn get_vote_delegation_prefix() -> Key {
+    Key::from(ADDRESS.to_db_key())
+        .push(&Keys::VALUES.vote_delegation.to_owned())
+        .expect("Cannot obtain a storage key")
+}
+
+/// Get the storage prefix of the software upgrades scheduled by governance
+pub fn get_upgrades_prefix() -> Key {
+    Key::from(ADDRESS.to_db_key())
+        .push(&Keys::VALUES.upgrade.to_owned())
+        .expect("Cannot obtain a storage key")
+}
+
+/// Get the key of the software upgrade scheduled by a proposal
+pub fn get_upgrade_key(id: u64) -> Key {
+    get_upgrades_prefix()
+        .push(&id.to_string())
+        .expect("Cannot obtain a storage key")
+}
+
+/// Check if key is a software upgrade key. If it is, returns the id of the
+/// proposal that scheduled the upgrade.
+pub fn is_upgrade_key(key: &Key) -> Option<u64> {
+    match &key.segments[..] {
+        [
+            DbKeySeg::AddressSeg(addr),
+            DbKeySeg::StringSeg(prefix),
+            DbKeySeg::StringSeg(id),
+        ] if addr == &ADDRESS && prefix == Keys::VALUES.upgrade => {
+            id.parse::<u64>().ok()
+        }
+        _ => None,
+    }
+}
+
+/// Get the prefix of the vote delegations of an address
+pub fn get_vote_delegations_prefix(delegator: &Address) -> Key {
+    get_vote_delegation_prefix()
+        .push(delegator)
+        .expect("Cannot obtain a storage key")
+}
+
+/// Get the key of the delegate an address delegates its votes to, either
+/// for the given proposal kind only or, if `None`, for all of them
+pub fn get_vote_delegation_key(
+    delegator: &Address,
+    kind: Option<ProposalKind>,
+) -> Key {
+    get_vote_delegations_prefix(delegator)
+        .push(&proposal_kind_segment(kind))
+        .expect("Cannot obtain a storage key")
+}
+
+/// Get the prefix of the vote delegations in which an address is one of the
+/// delegates
+pub fn get_vote_delegators_prefix(delegate: &Address) -> Key {
+    Key::from(ADDRESS.to_db_key())
+        .push(&Keys::VALUES.vote_delegator.to_owned())
+        .expect("Cannot obtain a storage key")
+        .push(delegate)
+        .expect("Cannot obtain a storage key")
+}
+
+/// Get the key indexing the vote delegation of an address, either for the
+/// given proposal kind only or, if `None`, for all of them, by one of its
+/// delegates
+pub fn get_vote_delegator_key(
+    delegate: &Address,
+    delegator: &Address,
+    kind: Option<ProposalKind>,
+) -> Key {
+    get_vote_delegators_prefix(delegate)
+        .push(delegator)
+        .expect("Cannot obtain a storage key")
+        .push(&proposal_kind_segment(kind))
+        .expect("Cannot obtain a storage key")
+}
+
 /// Get the proposal execution key
 pub fn get_proposal_execution_key(id: u64) -> Key {
     Key::from(ADDRESS.to_db_key())
@@ -1,24 +1,32 @@
 use std::collections::{BTreeMap, HashSet};
 use std::fmt::Display;
+use std::str::FromStr;
 
 use borsh::{BorshDeserialize, BorshSerialize};
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
+use crate::ibc::core::ics24_host::identifier::{ChannelId, PortId};
+use crate::ledger::gas::GasCostTable;
 use crate::ledger::governance::cli::onchain::{
-    PgfAction, PgfContinous, PgfRetro, PgfSteward, StewardsUpdate,
+    PgfAction, PgfContinous, PgfRetro, PgfSteward, SoftwareUpgrade,
+    StewardsUpdate,
 };
 use crate::ledger::governance::utils::{ProposalStatus, TallyType};
 use crate::ledger::storage_api::token::Amount;
 use crate::types::address::Address;
+use crate::types::dec::Dec;
 use crate::types::hash::Hash;
-use crate::types::storage::Epoch;
+use crate::types::storage::{BlockHeight, Epoch, KEY_SEGMENT_SEPARATOR};
+use crate::types::time::DurationSecs;
 
 #[allow(missing_docs)]
 #[derive(Debug, Error)]
 pub enum ProposalTypeError {
     #[error("Invalid proposal type.")]
     InvalidProposalType,
+    #[error("Invalid upgrade migration hash: {0}")]
+    InvalidMigrationHash(String),
 }
 
 /// Storage struture for pgf fundings
@@ -35,15 +43,69 @@ pub enum ProposalTypeError {
 )]
 pub struct StoragePgfFunding {
     /// The data about the pgf funding
-    pub detail: PGFTarget,
+    pub detail: PGFContinuous,
     /// The id of the proposal that added this funding
     pub id: u64,
+    /// The total amount paid so far
+    pub paid: Amount,
 }
 
 impl StoragePgfFunding {
     /// Init a new pgf funding struct
-    pub fn new(detail: PGFTarget, id: u64) -> Self {
-        Self { detail, id }
+    pub fn new(detail: PGFContinuous, id: u64) -> Self {
+        Self {
+            detail,
+            id,
+            paid: Amount::zero(),
+        }
+    }
+
+    /// Check if the funding is over in the given epoch, either because its
+    /// end epoch is reached or because its cap is paid in full
+    pub fn is_expired(&self, epoch: Epoch) -> bool {
+        matches!(self.detail.end_epoch, Some(end) if epoch >= end)
+            || matches!(self.detail.cap, Some(cap) if self.paid >= cap)
+    }
+
+    /// The amount due to the recipient in the given epoch, capped by the
+    /// rest of the total amount of the funding
+    pub fn due_at(&self, epoch: Epoch) -> Amount {
+        if epoch < self.detail.start_epoch || self.is_expired(epoch) {
+            return Amount::zero();
+        }
+        match self.detail.cap {
+            Some(cap) => std::cmp::min(
+                self.detail.amount,
+                cap.checked_sub(self.paid).unwrap_or_default(),
+            ),
+            None => self.detail.amount,
+        }
+    }
+}
+
+/// Storage structure for pgf fundings, as written before the fundings were
+/// paid over a schedule
+#[derive(Debug, Clone, BorshSerialize, BorshDeserialize)]
+pub struct LegacyStoragePgfFunding {
+    /// The data about the pgf funding
+    pub detail: PGFTarget,
+    /// The id of the proposal that added this funding
+    pub id: u64,
+}
+
+impl From<LegacyStoragePgfFunding> for StoragePgfFunding {
+    /// A legacy funding pays its target every epoch, without end or cap
+    fn from(legacy: LegacyStoragePgfFunding) -> Self {
+        Self::new(
+            PGFContinuous {
+                recipient: PGFRecipient::Internal(legacy.detail.target),
+                amount: legacy.detail.amount,
+                start_epoch: Epoch(0),
+                end_epoch: None,
+                cap: None,
+            },
+            legacy.id,
+        )
     }
 }
 
@@ -88,6 +150,126 @@ pub struct PGFTarget {
     pub amount: Amount,
 }
 
+/// A receiver on a counterparty chain, paid over IBC
+#[derive(
+    Debug,
+    Clone,
+    PartialEq,
+    BorshSerialize,
+    BorshDeserialize,
+    Serialize,
+    Deserialize,
+    Ord,
+    Eq,
+    PartialOrd,
+)]
+pub struct PGFIbcTarget {
+    /// The receiver address on the counterparty chain
+    pub receiver: String,
+    /// The port of the IBC transfer
+    pub port_id: String,
+    /// The channel of the IBC transfer
+    pub channel_id: String,
+}
+
+/// The recipient of a continuous PGF funding
+#[derive(
+    Debug,
+    Clone,
+    PartialEq,
+    BorshSerialize,
+    BorshDeserialize,
+    Serialize,
+    Deserialize,
+    Ord,
+    Eq,
+    PartialOrd,
+)]
+pub enum PGFRecipient {
+    /// An address on this chain
+    Internal(Address),
+    /// A receiver on a counterparty chain
+    Ibc(PGFIbcTarget),
+}
+
+impl PGFRecipient {
+    /// The key of the funding of this recipient in storage
+    pub fn funding_key(&self) -> String {
+        match self {
+            PGFRecipient::Internal(address) => address.encode(),
+            PGFRecipient::Ibc(target) => format!(
+                "ibc:{}:{}:{}",
+                target.port_id, target.channel_id, target.receiver
+            ),
+        }
+    }
+
+    /// Check that an IBC receiver can be paid over a valid port and channel
+    /// and fits in a storage key segment
+    pub fn is_valid(&self) -> bool {
+        match self {
+            PGFRecipient::Internal(_) => true,
+            PGFRecipient::Ibc(target) => {
+                !target.receiver.is_empty()
+                    && !target.receiver.contains(KEY_SEGMENT_SEPARATOR)
+                    && PortId::from_str(&target.port_id).is_ok()
+                    && ChannelId::from_str(&target.channel_id).is_ok()
+            }
+        }
+    }
+}
+
+impl Display for PGFRecipient {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PGFRecipient::Internal(address) => write!(f, "{}", address),
+            PGFRecipient::Ibc(target) => write!(
+                f,
+                "{} over {}/{}",
+                target.receiver, target.port_id, target.channel_id
+            ),
+        }
+    }
+}
+
+/// A continuous PGF funding, paid every epoch from its start epoch until
+/// its end epoch or its cap is reached
+#[derive(
+    Debug,
+    Clone,
+    PartialEq,
+    BorshSerialize,
+    BorshDeserialize,
+    Serialize,
+    Deserialize,
+    Ord,
+    Eq,
+    PartialOrd,
+)]
+pub struct PGFContinuous {
+    /// The recipient of the payments
+    pub recipient: PGFRecipient,
+    /// The amount paid every epoch
+    pub amount: Amount,
+    /// The first epoch in which the recipient is paid
+    pub start_epoch: Epoch,
+    /// The first epoch in which the recipient is no longer paid, if any
+    pub end_epoch: Option<Epoch>,
+    /// The maximum total amount paid to the recipient, if any
+    pub cap: Option<Amount>,
+}
+
+impl PGFContinuous {
+    /// Check that the funding pays a valid recipient a non-zero amount over
+    /// a non-empty schedule
+    pub fn is_valid(&self) -> bool {
+        self.recipient.is_valid()
+            && !self.amount.is_zero()
+            && !matches!(self.end_epoch, Some(end) if end <= self.start_epoch)
+            && !matches!(self.cap, Some(cap) if cap.is_zero())
+    }
+}
+
 /// The actions that a PGF Steward can propose to execute
 #[derive(
     Debug,
@@ -100,11 +282,239 @@ pub struct PGFTarget {
 )]
 pub enum PGFAction {
     /// A continuous payment
-    Continuous(AddRemove<PGFTarget>),
+    Continuous(AddRemove<PGFContinuous>),
     /// A retro payment
     Retro(PGFTarget),
 }
 
+/// An update of a protocol, PoS, governance or PGF parameter that a
+/// parameter change proposal executes once accepted. In JSON, a change is
+/// written as `{"parameter": "epochs_per_year", "value": 365}`.
+#[derive(
+    Debug,
+    Clone,
+    PartialEq,
+    BorshSerialize,
+    BorshDeserialize,
+    Serialize,
+    Deserialize,
+)]
+#[serde(tag = "parameter", content = "value", rename_all = "snake_case")]
+pub enum ParameterChange {
+    /// The minimum duration of an epoch
+    EpochDuration {
+        /// Minimum number of blocks in an epoch
+        min_num_of_blocks: u64,
+        /// Minimum duration of an epoch
+        min_duration: DurationSecs,
+    },
+    /// The max expected time per block
+    MaxExpectedTimePerBlock(DurationSecs),
+    /// The hashes of the whitelisted vps
+    VpWhitelist(Vec<String>),
+    /// The hashes of the whitelisted txs
+    TxWhitelist(Vec<String>),
+    /// The expected number of epochs per year
+    EpochsPerYear(u64),
+    /// The maximum number of signatures allowed per transaction
+    MaxSignaturesPerTransaction(u8),
+    /// The PoS inflation controller proportional gain
+    PosGainP(Dec),
+    /// The PoS inflation controller derivative gain
+    PosGainD(Dec),
+    /// The PoS block proposer reward
+    BlockProposerReward(Dec),
+    /// The PoS block vote reward
+    BlockVoteReward(Dec),
+    /// The maximum PoS inflation rate
+    MaxInflationRate(Dec),
+    /// The PoS target ratio of staked tokens
+    TargetStakedRatio(Dec),
+    /// The minimum PoS slash rate for a duplicate vote
+    DuplicateVoteMinSlashRate(Dec),
+    /// The minimum PoS slash rate for a light client attack
+    LightClientAttackMinSlashRate(Dec),
+    /// The minimum stake of a validator to be in the consensus or below
+    /// capacity validator sets
+    ValidatorStakeThreshold(Amount),
+    /// The minimum fraction of the liveness window that a validator has to
+    /// sign not to be jailed
+    LivenessThreshold(Dec),
+    /// The minimum amount of funds locked by a proposal
+    MinProposalFund(Amount),
+    /// The maximum size of a proposal code
+    MaxProposalCodeSize(u64),
+    /// The minimum proposal voting period in epochs
+    MinProposalVotingPeriod(u64),
+    /// The maximum proposal period in epochs
+    MaxProposalPeriod(u64),
+    /// The maximum size of a proposal content
+    MaxProposalContentSize(u64),
+    /// The minimum number of epochs between the end and grace epochs
+    MinProposalGraceEpochs(u64),
+    /// The governance veto threshold
+    VetoThreshold(Dec),
+    /// The PGF funding inflation rate
+    PgfInflationRate(Dec),
+    /// The PGF stewards inflation rate
+    StewardsInflationRate(Dec),
+    /// The gas costs in force from the given height
+    GasCosts {
+        /// The height of the first block charged with the new costs
+        activation_height: BlockHeight,
+        /// The new gas costs
+        table: GasCostTable,
+    },
+}
+
+impl ParameterChange {
+    /// Get the name of the changed parameter, as written in JSON
+    pub fn name(&self) -> &'static str {
+        match self {
+            ParameterChange::EpochDuration { .. } => "epoch_duration",
+            ParameterChange::MaxExpectedTimePerBlock(_) => {
+                "max_expected_time_per_block"
+            }
+            ParameterChange::VpWhitelist(_) => "vp_whitelist",
+            ParameterChange::TxWhitelist(_) => "tx_whitelist",
+            ParameterChange::EpochsPerYear(_) => "epochs_per_year",
+            ParameterChange::MaxSignaturesPerTransaction(_) => {
+                "max_signatures_per_transaction"
+            }
+            ParameterChange::PosGainP(_) => "pos_gain_p",
+            ParameterChange::PosGainD(_) => "pos_gain_d",
+            ParameterChange::BlockProposerReward(_) => "block_proposer_reward",
+            ParameterChange::BlockVoteReward(_) => "block_vote_reward",
+            ParameterChange::MaxInflationRate(_) => "max_inflation_rate",
+            ParameterChange::TargetStakedRatio(_) => "target_staked_ratio",
+            ParameterChange::DuplicateVoteMinSlashRate(_) => {
+                "duplicate_vote_min_slash_rate"
+            }
+            ParameterChange::LightClientAttackMinSlashRate(_) => {
+                "light_client_attack_min_slash_rate"
+            }
+            ParameterChange::ValidatorStakeThreshold(_) => {
+                "validator_stake_threshold"
+            }
+            ParameterChange::LivenessThreshold(_) => "liveness_threshold",
+            ParameterChange::MinProposalFund(_) => "min_proposal_fund",
+            ParameterChange::MaxProposalCodeSize(_) => "max_proposal_code_size",
+            ParameterChange::MinProposalVotingPeriod(_) => {
+                "min_proposal_voting_period"
+            }
+            ParameterChange::MaxProposalPeriod(_) => "max_proposal_period",
+            ParameterChange::MaxProposalContentSize(_) => {
+                "max_proposal_content_size"
+            }
+            ParameterChange::MinProposalGraceEpochs(_) => {
+                "min_proposal_grace_epochs"
+            }
+            ParameterChange::VetoThreshold(_) => "veto_threshold",
+            ParameterChange::PgfInflationRate(_) => "pgf_inflation_rate",
+            ParameterChange::StewardsInflationRate(_) => {
+                "stewards_inflation_rate"
+            }
+            ParameterChange::GasCosts { .. } => "gas_costs",
+        }
+    }
+}
+
+impl Display for ParameterChange {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ParameterChange::EpochDuration {
+                min_num_of_blocks,
+                min_duration,
+            } => write!(
+                f,
+                "{}: {} blocks, {} seconds",
+                self.name(),
+                min_num_of_blocks,
+                min_duration.0
+            ),
+            ParameterChange::MaxExpectedTimePerBlock(value) => {
+                write!(f, "{}: {} seconds", self.name(), value.0)
+            }
+            ParameterChange::VpWhitelist(hashes)
+            | ParameterChange::TxWhitelist(hashes) => {
+                write!(f, "{}: [{}]", self.name(), hashes.join(", "))
+            }
+            ParameterChange::EpochsPerYear(value)
+            | ParameterChange::MaxProposalCodeSize(value)
+            | ParameterChange::MinProposalVotingPeriod(value)
+            | ParameterChange::MaxProposalPeriod(value)
+            | ParameterChange::MaxProposalContentSize(value)
+            | ParameterChange::MinProposalGraceEpochs(value) => {
+                write!(f, "{}: {}", self.name(), value)
+            }
+            ParameterChange::MaxSignaturesPerTransaction(value) => {
+                write!(f, "{}: {}", self.name(), value)
+            }
+            ParameterChange::PosGainP(value)
+            | ParameterChange::PosGainD(value)
+            | ParameterChange::BlockProposerReward(value)
+            | ParameterChange::BlockVoteReward(value)
+            | ParameterChange::MaxInflationRate(value)
+            | ParameterChange::TargetStakedRatio(value)
+            | ParameterChange::DuplicateVoteMinSlashRate(value)
+            | ParameterChange::LightClientAttackMinSlashRate(value)
+            | ParameterChange::LivenessThreshold(value)
+            | ParameterChange::VetoThreshold(value)
+            | ParameterChange::PgfInflationRate(value)
+            | ParameterChange::StewardsInflationRate(value) => {
+                write!(f, "{}: {}", self.name(), value)
+            }
+            ParameterChange::ValidatorStakeThreshold(value)
+            | ParameterChange::MinProposalFund(value) => {
+                write!(f, "{}: {}", self.name(), value.to_string_native())
+            }
+            ParameterChange::GasCosts {
+                activation_height,
+                table,
+            } => write!(
+                f,
+                "{}: {:?} from height {}",
+                self.name(),
+                table,
+                activation_height
+            ),
+        }
+    }
+}
+
+/// A software upgrade scheduled by a governance proposal. From its height, the
+/// chain halts unless the node runs a binary implementing the upgrade.
+#[derive(
+    Debug,
+    Clone,
+    PartialEq,
+    Eq,
+    BorshSerialize,
+    BorshDeserialize,
+    Serialize,
+    Deserialize,
+)]
+pub struct UpgradePlan {
+    /// The name of the upgrade, declared by the binaries implementing it
+    pub name: String,
+    /// The height of the first block that must be processed by an upgraded
+    /// binary
+    pub height: BlockHeight,
+    /// The hash of the wasm code or state migration applied by the upgrade,
+    /// if any
+    pub migration: Option<Hash>,
+}
+
+impl Display for UpgradePlan {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} at height {}", self.name, self.height)?;
+        if let Some(migration) = &self.migration {
+            write!(f, " with migration {}", migration)?;
+        }
+        Ok(())
+    }
+}
+
 /// The type of a Proposal
 #[derive(
     Debug,
@@ -122,6 +532,10 @@ pub enum ProposalType {
     PGFSteward(HashSet<AddRemove<Address>>),
     /// PGF funding proposal
     PGFPayment(Vec<PGFAction>),
+    /// Parameter change proposal
+    ParameterChange(Vec<ParameterChange>),
+    /// Software upgrade proposal
+    Upgrade(UpgradePlan),
 }
 
 impl ProposalType {
@@ -129,6 +543,80 @@ impl ProposalType {
     pub fn is_default(&self) -> bool {
         matches!(self, ProposalType::Default(_))
     }
+
+    /// Get the kind of the proposal type
+    pub fn kind(&self) -> ProposalKind {
+        match self {
+            ProposalType::Default(_) => ProposalKind::Default,
+            ProposalType::PGFSteward(_) => ProposalKind::PGFSteward,
+            ProposalType::PGFPayment(_) => ProposalKind::PGFPayment,
+            ProposalType::ParameterChange(_) => ProposalKind::ParameterChange,
+            ProposalType::Upgrade(_) => ProposalKind::Upgrade,
+        }
+    }
+}
+
+/// The kind of a proposal type, regardless of the data it carries
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    BorshSerialize,
+    BorshDeserialize,
+    Serialize,
+    Deserialize,
+)]
+pub enum ProposalKind {
+    /// Default governance proposal
+    Default,
+    /// PGF stewards proposal
+    PGFSteward,
+    /// PGF funding proposal
+    PGFPayment,
+    /// Parameter change proposal
+    ParameterChange,
+    /// Software upgrade proposal
+    Upgrade,
+}
+
+impl ProposalKind {
+    /// Get the string used for the proposal kind in storage keys and in the
+    /// client
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ProposalKind::Default => "default",
+            ProposalKind::PGFSteward => "pgf-steward",
+            ProposalKind::PGFPayment => "pgf-payment",
+            ProposalKind::ParameterChange => "parameter-change",
+            ProposalKind::Upgrade => "upgrade",
+        }
+    }
+}
+
+impl Display for ProposalKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+impl FromStr for ProposalKind {
+    type Err = ProposalTypeError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "default" => Ok(ProposalKind::Default),
+            "pgf-steward" => Ok(ProposalKind::PGFSteward),
+            "pgf-payment" => Ok(ProposalKind::PGFPayment),
+            "parameter-change" => Ok(ProposalKind::ParameterChange),
+            "upgrade" => Ok(ProposalKind::Upgrade),
+            _ => Err(ProposalTypeError::InvalidProposalType),
+        }
+    }
 }
 
 impl Display for ProposalType {
@@ -137,6 +625,16 @@ impl Display for ProposalType {
             ProposalType::Default(_) => write!(f, "Default"),
             ProposalType::PGFSteward(_) => write!(f, "Pgf steward"),
             ProposalType::PGFPayment(_) => write!(f, "Pgf funding"),
+            ProposalType::ParameterChange(changes) => write!(
+                f,
+                "Parameter change ({})",
+                changes
+                    .iter()
+                    .map(ToString::to_string)
+                    .collect::<Vec<String>>()
+                    .join(", ")
+            ),
+            ProposalType::Upgrade(plan) => write!(f, "Upgrade ({})", plan),
         }
     }
 }
@@ -172,18 +670,19 @@ impl TryFrom<PgfContinous> for PGFAction {
     type Error = ProposalTypeError;
 
     fn try_from(value: PgfContinous) -> Result<Self, Self::Error> {
+        let funding = PGFContinuous {
+            recipient: PGFRecipient::Internal(value.target.address),
+            amount: value.target.amount,
+            start_epoch: Epoch::default(),
+            end_epoch: None,
+            cap: None,
+        };
         match value.action {
             PgfAction::Add => {
-                Ok(PGFAction::Continuous(AddRemove::Add(PGFTarget {
-                    target: value.target.address,
-                    amount: value.target.amount,
-                })))
+                Ok(PGFAction::Continuous(AddRemove::Add(funding)))
             }
             PgfAction::Remove => {
-                Ok(PGFAction::Continuous(AddRemove::Remove(PGFTarget {
-                    target: value.target.address,
-                    amount: value.target.amount,
-                })))
+                Ok(PGFAction::Continuous(AddRemove::Remove(funding)))
             }
         }
     }
@@ -200,6 +699,26 @@ impl TryFrom<PgfRetro> for PGFAction {
     }
 }
 
+impl TryFrom<SoftwareUpgrade> for UpgradePlan {
+    type Error = ProposalTypeError;
+
+    fn try_from(value: SoftwareUpgrade) -> Result<Self, Self::Error> {
+        let migration = value
+            .migration
+            .map(|hash| {
+                Hash::from_str(&hash).map_err(|_| {
+                    ProposalTypeError::InvalidMigrationHash(hash.clone())
+                })
+            })
+            .transpose()?;
+        Ok(UpgradePlan {
+            name: value.name,
+            height: value.height,
+            migration,
+        })
+    }
+}
+
 #[derive(Debug, Clone, BorshSerialize, BorshDeserialize)]
 /// Proposal rappresentation when fetched from the storage
 pub struct StorageProposal {
@@ -311,3 +830,70 @@ impl Display for StorageProposal {
         )
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::address::testing::established_address_1;
+
+    #[test]
+    fn test_pgf_funding_schedule() {
+        let mut funding = StoragePgfFunding::new(
+            PGFContinuous {
+                recipient: PGFRecipient::Internal(established_address_1()),
+                amount: Amount::from_u64(40),
+                start_epoch: Epoch(2),
+                end_epoch: Some(Epoch(10)),
+                cap: Some(Amount::from_u64(100)),
+            },
+            0,
+        );
+        assert!(funding.detail.is_valid());
+        assert_eq!(funding.due_at(Epoch(1)), Amount::zero());
+        assert_eq!(funding.due_at(Epoch(2)), Amount::from_u64(40));
+
+        // The last payment is capped
+        funding.paid = Amount::from_u64(80);
+        assert_eq!(funding.due_at(Epoch(4)), Amount::from_u64(20));
+        funding.paid = Amount::from_u64(100);
+        assert!(funding.is_expired(Epoch(5)));
+        assert_eq!(funding.due_at(Epoch(5)), Amount::zero());
+
+        // The funding expires at the end epoch
+        funding.paid = Amount::zero();
+        assert!(!funding.is_expired(Epoch(9)));
+        assert!(funding.is_expired(Epoch(10)));
+
+        let empty_schedule = PGFContinuous {
+            end_epoch: Some(Epoch(2)),
+            ..funding.detail.clone()
+        };
+        assert!(!empty_schedule.is_valid());
+    }
+
+    #[test]
+    fn test_pgf_ibc_recipient() {
+        let target = PGFIbcTarget {
+            receiver: "cosmos1receiver".to_string(),
+            port_id: "transfer".to_string(),
+            channel_id: "channel-0".to_string(),
+        };
+        let recipient = PGFRecipient::Ibc(target.clone());
+        assert!(recipient.is_valid());
+        assert_eq!(
+            recipient.funding_key(),
+            "ibc:transfer:channel-0:cosmos1receiver"
+        );
+
+        let recipient = PGFRecipient::Ibc(PGFIbcTarget {
+            receiver: "cosmos1/receiver".to_string(),
+            ..target.clone()
+        });
+        assert!(!recipient.is_valid());
+        let recipient = PGFRecipient::Ibc(PGFIbcTarget {
+            channel_id: "channel".to_string(),
+            ..target
+        });
+        assert!(!recipient.is_valid());
+    }
+}
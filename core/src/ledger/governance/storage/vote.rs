@@ -1,3 +1,4 @@
+use std::collections::BTreeMap;
 use std::fmt::Display;
 
 use borsh::{BorshDeserialize, BorshSerialize};
@@ -5,6 +6,8 @@ use serde::{Deserialize, Serialize};
 
 use super::super::cli::onchain::ProposalVote;
 use super::proposal::ProposalType;
+use crate::types::address::Address;
+use crate::types::storage::Epoch;
 
 /// The type of a governance vote with the optional associated Memo
 #[derive(
@@ -24,6 +27,10 @@ pub enum VoteType {
     PGFSteward,
     /// A vote for a PGF payment proposal
     PGFPayment,
+    /// A vote for a parameter change proposal
+    ParameterChange,
+    /// A vote for a software upgrade proposal
+    Upgrade,
 }
 
 #[derive(
@@ -44,6 +51,9 @@ pub enum StorageProposalVote {
     Nay,
     /// Abstain
     Abstain,
+    /// No, and reject the proposal while slashing its deposit if the veto
+    /// threshold is crossed
+    NoWithVeto,
 }
 
 impl StorageProposalVote {
@@ -62,6 +72,11 @@ impl StorageProposalVote {
         matches!(self, StorageProposalVote::Abstain)
     }
 
+    /// Check if a vote is no with veto
+    pub fn is_veto(&self) -> bool {
+        matches!(self, StorageProposalVote::NoWithVeto)
+    }
+
     /// Check if two votes are equal
     pub fn is_same_side(&self, other: &Self) -> bool {
         std::mem::discriminant(self) == std::mem::discriminant(other)
@@ -82,6 +97,7 @@ impl StorageProposalVote {
             StorageProposalVote::Yay(vote_type) => proposal_type.eq(vote_type),
             StorageProposalVote::Nay => true,
             StorageProposalVote::Abstain => true,
+            StorageProposalVote::NoWithVeto => true,
         }
     }
 
@@ -100,6 +116,12 @@ impl StorageProposalVote {
             (ProposalVote::Yay, ProposalType::PGFPayment(_)) => {
                 Some(StorageProposalVote::Yay(VoteType::PGFPayment))
             }
+            (ProposalVote::Yay, ProposalType::ParameterChange(_)) => {
+                Some(StorageProposalVote::Yay(VoteType::ParameterChange))
+            }
+            (ProposalVote::Yay, ProposalType::Upgrade(_)) => {
+                Some(StorageProposalVote::Yay(VoteType::Upgrade))
+            }
             (ProposalVote::Nay, ProposalType::Default(_)) => {
                 Some(StorageProposalVote::Nay)
             }
@@ -109,7 +131,16 @@ impl StorageProposalVote {
             (ProposalVote::Nay, ProposalType::PGFPayment(_)) => {
                 Some(StorageProposalVote::Nay)
             }
-            _ => None,
+            (ProposalVote::Nay, ProposalType::ParameterChange(_)) => {
+                Some(StorageProposalVote::Nay)
+            }
+            (ProposalVote::Nay, ProposalType::Upgrade(_)) => {
+                Some(StorageProposalVote::Nay)
+            }
+            (ProposalVote::Abstain, _) => Some(StorageProposalVote::Abstain),
+            (ProposalVote::NoWithVeto, _) => {
+                Some(StorageProposalVote::NoWithVeto)
+            }
         }
     }
 }
@@ -120,11 +151,14 @@ impl Display for StorageProposalVote {
             StorageProposalVote::Yay(vote_type) => match vote_type {
                 VoteType::Default
                 | VoteType::PGFSteward
-                | VoteType::PGFPayment => write!(f, "yay"),
+                | VoteType::PGFPayment
+                | VoteType::ParameterChange
+                | VoteType::Upgrade => write!(f, "yay"),
             },
 
             StorageProposalVote::Nay => write!(f, "nay"),
             StorageProposalVote::Abstain => write!(f, "abstain"),
+            StorageProposalVote::NoWithVeto => write!(f, "no-with-veto"),
         }
     }
 }
@@ -141,6 +175,73 @@ impl PartialEq<VoteType> for ProposalType {
             Self::PGFPayment(_) => {
                 matches!(other, VoteType::PGFPayment)
             }
+            Self::ParameterChange(_) => {
+                matches!(other, VoteType::ParameterChange)
+            }
+            Self::Upgrade(_) => {
+                matches!(other, VoteType::Upgrade)
+            }
+        }
+    }
+}
+
+/// The delegates that an address appointed to vote on its behalf, by the
+/// epoch from which they're in force, `None` for a revoked delegation. A new
+/// delegate is only in force from the next epoch, so that the delegation
+/// followed on a proposal is the one in force when its voting period started
+/// and stays the same while the votes are cast.
+#[derive(
+    Debug,
+    Clone,
+    Default,
+    PartialEq,
+    BorshSerialize,
+    BorshDeserialize,
+    Serialize,
+    Deserialize,
+)]
+pub struct VoteDelegation {
+    /// The delegates by the epoch from which they're in force
+    pub delegates: BTreeMap<Epoch, Option<Address>>,
+}
+
+impl VoteDelegation {
+    /// Get the delegate in force at the given epoch, if any
+    pub fn delegate_at(&self, epoch: Epoch) -> Option<&Address> {
+        self.delegates
+            .range(..=epoch)
+            .next_back()
+            .and_then(|(_, delegate)| delegate.as_ref())
+    }
+
+    /// Get all the delegates that are, or may still be, followed
+    pub fn delegates(&self) -> impl Iterator<Item = &Address> {
+        self.delegates.values().flatten()
+    }
+
+    /// Appoint a new delegate, or revoke the delegation, from the epoch after
+    /// the current one. The delegates replaced before `oldest_epoch`, the
+    /// oldest voting start epoch of the proposals that are still to be
+    /// tallied, are forgotten. Returns `None` if no delegate is left.
+    pub fn update(
+        mut self,
+        delegate: Option<Address>,
+        current_epoch: Epoch,
+        oldest_epoch: Epoch,
+    ) -> Option<Self> {
+        self.delegates.insert(current_epoch.next(), delegate);
+        let in_force = self
+            .delegates
+            .range(..=oldest_epoch)
+            .next_back()
+            .map(|(epoch, _)| *epoch);
+        if let Some(in_force) = in_force {
+            self.delegates = self.delegates.split_off(&in_force);
+        }
+        if self.delegates().next().is_some() {
+            Some(self)
+        } else {
+            None
         }
     }
 }
@@ -7,6 +7,7 @@ use super::cli::offline::OfflineVote;
 use super::storage::proposal::ProposalType;
 use super::storage::vote::StorageProposalVote;
 use crate::types::address::Address;
+use crate::types::dec::Dec;
 use crate::types::storage::Epoch;
 use crate::types::token;
 
@@ -51,7 +52,9 @@ impl Vote {
     }
 }
 
-/// Represent a tally type
+/// Represent a tally type. The no with veto votes count as nay votes in the
+/// tally, while the proposals vetoed are rejected regardless of the tally
+/// type.
 pub enum TallyType {
     /// Represent a tally type for proposal requiring 2/3 of the total voting
     /// power to be yay
@@ -76,6 +79,8 @@ impl TallyType {
             (ProposalType::PGFPayment(_), false) => {
                 TallyType::OneHalfOverOneThird
             }
+            (ProposalType::ParameterChange(_), _) => TallyType::TwoThirds,
+            (ProposalType::Upgrade(_), _) => TallyType::TwoThirds,
         }
     }
 }
@@ -87,6 +92,8 @@ pub enum TallyResult {
     Passed,
     /// Proposal was rejected
     Rejected,
+    /// Proposal was rejected by the no with veto votes
+    Vetoed,
 }
 
 impl Display for TallyResult {
@@ -94,27 +101,43 @@ impl Display for TallyResult {
         match self {
             TallyResult::Passed => write!(f, "passed"),
             TallyResult::Rejected => write!(f, "rejected"),
+            TallyResult::Vetoed => write!(f, "vetoed"),
         }
     }
 }
 
 impl TallyResult {
-    /// Create a new tally result
+    /// Create a new tally result. The proposal is vetoed if at least 1/3 of
+    /// the total voting power voted and the no with veto votes exceed the
+    /// given fraction of the voting power cast.
     pub fn new(
         tally_type: &TallyType,
         yay_voting_power: VotePower,
         nay_voting_power: VotePower,
         abstain_voting_power: VotePower,
+        veto_voting_power: VotePower,
         total_voting_power: VotePower,
+        veto_threshold: Dec,
     ) -> Self {
+        let voted_power = yay_voting_power
+            + nay_voting_power
+            + abstain_voting_power
+            + veto_voting_power;
+        if voted_power >= total_voting_power / 3
+            && !veto_voting_power.is_zero()
+            && veto_voting_power > veto_threshold * voted_power
+        {
+            return Self::Vetoed;
+        }
+
+        let nay_voting_power = nay_voting_power + veto_voting_power;
         let passed = match tally_type {
             TallyType::TwoThirds => {
                 yay_voting_power >= total_voting_power * 2 / 3
             }
             TallyType::OneHalfOverOneThird => {
                 let at_least_one_third_voted =
-                    yay_voting_power + nay_voting_power + abstain_voting_power
-                        >= total_voting_power / 3;
+                    voted_power >= total_voting_power / 3;
 
                 // At least half of non-abstained votes are yay
                 let at_last_half_voted_yay =
@@ -122,9 +145,7 @@ impl TallyResult {
                 at_least_one_third_voted && at_last_half_voted_yay
             }
             TallyType::LessOneHalfOverOneThirdNay => {
-                let less_one_third_voted =
-                    yay_voting_power + nay_voting_power + abstain_voting_power
-                        < total_voting_power / 3;
+                let less_one_third_voted = voted_power < total_voting_power / 3;
 
                 // More than half of non-abstained votes are yay
                 let more_than_half_voted_yay =
@@ -150,6 +171,8 @@ pub struct ProposalResult {
     pub total_nay_power: VotePower,
     /// The total voting power from abstained votes
     pub total_abstain_power: VotePower,
+    /// The total voting power from no with veto votes
+    pub total_veto_power: VotePower,
 }
 
 impl Display for ProposalResult {
@@ -159,17 +182,32 @@ impl Display for ProposalResult {
             .checked_div(self.total_voting_power)
             .unwrap_or_default();
 
-        write!(
-            f,
-            "{} with {} yay votes and {} nay votes ({:.2}%)",
-            self.result,
-            self.total_yay_power.to_string_native(),
-            self.total_nay_power.to_string_native(),
-            percentage
-                .checked_mul(token::Amount::from_u64(100))
-                .unwrap_or_default()
-                .to_string_native()
-        )
+        let percentage = percentage
+            .checked_mul(token::Amount::from_u64(100))
+            .unwrap_or_default()
+            .to_string_native();
+
+        if self.total_veto_power.is_zero() {
+            write!(
+                f,
+                "{} with {} yay votes and {} nay votes ({:.2}%)",
+                self.result,
+                self.total_yay_power.to_string_native(),
+                self.total_nay_power.to_string_native(),
+                percentage
+            )
+        } else {
+            write!(
+                f,
+                "{} with {} yay votes, {} nay votes and {} no with veto votes \
+                 ({:.2}%)",
+                self.result,
+                self.total_yay_power.to_string_native(),
+                self.total_nay_power.to_string_native(),
+                self.total_veto_power.to_string_native(),
+                percentage
+            )
+        }
     }
 }
 
@@ -177,13 +215,13 @@ impl ProposalResult {
     /// Return true if at least 1/3 of the total voting power voted and at least
     /// two third of the non-abstained voting power voted nay
     pub fn two_thirds_nay_over_two_thirds_total(&self) -> bool {
-        let at_least_two_thirds_voted = self.total_yay_power
-            + self.total_nay_power
-            + self.total_abstain_power
-            >= self.total_voting_power * 2 / 3;
+        let total_nay_power = self.total_nay_power + self.total_veto_power;
+        let at_least_two_thirds_voted =
+            self.total_yay_power + total_nay_power + self.total_abstain_power
+                >= self.total_voting_power * 2 / 3;
 
-        let at_least_two_thirds_nay = self.total_nay_power
-            >= (self.total_nay_power + self.total_yay_power) * 2 / 3;
+        let at_least_two_thirds_nay =
+            total_nay_power >= (total_nay_power + self.total_yay_power) * 2 / 3;
 
         at_least_two_thirds_voted && at_least_two_thirds_nay
     }
@@ -234,6 +272,14 @@ impl TallyVote {
         }
     }
 
+    /// Check if a vote is no with veto
+    pub fn is_veto(&self) -> bool {
+        match self {
+            TallyVote::OnChain(vote) => vote.is_veto(),
+            TallyVote::Offline(vote) => vote.is_veto(),
+        }
+    }
+
     /// Check if two votes are equal, returns an error if the variants of the
     /// two instances are different
     pub fn is_same_side(
@@ -265,25 +311,46 @@ pub struct ProposalVotes {
     pub delegator_voting_power: HashMap<Address, HashMap<Address, VotePower>>,
 }
 
+/// The voting power cast on a proposal, by vote
+#[derive(Default)]
+struct VotingPowers {
+    yay: VotePower,
+    nay: VotePower,
+    abstain: VotePower,
+    veto: VotePower,
+}
+
+impl VotingPowers {
+    /// Get the voting power cast with the given vote
+    fn of(&mut self, vote: &TallyVote) -> Option<&mut VotePower> {
+        if vote.is_yay() {
+            Some(&mut self.yay)
+        } else if vote.is_nay() {
+            Some(&mut self.nay)
+        } else if vote.is_abstain() {
+            Some(&mut self.abstain)
+        } else if vote.is_veto() {
+            Some(&mut self.veto)
+        } else {
+            None
+        }
+    }
+}
+
 /// Compute the result of a proposal
 pub fn compute_proposal_result(
     votes: ProposalVotes,
     total_voting_power: VotePower,
     tally_at: TallyType,
+    veto_threshold: Dec,
 ) -> ProposalResult {
-    let mut yay_voting_power = VotePower::default();
-    let mut nay_voting_power = VotePower::default();
-    let mut abstain_voting_power = VotePower::default();
+    let mut voting_powers = VotingPowers::default();
 
     for (address, vote_power) in votes.validator_voting_power {
         let vote_type = votes.validators_vote.get(&address);
         if let Some(vote) = vote_type {
-            if vote.is_yay() {
-                yay_voting_power += vote_power;
-            } else if vote.is_nay() {
-                nay_voting_power += vote_power;
-            } else if vote.is_abstain() {
-                abstain_voting_power += vote_power;
+            if let Some(voting_power) = voting_powers.of(vote) {
+                *voting_power += vote_power;
             }
         }
     }
@@ -314,57 +381,43 @@ pub fn compute_proposal_result(
                                 total_yay_power: VotePower::default(),
                                 total_nay_power: VotePower::default(),
                                 total_abstain_power: VotePower::default(),
+                                total_veto_power: VotePower::default(),
                             };
                         }
                     };
                 if !validator_vote_is_same_side {
-                    if delegator_vote.is_yay() {
-                        yay_voting_power += voting_power;
-                        if validator_vote.is_nay() {
-                            nay_voting_power -= voting_power;
-                        } else if validator_vote.is_abstain() {
-                            abstain_voting_power -= voting_power;
-                        }
-                    } else if delegator_vote.is_nay() {
-                        nay_voting_power += voting_power;
-                        if validator_vote.is_yay() {
-                            yay_voting_power -= voting_power;
-                        } else if validator_vote.is_abstain() {
-                            abstain_voting_power -= voting_power;
-                        }
-                    } else if delegator_vote.is_abstain() {
-                        abstain_voting_power += voting_power;
-                        if validator_vote.is_yay() {
-                            yay_voting_power -= voting_power;
-                        } else if validator_vote.is_nay() {
-                            nay_voting_power -= voting_power;
+                    // The vote of the delegator overrides the one of the
+                    // validator for its bond
+                    if let Some(power) = voting_powers.of(delegator_vote) {
+                        *power += voting_power;
+                        if let Some(power) = voting_powers.of(validator_vote) {
+                            *power -= voting_power;
                         }
                     }
                 }
-            } else if delegator_vote.is_yay() {
-                yay_voting_power += voting_power;
-            } else if delegator_vote.is_nay() {
-                nay_voting_power += voting_power;
-            } else if delegator_vote.is_abstain() {
-                abstain_voting_power += voting_power;
+            } else if let Some(power) = voting_powers.of(delegator_vote) {
+                *power += voting_power;
             }
         }
     }
 
     let tally_result = TallyResult::new(
         &tally_at,
-        yay_voting_power,
-        nay_voting_power,
-        abstain_voting_power,
+        voting_powers.yay,
+        voting_powers.nay,
+        voting_powers.abstain,
+        voting_powers.veto,
         total_voting_power,
+        veto_threshold,
     );
 
     ProposalResult {
         result: tally_result,
         total_voting_power,
-        total_yay_power: yay_voting_power,
-        total_nay_power: nay_voting_power,
-        total_abstain_power: abstain_voting_power,
+        total_yay_power: voting_powers.yay,
+        total_nay_power: voting_powers.nay,
+        total_abstain_power: voting_powers.abstain,
+        total_veto_power: voting_powers.veto,
     }
 }
 
@@ -383,3 +436,33 @@ pub fn is_valid_validator_voting_period(
         current_epoch <= voting_start_epoch + two_third_duration
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tally_result_veto() {
+        let power = token::Amount::native_whole;
+        let veto_threshold = Dec::new(334, 3).unwrap();
+        let tally = |yay, nay, veto| {
+            TallyResult::new(
+                &TallyType::OneHalfOverOneThird,
+                power(yay),
+                power(nay),
+                power(0),
+                power(veto),
+                power(300),
+                veto_threshold,
+            )
+        };
+
+        assert!(matches!(tally(70, 0, 30), TallyResult::Passed));
+        // The no with veto votes count as nay
+        assert!(matches!(tally(50, 30, 30), TallyResult::Rejected));
+        // Vetoed over the threshold, even if the yay votes are a majority
+        assert!(matches!(tally(70, 0, 40), TallyResult::Vetoed));
+        // Not vetoed without the quorum
+        assert!(matches!(tally(0, 0, 90), TallyResult::Rejected));
+    }
+}
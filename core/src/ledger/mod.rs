@@ -1,6 +1,7 @@
 //! The ledger modules
 
 pub mod eth_bridge;
+pub mod fee_grant;
 pub mod gas;
 pub mod governance;
 pub mod ibc;
@@ -9,6 +10,7 @@ pub mod masp_conversions;
 pub mod parameters;
 pub mod pgf;
 pub mod replay_protection;
+pub mod scheduler;
 pub mod storage;
 pub mod storage_api;
 pub mod tx_env;
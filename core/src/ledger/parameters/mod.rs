@@ -6,6 +6,7 @@ use std::collections::BTreeMap;
 use borsh::{BorshDeserialize, BorshSchema, BorshSerialize};
 use thiserror::Error;
 
+use super::gas::GasCostTable;
 use super::storage::types;
 use super::storage_api::token::Amount;
 use super::storage_api::{self, ResultExt, StorageRead, StorageWrite};
@@ -14,6 +15,7 @@ use crate::types::address::{Address, InternalAddress};
 use crate::types::chain::ProposalBytes;
 use crate::types::dec::Dec;
 use crate::types::hash::Hash;
+use crate::types::storage::BlockHeight;
 use crate::types::time::DurationSecs;
 use crate::types::token;
 
@@ -425,6 +427,61 @@ where
     Ok(gas_cost_table.get(token).map(|amount| amount.to_owned()))
 }
 
+/// Read the gas cost table in force at the given block height, i.e. the one
+/// with the latest activation height not above it. The default table applies
+/// until a table is set by governance.
+pub fn read_gas_cost_table<S>(
+    storage: &S,
+    height: BlockHeight,
+) -> storage_api::Result<GasCostTable>
+where
+    S: StorageRead,
+{
+    let tables: BTreeMap<BlockHeight, GasCostTable> = storage
+        .read(&storage::get_gas_cost_tables_key())?
+        .unwrap_or_default();
+    Ok(gas_cost_table_at(&tables, height))
+}
+
+/// Get the gas cost table in force at the given block height out of the
+/// tables set by governance, keyed by their activation height
+pub fn gas_cost_table_at(
+    tables: &BTreeMap<BlockHeight, GasCostTable>,
+    height: BlockHeight,
+) -> GasCostTable {
+    tables
+        .range(..=height)
+        .next_back()
+        .map(|(_, table)| *table)
+        .unwrap_or_default()
+}
+
+/// Set the gas cost table that comes in force at the given activation
+/// height. The tables in force at earlier heights are kept so that the blocks
+/// can be replayed with the costs they were executed with, hence the
+/// activation height must be after the current block height.
+pub fn update_gas_cost_table<S>(
+    storage: &mut S,
+    activation_height: BlockHeight,
+    table: GasCostTable,
+) -> storage_api::Result<()>
+where
+    S: StorageRead + StorageWrite,
+{
+    let current_height = storage.get_block_height()?;
+    if activation_height <= current_height {
+        return Err(storage_api::Error::new(format!(
+            "The gas cost table activation height {activation_height} must be \
+             after the current block height {current_height}"
+        )));
+    }
+    let key = storage::get_gas_cost_tables_key();
+    let mut tables: BTreeMap<BlockHeight, GasCostTable> =
+        storage.read(&key)?.unwrap_or_default();
+    tables.insert(activation_height, table);
+    storage.write(&key, tables)
+}
+
 /// Read all the parameters from storage. Returns the parameters and gas
 /// cost.
 pub fn read<S>(storage: &S) -> storage_api::Result<Parameters>
@@ -576,3 +633,53 @@ where
         fee_unshielding_descriptions_limit,
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ledger::storage::testing::TestWlStorage;
+
+    #[test]
+    fn test_gas_cost_table_versions() {
+        let mut wl_storage = TestWlStorage::default();
+        wl_storage.storage.block.height = BlockHeight(10);
+        assert_eq!(
+            read_gas_cost_table(&wl_storage, BlockHeight(10)).unwrap(),
+            GasCostTable::default()
+        );
+
+        let table = GasCostTable {
+            verify_tx_sig: 1,
+            ..Default::default()
+        };
+        // Tables can't be activated retroactively
+        assert!(
+            update_gas_cost_table(&mut wl_storage, BlockHeight(10), table)
+                .is_err()
+        );
+        update_gas_cost_table(&mut wl_storage, BlockHeight(20), table).unwrap();
+        let later_table = GasCostTable {
+            verify_tx_sig: 2,
+            ..Default::default()
+        };
+        update_gas_cost_table(&mut wl_storage, BlockHeight(30), later_table)
+            .unwrap();
+
+        assert_eq!(
+            read_gas_cost_table(&wl_storage, BlockHeight(19)).unwrap(),
+            GasCostTable::default()
+        );
+        assert_eq!(
+            read_gas_cost_table(&wl_storage, BlockHeight(20)).unwrap(),
+            table
+        );
+        assert_eq!(
+            read_gas_cost_table(&wl_storage, BlockHeight(29)).unwrap(),
+            table
+        );
+        assert_eq!(
+            read_gas_cost_table(&wl_storage, BlockHeight(100)).unwrap(),
+            later_table
+        );
+    }
+}
@@ -46,6 +46,7 @@ struct Keys {
     fee_unshielding_gas_limit: &'static str,
     fee_unshielding_descriptions_limit: &'static str,
     max_signatures_per_transaction: &'static str,
+    gas_cost_tables: &'static str,
 }
 
 /// Returns if the key is a parameter key.
@@ -126,6 +127,11 @@ pub fn is_max_tx_bytes_key(key: &Key) -> bool {
     is_max_tx_bytes_key_at_addr(key, &ADDRESS)
 }
 
+/// Returns if the key is the gas cost tables key.
+pub fn is_gas_cost_tables_key(key: &Key) -> bool {
+    is_gas_cost_tables_key_at_addr(key, &ADDRESS)
+}
+
 /// Storage key used for epoch parameter.
 pub fn get_epoch_duration_storage_key() -> Key {
     get_epoch_duration_key_at_addr(ADDRESS)
@@ -206,6 +212,11 @@ pub fn get_gas_cost_key() -> Key {
     get_minimum_gas_price_key_at_addr(ADDRESS)
 }
 
+/// Storage key used for the versioned gas cost tables
+pub fn get_gas_cost_tables_key() -> Key {
+    get_gas_cost_tables_key_at_addr(ADDRESS)
+}
+
 /// Storage key used for the max signatures per transaction key
 pub fn get_max_signatures_per_transaction_key() -> Key {
     get_max_signatures_per_transaction_key_at_addr(ADDRESS)
@@ -14,6 +14,7 @@ use crate::types::storage::{DbKeySeg, Key, KeySeg};
 struct Keys {
     stewards: &'static str,
     fundings: &'static str,
+    pauses: &'static str,
     pgf_inflation_rate: &'static str,
     steward_inflation_rate: &'static str,
 }
@@ -62,18 +63,51 @@ pub fn fundings_key_prefix() -> Key {
     }
 }
 
-/// LazyMap handler for the pgf fundings substorage
-pub fn fundings_handle() -> LazyMap<Address, StoragePgfFunding> {
+/// LazyMap handler for the pgf fundings substorage, indexed by the funding
+/// key of the recipients
+pub fn fundings_handle() -> LazyMap<String, StoragePgfFunding> {
     LazyMap::open(fundings_key_prefix())
 }
 
 /// Check if the given storage key is a pgf funding key.
 pub fn is_fundings_key(key: &Key) -> bool {
-    matches!(&key.segments[..], [DbKeySeg::AddressSeg(pgf), DbKeySeg::StringSeg(prefix), DbKeySeg::StringSeg(data), DbKeySeg::AddressSeg(_)] if pgf.eq(&ADDRESS)
+    matches!(&key.segments[..], [DbKeySeg::AddressSeg(pgf), DbKeySeg::StringSeg(prefix), DbKeySeg::StringSeg(data), DbKeySeg::StringSeg(_)] if pgf.eq(&ADDRESS)
                && prefix.as_str() == Keys::VALUES.fundings
                 && data.as_str() == lazy_map::DATA_SUBKEY)
 }
 
+/// Obtain the storage key prefix of the pauses of a pgf funding
+pub fn funding_pauses_prefix(funding: &str) -> Key {
+    Key::from(ADDRESS.to_db_key())
+        .push(&Keys::VALUES.pauses.to_owned())
+        .expect("Cannot obtain a storage key")
+        .push(&funding.to_owned())
+        .expect("Cannot obtain a storage key")
+}
+
+/// Obtain the storage key of the pause of a pgf funding by a steward
+pub fn funding_pause_key(funding: &str, steward: &Address) -> Key {
+    funding_pauses_prefix(funding)
+        .push(steward)
+        .expect("Cannot obtain a storage key")
+}
+
+/// Check if the given storage key is the pause of a pgf funding by a
+/// steward. If it is, returns the funding key and the steward address.
+pub fn is_funding_pause_key(key: &Key) -> Option<(&String, &Address)> {
+    match &key.segments[..] {
+        [
+            DbKeySeg::AddressSeg(pgf),
+            DbKeySeg::StringSeg(prefix),
+            DbKeySeg::StringSeg(funding),
+            DbKeySeg::AddressSeg(steward),
+        ] if pgf.eq(&ADDRESS) && prefix.as_str() == Keys::VALUES.pauses => {
+            Some((funding, steward))
+        }
+        _ => None,
+    }
+}
+
 /// Check if key is inside governance address space
 pub fn is_pgf_key(key: &Key) -> bool {
     matches!(&key.segments[0], DbKeySeg::AddressSeg(addr) if addr == &ADDRESS)
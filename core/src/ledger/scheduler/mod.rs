@@ -0,0 +1,13 @@
+//! Scheduler library code
+
+use crate::types::address::{Address, InternalAddress};
+
+/// Scheduler storage
+pub mod storage;
+
+/// The scheduler internal address
+pub const ADDRESS: Address = Address::Internal(InternalAddress::Scheduler);
+
+/// The maximum number of txs that may be scheduled at the same block height
+/// or epoch
+pub const MAX_TXS_PER_SCHEDULE: usize = 16;
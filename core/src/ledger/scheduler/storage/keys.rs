@@ -0,0 +1,143 @@
+//! The storage layout of the scheduled txs:
+//! - `#scheduler/counter`: the id of the next scheduled tx
+//! - `#scheduler/tx/{id}`: the scheduled tx
+//! - `#scheduler/owner/{owner}/{id}`: an empty entry for every scheduled tx of
+//!   an owner
+//! - `#scheduler/height/{height}/{id}`: an empty entry for every tx scheduled
+//!   at a block height
+//! - `#scheduler/epoch/{epoch}/{id}`: an empty entry for every tx scheduled at
+//!   an epoch
+
+use namada_macros::StorageKeys;
+
+use crate::ledger::scheduler::ADDRESS;
+use crate::types::address::Address;
+use crate::types::storage::{BlockHeight, DbKeySeg, Epoch, Key, KeySeg};
+use crate::types::transaction::scheduler::Schedule;
+
+/// Storage keys for the scheduler internal address.
+#[derive(StorageKeys)]
+struct Keys {
+    counter: &'static str,
+    tx: &'static str,
+    owner: &'static str,
+    height: &'static str,
+    epoch: &'static str,
+}
+
+/// Check if key is inside the scheduler address space
+pub fn is_scheduler_key(key: &Key) -> bool {
+    matches!(&key.segments[0], DbKeySeg::AddressSeg(addr) if addr == &ADDRESS)
+}
+
+/// Check if key is the scheduled txs counter key
+pub fn is_counter_key(key: &Key) -> bool {
+    matches!(&key.segments[..], [DbKeySeg::AddressSeg(addr), DbKeySeg::StringSeg(counter)] if addr == &ADDRESS && counter == Keys::VALUES.counter)
+}
+
+/// Check if key is a scheduled tx key. If it is, returns the id of the tx.
+pub fn is_tx_key(key: &Key) -> Option<u64> {
+    match &key.segments[..] {
+        [
+            DbKeySeg::AddressSeg(addr),
+            DbKeySeg::StringSeg(prefix),
+            DbKeySeg::StringSeg(id),
+        ] if addr == &ADDRESS && prefix == Keys::VALUES.tx => {
+            id.parse().ok()
+        }
+        _ => None,
+    }
+}
+
+/// Check if key is an owner index key. If it is, returns the owner and the
+/// id of the tx.
+pub fn is_owner_key(key: &Key) -> Option<(&Address, u64)> {
+    match &key.segments[..] {
+        [
+            DbKeySeg::AddressSeg(addr),
+            DbKeySeg::StringSeg(prefix),
+            DbKeySeg::AddressSeg(owner),
+            DbKeySeg::StringSeg(id),
+        ] if addr == &ADDRESS && prefix == Keys::VALUES.owner => {
+            Some((owner, id.parse().ok()?))
+        }
+        _ => None,
+    }
+}
+
+/// Check if key is a schedule index key, either by height or by epoch. If it
+/// is, returns the schedule and the id of the tx.
+pub fn is_schedule_key(key: &Key) -> Option<(Schedule, u64)> {
+    match &key.segments[..] {
+        [
+            DbKeySeg::AddressSeg(addr),
+            DbKeySeg::StringSeg(prefix),
+            DbKeySeg::StringSeg(at),
+            DbKeySeg::StringSeg(id),
+        ] if addr == &ADDRESS => {
+            let at: u64 = at.parse().ok()?;
+            let schedule = if prefix == Keys::VALUES.height {
+                Schedule::AtHeight(BlockHeight(at))
+            } else if prefix == Keys::VALUES.epoch {
+                Schedule::AtEpoch(Epoch(at))
+            } else {
+                return None;
+            };
+            Some((schedule, id.parse().ok()?))
+        }
+        _ => None,
+    }
+}
+
+/// Get the scheduled txs counter key
+pub fn get_counter_key() -> Key {
+    Key::from(ADDRESS.to_db_key())
+        .push(&Keys::VALUES.counter.to_owned())
+        .expect("Cannot obtain a storage key")
+}
+
+/// Get the key of a scheduled tx
+pub fn get_tx_key(id: u64) -> Key {
+    Key::from(ADDRESS.to_db_key())
+        .push(&Keys::VALUES.tx.to_owned())
+        .expect("Cannot obtain a storage key")
+        .push(&id.to_string())
+        .expect("Cannot obtain a storage key")
+}
+
+/// Get the prefix of the owner index entries of the given owner
+pub fn get_owner_prefix(owner: &Address) -> Key {
+    Key::from(ADDRESS.to_db_key())
+        .push(&Keys::VALUES.owner.to_owned())
+        .expect("Cannot obtain a storage key")
+        .push(owner)
+        .expect("Cannot obtain a storage key")
+}
+
+/// Get the owner index key of a scheduled tx
+pub fn get_owner_key(owner: &Address, id: u64) -> Key {
+    get_owner_prefix(owner)
+        .push(&id.to_string())
+        .expect("Cannot obtain a storage key")
+}
+
+/// Get the prefix of the schedule index entries of the txs due at the given
+/// schedule
+pub fn get_schedule_prefix(schedule: &Schedule) -> Key {
+    let (prefix, at) = match schedule {
+        Schedule::AtHeight(height) => (Keys::VALUES.height, height.0),
+        Schedule::AtEpoch(epoch) => (Keys::VALUES.epoch, epoch.0),
+    };
+    Key::from(ADDRESS.to_db_key())
+        .push(&prefix.to_owned())
+        .expect("Cannot obtain a storage key")
+        .push(&at.to_string())
+        .expect("Cannot obtain a storage key")
+}
+
+/// Get the schedule index key of a scheduled tx
+pub fn get_schedule_key(schedule: &Schedule, id: u64) -> Key {
+    get_schedule_prefix(schedule)
+        .push(&id.to_string())
+        .expect("Cannot obtain a storage key")
+}
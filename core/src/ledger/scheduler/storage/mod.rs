@@ -0,0 +1,2 @@
+/// Scheduler storage keys
+pub mod keys;
@@ -446,6 +446,27 @@ impl<H: StorageHasher + Default> MerkleTree<H> {
         self.tree(&store_type).subtree_get(&sub_key)
     }
 
+    /// Check if the given value is the one committed in the tree for the
+    /// key. The sub-trees store hashes of the values, except for the IBC tree
+    /// which stores the raw values and the bridge pool tree, whose keys are
+    /// already derived from the pending transfers.
+    pub fn has_value(
+        &self,
+        key: &Key,
+        value: impl AsRef<[u8]>,
+    ) -> Result<bool> {
+        let (store_type, sub_key) = StoreType::sub_key(key)?;
+        let tree = self.tree(&store_type);
+        match store_type {
+            StoreType::BridgePool => tree.subtree_has_key(&sub_key),
+            StoreType::Ibc => Ok(tree.subtree_get(&sub_key)? == value.as_ref()),
+            _ => {
+                let hash = Hash::from(H::hash(value));
+                Ok(tree.subtree_get(&sub_key)? == hash.0)
+            }
+        }
+    }
+
     /// Update the tree with the given key and value
     pub fn update(&mut self, key: &Key, value: impl AsRef<[u8]>) -> Result<()> {
         let (store_type, sub_key) = StoreType::sub_key(key)?;
@@ -894,6 +915,28 @@ mod test {
         assert!(restored_tree.has_key(&pos_key).unwrap());
     }
 
+    #[test]
+    fn test_has_value() {
+        let mut tree = MerkleTree::<Sha256Hasher>::default();
+
+        let key_prefix: Key =
+            Address::Internal(InternalAddress::Ibc).to_db_key().into();
+        let ibc_key = key_prefix.push(&"test".to_string()).unwrap();
+        let key_prefix: Key =
+            Address::Internal(InternalAddress::PoS).to_db_key().into();
+        let pos_key = key_prefix.push(&"test".to_string()).unwrap();
+        let pos_non_key = key_prefix.push(&"test2".to_string()).unwrap();
+
+        tree.update(&ibc_key, [1u8; 8]).unwrap();
+        tree.update(&pos_key, [2u8; 8]).unwrap();
+
+        assert!(tree.has_value(&ibc_key, [1u8; 8]).unwrap());
+        assert!(!tree.has_value(&ibc_key, [2u8; 8]).unwrap());
+        assert!(tree.has_value(&pos_key, [2u8; 8]).unwrap());
+        assert!(!tree.has_value(&pos_key, [1u8; 8]).unwrap());
+        assert!(!tree.has_value(&pos_non_key, [2u8; 8]).unwrap());
+    }
+
     #[test]
     fn test_ibc_existence_proof() {
         let mut tree = MerkleTree::<Sha256Hasher>::default();
@@ -14,7 +14,8 @@ use super::merkle_tree::{
     base_tree_key_prefix, subtree_key_prefix, MerkleTreeStoresRead, StoreType,
 };
 use super::{
-    BlockStateRead, BlockStateWrite, DBIter, DBWriteBatch, Error, Result, DB,
+    BlockStateRead, BlockStateWrite, DBIter, DBWriteBatch, Error,
+    IndexedEvent, Result, DB,
 };
 use crate::ledger::masp_conversions::ConversionState;
 use crate::ledger::storage::types::{self, KVBytes, PrefixIterator};
@@ -54,10 +55,6 @@ impl DB for MockDB {
         Self::default()
     }
 
-    fn flush(&self, _wait: bool) -> Result<()> {
-        Ok(())
-    }
-
     fn read_last_block(&self) -> Result<Option<BlockStateRead>> {
         // Block height
         let height: BlockHeight = match self.0.borrow().get("height") {
@@ -588,6 +585,21 @@ impl DB for MockDB {
         Ok(())
     }
 
+    fn prune_subspace_diffs(
+        &mut self,
+        _batch: &mut Self::WriteBatch,
+        height: BlockHeight,
+    ) -> Result<()> {
+        // Only the "old" and "new" diffs, the block data is also prepended
+        // with the height
+        let old_prefix = format!("{}/old/", height.raw());
+        let new_prefix = format!("{}/new/", height.raw());
+        self.0.borrow_mut().retain(|key, _| {
+            !key.starts_with(&old_prefix) && !key.starts_with(&new_prefix)
+        });
+        Ok(())
+    }
+
     fn write_replay_protection_entry(
         &mut self,
         _batch: &mut Self::WriteBatch,
@@ -618,6 +630,77 @@ impl DB for MockDB {
 
         Ok(())
     }
+
+    fn batch_write_events(
+        &self,
+        _batch: &mut Self::WriteBatch,
+        height: BlockHeight,
+        events: &[IndexedEvent],
+    ) -> Result<()> {
+        let prefix = format!("events/{}/", height.raw());
+        let mut db = self.0.borrow_mut();
+        db.retain(|key, _| !key.starts_with(&prefix));
+        for (n, event) in events.iter().enumerate() {
+            let key = format!("{prefix}{}", (n as u64).raw());
+            db.insert(key, event.serialize_to_vec());
+        }
+        Ok(())
+    }
+
+    fn read_events(&self, height: BlockHeight) -> Result<Vec<Vec<u8>>> {
+        let prefix = format!("events/{}/", height.raw());
+        self.0
+            .borrow()
+            .iter()
+            .filter(|(key, _)| key.starts_with(&prefix))
+            .map(|(_, value)| {
+                types::decode::<IndexedEvent>(value)
+                    .map(|indexed| indexed.event)
+                    .map_err(Error::CodingError)
+            })
+            .collect()
+    }
+
+    fn read_indexed_events(&self, index_key: &str) -> Result<Vec<Vec<u8>>> {
+        let mut events = vec![];
+        for (key, value) in self.0.borrow().iter().rev() {
+            if !key.starts_with("events/") {
+                continue;
+            }
+            let indexed: IndexedEvent =
+                types::decode(value).map_err(Error::CodingError)?;
+            if indexed.index_keys.iter().any(|key| key == index_key) {
+                events.push(indexed.event);
+            }
+        }
+        Ok(events)
+    }
+
+    fn prune_events(
+        &mut self,
+        _batch: &mut Self::WriteBatch,
+        below_height: BlockHeight,
+    ) -> Result<()> {
+        self.0.borrow_mut().retain(|key, _| {
+            match key
+                .strip_prefix("events/")
+                .and_then(|rest| rest.split_once('/'))
+            {
+                Some((height, _)) => u64::parse(height.to_owned())
+                    .map(|height| BlockHeight(height) >= below_height)
+                    .unwrap_or(true),
+                None => true,
+            }
+        });
+        Ok(())
+    }
+
+    fn checkpoint(&self, _path: &Path) -> Result<()> {
+        // The DB is in memory, so a copy can't be opened from a path
+        Err(Error::DBError(
+            "The mock DB cannot be checkpointed".to_string(),
+        ))
+    }
 }
 
 impl<'iter> DBIter<'iter> for MockDB {
@@ -703,6 +786,18 @@ impl<'iter> DBIter<'iter> for MockDB {
             db_prefix,
         )
     }
+
+    fn iter_all_replay_protection(&'iter self) -> Self::PrefixIter {
+        let db_prefix = "replay_protection/".to_owned();
+        let iter = self.0.borrow().clone().into_iter();
+        MockPrefixIterator::new(
+            MockIterator {
+                prefix: "replay_protection/all/".to_string(),
+                iter,
+            },
+            db_prefix,
+        )
+    }
 }
 
 /// A prefix iterator base for the [`MockPrefixIterator`].
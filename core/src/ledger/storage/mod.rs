@@ -11,6 +11,7 @@ pub mod write_log;
 
 use core::fmt::Debug;
 use std::cmp::Ordering;
+use std::collections::BTreeMap;
 use std::format;
 
 use borsh::{BorshDeserialize, BorshSerialize};
@@ -24,11 +25,8 @@ pub use wl_storage::{
     iter_prefix_post, iter_prefix_pre, PrefixIter, TempWlStorage, WlStorage,
 };
 
-use super::gas::MEMORY_ACCESS_GAS_PER_BYTE;
 use crate::ledger::eth_bridge::storage::bridge_pool::is_pending_transfer_key;
-use crate::ledger::gas::{
-    STORAGE_ACCESS_GAS_PER_BYTE, STORAGE_WRITE_GAS_PER_BYTE,
-};
+use crate::ledger::gas::GasCostTable;
 pub use crate::ledger::masp_conversions::{
     calculate_masp_rewards, encode_asset_type, ConversionState,
 };
@@ -44,8 +42,8 @@ use crate::types::hash::{Error as HashError, Hash};
 use crate::types::internal::{ExpiredTxsQueue, TxQueue};
 use crate::types::storage::{
     BlockHash, BlockHeight, BlockResults, Epoch, Epochs, EthEventsQueue,
-    Header, Key, KeySeg, MembershipProof, TxIndex, BLOCK_HASH_LENGTH,
-    BLOCK_HEIGHT_LENGTH, EPOCH_TYPE_LENGTH,
+    Header, Key, KeySeg, MembershipProof, PrefixValue, TxIndex,
+    BLOCK_HASH_LENGTH, BLOCK_HEIGHT_LENGTH, EPOCH_TYPE_LENGTH,
 };
 use crate::types::time::DateTimeUtc;
 
@@ -112,6 +110,12 @@ where
     pub eth_events_queue: EthEventsQueue,
     /// How many block heights in the past can the storage be queried
     pub storage_read_past_height_limit: Option<u64>,
+    /// The gas costs in force at the current block height
+    pub gas_costs: GasCostTable,
+    /// The events emitted in the current block, written with it on commit
+    pub block_events: Vec<IndexedEvent>,
+    /// The events emitted below this height are pruned on commit
+    pub events_prune_below: Option<BlockHeight>,
 }
 
 /// Last committed block
@@ -245,9 +249,19 @@ pub struct BlockStateWrite<'a> {
     pub eth_events_queue: &'a EthEventsQueue,
 }
 
-/// A database backend.
+/// An event emitted in a block, encoded for the persistent event store.
+#[derive(Debug, Clone, PartialEq, Eq, BorshSerialize, BorshDeserialize)]
+pub struct IndexedEvent {
+    /// The keys by which the event can be looked up
+    pub index_keys: Vec<String>,
+    /// The Borsh-encoded event
+    pub event: Vec<u8>,
+}
+
+/// A database backend. The layout of the persisted data is up to the
+/// implementation, which only has to be able to read back what it wrote.
 pub trait DB: std::fmt::Debug {
-    /// A DB's cache
+    /// A DB's cache, as configured by the node operator
     type Cache;
     /// A handle for batch writes
     type WriteBatch: DBWriteBatch;
@@ -258,9 +272,6 @@ pub trait DB: std::fmt::Debug {
         cache: Option<&Self::Cache>,
     ) -> Self;
 
-    /// Flush data on the memory to persistent them
-    fn flush(&self, wait: bool) -> Result<()>;
-
     /// Read the last committed block's metadata
     fn read_last_block(&self) -> Result<Option<BlockStateRead>>;
 
@@ -293,9 +304,9 @@ pub trait DB: std::fmt::Debug {
     fn read_subspace_val(&self, key: &Key) -> Result<Option<Vec<u8>>>;
 
     /// Read the value for account subspace key at the given height from the DB.
-    /// In our `PersistentStorage` (rocksdb), to find a value from arbitrary
-    /// height requires looking for diffs from the given `height`, possibly
-    /// up to the `last_height`.
+    /// In the persistent DB backends, to find a value from arbitrary height
+    /// requires looking for diffs from the given `height`, possibly up to the
+    /// `last_height`.
     fn read_subspace_val_with_height(
         &self,
         key: &Key,
@@ -357,6 +368,13 @@ pub trait DB: std::fmt::Debug {
         pruned_epoch: Epoch,
     ) -> Result<()>;
 
+    /// Prune the subspace diffs written at the given height
+    fn prune_subspace_diffs(
+        &mut self,
+        batch: &mut Self::WriteBatch,
+        pruned_height: BlockHeight,
+    ) -> Result<()>;
+
     /// Write a replay protection entry
     fn write_replay_protection_entry(
         &mut self,
@@ -370,6 +388,34 @@ pub trait DB: std::fmt::Debug {
         batch: &mut Self::WriteBatch,
         key: &Key,
     ) -> Result<()>;
+
+    /// Batch write the events emitted in the block at the given height, in
+    /// the order of their emission. Any events previously written at this
+    /// height are replaced.
+    fn batch_write_events(
+        &self,
+        batch: &mut Self::WriteBatch,
+        height: BlockHeight,
+        events: &[IndexedEvent],
+    ) -> Result<()>;
+
+    /// Read the encoded events emitted in the block at the given height
+    fn read_events(&self, height: BlockHeight) -> Result<Vec<Vec<u8>>>;
+
+    /// Read the encoded events indexed by the given key, from the most recent
+    fn read_indexed_events(&self, index_key: &str) -> Result<Vec<Vec<u8>>>;
+
+    /// Prune the events emitted below the given height, with their indices
+    fn prune_events(
+        &mut self,
+        batch: &mut Self::WriteBatch,
+        below_height: BlockHeight,
+    ) -> Result<()>;
+
+    /// Write a consistent copy of the last committed state to the given
+    /// directory, which can then be opened with [`DB::open`] while this DB
+    /// keeps being written
+    fn checkpoint(&self, path: &std::path::Path) -> Result<()>;
 }
 
 /// A database prefix iterator.
@@ -404,6 +450,9 @@ pub trait DBIter<'iter> {
 
     /// Read replay protection storage from the last block
     fn iter_replay_protection(&'iter self) -> Self::PrefixIter;
+
+    /// Read the replay protection storage of all the committed blocks
+    fn iter_all_replay_protection(&'iter self) -> Self::PrefixIter;
 }
 
 /// Atomic batch write.
@@ -451,6 +500,9 @@ where
             ethereum_height: None,
             eth_events_queue: EthEventsQueue::default(),
             storage_read_past_height_limit,
+            gas_costs: GasCostTable::default(),
+            block_events: Vec::new(),
+            events_prune_below: None,
         }
     }
 
@@ -567,6 +619,13 @@ where
             // prune old merkle tree stores
             self.prune_merkle_tree_stores(&mut batch)?;
         }
+        // The events are persisted atomically with the block
+        let events = std::mem::take(&mut self.block_events);
+        self.db
+            .batch_write_events(&mut batch, self.block.height, &events)?;
+        if let Some(below_height) = self.events_prune_below.take() {
+            self.db.prune_events(&mut batch, below_height)?;
+        }
         self.db.exec_batch(batch)
     }
 
@@ -580,7 +639,7 @@ where
     pub fn has_key(&self, key: &Key) -> Result<(bool, u64)> {
         Ok((
             self.block.tree.has_key(key)?,
-            key.len() as u64 * STORAGE_ACCESS_GAS_PER_BYTE,
+            key.len() as u64 * self.gas_costs.storage_access_per_byte,
         ))
     }
 
@@ -594,11 +653,14 @@ where
 
         match self.db.read_subspace_val(key)? {
             Some(v) => {
-                let gas =
-                    (key.len() + v.len()) as u64 * STORAGE_ACCESS_GAS_PER_BYTE;
+                let gas = (key.len() + v.len()) as u64
+                    * self.gas_costs.storage_access_per_byte;
                 Ok((Some(v), gas))
             }
-            None => Ok((None, key.len() as u64 * STORAGE_ACCESS_GAS_PER_BYTE)),
+            None => Ok((
+                None,
+                key.len() as u64 * self.gas_costs.storage_access_per_byte,
+            )),
         }
     }
 
@@ -620,14 +682,67 @@ where
             )? {
                 Some(v) => {
                     let gas = (key.len() + v.len()) as u64
-                        * STORAGE_ACCESS_GAS_PER_BYTE;
+                        * self.gas_costs.storage_access_per_byte;
                     Ok((Some(v), gas))
                 }
-                None => {
-                    Ok((None, key.len() as u64 * STORAGE_ACCESS_GAS_PER_BYTE))
+                None => Ok((
+                    None,
+                    key.len() as u64 * self.gas_costs.storage_access_per_byte,
+                )),
+            }
+        }
+    }
+
+    /// WARNING: This only works for values that have been committed to DB.
+    ///
+    /// Returns the key-value pairs with the given prefix at the given height
+    /// or the last committed height when 0, ordered by storage keys.
+    ///
+    /// The values at a past height are found by undoing the diffs of the
+    /// succeeding heights on the values at the last committed height, from the
+    /// most recent one. The cost is linear in the number of heights and diffs
+    /// with the given prefix, so callers serving untrusted requests should
+    /// bound the number of heights to go back.
+    pub fn read_prefix_with_height(
+        &self,
+        prefix: &Key,
+        height: BlockHeight,
+    ) -> Result<Vec<PrefixValue>> {
+        let last_height = self.get_last_block_height();
+        let mut values: BTreeMap<String, Vec<u8>> = self
+            .db
+            .iter_prefix(Some(prefix))
+            .map(|(key, value, _gas)| (key, value))
+            .collect();
+        // `0` means last committed height
+        if height != BlockHeight(0) {
+            for raw_height in (height.0 + 1..=last_height.0).rev() {
+                let diff_height = BlockHeight(raw_height);
+                let old_values: BTreeMap<String, Vec<u8>> = self
+                    .db
+                    .iter_old_diffs(diff_height, Some(prefix))
+                    .map(|(key, value, _gas)| (key, value))
+                    .collect();
+                // A key with a new value but no old value was created at
+                // this height
+                for (key, _value, _gas) in
+                    self.db.iter_new_diffs(diff_height, Some(prefix))
+                {
+                    if !old_values.contains_key(&key) {
+                        values.remove(&key);
+                    }
                 }
+                values.extend(old_values);
             }
         }
+
+        values
+            .into_iter()
+            .map(|(key, value)| {
+                let key = Key::parse(key).map_err(Error::KeyError)?;
+                Ok(PrefixValue { key, value })
+            })
+            .collect()
     }
 
     /// WARNING: This only works for values that have been committed to DB.
@@ -641,7 +756,7 @@ where
     ) -> (<D as DBIter<'_>>::PrefixIter, u64) {
         (
             self.db.iter_prefix(Some(prefix)),
-            prefix.len() as u64 * STORAGE_ACCESS_GAS_PER_BYTE,
+            prefix.len() as u64 * self.gas_costs.storage_access_per_byte,
         )
     }
 
@@ -672,7 +787,8 @@ where
         }
 
         let len = value.len();
-        let gas = (key.len() + len) as u64 * STORAGE_WRITE_GAS_PER_BYTE;
+        let gas =
+            (key.len() + len) as u64 * self.gas_costs.storage_write_per_byte;
         let size_diff =
             self.db.write_subspace_val(self.block.height, key, value)?;
         Ok((gas, size_diff))
@@ -690,7 +806,7 @@ where
                 self.db.delete_subspace_val(self.block.height, key)?;
         }
         let gas = (key.len() + deleted_bytes_len as usize) as u64
-            * STORAGE_WRITE_GAS_PER_BYTE;
+            * self.gas_costs.storage_write_per_byte;
         Ok((gas, deleted_bytes_len))
     }
 
@@ -746,7 +862,7 @@ where
     pub fn get_chain_id(&self) -> (String, u64) {
         (
             self.chain_id.to_string(),
-            CHAIN_ID_LENGTH as u64 * MEMORY_ACCESS_GAS_PER_BYTE,
+            CHAIN_ID_LENGTH as u64 * self.gas_costs.memory_access_per_byte,
         )
     }
 
@@ -754,7 +870,7 @@ where
     pub fn get_block_height(&self) -> (BlockHeight, u64) {
         (
             self.block.height,
-            BLOCK_HEIGHT_LENGTH as u64 * MEMORY_ACCESS_GAS_PER_BYTE,
+            BLOCK_HEIGHT_LENGTH as u64 * self.gas_costs.memory_access_per_byte,
         )
     }
 
@@ -762,7 +878,7 @@ where
     pub fn get_block_hash(&self) -> (BlockHash, u64) {
         (
             self.block.hash.clone(),
-            BLOCK_HASH_LENGTH as u64 * MEMORY_ACCESS_GAS_PER_BYTE,
+            BLOCK_HASH_LENGTH as u64 * self.gas_costs.memory_access_per_byte,
         )
     }
 
@@ -987,7 +1103,7 @@ where
     pub fn get_current_epoch(&self) -> (Epoch, u64) {
         (
             self.block.epoch,
-            EPOCH_TYPE_LENGTH as u64 * MEMORY_ACCESS_GAS_PER_BYTE,
+            EPOCH_TYPE_LENGTH as u64 * self.gas_costs.memory_access_per_byte,
         )
     }
 
@@ -995,7 +1111,7 @@ where
     pub fn get_last_epoch(&self) -> (Epoch, u64) {
         (
             self.last_epoch,
-            EPOCH_TYPE_LENGTH as u64 * MEMORY_ACCESS_GAS_PER_BYTE,
+            EPOCH_TYPE_LENGTH as u64 * self.gas_costs.memory_access_per_byte,
         )
     }
 
@@ -1025,21 +1141,25 @@ where
                 let header = self.header.clone();
                 let gas = match header {
                     Some(ref header) => {
-                        header.encoded_len() as u64 * MEMORY_ACCESS_GAS_PER_BYTE
+                        header.encoded_len() as u64
+                            * self.gas_costs.memory_access_per_byte
                     }
-                    None => MEMORY_ACCESS_GAS_PER_BYTE,
+                    None => self.gas_costs.memory_access_per_byte,
                 };
                 Ok((header, gas))
             }
             Some(h) => match self.db.read_block_header(h)? {
                 Some(header) => {
                     let gas = header.encoded_len() as u64
-                        * STORAGE_ACCESS_GAS_PER_BYTE;
+                        * self.gas_costs.storage_access_per_byte;
                     Ok((Some(header), gas))
                 }
-                None => Ok((None, STORAGE_ACCESS_GAS_PER_BYTE)),
+                None => Ok((None, self.gas_costs.storage_access_per_byte)),
             },
-            None => Ok((self.header.clone(), STORAGE_ACCESS_GAS_PER_BYTE)),
+            None => Ok((
+                self.header.clone(),
+                self.gas_costs.storage_access_per_byte,
+            )),
         }
     }
 
@@ -1061,30 +1181,13 @@ where
 
     /// Update the merkle tree with epoch data
     fn update_epoch_in_merkle_tree(&mut self) -> Result<()> {
-        let key_prefix: Key =
-            Address::Internal(InternalAddress::PoS).to_db_key().into();
-
-        let key = key_prefix
-            .push(&"epoch_start_height".to_string())
-            .map_err(Error::KeyError)?;
-        self.block
-            .tree
-            .update(&key, types::encode(&self.next_epoch_min_start_height))?;
-
-        let key = key_prefix
-            .push(&"epoch_start_time".to_string())
-            .map_err(Error::KeyError)?;
-        self.block
-            .tree
-            .update(&key, types::encode(&self.next_epoch_min_start_time))?;
-
-        let key = key_prefix
-            .push(&"current_epoch".to_string())
-            .map_err(Error::KeyError)?;
-        self.block
-            .tree
-            .update(&key, types::encode(&self.block.epoch))?;
-
+        for (key, value) in epoch_merkle_tree_entries(
+            self.next_epoch_min_start_height,
+            self.next_epoch_min_start_time,
+            self.block.epoch,
+        )? {
+            self.block.tree.update(&key, value)?;
+        }
         Ok(())
     }
 
@@ -1150,25 +1253,62 @@ where
             }
 
             let min_height = (self.get_last_block_height().0 - limit).into();
+            // The minimum height at the previous full commit, i.e. at the
+            // start of the previous epoch
+            let last_min_height = self
+                .block
+                .pred_epochs
+                .get_start_height_of_epoch(self.block.epoch.prev())
+                .and_then(|height| height.0.checked_sub(limit))
+                .map(BlockHeight);
             if let Some(epoch) = self.block.pred_epochs.get_epoch(min_height) {
                 if epoch.0 == 0 {
                     return Ok(());
                 }
-                // Remove stores at the previous epoch because the Merkle tree
-                // stores at the starting height of the epoch would be used to
-                // restore stores at a height (> min_height) in the epoch
-                for st in StoreType::iter_provable() {
-                    self.db.prune_merkle_tree_store(batch, st, epoch.prev())?;
+                // The previous full commit has already pruned the stores and
+                // diffs of the epochs before the one of its minimum height.
+                // The minimum height may have crossed several epochs since
+                // then, if they're shorter than the epoch of this commit.
+                let first_epoch = last_min_height
+                    .and_then(|height| self.block.pred_epochs.get_epoch(height))
+                    .unwrap_or_default();
+                for pruned_epoch in (first_epoch.0..epoch.0).map(Epoch) {
+                    self.prune_epoch_stores(batch, pruned_epoch)?;
                 }
             }
         }
-        // remove non-provable stores at the previous epoch
-        for st in StoreType::iter_non_provable() {
-            self.db.prune_merkle_tree_store(
-                batch,
-                st,
-                self.block.epoch.prev(),
-            )?;
+        // Without a limit, every store is retained so that the state at any
+        // height can be read or rolled back to
+        Ok(())
+    }
+
+    // Remove the stores at an epoch before the one of the minimum height,
+    // because the Merkle tree stores at the starting height of the epoch of
+    // the minimum height would be used to restore stores at a height
+    // (> min_height) in that epoch. The non-provable stores are retained for
+    // the same heights, so that the DB can be rolled back within the limit.
+    fn prune_epoch_stores(
+        &mut self,
+        batch: &mut D::WriteBatch,
+        epoch: Epoch,
+    ) -> Result<()> {
+        for st in StoreType::iter_subtrees() {
+            self.db.prune_merkle_tree_store(batch, st, epoch)?;
+        }
+        // The subspace diffs in the epoch are not needed anymore either, the
+        // values at the retained heights can be restored from the diffs of
+        // the succeeding heights
+        let pruned_heights =
+            self.block.pred_epochs.get_start_height_of_epoch(epoch).zip(
+                self.block
+                    .pred_epochs
+                    .get_start_height_of_epoch(epoch.next()),
+            );
+        if let Some((start_height, end_height)) = pruned_heights {
+            for raw_height in start_height.0..end_height.0 {
+                self.db
+                    .prune_subspace_diffs(batch, BlockHeight(raw_height))?;
+            }
         }
         Ok(())
     }
@@ -1217,6 +1357,47 @@ where
                 .expect("Failed hash conversion")
         }))
     }
+
+    /// Iterate the replay protection storage of all the committed blocks
+    pub fn iter_all_replay_protection(
+        &self,
+    ) -> Box<dyn Iterator<Item = Hash> + '_> {
+        Box::new(self.db.iter_all_replay_protection().map(|(key, _, _)| {
+            key.rsplit_once('/')
+                .expect("Missing tx hash in storage key")
+                .1
+                .parse()
+                .expect("Failed hash conversion")
+        }))
+    }
+}
+
+/// The epoch data that is written to the merkle tree on a new epoch. Unlike
+/// the rest of the tree, these entries have no counterpart in the subspace.
+pub fn epoch_merkle_tree_entries(
+    next_epoch_min_start_height: BlockHeight,
+    next_epoch_min_start_time: DateTimeUtc,
+    epoch: Epoch,
+) -> Result<[(Key, Vec<u8>); 3]> {
+    let key_prefix: Key =
+        Address::Internal(InternalAddress::PoS).to_db_key().into();
+    let entry = |sub_key: &str, value: Vec<u8>| {
+        key_prefix
+            .push(&sub_key.to_string())
+            .map(|key| (key, value))
+            .map_err(Error::KeyError)
+    };
+    Ok([
+        entry(
+            "epoch_start_height",
+            types::encode(&next_epoch_min_start_height),
+        )?,
+        entry(
+            "epoch_start_time",
+            types::encode(&next_epoch_min_start_time),
+        )?,
+        entry("current_epoch", types::encode(&epoch))?,
+    ])
 }
 
 impl From<MerkleTreeError> for Error {
@@ -1276,6 +1457,9 @@ pub mod testing {
                 ethereum_height: None,
                 eth_events_queue: EthEventsQueue::default(),
                 storage_read_past_height_limit: Some(1000),
+                gas_costs: GasCostTable::default(),
+                block_events: Vec::new(),
+                events_prune_below: None,
             }
         }
     }
@@ -7,7 +7,8 @@ use crate::ledger::parameters::EpochDuration;
 use crate::ledger::storage::write_log::{self, WriteLog};
 use crate::ledger::storage::{DBIter, Storage, StorageHasher, DB};
 use crate::ledger::storage_api::{ResultExt, StorageRead, StorageWrite};
-use crate::ledger::{gas, parameters, storage_api};
+use crate::ledger::gas::GasCostTable;
+use crate::ledger::{parameters, storage_api};
 use crate::types::address::Address;
 use crate::types::hash::Hash;
 use crate::types::storage::{self, BlockHeight};
@@ -51,10 +52,9 @@ where
     /// Create a temp storage that can mutated in memory, but never committed to
     /// DB.
     pub fn new(storage: &'a Storage<D, H>) -> Self {
-        Self {
-            write_log: WriteLog::default(),
-            storage,
-        }
+        let mut write_log = WriteLog::default();
+        write_log.set_gas_costs(storage.gas_costs);
+        Self { write_log, storage }
     }
 
     /// Check if the given tx hash has already been processed
@@ -182,6 +182,12 @@ where
         Self { write_log, storage }
     }
 
+    /// Set the gas costs charged for storage accesses from now on
+    pub fn set_gas_costs(&mut self, gas_costs: GasCostTable) {
+        self.storage.gas_costs = gas_costs;
+        self.write_log.set_gas_costs(gas_costs);
+    }
+
     /// Commit the current transaction's write log to the block when it's
     /// accepted by all the triggered validity predicates. Starts a new
     /// transaction write log.
@@ -306,7 +312,7 @@ where
             storage_iter,
             write_log_iter,
         },
-        prefix.len() as u64 * gas::STORAGE_ACCESS_GAS_PER_BYTE,
+        prefix.len() as u64 * storage.gas_costs.storage_access_per_byte,
     )
 }
 
@@ -331,7 +337,7 @@ where
             storage_iter,
             write_log_iter,
         },
-        prefix.len() as u64 * gas::STORAGE_ACCESS_GAS_PER_BYTE,
+        prefix.len() as u64 * storage.gas_costs.storage_access_per_byte,
     )
 }
 
@@ -7,9 +7,7 @@ use itertools::Itertools;
 use thiserror::Error;
 
 use crate::ledger;
-use crate::ledger::gas::{
-    MEMORY_ACCESS_GAS_PER_BYTE, STORAGE_WRITE_GAS_PER_BYTE,
-};
+use crate::ledger::gas::GasCostTable;
 use crate::ledger::replay_protection::{
     get_replay_protection_all_subkey, get_replay_protection_last_subkey,
 };
@@ -101,11 +99,19 @@ pub struct WriteLog {
     /// only be populated through a dump of the `tx_write_log` and should be
     /// cleaned either when committing or dumping the `tx_write_log`
     tx_precommit_write_log: HashMap<storage::Key, StorageModification>,
+    /// The storage modifications of the inner transactions of a batch that
+    /// have already been accepted by validity predicates. They are the prior
+    /// state of the next inner transactions and are committed to the
+    /// `block_write_log` or dropped together with the `tx_write_log`, so that
+    /// a batch is applied atomically.
+    batch_write_log: HashMap<storage::Key, StorageModification>,
     /// The IBC events for the current transaction
     ibc_events: BTreeSet<IbcEvent>,
     /// Storage modifications for the replay protection storage, always
     /// committed regardless of the result of the transaction
     replay_protection: HashMap<Hash, ReProtStorageModification>,
+    /// The gas costs in force at the current block height
+    gas_costs: GasCostTable,
 }
 
 /// Write log prefix iterator
@@ -131,13 +137,20 @@ impl Default for WriteLog {
             block_write_log: HashMap::with_capacity(100_000),
             tx_write_log: HashMap::with_capacity(100),
             tx_precommit_write_log: HashMap::with_capacity(100),
+            batch_write_log: HashMap::with_capacity(100),
             ibc_events: BTreeSet::new(),
             replay_protection: HashMap::with_capacity(1_000),
+            gas_costs: GasCostTable::default(),
         }
     }
 }
 
 impl WriteLog {
+    /// Set the gas costs charged for the modifications from now on
+    pub fn set_gas_costs(&mut self, gas_costs: GasCostTable) {
+        self.gas_costs = gas_costs;
+    }
+
     /// Read a value at the given key and return the value and the gas cost,
     /// returns [`None`] if the key is not present in the write log
     pub fn read(
@@ -152,6 +165,10 @@ impl WriteLog {
                 // If not found, then try to read from tx precommit write log
                 self.tx_precommit_write_log.get(key)
             })
+            .or_else(|| {
+                // If not found, then try to read from batch write log
+                self.batch_write_log.get(key)
+            })
             .or_else(|| {
                 // if not found, then try to read from block write log
                 self.block_write_log.get(key)
@@ -169,9 +186,12 @@ impl WriteLog {
                         key.len() + value.len()
                     }
                 };
-                (Some(v), gas as u64 * MEMORY_ACCESS_GAS_PER_BYTE)
+                (Some(v), gas as u64 * self.gas_costs.memory_access_per_byte)
             }
-            None => (None, key.len() as u64 * MEMORY_ACCESS_GAS_PER_BYTE),
+            None => (
+                None,
+                key.len() as u64 * self.gas_costs.memory_access_per_byte,
+            ),
         }
     }
 
@@ -182,8 +202,12 @@ impl WriteLog {
         &self,
         key: &storage::Key,
     ) -> (Option<&StorageModification>, u64) {
-        // try to read from tx write log first
-        match self.block_write_log.get(key) {
+        // try to read from batch write log first
+        match self
+            .batch_write_log
+            .get(key)
+            .or_else(|| self.block_write_log.get(key))
+        {
             Some(v) => {
                 let gas = match v {
                     StorageModification::Write { ref value } => {
@@ -197,9 +221,12 @@ impl WriteLog {
                         key.len() + value.len()
                     }
                 };
-                (Some(v), gas as u64 * MEMORY_ACCESS_GAS_PER_BYTE)
+                (Some(v), gas as u64 * self.gas_costs.memory_access_per_byte)
             }
-            None => (None, key.len() as u64 * MEMORY_ACCESS_GAS_PER_BYTE),
+            None => (
+                None,
+                key.len() as u64 * self.gas_costs.memory_access_per_byte,
+            ),
         }
     }
 
@@ -235,7 +262,10 @@ impl WriteLog {
             // the previous value exists on the storage
             None => len as i64,
         };
-        Ok((gas as u64 * STORAGE_WRITE_GAS_PER_BYTE, size_diff))
+        Ok((
+            gas as u64 * self.gas_costs.storage_write_per_byte,
+            size_diff,
+        ))
     }
 
     /// Write a key and a value.
@@ -302,7 +332,10 @@ impl WriteLog {
         };
         // Temp writes are not propagated to db so just charge the cost of
         // accessing storage
-        Ok((gas as u64 * MEMORY_ACCESS_GAS_PER_BYTE, size_diff))
+        Ok((
+            gas as u64 * self.gas_costs.memory_access_per_byte,
+            size_diff,
+        ))
     }
 
     /// Delete a key and its value, and return the gas cost and the size
@@ -330,7 +363,10 @@ impl WriteLog {
             None => 0,
         };
         let gas = key.len() + size_diff as usize;
-        Ok((gas as u64 * STORAGE_WRITE_GAS_PER_BYTE, -size_diff))
+        Ok((
+            gas as u64 * self.gas_costs.storage_write_per_byte,
+            -size_diff,
+        ))
     }
 
     /// Delete a key and its value.
@@ -370,7 +406,7 @@ impl WriteLog {
             address_gen.generate_address("TODO more randomness".as_bytes());
         let key = storage::Key::validity_predicate(&addr);
         let gas = (key.len() + vp_code_hash.len()) as u64
-            * STORAGE_WRITE_GAS_PER_BYTE;
+            * self.gas_costs.storage_write_per_byte;
         self.tx_write_log
             .insert(key, StorageModification::InitAccount { vp_code_hash });
         (addr, gas)
@@ -383,7 +419,7 @@ impl WriteLog {
             .iter()
             .fold(0, |acc, (k, v)| acc + k.len() + v.len());
         self.ibc_events.insert(event);
-        len as u64 * MEMORY_ACCESS_GAS_PER_BYTE
+        len as u64 * self.gas_costs.memory_access_per_byte
     }
 
     /// Get the storage keys changed and accounts keys initialized in the
@@ -405,6 +441,26 @@ impl WriteLog {
             .collect()
     }
 
+    /// Get the storage keys changed in the current block with their new
+    /// values, or `None` for the deleted keys. Temporary writes are not
+    /// included as they never reach the storage.
+    pub fn get_block_changes(&self) -> BTreeMap<storage::Key, Option<Vec<u8>>> {
+        self.block_write_log
+            .iter()
+            .filter_map(|(key, modification)| {
+                let value = match modification {
+                    StorageModification::Write { value } => Some(value.clone()),
+                    StorageModification::Delete => None,
+                    StorageModification::InitAccount { vp_code_hash } => {
+                        Some(vp_code_hash.0.to_vec())
+                    }
+                    StorageModification::Temp { .. } => return None,
+                };
+                Some((key.clone(), value))
+            })
+            .collect()
+    }
+
     /// Get the storage keys changed in the current transaction (left) and
     /// the addresses of accounts initialized in the current transaction
     /// (right). The first vector excludes keys of validity predicates of
@@ -461,6 +517,18 @@ impl WriteLog {
         self.tx_precommit_write_log.extend(tx_log)
     }
 
+    /// Add the entire content of the tx write log of an inner transaction of a
+    /// batch, accepted by the validity predicates, to the batch write log. The
+    /// tx log gets reset in the process.
+    pub fn commit_batch_tx(&mut self) {
+        let tx_log = std::mem::replace(
+            &mut self.tx_write_log,
+            HashMap::with_capacity(100),
+        );
+
+        self.batch_write_log.extend(tx_log)
+    }
+
     /// Commit the current transaction's write log and precommit log to the
     /// block when it's accepted by all the triggered validity predicates.
     /// Starts a new transaction write log.
@@ -468,7 +536,11 @@ impl WriteLog {
         // First precommit everything
         self.precommit_tx();
 
-        // Then commit to block
+        // Then commit to block, starting from the older batch changes
+        let batch_write_log = std::mem::replace(
+            &mut self.batch_write_log,
+            HashMap::with_capacity(100),
+        );
         self.tx_precommit_write_log.retain(|_, v| {
             !matches!(v, StorageModification::Temp { value: _ })
         });
@@ -477,6 +549,10 @@ impl WriteLog {
             HashMap::with_capacity(100),
         );
 
+        self.block_write_log
+            .extend(batch_write_log.into_iter().filter(|(_, v)| {
+                !matches!(v, StorageModification::Temp { value: _ })
+            }));
         self.block_write_log.extend(tx_precommit_write_log);
         self.take_ibc_events();
     }
@@ -485,6 +561,7 @@ impl WriteLog {
     /// declined by any of the triggered validity predicates. Starts a new
     /// transaction write log.
     pub fn drop_tx(&mut self) {
+        self.batch_write_log.clear();
         self.tx_precommit_write_log.clear();
         self.tx_write_log.clear();
     }
@@ -641,6 +718,11 @@ impl WriteLog {
                 matches.insert(key.to_string(), modification.clone());
             }
         }
+        for (key, modification) in &self.batch_write_log {
+            if key.split_prefix(prefix).is_some() {
+                matches.insert(key.to_string(), modification.clone());
+            }
+        }
 
         let iter = matches.into_iter();
         PrefixIter { iter }
@@ -656,6 +738,11 @@ impl WriteLog {
                 matches.insert(key.to_string(), modification.clone());
             }
         }
+        for (key, modification) in &self.batch_write_log {
+            if key.split_prefix(prefix).is_some() {
+                matches.insert(key.to_string(), modification.clone());
+            }
+        }
         for (key, modification) in &self.tx_write_log {
             if key.split_prefix(prefix).is_some() {
                 matches.insert(key.to_string(), modification.clone());
@@ -738,6 +825,9 @@ mod tests {
     use proptest::prelude::*;
 
     use super::*;
+    use crate::ledger::gas::{
+        MEMORY_ACCESS_GAS_PER_BYTE, STORAGE_WRITE_GAS_PER_BYTE,
+    };
     use crate::types::hash::Hash;
     use crate::types::{address, storage};
 
@@ -959,6 +1049,45 @@ mod tests {
         assert_eq!(value, None);
     }
 
+    #[test]
+    fn test_commit_batch() {
+        let mut write_log = WriteLog::default();
+
+        let key1 =
+            storage::Key::parse("key1").expect("cannot parse the key string");
+        let key2 =
+            storage::Key::parse("key2").expect("cannot parse the key string");
+        let val1 = "val1".as_bytes().to_vec();
+        let val2 = "val2".as_bytes().to_vec();
+
+        // the changes of an accepted inner tx are the prior state of the next
+        write_log.write(&key1, val1.clone()).unwrap();
+        write_log.commit_batch_tx();
+        assert!(write_log.get_keys().is_empty());
+        assert_matches!(
+            write_log.read_pre(&key1).0,
+            Some(StorageModification::Write { value }) if *value == val1
+        );
+        write_log.write(&key2, val2.clone()).unwrap();
+        assert_eq!(write_log.get_keys(), BTreeSet::from([key2.clone()]));
+
+        // dropping the tx drops the whole batch
+        write_log.drop_tx();
+        assert!(write_log.read(&key1).0.is_none());
+        assert!(write_log.read(&key2).0.is_none());
+
+        // committing the tx commits the whole batch to the block
+        write_log.write(&key1, val1.clone()).unwrap();
+        write_log.commit_batch_tx();
+        write_log.write(&key1, val2.clone()).unwrap();
+        write_log.write(&key2, val2.clone()).unwrap();
+        write_log.commit_tx();
+        assert_eq!(
+            write_log.get_block_changes(),
+            BTreeMap::from([(key1, Some(val2.clone())), (key2, Some(val2))])
+        );
+    }
+
     #[test]
     fn test_replay_protection_commit() {
         let mut storage =
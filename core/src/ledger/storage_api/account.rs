@@ -1,10 +1,16 @@
 //! Cryptographic signature keys storage API
 
+use std::collections::BTreeMap;
+
 use super::*;
-use crate::types::account::AccountPublicKeysMap;
+use crate::types::account::{
+    AccountPermission, AccountPublicKeysMap, EpochSpending, KeyPolicy,
+};
 use crate::types::address::Address;
 use crate::types::key::*;
 use crate::types::storage::Key;
+use crate::types::token::Amount;
+use crate::types::vesting::{vesting_key, Vesting};
 
 /// Init the subspace of a new account
 pub fn init_account_storage<S>(
@@ -33,6 +39,48 @@ where
     storage.read(&threshold_key)
 }
 
+/// Write the vesting schedule of the tokens locked in a new account
+pub fn init_vesting<S>(
+    storage: &mut S,
+    owner: &Address,
+    vesting: &Vesting,
+) -> Result<()>
+where
+    S: StorageWrite + StorageRead,
+{
+    storage.write(&vesting_key(owner), vesting)
+}
+
+/// Get the vesting schedule of the tokens locked in an account, if any
+pub fn vesting<S>(storage: &S, owner: &Address) -> Result<Option<Vesting>>
+where
+    S: StorageRead,
+{
+    storage.read(&vesting_key(owner))
+}
+
+/// Get the amount of a token locked in an account by its vesting schedule at
+/// the time of the current block. Without a block header, the whole vesting
+/// amount is locked.
+pub fn locked_amount<S>(
+    storage: &S,
+    owner: &Address,
+    token: &Address,
+) -> Result<Amount>
+where
+    S: StorageRead,
+{
+    let vesting = match vesting(storage, owner)? {
+        Some(vesting) if &vesting.token == token => vesting,
+        _ => return Ok(Amount::zero()),
+    };
+    let height = storage.get_block_height()?;
+    Ok(match storage.get_block_header(height)? {
+        Some(header) => vesting.locked_at(header.time),
+        None => vesting.amount,
+    })
+}
+
 /// Get the public keys associated with an account
 pub fn public_keys<S>(
     storage: &S,
@@ -105,3 +153,139 @@ where
     }
     Ok(())
 }
+
+/// Get the policies of the public keys of an account
+pub fn key_policies<S>(
+    storage: &S,
+    owner: &Address,
+) -> Result<BTreeMap<common::PublicKey, KeyPolicy>>
+where
+    S: StorageRead,
+{
+    key_policies_handle(owner).iter(storage)?.collect()
+}
+
+/// Replace the policies of the public keys of an account
+pub fn set_key_policies<S>(
+    storage: &mut S,
+    owner: &Address,
+    policies: &BTreeMap<common::PublicKey, KeyPolicy>,
+) -> Result<()>
+where
+    S: StorageWrite + StorageRead,
+{
+    let handle = key_policies_handle(owner);
+    let public_keys = handle
+        .iter(storage)?
+        .map(|res| res.map(|(public_key, _)| public_key))
+        .collect::<Result<Vec<_>>>()?;
+    for public_key in public_keys {
+        handle.remove(storage, &public_key)?;
+    }
+    for (public_key, policy) in policies {
+        handle.insert(storage, public_key.clone(), policy.clone())?;
+    }
+    Ok(())
+}
+
+/// Get the public key index map of an account, restricted to the keys that
+/// may authorize the given kind of changes. When `spending` is given, the
+/// keys must also allow a debit that brings the amount of the token debited
+/// in the current epoch to the given amount.
+pub fn permitted_public_keys_index_map<S>(
+    storage: &S,
+    owner: &Address,
+    permission: AccountPermission,
+    spending: Option<(&Address, Amount)>,
+) -> Result<AccountPublicKeysMap>
+where
+    S: StorageRead,
+{
+    let mut public_keys_map = public_keys_index_map(storage, owner)?;
+    let policies = key_policies(storage, owner)?;
+    public_keys_map.retain(|public_key| match policies.get(public_key) {
+        Some(policy) => {
+            policy.allows(permission)
+                && spending.map_or(true, |(token, spent)| {
+                    policy.within_spending_limit(token, spent)
+                })
+        }
+        None => true,
+    });
+    Ok(public_keys_map)
+}
+
+/// Check if as many public keys of an account as its threshold may authorize
+/// a debit of a token that brings the amount of it debited in the current
+/// epoch to `spent`
+pub fn can_authorize_spending<S>(
+    storage: &S,
+    owner: &Address,
+    token: &Address,
+    spent: Amount,
+) -> Result<bool>
+where
+    S: StorageRead,
+{
+    let permitted_keys = permitted_public_keys_index_map(
+        storage,
+        owner,
+        AccountPermission::Transfer,
+        Some((token, spent)),
+    )?;
+    let threshold = threshold(storage, owner)?.unwrap_or(1);
+    Ok(permitted_keys.pk_to_idx.len() >= usize::from(threshold))
+}
+
+/// Check if any public key of an account has a spending limit on the token
+pub fn has_spending_limit<S>(
+    storage: &S,
+    owner: &Address,
+    token: &Address,
+) -> Result<bool>
+where
+    S: StorageRead,
+{
+    Ok(key_policies(storage, owner)?
+        .values()
+        .any(|policy| policy.spending_limits.contains_key(token)))
+}
+
+/// Get the amount of a token debited from an account in the current epoch
+pub fn spent_in_current_epoch<S>(
+    storage: &S,
+    owner: &Address,
+    token: &Address,
+) -> Result<Amount>
+where
+    S: StorageRead,
+{
+    let epoch = storage.get_block_epoch()?;
+    let spending: Option<EpochSpending> =
+        storage.read(&spending_key(owner, token))?;
+    Ok(spending.unwrap_or_default().spent_in(epoch))
+}
+
+/// Record a debit of a token from an account, if any of its public keys has
+/// a spending limit on the token
+pub fn record_spending<S>(
+    storage: &mut S,
+    owner: &Address,
+    token: &Address,
+    amount: Amount,
+) -> Result<()>
+where
+    S: StorageWrite + StorageRead,
+{
+    if amount.is_zero() || !has_spending_limit(storage, owner, token)? {
+        return Ok(());
+    }
+    let spent = spent_in_current_epoch(storage, owner, token)?;
+    let spending = EpochSpending {
+        epoch: storage.get_block_epoch()?,
+        amount: spent
+            .checked_add(amount)
+            .ok_or_err_msg("Overflow in the amount spent in the epoch")?,
+    };
+    storage.write(&spending_key(owner, token), spending)
+}
@@ -0,0 +1,72 @@
+//! Fee grants
+
+use crate::ledger::fee_grant::storage::keys as fee_grant_keys;
+use crate::ledger::storage_api::{self, StorageRead, StorageWrite};
+use crate::types::address::Address;
+use crate::types::transaction::fee_grant::{FeeAllowance, GrantFeeAllowance};
+
+/// A fee allowance grant transaction. The spend limit of the granted token
+/// is set, keeping the ones of the other tokens, and the expiration of the
+/// allowance is replaced.
+pub fn grant_fee_allowance<S>(
+    storage: &mut S,
+    data: GrantFeeAllowance,
+) -> storage_api::Result<()>
+where
+    S: StorageRead + StorageWrite,
+{
+    let key = fee_grant_keys::get_allowance_key(&data.granter, &data.grantee);
+    let mut allowance: FeeAllowance = storage.read(&key)?.unwrap_or_default();
+    allowance.spend_limit.insert(data.token, data.spend_limit);
+    allowance.expiration = data.expiration;
+    storage.write(&key, allowance)
+}
+
+/// A fee allowance revocation transaction
+pub fn revoke_fee_allowance<S>(
+    storage: &mut S,
+    granter: &Address,
+    grantee: &Address,
+) -> storage_api::Result<()>
+where
+    S: StorageRead + StorageWrite,
+{
+    let key = fee_grant_keys::get_allowance_key(granter, grantee);
+    if !storage.has_key(&key)? {
+        return Err(storage_api::Error::new_const(
+            "No fee allowance was granted to the grantee",
+        ));
+    }
+    storage.delete(&key)
+}
+
+/// Read the fee allowance granted by a granter to a grantee
+pub fn read_fee_allowance<S>(
+    storage: &S,
+    granter: &Address,
+    grantee: &Address,
+) -> storage_api::Result<Option<FeeAllowance>>
+where
+    S: StorageRead,
+{
+    storage.read(&fee_grant_keys::get_allowance_key(granter, grantee))
+}
+
+/// Get the fee allowances granted by the given granter with their grantees
+pub fn fee_allowances_of<S>(
+    storage: &S,
+    granter: &Address,
+) -> storage_api::Result<Vec<(Address, FeeAllowance)>>
+where
+    S: StorageRead,
+{
+    let prefix = fee_grant_keys::get_allowances_prefix(granter);
+    let mut allowances = vec![];
+    for entry in storage_api::iter_prefix::<FeeAllowance>(storage, &prefix)? {
+        let (key, allowance) = entry?;
+        if let Some((_, grantee)) = fee_grant_keys::is_allowance_key(&key) {
+            allowances.push((grantee.clone(), allowance));
+        }
+    }
+    Ok(allowances)
+}
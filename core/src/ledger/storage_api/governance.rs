@@ -1,6 +1,6 @@
 //! Governance
 
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, BTreeSet};
 
 use borsh::BorshDeserialize;
 
@@ -8,16 +8,19 @@ use super::token;
 use crate::ledger::governance::parameters::GovernanceParameters;
 use crate::ledger::governance::storage::keys as governance_keys;
 use crate::ledger::governance::storage::proposal::{
-    ProposalType, StorageProposal,
+    ProposalKind, ProposalType, StorageProposal, UpgradePlan,
+};
+use crate::ledger::governance::storage::vote::{
+    StorageProposalVote, VoteDelegation,
 };
-use crate::ledger::governance::storage::vote::StorageProposalVote;
 use crate::ledger::governance::utils::Vote;
 use crate::ledger::governance::ADDRESS as governance_address;
 use crate::ledger::storage_api::{self, StorageRead, StorageWrite};
 use crate::types::address::Address;
-use crate::types::storage::Epoch;
+use crate::types::dec::Dec;
+use crate::types::storage::{BlockHeight, Epoch};
 use crate::types::transaction::governance::{
-    InitProposalData, VoteProposalData,
+    DelegateVotes, DepositProposalData, InitProposalData, VoteProposalData,
 };
 
 /// A proposal creation transaction.
@@ -86,6 +89,10 @@ where
     let funds_key = governance_keys::get_funds_key(proposal_id);
     storage.write(&funds_key, min_proposal_funds)?;
 
+    let deposit_key =
+        governance_keys::get_deposit_key(proposal_id, &data.author);
+    storage.write(&deposit_key, min_proposal_funds)?;
+
     // this key must always be written for each proposal
     let committing_proposals_key =
         governance_keys::get_committing_proposals_key(
@@ -119,7 +126,288 @@ where
         );
         storage.write(&vote_key, data.vote.clone())?;
     }
-    Ok(())
+    // The vote is also recorded regardless of the bonds of the voter, to be
+    // followed by the addresses delegating their votes to it
+    let delegate_vote_key =
+        governance_keys::get_delegate_vote_key(data.id, &data.voter);
+    storage.write(&delegate_vote_key, data.vote)
+}
+
+/// A proposal deposit transaction. The deposited amount is added to the
+/// funds of the proposal and recorded for the depositor, to be refunded
+/// unless the proposal gets vetoed.
+pub fn deposit_proposal<S>(
+    storage: &mut S,
+    data: DepositProposalData,
+) -> storage_api::Result<()>
+where
+    S: StorageRead + StorageWrite,
+{
+    let funds_key = governance_keys::get_funds_key(data.id);
+    let funds: token::Amount = storage.read(&funds_key)?.ok_or_else(|| {
+        storage_api::Error::new(format!("Proposal {} doesn't exist", data.id))
+    })?;
+    storage.write(&funds_key, funds + data.amount)?;
+
+    let deposit_key =
+        governance_keys::get_deposit_key(data.id, &data.depositor);
+    let deposit: token::Amount =
+        storage.read(&deposit_key)?.unwrap_or_default();
+    storage.write(&deposit_key, deposit + data.amount)?;
+
+    token::transfer(
+        storage,
+        &storage.get_native_token()?,
+        &data.depositor,
+        &governance_address,
+        data.amount,
+    )
+}
+
+/// Get the deposits made on a proposal by the depositors
+pub fn get_proposal_deposits<S>(
+    storage: &S,
+    proposal_id: u64,
+) -> storage_api::Result<BTreeMap<Address, token::Amount>>
+where
+    S: StorageRead,
+{
+    let prefix = governance_keys::get_deposits_prefix(proposal_id);
+    let mut deposits = BTreeMap::new();
+    for entry in storage_api::iter_prefix::<token::Amount>(storage, &prefix)? {
+        let (key, deposit) = entry?;
+        if let Some((_, depositor)) = governance_keys::is_deposit_key(&key) {
+            deposits.insert(depositor.clone(), deposit);
+        }
+    }
+    Ok(deposits)
+}
+
+/// The maximum number of delegations followed from an address to the vote
+/// of one of its direct or indirect delegates
+pub const MAX_VOTE_DELEGATION_DEPTH: usize = 8;
+
+/// A vote delegation transaction. The new delegate is only followed on the
+/// proposals whose voting period starts after the current epoch.
+pub fn delegate_votes<S>(
+    storage: &mut S,
+    data: DelegateVotes,
+) -> storage_api::Result<()>
+where
+    S: StorageRead + StorageWrite,
+{
+    let key = governance_keys::get_vote_delegation_key(
+        &data.delegator,
+        data.proposal_kind,
+    );
+    let pre: Option<VoteDelegation> = storage.read(&key)?;
+    let post = update_vote_delegation(
+        storage,
+        pre.clone().unwrap_or_default(),
+        data.delegate,
+    )?;
+
+    // Keep the index of the delegations by delegate in sync
+    let post_delegates: BTreeSet<Address> = post
+        .iter()
+        .flat_map(VoteDelegation::delegates)
+        .cloned()
+        .collect();
+    for delegate in pre.iter().flat_map(VoteDelegation::delegates) {
+        if !post_delegates.contains(delegate) {
+            storage.delete(&governance_keys::get_vote_delegator_key(
+                delegate,
+                &data.delegator,
+                data.proposal_kind,
+            ))?;
+        }
+    }
+    for delegate in &post_delegates {
+        storage.write(
+            &governance_keys::get_vote_delegator_key(
+                delegate,
+                &data.delegator,
+                data.proposal_kind,
+            ),
+            (),
+        )?;
+    }
+
+    match post {
+        Some(post) => storage.write(&key, post),
+        None => storage.delete(&key),
+    }
+}
+
+/// Appoint a new delegate in a vote delegation, or revoke it, from the next
+/// epoch. The delegates that can't be followed anymore on the proposals still
+/// to be tallied, whose voting period started at most the maximum proposal
+/// period ago, are dropped.
+pub fn update_vote_delegation<S>(
+    storage: &S,
+    delegation: VoteDelegation,
+    delegate: Option<Address>,
+) -> storage_api::Result<Option<VoteDelegation>>
+where
+    S: StorageRead,
+{
+    let current_epoch = storage.get_block_epoch()?;
+    let max_proposal_period = get_max_proposal_period(storage)?;
+    let oldest_epoch = current_epoch.sub_or_default(max_proposal_period.into());
+    Ok(delegation.update(delegate, current_epoch, oldest_epoch))
+}
+
+/// Read the delegate voting on behalf of the given address on the proposals
+/// of the given kind whose voting period starts at the given epoch. A
+/// delegation restricted to the proposal kind takes precedence over one for
+/// all the kinds.
+pub fn get_vote_delegate<S>(
+    storage: &S,
+    delegator: &Address,
+    kind: ProposalKind,
+    epoch: Epoch,
+) -> storage_api::Result<Option<Address>>
+where
+    S: StorageRead,
+{
+    for kind in [Some(kind), None] {
+        let key = governance_keys::get_vote_delegation_key(delegator, kind);
+        if let Some(delegation) = storage.read::<VoteDelegation>(&key)? {
+            if let Some(delegate) = delegation.delegate_at(epoch) {
+                return Ok(Some(delegate.clone()));
+            }
+        }
+    }
+    Ok(None)
+}
+
+/// Get the delegates of the given address at the given epoch, by the proposal
+/// kind they are restricted to or `None` for the delegate for all the kinds
+pub fn get_vote_delegations<S>(
+    storage: &S,
+    delegator: &Address,
+    epoch: Epoch,
+) -> storage_api::Result<BTreeMap<Option<ProposalKind>, Address>>
+where
+    S: StorageRead,
+{
+    let prefix = governance_keys::get_vote_delegations_prefix(delegator);
+    let mut delegations = BTreeMap::new();
+    for entry in storage_api::iter_prefix::<VoteDelegation>(storage, &prefix)? {
+        let (key, delegation) = entry?;
+        if let (Some((_, kind)), Some(delegate)) = (
+            governance_keys::is_vote_delegation_key(&key),
+            delegation.delegate_at(epoch),
+        ) {
+            delegations.insert(kind, delegate.clone());
+        }
+    }
+    Ok(delegations)
+}
+
+/// Get the addresses delegating their votes to the given delegate at the
+/// given epoch, with the proposal kinds of the delegations
+pub fn get_vote_delegators<S>(
+    storage: &S,
+    delegate: &Address,
+    epoch: Epoch,
+) -> storage_api::Result<BTreeMap<Address, BTreeSet<Option<ProposalKind>>>>
+where
+    S: StorageRead,
+{
+    let mut delegators: BTreeMap<_, BTreeSet<_>> = BTreeMap::new();
+    for (delegator, kind) in get_indexed_vote_delegators(storage, delegate)? {
+        let key = governance_keys::get_vote_delegation_key(&delegator, kind);
+        let delegation: Option<VoteDelegation> = storage.read(&key)?;
+        if delegation.as_ref().and_then(|d| d.delegate_at(epoch))
+            == Some(delegate)
+        {
+            delegators.entry(delegator).or_default().insert(kind);
+        }
+    }
+    Ok(delegators)
+}
+
+/// Get the vote delegations that have, or had, the given address among their
+/// delegates, from the index of the delegations by delegate
+fn get_indexed_vote_delegators<S>(
+    storage: &S,
+    delegate: &Address,
+) -> storage_api::Result<BTreeSet<(Address, Option<ProposalKind>)>>
+where
+    S: StorageRead,
+{
+    let prefix = governance_keys::get_vote_delegators_prefix(delegate);
+    let mut delegators = BTreeSet::new();
+    for entry in storage_api::iter_prefix_bytes(storage, &prefix)? {
+        let (key, _) = entry?;
+        if let Some((_, delegator, kind)) =
+            governance_keys::is_vote_delegator_key(&key)
+        {
+            delegators.insert((delegator.clone(), kind));
+        }
+    }
+    Ok(delegators)
+}
+
+/// Get the votes followed on a proposal by the addresses that didn't vote
+/// themselves, by delegator, with the delegate that cast the vote. The
+/// delegations are the ones in force at the given epoch, the start of the
+/// voting period of the proposal. The vote of a delegate that didn't vote
+/// either is looked up by following its own delegation, up to
+/// [`MAX_VOTE_DELEGATION_DEPTH`] delegations away from the voter.
+pub fn get_delegated_votes<S>(
+    storage: &S,
+    proposal_id: u64,
+    kind: ProposalKind,
+    epoch: Epoch,
+) -> storage_api::Result<BTreeMap<Address, (Address, StorageProposalVote)>>
+where
+    S: StorageRead,
+{
+    let prefix = governance_keys::get_delegate_votes_prefix(proposal_id);
+    let mut voters = BTreeSet::new();
+    // The addresses whose delegators are looked up next, with the voter and
+    // the vote they follow
+    let mut followed = Vec::new();
+    for entry in
+        storage_api::iter_prefix::<StorageProposalVote>(storage, &prefix)?
+    {
+        let (key, vote) = entry?;
+        if let Some(voter) = governance_keys::is_delegate_vote_key(&key) {
+            voters.insert(voter.clone());
+            followed.push((voter.clone(), voter.clone(), vote));
+        }
+    }
+
+    let mut delegated_votes = BTreeMap::new();
+    for _ in 0..MAX_VOTE_DELEGATION_DEPTH {
+        let mut next = Vec::new();
+        for (delegate, voter, vote) in followed {
+            let delegators = get_indexed_vote_delegators(storage, &delegate)?;
+            for (delegator, _) in delegators {
+                if voters.contains(&delegator)
+                    || delegated_votes.contains_key(&delegator)
+                {
+                    continue;
+                }
+                if get_vote_delegate(storage, &delegator, kind, epoch)?.as_ref()
+                    == Some(&delegate)
+                {
+                    delegated_votes.insert(
+                        delegator.clone(),
+                        (voter.clone(), vote.clone()),
+                    );
+                    next.push((delegator, voter.clone(), vote.clone()));
+                }
+            }
+        }
+        if next.is_empty() {
+            break;
+        }
+        followed = next;
+    }
+    Ok(delegated_votes)
 }
 
 /// Read a proposal by id from storage
@@ -247,6 +535,10 @@ where
 
     let max_proposal_period: u64 = get_max_proposal_period(storage)?;
 
+    let key = governance_keys::get_veto_threshold_key();
+    let veto_threshold: Dec =
+        storage.read(&key)?.expect("Parameter should be definied.");
+
     Ok(GovernanceParameters {
         min_proposal_fund,
         max_proposal_code_size,
@@ -254,6 +546,7 @@ where
         max_proposal_period,
         max_proposal_content_size,
         min_proposal_grace_epochs,
+        veto_threshold,
     })
 }
 
@@ -267,3 +560,350 @@ where
         storage.read(&key)?.expect("Parameter should be defined.");
     Ok(max_proposal_period)
 }
+
+/// Schedule the software upgrade of an accepted proposal
+pub fn schedule_upgrade<S>(
+    storage: &mut S,
+    proposal_id: u64,
+    plan: &UpgradePlan,
+) -> storage_api::Result<()>
+where
+    S: StorageRead + StorageWrite,
+{
+    let key = governance_keys::get_upgrade_key(proposal_id);
+    storage.write(&key, plan)
+}
+
+/// Remove the software upgrade scheduled by a proposal
+pub fn remove_upgrade<S>(
+    storage: &mut S,
+    proposal_id: u64,
+) -> storage_api::Result<()>
+where
+    S: StorageRead + StorageWrite,
+{
+    let key = governance_keys::get_upgrade_key(proposal_id);
+    storage.delete(&key)
+}
+
+/// Get the software upgrades scheduled by governance, by the id of the
+/// proposal that scheduled them
+pub fn get_upgrade_plans<S>(
+    storage: &S,
+) -> storage_api::Result<BTreeMap<u64, UpgradePlan>>
+where
+    S: StorageRead,
+{
+    let prefix = governance_keys::get_upgrades_prefix();
+    let mut plans = BTreeMap::new();
+    for entry in storage_api::iter_prefix::<UpgradePlan>(storage, &prefix)? {
+        let (key, plan) = entry?;
+        if let Some(proposal_id) = governance_keys::is_upgrade_key(&key) {
+            plans.insert(proposal_id, plan);
+        }
+    }
+    Ok(plans)
+}
+
+/// Get the scheduled software upgrades that must be applied from a block
+/// after the given height
+pub fn get_pending_upgrades<S>(
+    storage: &S,
+    height: BlockHeight,
+) -> storage_api::Result<BTreeMap<u64, UpgradePlan>>
+where
+    S: StorageRead,
+{
+    let mut plans = get_upgrade_plans(storage)?;
+    plans.retain(|_, plan| plan.height > height);
+    Ok(plans)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ledger::storage::testing::TestWlStorage;
+    use crate::types::address::testing::address_from_simple_seed;
+
+    fn delegate(
+        storage: &mut TestWlStorage,
+        delegator: &Address,
+        delegate: &Address,
+        proposal_kind: Option<ProposalKind>,
+    ) {
+        delegate_votes(
+            storage,
+            DelegateVotes {
+                delegator: delegator.clone(),
+                delegate: Some(delegate.clone()),
+                proposal_kind,
+            },
+        )
+        .unwrap();
+    }
+
+    fn vote(
+        storage: &mut TestWlStorage,
+        voter: &Address,
+        vote: StorageProposalVote,
+    ) {
+        vote_proposal(
+            storage,
+            VoteProposalData {
+                id: 0,
+                vote,
+                voter: voter.clone(),
+                delegations: vec![],
+            },
+        )
+        .unwrap();
+    }
+
+    /// Test that the votes are followed along the delegation chains in force
+    /// at the start of the voting period
+    #[test]
+    fn test_delegated_votes() {
+        let mut storage = TestWlStorage::default();
+        GovernanceParameters::default()
+            .init_storage(&mut storage)
+            .unwrap();
+        let [a, b, c, d, e, f, g] =
+            [0, 1, 2, 3, 4, 5, 6].map(address_from_simple_seed);
+
+        // a -> b -> c, only c votes
+        delegate(&mut storage, &a, &b, None);
+        delegate(&mut storage, &b, &c, None);
+        vote(&mut storage, &c, StorageProposalVote::Nay);
+        // d <-> e, none of them votes
+        delegate(&mut storage, &d, &e, None);
+        delegate(&mut storage, &e, &d, None);
+        // f delegates the PGF payment proposals to g, that votes
+        delegate(&mut storage, &f, &g, Some(ProposalKind::PGFPayment));
+        delegate(&mut storage, &f, &c, None);
+        vote(&mut storage, &g, StorageProposalVote::Abstain);
+
+        let votes =
+            get_delegated_votes(&storage, 0, ProposalKind::Default, Epoch(1))
+                .unwrap();
+        assert_eq!(
+            votes,
+            BTreeMap::from([
+                (a.clone(), (c.clone(), StorageProposalVote::Nay)),
+                (b.clone(), (c.clone(), StorageProposalVote::Nay)),
+                (f.clone(), (c.clone(), StorageProposalVote::Nay)),
+            ])
+        );
+        let votes = get_delegated_votes(
+            &storage,
+            0,
+            ProposalKind::PGFPayment,
+            Epoch(1),
+        )
+        .unwrap();
+        assert_eq!(
+            votes.get(&f),
+            Some(&(g.clone(), StorageProposalVote::Abstain))
+        );
+
+        // An own vote takes precedence over the delegation
+        vote(&mut storage, &b, StorageProposalVote::Abstain);
+        let votes =
+            get_delegated_votes(&storage, 0, ProposalKind::Default, Epoch(1))
+                .unwrap();
+        assert_eq!(
+            votes.get(&a),
+            Some(&(b.clone(), StorageProposalVote::Abstain))
+        );
+        assert_eq!(votes.get(&b), None);
+
+        assert_eq!(
+            get_vote_delegators(&storage, &c, Epoch(1)).unwrap(),
+            BTreeMap::from([
+                (b.clone(), BTreeSet::from([None])),
+                (f.clone(), BTreeSet::from([None])),
+            ])
+        );
+        delegate_votes(
+            &mut storage,
+            DelegateVotes {
+                delegator: f.clone(),
+                delegate: None,
+                proposal_kind: None,
+            },
+        )
+        .unwrap();
+        assert_eq!(
+            get_vote_delegations(&storage, &f, Epoch(1)).unwrap(),
+            BTreeMap::from([(Some(ProposalKind::PGFPayment), g)])
+        );
+    }
+
+    /// Test that a vote is only followed up to the maximum delegation depth
+    #[test]
+    fn test_delegated_votes_max_depth() {
+        let mut storage = TestWlStorage::default();
+        GovernanceParameters::default()
+            .init_storage(&mut storage)
+            .unwrap();
+        let addresses: Vec<_> = (0..=MAX_VOTE_DELEGATION_DEPTH as u64 + 1)
+            .map(address_from_simple_seed)
+            .collect();
+        for pair in addresses.windows(2) {
+            delegate(&mut storage, &pair[0], &pair[1], None);
+        }
+        let voter = addresses.last().unwrap();
+        vote(&mut storage, voter, StorageProposalVote::Yay);
+
+        let votes =
+            get_delegated_votes(&storage, 0, ProposalKind::Default, Epoch(1))
+                .unwrap();
+        assert_eq!(votes.len(), MAX_VOTE_DELEGATION_DEPTH);
+        assert!(!votes.contains_key(&addresses[0]));
+        assert_eq!(
+            votes.get(&addresses[1]),
+            Some(&(voter.clone(), StorageProposalVote::Yay))
+        );
+    }
+
+    /// Test that a delegation changed during a voting period isn't followed on
+    /// the proposal, and that the replaced delegates are only forgotten once
+    /// no proposal to tally can follow them anymore
+    #[test]
+    fn test_vote_delegation_changes() {
+        let mut storage = TestWlStorage::default();
+        let params = GovernanceParameters::default();
+        params.init_storage(&mut storage).unwrap();
+        let [a, b, c] = [0, 1, 2].map(address_from_simple_seed);
+
+        delegate(&mut storage, &a, &b, None);
+        vote(&mut storage, &b, StorageProposalVote::Yay);
+        vote(&mut storage, &c, StorageProposalVote::Nay);
+
+        // The new delegate is in force from the next epoch
+        storage.storage.block.epoch = Epoch(2);
+        delegate(&mut storage, &a, &c, None);
+        let votes =
+            get_delegated_votes(&storage, 0, ProposalKind::Default, Epoch(1))
+                .unwrap();
+        assert_eq!(votes.get(&a), Some(&(b.clone(), StorageProposalVote::Yay)));
+        let votes =
+            get_delegated_votes(&storage, 0, ProposalKind::Default, Epoch(3))
+                .unwrap();
+        assert_eq!(votes.get(&a), Some(&(c.clone(), StorageProposalVote::Nay)));
+        assert!(
+            get_vote_delegators(&storage, &b, Epoch(3))
+                .unwrap()
+                .is_empty()
+        );
+        assert!(
+            get_indexed_vote_delegators(&storage, &b)
+                .unwrap()
+                .contains(&(a.clone(), None))
+        );
+
+        // The replaced delegate is dropped after the maximum proposal period
+        storage.storage.block.epoch = Epoch(params.max_proposal_period + 3);
+        delegate(&mut storage, &a, &c, None);
+        assert!(
+            get_indexed_vote_delegators(&storage, &b)
+                .unwrap()
+                .is_empty()
+        );
+        let key = governance_keys::get_vote_delegation_key(&a, None);
+        let delegation: VoteDelegation = storage.read(&key).unwrap().unwrap();
+        assert!(delegation.delegates().all(|delegate| delegate == &c));
+    }
+
+    #[test]
+    fn test_proposal_deposits() {
+        let mut storage = TestWlStorage::default();
+        let native_token = storage.storage.native_token.clone();
+        let [author, depositor] = [0, 1].map(address_from_simple_seed);
+        for owner in [&author, &depositor] {
+            token::credit_tokens(
+                &mut storage,
+                &native_token,
+                owner,
+                token::Amount::native_whole(100),
+            )
+            .unwrap();
+        }
+        storage
+            .write(
+                &governance_keys::get_funds_key(0),
+                token::Amount::native_whole(50),
+            )
+            .unwrap();
+        storage
+            .write(
+                &governance_keys::get_deposit_key(0, &author),
+                token::Amount::native_whole(50),
+            )
+            .unwrap();
+
+        let deposit = |storage: &mut TestWlStorage, id, depositor: &Address| {
+            deposit_proposal(
+                storage,
+                DepositProposalData {
+                    id,
+                    depositor: depositor.clone(),
+                    amount: token::Amount::native_whole(30),
+                },
+            )
+        };
+        deposit(&mut storage, 0, &depositor).unwrap();
+        deposit(&mut storage, 0, &depositor).unwrap();
+        deposit(&mut storage, 0, &author).unwrap();
+        // The proposal must exist
+        assert!(deposit(&mut storage, 1, &depositor).is_err());
+
+        let funds: token::Amount = storage
+            .read(&governance_keys::get_funds_key(0))
+            .unwrap()
+            .unwrap();
+        assert_eq!(funds, token::Amount::native_whole(140));
+        assert_eq!(
+            get_proposal_deposits(&storage, 0).unwrap(),
+            BTreeMap::from([
+                (author.clone(), token::Amount::native_whole(80)),
+                (depositor.clone(), token::Amount::native_whole(60)),
+            ])
+        );
+        assert_eq!(
+            token::read_balance(&storage, &native_token, &governance_address)
+                .unwrap(),
+            token::Amount::native_whole(90)
+        );
+    }
+
+    #[test]
+    fn test_pending_upgrades() {
+        let mut storage = TestWlStorage::default();
+        let plan = |name: &str, height: u64| UpgradePlan {
+            name: name.to_string(),
+            height: BlockHeight(height),
+            migration: None,
+        };
+        schedule_upgrade(&mut storage, 3, &plan("v1", 100)).unwrap();
+        schedule_upgrade(&mut storage, 7, &plan("v2", 200)).unwrap();
+
+        assert_eq!(
+            get_upgrade_plans(&storage).unwrap(),
+            BTreeMap::from([(3, plan("v1", 100)), (7, plan("v2", 200))])
+        );
+        assert_eq!(
+            get_pending_upgrades(&storage, BlockHeight(99)).unwrap(),
+            BTreeMap::from([(3, plan("v1", 100)), (7, plan("v2", 200))])
+        );
+        // An upgrade is pending until its height is processed
+        assert_eq!(
+            get_pending_upgrades(&storage, BlockHeight(100)).unwrap(),
+            BTreeMap::from([(7, plan("v2", 200))])
+        );
+        assert!(
+            get_pending_upgrades(&storage, BlockHeight(200))
+                .unwrap()
+                .is_empty()
+        );
+    }
+}
@@ -4,9 +4,11 @@
 pub mod account;
 pub mod collections;
 mod error;
+pub mod fee_grant;
 pub mod governance;
 pub mod key;
 pub mod pgf;
+pub mod scheduler;
 pub mod token;
 pub mod tx;
 pub mod validation;
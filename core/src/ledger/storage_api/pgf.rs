@@ -2,13 +2,18 @@
 
 use std::collections::HashMap;
 
-use crate::ledger::governance::storage::proposal::StoragePgfFunding;
+use borsh::BorshDeserialize;
+
+use crate::ledger::governance::storage::proposal::{
+    LegacyStoragePgfFunding, StoragePgfFunding,
+};
 use crate::ledger::pgf::parameters::PgfParameters;
 use crate::ledger::pgf::storage::keys as pgf_keys;
 use crate::ledger::pgf::storage::steward::StewardDetail;
 use crate::ledger::storage_api::{self};
 use crate::types::address::Address;
 use crate::types::dec::Dec;
+use crate::types::storage::DbKeySeg;
 
 /// Query the current pgf steward set
 pub fn get_stewards<S>(storage: &S) -> storage_api::Result<Vec<StewardDetail>>
@@ -72,13 +77,109 @@ where
         .iter(storage)?
         .filter_map(|data| match data {
             Ok((_, funding)) => Some(funding),
-            Err(_) => None,
+            Err(err) => {
+                tracing::warn!("Skipping an undecodable PGF funding: {err}");
+                None
+            }
         })
         .collect::<Vec<StoragePgfFunding>>();
 
     Ok(fundings)
 }
 
+/// Rewrite the pgf continous payments stored by the address of their target,
+/// before they were paid over a schedule, as payments due every epoch without
+/// end or cap, stored by the funding key of their recipient. The payments
+/// that can't be decoded are left as they are.
+pub fn migrate_legacy_payments<S>(storage: &mut S) -> storage_api::Result<()>
+where
+    S: storage_api::StorageRead + storage_api::StorageWrite,
+{
+    let legacy_payments = storage_api::iter_prefix_bytes(
+        storage,
+        &pgf_keys::fundings_key_prefix(),
+    )?
+    .filter_map(|data| match data {
+        Ok((key, bytes))
+            if matches!(key.last(), Some(DbKeySeg::AddressSeg(_))) =>
+        {
+            Some((key, bytes))
+        }
+        _ => None,
+    })
+    .collect::<Vec<_>>();
+
+    for (key, bytes) in legacy_payments {
+        match LegacyStoragePgfFunding::try_from_slice(&bytes) {
+            Ok(legacy) => {
+                let funding = StoragePgfFunding::from(legacy);
+                storage.delete(&key)?;
+                pgf_keys::fundings_handle().insert(
+                    storage,
+                    funding.detail.recipient.funding_key(),
+                    funding,
+                )?;
+            }
+            Err(err) => {
+                tracing::warn!(
+                    "Skipping the undecodable legacy PGF funding {key}: {err}"
+                );
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Query a pgf continous payment by the funding key of its recipient
+pub fn get_payment<S>(
+    storage: &S,
+    funding: &str,
+) -> storage_api::Result<Option<StoragePgfFunding>>
+where
+    S: storage_api::StorageRead,
+{
+    pgf_keys::fundings_handle().get(storage, &funding.to_owned())
+}
+
+/// Check if a pgf continous payment is paused by any steward
+pub fn is_payment_paused<S>(
+    storage: &S,
+    funding: &str,
+) -> storage_api::Result<bool>
+where
+    S: storage_api::StorageRead,
+{
+    let prefix = pgf_keys::funding_pauses_prefix(funding);
+    let mut pauses = storage_api::iter_prefix_bytes(storage, &prefix)?;
+    Ok(pauses.next().is_some())
+}
+
+/// Pause a pgf continous payment on behalf of a steward, recording the epoch
+/// of the pause
+pub fn pause_payment<S>(
+    storage: &mut S,
+    steward: &Address,
+    funding: &str,
+) -> storage_api::Result<()>
+where
+    S: storage_api::StorageRead + storage_api::StorageWrite,
+{
+    let epoch = storage.get_block_epoch()?;
+    storage.write(&pgf_keys::funding_pause_key(funding, steward), epoch)
+}
+
+/// Remove the pauses of a pgf continous payment
+pub fn clear_payment_pauses<S>(
+    storage: &mut S,
+    funding: &str,
+) -> storage_api::Result<()>
+where
+    S: storage_api::StorageRead + storage_api::StorageWrite,
+{
+    storage.delete_prefix(&pgf_keys::funding_pauses_prefix(funding))
+}
+
 /// Query the pgf parameters
 pub fn get_parameters<S>(storage: &S) -> storage_api::Result<PgfParameters>
 where
@@ -0,0 +1,226 @@
+//! Scheduled txs
+
+use super::token;
+use crate::ledger::scheduler::storage::keys as scheduler_keys;
+use crate::ledger::scheduler::ADDRESS as scheduler_address;
+use crate::ledger::storage_api::{self, StorageRead, StorageWrite};
+use crate::types::address::Address;
+use crate::types::storage::{BlockHeight, Epoch};
+use crate::types::transaction::scheduler::{Schedule, ScheduleTx};
+
+/// Store a tx for execution at its schedule and escrow the fees for its
+/// whole gas limit from its owner. Returns the id of the scheduled tx.
+pub fn schedule_tx<S>(
+    storage: &mut S,
+    data: ScheduleTx,
+) -> storage_api::Result<u64>
+where
+    S: StorageRead + StorageWrite,
+{
+    let escrow = data.escrowed_fees().ok_or(storage_api::Error::new_const(
+        "The fees of the scheduled tx overflow",
+    ))?;
+    token::transfer(
+        storage,
+        &data.fee.token,
+        &data.owner,
+        &scheduler_address,
+        escrow,
+    )?;
+
+    let counter_key = scheduler_keys::get_counter_key();
+    let id: u64 = storage.read(&counter_key)?.unwrap_or_default();
+
+    storage.write(&scheduler_keys::get_owner_key(&data.owner, id), ())?;
+    storage.write(&scheduler_keys::get_schedule_key(&data.schedule, id), ())?;
+    storage.write(&scheduler_keys::get_tx_key(id), data)?;
+    storage.write(&counter_key, id + 1)?;
+
+    Ok(id)
+}
+
+/// Read a scheduled tx
+pub fn read_scheduled_tx<S>(
+    storage: &S,
+    id: u64,
+) -> storage_api::Result<Option<ScheduleTx>>
+where
+    S: StorageRead,
+{
+    storage.read(&scheduler_keys::get_tx_key(id))
+}
+
+/// Remove a scheduled tx and its index entries, without touching the
+/// escrowed fees. Returns the removed tx, if any.
+pub fn remove_scheduled_tx<S>(
+    storage: &mut S,
+    id: u64,
+) -> storage_api::Result<Option<ScheduleTx>>
+where
+    S: StorageRead + StorageWrite,
+{
+    let scheduled = match read_scheduled_tx(storage, id)? {
+        Some(scheduled) => scheduled,
+        None => return Ok(None),
+    };
+    storage.delete(&scheduler_keys::get_tx_key(id))?;
+    storage.delete(&scheduler_keys::get_owner_key(&scheduled.owner, id))?;
+    storage
+        .delete(&scheduler_keys::get_schedule_key(&scheduled.schedule, id))?;
+    Ok(Some(scheduled))
+}
+
+/// Move a scheduled tx to another schedule, keeping its id and escrowed
+/// fees
+pub fn reschedule_tx<S>(
+    storage: &mut S,
+    id: u64,
+    schedule: Schedule,
+) -> storage_api::Result<()>
+where
+    S: StorageRead + StorageWrite,
+{
+    let mut scheduled = read_scheduled_tx(storage, id)?.ok_or(
+        storage_api::Error::new_const("The scheduled tx doesn't exist"),
+    )?;
+    storage
+        .delete(&scheduler_keys::get_schedule_key(&scheduled.schedule, id))?;
+    storage.write(&scheduler_keys::get_schedule_key(&schedule, id), ())?;
+    scheduled.schedule = schedule;
+    storage.write(&scheduler_keys::get_tx_key(id), scheduled)
+}
+
+/// A scheduled tx cancellation transaction. The escrowed fees are refunded
+/// to the owner.
+pub fn cancel_scheduled_tx<S>(
+    storage: &mut S,
+    id: u64,
+    owner: &Address,
+) -> storage_api::Result<()>
+where
+    S: StorageRead + StorageWrite,
+{
+    match read_scheduled_tx(storage, id)? {
+        Some(scheduled) if &scheduled.owner == owner => {}
+        Some(_) => {
+            return Err(storage_api::Error::new_const(
+                "The scheduled tx belongs to another owner",
+            ));
+        }
+        None => {
+            return Err(storage_api::Error::new_const(
+                "The scheduled tx doesn't exist",
+            ));
+        }
+    }
+    let scheduled =
+        remove_scheduled_tx(storage, id)?.expect("The scheduled tx must exist");
+    let escrow = scheduled
+        .escrowed_fees()
+        .expect("The escrowed fees must have been validated");
+    token::transfer(
+        storage,
+        &scheduled.fee.token,
+        &scheduler_address,
+        owner,
+        escrow,
+    )
+}
+
+/// Pay the fees for the gas used by the execution of a removed scheduled tx
+/// to the block proposer and refund the rest of the escrow to the owner.
+pub fn settle_fees<S>(
+    storage: &mut S,
+    scheduled: &ScheduleTx,
+    gas_used: u64,
+    block_proposer: &Address,
+) -> storage_api::Result<()>
+where
+    S: StorageRead + StorageWrite,
+{
+    let escrow = scheduled
+        .escrowed_fees()
+        .expect("The escrowed fees must have been validated");
+    let fees = scheduled
+        .fees_for_gas(gas_used)
+        .expect("The fees are capped by the escrow");
+    token::transfer(
+        storage,
+        &scheduled.fee.token,
+        &scheduler_address,
+        block_proposer,
+        fees,
+    )?;
+    token::transfer(
+        storage,
+        &scheduled.fee.token,
+        &scheduler_address,
+        &scheduled.owner,
+        escrow - fees,
+    )
+}
+
+/// Get the ids of the txs that are due at the given block height and, if a
+/// new epoch has just started, at the given epoch, in ascending order.
+pub fn due_scheduled_txs<S>(
+    storage: &S,
+    height: BlockHeight,
+    new_epoch: Option<Epoch>,
+) -> storage_api::Result<Vec<u64>>
+where
+    S: StorageRead,
+{
+    let mut schedules = vec![Schedule::AtHeight(height)];
+    schedules.extend(new_epoch.map(Schedule::AtEpoch));
+
+    let mut ids = vec![];
+    for schedule in schedules {
+        let prefix = scheduler_keys::get_schedule_prefix(&schedule);
+        for entry in storage_api::iter_prefix_bytes(storage, &prefix)? {
+            let (key, _) = entry?;
+            if let Some((_, id)) = scheduler_keys::is_schedule_key(&key) {
+                ids.push(id);
+            }
+        }
+    }
+    ids.sort_unstable();
+    Ok(ids)
+}
+
+/// Count the txs scheduled at the given block height or epoch
+pub fn count_scheduled_txs<S>(
+    storage: &S,
+    schedule: &Schedule,
+) -> storage_api::Result<usize>
+where
+    S: StorageRead,
+{
+    let prefix = scheduler_keys::get_schedule_prefix(schedule);
+    let mut count = 0;
+    for entry in storage_api::iter_prefix_bytes(storage, &prefix)? {
+        entry?;
+        count += 1;
+    }
+    Ok(count)
+}
+
+/// Get the pending scheduled txs of the given owner with their ids
+pub fn scheduled_txs_of<S>(
+    storage: &S,
+    owner: &Address,
+) -> storage_api::Result<Vec<(u64, ScheduleTx)>>
+where
+    S: StorageRead,
+{
+    let prefix = scheduler_keys::get_owner_prefix(owner);
+    let mut scheduled_txs = vec![];
+    for entry in storage_api::iter_prefix_bytes(storage, &prefix)? {
+        let (key, _) = entry?;
+        if let Some((_, id)) = scheduler_keys::is_owner_key(&key) {
+            if let Some(scheduled) = read_scheduled_tx(storage, id)? {
+                scheduled_txs.push((id, scheduled));
+            }
+        }
+    }
+    Ok(scheduled_txs)
+}
@@ -113,7 +113,12 @@ where
             match dest_balance.checked_add(amount) {
                 Some(new_dest_balance) => {
                     storage.write(&src_key, new_src_balance)?;
-                    storage.write(&dest_key, new_dest_balance)
+                    storage.write(&dest_key, new_dest_balance)?;
+                    // The source's VP checks the debit against its spending
+                    // limits
+                    storage_api::account::record_spending(
+                        storage, src, token, amount,
+                    )
                 }
                 None => Err(storage_api::Error::new_const(
                     "The transfer would overflow destination balance",
@@ -178,6 +183,12 @@ where
             balance
         }
     };
+    storage_api::account::record_spending(
+        storage,
+        source,
+        token,
+        amount_to_burn,
+    )?;
 
     let total_supply = read_total_supply(&*storage, source)?;
     let new_total_supply =
@@ -4,10 +4,10 @@ pub mod generated;
 mod types;
 
 pub use types::{
-    standalone_signature, verify_standalone_sig, Code, Commitment,
-    CompressedSignature, Data, Error, Header, MaspBuilder, Section,
-    SerializeWithBorsh, Signable, SignableEthMessage, Signature,
-    SignatureIndex, Signed, Signer, Tx, TxError,
+    standalone_signature, verify_standalone_sig, Batch, BatchedTx, Code,
+    Commitment, CompressedSignature, Data, Error, Header, MaspBuilder, Memo,
+    Section, SerializeWithBorsh, Signable, SignableEthMessage, Signature,
+    SignatureIndex, Signed, Signer, Tx, TxError, MAX_MEMO_LEN,
 };
 
 #[cfg(test)]
@@ -30,4 +30,62 @@ mod tests {
         let tx_from_bytes = Tx::decode(&tx_from_hex[..]).unwrap();
         assert_eq!(tx, tx_from_bytes);
     }
+
+    #[test]
+    fn batched_txs_share_raw_header() {
+        use crate::types::transaction::TxType;
+
+        let mut tx = types::Tx::from_type(TxType::Raw);
+        let mut batch = Batch::default();
+        for i in 0..2_u8 {
+            let code_hash = tx
+                .add_section(Section::Code(Code::new(vec![i], None)))
+                .get_hash();
+            let data_hash =
+                tx.add_section(Section::Data(Data::new(vec![i]))).get_hash();
+            batch.txs.push(BatchedTx {
+                code_hash,
+                data_hash,
+            });
+        }
+        tx.set_batch(batch.clone());
+        assert_eq!(tx.batch(), Some(batch));
+
+        let inner_txs = tx.batched_txs().unwrap();
+        assert_eq!(inner_txs.len(), 2);
+        for (i, inner_tx) in inner_txs.iter().enumerate() {
+            assert_eq!(inner_tx.data(), Some(vec![i as u8]));
+            assert_eq!(inner_tx.raw_header_hash(), tx.raw_header_hash());
+            assert!(inner_tx.batch().is_none());
+        }
+    }
+
+    #[test]
+    fn memo_is_single_and_capped() {
+        use crate::types::transaction::TxType;
+
+        let mut tx = types::Tx::from_type(TxType::Raw);
+        assert_eq!(tx.memo(), None);
+        assert!(tx.validate_memo().is_ok());
+
+        let header_hash = tx.header_hash();
+        tx.add_memo("deposit 42");
+        assert_eq!(tx.memo(), Some("deposit 42".to_string()));
+        // The memo doesn't change the layout of the header
+        assert_eq!(tx.header_hash(), header_hash);
+        assert!(tx.validate_memo().is_ok());
+
+        // Setting a memo replaces the current one
+        tx.add_memo("deposit 43");
+        assert_eq!(tx.memo(), Some("deposit 43".to_string()));
+        assert!(tx.validate_memo().is_ok());
+
+        tx.add_memo("x".repeat(MAX_MEMO_LEN + 1));
+        assert_matches!(tx.validate_memo(), Err(TxError::InvalidMemo(_)));
+
+        // A transaction cannot carry several memos
+        tx.add_memo("deposit 42");
+        tx.add_section(Section::Memo(Memo::new("deposit 43")));
+        assert_matches!(tx.validate_memo(), Err(TxError::InvalidMemo(_)));
+    }
 }
@@ -567,6 +567,29 @@ impl Signature {
         signer: &Option<Address>,
         consume_verify_sig_gas: &mut F,
     ) -> std::result::Result<u8, VerifySigError>
+    where
+        F: FnMut() -> std::result::Result<(), crate::ledger::gas::Error>,
+    {
+        self.check_signature(
+            verified_pks,
+            public_keys_index_map,
+            signer,
+            consume_verify_sig_gas,
+            true,
+        )
+    }
+
+    /// Match the signatures of this section to the given public keys,
+    /// charging the gas of their verification. The signatures themselves are
+    /// only verified if `verify` is set.
+    fn check_signature<F>(
+        &self,
+        verified_pks: &mut HashSet<u8>,
+        public_keys_index_map: &AccountPublicKeysMap,
+        signer: &Option<Address>,
+        consume_verify_sig_gas: &mut F,
+        verify: bool,
+    ) -> std::result::Result<u8, VerifySigError>
     where
         F: FnMut() -> std::result::Result<(), crate::ledger::gas::Error>,
     {
@@ -581,11 +604,13 @@ impl Signature {
                         public_keys_index_map.get_public_key_from_index(*idx)
                     {
                         consume_verify_sig_gas()?;
-                        common::SigScheme::verify_signature(
-                            &pk,
-                            &self.get_raw_hash(),
-                            sig,
-                        )?;
+                        if verify {
+                            common::SigScheme::verify_signature(
+                                &pk,
+                                &self.get_raw_hash(),
+                                sig,
+                            )?;
+                        }
                         verified_pks.insert(*idx);
                         verifications += 1;
                     }
@@ -602,11 +627,13 @@ impl Signature {
                         public_keys_index_map.get_index_from_public_key(pk)
                     {
                         consume_verify_sig_gas()?;
-                        common::SigScheme::verify_signature(
-                            pk,
-                            &self.get_raw_hash(),
-                            &self.signatures[&(idx as u8)],
-                        )?;
+                        if verify {
+                            common::SigScheme::verify_signature(
+                                pk,
+                                &self.get_raw_hash(),
+                                &self.signatures[&(idx as u8)],
+                            )?;
+                        }
                         verified_pks.insert(map_idx);
                         verifications += 1;
                     }
@@ -805,6 +832,87 @@ impl borsh::BorshSchema for MaspBuilder {
     }
 }
 
+/// The commitments to the sections of an inner transaction of a batch
+#[derive(
+    Clone,
+    Debug,
+    PartialEq,
+    Eq,
+    BorshSerialize,
+    BorshDeserialize,
+    BorshSchema,
+    Serialize,
+    Deserialize,
+)]
+pub struct BatchedTx {
+    /// The SHA-256 hash of the inner transaction's code section
+    pub code_hash: crate::types::hash::Hash,
+    /// The SHA-256 hash of the inner transaction's data section
+    pub data_hash: crate::types::hash::Hash,
+}
+
+/// An ordered batch of inner transactions that are applied atomically, in
+/// place of a single inner transaction, under the same wrapper
+#[derive(
+    Clone,
+    Debug,
+    Default,
+    PartialEq,
+    Eq,
+    BorshSerialize,
+    BorshDeserialize,
+    BorshSchema,
+    Serialize,
+    Deserialize,
+)]
+pub struct Batch {
+    /// The inner transactions, in the order they are applied
+    pub txs: Vec<BatchedTx>,
+}
+
+impl Batch {
+    /// Hash this batch section
+    pub fn hash<'a>(&self, hasher: &'a mut Sha256) -> &'a mut Sha256 {
+        hasher.update(self.serialize_to_vec());
+        hasher
+    }
+}
+
+/// The maximum length in bytes of the memo of a transaction
+pub const MAX_MEMO_LEN: usize = 256;
+
+/// A free-form note attached to a transaction, e.g. to identify the account
+/// credited by a deposit to an exchange
+#[derive(
+    Clone,
+    Debug,
+    Default,
+    PartialEq,
+    Eq,
+    BorshSerialize,
+    BorshDeserialize,
+    BorshSchema,
+    Serialize,
+    Deserialize,
+)]
+pub struct Memo {
+    /// The text of the memo
+    pub text: String,
+}
+
+impl Memo {
+    /// Make a new memo section with the given text
+    pub fn new(text: impl Into<String>) -> Self {
+        Self { text: text.into() }
+    }
+
+    /// Hash this memo section
+    pub fn hash<'a>(&self, hasher: &'a mut Sha256) -> &'a mut Sha256 {
+        hasher.update(self.serialize_to_vec());
+        hasher
+    }
+}
+
 /// A section of a transaction. Carries an independent piece of information
 /// necessary for the processing of a transaction.
 #[derive(
@@ -838,6 +946,16 @@ pub enum Section {
     MaspBuilder(MaspBuilder),
     /// Wrap a header with a section for the purposes of computing hashes
     Header(Header),
+    /// A batch of inner transactions, designated by both the code and the
+    /// data hashes of the header
+    Batch(Batch),
+    /// The memo of a transaction. It's kept out of the header, whose layout
+    /// is fixed, and is covered by the signature of the wrapper.
+    Memo(Memo),
+    /// The address paying the fees of the wrapper on behalf of its signer, out
+    /// of the fee allowance it granted to it. It's kept out of the wrapper,
+    /// whose layout is fixed, and is covered by the signature of the wrapper.
+    FeeGranter(Address),
 }
 
 impl Section {
@@ -860,6 +978,12 @@ impl Section {
                 hasher
             }
             Self::Header(header) => header.hash(hasher),
+            Self::Batch(batch) => batch.hash(hasher),
+            Self::Memo(memo) => memo.hash(hasher),
+            Self::FeeGranter(granter) => {
+                hasher.update(granter.serialize_to_vec());
+                hasher
+            }
         }
     }
 
@@ -950,6 +1074,33 @@ impl Section {
             None
         }
     }
+
+    /// Extract the batch of inner transactions from this section if possible
+    pub fn batch(&self) -> Option<Batch> {
+        if let Self::Batch(data) = self {
+            Some(data.clone())
+        } else {
+            None
+        }
+    }
+
+    /// Extract the memo from this section if possible
+    pub fn memo(&self) -> Option<Memo> {
+        if let Self::Memo(data) = self {
+            Some(data.clone())
+        } else {
+            None
+        }
+    }
+
+    /// Extract the fee granter from this section if possible
+    pub fn fee_granter(&self) -> Option<Address> {
+        if let Self::FeeGranter(granter) = self {
+            Some(granter.clone())
+        } else {
+            None
+        }
+    }
 }
 
 /// A Namada transaction header indicating where transaction subcomponents can
@@ -1036,6 +1187,8 @@ pub enum TxError {
     SigError(String),
     #[error("Failed to deserialize Tx: {0}")]
     Deserialization(String),
+    #[error("Invalid memo: {0}")]
+    InvalidMemo(String),
 }
 
 /// A Namada transaction is represented as a header followed by a series of
@@ -1128,10 +1281,35 @@ impl Tx {
 
     /// Gets the hash of the decrypted transaction's header
     pub fn raw_header_hash(&self) -> crate::types::hash::Hash {
+        Section::Header(self.raw_header()).get_hash()
+    }
+
+    /// Get the decrypted transaction's header. The inner transactions of a
+    /// batch share the header of the batch, which is the one their signers
+    /// sign over.
+    fn raw_header(&self) -> Header {
         let mut raw_header = self.header();
         raw_header.tx_type = TxType::Raw;
+        if let Some(batch_hash) = self.enclosing_batch_hash() {
+            raw_header.code_hash = batch_hash;
+            raw_header.data_hash = batch_hash;
+        }
+        raw_header
+    }
 
-        Section::Header(raw_header).get_hash()
+    /// Get the hash of the batch section that this transaction is an inner
+    /// transaction of, if any
+    fn enclosing_batch_hash(&self) -> Option<crate::types::hash::Hash> {
+        let inner = BatchedTx {
+            code_hash: self.header.code_hash,
+            data_hash: self.header.data_hash,
+        };
+        self.sections.iter().find_map(|section| match section {
+            Section::Batch(batch) if batch.txs.contains(&inner) => {
+                Some(section.get_hash())
+            }
+            _ => None,
+        })
     }
 
     /// Get hashes of all the sections in this transaction
@@ -1157,9 +1335,7 @@ impl Tx {
         if self.header_hash() == *hash {
             return Some(Cow::Owned(Section::Header(self.header.clone())));
         } else if self.raw_header_hash() == *hash {
-            let mut header = self.header();
-            header.tx_type = TxType::Raw;
-            return Some(Cow::Owned(Section::Header(header)));
+            return Some(Cow::Owned(Section::Header(self.raw_header())));
         }
         for section in &self.sections {
             if section.get_hash() == *hash {
@@ -1235,6 +1411,99 @@ impl Tx {
         }
     }
 
+    /// Get the batch of inner transactions designated by the transaction code
+    /// hash in the header, if any
+    pub fn batch(&self) -> Option<Batch> {
+        match self
+            .get_section(self.code_sechash())
+            .as_ref()
+            .map(Cow::as_ref)
+        {
+            Some(Section::Batch(batch)) => Some(batch.clone()),
+            _ => None,
+        }
+    }
+
+    /// Add the given batch of inner transactions to the transaction and set
+    /// both the code and the data hashes in the header to designate it
+    pub fn set_batch(&mut self, batch: Batch) -> &mut Section {
+        let sec = Section::Batch(batch);
+        let hash = sec.get_hash();
+        self.set_code_sechash(hash);
+        self.set_data_sechash(hash);
+        self.sections.push(sec);
+        self.sections.last_mut().unwrap()
+    }
+
+    /// Split a batch into its inner transactions, in the order they must be
+    /// applied. Each of them is a copy of the batch whose header designates
+    /// the code and data sections of the inner transaction. Returns `None` if
+    /// this transaction is not a batch.
+    pub fn batched_txs(&self) -> Option<Vec<Tx>> {
+        let batch = self.batch()?;
+        let inner_txs = batch
+            .txs
+            .into_iter()
+            .map(|inner| {
+                let mut tx = self.clone();
+                tx.set_code_sechash(inner.code_hash);
+                tx.set_data_sechash(inner.data_hash);
+                tx
+            })
+            .collect();
+        Some(inner_txs)
+    }
+
+    /// Get the memo of the transaction, if any
+    pub fn memo(&self) -> Option<String> {
+        self.sections
+            .iter()
+            .find_map(Section::memo)
+            .map(|memo| memo.text)
+    }
+
+    /// Add the given memo to the transaction in place of its current memo, if
+    /// any
+    pub fn set_memo(&mut self, memo: Memo) -> &mut Section {
+        self.sections
+            .retain(|section| !matches!(section, Section::Memo(_)));
+        self.sections.push(Section::Memo(memo));
+        self.sections.last_mut().unwrap()
+    }
+
+    /// Get the address paying the fees of the wrapper on behalf of its signer,
+    /// if any
+    pub fn fee_granter(&self) -> Option<Address> {
+        self.sections.iter().find_map(Section::fee_granter)
+    }
+
+    /// Have the fees of the wrapper paid by the given granter, in place of its
+    /// current fee granter, if any
+    pub fn set_fee_granter(&mut self, granter: Address) -> &mut Section {
+        self.sections
+            .retain(|section| !matches!(section, Section::FeeGranter(_)));
+        self.sections.push(Section::FeeGranter(granter));
+        self.sections.last_mut().unwrap()
+    }
+
+    /// Check that the transaction carries at most one memo section, no longer
+    /// than [`MAX_MEMO_LEN`]
+    pub fn validate_memo(&self) -> std::result::Result<(), TxError> {
+        let mut memos = self.sections.iter().filter_map(Section::memo);
+        match (memos.next(), memos.next()) {
+            (Some(_), Some(_)) => Err(TxError::InvalidMemo(
+                "the transaction carries several memos".to_string(),
+            )),
+            (Some(memo), None) if memo.text.len() > MAX_MEMO_LEN => {
+                Err(TxError::InvalidMemo(format!(
+                    "the memo is {} bytes long, the maximum is {MAX_MEMO_LEN}",
+                    memo.text.len()
+                )))
+            }
+            _ => Ok(()),
+        }
+    }
+
     /// Convert this transaction into protobufs
     pub fn to_bytes(&self) -> Vec<u8> {
         let mut bytes = vec![];
@@ -1249,6 +1518,57 @@ impl Tx {
     /// Verify that the section with the given hash has been signed by the given
     /// public key
     pub fn verify_signatures<F>(
+        &self,
+        hashes: &[crate::types::hash::Hash],
+        public_keys_index_map: AccountPublicKeysMap,
+        signer: &Option<Address>,
+        threshold: u8,
+        max_signatures: Option<u8>,
+        consume_verify_sig_gas: F,
+    ) -> std::result::Result<Vec<&Signature>, Error>
+    where
+        F: FnMut() -> std::result::Result<(), crate::ledger::gas::Error>,
+    {
+        self.check_signatures(
+            hashes,
+            public_keys_index_map,
+            signer,
+            threshold,
+            max_signatures,
+            consume_verify_sig_gas,
+            true,
+        )
+    }
+
+    /// Check the signatures of the section with the given hash like
+    /// [`Self::verify_signatures`], charging the same gas, but without
+    /// verifying them cryptographically. Only meant to estimate the gas of a
+    /// transaction carrying dummy signatures in place of the real ones.
+    pub fn verify_dummy_signatures<F>(
+        &self,
+        hashes: &[crate::types::hash::Hash],
+        public_keys_index_map: AccountPublicKeysMap,
+        signer: &Option<Address>,
+        threshold: u8,
+        max_signatures: Option<u8>,
+        consume_verify_sig_gas: F,
+    ) -> std::result::Result<Vec<&Signature>, Error>
+    where
+        F: FnMut() -> std::result::Result<(), crate::ledger::gas::Error>,
+    {
+        self.check_signatures(
+            hashes,
+            public_keys_index_map,
+            signer,
+            threshold,
+            max_signatures,
+            consume_verify_sig_gas,
+            false,
+        )
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn check_signatures<F>(
         &self,
         hashes: &[crate::types::hash::Hash],
         public_keys_index_map: AccountPublicKeysMap,
@@ -1256,6 +1576,7 @@ impl Tx {
         threshold: u8,
         max_signatures: Option<u8>,
         mut consume_verify_sig_gas: F,
+        verify: bool,
     ) -> std::result::Result<Vec<&Signature>, Error>
     where
         F: FnMut() -> std::result::Result<(), crate::ledger::gas::Error>,
@@ -1286,11 +1607,12 @@ impl Tx {
 
                     // Finally verify that the signature itself is valid
                     let amt_verifieds = signatures
-                        .verify_signature(
+                        .check_signature(
                             &mut verified_pks,
                             &public_keys_index_map,
                             signer,
                             &mut consume_verify_sig_gas,
+                            verify,
                         )
                         .map_err(|e| {
                             if let VerifySigError::OutOfGas(inner) = e {
@@ -1532,6 +1854,12 @@ impl Tx {
         self
     }
 
+    /// Add a memo to the tx builder
+    pub fn add_memo(&mut self, memo: impl Into<String>) -> &mut Self {
+        self.set_memo(Memo::new(memo));
+        self
+    }
+
     /// Add wrapper tx to the tx builder
     pub fn add_wrapper(
         &mut self,
@@ -1,12 +1,14 @@
 //! Helper structures to manage accounts
 
-use std::collections::{BTreeMap, HashMap};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
 
-use borsh::{BorshDeserialize, BorshSerialize};
+use borsh::{BorshDeserialize, BorshSchema, BorshSerialize};
 use serde::{Deserialize, Serialize};
 
 use super::address::Address;
 use super::key::{common, RefTo};
+use super::storage::Epoch;
+use super::token::Amount;
 
 #[derive(
     Debug, Clone, BorshSerialize, BorshDeserialize, Serialize, Deserialize,
@@ -19,6 +21,8 @@ pub struct Account {
     pub threshold: u8,
     /// The address corresponding to the account owner
     pub address: Address,
+    /// The policies restricting some of the public keys
+    pub key_policies: BTreeMap<common::PublicKey, KeyPolicy>,
 }
 
 impl Account {
@@ -90,6 +94,18 @@ impl AccountPublicKeysMap {
         self.pk_to_idx.get(public_key).cloned()
     }
 
+    /// Keep only the public keys matching the predicate. The remaining keys
+    /// keep their index.
+    pub fn retain<F>(&mut self, mut predicate: F)
+    where
+        F: FnMut(&common::PublicKey) -> bool,
+    {
+        self.pk_to_idx.retain(|public_key, _| predicate(public_key));
+        let pk_to_idx = &self.pk_to_idx;
+        self.idx_to_pk
+            .retain(|_, public_key| pk_to_idx.contains_key(public_key));
+    }
+
     /// Index the given set of secret keys
     pub fn index_secret_keys(
         &self,
@@ -104,3 +120,150 @@ impl AccountPublicKeysMap {
             .collect()
     }
 }
+
+/// The kinds of changes to an account that its public keys may authorize
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    BorshSerialize,
+    BorshDeserialize,
+    BorshSchema,
+    Serialize,
+    Deserialize,
+)]
+pub enum AccountPermission {
+    /// Debit tokens from the account
+    Transfer,
+    /// Bond and unbond the tokens of the account
+    PoS,
+    /// Vote on governance proposals
+    Governance,
+    /// Any other change, including to the keys, threshold, policies and VP
+    /// of the account
+    Admin,
+}
+
+/// The restrictions on what a public key of an account may authorize. A key
+/// without a policy may authorize anything.
+#[derive(
+    Debug,
+    Clone,
+    Default,
+    PartialEq,
+    Eq,
+    BorshSerialize,
+    BorshDeserialize,
+    BorshSchema,
+    Serialize,
+    Deserialize,
+)]
+pub struct KeyPolicy {
+    /// The kinds of changes that the key may authorize
+    pub permissions: BTreeSet<AccountPermission>,
+    /// The maximum amount of a token that may be debited from the account in
+    /// an epoch, in total, for the key to authorize a debit. Tokens without
+    /// a limit may be debited without one.
+    pub spending_limits: BTreeMap<Address, Amount>,
+}
+
+impl KeyPolicy {
+    /// Check if the key may authorize the given kind of changes
+    pub fn allows(&self, permission: AccountPermission) -> bool {
+        self.permissions.contains(&permission)
+    }
+
+    /// Check if the amount of the given token debited from the account in
+    /// the current epoch, `spent`, is within the key's limit
+    pub fn within_spending_limit(&self, token: &Address, spent: Amount) -> bool {
+        self.spending_limits
+            .get(token)
+            .map_or(true, |limit| spent <= *limit)
+    }
+}
+
+/// The amount of a token debited from an account in an epoch, recorded for
+/// the accounts with a spending limit on the token
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    Default,
+    PartialEq,
+    Eq,
+    BorshSerialize,
+    BorshDeserialize,
+    BorshSchema,
+    Serialize,
+    Deserialize,
+)]
+pub struct EpochSpending {
+    /// The epoch of the debits
+    pub epoch: Epoch,
+    /// The total amount debited in the epoch
+    pub amount: Amount,
+}
+
+impl EpochSpending {
+    /// The amount debited in the given epoch. The record of a past epoch
+    /// counts as nothing spent yet.
+    pub fn spent_in(&self, epoch: Epoch) -> Amount {
+        if self.epoch == epoch {
+            self.amount
+        } else {
+            Amount::zero()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::address::{btc, nam};
+    use crate::types::key::testing::{keypair_1, keypair_2};
+
+    #[test]
+    fn test_key_policy() {
+        let policy = KeyPolicy {
+            permissions: BTreeSet::from([
+                AccountPermission::Transfer,
+                AccountPermission::Governance,
+            ]),
+            spending_limits: BTreeMap::from([(nam(), Amount::from_u64(100))]),
+        };
+        assert!(policy.allows(AccountPermission::Governance));
+        assert!(!policy.allows(AccountPermission::PoS));
+        assert!(policy.within_spending_limit(&nam(), Amount::from_u64(100)));
+        assert!(!policy.within_spending_limit(&nam(), Amount::from_u64(101)));
+        // Tokens without a limit aren't restricted
+        assert!(
+            policy.within_spending_limit(&btc(), Amount::from_u64(u64::MAX))
+        );
+
+        let spending = EpochSpending {
+            epoch: Epoch(3),
+            amount: Amount::from_u64(50),
+        };
+        assert_eq!(spending.spent_in(Epoch(3)), Amount::from_u64(50));
+        assert_eq!(spending.spent_in(Epoch(4)), Amount::zero());
+    }
+
+    #[test]
+    fn test_retain_public_keys() {
+        let pk_1 = keypair_1().ref_to();
+        let pk_2 = keypair_2().ref_to();
+        let mut public_keys_map =
+            AccountPublicKeysMap::from_iter([pk_1.clone(), pk_2.clone()]);
+        public_keys_map.retain(|public_key| public_key == &pk_2);
+        assert_eq!(public_keys_map.get_index_from_public_key(&pk_1), None);
+        assert_eq!(public_keys_map.get_public_key_from_index(0), None);
+        // The remaining key keeps its index
+        assert_eq!(public_keys_map.get_index_from_public_key(&pk_2), Some(1));
+        assert_eq!(public_keys_map.get_public_key_from_index(1), Some(pk_2));
+    }
+}
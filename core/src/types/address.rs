@@ -124,6 +124,12 @@ impl From<raw::Address<'_, raw::Validated>> for Address {
                 InternalAddress::IbcToken(IbcTokenHash(*raw_addr.data())),
             ),
             raw::Discriminant::Masp => Address::Internal(InternalAddress::Masp),
+            raw::Discriminant::Scheduler => {
+                Address::Internal(InternalAddress::Scheduler)
+            }
+            raw::Discriminant::FeeGrant => {
+                Address::Internal(InternalAddress::FeeGrant)
+            }
         }
     }
 }
@@ -218,6 +224,16 @@ impl<'addr> From<&'addr Address> for raw::Address<'addr, raw::Validated> {
                     .validate()
                     .expect("This raw address is valid")
             }
+            Address::Internal(InternalAddress::Scheduler) => {
+                raw::Address::from_discriminant(raw::Discriminant::Scheduler)
+                    .validate()
+                    .expect("This raw address is valid")
+            }
+            Address::Internal(InternalAddress::FeeGrant) => {
+                raw::Address::from_discriminant(raw::Discriminant::FeeGrant)
+                    .validate()
+                    .expect("This raw address is valid")
+            }
         }
     }
 }
@@ -495,6 +511,10 @@ pub enum InternalAddress {
     Pgf,
     /// Masp
     Masp,
+    /// Scheduled transactions
+    Scheduler,
+    /// Fee allowances
+    FeeGrant,
 }
 
 impl Display for InternalAddress {
@@ -516,6 +536,8 @@ impl Display for InternalAddress {
                 Self::Multitoken => "Multitoken".to_string(),
                 Self::Pgf => "PublicGoodFundings".to_string(),
                 Self::Masp => "MASP".to_string(),
+                Self::Scheduler => "Scheduler".to_string(),
+                Self::FeeGrant => "FeeGrant".to_string(),
             }
         )
     }
@@ -531,6 +553,8 @@ impl InternalAddress {
             "bridgepool" => Some(InternalAddress::EthBridgePool),
             "governance" => Some(InternalAddress::Governance),
             "masp" => Some(InternalAddress::Masp),
+            "scheduler" => Some(InternalAddress::Scheduler),
+            "feegrant" => Some(InternalAddress::FeeGrant),
             _ => None,
         }
     }
@@ -816,6 +840,8 @@ pub mod testing {
             InternalAddress::Nut(_) => {}
             InternalAddress::Pgf => {}
             InternalAddress::Masp => {}
+            InternalAddress::Scheduler => {}
+            InternalAddress::FeeGrant => {}
             InternalAddress::Multitoken => {} /* Add new addresses in the
                                                * `prop_oneof` below. */
         };
@@ -833,6 +859,8 @@ pub mod testing {
             Just(InternalAddress::Multitoken),
             Just(InternalAddress::Pgf),
             Just(InternalAddress::Masp),
+            Just(InternalAddress::Scheduler),
+            Just(InternalAddress::FeeGrant),
         ]
     }
 
@@ -65,6 +65,10 @@ pub enum Discriminant {
     IbcToken = 13,
     /// MASP raw address.
     Masp = 14,
+    /// Scheduled transactions raw address.
+    Scheduler = 15,
+    /// Fee allowances raw address.
+    FeeGrant = 16,
 }
 
 /// Raw address representation.
@@ -19,6 +19,7 @@ use serde::Serialize;
 use sha2::{Digest, Sha256};
 use thiserror::Error;
 
+use super::account::KeyPolicy;
 use super::address::Address;
 use super::storage::{self, DbKeySeg, Key};
 use crate::ledger::storage::{Sha256Hasher, StorageHasher};
@@ -31,6 +32,8 @@ struct Keys {
     public_keys: &'static str,
     threshold: &'static str,
     protocol_public_keys: &'static str,
+    key_policies: &'static str,
+    spending: &'static str,
 }
 
 /// Obtain a storage key for user's public key.
@@ -88,6 +91,48 @@ pub fn threshold_key(owner: &Address) -> storage::Key {
     }
 }
 
+/// Obtain the storage key prefix of the policies of a user's public keys
+pub fn key_policies_key_prefix(owner: &Address) -> storage::Key {
+    Key {
+        segments: vec![
+            DbKeySeg::AddressSeg(owner.to_owned()),
+            DbKeySeg::StringSeg(Keys::VALUES.key_policies.to_string()),
+        ],
+    }
+}
+
+/// LazyMap handler for the policies of a user's public keys
+pub fn key_policies_handle(
+    owner: &Address,
+) -> LazyMap<common::PublicKey, KeyPolicy> {
+    LazyMap::open(key_policies_key_prefix(owner))
+}
+
+/// Obtain the storage key of the amount of a token debited from a user's
+/// account in the last epoch with debits
+pub fn spending_key(owner: &Address, token: &Address) -> storage::Key {
+    Key {
+        segments: vec![
+            DbKeySeg::AddressSeg(owner.to_owned()),
+            DbKeySeg::StringSeg(Keys::VALUES.spending.to_string()),
+            DbKeySeg::AddressSeg(token.to_owned()),
+        ],
+    }
+}
+
+/// Check if the given storage key is a spending key. If it is, returns the
+/// owner and the token.
+pub fn is_spending_key(key: &Key) -> Option<(&Address, &Address)> {
+    match &key.segments[..] {
+        [
+            DbKeySeg::AddressSeg(owner),
+            DbKeySeg::StringSeg(prefix),
+            DbKeySeg::AddressSeg(token),
+        ] if prefix.as_str() == Keys::VALUES.spending => Some((owner, token)),
+        _ => None,
+    }
+}
+
 /// Obtain a storage key for user's protocol public key.
 pub fn protocol_pk_key(owner: &Address) -> storage::Key {
     Key {
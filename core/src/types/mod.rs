@@ -21,5 +21,6 @@ pub mod token;
 pub mod transaction;
 pub mod uint;
 pub mod validity_predicate;
+pub mod vesting;
 pub mod vote_extensions;
 pub mod voting_power;
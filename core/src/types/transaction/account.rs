@@ -1,9 +1,13 @@
+use std::collections::BTreeMap;
+
 use borsh::{BorshDeserialize, BorshSchema, BorshSerialize};
 use serde::{Deserialize, Serialize};
 
+use crate::types::account::KeyPolicy;
 use crate::types::address::Address;
 use crate::types::hash::Hash;
 use crate::types::key::common;
+use crate::types::vesting::Vesting;
 
 /// A tx data type to initialize a new established account
 #[derive(
@@ -25,6 +29,8 @@ pub struct InitAccount {
     pub vp_code_hash: Hash,
     /// The account signature threshold
     pub threshold: u8,
+    /// The optional vesting schedule of the tokens locked in the account
+    pub vesting: Option<Vesting>,
 }
 
 /// A tx data type to update an account's validity predicate
@@ -49,4 +55,7 @@ pub struct UpdateAccount {
     pub public_keys: Vec<common::PublicKey>,
     /// The account signature threshold
     pub threshold: Option<u8>,
+    /// The policies restricting what some of the public keys may authorize.
+    /// If given, these replace all the existing policies.
+    pub key_policies: Option<BTreeMap<common::PublicKey, KeyPolicy>>,
 }
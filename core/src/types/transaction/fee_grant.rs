@@ -0,0 +1,125 @@
+use std::collections::BTreeMap;
+
+use borsh::{BorshDeserialize, BorshSchema, BorshSerialize};
+use serde::{Deserialize, Serialize};
+
+use crate::types::address::Address;
+use crate::types::storage::Epoch;
+use crate::types::token::Amount;
+
+/// The wrapper fees that a granter has authorised to pay for a grantee
+#[derive(
+    Debug,
+    Clone,
+    Default,
+    PartialEq,
+    Eq,
+    BorshSerialize,
+    BorshDeserialize,
+    BorshSchema,
+    Serialize,
+    Deserialize,
+)]
+pub struct FeeAllowance {
+    /// The remaining amount of fees that may be paid, per fee token
+    pub spend_limit: BTreeMap<Address, Amount>,
+    /// The first epoch in which the allowance can no longer be used
+    pub expiration: Option<Epoch>,
+}
+
+impl FeeAllowance {
+    /// Check if the allowance can no longer be used in the given epoch
+    pub fn is_expired(&self, epoch: Epoch) -> bool {
+        matches!(self.expiration, Some(expiration) if epoch >= expiration)
+    }
+
+    /// The remaining amount of fees that may be paid in the given token
+    pub fn remaining(&self, token: &Address) -> Amount {
+        self.spend_limit.get(token).copied().unwrap_or_default()
+    }
+
+    /// Deduct the given amount of fees paid in the given token from the
+    /// allowance. Returns `false`, leaving the allowance untouched, if the
+    /// amount exceeds the remaining one.
+    pub fn spend(&mut self, token: &Address, amount: Amount) -> bool {
+        match self.remaining(token).checked_sub(amount) {
+            Some(remaining) => {
+                self.spend_limit.insert(token.clone(), remaining);
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+/// A tx data type to authorise a granter to pay, up to the given amount of
+/// a token, the wrapper fees of a grantee. The spend limits of any other
+/// token already granted are kept, while the expiration is replaced.
+#[derive(
+    Debug,
+    Clone,
+    PartialEq,
+    Eq,
+    BorshSerialize,
+    BorshDeserialize,
+    BorshSchema,
+    Serialize,
+    Deserialize,
+)]
+pub struct GrantFeeAllowance {
+    /// The address paying the fees
+    pub granter: Address,
+    /// The address whose wrapper fees are paid
+    pub grantee: Address,
+    /// The fee token
+    pub token: Address,
+    /// The maximum amount of fees paid in the token
+    pub spend_limit: Amount,
+    /// The first epoch in which the allowance can no longer be used
+    pub expiration: Option<Epoch>,
+}
+
+/// A tx data type to revoke the whole fee allowance granted to a grantee
+#[derive(
+    Debug,
+    Clone,
+    PartialEq,
+    Eq,
+    BorshSerialize,
+    BorshDeserialize,
+    BorshSchema,
+    Serialize,
+    Deserialize,
+)]
+pub struct RevokeFeeAllowance {
+    /// The address paying the fees
+    pub granter: Address,
+    /// The address whose wrapper fees are paid
+    pub grantee: Address,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::address::{btc, nam};
+
+    #[test]
+    fn test_fee_allowance() {
+        let mut allowance = FeeAllowance {
+            spend_limit: BTreeMap::from([(nam(), Amount::from_u64(100))]),
+            expiration: Some(Epoch(5)),
+        };
+        assert!(allowance.spend(&nam(), Amount::from_u64(60)));
+        assert_eq!(allowance.remaining(&nam()), Amount::from_u64(40));
+        // The remaining allowance is untouched when exceeded
+        assert!(!allowance.spend(&nam(), Amount::from_u64(41)));
+        assert_eq!(allowance.remaining(&nam()), Amount::from_u64(40));
+        // Tokens without a spend limit can't be used
+        assert!(!allowance.spend(&btc(), Amount::from_u64(1)));
+        assert!(allowance.spend(&btc(), Amount::zero()));
+
+        assert!(!allowance.is_expired(Epoch(4)));
+        assert!(allowance.is_expired(Epoch(5)));
+        assert!(!FeeAllowance::default().is_expired(Epoch(u64::MAX)));
+    }
+}
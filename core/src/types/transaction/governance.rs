@@ -5,15 +5,18 @@ use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
 use crate::ledger::governance::cli::onchain::{
-    DefaultProposal, PgfFundingProposal, PgfStewardProposal,
+    DefaultProposal, ParameterChangeProposal, PgfFundingProposal,
+    PgfStewardProposal, UpgradeProposal,
 };
 use crate::ledger::governance::storage::proposal::{
-    AddRemove, PGFAction, PGFTarget, ProposalType,
+    AddRemove, PGFAction, PGFContinuous, PGFTarget, ProposalKind, ProposalType,
+    UpgradePlan,
 };
 use crate::ledger::governance::storage::vote::StorageProposalVote;
 use crate::types::address::Address;
 use crate::types::hash::Hash;
 use crate::types::storage::Epoch;
+use crate::types::token;
 
 #[allow(missing_docs)]
 #[derive(Debug, Error)]
@@ -80,6 +83,49 @@ pub struct VoteProposalData {
     pub delegations: Vec<Address>,
 }
 
+/// A tx data type to appoint, or revoke, the delegate that votes on the
+/// governance proposals on behalf of an address. The votes of the delegate
+/// are followed for the bonds of the address, unless it votes itself.
+#[derive(
+    Debug,
+    Clone,
+    PartialEq,
+    BorshSerialize,
+    BorshDeserialize,
+    Serialize,
+    Deserialize,
+)]
+pub struct DelegateVotes {
+    /// The address delegating its votes
+    pub delegator: Address,
+    /// The delegate or `None` to revoke the delegation
+    pub delegate: Option<Address>,
+    /// The proposal kind the delegation is restricted to or `None` for all
+    /// of them. A delegation for a proposal kind takes precedence over one
+    /// for all of them.
+    pub proposal_kind: Option<ProposalKind>,
+}
+
+/// A tx data type to top up the deposit of a governance proposal before its
+/// voting period starts
+#[derive(
+    Debug,
+    Clone,
+    PartialEq,
+    BorshSerialize,
+    BorshDeserialize,
+    Serialize,
+    Deserialize,
+)]
+pub struct DepositProposalData {
+    /// The proposal id
+    pub id: u64,
+    /// The address depositing the tokens
+    pub depositor: Address,
+    /// The amount of native tokens deposited
+    pub amount: token::Amount,
+}
+
 impl TryFrom<DefaultProposal> for InitProposalData {
     type Error = ProposalError;
 
@@ -123,19 +169,26 @@ impl TryFrom<PgfFundingProposal> for InitProposalData {
             .data
             .continous
             .iter()
-            .cloned()
             .map(|funding| {
-                let target = PGFTarget {
-                    target: funding.address,
+                let recipient = funding
+                    .recipient()
+                    .ok_or(ProposalError::InvalidProposalData)?;
+                let target = PGFContinuous {
+                    recipient,
                     amount: funding.amount,
+                    start_epoch: funding
+                        .start_epoch
+                        .unwrap_or(value.proposal.grace_epoch),
+                    end_epoch: funding.end_epoch,
+                    cap: funding.cap,
                 };
                 if funding.amount.is_zero() {
-                    PGFAction::Continuous(AddRemove::Remove(target))
+                    Ok(PGFAction::Continuous(AddRemove::Remove(target)))
                 } else {
-                    PGFAction::Continuous(AddRemove::Add(target))
+                    Ok(PGFAction::Continuous(AddRemove::Add(target)))
                 }
             })
-            .collect::<Vec<PGFAction>>();
+            .collect::<Result<Vec<PGFAction>, _>>()?;
 
         let retro_fundings = value
             .data
@@ -164,3 +217,38 @@ impl TryFrom<PgfFundingProposal> for InitProposalData {
         })
     }
 }
+
+impl TryFrom<ParameterChangeProposal> for InitProposalData {
+    type Error = ProposalError;
+
+    fn try_from(value: ParameterChangeProposal) -> Result<Self, Self::Error> {
+        Ok(InitProposalData {
+            id: value.proposal.id,
+            content: Hash::default(),
+            author: value.proposal.author,
+            r#type: ProposalType::ParameterChange(value.data),
+            voting_start_epoch: value.proposal.voting_start_epoch,
+            voting_end_epoch: value.proposal.voting_end_epoch,
+            grace_epoch: value.proposal.grace_epoch,
+        })
+    }
+}
+
+impl TryFrom<UpgradeProposal> for InitProposalData {
+    type Error = ProposalError;
+
+    fn try_from(value: UpgradeProposal) -> Result<Self, Self::Error> {
+        let plan = UpgradePlan::try_from(value.data)
+            .map_err(|_| ProposalError::InvalidProposalData)?;
+
+        Ok(InitProposalData {
+            id: value.proposal.id,
+            content: Hash::default(),
+            author: value.proposal.author,
+            r#type: ProposalType::Upgrade(plan),
+            voting_start_epoch: value.proposal.voting_start_epoch,
+            voting_end_epoch: value.proposal.voting_end_epoch,
+            grace_epoch: value.proposal.grace_epoch,
+        })
+    }
+}
@@ -5,6 +5,8 @@ pub mod account;
 /// txs that contain decrypted payloads or assertions of
 /// non-decryptability
 pub mod decrypted;
+/// txs to grant fee allowances
+pub mod fee_grant;
 /// txs to manage governance
 pub mod governance;
 /// txs to manage pgf
@@ -13,10 +15,12 @@ pub mod pgf;
 pub mod pos;
 /// transaction protocols made by validators
 pub mod protocol;
+/// txs to schedule the execution of other txs
+pub mod scheduler;
 /// wrapper txs with encrypted payloads
 pub mod wrapper;
 
-use std::collections::BTreeSet;
+use std::collections::{BTreeMap, BTreeSet};
 use std::fmt;
 
 use borsh::{BorshDeserialize, BorshSchema, BorshSerialize};
@@ -26,11 +30,13 @@ use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
 pub use wrapper::*;
 
-use crate::ledger::gas::{Gas, VpsGas};
+use crate::ledger::gas::{Gas, GasProfile, VpsGas};
 use crate::types::address::Address;
+use crate::types::dec::Dec;
 use crate::types::hash::Hash;
 use crate::types::ibc::IbcEvent;
 use crate::types::storage;
+use crate::types::token::Amount;
 use crate::types::transaction::protocol::ProtocolTx;
 
 /// Get the hash of a transaction
@@ -53,6 +59,12 @@ pub struct TxResult {
     pub initialized_accounts: Vec<Address>,
     /// IBC events emitted by the transaction
     pub ibc_events: BTreeSet<IbcEvent>,
+    /// The results of the inner transactions of a batch, in the order they
+    /// were applied. Empty unless the transaction is a batch.
+    pub inner_results: Vec<TxResult>,
+    /// The gas used per section of the execution of the transaction, if it
+    /// was profiled
+    pub gas_profile: Option<GasProfile>,
 }
 
 impl TxResult {
@@ -72,6 +84,11 @@ pub struct VpsResult {
     pub rejected_vps: BTreeSet<Address>,
     /// The total gas used by all the VPs
     pub gas_used: VpsGas,
+    /// The gas used by each of the VPs
+    pub vps_gas: BTreeMap<Address, Gas>,
+    /// The gas used per section of the execution of each of the VPs, if the
+    /// transaction was profiled
+    pub vps_gas_profile: BTreeMap<Address, GasProfile>,
     /// Errors occurred in any of the VPs, if any
     pub errors: Vec<(Address, String)>,
     /// Sentinel to signal an invalid transaction signature
@@ -112,6 +129,99 @@ impl fmt::Display for VpsResult {
     }
 }
 
+/// The result of simulating a transaction on top of the last committed state.
+/// Nothing is written to the storage.
+#[derive(Clone, Debug, Default, BorshSerialize, BorshDeserialize)]
+pub struct TxSimulation {
+    /// The result of applying the inner transaction, as in a dry run
+    pub result: TxResult,
+    /// The previous and the new values of all the storage keys changed by the
+    /// wrapper and the inner transaction. The changes of an inner transaction
+    /// rejected by the VPs are not included, as they would be dropped.
+    pub state_diff: BTreeMap<storage::Key, KeyDiff>,
+    /// The outcome of each of the VPs triggered by the inner transaction
+    pub vps: Vec<VpOutcome>,
+    /// The fee that the wrapper transaction would charge, if the simulated
+    /// transaction is wrapped
+    pub wrapper_fee: Option<WrapperFee>,
+}
+
+/// The previous and the new value of a storage key changed by a transaction.
+/// `None` stands for a key that doesn't exist.
+#[derive(
+    Clone, Debug, Default, PartialEq, BorshSerialize, BorshDeserialize,
+)]
+pub struct KeyDiff {
+    /// The value before the transaction
+    pub pre: Option<Vec<u8>>,
+    /// The value after the transaction
+    pub post: Option<Vec<u8>>,
+}
+
+/// The outcome of a VP triggered by a transaction
+#[derive(Clone, Debug, PartialEq, BorshSerialize, BorshDeserialize)]
+pub struct VpOutcome {
+    /// The owner of the VP
+    pub address: Address,
+    /// Whether the VP accepted the transaction
+    pub accepted: bool,
+    /// The gas used by the VP
+    pub gas_used: Gas,
+    /// The error that made the VP reject the transaction, if any
+    pub error: Option<String>,
+}
+
+/// The fee charged by a wrapper transaction
+#[derive(Clone, Debug, PartialEq, BorshSerialize, BorshDeserialize)]
+pub struct WrapperFee {
+    /// The token in which the fee is paid
+    pub token: Address,
+    /// The amount of the fee
+    pub amount: Amount,
+}
+
+/// An estimate of the gas limit and of the gas price of a transaction
+#[derive(Clone, Debug, Default, BorshSerialize, BorshDeserialize)]
+pub struct FeeEstimate {
+    /// The result of the dry run of the transaction the estimate is based on
+    pub result: TxResult,
+    /// The suggested gas limit, i.e. the gas used by the dry run with a safety
+    /// margin on top
+    pub gas_limit: GasLimit,
+    /// The average ratio of the gas used in the recent blocks to the maximum
+    /// gas of a block
+    pub block_utilization: Dec,
+    /// The suggested price per gas unit for each of the tokens accepted for
+    /// fee payment
+    pub gas_prices: BTreeMap<Address, Amount>,
+}
+
+impl VpsResult {
+    /// The outcome of each of the triggered VPs, ordered by address
+    pub fn outcomes(&self) -> Vec<VpOutcome> {
+        self.accepted_vps
+            .iter()
+            .chain(&self.rejected_vps)
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .map(|address| VpOutcome {
+                address: address.clone(),
+                accepted: self.accepted_vps.contains(address),
+                gas_used: self
+                    .vps_gas
+                    .get(address)
+                    .copied()
+                    .unwrap_or_default(),
+                error: self
+                    .errors
+                    .iter()
+                    .find(|(addr, _)| addr == address)
+                    .map(|(_, err)| err.clone()),
+            })
+            .collect()
+    }
+}
+
 /// Format all the values of the given iterator into a string
 fn iterable_to_string<T: fmt::Display>(
     label: &str,
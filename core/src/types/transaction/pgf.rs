@@ -30,3 +30,20 @@ pub struct UpdateStewardCommission {
     /// The new commission distribution
     pub commission: HashMap<Address, Dec>,
 }
+
+/// A tx data type to pause a continuous pgf funding on behalf of a steward
+#[derive(
+    Debug,
+    Clone,
+    PartialEq,
+    BorshSerialize,
+    BorshDeserialize,
+    Serialize,
+    Deserialize,
+)]
+pub struct PausePgfFunding {
+    /// The pgf steward address
+    pub steward: Address,
+    /// The funding key of the recipient of the funding
+    pub funding: String,
+}
@@ -0,0 +1,147 @@
+use std::fmt::Display;
+
+use borsh::{BorshDeserialize, BorshSchema, BorshSerialize};
+use serde::{Deserialize, Serialize};
+
+use crate::proto::Tx;
+use crate::types::address::Address;
+use crate::types::storage::{BlockHeight, Epoch};
+use crate::types::token::Amount;
+use crate::types::transaction::{Fee, GasLimit};
+
+/// When a scheduled transaction is due for execution
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    BorshSerialize,
+    BorshDeserialize,
+    BorshSchema,
+    Serialize,
+    Deserialize,
+)]
+pub enum Schedule {
+    /// At the end of the block at the given height
+    AtHeight(BlockHeight),
+    /// At the end of the first block of the given epoch
+    AtEpoch(Epoch),
+}
+
+impl Schedule {
+    /// Check if the schedule is strictly after the given block height and
+    /// epoch, i.e. it can still be reached
+    pub fn is_after(&self, height: BlockHeight, epoch: Epoch) -> bool {
+        match self {
+            Schedule::AtHeight(at) => *at > height,
+            Schedule::AtEpoch(at) => *at > epoch,
+        }
+    }
+}
+
+impl Display for Schedule {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Schedule::AtHeight(height) => write!(f, "at height {height}"),
+            Schedule::AtEpoch(epoch) => write!(f, "at epoch {epoch}"),
+        }
+    }
+}
+
+/// A tx data type to schedule a signed inner transaction for execution by
+/// the protocol, escrowing the fees for it. It's stored as is until it
+/// becomes due or it's cancelled.
+#[derive(
+    Debug,
+    Clone,
+    BorshSerialize,
+    BorshDeserialize,
+    BorshSchema,
+    Serialize,
+    Deserialize,
+)]
+pub struct ScheduleTx {
+    /// The address that pays the fees and may cancel the scheduled tx
+    pub owner: Address,
+    /// When the tx is due for execution
+    pub schedule: Schedule,
+    /// The fee paid per unit of gas used by the execution
+    pub fee: Fee,
+    /// The maximum amount of gas the execution may use
+    pub gas_limit: GasLimit,
+    /// The signed inner transaction to execute
+    pub tx: Tx,
+}
+
+impl ScheduleTx {
+    /// The amount of fees escrowed for the execution, i.e. the fees for the
+    /// whole gas limit. Returns `None` on overflow.
+    pub fn escrowed_fees(&self) -> Option<Amount> {
+        self.fee
+            .amount_per_gas_unit
+            .checked_mul(self.gas_limit.into())
+    }
+
+    /// The fees due for an execution that used the given amount of whole gas
+    /// units, capped by the escrowed fees. Returns `None` on overflow.
+    pub fn fees_for_gas(&self, gas_used: u64) -> Option<Amount> {
+        let gas_used = gas_used.min(u64::from(self.gas_limit));
+        self.fee
+            .amount_per_gas_unit
+            .checked_mul(Amount::from_u64(gas_used))
+    }
+}
+
+/// A tx data type to cancel a scheduled transaction and get the escrowed
+/// fees refunded
+#[derive(
+    Debug,
+    Clone,
+    PartialEq,
+    Eq,
+    BorshSerialize,
+    BorshDeserialize,
+    BorshSchema,
+    Serialize,
+    Deserialize,
+)]
+pub struct CancelScheduledTx {
+    /// The id of the scheduled tx
+    pub id: u64,
+    /// The owner of the scheduled tx
+    pub owner: Address,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::address::nam;
+
+    #[test]
+    fn test_scheduled_tx_fees() {
+        let scheduled = ScheduleTx {
+            owner: nam(),
+            schedule: Schedule::AtHeight(BlockHeight(10)),
+            fee: Fee {
+                amount_per_gas_unit: Amount::from_u64(2),
+                token: nam(),
+            },
+            gas_limit: GasLimit::from(20_000),
+            tx: Tx::default(),
+        };
+        assert_eq!(scheduled.escrowed_fees(), Some(Amount::from_u64(40_000)));
+        assert_eq!(
+            scheduled.fees_for_gas(1_500),
+            Some(Amount::from_u64(3_000))
+        );
+        // The fees are capped by the gas limit
+        assert_eq!(scheduled.fees_for_gas(30_000), scheduled.escrowed_fees());
+
+        assert!(scheduled.schedule.is_after(BlockHeight(9), Epoch(100)));
+        assert!(!scheduled.schedule.is_after(BlockHeight(10), Epoch(0)));
+        assert!(
+            Schedule::AtEpoch(Epoch(2)).is_after(BlockHeight(100), Epoch(1))
+        );
+    }
+}
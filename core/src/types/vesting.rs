@@ -0,0 +1,184 @@
+//! Vesting schedules of the tokens locked in an account
+
+use borsh::{BorshDeserialize, BorshSchema, BorshSerialize};
+use namada_macros::StorageKeys;
+use serde::{Deserialize, Serialize};
+
+use super::address::Address;
+use super::storage::{DbKeySeg, Key};
+use super::time::DateTimeUtc;
+use super::token::Amount;
+use super::uint::Uint;
+
+/// Storage keys for vesting.
+#[derive(StorageKeys)]
+struct Keys {
+    vesting: &'static str,
+}
+
+/// When the locked tokens become transferable
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    BorshSerialize,
+    BorshDeserialize,
+    BorshSchema,
+    Serialize,
+    Deserialize,
+)]
+pub enum VestingSchedule {
+    /// All the tokens are unlocked at once at the given time
+    Cliff {
+        /// The time from which the tokens are unlocked
+        unlock: DateTimeUtc,
+    },
+    /// The tokens are unlocked linearly between the given times
+    Linear {
+        /// The time from which the tokens start to be unlocked
+        start: DateTimeUtc,
+        /// The time from which all the tokens are unlocked
+        end: DateTimeUtc,
+    },
+}
+
+/// An amount of a token that is locked in an account until it vests
+#[derive(
+    Debug,
+    Clone,
+    PartialEq,
+    Eq,
+    BorshSerialize,
+    BorshDeserialize,
+    BorshSchema,
+    Serialize,
+    Deserialize,
+)]
+pub struct Vesting {
+    /// The vesting token
+    pub token: Address,
+    /// The total amount of the token that vests
+    pub amount: Amount,
+    /// The vesting schedule
+    pub schedule: VestingSchedule,
+}
+
+impl Vesting {
+    /// The amount of the token that is still locked at the given time. A
+    /// partially vested amount is rounded up, in favour of the lock.
+    pub fn locked_at(&self, time: DateTimeUtc) -> Amount {
+        match self.schedule {
+            VestingSchedule::Cliff { unlock } => {
+                if time < unlock {
+                    self.amount
+                } else {
+                    Amount::zero()
+                }
+            }
+            VestingSchedule::Linear { start, end } => {
+                if time >= end {
+                    Amount::zero()
+                } else if time <= start {
+                    self.amount
+                } else {
+                    let remaining = (end.0 - time.0).num_seconds() as u64;
+                    let duration = (end.0 - start.0).num_seconds() as u64;
+                    self.amount
+                        .raw_amount()
+                        .checked_mul_div(remaining.into(), duration.into())
+                        .and_then(|(locked, rem)| {
+                            let locked = if rem.is_zero() {
+                                locked
+                            } else {
+                                locked + Uint::one()
+                            };
+                            Amount::from_uint(locked, 0).ok()
+                        })
+                        .unwrap_or(self.amount)
+                }
+            }
+        }
+    }
+
+    /// Check if all the tokens are unlocked at the given time
+    pub fn is_vested(&self, time: DateTimeUtc) -> bool {
+        self.locked_at(time).is_zero()
+    }
+}
+
+/// Obtain the storage key of the vesting schedule of an account
+pub fn vesting_key(owner: &Address) -> Key {
+    Key {
+        segments: vec![
+            DbKeySeg::AddressSeg(owner.to_owned()),
+            DbKeySeg::StringSeg(Keys::VALUES.vesting.to_string()),
+        ],
+    }
+}
+
+/// Check if the given storage key is a vesting schedule key. If it is,
+/// returns the owner.
+pub fn is_vesting_key(key: &Key) -> Option<&Address> {
+    match &key.segments[..] {
+        [DbKeySeg::AddressSeg(owner), DbKeySeg::StringSeg(prefix)]
+            if prefix.as_str() == Keys::VALUES.vesting =>
+        {
+            Some(owner)
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{TimeZone, Utc};
+
+    use super::*;
+    use crate::types::address::nam;
+
+    fn at(secs: i64) -> DateTimeUtc {
+        Utc.timestamp_opt(secs, 0).single().unwrap().into()
+    }
+
+    #[test]
+    fn test_vesting_locked_amount() {
+        let start = at(1_000);
+        let end = at(1_300);
+
+        let linear = Vesting {
+            token: nam(),
+            amount: Amount::from_uint(1_000, 0).unwrap(),
+            schedule: VestingSchedule::Linear { start, end },
+        };
+        assert_eq!(linear.locked_at(at(0)), linear.amount);
+        assert_eq!(linear.locked_at(start), linear.amount);
+        assert_eq!(
+            linear.locked_at(at(1_150)),
+            Amount::from_uint(500, 0).unwrap()
+        );
+        // Rounded up in favour of the lock
+        assert_eq!(
+            linear.locked_at(at(1_100)),
+            Amount::from_uint(667, 0).unwrap()
+        );
+        assert!(!linear.is_vested(at(1_299)));
+        assert!(linear.is_vested(end));
+
+        let cliff = Vesting {
+            schedule: VestingSchedule::Cliff { unlock: end },
+            ..linear
+        };
+        assert_eq!(cliff.locked_at(at(1_299)), cliff.amount);
+        assert!(cliff.is_vested(end));
+    }
+
+    #[test]
+    fn test_vesting_key() {
+        let owner = nam();
+        let key = vesting_key(&owner);
+        assert_eq!(is_vesting_key(&key), Some(&owner));
+        assert_eq!(is_vesting_key(&Key::validity_predicate(&owner)), None);
+    }
+}
@@ -1,12 +1,15 @@
 //! Structures encapsulating SDK arguments
 
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::path::PathBuf;
 use std::time::Duration as StdDuration;
 
 use namada_core::ledger::governance::cli::onchain::{
-    DefaultProposal, PgfFundingProposal, PgfStewardProposal,
+    DefaultProposal, ParameterChangeProposal, PgfFundingProposal,
+    PgfStewardProposal, UpgradeProposal,
 };
+use namada_core::ledger::governance::storage::proposal::ProposalKind;
+use namada_core::types::account::KeyPolicy;
 use namada_core::types::address::Address;
 use namada_core::types::chain::ChainId;
 use namada_core::types::dec::Dec;
@@ -17,12 +20,15 @@ use namada_core::types::masp::MaspValue;
 use namada_core::types::storage::Epoch;
 use namada_core::types::time::DateTimeUtc;
 use namada_core::types::transaction::GasLimit;
+use namada_core::types::transaction::scheduler::Schedule;
+use namada_core::types::vesting::VestingSchedule;
 use namada_core::types::{storage, token};
 use serde::{Deserialize, Serialize};
 use zeroize::Zeroizing;
 
 use crate::eth_bridge::bridge_pool;
 use crate::ibc::core::ics24_host::identifier::{ChannelId, PortId};
+use crate::signing::remote::ExternalSigner;
 use crate::signing::SigningTxData;
 use crate::{rpc, tx, Namada};
 
@@ -67,6 +73,8 @@ pub trait NamadaTypes: Clone + std::fmt::Debug {
     type Data: Clone + std::fmt::Debug;
     /// Bridge pool recommendations conversion rates table.
     type BpConversionTable: Clone + std::fmt::Debug;
+    /// The policies restricting the public keys of an account
+    type KeyPolicies: Clone + std::fmt::Debug;
 }
 
 /// The concrete types being used in Namada SDK
@@ -90,6 +98,7 @@ impl NamadaTypes for SdkTypes {
     type BpConversionTable = HashMap<Address, BpConversionTableEntry>;
     type Data = Vec<u8>;
     type EthereumAddress = ();
+    type KeyPolicies = BTreeMap<common::PublicKey, KeyPolicy>;
     type Keypair = namada_core::types::key::common::SecretKey;
     type NativeAddress = Address;
     type PublicKey = namada_core::types::key::common::PublicKey;
@@ -225,6 +234,8 @@ pub struct TxTransfer<C: NamadaTypes = SdkTypes> {
     pub amount: InputAmount,
     /// Native token address
     pub native_token: C::NativeAddress,
+    /// Memo attached to the transaction
+    pub memo: Option<String>,
     /// Path to the TX WASM code file
     pub tx_code_path: PathBuf,
 }
@@ -270,6 +281,14 @@ impl<C: NamadaTypes> TxTransfer<C> {
         }
     }
 
+    /// Memo attached to the transaction
+    pub fn memo(self, memo: String) -> Self {
+        Self {
+            memo: Some(memo),
+            ..self
+        }
+    }
+
     /// Path to the TX WASM code file
     pub fn tx_code_path(self, tx_code_path: PathBuf) -> Self {
         Self {
@@ -419,6 +438,10 @@ pub struct InitProposal<C: NamadaTypes = SdkTypes> {
     pub is_pgf_stewards: bool,
     /// Flag if proposal is of type Pgf funding
     pub is_pgf_funding: bool,
+    /// Flag if proposal is of type parameter change
+    pub is_parameter_change: bool,
+    /// Flag if proposal is of type software upgrade
+    pub is_upgrade: bool,
     /// Path to the tx WASM file
     pub tx_code_path: PathBuf,
 }
@@ -473,6 +496,19 @@ impl<C: NamadaTypes> InitProposal<C> {
         }
     }
 
+    /// Flag if proposal is of type parameter change
+    pub fn is_parameter_change(self, is_parameter_change: bool) -> Self {
+        Self {
+            is_parameter_change,
+            ..self
+        }
+    }
+
+    /// Flag if proposal is of type software upgrade
+    pub fn is_upgrade(self, is_upgrade: bool) -> Self {
+        Self { is_upgrade, ..self }
+    }
+
     /// Path to the tx WASM file
     pub fn tx_code_path(self, tx_code_path: PathBuf) -> Self {
         Self {
@@ -536,6 +572,67 @@ impl InitProposal {
                 })?;
 
             tx::build_pgf_stewards_proposal(context, self, proposal).await
+        } else if self.is_parameter_change {
+            let proposal = ParameterChangeProposal::try_from(
+                self.proposal_data.as_ref(),
+            )
+            .map_err(|e| {
+                crate::error::TxError::FailedGovernaneProposalDeserialize(
+                    e.to_string(),
+                )
+            })?;
+            let nam_address = context.native_token();
+            let author_balance = rpc::get_token_balance(
+                context.client(),
+                &nam_address,
+                &proposal.proposal.author,
+            )
+            .await?;
+            let proposal = proposal
+                .validate(
+                    &governance_parameters,
+                    current_epoch,
+                    author_balance,
+                    self.tx.force,
+                )
+                .map_err(|e| {
+                    crate::error::TxError::InvalidProposal(e.to_string())
+                })?;
+
+            tx::build_parameter_change_proposal(context, self, proposal).await
+        } else if self.is_upgrade {
+            let proposal = UpgradeProposal::try_from(
+                self.proposal_data.as_ref(),
+            )
+            .map_err(|e| {
+                crate::error::TxError::FailedGovernaneProposalDeserialize(
+                    e.to_string(),
+                )
+            })?;
+            let current_height = rpc::query_block(context.client())
+                .await?
+                .map(|block| block.height)
+                .unwrap_or_default();
+            let nam_address = context.native_token();
+            let author_balance = rpc::get_token_balance(
+                context.client(),
+                &nam_address,
+                &proposal.proposal.author,
+            )
+            .await?;
+            let proposal = proposal
+                .validate(
+                    &governance_parameters,
+                    current_epoch,
+                    current_height,
+                    author_balance,
+                    self.tx.force,
+                )
+                .map_err(|e| {
+                    crate::error::TxError::InvalidProposal(e.to_string())
+                })?;
+
+            tx::build_upgrade_proposal(context, self, proposal).await
         } else {
             let proposal = DefaultProposal::try_from(
                 self.proposal_data.as_ref(),
@@ -651,6 +748,152 @@ impl VoteProposal {
     }
 }
 
+/// Transaction to delegate the governance votes of an address
+#[derive(Clone, Debug)]
+pub struct DelegateVotes<C: NamadaTypes = SdkTypes> {
+    /// Common tx arguments
+    pub tx: Tx<C>,
+    /// The address delegating its votes
+    pub delegator: C::Address,
+    /// The address voting on behalf of the delegator, or `None` to revoke
+    /// the delegation
+    pub delegate: Option<C::Address>,
+    /// The kind of proposals the delegation is restricted to, if any
+    pub proposal_kind: Option<ProposalKind>,
+    /// Path to the TX WASM code file
+    pub tx_code_path: PathBuf,
+}
+
+impl<C: NamadaTypes> TxBuilder<C> for DelegateVotes<C> {
+    fn tx<F>(self, func: F) -> Self
+    where
+        F: FnOnce(Tx<C>) -> Tx<C>,
+    {
+        DelegateVotes {
+            tx: func(self.tx),
+            ..self
+        }
+    }
+}
+
+impl<C: NamadaTypes> DelegateVotes<C> {
+    /// The address delegating its votes
+    pub fn delegator(self, delegator: C::Address) -> Self {
+        Self { delegator, ..self }
+    }
+
+    /// The address voting on behalf of the delegator
+    pub fn delegate(self, delegate: C::Address) -> Self {
+        Self {
+            delegate: Some(delegate),
+            ..self
+        }
+    }
+
+    /// The kind of proposals the delegation is restricted to
+    pub fn proposal_kind(self, proposal_kind: ProposalKind) -> Self {
+        Self {
+            proposal_kind: Some(proposal_kind),
+            ..self
+        }
+    }
+
+    /// Path to the TX WASM code file
+    pub fn tx_code_path(self, tx_code_path: PathBuf) -> Self {
+        Self {
+            tx_code_path,
+            ..self
+        }
+    }
+}
+
+impl DelegateVotes {
+    /// Build a transaction from this builder
+    pub async fn build<'a>(
+        &self,
+        context: &impl Namada<'a>,
+    ) -> crate::error::Result<(crate::proto::Tx, SigningTxData, Option<Epoch>)>
+    {
+        tx::build_delegate_votes(context, self).await
+    }
+}
+
+/// Transaction to top up the deposit of a governance proposal
+#[derive(Clone, Debug)]
+pub struct DepositProposal<C: NamadaTypes = SdkTypes> {
+    /// Common tx arguments
+    pub tx: Tx<C>,
+    /// The proposal id
+    pub proposal_id: u64,
+    /// The address depositing the tokens
+    pub depositor: C::Address,
+    /// The amount of native tokens to deposit
+    pub amount: token::Amount,
+    /// Native token address
+    pub native_token: C::NativeAddress,
+    /// Path to the TX WASM code file
+    pub tx_code_path: PathBuf,
+}
+
+impl<C: NamadaTypes> TxBuilder<C> for DepositProposal<C> {
+    fn tx<F>(self, func: F) -> Self
+    where
+        F: FnOnce(Tx<C>) -> Tx<C>,
+    {
+        DepositProposal {
+            tx: func(self.tx),
+            ..self
+        }
+    }
+}
+
+impl<C: NamadaTypes> DepositProposal<C> {
+    /// The proposal id
+    pub fn proposal_id(self, proposal_id: u64) -> Self {
+        Self {
+            proposal_id,
+            ..self
+        }
+    }
+
+    /// The address depositing the tokens
+    pub fn depositor(self, depositor: C::Address) -> Self {
+        Self { depositor, ..self }
+    }
+
+    /// The amount of native tokens to deposit
+    pub fn amount(self, amount: token::Amount) -> Self {
+        Self { amount, ..self }
+    }
+
+    /// Native token address
+    pub fn native_token(self, native_token: C::NativeAddress) -> Self {
+        Self {
+            native_token,
+            ..self
+        }
+    }
+
+    /// Path to the TX WASM code file
+    pub fn tx_code_path(self, tx_code_path: PathBuf) -> Self {
+        Self {
+            tx_code_path,
+            ..self
+        }
+    }
+}
+
+impl DepositProposal {
+    /// Build a transaction from this builder
+    pub async fn build<'a>(
+        &self,
+        context: &impl Namada<'a>,
+    ) -> crate::error::Result<(crate::proto::Tx, SigningTxData, Option<Epoch>)>
+    {
+        tx::build_deposit_proposal(context, self).await
+    }
+}
+
 /// Transaction to initialize a new account
 #[derive(Clone, Debug)]
 pub struct TxInitAccount<C: NamadaTypes = SdkTypes> {
@@ -664,6 +907,19 @@ pub struct TxInitAccount<C: NamadaTypes = SdkTypes> {
     pub public_keys: Vec<C::PublicKey>,
     /// The account multisignature threshold
     pub threshold: Option<u8>,
+    /// The optional vesting schedule of the tokens locked in the new account
+    pub vesting: Option<AccountVesting<C>>,
+}
+
+/// The vesting schedule of the tokens locked in a new account
+#[derive(Clone, Debug)]
+pub struct AccountVesting<C: NamadaTypes = SdkTypes> {
+    /// The vesting token
+    pub token: C::Address,
+    /// The total amount of the token that vests
+    pub amount: InputAmount,
+    /// When the tokens become transferable
+    pub schedule: VestingSchedule,
 }
 
 impl<C: NamadaTypes> TxBuilder<C> for TxInitAccount<C> {
@@ -695,6 +951,16 @@ impl<C: NamadaTypes> TxInitAccount<C> {
         }
     }
 
+    /// Lock tokens in the new account until they vest. This also sets the
+    /// vesting VP, which enforces the schedule, as the account's VP.
+    pub fn vesting(self, vesting: AccountVesting<C>) -> Self {
+        Self {
+            vesting: Some(vesting),
+            vp_code_path: PathBuf::from(tx::VP_VESTING_WASM),
+            ..self
+        }
+    }
+
     /// Path to the VP WASM code file
     pub fn vp_code_path(self, vp_code_path: PathBuf) -> Self {
         Self {
@@ -777,6 +1043,8 @@ pub struct TxUpdateAccount<C: NamadaTypes = SdkTypes> {
     pub public_keys: Vec<C::PublicKey>,
     /// The account threshold
     pub threshold: Option<u8>,
+    /// The policies restricting the public keys, replacing the existing ones
+    pub key_policies: Option<C::KeyPolicies>,
 }
 
 impl<C: NamadaTypes> TxBuilder<C> for TxUpdateAccount<C> {
@@ -828,6 +1096,14 @@ impl<C: NamadaTypes> TxUpdateAccount<C> {
             ..self
         }
     }
+
+    /// The policies restricting the public keys, replacing the existing ones
+    pub fn key_policies(self, key_policies: C::KeyPolicies) -> Self {
+        Self {
+            key_policies: Some(key_policies),
+            ..self
+        }
+    }
 }
 
 impl TxUpdateAccount {
@@ -1109,6 +1385,36 @@ pub struct QueryProposal<C: NamadaTypes = SdkTypes> {
     pub proposal_id: Option<u64>,
 }
 
+/// Query the pending scheduled txs of an owner
+#[derive(Clone, Debug)]
+pub struct QueryScheduledTxs<C: NamadaTypes = SdkTypes> {
+    /// Common query args
+    pub query: Query<C>,
+    /// The owner of the scheduled txs
+    pub owner: C::Address,
+}
+
+/// Query the fee allowances granted by a granter
+#[derive(Clone, Debug)]
+pub struct QueryFeeAllowances<C: NamadaTypes = SdkTypes> {
+    /// Common query args
+    pub query: Query<C>,
+    /// The address paying the fees
+    pub granter: C::Address,
+    /// The address whose wrapper fees are paid, if only its allowance should
+    /// be queried
+    pub grantee: Option<C::Address>,
+}
+
+/// Query the governance vote delegations of an address
+#[derive(Clone, Debug)]
+pub struct QueryVoteDelegations<C: NamadaTypes = SdkTypes> {
+    /// Common query args
+    pub query: Query<C>,
+    /// The address delegating or receiving the votes
+    pub address: C::Address,
+}
+
 /// Query protocol parameters
 #[derive(Clone, Debug)]
 pub struct QueryProtocolParameters<C: NamadaTypes = SdkTypes> {
@@ -1577,6 +1883,338 @@ impl ResignSteward {
     }
 }
 
+#[derive(Clone, Debug)]
+/// Pause pgf funding args
+pub struct PausePgfFunding<C: NamadaTypes = SdkTypes> {
+    /// Common tx arguments
+    pub tx: Tx<C>,
+    /// Steward address
+    pub steward: C::Address,
+    /// The funding key of the recipient of the funding to pause
+    pub funding: String,
+    /// Path to the TX WASM code file
+    pub tx_code_path: PathBuf,
+}
+
+impl<C: NamadaTypes> TxBuilder<C> for PausePgfFunding<C> {
+    fn tx<F>(self, func: F) -> Self
+    where
+        F: FnOnce(Tx<C>) -> Tx<C>,
+    {
+        PausePgfFunding {
+            tx: func(self.tx),
+            ..self
+        }
+    }
+}
+
+impl<C: NamadaTypes> PausePgfFunding<C> {
+    /// Steward address
+    pub fn steward(self, steward: C::Address) -> Self {
+        Self { steward, ..self }
+    }
+
+    /// The funding key of the recipient of the funding to pause
+    pub fn funding(self, funding: String) -> Self {
+        Self { funding, ..self }
+    }
+
+    /// Path to the TX WASM code file
+    pub fn tx_code_path(self, tx_code_path: PathBuf) -> Self {
+        Self {
+            tx_code_path,
+            ..self
+        }
+    }
+}
+
+impl PausePgfFunding {
+    /// Build a transaction from this builder
+    pub async fn build<'a>(
+        &self,
+        context: &impl Namada<'a>,
+    ) -> crate::error::Result<(crate::proto::Tx, SigningTxData, Option<Epoch>)>
+    {
+        tx::build_pause_pgf_funding(context, self).await
+    }
+}
+
+#[derive(Clone, Debug)]
+/// Schedule tx args
+pub struct ScheduleTx<C: NamadaTypes = SdkTypes> {
+    /// Common tx arguments
+    pub tx: Tx<C>,
+    /// The owner of the scheduled tx, paying for its execution
+    pub owner: C::Address,
+    /// The serialized tx to schedule
+    pub scheduled_tx: C::Data,
+    /// The signatures of the tx to schedule
+    pub scheduled_signatures: Vec<C::Data>,
+    /// When to execute the scheduled tx
+    pub schedule: Schedule,
+    /// The max amount of gas used to execute the scheduled tx
+    pub scheduled_gas_limit: GasLimit,
+    /// Path to the TX WASM code file
+    pub tx_code_path: PathBuf,
+}
+
+impl<C: NamadaTypes> TxBuilder<C> for ScheduleTx<C> {
+    fn tx<F>(self, func: F) -> Self
+    where
+        F: FnOnce(Tx<C>) -> Tx<C>,
+    {
+        ScheduleTx {
+            tx: func(self.tx),
+            ..self
+        }
+    }
+}
+
+impl<C: NamadaTypes> ScheduleTx<C> {
+    /// The owner of the scheduled tx
+    pub fn owner(self, owner: C::Address) -> Self {
+        Self { owner, ..self }
+    }
+
+    /// The signatures of the tx to schedule
+    pub fn scheduled_signatures(
+        self,
+        scheduled_signatures: Vec<C::Data>,
+    ) -> Self {
+        Self {
+            scheduled_signatures,
+            ..self
+        }
+    }
+
+    /// When to execute the scheduled tx
+    pub fn schedule(self, schedule: Schedule) -> Self {
+        Self { schedule, ..self }
+    }
+
+    /// The max amount of gas used to execute the scheduled tx
+    pub fn scheduled_gas_limit(self, scheduled_gas_limit: GasLimit) -> Self {
+        Self {
+            scheduled_gas_limit,
+            ..self
+        }
+    }
+
+    /// Path to the TX WASM code file
+    pub fn tx_code_path(self, tx_code_path: PathBuf) -> Self {
+        Self {
+            tx_code_path,
+            ..self
+        }
+    }
+}
+
+impl ScheduleTx {
+    /// Build a transaction from this builder
+    pub async fn build<'a>(
+        &self,
+        context: &impl Namada<'a>,
+    ) -> crate::error::Result<(crate::proto::Tx, SigningTxData, Option<Epoch>)>
+    {
+        tx::build_schedule_tx(context, self).await
+    }
+}
+
+#[derive(Clone, Debug)]
+/// Cancel scheduled tx args
+pub struct CancelScheduledTx<C: NamadaTypes = SdkTypes> {
+    /// Common tx arguments
+    pub tx: Tx<C>,
+    /// The id of the scheduled tx
+    pub id: u64,
+    /// The owner of the scheduled tx
+    pub owner: C::Address,
+    /// Path to the TX WASM code file
+    pub tx_code_path: PathBuf,
+}
+
+impl<C: NamadaTypes> TxBuilder<C> for CancelScheduledTx<C> {
+    fn tx<F>(self, func: F) -> Self
+    where
+        F: FnOnce(Tx<C>) -> Tx<C>,
+    {
+        CancelScheduledTx {
+            tx: func(self.tx),
+            ..self
+        }
+    }
+}
+
+impl<C: NamadaTypes> CancelScheduledTx<C> {
+    /// The id of the scheduled tx
+    pub fn id(self, id: u64) -> Self {
+        Self { id, ..self }
+    }
+
+    /// The owner of the scheduled tx
+    pub fn owner(self, owner: C::Address) -> Self {
+        Self { owner, ..self }
+    }
+
+    /// Path to the TX WASM code file
+    pub fn tx_code_path(self, tx_code_path: PathBuf) -> Self {
+        Self {
+            tx_code_path,
+            ..self
+        }
+    }
+}
+
+impl CancelScheduledTx {
+    /// Build a transaction from this builder
+    pub async fn build<'a>(
+        &self,
+        context: &impl Namada<'a>,
+    ) -> crate::error::Result<(crate::proto::Tx, SigningTxData, Option<Epoch>)>
+    {
+        tx::build_cancel_scheduled_tx(context, self).await
+    }
+}
+
+#[derive(Clone, Debug)]
+/// Grant fee allowance args
+pub struct GrantFeeAllowance<C: NamadaTypes = SdkTypes> {
+    /// Common tx arguments
+    pub tx: Tx<C>,
+    /// The address paying the fees
+    pub granter: C::Address,
+    /// The address whose wrapper fees are paid
+    pub grantee: C::Address,
+    /// The fee token
+    pub token: C::Address,
+    /// The maximum amount of fees paid in the token
+    pub spend_limit: InputAmount,
+    /// The first epoch in which the allowance can no longer be used
+    pub expiration: Option<Epoch>,
+    /// Path to the TX WASM code file
+    pub tx_code_path: PathBuf,
+}
+
+impl<C: NamadaTypes> TxBuilder<C> for GrantFeeAllowance<C> {
+    fn tx<F>(self, func: F) -> Self
+    where
+        F: FnOnce(Tx<C>) -> Tx<C>,
+    {
+        GrantFeeAllowance {
+            tx: func(self.tx),
+            ..self
+        }
+    }
+}
+
+impl<C: NamadaTypes> GrantFeeAllowance<C> {
+    /// The address paying the fees
+    pub fn granter(self, granter: C::Address) -> Self {
+        Self { granter, ..self }
+    }
+
+    /// The address whose wrapper fees are paid
+    pub fn grantee(self, grantee: C::Address) -> Self {
+        Self { grantee, ..self }
+    }
+
+    /// The fee token
+    pub fn token(self, token: C::Address) -> Self {
+        Self { token, ..self }
+    }
+
+    /// The maximum amount of fees paid in the token
+    pub fn spend_limit(self, spend_limit: InputAmount) -> Self {
+        Self {
+            spend_limit,
+            ..self
+        }
+    }
+
+    /// The first epoch in which the allowance can no longer be used
+    pub fn expiration(self, expiration: Epoch) -> Self {
+        Self {
+            expiration: Some(expiration),
+            ..self
+        }
+    }
+
+    /// Path to the TX WASM code file
+    pub fn tx_code_path(self, tx_code_path: PathBuf) -> Self {
+        Self {
+            tx_code_path,
+            ..self
+        }
+    }
+}
+
+impl GrantFeeAllowance {
+    /// Build a transaction from this builder
+    pub async fn build<'a>(
+        &self,
+        context: &impl Namada<'a>,
+    ) -> crate::error::Result<(crate::proto::Tx, SigningTxData, Option<Epoch>)>
+    {
+        tx::build_grant_fee_allowance(context, self).await
+    }
+}
+
+#[derive(Clone, Debug)]
+/// Revoke fee allowance args
+pub struct RevokeFeeAllowance<C: NamadaTypes = SdkTypes> {
+    /// Common tx arguments
+    pub tx: Tx<C>,
+    /// The address paying the fees
+    pub granter: C::Address,
+    /// The address whose wrapper fees are paid
+    pub grantee: C::Address,
+    /// Path to the TX WASM code file
+    pub tx_code_path: PathBuf,
+}
+
+impl<C: NamadaTypes> TxBuilder<C> for RevokeFeeAllowance<C> {
+    fn tx<F>(self, func: F) -> Self
+    where
+        F: FnOnce(Tx<C>) -> Tx<C>,
+    {
+        RevokeFeeAllowance {
+            tx: func(self.tx),
+            ..self
+        }
+    }
+}
+
+impl<C: NamadaTypes> RevokeFeeAllowance<C> {
+    /// The address paying the fees
+    pub fn granter(self, granter: C::Address) -> Self {
+        Self { granter, ..self }
+    }
+
+    /// The address whose wrapper fees are paid
+    pub fn grantee(self, grantee: C::Address) -> Self {
+        Self { grantee, ..self }
+    }
+
+    /// Path to the TX WASM code file
+    pub fn tx_code_path(self, tx_code_path: PathBuf) -> Self {
+        Self {
+            tx_code_path,
+            ..self
+        }
+    }
+}
+
+impl RevokeFeeAllowance {
+    /// Build a transaction from this builder
+    pub async fn build<'a>(
+        &self,
+        context: &impl Namada<'a>,
+    ) -> crate::error::Result<(crate::proto::Tx, SigningTxData, Option<Epoch>)>
+    {
+        tx::build_revoke_fee_allowance(context, self).await
+    }
+}
+
 #[derive(Clone, Debug)]
 /// Re-activate a jailed validator args
 pub struct TxUnjailValidator<C: NamadaTypes = SdkTypes> {
@@ -1798,6 +2436,9 @@ pub struct Tx<C: NamadaTypes = SdkTypes> {
     pub dry_run: bool,
     /// Simulate applying both the wrapper and inner transactions
     pub dry_run_wrapper: bool,
+    /// Print the gas used per host function and validity predicate by a dry
+    /// run
+    pub gas_profile: bool,
     /// Dump the transaction bytes to file
     pub dump_tx: bool,
     /// The output directory path to where serialize the data
@@ -1814,7 +2455,8 @@ pub struct Tx<C: NamadaTypes = SdkTypes> {
     /// Whether to force overwrite the above alias, if it is provided, in the
     /// wallet.
     pub wallet_alias_force: bool,
-    /// The amount being payed (for gas unit) to include the transaction
+    /// The amount being payed (for gas unit) to include the transaction. If
+    /// `None`, the gas price is estimated from the recent blocks.
     pub fee_amount: Option<InputAmount>,
     /// The fee payer signing key
     pub wrapper_fee_payer: Option<C::Keypair>,
@@ -1822,8 +2464,12 @@ pub struct Tx<C: NamadaTypes = SdkTypes> {
     pub fee_token: C::Address,
     /// The optional spending key for fee unshielding
     pub fee_unshield: Option<C::TransferSource>,
-    /// The max amount of gas used to process tx
-    pub gas_limit: GasLimit,
+    /// The optional address paying the fees out of the fee allowance it
+    /// granted to the fee payer
+    pub fee_granter: Option<C::Address>,
+    /// The max amount of gas used to process tx. If `None`, it is estimated
+    /// from a dry run of the tx.
+    pub gas_limit: Option<GasLimit>,
     /// The optional expiration of the transaction
     pub expiration: Option<DateTimeUtc>,
     /// Generate an ephimeral signing key to be used only once to sign a
@@ -1843,6 +2489,8 @@ pub struct Tx<C: NamadaTypes = SdkTypes> {
     pub password: Option<Zeroizing<String>>,
     /// Use device to sign the transaction
     pub use_device: bool,
+    /// The external signer holding the signing keys missing from the wallet
+    pub signer: Option<ExternalSigner>,
 }
 
 /// Builder functions for Tx
@@ -1862,6 +2510,11 @@ pub trait TxBuilder<C: NamadaTypes>: Sized {
             ..x
         })
     }
+    /// Print the gas used per host function and validity predicate by a dry
+    /// run
+    fn gas_profile(self, gas_profile: bool) -> Self {
+        self.tx(|x| Tx { gas_profile, ..x })
+    }
     /// Dump the transaction bytes to file
     fn dump_tx(self, dump_tx: bool) -> Self {
         self.tx(|x| Tx { dump_tx, ..x })
@@ -1935,9 +2588,20 @@ pub trait TxBuilder<C: NamadaTypes>: Sized {
             ..x
         })
     }
+    /// The optional address paying the fees out of the fee allowance it
+    /// granted to the fee payer
+    fn fee_granter(self, fee_granter: C::Address) -> Self {
+        self.tx(|x| Tx {
+            fee_granter: Some(fee_granter),
+            ..x
+        })
+    }
     /// The max amount of gas used to process tx
     fn gas_limit(self, gas_limit: GasLimit) -> Self {
-        self.tx(|x| Tx { gas_limit, ..x })
+        self.tx(|x| Tx {
+            gas_limit: Some(gas_limit),
+            ..x
+        })
     }
     /// The optional expiration of the transaction
     fn expiration(self, expiration: DateTimeUtc) -> Self {
@@ -1990,6 +2654,13 @@ pub trait TxBuilder<C: NamadaTypes>: Sized {
             ..x
         })
     }
+    /// The external signer holding the signing keys missing from the wallet
+    fn signer(self, signer: ExternalSigner) -> Self {
+        self.tx(|x| Tx {
+            signer: Some(signer),
+            ..x
+        })
+    }
 }
 
 impl<C: NamadaTypes> TxBuilder<C> for Tx<C> {
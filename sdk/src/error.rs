@@ -4,8 +4,11 @@ use namada_core::proto::Tx;
 use namada_core::types::address::Address;
 use namada_core::types::dec::Dec;
 use namada_core::types::ethereum_events::EthAddress;
+use namada_core::types::key::common;
 use namada_core::types::storage;
 use namada_core::types::storage::Epoch;
+use namada_core::types::time::DateTimeUtc;
+use namada_core::types::transaction::scheduler::Schedule;
 use prost::EncodeError;
 use tendermint_rpc::Error as RpcError;
 use thiserror::Error;
@@ -75,6 +78,9 @@ pub enum EventError {
     /// Missing value in attributes.
     #[error("Attributes missing value: {0}")]
     MissingValue(String),
+    /// Error when parsing an event query
+    #[error("Invalid event query: {0}")]
+    InvalidQuery(String),
 }
 
 /// Errors that deal with querying some kind of data
@@ -174,6 +180,9 @@ pub enum TxError {
     /// The address is not a valid steward
     #[error("The address {0} is not a valid steward.")]
     InvalidSteward(Address),
+    /// The continuous pgf funding doesn't exist
+    #[error("The pgf funding {0} doesn't exist.")]
+    InvalidPgfFunding(String),
     /// Rate of epoch change too large for current epoch
     #[error(
         "New rate, {0}, is too large of a change with respect to the \
@@ -267,6 +276,15 @@ pub enum TxError {
     /// The proposal can't be found
     #[error("Proposal {0} can't be found")]
     ProposalDoesNotExist(u64),
+    /// The voting period of the proposal has started
+    #[error("Proposal {0} can't receive deposits after its voting starts")]
+    InvalidProposalDepositPeriod(u64),
+    /// A validator can't delegate its governance votes
+    #[error("The validator {0} can't delegate its governance votes")]
+    ValidatorVoteDelegation(Address),
+    /// An address can't delegate its governance votes to itself
+    #[error("The address {0} can't delegate its governance votes to itself")]
+    SelfVoteDelegation(Address),
     /// Updating an VP of an implicit account
     #[error(
         "A validity predicate of an implicit address cannot be directly \
@@ -293,6 +311,12 @@ pub enum TxError {
     /// Account threshold is not set
     #[error("Account threshold must be set.")]
     MissingAccountThreshold,
+    /// The linear vesting schedule doesn't end after it starts
+    #[error("The vesting end {1} must be later than its start {0}.")]
+    InvalidVestingSchedule(DateTimeUtc, DateTimeUtc),
+    /// A key policy is given for a public key that the account doesn't have
+    #[error("The public key {0} with a policy is not a key of the account.")]
+    KeyPolicyWithoutKey(common::PublicKey),
     /// Not enough signature
     #[error("Account threshold is {0} but the valid signatures are {1}.")]
     MissingSigningKeys(u8, u8),
@@ -330,6 +354,25 @@ pub enum TxError {
     /// The consensus key is not unique
     #[error("The consensus key has already been registered and is not unique")]
     ConsensusKeyNotUnique,
+    /// The memo is too long
+    #[error("The memo is {0} bytes long, the maximum is {1} bytes")]
+    MemoTooLong(usize, usize),
+    /// The schedule of a tx is not in the future
+    #[error("The schedule {0} is not in the future")]
+    InvalidSchedule(Schedule),
+    /// The scheduled tx doesn't exist or belongs to another owner
+    #[error("There's no pending scheduled tx {0} of {1}")]
+    ScheduledTxNotFound(u64, Address),
+    /// No usable fee allowance was granted to the fee payer
+    #[error("{0} hasn't granted any unexpired fee allowance to {1}")]
+    FeeAllowanceNotFound(Address, Address),
+    /// The fee allowance is lower than the fees
+    #[error(
+        "The fee allowance granted by {0} in token {1} is lower than the \
+         amount required for fees. Amount of the fees is {2} and the \
+         remaining allowance is {3}."
+    )]
+    FeeAllowanceTooLow(Address, Address, String, String),
     /// Other Errors that may show up when using the interface
     #[error("{0}")]
     Other(String),
@@ -98,14 +98,8 @@ pub async fn build_bridge_pool_tx(
     )
     .add_data(transfer);
 
-    let epoch = prepare_tx(
-        context,
-        &tx_args,
-        &mut tx,
-        signing_data.fee_payer.clone(),
-        None,
-    )
-    .await?;
+    let epoch =
+        prepare_tx(context, &tx_args, &mut tx, &signing_data, None).await?;
 
     Ok((tx, signing_data, epoch))
 }
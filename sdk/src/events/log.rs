@@ -3,14 +3,23 @@
 //! The log can only hold `N` events at a time, where `N` is a configurable
 //! parameter. If the log is holding `N` events, and a new event is logged,
 //! old events are pruned.
+//!
+//! The ledger also persists all the events in its event store, so the log
+//! only serves as a cache of the most recent ones.
 
 use std::default::Default;
 
+use borsh::{BorshDeserialize, BorshSerialize};
 use circular_queue::CircularQueue;
 
 use crate::events::Event;
 
 pub mod dumb_queries;
+pub mod query;
+
+/// The maximum number of events that can be requested in a single
+/// [`EventsPage`].
+pub const MAX_EVENTS_PER_PAGE: u64 = 100;
 
 /// Parameters to configure the pruning of the event log.
 #[derive(Debug, Copy, Clone)]
@@ -31,6 +40,16 @@ impl Default for Params {
     }
 }
 
+/// A page of the events of the log matching a query, from the most recent
+/// to the oldest.
+#[derive(Debug, Clone, PartialEq, Eq, BorshSerialize, BorshDeserialize)]
+pub struct EventsPage {
+    /// The matching events of the requested page
+    pub events: Vec<Event>,
+    /// The total number of matching events held in the log
+    pub total: u64,
+}
+
 /// Represents a log of [`Event`] instances emitted by
 /// `FinalizeBlock` calls, in the ledger.
 #[derive(Debug)]
@@ -81,6 +100,36 @@ impl EventLog {
             .iter()
             .filter(move |&event| matcher.matches(event))
     }
+
+    /// Returns an iterator over the events of this [`EventLog`] matching
+    /// the given query, from the most recent.
+    #[inline]
+    pub fn iter_with_query<'log>(
+        &'log self,
+        query: &'log query::EventQuery,
+    ) -> impl Iterator<Item = &'log Event> {
+        self.queue.iter().filter(move |&event| query.matches(event))
+    }
+
+    /// Returns the given page of the events matching the query, starting
+    /// from page `0` with the most recent events.
+    pub fn page(
+        &self,
+        query: &query::EventQuery,
+        page: u64,
+        per_page: u64,
+    ) -> EventsPage {
+        let skip = page.saturating_mul(per_page);
+        let mut events = vec![];
+        let mut total = 0_u64;
+        for event in self.iter_with_query(query) {
+            if total >= skip && (events.len() as u64) < per_page {
+                events.push(event.clone());
+            }
+            total += 1;
+        }
+        EventsPage { events, total }
+    }
 }
 
 #[cfg(test)]
@@ -201,4 +250,41 @@ mod tests {
             assert_eq!(events[0], event);
         }
     }
+
+    /// Test paging through the events matching a query, from the most
+    /// recent.
+    #[test]
+    fn test_log_page() {
+        let mut log = EventLog::new(Params::default());
+        for height in 0..5_u64 {
+            let mut events = mock_tx_events(HASH);
+            for event in events.iter_mut() {
+                event["height"] = height.to_string();
+            }
+            log.log_events(events);
+        }
+
+        let query: query::EventQuery = "applied.height >= 1 AND applied.hash \
+                                        EXISTS"
+            .parse()
+            .unwrap();
+        let heights = |page: &EventsPage| -> Vec<String> {
+            page.events
+                .iter()
+                .map(|event| event["height"].clone())
+                .collect()
+        };
+
+        let page = log.page(&query, 0, 3);
+        assert_eq!(page.total, 4);
+        assert_eq!(heights(&page), vec!["4", "3", "2"]);
+
+        let page = log.page(&query, 1, 3);
+        assert_eq!(page.total, 4);
+        assert_eq!(heights(&page), vec!["1"]);
+
+        let page = log.page(&query, 2, 3);
+        assert_eq!(page.total, 4);
+        assert!(page.events.is_empty());
+    }
 }
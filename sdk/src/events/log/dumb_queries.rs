@@ -11,7 +11,8 @@ use std::collections::HashMap;
 use namada_core::types::hash::Hash;
 use namada_core::types::storage::BlockHeight;
 
-use crate::events::{Event, EventType};
+use crate::events::{index_key, Event, EventType};
+use crate::ibc::core::ics02_client::events::CONSENSUS_HEIGHTS_ATTRIBUTE_KEY;
 use crate::ibc::core::ics04_channel::packet::Sequence;
 use crate::ibc::core::ics24_host::identifier::{ChannelId, ClientId, PortId};
 use crate::ibc::Height as IbcHeight;
@@ -40,6 +41,18 @@ impl QueryMatcher {
         })
     }
 
+    /// The key of the persistent event store index to look up the events
+    /// that may match this query by. The most selective attribute is used,
+    /// the events found still have to be checked with
+    /// [`QueryMatcher::matches`].
+    pub fn index_key(&self) -> Option<String> {
+        ["hash", CONSENSUS_HEIGHTS_ATTRIBUTE_KEY, "packet_sequence"]
+            .into_iter()
+            .find(|key| self.attributes.contains_key(*key))
+            .or_else(|| self.attributes.keys().map(String::as_str).min())
+            .map(|key| index_key(&self.event_type, key, &self.attributes[key]))
+    }
+
     /// Returns a query matching the given accepted transaction hash.
     pub fn accepted(tx_hash: Hash) -> Self {
         let mut attributes = HashMap::new();
@@ -65,9 +78,7 @@ impl QueryMatcher {
         client_id: ClientId,
         consensus_height: BlockHeight,
     ) -> Self {
-        use crate::ibc::core::ics02_client::events::{
-            CLIENT_ID_ATTRIBUTE_KEY, CONSENSUS_HEIGHTS_ATTRIBUTE_KEY,
-        };
+        use crate::ibc::core::ics02_client::events::CLIENT_ID_ATTRIBUTE_KEY;
 
         let mut attributes = HashMap::new();
         attributes
@@ -119,7 +130,7 @@ impl QueryMatcher {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::events::EventLevel;
+    use crate::events::{EventLevel, MAX_INDEXED_ATTRIBUTE_LEN};
 
     /// Test if query matching is working as expected.
     #[test]
@@ -166,4 +177,26 @@ mod tests {
             }
         }
     }
+
+    /// Test that the events matching a query are indexed by the query's
+    /// index key.
+    #[test]
+    fn test_query_index_key() {
+        let hash = Hash::sha256(b"tx");
+        let matcher = QueryMatcher::applied(hash);
+
+        let mut event = Event {
+            event_type: EventType::Applied,
+            level: EventLevel::Tx,
+            attributes: HashMap::new(),
+        };
+        event["hash"] = hash.to_string();
+        event["log"] = "a".repeat(MAX_INDEXED_ATTRIBUTE_LEN + 1);
+        assert!(matcher.matches(&event));
+
+        // the long log isn't indexed
+        let index_keys = event.index_keys();
+        assert_eq!(index_keys.len(), 1);
+        assert_eq!(matcher.index_key().as_ref(), Some(&index_keys[0]));
+    }
 }
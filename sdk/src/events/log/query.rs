@@ -0,0 +1,661 @@
+//! Query language to match the events of the [`EventLog`].
+//!
+//! The language is a superset of the CometBFT event query language:
+//!
+//! ```text
+//! query     = conjunct { "OR" conjunct }
+//! conjunct  = term { "AND" term }
+//! term      = "(" query ")" | condition
+//! condition = tag "EXISTS"
+//!           | tag "CONTAINS" string
+//!           | tag ( "=" | "<" | "<=" | ">" | ">=" ) operand
+//! operand   = string | number
+//! string    = "'" { any character but "'" } "'"
+//! number    = [ "-" ] digits [ "." digits ]
+//! ```
+//!
+//! A tag is either `tm.event`, which can only be compared for equality with
+//! `'NewBlock'` (matching all events, as they're all emitted when finalizing
+//! a block) or `'Tx'` (matching only the events of transactions), or an
+//! attribute of the events of a given type, written as
+//! `<event type>.<attribute>`, e.g. `applied.hash` or `pgf_payment.amount`.
+//! An attribute condition never holds for events of another type.
+//!
+//! Range comparisons need a number operand, and are evaluated numerically on
+//! the attribute values that parse as decimal numbers. Equality against a
+//! number is numeric too, while equality against a string compares the raw
+//! attribute value.
+//!
+//! For example:
+//!
+//! ```text
+//! applied.height >= 100 AND (applied.code = '0' OR applied.code = '1')
+//! ```
+//!
+//! A query can't be longer than [`MAX_QUERY_LEN`] bytes, nor nest more than
+//! [`MAX_QUERY_DEPTH`] parenthesized queries.
+//!
+//! [`EventLog`]: super::EventLog
+
+use std::fmt::{self, Display};
+use std::iter::Peekable;
+use std::str::{CharIndices, FromStr};
+
+use namada_core::types::dec::Dec;
+
+use crate::error::EventError;
+use crate::events::{Event, EventLevel};
+
+/// The tag of the CometBFT event kind.
+const EVENT_KIND_TAG: &str = "tm.event";
+
+/// The maximum length of a query, in bytes.
+pub const MAX_QUERY_LEN: usize = 4096;
+
+/// The maximum nesting depth of the parenthesized queries.
+pub const MAX_QUERY_DEPTH: usize = 32;
+
+/// A parsed event query.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EventQuery {
+    /// Holds if any of the queries holds
+    Or(Vec<EventQuery>),
+    /// Holds if all of the queries hold
+    And(Vec<EventQuery>),
+    /// A single condition
+    Condition(Condition),
+}
+
+/// A condition on an event.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Condition {
+    /// A condition on the CometBFT event kind (`tm.event`)
+    EventKind(EventKind),
+    /// A condition on an attribute of the events of a given type
+    Attribute {
+        /// The type of the matching events
+        event_type: String,
+        /// The attribute key
+        key: String,
+        /// The condition on the attribute value
+        op: Operation,
+    },
+}
+
+/// The CometBFT event kinds that can be queried.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EventKind {
+    /// Events emitted when finalizing a block, i.e. all the events
+    NewBlock,
+    /// Events of transactions
+    Tx,
+}
+
+/// A condition on an attribute value.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Operation {
+    /// The attribute is present
+    Exists,
+    /// The attribute is equal to the operand
+    Eq(Operand),
+    /// The attribute is less than the number
+    Lt(Dec),
+    /// The attribute is less than or equal to the number
+    Le(Dec),
+    /// The attribute is greater than the number
+    Gt(Dec),
+    /// The attribute is greater than or equal to the number
+    Ge(Dec),
+    /// The attribute contains the string
+    Contains(String),
+}
+
+/// An operand of a comparison.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Operand {
+    /// A quoted string
+    String(String),
+    /// A decimal number
+    Number(Dec),
+}
+
+impl EventQuery {
+    /// Checks if the given [`Event`] matches this query.
+    pub fn matches(&self, event: &Event) -> bool {
+        match self {
+            Self::Or(queries) => queries.iter().any(|q| q.matches(event)),
+            Self::And(queries) => queries.iter().all(|q| q.matches(event)),
+            Self::Condition(condition) => condition.matches(event),
+        }
+    }
+}
+
+impl Condition {
+    /// Checks if the given [`Event`] satisfies this condition.
+    pub fn matches(&self, event: &Event) -> bool {
+        match self {
+            Self::EventKind(EventKind::NewBlock) => true,
+            Self::EventKind(EventKind::Tx) => event.level == EventLevel::Tx,
+            Self::Attribute {
+                event_type,
+                key,
+                op,
+            } => {
+                event.event_type.to_string() == *event_type
+                    && op.matches(event.attributes.get(key))
+            }
+        }
+    }
+}
+
+impl Operation {
+    /// Checks if the given attribute value, if any, satisfies this
+    /// operation.
+    fn matches(&self, value: Option<&String>) -> bool {
+        let value = match value {
+            Some(value) => value,
+            None => return false,
+        };
+        match self {
+            Self::Exists => true,
+            Self::Eq(Operand::String(string)) => value == string,
+            Self::Eq(Operand::Number(number)) => {
+                matches!(parse_number(value), Some(value) if value == *number)
+            }
+            Self::Lt(number) => {
+                matches!(parse_number(value), Some(value) if value < *number)
+            }
+            Self::Le(number) => {
+                matches!(parse_number(value), Some(value) if value <= *number)
+            }
+            Self::Gt(number) => {
+                matches!(parse_number(value), Some(value) if value > *number)
+            }
+            Self::Ge(number) => {
+                matches!(parse_number(value), Some(value) if value >= *number)
+            }
+            Self::Contains(string) => value.contains(string.as_str()),
+        }
+    }
+}
+
+/// Parse an attribute value as a number, if possible.
+fn parse_number(value: &str) -> Option<Dec> {
+    Dec::from_str(value).ok()
+}
+
+impl FromStr for EventQuery {
+    type Err = EventError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.len() > MAX_QUERY_LEN {
+            return Err(invalid(format!(
+                "the query is longer than {MAX_QUERY_LEN} bytes"
+            )));
+        }
+        let mut parser = Parser {
+            tokens: tokenize(s)?,
+            pos: 0,
+            depth: 0,
+        };
+        let query = parser.query()?;
+        match parser.next_token() {
+            None => Ok(query),
+            Some(token) => Err(invalid(format!("unexpected {token}"))),
+        }
+    }
+}
+
+/// A token of the query language.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Token {
+    /// A tag or a keyword
+    Word(String),
+    /// A quoted string, without the quotes
+    String(String),
+    /// A number
+    Number(String),
+    /// A comparison operator
+    Op(&'static str),
+    /// An opening parenthesis
+    LParen,
+    /// A closing parenthesis
+    RParen,
+}
+
+impl Display for Token {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Token::Word(word) => write!(f, "`{word}`"),
+            Token::String(string) => write!(f, "string '{string}'"),
+            Token::Number(number) => write!(f, "number {number}"),
+            Token::Op(op) => write!(f, "`{op}`"),
+            Token::LParen => write!(f, "`(`"),
+            Token::RParen => write!(f, "`)`"),
+        }
+    }
+}
+
+/// Build an invalid query error.
+fn invalid(msg: impl Into<String>) -> EventError {
+    EventError::InvalidQuery(msg.into())
+}
+
+/// Split a query into tokens.
+fn tokenize(s: &str) -> Result<Vec<Token>, EventError> {
+    let mut tokens = vec![];
+    let mut chars = s.char_indices().peekable();
+    while let Some(&(start, c)) = chars.peek() {
+        match c {
+            c if c.is_whitespace() => {
+                chars.next();
+            }
+            '(' => {
+                chars.next();
+                tokens.push(Token::LParen);
+            }
+            ')' => {
+                chars.next();
+                tokens.push(Token::RParen);
+            }
+            '=' => {
+                chars.next();
+                tokens.push(Token::Op("="));
+            }
+            '<' | '>' => {
+                chars.next();
+                let eq = chars.next_if(|&(_, c)| c == '=').is_some();
+                tokens.push(Token::Op(match (c, eq) {
+                    ('<', false) => "<",
+                    ('<', true) => "<=",
+                    (_, false) => ">",
+                    (_, true) => ">=",
+                }));
+            }
+            '\'' => {
+                chars.next();
+                let string = take_while(s, &mut chars, |c| c != '\'');
+                if chars.next().is_none() {
+                    return Err(invalid(format!(
+                        "unterminated string starting at {start}"
+                    )));
+                }
+                tokens.push(Token::String(string.to_string()));
+            }
+            c if c == '-' || c.is_ascii_digit() => {
+                chars.next();
+                let digits = take_while(s, &mut chars, |c| {
+                    c.is_ascii_digit() || c == '.'
+                });
+                tokens.push(Token::Number(format!("{c}{digits}")));
+            }
+            c if c.is_alphabetic() || c == '_' => {
+                let word = take_while(s, &mut chars, |c| {
+                    c.is_alphanumeric() || matches!(c, '_' | '.' | '-')
+                });
+                tokens.push(Token::Word(word.to_string()));
+            }
+            c => {
+                return Err(invalid(format!(
+                    "unexpected character `{c}` at {start}"
+                )));
+            }
+        }
+    }
+    Ok(tokens)
+}
+
+/// Consume the characters satisfying the predicate, returning them as a
+/// slice of the input.
+fn take_while<'s>(
+    s: &'s str,
+    chars: &mut Peekable<CharIndices<'_>>,
+    predicate: impl Fn(char) -> bool,
+) -> &'s str {
+    let start = chars.peek().map(|&(i, _)| i).unwrap_or(s.len());
+    while chars.next_if(|&(_, c)| predicate(c)).is_some() {}
+    let end = chars.peek().map(|&(i, _)| i).unwrap_or(s.len());
+    &s[start..end]
+}
+
+/// A recursive descent parser over the query tokens.
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+    /// The number of parenthesized queries being parsed
+    depth: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn next_token(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        token
+    }
+
+    /// Consume the given keyword, if it's next.
+    fn eat_keyword(&mut self, keyword: &str) -> bool {
+        match self.peek() {
+            Some(Token::Word(word)) if word == keyword => {
+                self.pos += 1;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    fn query(&mut self) -> Result<EventQuery, EventError> {
+        let mut conjuncts = vec![self.conjunct()?];
+        while self.eat_keyword("OR") {
+            conjuncts.push(self.conjunct()?);
+        }
+        Ok(if conjuncts.len() == 1 {
+            conjuncts.remove(0)
+        } else {
+            EventQuery::Or(conjuncts)
+        })
+    }
+
+    fn conjunct(&mut self) -> Result<EventQuery, EventError> {
+        let mut terms = vec![self.term()?];
+        while self.eat_keyword("AND") {
+            terms.push(self.term()?);
+        }
+        Ok(if terms.len() == 1 {
+            terms.remove(0)
+        } else {
+            EventQuery::And(terms)
+        })
+    }
+
+    fn term(&mut self) -> Result<EventQuery, EventError> {
+        if self.peek() == Some(&Token::LParen) {
+            self.pos += 1;
+            if self.depth == MAX_QUERY_DEPTH {
+                return Err(invalid(format!(
+                    "the query nests more than {MAX_QUERY_DEPTH} \
+                     parenthesized queries"
+                )));
+            }
+            self.depth += 1;
+            let query = self.query()?;
+            self.depth -= 1;
+            return match self.next_token() {
+                Some(Token::RParen) => Ok(query),
+                Some(token) => {
+                    Err(invalid(format!("expected `)`, found {token}")))
+                }
+                None => Err(invalid("expected `)`, found end of query")),
+            };
+        }
+        self.condition().map(EventQuery::Condition)
+    }
+
+    fn condition(&mut self) -> Result<Condition, EventError> {
+        let tag = match self.next_token() {
+            Some(Token::Word(word))
+                if !matches!(
+                    word.as_str(),
+                    "AND" | "OR" | "EXISTS" | "CONTAINS"
+                ) =>
+            {
+                word
+            }
+            Some(token) => {
+                return Err(invalid(format!("expected a tag, found {token}")));
+            }
+            None => return Err(invalid("expected a tag, found end of query")),
+        };
+        let op = self.operation(&tag)?;
+
+        if tag == EVENT_KIND_TAG {
+            return match op {
+                Operation::Eq(Operand::String(kind)) if kind == "NewBlock" => {
+                    Ok(Condition::EventKind(EventKind::NewBlock))
+                }
+                Operation::Eq(Operand::String(kind)) if kind == "Tx" => {
+                    Ok(Condition::EventKind(EventKind::Tx))
+                }
+                _ => Err(invalid(format!(
+                    "`{EVENT_KIND_TAG}` can only be compared for equality \
+                     with 'NewBlock' or 'Tx'"
+                ))),
+            };
+        }
+        match tag.split_once('.') {
+            Some((event_type, key))
+                if !event_type.is_empty() && !key.is_empty() =>
+            {
+                Ok(Condition::Attribute {
+                    event_type: event_type.to_string(),
+                    key: key.to_string(),
+                    op,
+                })
+            }
+            _ => Err(invalid(format!(
+                "invalid tag `{tag}`, expected `<event type>.<attribute>`"
+            ))),
+        }
+    }
+
+    fn operation(&mut self, tag: &str) -> Result<Operation, EventError> {
+        match self.next_token() {
+            Some(Token::Word(word)) if word == "EXISTS" => {
+                Ok(Operation::Exists)
+            }
+            Some(Token::Word(word)) if word == "CONTAINS" => match self
+                .next_token()
+            {
+                Some(Token::String(string)) => Ok(Operation::Contains(string)),
+                _ => Err(invalid(format!(
+                    "`CONTAINS` on `{tag}` must be followed by a string"
+                ))),
+            },
+            Some(Token::Op(op)) => {
+                let operand = match self.next_token() {
+                    Some(Token::String(string)) => Operand::String(string),
+                    Some(Token::Number(number)) => Operand::Number(
+                        Dec::from_str(&number).map_err(|err| {
+                            invalid(format!("invalid number {number}: {err}"))
+                        })?,
+                    ),
+                    _ => {
+                        return Err(invalid(format!(
+                            "`{op}` on `{tag}` must be followed by a string \
+                             or a number"
+                        )));
+                    }
+                };
+                match (op, operand) {
+                    ("=", operand) => Ok(Operation::Eq(operand)),
+                    (op, Operand::Number(number)) => Ok(match op {
+                        "<" => Operation::Lt(number),
+                        "<=" => Operation::Le(number),
+                        ">" => Operation::Gt(number),
+                        _ => Operation::Ge(number),
+                    }),
+                    (op, Operand::String(_)) => Err(invalid(format!(
+                        "`{op}` on `{tag}` must be followed by a number"
+                    ))),
+                }
+            }
+            Some(token) => Err(invalid(format!(
+                "expected an operator after `{tag}`, found {token}"
+            ))),
+            None => Err(invalid(format!(
+                "expected an operator after `{tag}`, found end of query"
+            ))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+    use crate::events::EventType;
+
+    fn event(
+        event_type: EventType,
+        level: EventLevel,
+        attributes: &[(&str, &str)],
+    ) -> Event {
+        Event {
+            event_type,
+            level,
+            attributes: attributes
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect::<HashMap<_, _>>(),
+        }
+    }
+
+    fn query(s: &str) -> EventQuery {
+        s.parse().unwrap()
+    }
+
+    /// Test that the query grammar is parsed with `AND` binding tighter than
+    /// `OR`.
+    #[test]
+    fn test_parse_event_query() {
+        let parsed = query(
+            "tm.event='NewBlock' AND applied.height >= 10 OR \
+             (proposal.proposal_id EXISTS AND pgf_payment.target CONTAINS \
+             'atest')",
+        );
+        let expected = EventQuery::Or(vec![
+            EventQuery::And(vec![
+                EventQuery::Condition(Condition::EventKind(
+                    EventKind::NewBlock,
+                )),
+                EventQuery::Condition(Condition::Attribute {
+                    event_type: "applied".to_string(),
+                    key: "height".to_string(),
+                    op: Operation::Ge(Dec::from(10_u64)),
+                }),
+            ]),
+            EventQuery::And(vec![
+                EventQuery::Condition(Condition::Attribute {
+                    event_type: "proposal".to_string(),
+                    key: "proposal_id".to_string(),
+                    op: Operation::Exists,
+                }),
+                EventQuery::Condition(Condition::Attribute {
+                    event_type: "pgf_payment".to_string(),
+                    key: "target".to_string(),
+                    op: Operation::Contains("atest".to_string()),
+                }),
+            ]),
+        ]);
+        assert_eq!(parsed, expected);
+
+        for invalid in [
+            "",
+            "applied.height",
+            "applied.height >",
+            "applied.height > 'ten'",
+            "applied.hash CONTAINS 10",
+            "height = 10",
+            "tm.event = 'Begin'",
+            "(applied.height = 10",
+            "applied.height = 10 applied.code = '0'",
+            "applied.hash = 'DEADBEEF",
+            "applied.height = 10 AND",
+            "applied.height ! 10",
+        ] {
+            assert!(invalid.parse::<EventQuery>().is_err(), "{invalid}");
+        }
+    }
+
+    /// Test that the queries that are too long or too deeply nested are
+    /// rejected.
+    #[test]
+    fn test_event_query_limits() {
+        let nested = |depth: usize| {
+            format!(
+                "{}applied.height = 10{}",
+                "(".repeat(depth),
+                ")".repeat(depth)
+            )
+        };
+        assert!(nested(MAX_QUERY_DEPTH).parse::<EventQuery>().is_ok());
+        assert!(nested(MAX_QUERY_DEPTH + 1).parse::<EventQuery>().is_err());
+        // Unbalanced parentheses that would overflow the stack without a
+        // depth limit
+        assert!("(".repeat(MAX_QUERY_LEN).parse::<EventQuery>().is_err());
+
+        let long = format!("applied.hash = '{}'", "A".repeat(MAX_QUERY_LEN));
+        assert!(matches!(
+            long.parse::<EventQuery>(),
+            Err(EventError::InvalidQuery(_))
+        ));
+    }
+
+    /// Test that the conditions are evaluated against the events' types,
+    /// levels and attributes.
+    #[test]
+    fn test_event_query_matching() {
+        let applied = event(
+            EventType::Applied,
+            EventLevel::Tx,
+            &[("hash", "DEADBEEF"), ("height", "12"), ("code", "0")],
+        );
+        let payment = event(
+            EventType::PgfPayment,
+            EventLevel::Block,
+            &[("target", "atest1steward"), ("amount", "1000.500000")],
+        );
+        let proposal = event(
+            EventType::Proposal,
+            EventLevel::Block,
+            &[("proposal_id", "3")],
+        );
+        let ibc = event(
+            EventType::Ibc("send_packet".to_string()),
+            EventLevel::Tx,
+            &[("packet_sequence", "7")],
+        );
+
+        let cases = [
+            ("tm.event = 'NewBlock'", [true, true, true, true]),
+            ("tm.event = 'Tx'", [true, false, false, true]),
+            ("applied.hash = 'DEADBEEF'", [true, false, false, false]),
+            ("applied.height > 11", [true, false, false, false]),
+            ("applied.height < 12", [false, false, false, false]),
+            ("applied.height <= 12.0", [true, false, false, false]),
+            ("applied.code = 0", [true, false, false, false]),
+            ("pgf_payment.amount >= 1000.5", [false, true, false, false]),
+            ("pgf_payment.amount > 1000.5", [false, false, false, false]),
+            (
+                "pgf_payment.target CONTAINS 'stew'",
+                [false, true, false, false],
+            ),
+            ("proposal.proposal_id EXISTS", [false, false, true, false]),
+            ("proposal.tally_result EXISTS", [false, false, false, false]),
+            (
+                "send_packet.packet_sequence = 7",
+                [false, false, false, true],
+            ),
+            (
+                "applied.height = 12 OR proposal.proposal_id = 3",
+                [true, false, true, false],
+            ),
+            (
+                "tm.event = 'Tx' AND (applied.code = '1' OR \
+                 send_packet.packet_sequence EXISTS)",
+                [false, false, false, true],
+            ),
+        ];
+        for (q, expected) in cases {
+            let q = query(q);
+            let matched = [&applied, &payment, &proposal, &ibc]
+                .map(|event| q.matches(event));
+            assert_eq!(matched, expected, "{q:?}");
+        }
+    }
+}
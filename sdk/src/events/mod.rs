@@ -8,6 +8,8 @@ use std::ops::{Index, IndexMut};
 use std::str::FromStr;
 
 use borsh::{BorshDeserialize, BorshSerialize};
+use borsh_ext::BorshSerializeExt;
+use namada_core::ledger::storage::IndexedEvent;
 use namada_core::types::ibc::IbcEvent;
 use namada_core::types::transaction::TxType;
 use serde_json::Value;
@@ -75,7 +77,7 @@ impl FromStr for EventType {
             "accepted" => Ok(EventType::Accepted),
             "applied" => Ok(EventType::Applied),
             "proposal" => Ok(EventType::Proposal),
-            "pgf_payments" => Ok(EventType::PgfPayment),
+            "pgf_payment" | "pgf_payments" => Ok(EventType::PgfPayment),
             // IBC
             "update_client" => Ok(EventType::Ibc("update_client".to_string())),
             "send_packet" => Ok(EventType::Ibc("send_packet".to_string())),
@@ -88,8 +90,8 @@ impl FromStr for EventType {
 }
 
 impl Event {
-    /// Creates a new event with the hash and height of the transaction
-    /// already filled in
+    /// Creates a new event with the hash and height of the transaction, and
+    /// its memo if it has one, already filled in
     pub fn new_tx_event(tx: &crate::proto::Tx, height: u64) -> Self {
         let mut event = match tx.header().tx_type {
             TxType::Wrapper(_) => {
@@ -127,6 +129,9 @@ impl Event {
         };
         event["height"] = height.to_string();
         event["log"] = "".to_string();
+        if let Some(memo) = tx.memo() {
+            event["memo"] = memo;
+        }
         event
     }
 
@@ -140,6 +145,43 @@ impl Event {
     pub fn get(&self, key: &str) -> Option<&String> {
         self.attributes.get(key)
     }
+
+    /// The keys by which the event is indexed in the persistent event store,
+    /// one for each attribute whose value is no longer than
+    /// [`MAX_INDEXED_ATTRIBUTE_LEN`].
+    pub fn index_keys(&self) -> Vec<String> {
+        let mut keys: Vec<_> = self
+            .attributes
+            .iter()
+            .filter(|(_, value)| value.len() <= MAX_INDEXED_ATTRIBUTE_LEN)
+            .map(|(key, value)| index_key(&self.event_type, key, value))
+            .collect();
+        keys.sort();
+        keys
+    }
+
+    /// Encode the event with its index keys for the persistent event store
+    pub fn to_indexed(&self) -> IndexedEvent {
+        IndexedEvent {
+            index_keys: self.index_keys(),
+            event: self.serialize_to_vec(),
+        }
+    }
+}
+
+/// The maximum length of an attribute value by which events are indexed in
+/// the persistent event store. Longer values, like packet data or logs, are
+/// not worth indexing.
+pub const MAX_INDEXED_ATTRIBUTE_LEN: usize = 128;
+
+/// The key by which the events of the given type with the given attribute
+/// value are indexed in the persistent event store
+pub fn index_key(
+    event_type: &EventType,
+    attribute: &str,
+    value: &str,
+) -> String {
+    format!("{event_type}/{attribute}/{value}")
 }
 
 impl Index<&str> for Event {
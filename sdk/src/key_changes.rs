@@ -0,0 +1,230 @@
+//! A log of the storage keys changed by the blocks finalized in the ledger.
+//!
+//! Like the [`EventLog`](crate::events::log::EventLog), the log only holds
+//! the changes of the last `N` blocks, where `N` is a configurable parameter.
+//! Clients interested in the changes to some keys can query the log for the
+//! blocks they have missed instead of polling each key on every block.
+
+use std::collections::BTreeMap;
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use circular_queue::CircularQueue;
+use namada_core::types::storage::{BlockHeight, Key};
+use serde::{Deserialize, Serialize};
+
+/// Parameters to configure the pruning of the key change log.
+#[derive(Debug, Copy, Clone)]
+pub struct Params {
+    /// Maximum number of blocks whose changes the log can hold.
+    ///
+    /// When a new block is logged while the log is full, the changes of
+    /// the oldest block are pruned.
+    pub max_log_blocks: usize,
+}
+
+impl Default for Params {
+    fn default() -> Self {
+        Self {
+            max_log_blocks: 100,
+        }
+    }
+}
+
+/// A single storage key changed in a block.
+#[derive(
+    Debug,
+    Clone,
+    PartialEq,
+    Eq,
+    BorshSerialize,
+    BorshDeserialize,
+    Serialize,
+    Deserialize,
+)]
+pub struct KeyChange {
+    /// The changed key
+    pub key: Key,
+    /// The new value of the key, or `None` if the key was deleted
+    pub value: Option<Vec<u8>>,
+}
+
+/// The storage keys changed in a block, with their new values.
+#[derive(
+    Debug,
+    Clone,
+    PartialEq,
+    Eq,
+    BorshSerialize,
+    BorshDeserialize,
+    Serialize,
+    Deserialize,
+)]
+pub struct BlockKeyChanges {
+    /// Height of the block
+    pub height: BlockHeight,
+    /// The keys changed in the block, in key order
+    pub changes: Vec<KeyChange>,
+}
+
+impl BlockKeyChanges {
+    /// Build the changes of the block at the given height from the
+    /// modified keys and their new values.
+    pub fn new(
+        height: BlockHeight,
+        changes: BTreeMap<Key, Option<Vec<u8>>>,
+    ) -> Self {
+        let changes = changes
+            .into_iter()
+            .map(|(key, value)| KeyChange { key, value })
+            .collect();
+        Self { height, changes }
+    }
+
+    /// Keep only the changes to keys matching any of the given prefixes.
+    /// Returns `None` if no change matches.
+    pub fn filter_prefixes(&self, prefixes: &[Key]) -> Option<Self> {
+        let changes: Vec<_> = self
+            .changes
+            .iter()
+            .filter(|change| {
+                prefixes
+                    .iter()
+                    .any(|prefix| change.key.split_prefix(prefix).is_some())
+            })
+            .cloned()
+            .collect();
+        if changes.is_empty() {
+            None
+        } else {
+            Some(Self {
+                height: self.height,
+                changes,
+            })
+        }
+    }
+}
+
+/// Represents a log of [`BlockKeyChanges`] recorded by `FinalizeBlock`
+/// calls, in the ledger.
+#[derive(Debug)]
+pub struct KeyChangeLog {
+    queue: CircularQueue<BlockKeyChanges>,
+}
+
+impl Default for KeyChangeLog {
+    fn default() -> Self {
+        Self::new(Default::default())
+    }
+}
+
+impl KeyChangeLog {
+    /// Return a new key change log.
+    pub fn new(params: Params) -> Self {
+        Self {
+            queue: CircularQueue::with_capacity(params.max_log_blocks),
+        }
+    }
+
+    /// Log the key changes of a new block.
+    pub fn log_changes(&mut self, changes: BlockKeyChanges) {
+        tracing::debug!(
+            height = changes.height.0,
+            num_changes = changes.changes.len(),
+            "Added new block to the key change log"
+        );
+        self.queue.push(changes);
+    }
+
+    /// The height of the oldest block still held in the log, if any.
+    pub fn oldest_height(&self) -> Option<BlockHeight> {
+        self.queue.iter().last().map(|changes| changes.height)
+    }
+
+    /// Returns the changes to keys matching any of the given prefixes,
+    /// from all the logged blocks at or above the given height, in
+    /// ascending height order. Blocks without any matching change are
+    /// skipped.
+    pub fn changes_since(
+        &self,
+        from_height: BlockHeight,
+        prefixes: &[Key],
+    ) -> Vec<BlockKeyChanges> {
+        let mut changes: Vec<_> = self
+            .queue
+            .iter()
+            .take_while(|changes| changes.height >= from_height)
+            .filter_map(|changes| changes.filter_prefixes(prefixes))
+            .collect();
+        changes.reverse();
+        changes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use namada_core::types::address::testing::{
+        established_address_1, established_address_2,
+    };
+    use namada_core::types::storage::DbKeySeg;
+
+    use super::*;
+
+    fn balance_key(owner: &namada_core::types::address::Address) -> Key {
+        Key::from(DbKeySeg::StringSeg("balance".to_string()))
+            .push(owner)
+            .unwrap()
+    }
+
+    /// Test that the log only returns the matching changes of the blocks
+    /// it still holds, from the requested height.
+    #[test]
+    fn test_key_change_log_changes_since() {
+        let owner_1 = established_address_1();
+        let owner_2 = established_address_2();
+        let mut log = KeyChangeLog::new(Params { max_log_blocks: 3 });
+
+        for height in 1..=4_u64 {
+            let mut changes = BTreeMap::new();
+            changes.insert(balance_key(&owner_1), Some(vec![height as u8]));
+            if height % 2 == 0 {
+                changes.insert(balance_key(&owner_2), None);
+            }
+            log.log_changes(BlockKeyChanges::new(height.into(), changes));
+        }
+
+        // the first block has been pruned
+        assert_eq!(log.oldest_height(), Some(2.into()));
+
+        let prefix = balance_key(&owner_1);
+        let changes = log.changes_since(1.into(), &[prefix.clone()]);
+        let heights: Vec<_> = changes.iter().map(|c| c.height.0).collect();
+        assert_eq!(heights, vec![2, 3, 4]);
+        for block in &changes {
+            assert_eq!(
+                block.changes,
+                vec![KeyChange {
+                    key: prefix.clone(),
+                    value: Some(vec![block.height.0 as u8]),
+                }]
+            );
+        }
+
+        let prefix = balance_key(&owner_2);
+        let changes = log.changes_since(3.into(), &[prefix.clone()]);
+        assert_eq!(
+            changes,
+            vec![BlockKeyChanges {
+                height: 4.into(),
+                changes: vec![KeyChange {
+                    key: prefix,
+                    value: None,
+                }],
+            }]
+        );
+
+        let prefix = Key::from(DbKeySeg::StringSeg("balance".to_string()));
+        let changes = log.changes_since(4.into(), &[prefix]);
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].changes.len(), 2);
+    }
+}
@@ -24,6 +24,7 @@ pub mod error;
 pub mod events;
 pub(crate) mod internal_macros;
 pub mod io;
+pub mod key_changes;
 pub mod queries;
 pub mod wallet;
 
@@ -40,6 +41,7 @@ use namada_core::types::masp::{TransferSource, TransferTarget};
 use namada_core::types::token;
 use namada_core::types::token::NATIVE_MAX_DECIMAL_PLACES;
 use namada_core::types::transaction::GasLimit;
+use namada_core::types::transaction::scheduler::Schedule;
 use tokio::sync::{RwLock, RwLockReadGuard, RwLockWriteGuard};
 
 use crate::ibc::core::ics24_host::identifier::{ChannelId, PortId};
@@ -53,14 +55,17 @@ use crate::signing::SigningTxData;
 use crate::token::DenominatedAmount;
 use crate::tx::{
     ProcessTxResponse, TX_BOND_WASM, TX_BRIDGE_POOL_WASM,
-    TX_CHANGE_COMMISSION_WASM, TX_CHANGE_CONSENSUS_KEY_WASM,
-    TX_CHANGE_METADATA_WASM, TX_CLAIM_REWARDS_WASM,
-    TX_DEACTIVATE_VALIDATOR_WASM, TX_IBC_WASM, TX_INIT_PROPOSAL,
-    TX_INIT_VALIDATOR_WASM, TX_REACTIVATE_VALIDATOR_WASM, TX_REDELEGATE_WASM,
-    TX_RESIGN_STEWARD, TX_REVEAL_PK, TX_TRANSFER_WASM, TX_UNBOND_WASM,
-    TX_UNJAIL_VALIDATOR_WASM, TX_UPDATE_ACCOUNT_WASM,
-    TX_UPDATE_STEWARD_COMMISSION, TX_VOTE_PROPOSAL, TX_WITHDRAW_WASM,
-    VP_USER_WASM,
+    TX_CANCEL_SCHEDULED_TX_WASM, TX_CHANGE_COMMISSION_WASM,
+    TX_CHANGE_CONSENSUS_KEY_WASM, TX_CHANGE_METADATA_WASM,
+    TX_CLAIM_REWARDS_WASM, TX_DEACTIVATE_VALIDATOR_WASM,
+    TX_DELEGATE_VOTES_WASM, TX_DEPOSIT_PROPOSAL_WASM,
+    TX_GRANT_FEE_ALLOWANCE_WASM, TX_IBC_WASM, TX_INIT_PROPOSAL,
+    TX_INIT_VALIDATOR_WASM, TX_PAUSE_PGF_FUNDING_WASM,
+    TX_REACTIVATE_VALIDATOR_WASM, TX_REDELEGATE_WASM, TX_RESIGN_STEWARD,
+    TX_REVEAL_PK, TX_REVOKE_FEE_ALLOWANCE_WASM, TX_SCHEDULE_TX_WASM,
+    TX_TRANSFER_WASM, TX_UNBOND_WASM, TX_UNJAIL_VALIDATOR_WASM,
+    TX_UPDATE_ACCOUNT_WASM, TX_UPDATE_STEWARD_COMMISSION, TX_VOTE_PROPOSAL,
+    TX_WITHDRAW_WASM, VP_USER_WASM,
 };
 use crate::wallet::{Wallet, WalletIo, WalletStorage};
 
@@ -111,6 +116,7 @@ pub trait Namada<'a>: Sized {
         args::Tx {
             dry_run: false,
             dry_run_wrapper: false,
+            gas_profile: false,
             dump_tx: false,
             output_folder: None,
             force: false,
@@ -122,7 +128,8 @@ pub trait Namada<'a>: Sized {
             wrapper_fee_payer: None,
             fee_token: self.native_token(),
             fee_unshield: None,
-            gas_limit: GasLimit::from(20_000),
+            fee_granter: None,
+            gas_limit: None,
             expiration: None,
             disposable_signing_key: false,
             chain_id: None,
@@ -132,6 +139,7 @@ pub trait Namada<'a>: Sized {
             verification_key: None,
             password: None,
             use_device: false,
+            signer: None,
         }
     }
 
@@ -151,6 +159,7 @@ pub trait Namada<'a>: Sized {
             tx_code_path: PathBuf::from(TX_TRANSFER_WASM),
             tx: self.tx_builder(),
             native_token: self.native_token(),
+            memo: None,
         }
     }
 
@@ -166,6 +175,7 @@ pub trait Namada<'a>: Sized {
             tx_code_path: PathBuf::from(TX_INIT_ACCOUNT_WASM),
             public_keys,
             threshold,
+            vesting: None,
         }
     }
 
@@ -263,6 +273,8 @@ pub trait Namada<'a>: Sized {
             is_offline: false,
             is_pgf_stewards: false,
             is_pgf_funding: false,
+            is_parameter_change: false,
+            is_upgrade: false,
             tx_code_path: PathBuf::from(TX_INIT_PROPOSAL),
             tx: self.tx_builder(),
         }
@@ -275,6 +287,7 @@ pub trait Namada<'a>: Sized {
             vp_code_path: None,
             public_keys: vec![],
             threshold: None,
+            key_policies: None,
             tx_code_path: PathBuf::from(TX_UPDATE_ACCOUNT_WASM),
             tx: self.tx_builder(),
         }
@@ -297,6 +310,38 @@ pub trait Namada<'a>: Sized {
         }
     }
 
+    /// Make a DelegateVotes builder from the given minimum set of arguments
+    fn new_delegate_votes(
+        &self,
+        delegator: Address,
+        delegate: Address,
+    ) -> args::DelegateVotes {
+        args::DelegateVotes {
+            delegator,
+            delegate: Some(delegate),
+            proposal_kind: None,
+            tx: self.tx_builder(),
+            tx_code_path: PathBuf::from(TX_DELEGATE_VOTES_WASM),
+        }
+    }
+
+    /// Make a DepositProposal builder from the given minimum set of arguments
+    fn new_deposit_proposal(
+        &self,
+        proposal_id: u64,
+        depositor: Address,
+        amount: token::Amount,
+    ) -> args::DepositProposal {
+        args::DepositProposal {
+            proposal_id,
+            depositor,
+            amount,
+            native_token: self.native_token(),
+            tx: self.tx_builder(),
+            tx_code_path: PathBuf::from(TX_DEPOSIT_PROPOSAL_WASM),
+        }
+    }
+
     /// Make a CommissionRateChange builder from the given minimum set of
     /// arguments
     fn new_change_commission_rate(
@@ -484,6 +529,89 @@ pub trait Namada<'a>: Sized {
         }
     }
 
+    /// Make a PausePgfFunding builder from the given minimum set of arguments
+    fn new_pause_pgf_funding(
+        &self,
+        steward: Address,
+        funding: String,
+    ) -> args::PausePgfFunding {
+        args::PausePgfFunding {
+            steward,
+            funding,
+            tx: self.tx_builder(),
+            tx_code_path: PathBuf::from(TX_PAUSE_PGF_FUNDING_WASM),
+        }
+    }
+
+    /// Make a ScheduleTx builder from the given minimum set of arguments
+    fn new_schedule_tx(
+        &self,
+        owner: Address,
+        scheduled_tx: Vec<u8>,
+        schedule: Schedule,
+        scheduled_gas_limit: GasLimit,
+    ) -> args::ScheduleTx {
+        args::ScheduleTx {
+            owner,
+            scheduled_tx,
+            scheduled_signatures: vec![],
+            schedule,
+            scheduled_gas_limit,
+            tx: self.tx_builder(),
+            tx_code_path: PathBuf::from(TX_SCHEDULE_TX_WASM),
+        }
+    }
+
+    /// Make a CancelScheduledTx builder from the given minimum set of
+    /// arguments
+    fn new_cancel_scheduled_tx(
+        &self,
+        id: u64,
+        owner: Address,
+    ) -> args::CancelScheduledTx {
+        args::CancelScheduledTx {
+            id,
+            owner,
+            tx: self.tx_builder(),
+            tx_code_path: PathBuf::from(TX_CANCEL_SCHEDULED_TX_WASM),
+        }
+    }
+
+    /// Make a GrantFeeAllowance builder from the given minimum set of
+    /// arguments
+    fn new_grant_fee_allowance(
+        &self,
+        granter: Address,
+        grantee: Address,
+        token: Address,
+        spend_limit: InputAmount,
+    ) -> args::GrantFeeAllowance {
+        args::GrantFeeAllowance {
+            granter,
+            grantee,
+            token,
+            spend_limit,
+            expiration: None,
+            tx: self.tx_builder(),
+            tx_code_path: PathBuf::from(TX_GRANT_FEE_ALLOWANCE_WASM),
+        }
+    }
+
+    /// Make a RevokeFeeAllowance builder from the given minimum set of
+    /// arguments
+    fn new_revoke_fee_allowance(
+        &self,
+        granter: Address,
+        grantee: Address,
+    ) -> args::RevokeFeeAllowance {
+        args::RevokeFeeAllowance {
+            granter,
+            grantee,
+            tx: self.tx_builder(),
+            tx_code_path: PathBuf::from(TX_REVOKE_FEE_ALLOWANCE_WASM),
+        }
+    }
+
     /// Make a TxCustom builder from the given minimum set of arguments
     fn new_custom(&self, owner: Address) -> args::TxCustom {
         args::TxCustom {
@@ -506,6 +634,20 @@ pub trait Namada<'a>: Sized {
         signing::sign_tx(self, args, tx, signing_data, with).await
     }
 
+    /// Sign the given batch of transactions using the signing data of each of
+    /// its inner transactions
+    async fn sign_batch<
+        F: std::future::Future<Output = crate::error::Result<Tx>>,
+    >(
+        &self,
+        tx: &mut Tx,
+        args: &args::Tx,
+        signing_data: Vec<SigningTxData>,
+        with: impl Fn(Tx, common::PublicKey, HashSet<signing::Signable>) -> F,
+    ) -> crate::error::Result<()> {
+        signing::sign_batch(self, args, tx, signing_data, with).await
+    }
+
     /// Process the given transaction using the given flags
     async fn submit(
         &self,
@@ -582,6 +724,7 @@ where
             prototype: args::Tx {
                 dry_run: false,
                 dry_run_wrapper: false,
+                gas_profile: false,
                 dump_tx: false,
                 output_folder: None,
                 force: false,
@@ -593,7 +736,8 @@ where
                 wrapper_fee_payer: None,
                 fee_token: native_token,
                 fee_unshield: None,
-                gas_limit: GasLimit::from(20_000),
+                fee_granter: None,
+                gas_limit: None,
                 expiration: None,
                 disposable_signing_key: false,
                 chain_id: None,
@@ -603,6 +747,7 @@ where
                 verification_key: None,
                 password: None,
                 use_device: false,
+                signer: None,
             },
         }
     }
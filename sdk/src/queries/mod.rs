@@ -104,6 +104,7 @@ mod testing {
 
     use super::*;
     use crate::events::log::EventLog;
+    use crate::key_changes::KeyChangeLog;
     use crate::tendermint_rpc::error::Error as RpcError;
 
     /// A test client that has direct access to the storage
@@ -117,6 +118,8 @@ mod testing {
         pub wl_storage: TestWlStorage,
         /// event log
         pub event_log: EventLog,
+        /// key change log
+        pub key_change_log: KeyChangeLog,
     }
 
     impl<RPC> TestClient<RPC>
@@ -149,6 +152,7 @@ mod testing {
                 rpc,
                 wl_storage,
                 event_log,
+                key_change_log: KeyChangeLog::default(),
             }
         }
     }
@@ -185,6 +189,7 @@ mod testing {
             let ctx = RequestCtx {
                 wl_storage: &self.wl_storage,
                 event_log: &self.event_log,
+                key_change_log: &self.key_change_log,
                 vp_wasm_cache: (),
                 tx_wasm_cache: (),
                 storage_read_past_height_limit: None,
@@ -1025,6 +1025,7 @@ mod test {
         };
         let ctx = RequestCtx {
             event_log: &client.event_log,
+            key_change_log: &client.key_change_log,
             wl_storage: &client.wl_storage,
             vp_wasm_cache: (),
             tx_wasm_cache: (),
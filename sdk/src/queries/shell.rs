@@ -19,15 +19,17 @@ use namada_core::types::storage::{
 };
 use namada_core::types::token::MaspDenom;
 #[cfg(any(test, feature = "async-client"))]
-use namada_core::types::transaction::TxResult;
+use namada_core::types::transaction::{FeeEstimate, TxResult, TxSimulation};
 
 use self::eth_bridge::{EthBridge, ETH_BRIDGE};
-use crate::events::log::dumb_queries;
+use crate::events::log::query::EventQuery;
+use crate::events::log::{dumb_queries, EventsPage, MAX_EVENTS_PER_PAGE};
 use crate::events::{Event, EventType};
 use crate::ibc::core::ics04_channel::packet::Sequence;
 use crate::ibc::core::ics24_host::identifier::{ChannelId, ClientId, PortId};
+use crate::key_changes::BlockKeyChanges;
 use crate::queries::types::{RequestCtx, RequestQuery};
-use crate::queries::{require_latest_height, EncodedResponseQuery};
+use crate::queries::EncodedResponseQuery;
 use crate::tendermint::merkle::proof::ProofOps;
 
 type ConversionWithoutPath = (
@@ -44,6 +46,11 @@ type Conversion = (
     MerklePath<Node>,
 );
 
+/// The maximum number of block heights in the past a storage prefix can be
+/// queried at, as the values are rebuilt from the diffs of every succeeding
+/// height
+pub const MAX_PREFIX_QUERY_PAST_HEIGHTS: u64 = 10_000;
+
 router! {SHELL,
     // Shell provides storage read access, block metadata and can dry-run a tx
 
@@ -69,10 +76,24 @@ router! {SHELL,
     // Dry run a transaction
     ( "dry_run_tx" ) -> TxResult = (with_options dry_run_tx),
 
+    // Simulate a transaction with the full state diff and the outcome of
+    // every triggered VP
+    ( "simulate_tx" ) -> TxSimulation = (with_options simulate_tx),
+
+    // Estimate the gas limit and the gas prices of a transaction from a dry
+    // run and the gas utilization of the recent blocks. The signatures of the
+    // transaction are charged but not verified, so it can carry dummy ones.
+    ( "estimate_fee" ) -> FeeEstimate = (with_options estimate_fee),
+
     // Raw storage access - prefix iterator
     ( "prefix" / [storage_key: storage::Key] )
         -> Vec<PrefixValue> = (with_options storage_prefix),
 
+    // Storage keys matching the prefix changed in the blocks at or above
+    // the given height that are still held in the key change log
+    ( "key_changes" / [from_height: BlockHeight] / [prefix: storage::Key] )
+        -> Vec<BlockKeyChanges> = key_changes,
+
     // Raw storage access - is given storage key present?
     ( "has_key" / [storage_key: storage::Key] )
         -> bool = storage_has_key,
@@ -92,6 +113,11 @@ router! {SHELL,
     // was the transaction applied?
     ( "applied" / [tx_hash: Hash] ) -> Option<Event> = applied,
 
+    // Page through the logged events matching the query passed in the
+    // request data
+    ( "events" / [page: u64] / [per_page: u64] )
+        -> EventsPage = (with_options events),
+
     // Query account subspace
     ( "account" / [owner: Address] ) -> Option<Account> = account,
 
@@ -118,6 +144,28 @@ where
     unimplemented!("Dry running tx requires \"wasm-runtime\" feature.")
 }
 
+fn simulate_tx<D, H, V, T>(
+    _ctx: RequestCtx<'_, D, H, V, T>,
+    _request: &RequestQuery,
+) -> storage_api::Result<EncodedResponseQuery>
+where
+    D: 'static + DB + for<'iter> DBIter<'iter> + Sync,
+    H: 'static + StorageHasher + Sync,
+{
+    unimplemented!("Simulating tx requires \"wasm-runtime\" feature.")
+}
+
+fn estimate_fee<D, H, V, T>(
+    _ctx: RequestCtx<'_, D, H, V, T>,
+    _request: &RequestQuery,
+) -> storage_api::Result<EncodedResponseQuery>
+where
+    D: 'static + DB + for<'iter> DBIter<'iter> + Sync,
+    H: 'static + StorageHasher + Sync,
+{
+    unimplemented!("Estimating fee requires \"wasm-runtime\" feature.")
+}
+
 /// Query to read block results from storage
 pub fn read_results<D, H, V, T>(
     ctx: RequestCtx<'_, D, H, V, T>,
@@ -252,15 +300,13 @@ where
     Ok(ctx.wl_storage.storage.last_block.clone())
 }
 
-/// Returns data with `vec![]` when the storage key is not found. For all
-/// borsh-encoded types, it is safe to check `data.is_empty()` to see if the
-/// value was found, except for unit - see `fn query_storage_value` in
-/// `apps/src/lib/client/rpc.rs` for unit type handling via `storage_has_key`.
-fn storage_value<D, H, V, T>(
-    ctx: RequestCtx<'_, D, H, V, T>,
+/// Get the block height at which a storage query should be answered, `0`
+/// meaning the last committed height, and check that it's not older than the
+/// configured `storage_read_past_height_limit`.
+fn queried_height<D, H, V, T>(
+    ctx: &RequestCtx<'_, D, H, V, T>,
     request: &RequestQuery,
-    storage_key: storage::Key,
-) -> storage_api::Result<EncodedResponseQuery>
+) -> storage_api::Result<BlockHeight>
 where
     D: 'static + DB + for<'iter> DBIter<'iter> + Sync,
     H: 'static + StorageHasher + Sync,
@@ -289,6 +335,23 @@ where
             )));
         }
     }
+    Ok(queried_height)
+}
+
+/// Returns data with `vec![]` when the storage key is not found. For all
+/// borsh-encoded types, it is safe to check `data.is_empty()` to see if the
+/// value was found, except for unit - see `fn query_storage_value` in
+/// `apps/src/lib/client/rpc.rs` for unit type handling via `storage_has_key`.
+fn storage_value<D, H, V, T>(
+    ctx: RequestCtx<'_, D, H, V, T>,
+    request: &RequestQuery,
+    storage_key: storage::Key,
+) -> storage_api::Result<EncodedResponseQuery>
+where
+    D: 'static + DB + for<'iter> DBIter<'iter> + Sync,
+    H: 'static + StorageHasher + Sync,
+{
+    let queried_height = queried_height(&ctx, request)?;
 
     match ctx
         .wl_storage
@@ -342,30 +405,37 @@ where
     D: 'static + DB + for<'iter> DBIter<'iter> + Sync,
     H: 'static + StorageHasher + Sync,
 {
-    require_latest_height(&ctx, request)?;
+    let queried_height = queried_height(&ctx, request)?;
+    let last_committed_height = ctx.wl_storage.storage.get_last_block_height();
+    if last_committed_height.0.saturating_sub(queried_height.0)
+        > MAX_PREFIX_QUERY_PAST_HEIGHTS
+    {
+        return Err(storage_api::Error::new(std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            format!(
+                "Cannot query a storage prefix more than \
+                 {MAX_PREFIX_QUERY_PAST_HEIGHTS} blocks in the past."
+            ),
+        )));
+    }
 
-    let iter = storage_api::iter_prefix_bytes(ctx.wl_storage, &storage_key)?;
-    let data: storage_api::Result<Vec<PrefixValue>> = iter
-        .map(|iter_result| {
-            let (key, value) = iter_result?;
-            Ok(PrefixValue { key, value })
-        })
-        .collect();
-    let data = data?;
+    let data = if queried_height >= last_committed_height {
+        let iter =
+            storage_api::iter_prefix_bytes(ctx.wl_storage, &storage_key)?;
+        let data: storage_api::Result<Vec<PrefixValue>> = iter
+            .map(|iter_result| {
+                let (key, value) = iter_result?;
+                Ok(PrefixValue { key, value })
+            })
+            .collect();
+        data?
+    } else {
+        ctx.wl_storage
+            .storage
+            .read_prefix_with_height(&storage_key, queried_height)
+            .into_storage_result()?
+    };
     let proof = if request.prove {
-        let queried_height = {
-            let last_committed_height =
-                ctx.wl_storage.storage.get_last_block_height();
-
-            let height: BlockHeight = request.height.into();
-            let is_last_height_query = height.0 == 0;
-
-            if hints::likely(is_last_height_query) {
-                last_committed_height
-            } else {
-                height
-            }
-        };
         let mut ops = vec![];
         for PrefixValue { key, value } in &data {
             let mut proof = ctx
@@ -401,6 +471,63 @@ where
     Ok(data)
 }
 
+fn key_changes<D, H, V, T>(
+    ctx: RequestCtx<'_, D, H, V, T>,
+    from_height: BlockHeight,
+    prefix: storage::Key,
+) -> storage_api::Result<Vec<BlockKeyChanges>>
+where
+    D: 'static + DB + for<'iter> DBIter<'iter> + Sync,
+    H: 'static + StorageHasher + Sync,
+{
+    match ctx.key_change_log.oldest_height() {
+        Some(oldest_height) if from_height < oldest_height => {
+            Err(storage_api::Error::new(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                format!(
+                    "The key changes of the blocks below height \
+                     {oldest_height} are no longer held by the node."
+                ),
+            )))
+        }
+        _ => Ok(ctx.key_change_log.changes_since(from_height, &[prefix])),
+    }
+}
+
+/// Find the most recent event matching the query. The events of the last
+/// blocks are looked up in the in-memory event log first, falling back on the
+/// persistent event store for the older ones.
+fn find_event<D, H, V, T>(
+    ctx: &RequestCtx<'_, D, H, V, T>,
+    matcher: dumb_queries::QueryMatcher,
+) -> storage_api::Result<Option<Event>>
+where
+    D: 'static + DB + for<'iter> DBIter<'iter> + Sync,
+    H: 'static + StorageHasher + Sync,
+{
+    if let Some(event) = ctx.event_log.iter_with_matcher(matcher.clone()).next()
+    {
+        return Ok(Some(event.clone()));
+    }
+    let index_key = match matcher.index_key() {
+        Some(index_key) => index_key,
+        None => return Ok(None),
+    };
+    let events = ctx
+        .wl_storage
+        .storage
+        .db
+        .read_indexed_events(&index_key)
+        .into_storage_result()?;
+    for bytes in events {
+        let event = Event::try_from_slice(&bytes).into_storage_result()?;
+        if matcher.matches(&event) {
+            return Ok(Some(event));
+        }
+    }
+    Ok(None)
+}
+
 fn accepted<D, H, V, T>(
     ctx: RequestCtx<'_, D, H, V, T>,
     tx_hash: Hash,
@@ -410,12 +537,7 @@ where
     H: 'static + StorageHasher + Sync,
 {
     let matcher = dumb_queries::QueryMatcher::accepted(tx_hash);
-    Ok(ctx
-        .event_log
-        .iter_with_matcher(matcher)
-        .by_ref()
-        .next()
-        .cloned())
+    find_event(&ctx, matcher)
 }
 
 fn applied<D, H, V, T>(
@@ -427,12 +549,37 @@ where
     H: 'static + StorageHasher + Sync,
 {
     let matcher = dumb_queries::QueryMatcher::applied(tx_hash);
-    Ok(ctx
-        .event_log
-        .iter_with_matcher(matcher)
-        .by_ref()
-        .next()
-        .cloned())
+    find_event(&ctx, matcher)
+}
+
+fn events<D, H, V, T>(
+    ctx: RequestCtx<'_, D, H, V, T>,
+    request: &RequestQuery,
+    page: u64,
+    per_page: u64,
+) -> storage_api::Result<EncodedResponseQuery>
+where
+    D: 'static + DB + for<'iter> DBIter<'iter> + Sync,
+    H: 'static + StorageHasher + Sync,
+{
+    if per_page == 0 || per_page > MAX_EVENTS_PER_PAGE {
+        return Err(storage_api::Error::new(std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            format!(
+                "The number of events per page must be between 1 and \
+                 {MAX_EVENTS_PER_PAGE}, got {per_page}."
+            ),
+        )));
+    }
+    let query: EventQuery = std::str::from_utf8(&request.data)
+        .into_storage_result()?
+        .parse()
+        .into_storage_result()?;
+    let page = ctx.event_log.page(&query, page, per_page);
+    Ok(EncodedResponseQuery {
+        data: page.serialize_to_vec(),
+        ..Default::default()
+    })
 }
 
 fn ibc_client_update<D, H, V, T>(
@@ -448,12 +595,7 @@ where
         client_id,
         consensus_height,
     );
-    Ok(ctx
-        .event_log
-        .iter_with_matcher(matcher)
-        .by_ref()
-        .next()
-        .cloned())
+    find_event(&ctx, matcher)
 }
 
 fn ibc_packet<D, H, V, T>(
@@ -477,12 +619,7 @@ where
         destination_channel,
         sequence,
     );
-    Ok(ctx
-        .event_log
-        .iter_with_matcher(matcher)
-        .by_ref()
-        .next()
-        .cloned())
+    find_event(&ctx, matcher)
 }
 
 fn account<D, H, V, T>(
@@ -500,11 +637,14 @@ where
             storage_api::account::public_keys(ctx.wl_storage, &owner)?;
         let threshold =
             storage_api::account::threshold(ctx.wl_storage, &owner)?;
+        let key_policies =
+            storage_api::account::key_policies(ctx.wl_storage, &owner)?;
 
         Ok(Some(Account {
             public_keys_map: AccountPublicKeysMap::from_iter(public_keys),
             address: owner,
             threshold: threshold.unwrap_or(1),
+            key_policies,
         }))
     } else {
         Ok(None)
@@ -545,6 +685,12 @@ mod test {
         let path = RPC.shell().dry_run_tx_path();
         assert_eq!("/shell/dry_run_tx", path);
 
+        let path = RPC.shell().simulate_tx_path();
+        assert_eq!("/shell/simulate_tx", path);
+
+        let path = RPC.shell().estimate_fee_path();
+        assert_eq!("/shell/estimate_fee", path);
+
         let path = RPC.shell().storage_prefix_path(&key);
         assert_eq!(format!("/shell/prefix/{}", key), path);
 
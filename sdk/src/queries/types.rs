@@ -6,6 +6,7 @@ use namada_core::types::storage::BlockHeight;
 use thiserror::Error;
 
 use crate::events::log::EventLog;
+use crate::key_changes::KeyChangeLog;
 use crate::tendermint::merkle::proof::ProofOps;
 pub use crate::tendermint::v0_37::abci::request::Query as RequestQuery;
 /// A request context provides read-only access to storage and WASM compilation
@@ -20,6 +21,8 @@ where
     pub wl_storage: &'shell WlStorage<D, H>,
     /// Log of events emitted by `FinalizeBlock` ABCI calls.
     pub event_log: &'shell EventLog,
+    /// Log of storage keys changed by `FinalizeBlock` ABCI calls.
+    pub key_change_log: &'shell KeyChangeLog,
     /// Cache of VP wasm compiled artifacts.
     pub vp_wasm_cache: VpCache,
     /// Cache of transaction wasm compiled artifacts.
@@ -0,0 +1,43 @@
+use namada_core::ledger::storage::{DBIter, StorageHasher, DB};
+use namada_core::ledger::storage_api;
+use namada_core::types::address::Address;
+use namada_core::types::transaction::fee_grant::FeeAllowance;
+
+use crate::queries::types::RequestCtx;
+
+// Fee grants validity predicate queries
+router! {FEE_GRANT,
+    ( "allowance" / [ granter: Address ] / [ grantee: Address ] )
+        -> Option<FeeAllowance> = fee_allowance,
+    ( "granter" / [ granter: Address ] )
+        -> Vec<(Address, FeeAllowance)> = fee_allowances_of,
+}
+
+/// Query the fee allowance granted by a granter to a grantee
+fn fee_allowance<D, H, V, T>(
+    ctx: RequestCtx<'_, D, H, V, T>,
+    granter: Address,
+    grantee: Address,
+) -> storage_api::Result<Option<FeeAllowance>>
+where
+    D: 'static + DB + for<'iter> DBIter<'iter> + Sync,
+    H: 'static + StorageHasher + Sync,
+{
+    storage_api::fee_grant::read_fee_allowance(
+        ctx.wl_storage,
+        &granter,
+        &grantee,
+    )
+}
+
+/// Query the fee allowances granted by a granter with their grantees
+fn fee_allowances_of<D, H, V, T>(
+    ctx: RequestCtx<'_, D, H, V, T>,
+    granter: Address,
+) -> storage_api::Result<Vec<(Address, FeeAllowance)>>
+where
+    D: 'static + DB + for<'iter> DBIter<'iter> + Sync,
+    H: 'static + StorageHasher + Sync,
+{
+    storage_api::fee_grant::fee_allowances_of(ctx.wl_storage, &granter)
+}
@@ -1,10 +1,15 @@
 // cd shared && cargo expand ledger::queries::vp::governance
 
+use std::collections::{BTreeMap, BTreeSet};
+
 use namada_core::ledger::governance::parameters::GovernanceParameters;
-use namada_core::ledger::governance::storage::proposal::StorageProposal;
+use namada_core::ledger::governance::storage::proposal::{
+    ProposalKind, StorageProposal, UpgradePlan,
+};
 use namada_core::ledger::governance::utils::Vote;
 use namada_core::ledger::storage::{DBIter, StorageHasher, DB};
 use namada_core::ledger::storage_api;
+use namada_core::types::address::Address;
 
 use crate::queries::types::RequestCtx;
 
@@ -12,7 +17,11 @@ use crate::queries::types::RequestCtx;
 router! {GOV,
     ( "proposal" / [id: u64 ] ) -> Option<StorageProposal> = proposal_id,
     ( "proposal" / [id: u64 ] / "votes" ) -> Vec<Vote> = proposal_id_votes,
+    ( "proposal" / [id: u64 ] / "delegated_votes" ) -> Vec<Vote> = proposal_id_delegated_votes,
+    ( "vote_delegations" / [owner: Address] ) -> BTreeMap<Option<ProposalKind>, Address> = vote_delegations,
+    ( "vote_delegators" / [delegate: Address] ) -> BTreeMap<Address, BTreeSet<Option<ProposalKind>>> = vote_delegators,
     ( "parameters" ) -> GovernanceParameters = parameters,
+    ( "pending_upgrades" ) -> BTreeMap<u64, UpgradePlan> = pending_upgrades,
 }
 
 /// Find if the given address belongs to a validator account.
@@ -39,6 +48,82 @@ where
     storage_api::governance::get_proposal_votes(ctx.wl_storage, id)
 }
 
+/// Get the votes followed on a proposal by the addresses delegating their
+/// votes, as if they were cast on each of their bonds
+fn proposal_id_delegated_votes<D, H, V, T>(
+    ctx: RequestCtx<'_, D, H, V, T>,
+    id: u64,
+) -> storage_api::Result<Vec<Vote>>
+where
+    D: 'static + DB + for<'iter> DBIter<'iter> + Sync,
+    H: 'static + StorageHasher + Sync,
+{
+    let proposal = match storage_api::governance::get_proposal_by_id(
+        ctx.wl_storage,
+        id,
+    )? {
+        Some(proposal) => proposal,
+        None => return Ok(vec![]),
+    };
+    let delegated_votes = storage_api::governance::get_delegated_votes(
+        ctx.wl_storage,
+        id,
+        proposal.r#type.kind(),
+        proposal.voting_start_epoch,
+    )?;
+
+    let mut votes = vec![];
+    for (delegator, (_delegate, data)) in delegated_votes {
+        let validators = namada_proof_of_stake::find_delegation_validators(
+            ctx.wl_storage,
+            &delegator,
+        )?;
+        for validator in validators {
+            if validator == delegator {
+                continue;
+            }
+            votes.push(Vote {
+                validator,
+                delegator: delegator.clone(),
+                data: data.clone(),
+            });
+        }
+    }
+    Ok(votes)
+}
+
+/// Get the latest delegates of the given address, in force from the next
+/// epoch, by the proposal kind they are restricted to
+fn vote_delegations<D, H, V, T>(
+    ctx: RequestCtx<'_, D, H, V, T>,
+    owner: Address,
+) -> storage_api::Result<BTreeMap<Option<ProposalKind>, Address>>
+where
+    D: 'static + DB + for<'iter> DBIter<'iter> + Sync,
+    H: 'static + StorageHasher + Sync,
+{
+    let epoch = ctx.wl_storage.storage.last_epoch.next();
+    storage_api::governance::get_vote_delegations(ctx.wl_storage, &owner, epoch)
+}
+
+/// Get the addresses delegating their votes to the given delegate from the
+/// next epoch
+fn vote_delegators<D, H, V, T>(
+    ctx: RequestCtx<'_, D, H, V, T>,
+    delegate: Address,
+) -> storage_api::Result<BTreeMap<Address, BTreeSet<Option<ProposalKind>>>>
+where
+    D: 'static + DB + for<'iter> DBIter<'iter> + Sync,
+    H: 'static + StorageHasher + Sync,
+{
+    let epoch = ctx.wl_storage.storage.last_epoch.next();
+    storage_api::governance::get_vote_delegators(
+        ctx.wl_storage,
+        &delegate,
+        epoch,
+    )
+}
+
 /// Get the governane parameters
 fn parameters<D, H, V, T>(
     ctx: RequestCtx<'_, D, H, V, T>,
@@ -49,3 +134,18 @@ where
 {
     storage_api::governance::get_parameters(ctx.wl_storage)
 }
+
+/// Get the software upgrades scheduled by governance whose height hasn't
+/// been processed yet, by the id of the proposal that scheduled them
+fn pending_upgrades<D, H, V, T>(
+    ctx: RequestCtx<'_, D, H, V, T>,
+) -> storage_api::Result<BTreeMap<u64, UpgradePlan>>
+where
+    D: 'static + DB + for<'iter> DBIter<'iter> + Sync,
+    H: 'static + StorageHasher + Sync,
+{
+    storage_api::governance::get_pending_upgrades(
+        ctx.wl_storage,
+        ctx.wl_storage.storage.get_last_block_height(),
+    )
+}
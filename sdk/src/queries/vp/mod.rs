@@ -1,16 +1,22 @@
 //! Queries router and handlers for validity predicates
 
 // Re-export to show in rustdoc!
+use fee_grant::FEE_GRANT;
+pub use fee_grant::FeeGrant;
 pub use governance::Gov;
 use governance::GOV;
 pub use pos::Pos;
 use pos::POS;
 pub use token::Token;
 use token::TOKEN;
+mod fee_grant;
 mod governance;
 pub use pgf::Pgf;
 use pgf::PGF;
 mod pgf;
+use scheduler::SCHEDULER;
+pub use scheduler::Scheduler;
+mod scheduler;
 
 pub mod pos;
 mod token;
@@ -21,4 +27,6 @@ router! {VP,
     ( "token" ) = (sub TOKEN),
     ( "governance" ) = (sub GOV),
     ( "pgf" ) = (sub PGF),
+    ( "scheduler" ) = (sub SCHEDULER),
+    ( "fee_grant" ) = (sub FEE_GRANT),
 }
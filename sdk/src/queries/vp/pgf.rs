@@ -12,6 +12,8 @@ router! {PGF,
     ( "stewards" / [ address: Address ] ) -> bool = is_steward,
     ( "stewards" ) -> Vec<StewardDetail> = stewards,
     ( "fundings" ) -> Vec<StoragePgfFunding> = funding,
+    ( "funding" / [ funding: String ] ) -> Option<StoragePgfFunding> = funding_by_key,
+    ( "paused" / [ funding: String ] ) -> bool = is_funding_paused,
     ( "parameters" ) -> PgfParameters = parameters,
 }
 
@@ -49,6 +51,30 @@ where
     storage_api::pgf::get_payments(ctx.wl_storage)
 }
 
+/// Query a continous pgf funding by the funding key of its recipient
+fn funding_by_key<D, H, V, T>(
+    ctx: RequestCtx<'_, D, H, V, T>,
+    funding: String,
+) -> storage_api::Result<Option<StoragePgfFunding>>
+where
+    D: 'static + DB + for<'iter> DBIter<'iter> + Sync,
+    H: 'static + StorageHasher + Sync,
+{
+    storage_api::pgf::get_payment(ctx.wl_storage, &funding)
+}
+
+/// Check if a continous pgf funding is paused by any steward
+fn is_funding_paused<D, H, V, T>(
+    ctx: RequestCtx<'_, D, H, V, T>,
+    funding: String,
+) -> storage_api::Result<bool>
+where
+    D: 'static + DB + for<'iter> DBIter<'iter> + Sync,
+    H: 'static + StorageHasher + Sync,
+{
+    storage_api::pgf::is_payment_paused(ctx.wl_storage, &funding)
+}
+
 /// Query the PGF parameters
 fn parameters<D, H, V, T>(
     ctx: RequestCtx<'_, D, H, V, T>,
@@ -0,0 +1,36 @@
+use namada_core::ledger::storage::{DBIter, StorageHasher, DB};
+use namada_core::ledger::storage_api;
+use namada_core::types::address::Address;
+use namada_core::types::transaction::scheduler::ScheduleTx;
+
+use crate::queries::types::RequestCtx;
+
+// Scheduler validity predicate queries
+router! {SCHEDULER,
+    ( "owner" / [ owner: Address ] ) -> Vec<(u64, ScheduleTx)> = scheduled_txs_of,
+    ( "tx" / [ id: u64 ] ) -> Option<ScheduleTx> = scheduled_tx,
+}
+
+/// Query the pending scheduled txs of an owner with their ids
+fn scheduled_txs_of<D, H, V, T>(
+    ctx: RequestCtx<'_, D, H, V, T>,
+    owner: Address,
+) -> storage_api::Result<Vec<(u64, ScheduleTx)>>
+where
+    D: 'static + DB + for<'iter> DBIter<'iter> + Sync,
+    H: 'static + StorageHasher + Sync,
+{
+    storage_api::scheduler::scheduled_txs_of(ctx.wl_storage, &owner)
+}
+
+/// Query a pending scheduled tx by its id
+fn scheduled_tx<D, H, V, T>(
+    ctx: RequestCtx<'_, D, H, V, T>,
+    id: u64,
+) -> storage_api::Result<Option<ScheduleTx>>
+where
+    D: 'static + DB + for<'iter> DBIter<'iter> + Sync,
+    H: 'static + StorageHasher + Sync,
+{
+    storage_api::scheduler::read_scheduled_tx(ctx.wl_storage, id)
+}
@@ -9,7 +9,9 @@ use masp_primitives::asset_type::AssetType;
 use masp_primitives::merkle_tree::MerklePath;
 use masp_primitives::sapling::Node;
 use namada_core::ledger::governance::parameters::GovernanceParameters;
-use namada_core::ledger::governance::storage::proposal::StorageProposal;
+use namada_core::ledger::governance::storage::proposal::{
+    ProposalKind, StoragePgfFunding, StorageProposal, UpgradePlan,
+};
 use namada_core::ledger::governance::utils::Vote;
 use namada_core::ledger::ibc::storage::{
     ibc_denom_key, ibc_denom_key_prefix, is_ibc_denom_key,
@@ -20,11 +22,14 @@ use namada_core::types::address::{Address, InternalAddress};
 use namada_core::types::hash::Hash;
 use namada_core::types::key::common;
 use namada_core::types::storage::{
-    BlockHeight, BlockResults, Epoch, Key, PrefixValue,
+    BlockHeight, BlockResults, Epoch, Key, PrefixValue, TxIndex,
 };
 use namada_core::types::token::{
     Amount, DenominatedAmount, Denomination, MaspDenom,
 };
+use namada_core::types::transaction::fee_grant::FeeAllowance;
+use namada_core::types::transaction::scheduler::ScheduleTx;
+use namada_core::types::transaction::{FeeEstimate, TxSimulation};
 use namada_core::types::{storage, token};
 use namada_proof_of_stake::parameters::PosParams;
 use namada_proof_of_stake::types::{
@@ -36,6 +41,7 @@ use crate::args::InputAmount;
 use crate::control_flow::time;
 use crate::error::{EncodingError, Error, QueryError, TxError};
 use crate::events::Event;
+use crate::events::log::EventsPage;
 use crate::internal_macros::echo_error;
 use crate::io::Io;
 use crate::proto::Tx;
@@ -160,6 +166,32 @@ pub async fn query_results<C: crate::queries::Client + Sync>(
     convert_response::<C, _>(RPC.shell().read_results(client).await)
 }
 
+/// Query the memos of the transactions of the block at the given height,
+/// indexed by the position of the transactions in the block
+pub async fn query_block_memos<C: crate::queries::Client + Sync>(
+    client: &C,
+    height: BlockHeight,
+) -> Result<BTreeMap<TxIndex, String>, Error> {
+    let tm_height = Height::try_from(height.0)
+        .map_err(|err| Error::Other(format!("Invalid height: {err}")))?;
+    let block = client.block(tm_height).await.map_err(|err| {
+        Error::from(QueryError::General(format!(
+            "for the block at height {height}: {err}"
+        )))
+    })?;
+    let memos = block
+        .block
+        .data
+        .iter()
+        .enumerate()
+        .filter_map(|(index, tx_bytes)| {
+            let memo = Tx::try_from(tx_bytes.as_ref()).ok()?.memo()?;
+            Some((TxIndex(index as u32), memo))
+        })
+        .collect();
+    Ok(memos)
+}
+
 /// Query token amount of owner.
 pub async fn get_token_balance<C: crate::queries::Client + Sync>(
     client: &C,
@@ -189,6 +221,32 @@ pub async fn is_steward<C: crate::queries::Client + Sync>(
     )
 }
 
+/// Query a continous pgf funding by the funding key of its recipient
+pub async fn query_pgf_funding<C: crate::queries::Client + Sync>(
+    client: &C,
+    funding: &str,
+) -> Result<Option<StoragePgfFunding>, error::Error> {
+    convert_response::<C, _>(
+        RPC.vp()
+            .pgf()
+            .funding_by_key(client, &funding.to_owned())
+            .await,
+    )
+}
+
+/// Check if a continous pgf funding is paused by any steward
+pub async fn is_pgf_funding_paused<C: crate::queries::Client + Sync>(
+    client: &C,
+    funding: &str,
+) -> Result<bool, error::Error> {
+    convert_response::<C, _>(
+        RPC.vp()
+            .pgf()
+            .is_funding_paused(client, &funding.to_owned())
+            .await,
+    )
+}
+
 /// Check if a given address is a known delegator
 pub async fn is_delegator<C: crate::queries::Client + Sync>(
     client: &C,
@@ -463,6 +521,24 @@ pub async fn query_tx_events<C: crate::queries::Client + Sync>(
     }
 }
 
+/// Query a page of the events held in the node's event log that match the
+/// given event query, from the most recent. See
+/// [`crate::events::log::query`] for the query language.
+pub async fn query_events<C: crate::queries::Client + Sync>(
+    client: &C,
+    query: &str,
+    page: u64,
+    per_page: u64,
+) -> Result<EventsPage, Error> {
+    let data = Some(query.as_bytes().to_vec());
+    let response = convert_response::<C, _>(
+        RPC.shell()
+            .events(client, data, None, false, &page, &per_page)
+            .await,
+    )?;
+    Ok(response.data)
+}
+
 /// Dry run a transaction
 pub async fn dry_run_tx<'a, N: Namada<'a>>(
     context: &N,
@@ -479,6 +555,35 @@ pub async fn dry_run_tx<'a, N: Namada<'a>>(
     Ok(result)
 }
 
+/// Simulate a transaction, without committing anything. The simulation
+/// includes the changed storage values, the outcome of every triggered VP and
+/// the fee charged by the wrapper, if any.
+pub async fn simulate_tx<C: crate::queries::Client + Sync>(
+    client: &C,
+    tx_bytes: Vec<u8>,
+) -> Result<TxSimulation, Error> {
+    let response = convert_response::<C, _>(
+        RPC.shell()
+            .simulate_tx(client, Some(tx_bytes), None, false)
+            .await,
+    )?;
+    Ok(response.data)
+}
+
+/// Estimate the gas limit of a transaction and the gas price for each of the
+/// fee tokens
+pub async fn estimate_fee<C: crate::queries::Client + Sync>(
+    client: &C,
+    tx_bytes: Vec<u8>,
+) -> Result<FeeEstimate, Error> {
+    let response = convert_response::<C, _>(
+        RPC.shell()
+            .estimate_fee(client, Some(tx_bytes), None, false)
+            .await,
+    )?;
+    Ok(response.data)
+}
+
 /// Data needed for broadcasting a tx and
 /// monitoring its progress on chain
 ///
@@ -746,6 +851,53 @@ pub async fn query_proposal_by_id<C: crate::queries::Client + Sync>(
     )
 }
 
+/// Query the pending scheduled txs of the given owner with their ids
+pub async fn query_scheduled_txs<C: crate::queries::Client + Sync>(
+    client: &C,
+    owner: &Address,
+) -> Result<Vec<(u64, ScheduleTx)>, Error> {
+    convert_response::<C, _>(
+        RPC.vp().scheduler().scheduled_txs_of(client, owner).await,
+    )
+}
+
+/// Query a pending scheduled tx by its id
+pub async fn query_scheduled_tx<C: crate::queries::Client + Sync>(
+    client: &C,
+    id: u64,
+) -> Result<Option<ScheduleTx>, Error> {
+    convert_response::<C, _>(
+        RPC.vp().scheduler().scheduled_tx(client, &id).await,
+    )
+}
+
+/// Query the fee allowance granted by a granter to a grantee
+pub async fn query_fee_allowance<C: crate::queries::Client + Sync>(
+    client: &C,
+    granter: &Address,
+    grantee: &Address,
+) -> Result<Option<FeeAllowance>, Error> {
+    convert_response::<C, _>(
+        RPC.vp()
+            .fee_grant()
+            .fee_allowance(client, granter, grantee)
+            .await,
+    )
+}
+
+/// Query the fee allowances granted by the given granter with their grantees
+pub async fn query_fee_allowances<C: crate::queries::Client + Sync>(
+    client: &C,
+    granter: &Address,
+) -> Result<Vec<(Address, FeeAllowance)>, Error> {
+    convert_response::<C, _>(
+        RPC.vp()
+            .fee_grant()
+            .fee_allowances_of(client, granter)
+            .await,
+    )
+}
+
 /// Query and return validator's commission rate and max commission rate change
 /// per epoch
 pub async fn query_commission_rate<C: crate::queries::Client + Sync>(
@@ -928,6 +1080,52 @@ pub async fn query_proposal_votes<C: crate::queries::Client + Sync>(
     )
 }
 
+/// Get the votes followed on a proposal by the addresses delegating their
+/// votes, for each of their bonds
+pub async fn query_proposal_delegated_votes<
+    C: crate::queries::Client + Sync,
+>(
+    client: &C,
+    proposal_id: u64,
+) -> Result<Vec<Vote>, error::Error> {
+    convert_response::<C, Vec<Vote>>(
+        RPC.vp()
+            .gov()
+            .proposal_id_delegated_votes(client, &proposal_id)
+            .await,
+    )
+}
+
+/// Get the governance delegates of an address, by the proposal kind they are
+/// restricted to or `None` for the delegate for all the kinds
+pub async fn query_vote_delegations<C: crate::queries::Client + Sync>(
+    client: &C,
+    owner: &Address,
+) -> Result<BTreeMap<Option<ProposalKind>, Address>, error::Error> {
+    convert_response::<C, _>(
+        RPC.vp().gov().vote_delegations(client, owner).await,
+    )
+}
+
+/// Get the addresses delegating their governance votes to the given
+/// delegate, with the proposal kinds of the delegations
+pub async fn query_vote_delegators<C: crate::queries::Client + Sync>(
+    client: &C,
+    delegate: &Address,
+) -> Result<BTreeMap<Address, BTreeSet<Option<ProposalKind>>>, error::Error> {
+    convert_response::<C, _>(
+        RPC.vp().gov().vote_delegators(client, delegate).await,
+    )
+}
+
+/// Get the software upgrades scheduled by governance whose height hasn't been
+/// processed yet, by the id of the proposal that scheduled them
+pub async fn query_pending_upgrades<C: crate::queries::Client + Sync>(
+    client: &C,
+) -> Result<BTreeMap<u64, UpgradePlan>, error::Error> {
+    convert_response::<C, _>(RPC.vp().gov().pending_upgrades(client).await)
+}
+
 /// Get the bond amount at the given epoch
 pub async fn get_bond_amount_at<C: crate::queries::Client + Sync>(
     client: &C,
@@ -1,4 +1,7 @@
 //! Functions to sign transactions
+
+pub mod remote;
+
 use std::collections::{BTreeMap, HashMap, HashSet};
 use std::fmt::Display;
 
@@ -28,7 +31,7 @@ use namada_core::types::transaction::governance::{
     InitProposalData, VoteProposalData,
 };
 use namada_core::types::transaction::pos::InitValidator;
-use namada_core::types::transaction::{pos, Fee};
+use namada_core::types::transaction::{pos, Fee, GasLimit};
 use prost::Message;
 use rand::rngs::OsRng;
 use serde::{Deserialize, Serialize};
@@ -45,7 +48,9 @@ use crate::ibc::applications::transfer::msgs::transfer::MsgTransfer;
 use crate::ibc_proto::google::protobuf::Any;
 use crate::io::*;
 use crate::masp::make_asset_type;
-use crate::proto::{MaspBuilder, Section, Tx};
+use crate::proto::{
+    MaspBuilder, Section, Signature as SignatureSection, Signer, Tx,
+};
 use crate::rpc::validate_amount;
 use crate::tx::{
     TX_BOND_WASM, TX_CHANGE_COMMISSION_WASM, TX_CHANGE_CONSENSUS_KEY_WASM,
@@ -57,7 +62,7 @@ use crate::tx::{
     VP_VALIDATOR_WASM,
 };
 pub use crate::wallet::store::AddressVpType;
-use crate::wallet::{Wallet, WalletIo};
+use crate::wallet::{gen_secret_key, Wallet, WalletIo};
 use crate::{args, display_line, rpc, Namada};
 
 #[cfg(feature = "std")]
@@ -197,7 +202,8 @@ pub async fn tx_signers<'a>(
 }
 
 /// The different parts of a transaction that can be signed
-#[derive(Eq, Hash, PartialEq)]
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum Signable {
     FeeHeader,
     RawHeader,
@@ -233,9 +239,63 @@ pub async fn sign_tx<'a, F: std::future::Future<Output = Result<Tx, Error>>>(
     signing_data: SigningTxData,
     sign: impl Fn(Tx, common::PublicKey, HashSet<Signable>) -> F,
 ) -> Result<(), Error> {
-    let mut used_pubkeys = HashSet::new();
+    let supplied_pubkeys = add_supplied_signatures(args, tx);
+    let fee_payer = signing_data.fee_payer.clone();
+    sign_raw_header(
+        context,
+        args,
+        tx,
+        signing_data,
+        &fee_payer,
+        supplied_pubkeys,
+        &sign,
+    )
+    .await?;
+    sign_fee_header(context, args, tx, &fee_payer, &sign).await
+}
 
-    // First try to sign the raw header with the supplied signatures
+/// Sign a batch of transactions made by `tx::build_batch`. The raw header
+/// shared by the inner transactions is signed with the signing data of each of
+/// them, and then the wrapper is signed by the fee payer of the first one.
+pub async fn sign_batch<
+    'a,
+    F: std::future::Future<Output = Result<Tx, Error>>,
+>(
+    context: &impl Namada<'a>,
+    args: &args::Tx,
+    tx: &mut Tx,
+    signing_data: Vec<SigningTxData>,
+    sign: impl Fn(Tx, common::PublicKey, HashSet<Signable>) -> F,
+) -> Result<(), Error> {
+    let fee_payer = match signing_data.first() {
+        Some(signing_data) => signing_data.fee_payer.clone(),
+        None => {
+            return Err(Error::Other("Cannot sign an empty batch".to_string()));
+        }
+    };
+    let supplied_pubkeys = add_supplied_signatures(args, tx);
+    for signing_data in signing_data {
+        sign_raw_header(
+            context,
+            args,
+            tx,
+            signing_data,
+            &fee_payer,
+            supplied_pubkeys.clone(),
+            &sign,
+        )
+        .await?;
+    }
+    sign_fee_header(context, args, tx, &fee_payer, &sign).await
+}
+
+/// Add the signatures supplied in the arguments to the raw header and return
+/// the public keys that made them
+fn add_supplied_signatures(
+    args: &args::Tx,
+    tx: &mut Tx,
+) -> HashSet<common::PublicKey> {
+    let mut used_pubkeys = HashSet::new();
     if !args.signatures.is_empty() {
         let signatures = args
             .signatures
@@ -248,8 +308,26 @@ pub async fn sign_tx<'a, F: std::future::Future<Output = Result<Tx, Error>>>(
             .collect();
         tx.add_signatures(signatures);
     }
+    used_pubkeys
+}
 
-    // Then try to sign the raw header with private keys in the software wallet
+/// Sign the raw header with the keys of the signing data that haven't been
+/// used yet. The fee payer is left out of the fallback, as it signs the raw
+/// header together with the wrapper.
+async fn sign_raw_header<
+    'a,
+    F: std::future::Future<Output = Result<Tx, Error>>,
+>(
+    context: &impl Namada<'a>,
+    args: &args::Tx,
+    tx: &mut Tx,
+    signing_data: SigningTxData,
+    fee_payer: &common::PublicKey,
+    mut used_pubkeys: HashSet<common::PublicKey>,
+    sign: &impl Fn(Tx, common::PublicKey, HashSet<Signable>) -> F,
+) -> Result<(), Error> {
+    // First try to sign the raw header with private keys in the software
+    // wallet
     if let Some(account_public_keys_map) = signing_data.account_public_keys_map
     {
         let mut wallet = context.wallet_mut().await;
@@ -281,7 +359,7 @@ pub async fn sign_tx<'a, F: std::future::Future<Output = Result<Tx, Error>>>(
 
     // Then try to sign the raw header using the hardware wallet
     for pubkey in signing_data.public_keys {
-        if !used_pubkeys.contains(&pubkey) && pubkey != signing_data.fee_payer {
+        if !used_pubkeys.contains(&pubkey) && &pubkey != fee_payer {
             if let Ok(ntx) = sign(
                 tx.clone(),
                 pubkey.clone(),
@@ -294,14 +372,25 @@ pub async fn sign_tx<'a, F: std::future::Future<Output = Result<Tx, Error>>>(
             }
         }
     }
+    Ok(())
+}
 
-    // Then try signing the fee header with the software wallet otherwise use
-    // the fallback
+/// Sign the fee header with the software wallet, otherwise use the fallback
+async fn sign_fee_header<
+    'a,
+    F: std::future::Future<Output = Result<Tx, Error>>,
+>(
+    context: &impl Namada<'a>,
+    args: &args::Tx,
+    tx: &mut Tx,
+    fee_payer: &common::PublicKey,
+    sign: &impl Fn(Tx, common::PublicKey, HashSet<Signable>) -> F,
+) -> Result<(), Error> {
     let key = {
         // Lock the wallet just long enough to extract a key from it without
         // interfering with the sign closure call
         let mut wallet = context.wallet_mut().await;
-        find_key_by_pk(*wallet, args, &signing_data.fee_payer)
+        find_key_by_pk(*wallet, args, fee_payer)
     };
     match key {
         Ok(fee_payer_keypair) => {
@@ -310,7 +399,7 @@ pub async fn sign_tx<'a, F: std::future::Future<Output = Result<Tx, Error>>>(
         Err(_) => {
             *tx = sign(
                 tx.clone(),
-                signing_data.fee_payer.clone(),
+                fee_payer.clone(),
                 HashSet::from([Signable::FeeHeader, Signable::RawHeader]),
             )
             .await?;
@@ -456,9 +545,50 @@ pub async fn wrap_tx<'a, N: Namada<'a>>(
     args: &args::Tx<SdkTypes>,
     tx_source_balance: Option<TxSourcePostBalance>,
     epoch: Epoch,
-    fee_payer: common::PublicKey,
+    signing_data: &SigningTxData,
 ) -> Result<Option<Epoch>, Error> {
+    let fee_payer = signing_data.fee_payer.clone();
     let fee_payer_address = Address::from(&fee_payer);
+    // Estimate the gas limit and the gas price unless both are provided
+    let fee_estimate = if args.gas_limit.is_none() || args.fee_amount.is_none()
+    {
+        let max_block_gas = rpc::query_storage_value::<_, u64>(
+            context.client(),
+            &parameter_storage::get_max_block_gas_key(),
+        )
+        .await?;
+        let dummy_tx = dummy_signed_tx(
+            tx,
+            signing_data,
+            args,
+            epoch,
+            GasLimit::from(max_block_gas),
+        );
+        match rpc::estimate_fee(context.client(), dummy_tx.to_bytes()).await {
+            Ok(estimate) => Some(estimate),
+            Err(e) if args.gas_limit.is_none() => {
+                return Err(Error::Other(format!(
+                    "Failed to estimate the gas limit of the transaction: {e}"
+                )));
+            }
+            // Fall back to the minimum gas price
+            Err(_) => None,
+        }
+    } else {
+        None
+    };
+    let gas_limit = match (args.gas_limit, &fee_estimate) {
+        (Some(gas_limit), _) => gas_limit,
+        (None, Some(estimate)) => {
+            display_line!(
+                context.io(),
+                "Using the estimated gas limit {}",
+                u64::from(estimate.gas_limit)
+            );
+            estimate.gas_limit
+        }
+        (None, None) => unreachable!("The gas limit must have been estimated"),
+    };
     // Validate fee amount and token
     let gas_cost_key = parameter_storage::get_gas_cost_key();
     let minimum_fee = match rpc::query_storage_value::<
@@ -511,18 +641,31 @@ pub async fn wrap_tx<'a, N: Namada<'a>>(
                 amount
             }
         }
-        None => minimum_fee,
+        None => {
+            // The estimated price is never below the minimum one
+            match fee_estimate
+                .as_ref()
+                .and_then(|estimate| estimate.gas_prices.get(&args.fee_token))
+            {
+                Some(estimated_price) => *estimated_price,
+                None => minimum_fee,
+            }
+        }
     };
 
+    // The fees are paid by the fee granter, if any
+    let fee_source = args
+        .fee_granter
+        .clone()
+        .unwrap_or_else(|| fee_payer_address.clone());
     let mut updated_balance = match tx_source_balance {
         Some(TxSourcePostBalance {
             post_balance: balance,
             source,
             token,
-        }) if token == args.fee_token && source == fee_payer_address => balance,
+        }) if token == args.fee_token && source == fee_source => balance,
         _ => {
-            let balance_key =
-                token::balance_key(&args.fee_token, &fee_payer_address);
+            let balance_key = token::balance_key(&args.fee_token, &fee_source);
 
             rpc::query_storage_value::<_, token::Amount>(
                 context.client(),
@@ -533,13 +676,45 @@ pub async fn wrap_tx<'a, N: Namada<'a>>(
         }
     };
 
-    let total_fee = fee_amount * u64::from(args.gas_limit);
+    let total_fee = fee_amount * u64::from(gas_limit);
+
+    if let Some(granter) = &args.fee_granter {
+        let allowance = rpc::query_fee_allowance(
+            context.client(),
+            granter,
+            &fee_payer_address,
+        )
+        .await?;
+        match allowance {
+            Some(allowance) if !allowance.is_expired(epoch) => {
+                let remaining = allowance.remaining(&args.fee_token);
+                if remaining < total_fee && !args.force {
+                    return Err(Error::from(TxError::FeeAllowanceTooLow(
+                        granter.clone(),
+                        args.fee_token.clone(),
+                        context.format_amount(&args.fee_token, total_fee).await,
+                        context.format_amount(&args.fee_token, remaining).await,
+                    )));
+                }
+            }
+            _ if !args.force => {
+                return Err(Error::from(TxError::FeeAllowanceNotFound(
+                    granter.clone(),
+                    fee_payer_address,
+                )));
+            }
+            _ => {}
+        }
+    }
 
     let (unshield, unshielding_epoch) = match total_fee
         .checked_sub(updated_balance)
     {
         Some(diff) if !diff.is_zero() => {
-            if let Some(spending_key) = args.fee_unshield.clone() {
+            // Funds can't be unshielded to pay the fees of a fee granter
+            if let (None, Some(spending_key)) =
+                (&args.fee_granter, args.fee_unshield.clone())
+            {
                 // Unshield funds for fee payment
                 let target = namada_core::types::masp::TransferTarget::Address(
                     fee_payer_address.clone(),
@@ -644,10 +819,7 @@ pub async fn wrap_tx<'a, N: Namada<'a>>(
                         .format_amount(&token_addr, updated_balance)
                         .await;
                     return Err(Error::from(TxError::BalanceTooLowForFees(
-                        fee_payer_address,
-                        token_addr,
-                        fee_amount,
-                        balance,
+                        fee_source, token_addr, fee_amount, balance,
                     )));
                 }
 
@@ -682,20 +854,103 @@ pub async fn wrap_tx<'a, N: Namada<'a>>(
         fee_payer,
         epoch,
         // TODO: partially validate the gas limit in client
-        args.gas_limit,
+        gas_limit,
         unshield_section_hash,
     );
+    if let Some(granter) = &args.fee_granter {
+        tx.set_fee_granter(granter.clone());
+    }
 
     Ok(unshielding_epoch)
 }
 
+/// Wrap a copy of the given tx with the given gas limit and no fees, and sign
+/// it and its wrapper with dummy signatures: one per key of the signing data,
+/// of the same scheme as the key. The tx then has the size of the signed tx
+/// and its dry run charges the signature checks made by the ledger.
+fn dummy_signed_tx(
+    tx: &Tx,
+    signing_data: &SigningTxData,
+    args: &args::Tx<SdkTypes>,
+    epoch: Epoch,
+    gas_limit: GasLimit,
+) -> Tx {
+    // Sign the targets with throwaway keys in place of the given ones
+    let dummy_section = |targets: Vec<namada_core::types::hash::Hash>,
+                         signer: Signer,
+                         public_keys: Vec<(u8, &common::PublicKey)>| {
+        let mut section = SignatureSection {
+            targets,
+            signer,
+            signatures: BTreeMap::new(),
+        };
+        let hash = section.get_raw_hash();
+        section.signatures = public_keys
+            .into_iter()
+            .map(|(index, public_key)| {
+                let scheme = match public_key {
+                    common::PublicKey::Ed25519(_) => SchemeType::Ed25519,
+                    common::PublicKey::Secp256k1(_) => SchemeType::Secp256k1,
+                };
+                let secret_key = gen_secret_key(scheme, &mut OsRng);
+                (index, common::SigScheme::sign(&secret_key, hash))
+            })
+            .collect();
+        Section::Signature(section)
+    };
+
+    let mut tx = tx.clone();
+    tx.protocol_filter();
+    if let Some(account_public_keys_map) = &signing_data.account_public_keys_map
+    {
+        // Index the signatures as `Tx::sign_raw` does
+        let public_keys: BTreeMap<_, _> = signing_data
+            .public_keys
+            .iter()
+            .filter_map(|public_key| {
+                account_public_keys_map
+                    .get_index_from_public_key(public_key)
+                    .map(|index| (index, public_key))
+            })
+            .collect();
+        let signer = match &signing_data.owner {
+            Some(owner) => Signer::Address(owner.clone()),
+            None => Signer::PubKeys(
+                public_keys.values().map(|&pk| pk.clone()).collect(),
+            ),
+        };
+        let public_keys = public_keys.into_iter().collect();
+        let section =
+            dummy_section(vec![tx.raw_header_hash()], signer, public_keys);
+        tx.add_section(section);
+    }
+
+    tx.add_wrapper(
+        Fee {
+            amount_per_gas_unit: Amount::zero(),
+            token: args.fee_token.clone(),
+        },
+        signing_data.fee_payer.clone(),
+        epoch,
+        gas_limit,
+        None,
+    );
+    let section = dummy_section(
+        tx.sechashes(),
+        Signer::PubKeys(vec![signing_data.fee_payer.clone()]),
+        vec![(0, &signing_data.fee_payer)],
+    );
+    tx.add_section(section);
+    tx
+}
+
 #[allow(clippy::result_large_err)]
 fn other_err<T>(string: String) -> Result<T, Error> {
     Err(Error::Other(string))
 }
 
 /// Represents the transaction data that is displayed on a Ledger device
-#[derive(Default, Serialize, Deserialize)]
+#[derive(Clone, Default, Serialize, Deserialize)]
 pub struct LedgerVector {
     blob: String,
     index: u64,
@@ -980,10 +1235,15 @@ impl<'a> Display for LedgerProposalVote<'a> {
                 VoteType::PGFPayment => {
                     write!(f, "yay for PGF payment proposal")
                 }
+                VoteType::ParameterChange => {
+                    write!(f, "yay for parameter change proposal")
+                }
+                VoteType::Upgrade => write!(f, "yay for upgrade proposal"),
             },
 
             StorageProposalVote::Nay => write!(f, "nay"),
             StorageProposalVote::Abstain => write!(f, "abstain"),
+            StorageProposalVote::NoWithVeto => write!(f, "no with veto"),
         }
     }
 }
@@ -1008,6 +1268,10 @@ impl<'a> Display for LedgerProposalType<'a> {
             }
             ProposalType::PGFSteward(_) => write!(f, "PGF Steward"),
             ProposalType::PGFPayment(_) => write!(f, "PGF Payment"),
+            // List the changes, so that they can be reviewed on the device
+            ProposalType::ParameterChange(_) | ProposalType::Upgrade(_) => {
+                write!(f, "{}", self.0)
+            }
         }
     }
 }
@@ -1035,6 +1299,13 @@ pub async fn to_ledger_vector<'a>(
         ..Default::default()
     };
 
+    if tx.batch().is_some() {
+        return Err(Error::Other(
+            "batches of transactions are not supported by hardware wallets"
+                .to_string(),
+        ));
+    }
+
     let code_sec = tx
         .get_section(tx.code_sechash())
         .ok_or_else(|| {
@@ -1414,6 +1685,10 @@ pub async fn to_ledger_vector<'a>(
             &asset_types,
         )
         .await;
+        if let Some(memo) = tx.memo() {
+            tv.output.push(format!("Memo : {}", memo));
+            tv.output_expert.push(format!("Memo : {}", memo));
+        }
     } else if code_sec.tag == Some(TX_IBC_WASM.to_string()) {
         let any_msg = Any::decode(
             tx.data()
@@ -1762,6 +2037,9 @@ pub async fn to_ledger_vector<'a>(
                 format!("Fees/gas unit : {}", fee_amount_per_gas_unit),
             ]);
         }
+        if let Some(granter) = tx.fee_granter() {
+            tv.output_expert.push(format!("Fee granter : {}", granter));
+        }
     }
 
     // Finally, index each line and break those that are too long
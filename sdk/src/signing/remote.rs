@@ -0,0 +1,313 @@
+//! Signing of transactions by an external signer, such as an HSM or a custody
+//! service, that holds the secret keys on behalf of the client.
+//!
+//! An external signer is sent one JSON request per signature, terminated by a
+//! newline, and must reply with a single line JSON response. A request
+//! contains the public key to sign with, the part of the tx being signed, the
+//! hashes of the tx sections that the signature commits to, the hash to sign
+//! and the same human-readable description of the tx that is displayed by the
+//! hardware wallets:
+//!
+//! ```json
+//! {
+//!   "pubkey": "tpknam1...",
+//!   "part": "raw_header",
+//!   "targets": ["5B3F..."],
+//!   "message": "0C9A...",
+//!   "description": { "blob": "...", "name": "Transfer_0", "output": [...] }
+//! }
+//! ```
+//!
+//! The response contains either the hex-encoded Borsh serialization of the
+//! signature over the message or the reason why the signer refused to sign:
+//!
+//! ```json
+//! { "signature": "00a1..." }
+//! { "error": "..." }
+//! ```
+
+use std::collections::{BTreeMap, HashSet};
+use std::fmt::Display;
+use std::path::PathBuf;
+use std::str::FromStr;
+
+use borsh::BorshDeserialize;
+use data_encoding::HEXLOWER;
+use namada_core::types::key::*;
+use serde::{Deserialize, Serialize};
+
+use super::{to_ledger_vector, LedgerVector, Signable};
+use crate::error::Error;
+use crate::proto::{Section, Signature, Signer, Tx};
+use crate::Namada;
+
+/// A signing request sent to an external signer
+#[derive(Serialize, Deserialize)]
+pub struct SignRequest {
+    /// The public key of the secret key to sign with
+    pub pubkey: common::PublicKey,
+    /// The part of the tx being signed
+    pub part: Signable,
+    /// The hex-encoded hashes of the tx sections committed to by the
+    /// signature
+    pub targets: Vec<String>,
+    /// The hex-encoded hash to sign
+    pub message: String,
+    /// The human-readable description of the tx
+    pub description: LedgerVector,
+}
+
+/// The response of an external signer to a signing request
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SignResponse {
+    /// The hex-encoded Borsh serialization of the signature
+    Signature(String),
+    /// The reason why the signer refused to sign
+    Error(String),
+}
+
+/// A signer holding the secret keys outside of the client
+#[async_trait::async_trait(?Send)]
+pub trait RemoteSigner {
+    /// Sign the message of the given request
+    async fn sign(
+        &self,
+        request: &SignRequest,
+    ) -> Result<common::Signature, Error>;
+}
+
+/// The reference external signers, exchanging the JSON messages of the
+/// module's protocol
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ExternalSigner {
+    /// A program spawned for every request, that reads the request from its
+    /// standard input and writes the response to its standard output
+    Process {
+        /// The path of the program
+        program: PathBuf,
+        /// The arguments of the program
+        args: Vec<String>,
+    },
+    /// A signer listening on a Unix socket, that accepts a connection for
+    /// every request
+    UnixSocket(PathBuf),
+}
+
+impl ExternalSigner {
+    const PROCESS_PREFIX: &'static str = "exec:";
+    const UNIX_SOCKET_PREFIX: &'static str = "unix:";
+
+    /// Send the given serialized request and return the serialized response
+    #[cfg(not(target_family = "wasm"))]
+    async fn exchange(&self, request: String) -> std::io::Result<String> {
+        use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+
+        let request = request + "\n";
+        match self {
+            Self::Process { program, args } => {
+                let mut child = tokio::process::Command::new(program)
+                    .args(args)
+                    .stdin(std::process::Stdio::piped())
+                    .stdout(std::process::Stdio::piped())
+                    .kill_on_drop(true)
+                    .spawn()?;
+                let mut stdin =
+                    child.stdin.take().expect("The stdin must be piped");
+                stdin.write_all(request.as_bytes()).await?;
+                // Close the stdin to signal the end of the request
+                drop(stdin);
+                let output = child.wait_with_output().await?;
+                if !output.status.success() {
+                    return Err(std::io::Error::new(
+                        std::io::ErrorKind::Other,
+                        format!("The signer exited with {}", output.status),
+                    ));
+                }
+                String::from_utf8(output.stdout).map_err(|err| {
+                    std::io::Error::new(std::io::ErrorKind::InvalidData, err)
+                })
+            }
+            #[cfg(unix)]
+            Self::UnixSocket(path) => {
+                let mut stream = tokio::net::UnixStream::connect(path).await?;
+                stream.write_all(request.as_bytes()).await?;
+                let mut response = String::new();
+                BufReader::new(stream).read_line(&mut response).await?;
+                Ok(response)
+            }
+            #[cfg(not(unix))]
+            Self::UnixSocket(_) => Err(std::io::Error::new(
+                std::io::ErrorKind::Unsupported,
+                "Unix sockets are not supported on this platform",
+            )),
+        }
+    }
+}
+
+#[cfg(not(target_family = "wasm"))]
+#[async_trait::async_trait(?Send)]
+impl RemoteSigner for ExternalSigner {
+    async fn sign(
+        &self,
+        request: &SignRequest,
+    ) -> Result<common::Signature, Error> {
+        let request = serde_json::to_string(request).map_err(|err| {
+            Error::Other(format!("Failed to encode the signing request: {err}"))
+        })?;
+        let response = self.exchange(request).await.map_err(|err| {
+            Error::Other(format!(
+                "Failed to communicate with the signer {self}: {err}"
+            ))
+        })?;
+        let response: SignResponse = serde_json::from_str(response.trim())
+            .map_err(|err| {
+                Error::Other(format!(
+                    "Invalid response from the signer {self}: {err}"
+                ))
+            })?;
+        match response {
+            SignResponse::Signature(signature) => HEXLOWER
+                .decode(signature.as_bytes())
+                .ok()
+                .and_then(|bytes| {
+                    common::Signature::try_from_slice(&bytes).ok()
+                })
+                .ok_or_else(|| {
+                    Error::Other(format!(
+                        "Unable to decode the signature from the signer {self}"
+                    ))
+                }),
+            SignResponse::Error(err) => Err(Error::Other(format!(
+                "The signer {self} refused to sign: {err}"
+            ))),
+        }
+    }
+}
+
+impl FromStr for ExternalSigner {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if let Some(command) = s.strip_prefix(Self::PROCESS_PREFIX) {
+            let mut words = command.split_whitespace();
+            let program = words
+                .next()
+                .ok_or_else(|| "The signer program is missing".to_string())?;
+            Ok(Self::Process {
+                program: PathBuf::from(program),
+                args: words.map(str::to_string).collect(),
+            })
+        } else if let Some(path) = s.strip_prefix(Self::UNIX_SOCKET_PREFIX) {
+            if path.is_empty() {
+                return Err("The signer socket path is missing".to_string());
+            }
+            Ok(Self::UnixSocket(PathBuf::from(path)))
+        } else {
+            Err(format!(
+                "Unknown signer {s}, expected either \"{}<program> [args]\" \
+                 or \"{}<socket path>\"",
+                Self::PROCESS_PREFIX,
+                Self::UNIX_SOCKET_PREFIX
+            ))
+        }
+    }
+}
+
+impl Display for ExternalSigner {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Process { program, args } => {
+                write!(f, "{}{}", Self::PROCESS_PREFIX, program.display())?;
+                for arg in args {
+                    write!(f, " {arg}")?;
+                }
+                Ok(())
+            }
+            Self::UnixSocket(path) => {
+                write!(f, "{}{}", Self::UNIX_SOCKET_PREFIX, path.display())
+            }
+        }
+    }
+}
+
+/// Sign the given parts of a transaction with the given remote signer. Meant
+/// to be used as the fallback signing procedure of [`super::sign_tx`] for the
+/// keys that are missing from the software wallet.
+pub async fn sign_with_remote_signer<'a>(
+    context: &impl Namada<'a>,
+    signer: &impl RemoteSigner,
+    mut tx: Tx,
+    pubkey: common::PublicKey,
+    parts: HashSet<Signable>,
+) -> Result<Tx, Error> {
+    let description = to_ledger_vector(context, &tx).await?;
+    tx.protocol_filter();
+    // The raw header must be signed first, as the signature of the fee header
+    // commits to every other section
+    for part in [Signable::RawHeader, Signable::FeeHeader] {
+        if !parts.contains(&part) {
+            continue;
+        }
+        let targets = match part {
+            Signable::RawHeader => vec![tx.raw_header_hash()],
+            Signable::FeeHeader => tx.sechashes(),
+        };
+        let section = Signature {
+            targets,
+            signer: Signer::PubKeys(vec![pubkey.clone()]),
+            signatures: BTreeMap::new(),
+        };
+        let message = section.get_raw_hash();
+        let request = SignRequest {
+            pubkey: pubkey.clone(),
+            part,
+            targets: section.targets.iter().map(ToString::to_string).collect(),
+            message: message.to_string(),
+            description: description.clone(),
+        };
+        let signature = signer.sign(&request).await?;
+        common::SigScheme::verify_signature(&pubkey, &message, &signature)
+            .map_err(|err| {
+                Error::Other(format!(
+                    "The remote signer returned an invalid signature for \
+                     {pubkey}: {err}"
+                ))
+            })?;
+        tx.add_section(Section::Signature(Signature {
+            signatures: [(0, signature)].into(),
+            ..section
+        }));
+    }
+    Ok(tx)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_external_signer_from_str() {
+        let signer: ExternalSigner =
+            "exec:/usr/bin/hsm-signer --slot 2".parse().unwrap();
+        assert_eq!(
+            signer,
+            ExternalSigner::Process {
+                program: PathBuf::from("/usr/bin/hsm-signer"),
+                args: vec!["--slot".to_string(), "2".to_string()],
+            }
+        );
+        assert_eq!(signer.to_string().parse::<ExternalSigner>(), Ok(signer));
+
+        let signer: ExternalSigner = "unix:/run/signer.sock".parse().unwrap();
+        assert_eq!(
+            signer,
+            ExternalSigner::UnixSocket(PathBuf::from("/run/signer.sock"))
+        );
+        assert_eq!(signer.to_string().parse::<ExternalSigner>(), Ok(signer));
+
+        assert!("exec:".parse::<ExternalSigner>().is_err());
+        assert!("unix:".parse::<ExternalSigner>().is_err());
+        assert!("/usr/bin/hsm-signer".parse::<ExternalSigner>().is_err());
+    }
+}
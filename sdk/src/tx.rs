@@ -24,14 +24,17 @@ use namada_core::ibc::core::timestamp::Timestamp as IbcTimestamp;
 use namada_core::ibc::core::Msg;
 use namada_core::ibc::Height as IbcHeight;
 use namada_core::ledger::governance::cli::onchain::{
-    DefaultProposal, OnChainProposal, PgfFundingProposal, PgfStewardProposal,
-    ProposalVote,
+    DefaultProposal, OnChainProposal, ParameterChangeProposal,
+    PgfFundingProposal, PgfStewardProposal, ProposalVote, UpgradeProposal,
 };
 use namada_core::ledger::governance::storage::proposal::ProposalType;
 use namada_core::ledger::governance::storage::vote::StorageProposalVote;
 use namada_core::ledger::ibc::storage::channel_key;
+use namada_core::ledger::parameters::storage as parameter_storage;
 use namada_core::ledger::pgf::cli::steward::Commission;
-use namada_core::types::address::{Address, InternalAddress, MASP};
+use namada_core::types::address::{
+    Address, ImplicitAddress, InternalAddress, MASP,
+};
 use namada_core::types::dec::Dec;
 use namada_core::types::hash::Hash;
 use namada_core::types::ibc::IbcShieldedTransfer;
@@ -41,11 +44,20 @@ use namada_core::types::storage::Epoch;
 use namada_core::types::time::DateTimeUtc;
 use namada_core::types::token::MaspDenom;
 use namada_core::types::transaction::account::{InitAccount, UpdateAccount};
+use namada_core::types::transaction::fee_grant::{
+    GrantFeeAllowance, RevokeFeeAllowance,
+};
 use namada_core::types::transaction::governance::{
-    InitProposalData, VoteProposalData,
+    DelegateVotes, DepositProposalData, InitProposalData, VoteProposalData,
+};
+use namada_core::types::transaction::pgf::{
+    PausePgfFunding, UpdateStewardCommission,
+};
+use namada_core::types::transaction::scheduler::{
+    CancelScheduledTx, Schedule, ScheduleTx,
 };
-use namada_core::types::transaction::pgf::UpdateStewardCommission;
-use namada_core::types::transaction::pos;
+use namada_core::types::transaction::{pos, Fee, GasLimit, TxResult, TxType};
+use namada_core::types::vesting::{Vesting, VestingSchedule};
 use namada_core::types::{storage, token};
 use namada_proof_of_stake::parameters::PosParams;
 use namada_proof_of_stake::types::{CommissionPair, ValidatorState};
@@ -57,7 +69,9 @@ use crate::ibc::core::ics24_host::identifier::{ChannelId, PortId};
 use crate::io::Io;
 use crate::masp::TransferErr::Build;
 use crate::masp::{make_asset_type, ShieldedContext, ShieldedTransfer};
-use crate::proto::{MaspBuilder, Tx};
+use crate::proto::{
+    Batch, BatchedTx, MaspBuilder, Section, SignatureIndex, Tx, MAX_MEMO_LEN,
+};
 use crate::queries::Client;
 use crate::rpc::{
     self, query_wasm_code_hash, validate_amount, TxBroadcastData, TxResponse,
@@ -82,6 +96,10 @@ pub const TX_REACTIVATE_VALIDATOR_WASM: &str = "tx_reactivate_validator.wasm";
 pub const TX_INIT_PROPOSAL: &str = "tx_init_proposal.wasm";
 /// Vote transaction WASM path
 pub const TX_VOTE_PROPOSAL: &str = "tx_vote_proposal.wasm";
+/// Delegate governance votes transaction WASM path
+pub const TX_DELEGATE_VOTES_WASM: &str = "tx_delegate_votes.wasm";
+/// Proposal deposit transaction WASM path
+pub const TX_DEPOSIT_PROPOSAL_WASM: &str = "tx_deposit_proposal.wasm";
 /// Reveal public key transaction WASM path
 pub const TX_REVEAL_PK: &str = "tx_reveal_pk.wasm";
 /// Update validity predicate WASM path
@@ -92,6 +110,8 @@ pub const TX_TRANSFER_WASM: &str = "tx_transfer.wasm";
 pub const TX_IBC_WASM: &str = "tx_ibc.wasm";
 /// User validity predicate WASM path
 pub const VP_USER_WASM: &str = "vp_user.wasm";
+/// Vesting account validity predicate WASM path
+pub const VP_VESTING_WASM: &str = "vp_vesting.wasm";
 /// Validator validity predicate WASM path
 pub const VP_VALIDATOR_WASM: &str = "vp_validator.wasm";
 /// Bond WASM path
@@ -116,8 +136,18 @@ pub const TX_RESIGN_STEWARD: &str = "tx_resign_steward.wasm";
 /// Update steward commission WASM path
 pub const TX_UPDATE_STEWARD_COMMISSION: &str =
     "tx_update_steward_commission.wasm";
+/// Pause pgf funding WASM path
+pub const TX_PAUSE_PGF_FUNDING_WASM: &str = "tx_pause_pgf_funding.wasm";
 /// Redelegate transaction WASM path
 pub const TX_REDELEGATE_WASM: &str = "tx_redelegate.wasm";
+/// Schedule tx transaction WASM path
+pub const TX_SCHEDULE_TX_WASM: &str = "tx_schedule_tx.wasm";
+/// Cancel scheduled tx transaction WASM path
+pub const TX_CANCEL_SCHEDULED_TX_WASM: &str = "tx_cancel_scheduled_tx.wasm";
+/// Grant fee allowance transaction WASM path
+pub const TX_GRANT_FEE_ALLOWANCE_WASM: &str = "tx_grant_fee_allowance.wasm";
+/// Revoke fee allowance transaction WASM path
+pub const TX_REVOKE_FEE_ALLOWANCE_WASM: &str = "tx_revoke_fee_allowance.wasm";
 
 /// Default timeout in seconds for requests to the `/accepted`
 /// and `/applied` ABCI query endpoints.
@@ -177,14 +207,21 @@ pub async fn prepare_tx<'a>(
     context: &impl Namada<'a>,
     args: &args::Tx,
     tx: &mut Tx,
-    fee_payer: common::PublicKey,
+    signing_data: &SigningTxData,
     tx_source_balance: Option<TxSourcePostBalance>,
 ) -> Result<Option<Epoch>> {
     if !args.dry_run {
         let epoch = rpc::query_epoch(context.client()).await?;
 
-        signing::wrap_tx(context, tx, args, tx_source_balance, epoch, fee_payer)
-            .await
+        signing::wrap_tx(
+            context,
+            tx,
+            args,
+            tx_source_balance,
+            epoch,
+            signing_data,
+        )
+        .await
     } else {
         Ok(None)
     }
@@ -208,7 +245,12 @@ pub async fn process_tx<'a>(
     // println!("HTTP request body: {}", request_body);
 
     if args.dry_run || args.dry_run_wrapper {
-        expect_dry_broadcast(TxBroadcastData::DryRun(tx), context).await
+        expect_dry_broadcast(
+            TxBroadcastData::DryRun(tx),
+            args.gas_profile,
+            context,
+        )
+        .await
     } else {
         // We use this to determine when the wrapper tx makes it on-chain
         let wrapper_hash = tx.header_hash().to_string();
@@ -281,7 +323,7 @@ pub async fn build_reveal_pk<'a>(
         args.tx_reveal_code_path.clone(),
         public_key,
         do_nothing,
-        &signing_data.fee_payer,
+        &signing_data,
         None,
     )
     .await
@@ -610,7 +652,7 @@ pub async fn build_validator_commission_change<'a>(
         tx_code_path.clone(),
         data,
         do_nothing,
-        &signing_data.fee_payer,
+        &signing_data,
         None,
     )
     .await
@@ -739,7 +781,7 @@ pub async fn build_validator_metadata_change<'a>(
         tx_code_path.clone(),
         data,
         do_nothing,
-        &signing_data.fee_payer,
+        &signing_data,
         None,
     )
     .await
@@ -798,7 +840,7 @@ pub async fn build_update_steward_commission<'a>(
         tx_code_path.clone(),
         data,
         do_nothing,
-        &signing_data.fee_payer,
+        &signing_data,
         None,
     )
     .await
@@ -838,7 +880,323 @@ pub async fn build_resign_steward<'a>(
         tx_code_path.clone(),
         steward.clone(),
         do_nothing,
-        &signing_data.fee_payer,
+        &signing_data,
+        None,
+    )
+    .await
+    .map(|(tx, epoch)| (tx, signing_data, epoch))
+}
+
+/// Craft transaction for a steward to pause a continuous pgf funding
+pub async fn build_pause_pgf_funding<'a>(
+    context: &impl Namada<'a>,
+    args::PausePgfFunding {
+        tx: tx_args,
+        steward,
+        funding,
+        tx_code_path,
+    }: &args::PausePgfFunding,
+) -> Result<(Tx, SigningTxData, Option<Epoch>)> {
+    let default_signer = Some(steward.clone());
+    let signing_data = signing::aux_signing_data(
+        context,
+        tx_args,
+        Some(steward.clone()),
+        default_signer,
+    )
+    .await?;
+
+    if !rpc::is_steward(context.client(), steward).await && !tx_args.force {
+        edisplay_line!(
+            context.io(),
+            "The given address {} is not a steward.",
+            &steward
+        );
+        return Err(Error::from(TxError::InvalidSteward(steward.clone())));
+    };
+
+    let pgf_funding = rpc::query_pgf_funding(context.client(), funding).await?;
+    if pgf_funding.is_none() && !tx_args.force {
+        edisplay_line!(
+            context.io(),
+            "The pgf funding {} doesn't exist.",
+            funding
+        );
+        return Err(Error::from(TxError::InvalidPgfFunding(funding.clone())));
+    }
+
+    let data = PausePgfFunding {
+        steward: steward.clone(),
+        funding: funding.clone(),
+    };
+
+    build(
+        context,
+        tx_args,
+        tx_code_path.clone(),
+        data,
+        do_nothing,
+        &signing_data,
+        None,
+    )
+    .await
+    .map(|(tx, epoch)| (tx, signing_data, epoch))
+}
+
+/// Build a tx to schedule the execution of a signed tx at a future block
+/// height or epoch, escrowing the fees for its whole gas limit from the owner
+pub async fn build_schedule_tx<'a>(
+    context: &impl Namada<'a>,
+    args::ScheduleTx {
+        tx: tx_args,
+        owner,
+        scheduled_tx,
+        scheduled_signatures,
+        schedule,
+        scheduled_gas_limit,
+        tx_code_path,
+    }: &args::ScheduleTx,
+) -> Result<(Tx, SigningTxData, Option<Epoch>)> {
+    let default_signer = Some(owner.clone());
+    let signing_data = signing::aux_signing_data(
+        context,
+        tx_args,
+        Some(owner.clone()),
+        default_signer,
+    )
+    .await?;
+
+    let mut scheduled = Tx::deserialize(scheduled_tx.as_ref()).map_err(|_| {
+        Error::Other("Invalid scheduled tx deserialization.".to_string())
+    })?;
+    let signatures = scheduled_signatures
+        .iter()
+        .map(|bytes| {
+            SignatureIndex::deserialize(bytes).map_err(|_| {
+                Error::Other("Invalid signature deserialization.".to_string())
+            })
+        })
+        .collect::<Result<Vec<_>>>()?;
+    scheduled.add_signatures(signatures);
+
+    let epoch = rpc::query_epoch(context.client()).await?;
+    let height = rpc::query_block(context.client())
+        .await?
+        .map(|block| block.height)
+        .unwrap_or_default();
+    let is_in_future = match schedule {
+        // The height of the block including the tx is at least the next one
+        Schedule::AtHeight(at) => *at > height.next_height(),
+        Schedule::AtEpoch(_) => schedule.is_after(height, epoch),
+    };
+    if !is_in_future && !tx_args.force {
+        edisplay_line!(
+            context.io(),
+            "The schedule {} is not in the future, the last block is at \
+             height {} in epoch {}.",
+            schedule,
+            height,
+            epoch
+        );
+        return Err(Error::from(TxError::InvalidSchedule(*schedule)));
+    }
+
+    // The scheduled tx pays the minimum gas price unless a fee amount is given
+    let minimum_gas_price = rpc::query_storage_value::<
+        _,
+        BTreeMap<Address, token::Amount>,
+    >(context.client(), &parameter_storage::get_gas_cost_key())
+    .await?
+    .get(&tx_args.fee_token)
+    .copied()
+    .unwrap_or_default();
+    let amount_per_gas_unit = match tx_args.fee_amount {
+        Some(amount) => {
+            let validated = validate_amount(
+                context,
+                amount,
+                &tx_args.fee_token,
+                tx_args.force,
+            )
+            .await?;
+            token::Amount::from_uint(validated.amount, 0)
+                .map_err(|e| Error::Other(e.to_string()))?
+                .max(minimum_gas_price)
+        }
+        None => minimum_gas_price,
+    };
+
+    let data = ScheduleTx {
+        owner: owner.clone(),
+        schedule: *schedule,
+        fee: Fee {
+            amount_per_gas_unit,
+            token: tx_args.fee_token.clone(),
+        },
+        gas_limit: *scheduled_gas_limit,
+        tx: scheduled,
+    };
+
+    build(
+        context,
+        tx_args,
+        tx_code_path.clone(),
+        data,
+        do_nothing,
+        &signing_data,
+        None,
+    )
+    .await
+    .map(|(tx, epoch)| (tx, signing_data, epoch))
+}
+
+/// Build a tx to cancel a scheduled tx and get its escrowed fees refunded
+pub async fn build_cancel_scheduled_tx<'a>(
+    context: &impl Namada<'a>,
+    args::CancelScheduledTx {
+        tx: tx_args,
+        id,
+        owner,
+        tx_code_path,
+    }: &args::CancelScheduledTx,
+) -> Result<(Tx, SigningTxData, Option<Epoch>)> {
+    let default_signer = Some(owner.clone());
+    let signing_data = signing::aux_signing_data(
+        context,
+        tx_args,
+        Some(owner.clone()),
+        default_signer,
+    )
+    .await?;
+
+    let is_owned = rpc::query_scheduled_tx(context.client(), *id)
+        .await?
+        .map(|scheduled| &scheduled.owner == owner)
+        .unwrap_or_default();
+    if !is_owned && !tx_args.force {
+        edisplay_line!(
+            context.io(),
+            "There's no pending scheduled tx {} of {}.",
+            id,
+            owner
+        );
+        return Err(Error::from(TxError::ScheduledTxNotFound(
+            *id,
+            owner.clone(),
+        )));
+    }
+
+    let data = CancelScheduledTx {
+        id: *id,
+        owner: owner.clone(),
+    };
+
+    build(
+        context,
+        tx_args,
+        tx_code_path.clone(),
+        data,
+        do_nothing,
+        &signing_data,
+        None,
+    )
+    .await
+    .map(|(tx, epoch)| (tx, signing_data, epoch))
+}
+
+/// Build a tx to let a granter pay the wrapper fees of a grantee, up to a
+/// spend limit in a token
+pub async fn build_grant_fee_allowance<'a>(
+    context: &impl Namada<'a>,
+    args::GrantFeeAllowance {
+        tx: tx_args,
+        granter,
+        grantee,
+        token,
+        spend_limit,
+        expiration,
+        tx_code_path,
+    }: &args::GrantFeeAllowance,
+) -> Result<(Tx, SigningTxData, Option<Epoch>)> {
+    let default_signer = Some(granter.clone());
+    let signing_data = signing::aux_signing_data(
+        context,
+        tx_args,
+        Some(granter.clone()),
+        default_signer,
+    )
+    .await?;
+
+    let spend_limit =
+        validate_amount(context, *spend_limit, token, tx_args.force).await?;
+
+    let data = GrantFeeAllowance {
+        granter: granter.clone(),
+        grantee: grantee.clone(),
+        token: token.clone(),
+        spend_limit: spend_limit.amount,
+        expiration: *expiration,
+    };
+
+    build(
+        context,
+        tx_args,
+        tx_code_path.clone(),
+        data,
+        do_nothing,
+        &signing_data,
+        None,
+    )
+    .await
+    .map(|(tx, epoch)| (tx, signing_data, epoch))
+}
+
+/// Build a tx to revoke the fee allowance granted to a grantee
+pub async fn build_revoke_fee_allowance<'a>(
+    context: &impl Namada<'a>,
+    args::RevokeFeeAllowance {
+        tx: tx_args,
+        granter,
+        grantee,
+        tx_code_path,
+    }: &args::RevokeFeeAllowance,
+) -> Result<(Tx, SigningTxData, Option<Epoch>)> {
+    let default_signer = Some(granter.clone());
+    let signing_data = signing::aux_signing_data(
+        context,
+        tx_args,
+        Some(granter.clone()),
+        default_signer,
+    )
+    .await?;
+
+    let allowance =
+        rpc::query_fee_allowance(context.client(), granter, grantee).await?;
+    if allowance.is_none() && !tx_args.force {
+        edisplay_line!(
+            context.io(),
+            "There's no fee allowance granted by {} to {}.",
+            granter,
+            grantee
+        );
+        return Err(Error::from(TxError::FeeAllowanceNotFound(
+            granter.clone(),
+            grantee.clone(),
+        )));
+    }
+
+    let data = RevokeFeeAllowance {
+        granter: granter.clone(),
+        grantee: grantee.clone(),
+    };
+
+    build(
+        context,
+        tx_args,
+        tx_code_path.clone(),
+        data,
+        do_nothing,
+        &signing_data,
         None,
     )
     .await
@@ -945,7 +1303,7 @@ pub async fn build_unjail_validator<'a>(
         tx_code_path.clone(),
         validator.clone(),
         do_nothing,
-        &signing_data.fee_payer,
+        &signing_data,
         None,
     )
     .await
@@ -1016,7 +1374,7 @@ pub async fn build_deactivate_validator<'a>(
         tx_code_path.clone(),
         validator.clone(),
         do_nothing,
-        &signing_data.fee_payer,
+        &signing_data,
         None,
     )
     .await
@@ -1086,7 +1444,7 @@ pub async fn build_reactivate_validator<'a>(
         tx_code_path.clone(),
         validator.clone(),
         do_nothing,
-        &signing_data.fee_payer,
+        &signing_data,
         None,
     )
     .await
@@ -1266,7 +1624,7 @@ pub async fn build_redelegation<'a>(
         tx_code_path.clone(),
         data,
         do_nothing,
-        &signing_data.fee_payer,
+        &signing_data,
         None,
     )
     .await
@@ -1349,7 +1707,7 @@ pub async fn build_withdraw<'a>(
         tx_code_path.clone(),
         data,
         do_nothing,
-        &signing_data.fee_payer,
+        &signing_data,
         None,
     )
     .await
@@ -1397,7 +1755,7 @@ pub async fn build_claim_rewards<'a>(
         tx_code_path.clone(),
         data,
         do_nothing,
-        &signing_data.fee_payer,
+        &signing_data,
         None,
     )
     .await
@@ -1504,7 +1862,7 @@ pub async fn build_unbond<'a>(
         tx_code_path.clone(),
         data,
         do_nothing,
-        &signing_data.fee_payer,
+        &signing_data,
         None,
     )
     .await?;
@@ -1689,7 +2047,7 @@ pub async fn build_bond<'a>(
         tx_code_path.clone(),
         data,
         do_nothing,
-        &signing_data.fee_payer,
+        &signing_data,
         tx_source_balance,
     )
     .await
@@ -1706,6 +2064,8 @@ pub async fn build_default_proposal<'a>(
         is_offline: _,
         is_pgf_stewards: _,
         is_pgf_funding: _,
+        is_parameter_change: _,
+        is_upgrade: _,
         tx_code_path,
     }: &args::InitProposal,
     proposal: DefaultProposal,
@@ -1742,7 +2102,7 @@ pub async fn build_default_proposal<'a>(
         tx_code_path.clone(),
         init_proposal_data,
         push_data,
-        &signing_data.fee_payer,
+        &signing_data,
         None, // TODO: need to pay the fee to submit a proposal
     )
     .await
@@ -1829,13 +2189,145 @@ pub async fn build_vote_proposal<'a>(
         tx_code_path.clone(),
         data,
         do_nothing,
-        &signing_data.fee_payer,
+        &signing_data,
         None,
     )
     .await
     .map(|(tx, epoch)| (tx, signing_data, epoch))
 }
 
+/// Build a tx to delegate the governance votes of an address, or to revoke
+/// the delegation
+pub async fn build_delegate_votes<'a>(
+    context: &impl Namada<'a>,
+    args::DelegateVotes {
+        tx: tx_args,
+        delegator,
+        delegate,
+        proposal_kind,
+        tx_code_path,
+    }: &args::DelegateVotes,
+) -> Result<(Tx, SigningTxData, Option<Epoch>)> {
+    let default_signer = Some(delegator.clone());
+    let signing_data = signing::aux_signing_data(
+        context,
+        tx_args,
+        Some(delegator.clone()),
+        default_signer,
+    )
+    .await?;
+
+    if rpc::is_validator(context.client(), delegator).await? {
+        edisplay_line!(
+            context.io(),
+            "Validators vote with their own stake only."
+        );
+        if !tx_args.force {
+            return Err(Error::from(TxError::ValidatorVoteDelegation(
+                delegator.clone(),
+            )));
+        }
+    }
+    if delegate.as_ref() == Some(delegator) && !tx_args.force {
+        return Err(Error::from(TxError::SelfVoteDelegation(
+            delegator.clone(),
+        )));
+    }
+
+    let data = DelegateVotes {
+        delegator: delegator.clone(),
+        delegate: delegate.clone(),
+        proposal_kind: *proposal_kind,
+    };
+
+    build(
+        context,
+        tx_args,
+        tx_code_path.clone(),
+        data,
+        do_nothing,
+        &signing_data,
+        None,
+    )
+    .await
+    .map(|(tx, epoch)| (tx, signing_data, epoch))
+}
+
+/// Build a tx to top up the deposit of a governance proposal
+pub async fn build_deposit_proposal<'a>(
+    context: &impl Namada<'a>,
+    args::DepositProposal {
+        tx: tx_args,
+        proposal_id,
+        depositor,
+        amount,
+        native_token,
+        tx_code_path,
+    }: &args::DepositProposal,
+) -> Result<(Tx, SigningTxData, Option<Epoch>)> {
+    let default_signer = Some(depositor.clone());
+    let signing_data = signing::aux_signing_data(
+        context,
+        tx_args,
+        Some(depositor.clone()),
+        default_signer,
+    )
+    .await?;
+
+    let proposal = if let Some(proposal) =
+        rpc::query_proposal_by_id(context.client(), *proposal_id).await?
+    {
+        proposal
+    } else {
+        return Err(Error::from(TxError::ProposalDoesNotExist(*proposal_id)));
+    };
+
+    let epoch = rpc::query_epoch(context.client()).await?;
+    if epoch >= proposal.voting_start_epoch {
+        if tx_args.force {
+            eprintln!("Invalid proposal {} deposit period.", proposal_id);
+        } else {
+            return Err(Error::from(TxError::InvalidProposalDepositPeriod(
+                *proposal_id,
+            )));
+        }
+    }
+
+    let balance_key = token::balance_key(native_token, depositor);
+    let post_balance = check_balance_too_low_err(
+        native_token,
+        depositor,
+        *amount,
+        balance_key,
+        tx_args.force,
+        context,
+    )
+    .await?;
+    let tx_source_balance = Some(TxSourcePostBalance {
+        post_balance,
+        source: depositor.clone(),
+        token: native_token.clone(),
+    });
+
+    let data = DepositProposalData {
+        id: *proposal_id,
+        depositor: depositor.clone(),
+        amount: *amount,
+    };
+
+    build(
+        context,
+        tx_args,
+        tx_code_path.clone(),
+        data,
+        do_nothing,
+        &signing_data,
+        tx_source_balance,
+    )
+    .await
+    .map(|(tx, epoch)| (tx, signing_data, epoch))
+}
+
 /// Build a pgf funding proposal governance
 pub async fn build_pgf_funding_proposal<'a>(
     context: &impl Namada<'a>,
@@ -1846,6 +2338,8 @@ pub async fn build_pgf_funding_proposal<'a>(
         is_offline: _,
         is_pgf_stewards: _,
         is_pgf_funding: _,
+        is_parameter_change: _,
+        is_upgrade: _,
         tx_code_path,
     }: &args::InitProposal,
     proposal: PgfFundingProposal,
@@ -1874,7 +2368,7 @@ pub async fn build_pgf_funding_proposal<'a>(
         tx_code_path.clone(),
         init_proposal_data,
         add_section,
-        &signing_data.fee_payer,
+        &signing_data,
         None, // TODO: need to pay the fee to submit a proposal
     )
     .await
@@ -1891,6 +2385,8 @@ pub async fn build_pgf_stewards_proposal<'a>(
         is_offline: _,
         is_pgf_stewards: _,
         is_pgf_funding: _,
+        is_parameter_change: _,
+        is_upgrade: _,
         tx_code_path,
     }: &args::InitProposal,
     proposal: PgfStewardProposal,
@@ -1920,7 +2416,103 @@ pub async fn build_pgf_stewards_proposal<'a>(
         tx_code_path.clone(),
         init_proposal_data,
         add_section,
-        &signing_data.fee_payer,
+        &signing_data,
+        None, // TODO: need to pay the fee to submit a proposal
+    )
+    .await
+    .map(|(tx, epoch)| (tx, signing_data, epoch))
+}
+
+/// Build a parameter change proposal governance
+pub async fn build_parameter_change_proposal<'a>(
+    context: &impl Namada<'a>,
+    args::InitProposal {
+        tx,
+        proposal_data: _,
+        native_token: _,
+        is_offline: _,
+        is_pgf_stewards: _,
+        is_pgf_funding: _,
+        is_parameter_change: _,
+        is_upgrade: _,
+        tx_code_path,
+    }: &args::InitProposal,
+    proposal: ParameterChangeProposal,
+) -> Result<(Tx, SigningTxData, Option<Epoch>)> {
+    let default_signer = Some(proposal.proposal.author.clone());
+    let signing_data = signing::aux_signing_data(
+        context,
+        tx,
+        Some(proposal.proposal.author.clone()),
+        default_signer,
+    )
+    .await?;
+
+    let init_proposal_data = InitProposalData::try_from(proposal.clone())
+        .map_err(|e| TxError::InvalidProposal(e.to_string()))?;
+
+    let add_section = |tx: &mut Tx, data: &mut InitProposalData| {
+        let (_, extra_section_hash) =
+            tx.add_extra_section(proposal_to_vec(proposal.proposal)?, None);
+        data.content = extra_section_hash;
+        Ok(())
+    };
+
+    build(
+        context,
+        tx,
+        tx_code_path.clone(),
+        init_proposal_data,
+        add_section,
+        &signing_data,
+        None, // TODO: need to pay the fee to submit a proposal
+    )
+    .await
+    .map(|(tx, epoch)| (tx, signing_data, epoch))
+}
+
+/// Build a software upgrade proposal governance
+pub async fn build_upgrade_proposal<'a>(
+    context: &impl Namada<'a>,
+    args::InitProposal {
+        tx,
+        proposal_data: _,
+        native_token: _,
+        is_offline: _,
+        is_pgf_stewards: _,
+        is_pgf_funding: _,
+        is_parameter_change: _,
+        is_upgrade: _,
+        tx_code_path,
+    }: &args::InitProposal,
+    proposal: UpgradeProposal,
+) -> Result<(Tx, SigningTxData, Option<Epoch>)> {
+    let default_signer = Some(proposal.proposal.author.clone());
+    let signing_data = signing::aux_signing_data(
+        context,
+        tx,
+        Some(proposal.proposal.author.clone()),
+        default_signer,
+    )
+    .await?;
+
+    let init_proposal_data = InitProposalData::try_from(proposal.clone())
+        .map_err(|e| TxError::InvalidProposal(e.to_string()))?;
+
+    let add_section = |tx: &mut Tx, data: &mut InitProposalData| {
+        let (_, extra_section_hash) =
+            tx.add_extra_section(proposal_to_vec(proposal.proposal)?, None);
+        data.content = extra_section_hash;
+        Ok(())
+    };
+
+    build(
+        context,
+        tx,
+        tx_code_path.clone(),
+        init_proposal_data,
+        add_section,
+        &signing_data,
         None, // TODO: need to pay the fee to submit a proposal
     )
     .await
@@ -2047,7 +2639,7 @@ pub async fn build_ibc_transfer<'a>(
         context,
         &args.tx,
         &mut tx,
-        signing_data.fee_payer.clone(),
+        &signing_data,
         tx_source_balance,
     )
     .await?;
@@ -2063,7 +2655,7 @@ pub async fn build<'a, F, D>(
     path: PathBuf,
     data: D,
     on_tx: F,
-    gas_payer: &common::PublicKey,
+    signing_data: &SigningTxData,
     tx_source_balance: Option<TxSourcePostBalance>,
 ) -> Result<(Tx, Option<Epoch>)>
 where
@@ -2076,7 +2668,7 @@ where
         path,
         data,
         on_tx,
-        gas_payer,
+        signing_data,
         tx_source_balance,
     )
     .await
@@ -2089,7 +2681,7 @@ async fn build_pow_flag<'a, F, D>(
     path: PathBuf,
     mut data: D,
     on_tx: F,
-    gas_payer: &common::PublicKey,
+    signing_data: &SigningTxData,
     tx_source_balance: Option<TxSourcePostBalance>,
 ) -> Result<(Tx, Option<Epoch>)>
 where
@@ -2117,7 +2709,7 @@ where
         context,
         tx_args,
         &mut tx_builder,
-        gas_payer.clone(),
+        signing_data,
         tx_source_balance,
     )
     .await?;
@@ -2196,6 +2788,15 @@ pub async fn build_transfer<'a, N: Namada<'a>>(
     let source = args.source.effective_address();
     let target = args.target.effective_address();
 
+    if let Some(memo) = &args.memo {
+        if memo.len() > MAX_MEMO_LEN {
+            return Err(Error::from(TxError::MemoTooLong(
+                memo.len(),
+                MAX_MEMO_LEN,
+            )));
+        }
+    }
+
     // Check that the source address exists on chain
     source_exists_or_err(source.clone(), args.tx.force, context).await?;
     // Check that the target address exists on chain
@@ -2290,7 +2891,11 @@ pub async fn build_transfer<'a, N: Namada<'a>>(
         shielded: None,
     };
 
+    let memo = args.memo.clone();
     let add_shielded = |tx: &mut Tx, transfer: &mut token::Transfer| {
+        if let Some(memo) = memo {
+            tx.add_memo(memo);
+        }
         // Add the MASP Transaction and its Builder to facilitate validation
         if let Some(ShieldedTransfer {
             builder,
@@ -2324,7 +2929,7 @@ pub async fn build_transfer<'a, N: Namada<'a>>(
         args.tx_code_path.clone(),
         transfer,
         add_shielded,
-        &signing_data.fee_payer,
+        &signing_data,
         tx_source_balance,
     )
     .await?;
@@ -2359,6 +2964,7 @@ pub async fn build_init_account<'a>(
         tx_code_path,
         public_keys,
         threshold,
+        vesting,
     }: &args::TxInitAccount,
 ) -> Result<(Tx, SigningTxData, Option<Epoch>)> {
     let signing_data =
@@ -2377,11 +2983,42 @@ pub async fn build_init_account<'a>(
         }
     };
 
+    let vesting = match vesting {
+        Some(args::AccountVesting {
+            token,
+            amount,
+            schedule,
+        }) => {
+            if let VestingSchedule::Linear { start, end } = schedule {
+                if end <= start && !tx_args.force {
+                    edisplay_line!(
+                        context.io(),
+                        "The vesting end {} must be later than its start {}.",
+                        end,
+                        start
+                    );
+                    return Err(Error::from(TxError::InvalidVestingSchedule(
+                        *start, *end,
+                    )));
+                }
+            }
+            let amount =
+                validate_amount(context, *amount, token, tx_args.force).await?;
+            Some(Vesting {
+                token: token.clone(),
+                amount: amount.amount,
+                schedule: *schedule,
+            })
+        }
+        None => None,
+    };
+
     let data = InitAccount {
         public_keys: public_keys.clone(),
         // We will add the hash inside the add_code_hash function
         vp_code_hash: Hash::zero(),
         threshold,
+        vesting,
     };
 
     let add_code_hash = |tx: &mut Tx, data: &mut InitAccount| {
@@ -2398,7 +3035,7 @@ pub async fn build_init_account<'a>(
         tx_code_path.clone(),
         data,
         add_code_hash,
-        &signing_data.fee_payer,
+        &signing_data,
         None,
     )
     .await
@@ -2415,6 +3052,7 @@ pub async fn build_update_account<'a>(
         addr,
         public_keys,
         threshold,
+        key_policies,
     }: &args::TxUpdateAccount,
 ) -> Result<(Tx, SigningTxData, Option<Epoch>)> {
     let default_signer = Some(addr.clone());
@@ -2426,16 +3064,42 @@ pub async fn build_update_account<'a>(
     )
     .await?;
 
-    let addr = if let Some(account) =
-        rpc::get_account_info(context.client(), addr).await?
-    {
-        account.address
+    let account = rpc::get_account_info(context.client(), addr).await?;
+    let addr = if let Some(account) = &account {
+        account.address.clone()
     } else if tx_args.force {
         addr.clone()
     } else {
         return Err(Error::from(TxError::LocationDoesNotExist(addr.clone())));
     };
 
+    // The policies must restrict the keys that the account will have
+    if let Some(key_policies) = key_policies {
+        for public_key in key_policies.keys() {
+            let is_account_key = if public_keys.is_empty() {
+                account.as_ref().map_or(false, |account| {
+                    account.get_index_from_public_key(public_key).is_some()
+                })
+            } else {
+                public_keys.contains(public_key)
+            };
+            if !is_account_key {
+                edisplay_line!(
+                    context.io(),
+                    "The public key {} with a policy is not a key of the \
+                     account {}.",
+                    public_key,
+                    addr
+                );
+                if !tx_args.force {
+                    return Err(Error::from(TxError::KeyPolicyWithoutKey(
+                        public_key.clone(),
+                    )));
+                }
+            }
+        }
+    }
+
     let vp_code_hash = match vp_code_path {
         Some(code_path) => {
             let vp_hash = query_wasm_code_hash_buf(context, code_path).await?;
@@ -2460,6 +3124,7 @@ pub async fn build_update_account<'a>(
         vp_code_hash: extra_section_hash,
         public_keys: public_keys.clone(),
         threshold: *threshold,
+        key_policies: key_policies.clone(),
     };
 
     let add_code_hash = |tx: &mut Tx, data: &mut UpdateAccount| {
@@ -2480,7 +3145,7 @@ pub async fn build_update_account<'a>(
         tx_code_path.clone(),
         data,
         add_code_hash,
-        &signing_data.fee_payer,
+        &signing_data,
         None,
     )
     .await
@@ -2526,18 +3191,107 @@ pub async fn build_custom<'a>(
         tx
     };
 
-    let epoch = prepare_tx(
-        context,
-        tx_args,
-        &mut tx,
-        signing_data.fee_payer.clone(),
-        None,
-    )
-    .await?;
+    let epoch =
+        prepare_tx(context, tx_args, &mut tx, &signing_data, None).await?;
 
     Ok((tx, signing_data, epoch))
 }
 
+/// Compose transactions made by the other `build_*` functions into a batch,
+/// whose inner transactions are applied atomically and in the given order under
+/// the wrapper of the first transaction, which is charged the fees. The gas
+/// limit of the batch is the sum of those of all the wrappers. The batch must
+/// be signed with the signing data of each of its inner transactions.
+pub fn build_batch(
+    txs: Vec<(Tx, SigningTxData)>,
+) -> Result<(Tx, Vec<SigningTxData>)> {
+    let header = match txs.first() {
+        Some((tx, _)) => tx.header(),
+        None => {
+            return Err(Error::Other(
+                "Cannot build an empty batch".to_string(),
+            ));
+        }
+    };
+    let mut batch_tx = Tx {
+        header,
+        sections: vec![],
+    };
+    let mut batch = Batch::default();
+    let mut gas_limit: u64 = 0;
+    let mut signing_data = Vec::with_capacity(txs.len());
+
+    for (index, (tx, tx_signing_data)) in txs.into_iter().enumerate() {
+        if tx.batch().is_some() {
+            return Err(Error::Other(
+                "Batches of transactions cannot be nested".to_string(),
+            ));
+        }
+        if let Some(wrapper) = tx.header.wrapper() {
+            if index > 0 && wrapper.unshield_section_hash.is_some() {
+                return Err(Error::Other(
+                    "Only the first transaction of a batch can unshield the \
+                     fees"
+                        .to_string(),
+                ));
+            }
+            gas_limit = gas_limit
+                .checked_add(u64::from(wrapper.gas_limit))
+                .ok_or_else(|| {
+                    Error::Other("Overflow in the gas limit".to_string())
+                })?;
+        }
+        batch.txs.push(BatchedTx {
+            code_hash: *tx.code_sechash(),
+            data_hash: *tx.data_sechash(),
+        });
+        for section in tx.sections {
+            // The inner txs are not signed yet, and the same code may be
+            // shared by several of them
+            if !matches!(section, Section::Signature(_))
+                && batch_tx.get_section(&section.get_hash()).is_none()
+            {
+                batch_tx.add_section(section);
+            }
+        }
+        signing_data.push(tx_signing_data);
+    }
+
+    if let TxType::Wrapper(wrapper) = &mut batch_tx.header.tx_type {
+        wrapper.gas_limit = GasLimit::from(gas_limit);
+    }
+    batch_tx.set_batch(batch);
+
+    Ok((batch_tx, signing_data))
+}
+
+/// Compose a transaction made by another `build_*` function into a batch that
+/// first reveals the public key of the given implicit address, unless it's
+/// already revealed. Otherwise, the transaction is returned as is.
+pub async fn build_batch_with_reveal_pk<'a>(
+    context: &impl Namada<'a>,
+    args: &args::Tx,
+    address: &Address,
+    (tx, signing_data): (Tx, SigningTxData),
+) -> Result<(Tx, Vec<SigningTxData>)> {
+    if let Address::Implicit(ImplicitAddress(pkh)) = address {
+        if is_reveal_pk_needed(context.client(), address, args.force).await? {
+            let public_key = context
+                .wallet()
+                .await
+                .find_public_key_by_pkh(pkh)
+                .map_err(|e| Error::Other(e.to_string()))?;
+            let (reveal_tx, reveal_signing_data, _epoch) =
+                build_reveal_pk(context, args, &public_key).await?;
+            return build_batch(vec![
+                (reveal_tx, reveal_signing_data),
+                (tx, signing_data),
+            ]);
+        }
+    }
+    Ok((tx, vec![signing_data]))
+}
+
 /// Generate IBC shielded transfer
 pub async fn gen_ibc_shielded_transfer<'a, N: Namada<'a>>(
     context: &N,
@@ -2656,11 +3410,15 @@ async fn get_ibc_src_port_channel<'a>(
 
 async fn expect_dry_broadcast<'a>(
     to_broadcast: TxBroadcastData,
+    gas_profile: bool,
     context: &impl Namada<'a>,
 ) -> Result<ProcessTxResponse> {
     match to_broadcast {
         TxBroadcastData::DryRun(tx) => {
-            rpc::dry_run_tx(context, tx.to_bytes()).await?;
+            let result = rpc::dry_run_tx(context, tx.to_bytes()).await?;
+            if gas_profile {
+                display_gas_profile(context, &result);
+            }
             Ok(ProcessTxResponse::DryRun)
         }
         TxBroadcastData::Live {
@@ -2671,6 +3429,22 @@ async fn expect_dry_broadcast<'a>(
     }
 }
 
+/// Display the gas used per section by the tx and by each of the triggered
+/// VPs in a dry run
+fn display_gas_profile<'a>(context: &impl Namada<'a>, result: &TxResult) {
+    if let Some(profile) = &result.gas_profile {
+        display_line!(context.io(), "Transaction gas profile:\n{}", profile);
+    }
+    for (vp, profile) in &result.vps_result.vps_gas_profile {
+        display_line!(
+            context.io(),
+            "Validity predicate of {} gas profile:\n{}",
+            vp,
+            profile
+        );
+    }
+}
+
 fn lift_rpc_error<T>(res: std::result::Result<T, RpcError>) -> Result<T> {
     res.map_err(|err| Error::from(TxError::TxBroadcast(err)))
 }
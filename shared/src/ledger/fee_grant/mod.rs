@@ -0,0 +1,92 @@
+//! Fee grants VP
+
+use std::collections::BTreeSet;
+
+use namada_core::ledger::fee_grant::storage::keys as fee_grant_storage;
+use namada_core::ledger::storage;
+use namada_core::proto::Tx;
+use namada_core::types::transaction::fee_grant::FeeAllowance;
+use thiserror::Error;
+
+use crate::ledger::native_vp;
+use crate::ledger::native_vp::{Ctx, NativeVp};
+use crate::ledger::storage_api::StorageRead;
+use crate::types::address::{Address, InternalAddress};
+use crate::types::storage::Key;
+use crate::vm::WasmCacheAccess;
+
+/// for handling FeeGrant NativeVP errors
+pub type Result<T> = std::result::Result<T, Error>;
+
+/// The fee grants internal address
+pub const ADDRESS: Address = Address::Internal(InternalAddress::FeeGrant);
+
+#[allow(missing_docs)]
+#[derive(Error, Debug)]
+pub enum Error {
+    #[error("Native VP error: {0}")]
+    NativeVpError(#[from] native_vp::Error),
+}
+
+/// Fee grants VP
+pub struct FeeGrantVp<'a, DB, H, CA>
+where
+    DB: storage::DB + for<'iter> storage::DBIter<'iter>,
+    H: storage::StorageHasher,
+    CA: WasmCacheAccess,
+{
+    /// Context to interact with the host structures.
+    pub ctx: Ctx<'a, DB, H, CA>,
+}
+
+impl<'a, DB, H, CA> NativeVp for FeeGrantVp<'a, DB, H, CA>
+where
+    DB: 'static + storage::DB + for<'iter> storage::DBIter<'iter>,
+    H: 'static + storage::StorageHasher,
+    CA: 'static + WasmCacheAccess,
+{
+    type Error = Error;
+
+    fn validate_tx(
+        &self,
+        _tx_data: &Tx,
+        keys_changed: &BTreeSet<Key>,
+        verifiers: &BTreeSet<Address>,
+    ) -> Result<bool> {
+        Ok(keys_changed.iter().all(|key| {
+            let result = self.is_valid_key(key, verifiers);
+            match &result {
+                Err(err) => {
+                    tracing::info!("Key {key} rejected with error: {err:#?}.")
+                }
+                Ok(false) => tracing::info!("Key {key} rejected"),
+                Ok(true) => {}
+            }
+            result.unwrap_or(false)
+        }))
+    }
+}
+
+impl<'a, DB, H, CA> FeeGrantVp<'a, DB, H, CA>
+where
+    DB: 'static + storage::DB + for<'iter> storage::DBIter<'iter>,
+    H: 'static + storage::StorageHasher,
+    CA: 'static + WasmCacheAccess,
+{
+    fn is_valid_key(
+        &self,
+        key: &Key,
+        verifiers: &BTreeSet<Address>,
+    ) -> Result<bool> {
+        if let Some((granter, _grantee)) =
+            fee_grant_storage::is_allowance_key(key)
+        {
+            // Only the granter can change the allowances it grants, which
+            // must be well-formed unless revoked
+            self.ctx.post().read::<FeeAllowance>(key)?;
+            return Ok(verifiers.contains(granter));
+        }
+        // Any other change in the fee grants subspace is invalid
+        Ok(!fee_grant_storage::is_fee_grant_key(key))
+    }
+}
@@ -5,15 +5,20 @@ pub mod utils;
 use std::collections::BTreeSet;
 
 use borsh::BorshDeserialize;
+use namada_core::ledger::governance::cli::is_valid_parameter_change_data;
 use namada_core::ledger::governance::storage::keys as gov_storage;
 use namada_core::ledger::governance::storage::proposal::{
-    AddRemove, ProposalType,
+    AddRemove, PGFAction, ProposalType,
+};
+use namada_core::ledger::governance::storage::vote::{
+    StorageProposalVote, VoteDelegation,
 };
-use namada_core::ledger::governance::storage::vote::StorageProposalVote;
 use namada_core::ledger::governance::utils::is_valid_validator_voting_period;
 use namada_core::ledger::storage;
 use namada_core::ledger::storage_api::account;
-use namada_core::ledger::storage_api::governance::is_proposal_accepted;
+use namada_core::ledger::storage_api::governance::{
+    is_proposal_accepted, update_vote_delegation,
+};
 use namada_core::ledger::vp_env::VpEnv;
 use namada_core::proto::Tx;
 use namada_proof_of_stake::is_validator;
@@ -48,6 +53,8 @@ pub enum Error {
     InvalidVoteKey(String),
     #[error("Vote type is not compatible with proposal type.")]
     InvalidVoteType,
+    #[error("Deposit key is not valid: {0}")]
+    InvalidDepositKey(String),
 }
 
 /// Governance VP
@@ -91,6 +98,18 @@ where
                 (KeyType::VOTE, Some(proposal_id)) => {
                     self.is_valid_vote_key(proposal_id, key, verifiers)
                 }
+                (KeyType::DELEGATE_VOTE, Some(proposal_id)) => {
+                    self.is_valid_delegate_vote_key(proposal_id, key, verifiers)
+                }
+                (KeyType::VOTE_DELEGATION, _) => {
+                    self.is_valid_vote_delegation_key(key, verifiers)
+                }
+                (KeyType::VOTE_DELEGATOR, _) => {
+                    self.is_valid_vote_delegator_key(key, verifiers)
+                }
+                (KeyType::DEPOSIT, Some(proposal_id)) => {
+                    self.is_valid_deposit(proposal_id, key, verifiers)
+                }
                 (KeyType::CONTENT, Some(proposal_id)) => {
                     self.is_valid_content_key(proposal_id)
                 }
@@ -109,9 +128,11 @@ where
                 (KeyType::END_EPOCH, Some(proposal_id)) => {
                     self.is_valid_end_epoch(proposal_id)
                 }
-                (KeyType::FUNDS, Some(proposal_id)) => {
-                    self.is_valid_funds(proposal_id, &native_token)
-                }
+                (KeyType::FUNDS, Some(proposal_id)) => self.is_valid_funds(
+                    proposal_id,
+                    &native_token,
+                    keys_changed,
+                ),
                 (KeyType::AUTHOR, Some(proposal_id)) => {
                     self.is_valid_author(proposal_id, verifiers)
                 }
@@ -120,7 +141,9 @@ where
                     self.is_valid_proposal_commit()
                 }
                 (KeyType::PARAMETER, _) => self.is_valid_parameter(tx_data),
-                (KeyType::BALANCE, _) => self.is_valid_balance(&native_token),
+                (KeyType::BALANCE, _) => {
+                    self.is_valid_balance(&native_token, keys_changed)
+                }
                 (KeyType::UNKNOWN_GOVERNANCE, _) => Ok(false),
                 (KeyType::UNKNOWN, _) => Ok(true),
                 _ => Ok(false),
@@ -272,6 +295,178 @@ where
         Ok(is_delegator)
     }
 
+    /// Validate the key of a vote that may be followed by the addresses
+    /// delegating their votes to the voter
+    fn is_valid_delegate_vote_key(
+        &self,
+        proposal_id: u64,
+        key: &Key,
+        verifiers: &BTreeSet<Address>,
+    ) -> Result<bool> {
+        let voter = gov_storage::is_delegate_vote_key(key)
+            .ok_or_else(|| Error::InvalidVoteKey(key.to_string()))?;
+
+        let counter_key = gov_storage::get_counter_key();
+        let pre_counter: u64 = self.force_read(&counter_key, ReadType::Pre)?;
+        if pre_counter <= proposal_id {
+            tracing::info!(
+                "Invalid proposal ID. Expected {pre_counter} or lower, got \
+                 {proposal_id}."
+            );
+            return Ok(false);
+        }
+
+        if !verifiers.contains(voter) {
+            return Ok(false);
+        }
+
+        let proposal_type: ProposalType = self.force_read(
+            &gov_storage::get_proposal_type_key(proposal_id),
+            ReadType::Pre,
+        )?;
+        let vote: StorageProposalVote = self.force_read(key, ReadType::Post)?;
+        if !vote.is_compatible(&proposal_type) {
+            return Err(Error::InvalidVoteType);
+        }
+
+        let current_epoch = self.ctx.get_block_epoch()?;
+        let pre_voting_start_epoch: Epoch = self.force_read(
+            &gov_storage::get_voting_start_epoch_key(proposal_id),
+            ReadType::Pre,
+        )?;
+        let pre_voting_end_epoch: Epoch = self.force_read(
+            &gov_storage::get_voting_end_epoch_key(proposal_id),
+            ReadType::Pre,
+        )?;
+        let is_validator = is_validator(&self.ctx.pre(), voter)?;
+
+        Ok(self.is_valid_voting_window(
+            current_epoch,
+            pre_voting_start_epoch,
+            pre_voting_end_epoch,
+            is_validator,
+        ))
+    }
+
+    /// Validate a vote delegation key. Validators vote with their own stake
+    /// only, so they can't delegate their votes. A new delegate can only be
+    /// appointed from the next epoch, so that the delegations followed on the
+    /// proposals in their voting period don't change, and must be indexed.
+    fn is_valid_vote_delegation_key(
+        &self,
+        key: &Key,
+        verifiers: &BTreeSet<Address>,
+    ) -> Result<bool> {
+        let (delegator, kind) = gov_storage::is_vote_delegation_key(key)
+            .ok_or_else(|| Error::InvalidVoteKey(key.to_string()))?;
+
+        if !verifiers.contains(delegator)
+            || is_validator(&self.ctx.pre(), delegator)?
+        {
+            return Ok(false);
+        }
+
+        let current_epoch = self.ctx.get_block_epoch()?;
+        let pre: Option<VoteDelegation> = self.ctx.pre().read(key)?;
+        let post: Option<VoteDelegation> = self.ctx.post().read(key)?;
+        let new_delegate = match &post {
+            Some(post) => match post.delegates.get(&current_epoch.next()) {
+                Some(delegate) => delegate.clone(),
+                None => return Ok(false),
+            },
+            None => None,
+        };
+        if new_delegate.as_ref() == Some(delegator) {
+            return Ok(false);
+        }
+        let expected = update_vote_delegation(
+            &self.ctx.pre(),
+            pre.unwrap_or_default(),
+            new_delegate.clone(),
+        )?;
+        if post != expected {
+            return Ok(false);
+        }
+
+        match new_delegate {
+            Some(delegate) => {
+                let index_key = gov_storage::get_vote_delegator_key(
+                    &delegate, delegator, kind,
+                );
+                Ok(self.ctx.has_key_post(&index_key)?)
+            }
+            None => Ok(true),
+        }
+    }
+
+    /// Validate a key indexing a vote delegation by one of its delegates. The
+    /// key must be present exactly while the delegation has the delegate.
+    fn is_valid_vote_delegator_key(
+        &self,
+        key: &Key,
+        verifiers: &BTreeSet<Address>,
+    ) -> Result<bool> {
+        let (delegate, delegator, kind) =
+            gov_storage::is_vote_delegator_key(key)
+                .ok_or_else(|| Error::InvalidVoteKey(key.to_string()))?;
+
+        if !verifiers.contains(delegator) {
+            return Ok(false);
+        }
+
+        let delegation_key =
+            gov_storage::get_vote_delegation_key(delegator, kind);
+        let delegation: Option<VoteDelegation> =
+            self.ctx.post().read(&delegation_key)?;
+        let is_delegate = delegation.map_or(false, |delegation| {
+            delegation.delegates().any(|address| address == delegate)
+        });
+        Ok(self.ctx.has_key_post(key)? == is_delegate)
+    }
+
+    /// Validate the deposit made by an address on a proposal, that must
+    /// match the increase of the proposal funds. The deposit made on a new
+    /// proposal must be the one of its author.
+    fn is_valid_deposit(
+        &self,
+        proposal_id: u64,
+        key: &Key,
+        verifiers: &BTreeSet<Address>,
+    ) -> Result<bool> {
+        let (_, depositor) = gov_storage::is_deposit_key(key)
+            .ok_or_else(|| Error::InvalidDepositKey(key.to_string()))?;
+
+        if !verifiers.contains(depositor) {
+            return Ok(false);
+        }
+
+        let funds_key = gov_storage::get_funds_key(proposal_id);
+        let pre_funds: Option<token::Amount> =
+            self.ctx.pre().read(&funds_key)?;
+        let post_funds: token::Amount =
+            self.force_read(&funds_key, ReadType::Post)?;
+        let pre_deposit: token::Amount =
+            self.ctx.pre().read(key)?.unwrap_or_default();
+        let post_deposit: token::Amount =
+            self.force_read(key, ReadType::Post)?;
+
+        if post_deposit <= pre_deposit {
+            return Ok(false);
+        }
+
+        match pre_funds {
+            Some(pre_funds) => Ok(post_funds > pre_funds
+                && post_deposit - pre_deposit == post_funds - pre_funds),
+            None => {
+                let author: Address = self.force_read(
+                    &gov_storage::get_author_key(proposal_id),
+                    ReadType::Post,
+                )?;
+                Ok(depositor == &author && post_deposit == post_funds)
+            }
+        }
+    }
+
     /// Validate a content key
     pub fn is_valid_content_key(&self, proposal_id: u64) -> Result<bool> {
         let content_key: Key = gov_storage::get_content_key(proposal_id);
@@ -335,10 +530,28 @@ where
             }
             ProposalType::PGFPayment(payments) => {
                 if payments.len() > MAX_PGF_ACTIONS {
-                    Ok(false)
-                } else {
-                    Ok(true)
+                    return Ok(false);
                 }
+                // Only check the schedule of the added fundings, a removal
+                // matches on the recipient alone
+                let is_valid_fundings =
+                    payments.iter().all(|payment| match payment {
+                        PGFAction::Continuous(AddRemove::Add(funding)) => {
+                            funding.is_valid()
+                        }
+                        PGFAction::Continuous(AddRemove::Remove(funding)) => {
+                            funding.recipient.is_valid()
+                        }
+                        PGFAction::Retro(_) => true,
+                    });
+                Ok(is_valid_fundings)
+            }
+            ProposalType::ParameterChange(changes) => {
+                Ok(is_valid_parameter_change_data(&changes).is_ok())
+            }
+            ProposalType::Upgrade(plan) => {
+                let current_height = self.ctx.get_block_height()?;
+                Ok(!plan.name.trim().is_empty() && plan.height > current_height)
             }
             _ => Ok(true), // default proposal
         }
@@ -509,49 +722,70 @@ where
         &self,
         proposal_id: u64,
         native_token_address: &Address,
+        keys_changed: &BTreeSet<Key>,
     ) -> Result<bool> {
         let funds_key = gov_storage::get_funds_key(proposal_id);
-        let balance_key =
-            token::balance_key(native_token_address, self.ctx.address);
         let min_funds_parameter_key = gov_storage::get_min_proposal_fund_key();
 
         let min_funds_parameter: token::Amount =
             self.force_read(&min_funds_parameter_key, ReadType::Pre)?;
-        let pre_balance: Option<token::Amount> =
-            self.ctx.pre().read(&balance_key)?;
-        let post_balance: token::Amount =
-            self.force_read(&balance_key, ReadType::Post)?;
+        let pre_funds: Option<token::Amount> =
+            self.ctx.pre().read(&funds_key)?;
         let post_funds: token::Amount =
             self.force_read(&funds_key, ReadType::Post)?;
 
-        if let Some(pre_balance) = pre_balance {
-            Ok(post_funds >= min_funds_parameter
-                && post_balance - pre_balance == post_funds)
-        } else {
-            Ok(post_funds >= min_funds_parameter && post_balance == post_funds)
+        if let Some(pre_funds) = pre_funds {
+            // The funds of an existing proposal can only be topped up before
+            // its voting period starts
+            let pre_voting_start_epoch: Epoch = self.force_read(
+                &gov_storage::get_voting_start_epoch_key(proposal_id),
+                ReadType::Pre,
+            )?;
+            let current_epoch = self.ctx.get_block_epoch()?;
+            if post_funds <= pre_funds
+                || current_epoch >= pre_voting_start_epoch
+            {
+                return Ok(false);
+            }
+        } else if post_funds < min_funds_parameter {
+            return Ok(false);
         }
+
+        self.is_valid_balance(native_token_address, keys_changed)
     }
 
-    /// Validate a balance key
-    fn is_valid_balance(&self, native_token_address: &Address) -> Result<bool> {
+    /// Validate a balance key. The balance of the governance address can
+    /// only increase by the funds deposited on the proposals.
+    fn is_valid_balance(
+        &self,
+        native_token_address: &Address,
+        keys_changed: &BTreeSet<Key>,
+    ) -> Result<bool> {
         let balance_key =
             token::balance_key(native_token_address, self.ctx.address);
-        let min_funds_parameter_key = gov_storage::get_min_proposal_fund_key();
 
-        let pre_balance: Option<token::Amount> =
-            self.ctx.pre().read(&balance_key)?;
-
-        let min_funds_parameter: token::Amount =
-            self.force_read(&min_funds_parameter_key, ReadType::Pre)?;
+        let pre_balance: token::Amount =
+            self.ctx.pre().read(&balance_key)?.unwrap_or_default();
         let post_balance: token::Amount =
             self.force_read(&balance_key, ReadType::Post)?;
 
-        if let Some(pre_balance) = pre_balance {
-            Ok(post_balance > pre_balance
-                && post_balance - pre_balance >= min_funds_parameter)
-        } else {
-            Ok(post_balance >= min_funds_parameter)
+        let mut deposited = token::Amount::zero();
+        for key in keys_changed {
+            if !gov_storage::is_balance_key(key) {
+                continue;
+            }
+            let pre_funds: token::Amount =
+                self.ctx.pre().read(key)?.unwrap_or_default();
+            let post_funds: token::Amount =
+                self.force_read(key, ReadType::Post)?;
+            match post_funds.checked_sub(pre_funds) {
+                Some(funds) => deposited += funds,
+                None => return Ok(false),
+            }
         }
+
+        Ok(post_balance > pre_balance
+            && post_balance - pre_balance == deposited)
     }
 
     /// Validate a author key
@@ -689,6 +923,14 @@ enum KeyType {
     #[allow(non_camel_case_types)]
     VOTE,
     #[allow(non_camel_case_types)]
+    DELEGATE_VOTE,
+    #[allow(non_camel_case_types)]
+    VOTE_DELEGATION,
+    #[allow(non_camel_case_types)]
+    VOTE_DELEGATOR,
+    #[allow(non_camel_case_types)]
+    DEPOSIT,
+    #[allow(non_camel_case_types)]
     CONTENT,
     #[allow(non_camel_case_types)]
     PROPOSAL_CODE,
@@ -720,6 +962,14 @@ impl KeyType {
     fn from_key(key: &Key, native_token: &Address) -> Self {
         if gov_storage::is_vote_key(key) {
             Self::VOTE
+        } else if gov_storage::is_delegate_vote_key(key).is_some() {
+            Self::DELEGATE_VOTE
+        } else if gov_storage::is_vote_delegation_key(key).is_some() {
+            Self::VOTE_DELEGATION
+        } else if gov_storage::is_vote_delegator_key(key).is_some() {
+            Self::VOTE_DELEGATOR
+        } else if gov_storage::is_deposit_key(key).is_some() {
+            Self::DEPOSIT
         } else if gov_storage::is_content_key(key) {
             KeyType::CONTENT
         } else if gov_storage::is_proposal_type_key(key) {
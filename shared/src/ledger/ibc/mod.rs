@@ -1,11 +1,36 @@
 //! IBC integration
 
+use std::cell::RefCell;
+use std::collections::BTreeSet;
+use std::rc::Rc;
+use std::str::FromStr;
+use std::time::Duration;
+
+use namada_core::ibc::applications::transfer::msgs::transfer::MsgTransfer;
+use namada_core::ibc::applications::transfer::packet::PacketData;
+use namada_core::ibc::applications::transfer::PrefixedCoin;
+use namada_core::ibc::core::ics04_channel::timeout::TimeoutHeight;
+use namada_core::ibc::core::ics24_host::identifier::{ChannelId, PortId};
+use namada_core::ibc::core::Msg;
+use namada_core::ledger::governance::storage::proposal::PGFIbcTarget;
 pub use namada_core::ledger::ibc::storage;
 use namada_core::ledger::ibc::storage::{
     channel_counter_key, client_counter_key, connection_counter_key,
 };
-use namada_core::ledger::storage::WlStorage;
-use namada_core::ledger::storage_api::StorageWrite;
+use namada_core::ledger::ibc::{
+    IbcActions, IbcCommonContext, IbcStorageContext,
+};
+use namada_core::ledger::storage::{PrefixIter, WlStorage};
+use namada_core::ledger::storage_api::{
+    self, ResultExt, StorageRead, StorageWrite,
+};
+use namada_core::types::address::{Address, InternalAddress};
+use namada_core::types::ibc::{IbcEvent, IbcShieldedTransfer};
+use namada_core::types::storage::{
+    BlockHash, BlockHeight, Epoch, Header, Key, TxIndex,
+};
+use namada_core::types::token::{self, DenominatedAmount};
+use prost::Message;
 
 use crate::ledger::storage::{self as ledger_storage, StorageHasher};
 
@@ -39,3 +64,247 @@ where
         .write_bytes(&key, value)
         .expect("Unable to write the initial channel counter");
 }
+
+/// IBC context used by the protocol to send tokens out of the chain while
+/// finalizing a block. The changes are written with the protocol writes of
+/// the wrapped storage and the current block header is readable, unlike with
+/// the plain [`WlStorage`].
+#[derive(Debug)]
+struct IbcProtocolContext<'a, D, H>
+where
+    D: ledger_storage::DB + for<'iter> ledger_storage::DBIter<'iter>,
+    H: StorageHasher,
+{
+    wl_storage: &'a mut WlStorage<D, H>,
+}
+
+impl<D, H> StorageRead for IbcProtocolContext<'_, D, H>
+where
+    D: 'static + ledger_storage::DB + for<'iter> ledger_storage::DBIter<'iter>,
+    H: 'static + StorageHasher,
+{
+    type PrefixIter<'iter>
+        = PrefixIter<'iter, D>
+    where
+        Self: 'iter;
+
+    fn read_bytes(&self, key: &Key) -> storage_api::Result<Option<Vec<u8>>> {
+        self.wl_storage.read_bytes(key)
+    }
+
+    fn has_key(&self, key: &Key) -> storage_api::Result<bool> {
+        self.wl_storage.has_key(key)
+    }
+
+    fn iter_prefix<'iter>(
+        &'iter self,
+        prefix: &Key,
+    ) -> storage_api::Result<Self::PrefixIter<'iter>> {
+        self.wl_storage.iter_prefix(prefix)
+    }
+
+    fn iter_next<'iter>(
+        &'iter self,
+        iter: &mut Self::PrefixIter<'iter>,
+    ) -> storage_api::Result<Option<(String, Vec<u8>)>> {
+        self.wl_storage.iter_next(iter)
+    }
+
+    fn get_chain_id(&self) -> storage_api::Result<String> {
+        self.wl_storage.get_chain_id()
+    }
+
+    fn get_block_height(&self) -> storage_api::Result<BlockHeight> {
+        self.wl_storage.get_block_height()
+    }
+
+    fn get_block_header(
+        &self,
+        height: BlockHeight,
+    ) -> storage_api::Result<Option<Header>> {
+        // The header of the block being finalized isn't in the DB yet
+        let (header, _gas) = self
+            .wl_storage
+            .storage
+            .get_block_header(Some(height))
+            .into_storage_result()?;
+        Ok(header)
+    }
+
+    fn get_block_hash(&self) -> storage_api::Result<BlockHash> {
+        self.wl_storage.get_block_hash()
+    }
+
+    fn get_block_epoch(&self) -> storage_api::Result<Epoch> {
+        self.wl_storage.get_block_epoch()
+    }
+
+    fn get_tx_index(&self) -> storage_api::Result<TxIndex> {
+        self.wl_storage.get_tx_index()
+    }
+
+    fn get_native_token(&self) -> storage_api::Result<Address> {
+        self.wl_storage.get_native_token()
+    }
+}
+
+impl<D, H> StorageWrite for IbcProtocolContext<'_, D, H>
+where
+    D: 'static + ledger_storage::DB + for<'iter> ledger_storage::DBIter<'iter>,
+    H: 'static + StorageHasher,
+{
+    fn write_bytes(
+        &mut self,
+        key: &Key,
+        val: impl AsRef<[u8]>,
+    ) -> storage_api::Result<()> {
+        self.wl_storage.write_bytes(key, val)
+    }
+
+    fn delete(&mut self, key: &Key) -> storage_api::Result<()> {
+        self.wl_storage.delete(key)
+    }
+}
+
+impl<D, H> IbcStorageContext for IbcProtocolContext<'_, D, H>
+where
+    D: 'static + ledger_storage::DB + for<'iter> ledger_storage::DBIter<'iter>,
+    H: 'static + StorageHasher,
+{
+    fn emit_ibc_event(&mut self, event: IbcEvent) -> storage_api::Result<()> {
+        let _gas = self.wl_storage.write_log.emit_ibc_event(event);
+        Ok(())
+    }
+
+    fn get_ibc_events(
+        &self,
+        event_type: impl AsRef<str>,
+    ) -> storage_api::Result<Vec<IbcEvent>> {
+        Ok(self
+            .wl_storage
+            .write_log
+            .get_ibc_events()
+            .iter()
+            .filter(|event| event.event_type == event_type.as_ref())
+            .cloned()
+            .collect())
+    }
+
+    fn transfer_token(
+        &mut self,
+        src: &Address,
+        dest: &Address,
+        token: &Address,
+        amount: DenominatedAmount,
+    ) -> storage_api::Result<()> {
+        storage_api::token::transfer(self, token, src, dest, amount.amount)
+    }
+
+    fn handle_masp_tx(
+        &mut self,
+        _shielded: &IbcShieldedTransfer,
+    ) -> storage_api::Result<()> {
+        Err(storage_api::Error::new_const(
+            "Shielded IBC transfers are not supported by the protocol",
+        ))
+    }
+
+    fn mint_token(
+        &mut self,
+        target: &Address,
+        token: &Address,
+        amount: DenominatedAmount,
+    ) -> storage_api::Result<()> {
+        storage_api::token::credit_tokens(self, token, target, amount.amount)?;
+        let minter_key = token::minter_key(token);
+        self.write(&minter_key, Address::Internal(InternalAddress::Ibc))
+    }
+
+    fn burn_token(
+        &mut self,
+        target: &Address,
+        token: &Address,
+        amount: DenominatedAmount,
+    ) -> storage_api::Result<()> {
+        storage_api::token::burn(self, token, target, amount.amount)
+    }
+
+    fn log_string(&self, message: String) {
+        tracing::debug!("IBC: {message}");
+    }
+}
+
+impl<D, H> IbcCommonContext for IbcProtocolContext<'_, D, H>
+where
+    D: 'static + ledger_storage::DB + for<'iter> ledger_storage::DBIter<'iter>,
+    H: 'static + StorageHasher,
+{
+}
+
+/// Send `amount` of `token` from the `source` account to a receiver on
+/// another chain with an ICS-20 transfer executed by the protocol. The packet
+/// times out an hour after the current block time. Returns the IBC events
+/// emitted by the transfer.
+pub fn transfer_over_ibc<D, H>(
+    wl_storage: &mut WlStorage<D, H>,
+    token: &Address,
+    source: &Address,
+    target: &PGFIbcTarget,
+    amount: token::Amount,
+) -> storage_api::Result<BTreeSet<IbcEvent>>
+where
+    D: 'static + ledger_storage::DB + for<'iter> ledger_storage::DBIter<'iter>,
+    H: 'static + StorageHasher,
+{
+    let port_id = PortId::from_str(&target.port_id).into_storage_result()?;
+    let channel_id =
+        ChannelId::from_str(&target.channel_id).into_storage_result()?;
+    let denom = storage_api::token::read_denom(wl_storage, token)?.ok_or_else(
+        || storage_api::Error::new_const("No denomination found for token"),
+    )?;
+    let coin = PrefixedCoin {
+        denom: token
+            .to_string()
+            .parse()
+            .map_err(|e| storage_api::Error::new(format!("{e}")))?,
+        // Set the IBC amount as an integer
+        amount: DenominatedAmount { amount, denom }.into(),
+    };
+    let packet_data = PacketData {
+        token: coin,
+        sender: source.to_string().into(),
+        receiver: target.receiver.clone().into(),
+        memo: String::default().into(),
+    };
+
+    let ctx = Rc::new(RefCell::new(IbcProtocolContext { wl_storage }));
+    let now = ctx
+        .borrow()
+        .host_timestamp()
+        .map_err(|e| storage_api::Error::new(e.to_string()))?;
+    // we cannot set 0 to both the timeout height and the timestamp
+    let timeout_timestamp = (now + Duration::new(3600, 0))
+        .map_err(|e| storage_api::Error::new(e.to_string()))?;
+    let msg = MsgTransfer {
+        port_id_on_a: port_id,
+        chan_id_on_a: channel_id,
+        packet_data,
+        timeout_height_on_b: TimeoutHeight::Never,
+        timeout_timestamp_on_b: timeout_timestamp,
+    };
+    let data = msg.to_any().encode_to_vec();
+
+    let mut actions = IbcActions::new(ctx.clone());
+    let result = actions
+        .validate(&data)
+        .and_then(|()| actions.execute(&data))
+        .into_storage_result();
+    drop(actions);
+
+    let ctx = Rc::try_unwrap(ctx)
+        .map_err(|_| storage_api::Error::new_const("IBC context is in use"))?
+        .into_inner();
+    // The events of a failed transfer mustn't be left in the write log
+    let events = ctx.wl_storage.write_log.take_ibc_events();
+    result.map(|()| events)
+}
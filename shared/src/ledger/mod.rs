@@ -1,11 +1,13 @@
 //! The ledger modules
 
-pub use namada_sdk::{eth_bridge, events};
+pub use namada_sdk::{eth_bridge, events, key_changes};
+pub mod fee_grant;
 pub mod governance;
 pub mod ibc;
 pub mod native_vp;
 pub mod pgf;
 pub mod pos;
+pub mod scheduler;
 #[cfg(feature = "wasm-runtime")]
 pub mod protocol;
 pub use namada_sdk::queries;
@@ -23,7 +25,9 @@ use namada_sdk::queries::{EncodedResponseQuery, RequestCtx, RequestQuery};
 use crate::vm::wasm::{TxCache, VpCache};
 use crate::vm::WasmCacheAccess;
 
-/// Dry run a transaction
+/// Dry run a transaction. The execution of the inner transaction and of the
+/// VPs it triggers is profiled, so the result reports the gas used per host
+/// function.
 #[cfg(feature = "wasm-runtime")]
 pub fn dry_run_tx<D, H, CA>(
     mut ctx: RequestCtx<'_, D, H, VpCache<CA>, TxCache<CA>>,
@@ -35,20 +39,249 @@ where
     CA: 'static + WasmCacheAccess + Sync,
 {
     use borsh_ext::BorshSerializeExt;
-    use namada_core::ledger::gas::{Gas, GasMetering, TxGasMeter};
     use namada_core::ledger::storage::TempWlStorage;
+
+    let wl_storage = ctx.wl_storage;
+    let mut temp_wl_storage = TempWlStorage::new(&wl_storage.storage);
+    let (data, _wrapper_fee) =
+        run_tx(&mut ctx, &mut temp_wl_storage, &request.data, true, false)?;
+    // NOTE: the keys changed by the wrapper transaction (if any) are not
+    // returned from this function
+    let data = data.serialize_to_vec();
+    Ok(EncodedResponseQuery {
+        data,
+        proof: None,
+        info: Default::default(),
+    })
+}
+
+/// Simulate a transaction, returning the values of the changed keys, the
+/// outcome of every triggered VP and the fee charged by the wrapper, if any
+#[cfg(feature = "wasm-runtime")]
+pub fn simulate_tx<D, H, CA>(
+    mut ctx: RequestCtx<'_, D, H, VpCache<CA>, TxCache<CA>>,
+    request: &RequestQuery,
+) -> storage_api::Result<EncodedResponseQuery>
+where
+    D: 'static + DB + for<'iter> DBIter<'iter> + Sync,
+    H: 'static + StorageHasher + Sync,
+    CA: 'static + WasmCacheAccess + Sync,
+{
+    use std::collections::BTreeMap;
+
+    use borsh_ext::BorshSerializeExt;
+    use namada_core::ledger::storage::TempWlStorage;
+    use namada_core::types::transaction::{KeyDiff, TxSimulation};
+
+    let wl_storage = ctx.wl_storage;
+    let storage = &wl_storage.storage;
+    let mut temp_wl_storage = TempWlStorage::new(storage);
+    let (result, wrapper_fee) =
+        run_tx(&mut ctx, &mut temp_wl_storage, &request.data, false, false)?;
+    // The changes of an inner tx rejected by the VPs would be dropped
+    if result.is_accepted() {
+        temp_wl_storage.write_log.commit_tx();
+    } else {
+        temp_wl_storage.write_log.drop_tx();
+    }
+
+    let mut state_diff = BTreeMap::new();
+    for (key, post) in temp_wl_storage.write_log.get_block_changes() {
+        let (pre, _gas) = storage.read(&key).into_storage_result()?;
+        state_diff.insert(key, KeyDiff { pre, post });
+    }
+    let simulation = TxSimulation {
+        vps: result.vps_result.outcomes(),
+        result,
+        state_diff,
+        wrapper_fee,
+    };
+    Ok(EncodedResponseQuery {
+        data: simulation.serialize_to_vec(),
+        proof: None,
+        info: Default::default(),
+    })
+}
+
+/// The number of recent blocks whose gas utilization determines the gas prices
+/// suggested by [`estimate_fee`]
+#[cfg(feature = "wasm-runtime")]
+const FEE_ESTIMATE_BLOCKS: u64 = 10;
+
+/// The safety margin, in percent, that [`estimate_fee`] adds to the gas used by
+/// the dry run of a transaction to suggest its gas limit. It covers the changes
+/// of the state between the estimate and the inclusion of the transaction in a
+/// block.
+#[cfg(feature = "wasm-runtime")]
+const FEE_ESTIMATE_GAS_MARGIN_PERCENT: u64 = 10;
+
+/// Estimate the gas limit of a transaction by dry running it, and suggest a gas
+/// price for each of the fee tokens from the gas utilization of the recent
+/// blocks.
+///
+/// The transaction is expected to be wrapped and to carry dummy signatures,
+/// one per key of its real signers, so that the gas of the wrapper and of the
+/// signature checks is part of the estimate. The signatures are charged but
+/// not verified.
+#[cfg(feature = "wasm-runtime")]
+pub fn estimate_fee<D, H, CA>(
+    mut ctx: RequestCtx<'_, D, H, VpCache<CA>, TxCache<CA>>,
+    request: &RequestQuery,
+) -> storage_api::Result<EncodedResponseQuery>
+where
+    D: 'static + DB + for<'iter> DBIter<'iter> + Sync,
+    H: 'static + StorageHasher + Sync,
+    CA: 'static + WasmCacheAccess + Sync,
+{
+    use std::collections::BTreeMap;
+
+    use borsh_ext::BorshSerializeExt;
+    use namada_core::ledger::storage::TempWlStorage;
+    use namada_core::ledger::storage_api::StorageRead;
+    use namada_core::types::address::Address;
+    use namada_core::types::token;
+    use namada_core::types::transaction::{FeeEstimate, GasLimit};
+
+    let wl_storage = ctx.wl_storage;
+    let mut temp_wl_storage = TempWlStorage::new(&wl_storage.storage);
+    let (result, _wrapper_fee) =
+        run_tx(&mut ctx, &mut temp_wl_storage, &request.data, false, true)?;
+
+    let gas_used = result.gas_used.get_whole_gas_units();
+    // Round the margin up
+    let gas_limit = GasLimit::from(
+        gas_used
+            .saturating_mul(100 + FEE_ESTIMATE_GAS_MARGIN_PERCENT)
+            .saturating_add(99)
+            / 100,
+    );
+    let block_utilization = recent_block_utilization(wl_storage)?;
+    let minimum_gas_prices: BTreeMap<Address, token::Amount> = wl_storage
+        .read(&parameters::storage::get_gas_cost_key())?
+        .unwrap_or_default();
+    let gas_prices = minimum_gas_prices
+        .into_iter()
+        .map(|(token, minimum)| {
+            (token, suggest_gas_price(minimum, block_utilization))
+        })
+        .collect();
+
+    let estimate = FeeEstimate {
+        result,
+        gas_limit,
+        block_utilization,
+        gas_prices,
+    };
+    Ok(EncodedResponseQuery {
+        data: estimate.serialize_to_vec(),
+        proof: None,
+        info: Default::default(),
+    })
+}
+
+/// The average ratio, capped at one, of the gas used by the transactions of the
+/// last [`FEE_ESTIMATE_BLOCKS`] committed blocks to the maximum gas of a block
+#[cfg(feature = "wasm-runtime")]
+fn recent_block_utilization<D, H>(
+    wl_storage: &namada_core::ledger::storage::WlStorage<D, H>,
+) -> storage_api::Result<namada_core::types::dec::Dec>
+where
+    D: 'static + DB + for<'iter> DBIter<'iter> + Sync,
+    H: 'static + StorageHasher + Sync,
+{
+    use borsh::BorshDeserialize;
+    use namada_core::types::dec::Dec;
+
+    use crate::ledger::events::{Event, EventType};
+
+    let max_block_gas = gas::get_max_block_gas(wl_storage)?;
+    let last_height = wl_storage.storage.get_last_block_height().0;
+    let blocks = last_height.min(FEE_ESTIMATE_BLOCKS);
+    let capacity = max_block_gas.saturating_mul(blocks);
+    if capacity == 0 {
+        return Ok(Dec::zero());
+    }
+
+    let mut gas_used: u64 = 0;
+    for height in (last_height - blocks + 1)..=last_height {
+        let events = wl_storage
+            .storage
+            .db
+            .read_events(height.into())
+            .into_storage_result()?;
+        for bytes in events {
+            let event = Event::try_from_slice(&bytes).into_storage_result()?;
+            // Both the wrappers and the decrypted txs consume block gas
+            if !matches!(
+                event.event_type,
+                EventType::Accepted | EventType::Applied
+            ) {
+                continue;
+            }
+            let tx_gas = event
+                .attributes
+                .get("gas_used")
+                .and_then(|gas| gas.parse::<u64>().ok())
+                .unwrap_or_default();
+            gas_used = gas_used.saturating_add(tx_gas);
+        }
+    }
+    let utilization = Dec::from(gas_used.min(capacity)) / Dec::from(capacity);
+    Ok(utilization)
+}
+
+/// Suggest a gas price from the minimum one and the utilization of the recent
+/// blocks. The minimum price is suggested up to half utilization, above which
+/// the price grows linearly up to twice the minimum at full utilization.
+#[cfg(feature = "wasm-runtime")]
+fn suggest_gas_price(
+    minimum: namada_core::types::token::Amount,
+    block_utilization: namada_core::types::dec::Dec,
+) -> namada_core::types::token::Amount {
+    use namada_core::types::dec::Dec;
+
+    let multiplier = std::cmp::max(Dec::one(), block_utilization * 2_u64);
+    minimum.mul_ceil(multiplier)
+}
+
+/// Apply the transaction encoded in `tx_bytes` and its wrapper, if any, on the
+/// temporary storage. Returns the result of the inner transaction, with the
+/// gas used by both, and the fee charged by the wrapper. The changes of the
+/// wrapper are committed in the temporary storage, those of the inner
+/// transaction are left in the write log of the transaction. With
+/// `dummy_signatures`, the signatures of the transaction and of its wrapper
+/// are charged but not verified.
+#[cfg(feature = "wasm-runtime")]
+fn run_tx<D, H, CA>(
+    ctx: &mut RequestCtx<'_, D, H, VpCache<CA>, TxCache<CA>>,
+    temp_wl_storage: &mut namada_core::ledger::storage::TempWlStorage<'_, D, H>,
+    tx_bytes: &[u8],
+    gas_profiling: bool,
+    dummy_signatures: bool,
+) -> storage_api::Result<(
+    namada_core::types::transaction::TxResult,
+    Option<namada_core::types::transaction::WrapperFee>,
+)>
+where
+    D: 'static + DB + for<'iter> DBIter<'iter> + Sync,
+    H: 'static + StorageHasher + Sync,
+    CA: 'static + WasmCacheAccess + Sync,
+{
+    use namada_core::ledger::gas::{Gas, GasMetering, TxGasMeter};
     use namada_core::proto::Tx;
-    use namada_core::types::transaction::DecryptedTx;
+    use namada_core::types::transaction::{DecryptedTx, WrapperFee};
 
     use crate::ledger::protocol::ShellParams;
     use crate::types::storage::TxIndex;
     use crate::types::transaction::TxType;
 
-    let mut tx = Tx::try_from(&request.data[..]).into_storage_result()?;
-    tx.validate_tx().into_storage_result()?;
+    let mut tx = Tx::try_from(tx_bytes).into_storage_result()?;
+    if !dummy_signatures {
+        tx.validate_tx().into_storage_result()?;
+    }
 
-    let mut temp_wl_storage = TempWlStorage::new(&ctx.wl_storage.storage);
     let mut cumulated_gas = Gas::default();
+    let mut wrapper_fee = None;
 
     // Wrapper dry run to allow estimating the gas cost of a transaction
     let mut tx_gas_meter = match tx.header().tx_type {
@@ -59,10 +292,10 @@ where
                 tx.clone(),
                 &wrapper,
                 None,
-                &request.data,
+                tx_bytes,
                 ShellParams::new(
                     &mut tx_gas_meter,
-                    &mut temp_wl_storage,
+                    temp_wl_storage,
                     &mut ctx.vp_wasm_cache,
                     &mut ctx.tx_wasm_cache,
                 ),
@@ -72,6 +305,10 @@ where
 
             temp_wl_storage.write_log.commit_tx();
             cumulated_gas = tx_gas_meter.get_tx_consumed_gas();
+            wrapper_fee = Some(WrapperFee {
+                token: wrapper.fee.token.clone(),
+                amount: wrapper.get_tx_fee().into_storage_result()?,
+            });
 
             tx.update_header(TxType::Decrypted(DecryptedTx::Decrypted));
             TxGasMeter::new_from_sub_limit(tx_gas_meter.get_available_gas())
@@ -99,17 +336,34 @@ where
         }
     };
 
-    let mut data = protocol::apply_wasm_tx(
+    if gas_profiling {
+        tx_gas_meter.enable_profiling();
+    }
+    if dummy_signatures {
+        tx_gas_meter.accept_dummy_signatures();
+    }
+    let mut data = match protocol::apply_wasm_tx(
         tx,
         &TxIndex(0),
         ShellParams::new(
             &mut tx_gas_meter,
-            &mut temp_wl_storage,
+            temp_wl_storage,
             &mut ctx.vp_wasm_cache,
             &mut ctx.tx_wasm_cache,
         ),
-    )
-    .into_storage_result()?;
+    ) {
+        Ok(data) => data,
+        // Report where the gas went, if profiled
+        Err(err @ protocol::Error::GasError(_)) => {
+            return Err(match tx_gas_meter.gas_profile() {
+                Some(profile) => storage_api::Error::new(format!(
+                    "{err}. Gas used per section:\n{profile}"
+                )),
+                None => storage_api::Error::new(err),
+            });
+        }
+        Err(err) => return Err(err).into_storage_result(),
+    };
     cumulated_gas = cumulated_gas
         .checked_add(tx_gas_meter.get_tx_consumed_gas())
         .ok_or(namada_core::ledger::storage_api::Error::SimpleMessage(
@@ -117,14 +371,7 @@ where
         ))?;
     // Account gas for both inner and wrapper (if available)
     data.gas_used = cumulated_gas;
-    // NOTE: the keys changed by the wrapper transaction (if any) are not
-    // returned from this function
-    let data = data.serialize_to_vec();
-    Ok(EncodedResponseQuery {
-        data,
-        proof: None,
-        info: Default::default(),
-    })
+    Ok((data, wrapper_fee))
 }
 
 #[cfg(test)]
@@ -132,21 +379,27 @@ mod test {
     use borsh::BorshDeserialize;
     use borsh_ext::BorshSerializeExt;
     use namada_core::ledger::storage::testing::TestWlStorage;
-    use namada_core::ledger::storage_api::{self, StorageWrite};
+    use namada_core::ledger::storage_api::{self, StorageRead, StorageWrite};
+    use namada_core::types::dec::Dec;
     use namada_core::types::hash::Hash;
-    use namada_core::types::storage::{BlockHeight, Key};
+    use namada_core::types::key::{self, RefTo};
+    use namada_core::types::storage::{BlockHeight, Epoch, Key, KeySeg};
     use namada_core::types::transaction::decrypted::DecryptedTx;
-    use namada_core::types::transaction::TxType;
+    use namada_core::types::transaction::{
+        Fee, KeyDiff, TxType, WrapperFee, WrapperTx,
+    };
     use namada_core::types::{address, token};
+    use namada_sdk::key_changes::KeyChangeLog;
     use namada_sdk::queries::{Router, RPC};
+    use namada_test_utils::tx_data::TxWriteData;
     use namada_test_utils::TestWasms;
     use tempfile::TempDir;
     use tendermint_rpc::{Error as RpcError, Response};
 
     use crate::ledger::events::log::EventLog;
     use crate::ledger::queries::Client;
-    use crate::ledger::{EncodedResponseQuery, RequestCtx, RequestQuery};
-    use crate::proto::{Code, Data, Tx};
+    use crate::ledger::{gas, EncodedResponseQuery, RequestCtx, RequestQuery};
+    use crate::proto::{Code, Data, Section, Signature, Tx};
     use crate::vm::wasm::{TxCache, VpCache};
     use crate::vm::{wasm, WasmCacheRoAccess};
 
@@ -161,6 +414,8 @@ mod test {
         pub wl_storage: TestWlStorage,
         /// event log
         pub event_log: EventLog,
+        /// key change log
+        pub key_change_log: KeyChangeLog,
         /// VP wasm compilation cache
         pub vp_wasm_cache: VpCache<WasmCacheRoAccess>,
         /// tx wasm compilation cache
@@ -205,6 +460,7 @@ mod test {
                 rpc,
                 wl_storage,
                 event_log,
+                key_change_log: KeyChangeLog::default(),
                 vp_wasm_cache: vp_wasm_cache.read_only(),
                 tx_wasm_cache: tx_wasm_cache.read_only(),
                 vp_cache_dir,
@@ -241,6 +497,7 @@ mod test {
             let ctx = RequestCtx {
                 wl_storage: &self.wl_storage,
                 event_log: &self.event_log,
+                key_change_log: &self.key_change_log,
                 vp_wasm_cache: self.vp_wasm_cache.clone(),
                 tx_wasm_cache: self.tx_wasm_cache.clone(),
                 storage_read_past_height_limit: None,
@@ -249,6 +506,10 @@ mod test {
             // really permit error types other than [`std::io::Error`]
             if request.path == "/shell/dry_run_tx" {
                 super::dry_run_tx(ctx, &request)
+            } else if request.path == "/shell/simulate_tx" {
+                super::simulate_tx(ctx, &request)
+            } else if request.path == "/shell/estimate_fee" {
+                super::estimate_fee(ctx, &request)
             } else {
                 self.rpc.handle(ctx, &request)
             }
@@ -296,10 +557,36 @@ mod test {
         let tx_bytes = outer_tx.to_bytes();
         let result = RPC
             .shell()
-            .dry_run_tx(&client, Some(tx_bytes), None, false)
+            .dry_run_tx(&client, Some(tx_bytes.clone()), None, false)
             .await
             .unwrap();
         assert!(result.data.is_accepted());
+        // The dry run is profiled
+        let gas_profile = result.data.gas_profile.expect("missing gas profile");
+        assert!(gas_profile.0.contains_key(gas::SETUP_GAS_SECTION));
+        assert_eq!(gas_profile.total_gas(), result.data.gas_used);
+
+        // Request tx simulation
+        let simulation = RPC
+            .shell()
+            .simulate_tx(&client, Some(tx_bytes.clone()), None, false)
+            .await
+            .unwrap();
+        assert!(simulation.data.result.is_accepted());
+        assert!(simulation.data.wrapper_fee.is_none());
+        assert!(simulation.data.result.gas_profile.is_none());
+
+        // Request fee estimate
+        let estimate = RPC
+            .shell()
+            .estimate_fee(&client, Some(tx_bytes), None, false)
+            .await
+            .unwrap();
+        assert!(estimate.data.result.is_accepted());
+        assert!(
+            u64::from(estimate.data.gas_limit)
+                >= estimate.data.result.gas_used.get_whole_gas_units()
+        );
 
         // Request storage value for a balance key ...
         let token_addr = address::testing::established_address_1();
@@ -366,4 +653,206 @@ mod test {
 
         Ok(())
     }
+
+    /// Store the code of a test wasm in the storage of the client and return
+    /// its hash
+    fn store_wasm<RPC: Router>(
+        client: &mut TestClient<RPC>,
+        wasm: TestWasms,
+    ) -> Hash {
+        let code = wasm.read_bytes();
+        let hash = Hash::sha256(&code);
+        let key = Key::wasm_code(&hash);
+        let len_key = Key::wasm_code_len(&hash);
+        client.wl_storage.storage.write(&key, &code).unwrap();
+        client
+            .wl_storage
+            .storage
+            .write(&len_key, (code.len() as u64).serialize_to_vec())
+            .unwrap();
+        hash
+    }
+
+    /// Build a decrypted tx that writes `value` to `key` with the `tx_write`
+    /// wasm of the given hash
+    fn write_tx<RPC: Router>(
+        client: &TestClient<RPC>,
+        tx_hash: Hash,
+        key: Key,
+        value: Vec<u8>,
+    ) -> Tx {
+        let mut tx = Tx::from_type(TxType::Decrypted(DecryptedTx::Decrypted));
+        tx.header.chain_id = client.wl_storage.storage.chain_id.clone();
+        tx.set_code(Code::from_hash(tx_hash, None));
+        tx.set_data(Data::new(TxWriteData { key, value }.serialize_to_vec()));
+        tx
+    }
+
+    /// Test that a simulation reports the values of the changed keys before
+    /// and after the tx, without writing them to the storage
+    #[tokio::test]
+    async fn test_simulate_tx_state_diff() {
+        let mut client = TestClient::new(RPC);
+        let tx_hash = store_wasm(&mut client, TestWasms::TxWriteStorageKey);
+        let vp_hash = store_wasm(&mut client, TestWasms::VpAlwaysTrue);
+        let owner = address::testing::established_address_1();
+        client
+            .wl_storage
+            .storage
+            .write(&Key::validity_predicate(&owner), vp_hash.serialize_to_vec())
+            .unwrap();
+
+        // The tx_write wasm expects an existing value to be a string
+        let key = Key::from(owner.to_db_key())
+            .push(&"test".to_string())
+            .unwrap();
+        let old_value = "old".to_string();
+        StorageWrite::write(&mut client.wl_storage, &key, &old_value).unwrap();
+        client.wl_storage.commit_tx();
+        client.wl_storage.commit_block().unwrap();
+
+        let new_value = "new".to_string().serialize_to_vec();
+        let tx = write_tx(&client, tx_hash, key.clone(), new_value.clone());
+        let simulation = RPC
+            .shell()
+            .simulate_tx(&client, Some(tx.to_bytes()), None, false)
+            .await
+            .unwrap()
+            .data;
+        assert!(simulation.result.is_accepted());
+        assert_eq!(
+            simulation.state_diff.get(&key),
+            Some(&KeyDiff {
+                pre: Some(old_value.serialize_to_vec()),
+                post: Some(new_value),
+            })
+        );
+        assert!(
+            simulation
+                .vps
+                .iter()
+                .any(|vp| vp.address == owner && vp.accepted)
+        );
+
+        // Nothing is written to the storage
+        let value: Option<String> =
+            StorageRead::read(&client.wl_storage, &key).unwrap();
+        assert_eq!(value, Some(old_value));
+    }
+
+    /// Test that a simulation reports the VPs that reject the tx and leaves
+    /// out of the state diff the changes that would be dropped
+    #[tokio::test]
+    async fn test_simulate_tx_rejected_vp() {
+        let mut client = TestClient::new(RPC);
+        let tx_hash = store_wasm(&mut client, TestWasms::TxWriteStorageKey);
+        let vp_hash = store_wasm(&mut client, TestWasms::VpAlwaysFalse);
+        let owner = address::testing::established_address_2();
+        client
+            .wl_storage
+            .storage
+            .write(&Key::validity_predicate(&owner), vp_hash.serialize_to_vec())
+            .unwrap();
+
+        let key = Key::from(owner.to_db_key())
+            .push(&"test".to_string())
+            .unwrap();
+        let tx = write_tx(
+            &client,
+            tx_hash,
+            key.clone(),
+            "new".to_string().serialize_to_vec(),
+        );
+        let simulation = RPC
+            .shell()
+            .simulate_tx(&client, Some(tx.to_bytes()), None, false)
+            .await
+            .unwrap()
+            .data;
+        assert!(!simulation.result.is_accepted());
+        let outcome = simulation
+            .vps
+            .iter()
+            .find(|vp| vp.address == owner)
+            .expect("The VP of the owner must be triggered");
+        assert!(!outcome.accepted);
+        assert!(!simulation.state_diff.contains_key(&key));
+    }
+
+    /// Test that a simulation of a wrapped tx reports the fee charged by the
+    /// wrapper
+    #[tokio::test]
+    async fn test_simulate_tx_wrapper_fee() {
+        let mut client = TestClient::new(RPC);
+        let tx_hash = store_wasm(&mut client, TestWasms::TxNoOp);
+        let keypair = key::testing::keypair_1();
+        let fee_payer = address::Address::from(&keypair.ref_to());
+        let native_token = client.wl_storage.storage.native_token.clone();
+        let balance_key = token::balance_key(&native_token, &fee_payer);
+        StorageWrite::write(
+            &mut client.wl_storage,
+            &balance_key,
+            token::Amount::native_whole(1000),
+        )
+        .unwrap();
+        client.wl_storage.commit_tx();
+        client.wl_storage.commit_block().unwrap();
+
+        let fee = Fee {
+            amount_per_gas_unit: token::Amount::from(10),
+            token: native_token.clone(),
+        };
+        let gas_limit: u64 = 20_000_000;
+        let mut wrapper =
+            Tx::from_type(TxType::Wrapper(Box::new(WrapperTx::new(
+                fee,
+                keypair.ref_to(),
+                Epoch(0),
+                gas_limit.into(),
+                None,
+            ))));
+        wrapper.header.chain_id = client.wl_storage.storage.chain_id.clone();
+        wrapper.set_code(Code::from_hash(tx_hash, None));
+        wrapper.set_data(Data::new(vec![]));
+        wrapper.add_section(Section::Signature(Signature::new(
+            wrapper.sechashes(),
+            [(0, keypair)].into_iter().collect(),
+            None,
+        )));
+
+        let simulation = RPC
+            .shell()
+            .simulate_tx(&client, Some(wrapper.to_bytes()), None, false)
+            .await
+            .unwrap()
+            .data;
+        assert!(simulation.result.is_accepted());
+        assert_eq!(
+            simulation.wrapper_fee,
+            Some(WrapperFee {
+                token: native_token,
+                amount: token::Amount::from(10 * gas_limit),
+            })
+        );
+    }
+
+    /// Test that the suggested gas price only grows above half the block
+    /// utilization
+    #[test]
+    fn test_suggest_gas_price() {
+        let minimum = token::Amount::from(100);
+        assert_eq!(super::suggest_gas_price(minimum, Dec::zero()), minimum);
+        assert_eq!(
+            super::suggest_gas_price(minimum, Dec::new(5, 1).unwrap()),
+            minimum
+        );
+        assert_eq!(
+            super::suggest_gas_price(minimum, Dec::new(75, 2).unwrap()),
+            token::Amount::from(150)
+        );
+        assert_eq!(
+            super::suggest_gas_price(minimum, Dec::one()),
+            token::Amount::from(200)
+        );
+    }
 }
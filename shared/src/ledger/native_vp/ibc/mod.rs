@@ -8,9 +8,6 @@ use std::rc::Rc;
 use std::time::Duration;
 
 use context::{PseudoExecutionContext, VpValidationContext};
-use namada_core::ledger::gas::{
-    IBC_ACTION_EXECUTE_GAS, IBC_ACTION_VALIDATE_GAS,
-};
 use namada_core::ledger::ibc::{
     Error as ActionError, IbcActions, TransferModule, ValidationParams,
 };
@@ -110,7 +107,7 @@ where
         actions.add_transfer_module(module.module_id(), module);
         // Charge gas for the expensive execution
         self.ctx
-            .charge_gas(IBC_ACTION_EXECUTE_GAS)
+            .charge_gas(self.ctx.storage.gas_costs.ibc_action_execute)
             .map_err(Error::NativeVpError)?;
         actions.execute(tx_data)?;
 
@@ -156,7 +153,7 @@ where
         actions.add_transfer_module(module.module_id(), module);
         // Charge gas for the expensive validation
         self.ctx
-            .charge_gas(IBC_ACTION_VALIDATE_GAS)
+            .charge_gas(self.ctx.storage.gas_costs.ibc_action_validate)
             .map_err(Error::NativeVpError)?;
         actions.validate(tx_data).map_err(Error::IbcAction)
     }
@@ -463,6 +463,7 @@ where
     fn get_tx_index(&self) -> Result<TxIndex, storage_api::Error> {
         vp_host_fns::get_tx_index(
             &mut self.gas_meter.borrow_mut(),
+            self.storage,
             self.tx_index,
             &mut self.sentinel.borrow_mut(),
         )
@@ -574,6 +575,7 @@ where
     fn get_tx_code_hash(&self) -> Result<Option<Hash>, storage_api::Error> {
         vp_host_fns::get_tx_code_hash(
             &mut self.gas_meter.borrow_mut(),
+            self.storage,
             self.tx,
             &mut self.sentinel.borrow_mut(),
         )
@@ -8,14 +8,15 @@ use std::collections::BTreeSet;
 use namada_core::ledger::pgf::storage::keys as pgf_storage;
 use namada_core::ledger::storage;
 use namada_core::ledger::storage_api::governance::is_proposal_accepted;
-use namada_core::ledger::storage_api::pgf as pgf_api;
+use namada_core::ledger::storage_api::{pgf as pgf_api, StorageRead};
+use namada_core::ledger::vp_env::VpEnv;
 use namada_core::proto::Tx;
 use thiserror::Error;
 
 use crate::ledger::native_vp;
 use crate::ledger::native_vp::{Ctx, NativeVp};
 use crate::types::address::{Address, InternalAddress};
-use crate::types::storage::Key;
+use crate::types::storage::{Epoch, Key};
 use crate::vm::WasmCacheAccess;
 
 /// for handling Pgf NativeVP errors
@@ -93,6 +94,8 @@ where
                     Ok(is_valid)
                 }
                 KeyType::FUNDINGS => Ok(false),
+                KeyType::PAUSE(funding, steward) => self
+                    .is_valid_funding_pause(key, &funding, &steward, verifiers),
                 KeyType::PGF_INFLATION_RATE
                 | KeyType::STEWARD_INFLATION_RATE => {
                     self.is_valid_parameter_change(tx_data)
@@ -112,6 +115,29 @@ where
     H: 'static + storage::StorageHasher,
     CA: 'static + WasmCacheAccess,
 {
+    /// Validate the pause of a continous funding. Only a steward can pause
+    /// an existing funding, or lift its own pause, and the pause records the
+    /// current epoch.
+    pub fn is_valid_funding_pause(
+        &self,
+        key: &Key,
+        funding: &str,
+        steward: &Address,
+        verifiers: &BTreeSet<Address>,
+    ) -> Result<bool> {
+        if !verifiers.contains(steward)
+            || !pgf_api::is_steward(&self.ctx.pre(), steward)?
+            || pgf_api::get_payment(&self.ctx.pre(), funding)?.is_none()
+        {
+            return Ok(false);
+        }
+        let pause_post: Option<Epoch> = self.ctx.post().read(key)?;
+        match pause_post {
+            Some(epoch) => Ok(epoch == self.ctx.get_block_epoch()?),
+            None => Ok(true),
+        }
+    }
+
     /// Validate a governance parameter
     pub fn is_valid_parameter_change(&self, tx: &Tx) -> Result<bool> {
         match tx.data() {
@@ -130,6 +156,8 @@ enum KeyType {
     #[allow(non_camel_case_types)]
     FUNDINGS,
     #[allow(non_camel_case_types)]
+    PAUSE(String, Address),
+    #[allow(non_camel_case_types)]
     PGF_INFLATION_RATE,
     #[allow(non_camel_case_types)]
     STEWARD_INFLATION_RATE,
@@ -145,6 +173,10 @@ impl From<&Key> for KeyType {
             Self::STEWARDS
         } else if pgf_storage::is_fundings_key(key) {
             KeyType::FUNDINGS
+        } else if let Some((funding, steward)) =
+            pgf_storage::is_funding_pause_key(key)
+        {
+            KeyType::PAUSE(funding.clone(), steward.clone())
         } else if pgf_storage::is_pgf_inflation_rate_key(key) {
             Self::PGF_INFLATION_RATE
         } else if pgf_storage::is_steward_inflation_rate_key(key) {
@@ -2,6 +2,7 @@ use std::collections::HashMap;
 
 use namada_core::types::address::Address;
 use namada_core::types::token;
+use namada_sdk::events::{Event, EventLevel};
 
 use crate::ledger::events::EventType;
 
@@ -13,54 +14,74 @@ pub struct ProposalEvent {
     pub attributes: HashMap<String, String>,
 }
 
+impl From<ProposalEvent> for Event {
+    fn from(proposal_event: ProposalEvent) -> Self {
+        Self {
+            event_type: EventType::PgfPayment,
+            level: EventLevel::Block,
+            attributes: proposal_event.attributes,
+        }
+    }
+}
+
 impl ProposalEvent {
-    /// Create a proposal event
+    /// Create a proposal event. A failed payment carries the reason of the
+    /// failure in the `info` attribute.
     pub fn new(
         event_type: String,
-        target: Address,
+        target: String,
         amount: token::Amount,
         is_steward: bool,
-        success: bool,
+        error: Option<String>,
     ) -> Self {
-        let attributes = HashMap::from([
-            ("target".to_string(), target.to_string()),
+        let mut attributes = HashMap::from([
+            ("target".to_string(), target),
             ("amount".to_string(), amount.to_string_native()),
             ("is_steward".to_string(), is_steward.to_string()),
-            ("successed".to_string(), success.to_string()),
+            ("successed".to_string(), error.is_none().to_string()),
         ]);
+        if let Some(error) = error {
+            attributes.insert("info".to_string(), error);
+        }
         Self {
             event_type,
             attributes,
         }
     }
 
-    /// Create a new proposal event for pgf continous funding
+    /// Create a new proposal event for a pgf funding payment, continous or
+    /// retroactive, of the given proposal
     pub fn pgf_funding_payment(
-        target: Address,
+        proposal_id: u64,
+        target: String,
         amount: token::Amount,
-        success: bool,
+        error: Option<String>,
     ) -> Self {
-        ProposalEvent::new(
+        let mut event = ProposalEvent::new(
             EventType::PgfPayment.to_string(),
             target,
             amount,
             false,
-            success,
-        )
+            error,
+        );
+        event
+            .attributes
+            .insert("proposal_id".to_string(), proposal_id.to_string());
+        event
     }
 
     /// Create a new proposal event for steward payments
     pub fn pgf_steward_payment(
         target: Address,
         amount: token::Amount,
-        success: bool,
+        error: Option<String>,
     ) -> Self {
         ProposalEvent::new(
             EventType::PgfPayment.to_string(),
-            target,
+            target.to_string(),
             amount,
             true,
-            success,
+            error,
         )
     }
 }
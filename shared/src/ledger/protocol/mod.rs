@@ -4,19 +4,24 @@ use std::collections::BTreeSet;
 use borsh_ext::BorshSerializeExt;
 use eyre::{eyre, WrapErr};
 use masp_primitives::transaction::Transaction;
+use namada_core::ledger::fee_grant::storage::keys as fee_grant_storage;
 use namada_core::ledger::gas::TxGasMeter;
 use namada_core::ledger::storage::wl_storage::WriteLogAndStorage;
 use namada_core::ledger::storage_api::StorageRead;
 use namada_core::proto::Section;
+use namada_core::types::account::EpochSpending;
 use namada_core::types::hash::Hash;
+use namada_core::types::key::spending_key;
 use namada_core::types::storage::Key;
 use namada_core::types::token::Amount;
+use namada_core::types::transaction::fee_grant::FeeAllowance;
 use namada_core::types::transaction::WrapperTx;
 use namada_sdk::tx::TX_TRANSFER_WASM;
 use rayon::iter::{IntoParallelRefIterator, ParallelIterator};
 use thiserror::Error;
 
-use crate::ledger::gas::{GasMetering, VpGasMeter};
+use crate::ledger::fee_grant::FeeGrantVp;
+use crate::ledger::gas::{GasMetering, VPS_GAS_SECTION, VpGasMeter};
 use crate::ledger::governance::GovernanceVp;
 use crate::ledger::native_vp::ethereum_bridge::bridge_pool_vp::BridgePoolVp;
 use crate::ledger::native_vp::ethereum_bridge::nut::NonUsableTokens;
@@ -28,6 +33,7 @@ use crate::ledger::native_vp::parameters::{self, ParametersVp};
 use crate::ledger::native_vp::{self, NativeVp};
 use crate::ledger::pgf::PgfVp;
 use crate::ledger::pos::{self, PosVP};
+use crate::ledger::scheduler::SchedulerVp;
 use crate::ledger::storage::write_log::WriteLog;
 use crate::ledger::storage::{DBIter, Storage, StorageHasher, WlStorage, DB};
 use crate::ledger::storage_api;
@@ -85,6 +91,10 @@ pub enum Error {
     GovernanceNativeVpError(crate::ledger::governance::Error),
     #[error("Pgf native VP error: {0}")]
     PgfNativeVpError(crate::ledger::pgf::Error),
+    #[error("Scheduler native VP error: {0}")]
+    SchedulerNativeVpError(crate::ledger::scheduler::Error),
+    #[error("Fee grant native VP error: {0}")]
+    FeeGrantNativeVpError(crate::ledger::fee_grant::Error),
     #[error("Ethereum bridge native VP error: {0:?}")]
     EthBridgeNativeVpError(native_vp::ethereum_bridge::vp::Error),
     #[error("Ethereum bridge pool native VP error: {0:?}")]
@@ -194,6 +204,8 @@ where
                 vps_result: VpsResult::default(),
                 initialized_accounts: vec![],
                 ibc_events: BTreeSet::default(),
+                inner_results: vec![],
+                gas_profile: None,
             })
         }
         TxType::Decrypted(DecryptedTx::Undecryptable) => {
@@ -249,6 +261,7 @@ where
     // Charge fee before performing any fallible operations
     charge_fee(
         wrapper,
+        tx.fee_granter().as_ref(),
         fee_unshield_transaction,
         &mut shell_params,
         block_proposer,
@@ -258,7 +271,7 @@ where
     // Account for gas
     shell_params
         .tx_gas_meter
-        .add_wrapper_gas(tx_bytes)
+        .add_wrapper_gas(tx_bytes, &shell_params.wl_storage.storage().gas_costs)
         .map_err(|err| Error::GasError(err.to_string()))?;
 
     Ok(changed_keys)
@@ -290,6 +303,7 @@ pub fn get_fee_unshielding_transaction(
 /// - The accumulated fee amount to be credited to the block proposer overflows
 fn charge_fee<'a, D, H, CA, WLS>(
     wrapper: &WrapperTx,
+    fee_granter: Option<&Address>,
     masp_transaction: Option<Transaction>,
     shell_params: &mut ShellParams<'a, CA, WLS>,
     block_proposer: Option<&Address>,
@@ -374,8 +388,10 @@ where
 
     // Charge or check fees
     match block_proposer {
-        Some(proposer) => transfer_fee(*wl_storage, proposer, wrapper)?,
-        None => check_fees(*wl_storage, wrapper)?,
+        Some(proposer) => {
+            transfer_fee(*wl_storage, proposer, wrapper, fee_granter)?
+        }
+        None => check_fees(*wl_storage, wrapper, fee_granter)?,
     }
 
     changed_keys.extend(wl_storage.write_log_mut().get_keys_with_precommit());
@@ -386,30 +402,56 @@ where
     Ok(())
 }
 
-/// Perform the actual transfer of fess from the fee payer to the block
-/// proposer.
+/// Perform the actual transfer of fess from the fee payer, or from its fee
+/// granter, to the block proposer.
 pub fn transfer_fee<WLS>(
     wl_storage: &mut WLS,
     block_proposer: &Address,
     wrapper: &WrapperTx,
+    fee_granter: Option<&Address>,
 ) -> Result<()>
 where
     WLS: WriteLogAndStorage + StorageRead,
 {
+    let allowance = read_fee_allowance(wl_storage, wrapper, fee_granter)?;
+    let fee_payer = allowance
+        .as_ref()
+        .map_or_else(|| wrapper.fee_payer(), |(granter, _)| granter.clone());
     let balance = storage_api::token::read_balance(
         wl_storage,
         &wrapper.fee.token,
-        &wrapper.fee_payer(),
+        &fee_payer,
     )
     .unwrap();
 
     match wrapper.get_tx_fee() {
         Ok(fees) => {
-            if balance.checked_sub(fees).is_some() {
+            if let Some((granter, allowance)) = allowance {
+                // The granter's debit is not checked by its VP, so it must
+                // not touch the funds locked by its vesting schedule nor go
+                // past its spending limits. In that case nothing is moved.
+                check_granter_spending(
+                    wl_storage,
+                    &granter,
+                    &wrapper.fee.token,
+                    balance,
+                    fees,
+                )?;
+                spend_fee_allowance(
+                    wl_storage, wrapper, &granter, allowance, fees,
+                )?;
+                token_transfer(
+                    wl_storage,
+                    &wrapper.fee.token,
+                    &granter,
+                    block_proposer,
+                    fees,
+                )
+            } else if balance.checked_sub(fees).is_some() {
                 token_transfer(
                     wl_storage,
                     &wrapper.fee.token,
-                    &wrapper.fee_payer(),
+                    &fee_payer,
                     block_proposer,
                     fees,
                 )
@@ -428,7 +470,7 @@ where
                 token_transfer(
                     wl_storage,
                     &wrapper.fee.token,
-                    &wrapper.fee_payer(),
+                    &fee_payer,
                     block_proposer,
                     balance,
                 )
@@ -463,7 +505,8 @@ where
 /// insufficient balance or if the transfer the `dest` would overflow (This can
 /// only happen if the total supply does't fit in `token::Amount`). Contrary to
 /// `storage_api::token::transfer` this function updates the tx write log and
-/// not the block write log.
+/// not the block write log. Like it, the debit is recorded in the spending of
+/// `src` in the current epoch.
 fn token_transfer<WLS>(
     wl_storage: &mut WLS,
     token: &Address,
@@ -496,13 +539,11 @@ where
                         .write_log_mut()
                         .write(&src_key, new_src_balance.serialize_to_vec())
                         .map_err(|e| Error::FeeError(e.to_string()))?;
-                    match wl_storage
+                    wl_storage
                         .write_log_mut()
                         .write(&dest_key, new_dest_balance.serialize_to_vec())
-                    {
-                        Ok(_) => Ok(()),
-                        Err(e) => Err(Error::FeeError(e.to_string())),
-                    }
+                        .map_err(|e| Error::FeeError(e.to_string()))?;
+                    record_spending(wl_storage, token, src, amount)
                 }
                 None => Err(Error::FeeError(
                     "The transfer would overflow destination balance"
@@ -514,15 +555,186 @@ where
     }
 }
 
-/// Check if the fee payer has enough transparent balance to pay fees
-pub fn check_fees<WLS>(wl_storage: &WLS, wrapper: &WrapperTx) -> Result<()>
+/// Read the fee allowance granted to the fee payer of the wrapper by the given
+/// fee granter, if any, together with the granter. Returns an error if the
+/// allowance is missing or expired.
+fn read_fee_allowance<WLS>(
+    wl_storage: &WLS,
+    wrapper: &WrapperTx,
+    fee_granter: Option<&Address>,
+) -> Result<Option<(Address, FeeAllowance)>>
+where
+    WLS: StorageRead,
+{
+    let granter = match fee_granter {
+        Some(granter) => granter,
+        None => return Ok(None),
+    };
+    let allowance = storage_api::fee_grant::read_fee_allowance(
+        wl_storage,
+        granter,
+        &wrapper.fee_payer(),
+    )
+    .map_err(|e| Error::FeeError(e.to_string()))?
+    .ok_or_else(|| {
+        Error::FeeError(format!(
+            "{} hasn't granted any fee allowance to {}",
+            granter,
+            wrapper.fee_payer()
+        ))
+    })?;
+    let epoch = wl_storage
+        .get_block_epoch()
+        .map_err(|e| Error::FeeError(e.to_string()))?;
+    if allowance.is_expired(epoch) {
+        return Err(Error::FeeError(format!(
+            "The fee allowance granted by {} to {} has expired",
+            granter,
+            wrapper.fee_payer()
+        )));
+    }
+    Ok(Some((granter.clone(), allowance)))
+}
+
+/// Deduct the given amount of fees from the fee allowance granted to the fee
+/// payer of the wrapper. Like the fee transfer, this updates the tx write
+/// log.
+fn spend_fee_allowance<WLS>(
+    wl_storage: &mut WLS,
+    wrapper: &WrapperTx,
+    granter: &Address,
+    mut allowance: FeeAllowance,
+    amount: Amount,
+) -> Result<()>
+where
+    WLS: WriteLogAndStorage + StorageRead,
+{
+    if !allowance.spend(&wrapper.fee.token, amount) {
+        return Err(Error::FeeError(format!(
+            "The fees exceed the fee allowance in {} granted by {}",
+            wrapper.fee.token, granter
+        )));
+    }
+    let key =
+        fee_grant_storage::get_allowance_key(granter, &wrapper.fee_payer());
+    wl_storage
+        .write_log_mut()
+        .write(&key, allowance.serialize_to_vec())
+        .map_err(|e| Error::FeeError(e.to_string()))?;
+    Ok(())
+}
+
+/// Check that the fees of the granter of the wrapper neither touch the funds
+/// locked by its vesting schedule nor go past the spending limits of its
+/// keys
+fn check_granter_spending<WLS>(
+    wl_storage: &WLS,
+    granter: &Address,
+    token: &Address,
+    balance: Amount,
+    fees: Amount,
+) -> Result<()>
+where
+    WLS: StorageRead,
+{
+    let locked =
+        storage_api::account::locked_amount(wl_storage, granter, token)
+            .map_err(|e| Error::FeeError(e.to_string()))?;
+    match balance.checked_sub(fees) {
+        Some(remaining) if remaining >= locked => {}
+        _ => {
+            return Err(Error::FeeError(format!(
+                "Insufficient spendable balance of the fee granter {} to pay \
+                 fees",
+                granter
+            )));
+        }
+    }
+
+    let has_spending_limit =
+        storage_api::account::has_spending_limit(wl_storage, granter, token)
+            .map_err(|e| Error::FeeError(e.to_string()))?;
+    if !has_spending_limit {
+        return Ok(());
+    }
+    let spent = storage_api::account::spent_in_current_epoch(
+        wl_storage, granter, token,
+    )
+    .map_err(|e| Error::FeeError(e.to_string()))?
+    .checked_add(fees)
+    .ok_or_else(|| {
+        Error::FeeError("Overflow in the amount spent in the epoch".to_string())
+    })?;
+    let authorized = storage_api::account::can_authorize_spending(
+        wl_storage, granter, token, spent,
+    )
+    .map_err(|e| Error::FeeError(e.to_string()))?;
+    if !authorized {
+        return Err(Error::FeeError(format!(
+            "The fees exceed the spending limits of the fee granter {} in {}",
+            granter, token
+        )));
+    }
+    Ok(())
+}
+
+/// Record the debit of a token from an account in the tx write log, if any
+/// of its public keys has a spending limit on the token, like
+/// `storage_api::account::record_spending` does for the other debits
+fn record_spending<WLS>(
+    wl_storage: &mut WLS,
+    token: &Address,
+    owner: &Address,
+    amount: Amount,
+) -> Result<()>
+where
+    WLS: WriteLogAndStorage + StorageRead,
+{
+    let has_spending_limit =
+        storage_api::account::has_spending_limit(wl_storage, owner, token)
+            .map_err(|e| Error::FeeError(e.to_string()))?;
+    if amount.is_zero() || !has_spending_limit {
+        return Ok(());
+    }
+    let spent = storage_api::account::spent_in_current_epoch(
+        wl_storage, owner, token,
+    )
+    .map_err(|e| Error::FeeError(e.to_string()))?
+    .checked_add(amount)
+    .ok_or_else(|| {
+        Error::FeeError("Overflow in the amount spent in the epoch".to_string())
+    })?;
+    let spending = EpochSpending {
+        epoch: wl_storage
+            .get_block_epoch()
+            .map_err(|e| Error::FeeError(e.to_string()))?,
+        amount: spent,
+    };
+    wl_storage
+        .write_log_mut()
+        .write(&spending_key(owner, token), spending.serialize_to_vec())
+        .map_err(|e| Error::FeeError(e.to_string()))?;
+    Ok(())
+}
+
+/// Check if the fee payer, or its fee granter, has enough transparent
+/// balance to pay fees
+pub fn check_fees<WLS>(
+    wl_storage: &WLS,
+    wrapper: &WrapperTx,
+    fee_granter: Option<&Address>,
+) -> Result<()>
 where
     WLS: WriteLogAndStorage + StorageRead,
 {
+    let allowance = read_fee_allowance(wl_storage, wrapper, fee_granter)?;
+    let fee_payer = allowance
+        .as_ref()
+        .map_or_else(|| wrapper.fee_payer(), |(granter, _)| granter.clone());
     let balance = storage_api::token::read_balance(
         wl_storage,
         &wrapper.fee.token,
-        &wrapper.fee_payer(),
+        &fee_payer,
     )
     .unwrap();
 
@@ -530,6 +742,23 @@ where
         .get_tx_fee()
         .map_err(|e| Error::FeeError(e.to_string()))?;
 
+    if let Some((granter, allowance)) = allowance {
+        if allowance.remaining(&wrapper.fee.token) < fees {
+            return Err(Error::FeeError(format!(
+                "The fees exceed the fee allowance in {} granted by {}",
+                wrapper.fee.token, granter
+            )));
+        }
+        check_granter_spending(
+            wl_storage,
+            &granter,
+            &wrapper.fee.token,
+            balance,
+            fees,
+        )?;
+        return Ok(());
+    }
+
     if balance.checked_sub(fees).is_some() {
         Ok(())
     } else {
@@ -577,8 +806,96 @@ where
         return Err(Error::ReplayAttempt(tx_hash));
     }
 
+    let inner_txs = match tx.batched_txs() {
+        Some(inner_txs) => inner_txs,
+        None => {
+            return apply_inner_tx(
+                &tx,
+                tx_index,
+                storage,
+                tx_gas_meter,
+                write_log,
+                vp_wasm_cache,
+                tx_wasm_cache,
+            );
+        }
+    };
+
+    // The inner txs of a batch are applied in order, each on top of the
+    // changes of the previous ones. The batch is rejected as a whole as soon
+    // as any of them is rejected, in which case the remaining ones are not
+    // applied.
+    let mut result = TxResult::default();
+    for inner_tx in inner_txs {
+        let gas_before = tx_gas_meter.get_tx_consumed_gas();
+        let mut inner_result = apply_inner_tx(
+            &inner_tx,
+            tx_index,
+            storage,
+            tx_gas_meter,
+            write_log,
+            vp_wasm_cache,
+            tx_wasm_cache,
+        )?;
+        inner_result.gas_used = tx_gas_meter
+            .get_tx_consumed_gas()
+            .checked_sub(gas_before)
+            .unwrap_or_default();
+        let accepted = inner_result.is_accepted();
+
+        // The gas of the VPs is already accounted for in the results of the
+        // inner txs, so it's not merged here
+        let vps_result = &inner_result.vps_result;
+        result
+            .vps_result
+            .accepted_vps
+            .extend(vps_result.accepted_vps.iter().cloned());
+        result
+            .vps_result
+            .rejected_vps
+            .extend(vps_result.rejected_vps.iter().cloned());
+        result
+            .vps_result
+            .errors
+            .extend(vps_result.errors.iter().cloned());
+        result.vps_result.invalid_sig |= vps_result.invalid_sig;
+        result
+            .changed_keys
+            .extend(inner_result.changed_keys.clone());
+        result
+            .initialized_accounts
+            .extend(inner_result.initialized_accounts.clone());
+        result.ibc_events.extend(inner_result.ibc_events.clone());
+        result.inner_results.push(inner_result);
+        if !accepted {
+            break;
+        }
+        write_log.commit_batch_tx();
+    }
+    result.gas_used = tx_gas_meter.get_tx_consumed_gas();
+    result.gas_profile = tx_gas_meter.gas_profile().cloned();
+
+    Ok(result)
+}
+
+/// Apply a single inner transaction, i.e. either a standalone one or one of
+/// the inner transactions of a batch
+fn apply_inner_tx<D, H, CA>(
+    tx: &Tx,
+    tx_index: &TxIndex,
+    storage: &Storage<D, H>,
+    tx_gas_meter: &mut TxGasMeter,
+    write_log: &mut WriteLog,
+    vp_wasm_cache: &mut VpCache<CA>,
+    tx_wasm_cache: &mut TxCache<CA>,
+) -> Result<TxResult>
+where
+    D: 'static + DB + for<'iter> DBIter<'iter> + Sync,
+    H: 'static + StorageHasher + Sync,
+    CA: 'static + WasmCacheAccess + Sync,
+{
     let verifiers = execute_tx(
-        &tx,
+        tx,
         tx_index,
         storage,
         tx_gas_meter,
@@ -588,7 +905,7 @@ where
     )?;
 
     let vps_result = check_vps(CheckVps {
-        tx: &tx,
+        tx,
         tx_index,
         storage,
         tx_gas_meter,
@@ -608,6 +925,8 @@ where
         vps_result,
         initialized_accounts,
         ibc_events,
+        inner_results: vec![],
+        gas_profile: tx_gas_meter.gas_profile().cloned(),
     })
 }
 
@@ -767,6 +1086,7 @@ where
     )?;
     tracing::debug!("Total VPs gas cost {:?}", vps_result.gas_used);
 
+    tx_gas_meter.enter_section(VPS_GAS_SECTION);
     tx_gas_meter
         .add_vps_gas(&vps_result.gas_used)
         .map_err(|err| Error::GasError(err.to_string()))?;
@@ -964,6 +1284,28 @@ where
                                 gas_meter = pgf_vp.ctx.gas_meter.into_inner();
                                 (result, pgf_vp.ctx.sentinel.into_inner())
                             }
+                            InternalAddress::Scheduler => {
+                                let scheduler_vp = SchedulerVp { ctx };
+                                let result = scheduler_vp
+                                    .validate_tx(tx, &keys_changed, &verifiers)
+                                    .map_err(Error::SchedulerNativeVpError);
+                                // Take the gas meter and the sentinel back out
+                                // of the context
+                                gas_meter =
+                                    scheduler_vp.ctx.gas_meter.into_inner();
+                                (result, scheduler_vp.ctx.sentinel.into_inner())
+                            }
+                            InternalAddress::FeeGrant => {
+                                let fee_grant_vp = FeeGrantVp { ctx };
+                                let result = fee_grant_vp
+                                    .validate_tx(tx, &keys_changed, &verifiers)
+                                    .map_err(Error::FeeGrantNativeVpError);
+                                // Take the gas meter and the sentinel back out
+                                // of the context
+                                gas_meter =
+                                    fee_grant_vp.ctx.gas_meter.into_inner();
+                                (result, fee_grant_vp.ctx.sentinel.into_inner())
+                            }
                             InternalAddress::Nut(_) => {
                                 let non_usable_tokens = NonUsableTokens { ctx };
                                 let result = non_usable_tokens
@@ -1051,6 +1393,12 @@ where
                 },
             }
 
+            result
+                .vps_gas
+                .insert(addr.clone(), gas_meter.get_vp_consumed_gas());
+            if let Some(profile) = gas_meter.gas_profile() {
+                result.vps_gas_profile.insert(addr.clone(), profile.clone());
+            }
             result
                 .gas_used
                 .set(gas_meter)
@@ -1078,6 +1426,10 @@ fn merge_vp_results(
     let mut errors = a.errors;
     errors.append(&mut b.errors);
     let invalid_sig = a.invalid_sig || b.invalid_sig;
+    let mut vps_gas = a.vps_gas;
+    vps_gas.append(&mut b.vps_gas);
+    let mut vps_gas_profile = a.vps_gas_profile;
+    vps_gas_profile.append(&mut b.vps_gas_profile);
     let mut gas_used = a.gas_used;
 
     gas_used
@@ -1088,6 +1440,8 @@ fn merge_vp_results(
         accepted_vps,
         rejected_vps,
         gas_used,
+        vps_gas,
+        vps_gas_profile,
         errors,
         invalid_sig,
     })
@@ -0,0 +1,353 @@
+//! Scheduler VP
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use namada_core::ledger::gas::get_max_block_gas;
+use namada_core::ledger::parameters::read_gas_cost;
+use namada_core::ledger::scheduler::MAX_TXS_PER_SCHEDULE;
+use namada_core::ledger::scheduler::storage::keys as scheduler_storage;
+use namada_core::ledger::storage;
+use namada_core::ledger::storage_api::scheduler::count_scheduled_txs;
+use namada_core::ledger::storage_api::token::read_balance;
+use namada_core::ledger::vp_env::VpEnv;
+use namada_core::proto::Tx;
+use namada_core::types::transaction::scheduler::ScheduleTx;
+use thiserror::Error;
+
+use crate::ledger::native_vp;
+use crate::ledger::native_vp::{Ctx, NativeVp};
+use crate::ledger::storage_api::StorageRead;
+use crate::types::address::{Address, InternalAddress};
+use crate::types::storage::Key;
+use crate::types::token;
+use crate::vm::WasmCacheAccess;
+
+/// for handling Scheduler NativeVP errors
+pub type Result<T> = std::result::Result<T, Error>;
+
+/// The scheduler internal address
+pub const ADDRESS: Address = Address::Internal(InternalAddress::Scheduler);
+
+#[allow(missing_docs)]
+#[derive(Error, Debug)]
+pub enum Error {
+    #[error("Native VP error: {0}")]
+    NativeVpError(#[from] native_vp::Error),
+}
+
+/// The scheduled txs created and deleted by a tx, by id
+#[derive(Default)]
+struct ScheduledTxChanges {
+    created: BTreeMap<u64, ScheduleTx>,
+    deleted: BTreeMap<u64, ScheduleTx>,
+}
+
+impl ScheduledTxChanges {
+    /// Get a created or deleted scheduled tx with a flag set if it was created
+    fn get(&self, id: u64) -> Option<(&ScheduleTx, bool)> {
+        self.created
+            .get(&id)
+            .map(|scheduled| (scheduled, true))
+            .or_else(|| {
+                self.deleted.get(&id).map(|scheduled| (scheduled, false))
+            })
+    }
+
+    /// The total amount of fees escrowed and released in the given token
+    fn escrow_changes(
+        &self,
+        token: &Address,
+    ) -> Option<(token::Amount, token::Amount)> {
+        let sum = |scheduled_txs: &BTreeMap<u64, ScheduleTx>| {
+            scheduled_txs
+                .values()
+                .filter(|scheduled| &scheduled.fee.token == token)
+                .try_fold(token::Amount::zero(), |acc, scheduled| {
+                    acc.checked_add(scheduled.escrowed_fees()?)
+                })
+        };
+        Some((sum(&self.created)?, sum(&self.deleted)?))
+    }
+}
+
+/// Scheduler VP
+pub struct SchedulerVp<'a, DB, H, CA>
+where
+    DB: storage::DB + for<'iter> storage::DBIter<'iter>,
+    H: storage::StorageHasher,
+    CA: WasmCacheAccess,
+{
+    /// Context to interact with the host structures.
+    pub ctx: Ctx<'a, DB, H, CA>,
+}
+
+impl<'a, DB, H, CA> NativeVp for SchedulerVp<'a, DB, H, CA>
+where
+    DB: 'static + storage::DB + for<'iter> storage::DBIter<'iter>,
+    H: 'static + storage::StorageHasher,
+    CA: 'static + WasmCacheAccess,
+{
+    type Error = Error;
+
+    fn validate_tx(
+        &self,
+        _tx_data: &Tx,
+        keys_changed: &BTreeSet<Key>,
+        verifiers: &BTreeSet<Address>,
+    ) -> Result<bool> {
+        let mut changes = ScheduledTxChanges::default();
+        for key in keys_changed {
+            let id = match scheduler_storage::is_tx_key(key) {
+                Some(id) => id,
+                None => continue,
+            };
+            let pre: Option<ScheduleTx> = self.ctx.pre().read(key)?;
+            let post: Option<ScheduleTx> = self.ctx.post().read(key)?;
+            match (pre, post) {
+                (None, Some(scheduled)) => {
+                    changes.created.insert(id, scheduled);
+                }
+                (Some(scheduled), None) => {
+                    changes.deleted.insert(id, scheduled);
+                }
+                _ => {
+                    tracing::info!("Scheduled tx {id} cannot be modified");
+                    return Ok(false);
+                }
+            }
+        }
+
+        Ok(keys_changed.iter().all(|key| {
+            let result =
+                self.is_valid_key(key, &changes, keys_changed, verifiers);
+            match &result {
+                Err(err) => {
+                    tracing::info!("Key {key} rejected with error: {err:#?}.")
+                }
+                Ok(false) => tracing::info!("Key {key} rejected"),
+                Ok(true) => {}
+            }
+            result.unwrap_or(false)
+        }))
+    }
+}
+
+impl<'a, DB, H, CA> SchedulerVp<'a, DB, H, CA>
+where
+    DB: 'static + storage::DB + for<'iter> storage::DBIter<'iter>,
+    H: 'static + storage::StorageHasher,
+    CA: 'static + WasmCacheAccess,
+{
+    fn is_valid_key(
+        &self,
+        key: &Key,
+        changes: &ScheduledTxChanges,
+        keys_changed: &BTreeSet<Key>,
+        verifiers: &BTreeSet<Address>,
+    ) -> Result<bool> {
+        if let Some(id) = scheduler_storage::is_tx_key(key) {
+            return match changes.get(id) {
+                Some((scheduled, true)) => self.is_valid_new_scheduled_tx(
+                    id,
+                    scheduled,
+                    keys_changed,
+                    verifiers,
+                ),
+                Some((scheduled, false)) => Ok(verifiers
+                    .contains(&scheduled.owner)
+                    && keys_changed.contains(
+                        &scheduler_storage::get_owner_key(&scheduled.owner, id),
+                    )
+                    && keys_changed.contains(
+                        &scheduler_storage::get_schedule_key(
+                            &scheduled.schedule,
+                            id,
+                        ),
+                    )),
+                None => Ok(false),
+            };
+        }
+        if let Some((owner, id)) = scheduler_storage::is_owner_key(key) {
+            return self.is_valid_index_key(
+                key,
+                changes.get(id),
+                |scheduled| &scheduled.owner == owner,
+            );
+        }
+        if let Some((schedule, id)) = scheduler_storage::is_schedule_key(key) {
+            return self.is_valid_index_key(
+                key,
+                changes.get(id),
+                |scheduled: &ScheduleTx| scheduled.schedule == schedule,
+            );
+        }
+        if scheduler_storage::is_counter_key(key) {
+            return self.is_valid_counter(changes);
+        }
+        if let Some([token, owner]) = token::is_any_token_balance_key(key) {
+            if owner == &ADDRESS {
+                return self.is_valid_balance(token, changes);
+            }
+            return Ok(true);
+        }
+        // Any other change in the scheduler subspace is invalid
+        Ok(!scheduler_storage::is_scheduler_key(key))
+    }
+
+    /// Validate a newly scheduled tx
+    fn is_valid_new_scheduled_tx(
+        &self,
+        id: u64,
+        scheduled: &ScheduleTx,
+        keys_changed: &BTreeSet<Key>,
+        verifiers: &BTreeSet<Address>,
+    ) -> Result<bool> {
+        let has_index_keys = keys_changed
+            .contains(&scheduler_storage::get_owner_key(&scheduled.owner, id))
+            && keys_changed.contains(&scheduler_storage::get_schedule_key(
+                &scheduled.schedule,
+                id,
+            ));
+        if !verifiers.contains(&scheduled.owner) || !has_index_keys {
+            return Ok(false);
+        }
+
+        let height = self.ctx.get_block_height()?;
+        let epoch = self.ctx.get_block_epoch()?;
+        if !scheduled.schedule.is_after(height, epoch) {
+            tracing::info!(
+                "Scheduled tx {id} is due {}, which is not in the future",
+                scheduled.schedule
+            );
+            return Ok(false);
+        }
+        if scheduled.tx.header.chain_id.as_str() != self.ctx.get_chain_id()? {
+            tracing::info!("Scheduled tx {id} is for another chain");
+            return Ok(false);
+        }
+        // A scheduled tx must fit in a block, and only a few of them may be
+        // due at once, as they're executed on top of the txs of the block
+        let max_block_gas = get_max_block_gas(&self.ctx.pre())?;
+        if u64::from(scheduled.gas_limit) > max_block_gas {
+            tracing::info!(
+                "Scheduled tx {id} has a gas limit above the max block gas \
+                 {max_block_gas}"
+            );
+            return Ok(false);
+        }
+        if count_scheduled_txs(&self.ctx.post(), &scheduled.schedule)?
+            > MAX_TXS_PER_SCHEDULE
+        {
+            tracing::info!(
+                "More than {MAX_TXS_PER_SCHEDULE} txs are scheduled {}",
+                scheduled.schedule
+            );
+            return Ok(false);
+        }
+
+        let minimum_gas_price =
+            read_gas_cost(&self.ctx.pre(), &scheduled.fee.token)?;
+        match minimum_gas_price {
+            Some(minimum) => Ok(scheduled.fee.amount_per_gas_unit >= minimum
+                && scheduled.escrowed_fees().is_some()),
+            None => {
+                tracing::info!(
+                    "Scheduled tx {id} pays fees in a non-whitelisted token"
+                );
+                Ok(false)
+            }
+        }
+    }
+
+    /// Validate an index entry of a created or deleted scheduled tx, which
+    /// must be written or deleted together with it
+    fn is_valid_index_key(
+        &self,
+        key: &Key,
+        scheduled: Option<(&ScheduleTx, bool)>,
+        matches: impl FnOnce(&ScheduleTx) -> bool,
+    ) -> Result<bool> {
+        match scheduled {
+            Some((scheduled, is_created)) => Ok(matches(scheduled)
+                && self.ctx.post().has_key(key)? == is_created),
+            None => Ok(false),
+        }
+    }
+
+    /// Validate that the counter was incremented by the number of newly
+    /// scheduled txs, whose ids were taken from it
+    fn is_valid_counter(&self, changes: &ScheduledTxChanges) -> Result<bool> {
+        let counter_key = scheduler_storage::get_counter_key();
+        let pre: u64 = self.ctx.pre().read(&counter_key)?.unwrap_or_default();
+        let post: u64 = self.ctx.post().read(&counter_key)?.unwrap_or_default();
+        Ok(post.checked_sub(pre) == Some(changes.created.len() as u64)
+            && changes.created.keys().all(|id| (pre..post).contains(id)))
+    }
+
+    /// Validate that the balance of the scheduler changed exactly by the
+    /// fees escrowed by the newly scheduled txs minus the fees released by
+    /// the deleted ones
+    fn is_valid_balance(
+        &self,
+        token: &Address,
+        changes: &ScheduledTxChanges,
+    ) -> Result<bool> {
+        let (escrowed, released) = match changes.escrow_changes(token) {
+            Some(escrow_changes) => escrow_changes,
+            None => return Ok(false),
+        };
+        let pre = read_balance(&self.ctx.pre(), token, &ADDRESS)?;
+        let post = read_balance(&self.ctx.post(), token, &ADDRESS)?;
+        Ok(pre.checked_add(escrowed).is_some()
+            && pre.checked_add(escrowed) == post.checked_add(released))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use namada_core::types::transaction::scheduler::Schedule;
+
+    use super::*;
+    use crate::types::address::nam;
+    use crate::types::address::testing::established_address_1;
+    use crate::types::storage::Epoch;
+    use crate::types::transaction::{Fee, GasLimit};
+
+    fn scheduled_tx(token: Address, amount: u64) -> ScheduleTx {
+        ScheduleTx {
+            owner: established_address_1(),
+            schedule: Schedule::AtEpoch(Epoch(1)),
+            fee: Fee {
+                amount_per_gas_unit: token::Amount::from_u64(amount),
+                token,
+            },
+            gas_limit: GasLimit::from(10),
+            tx: Tx::default(),
+        }
+    }
+
+    /// Test that the escrow changes are tallied per token
+    #[test]
+    fn test_escrow_changes() {
+        let other_token = Address::Internal(InternalAddress::Pgf);
+        let mut changes = ScheduledTxChanges::default();
+        changes.created.insert(0, scheduled_tx(nam(), 1));
+        changes.created.insert(1, scheduled_tx(nam(), 2));
+        changes
+            .created
+            .insert(2, scheduled_tx(other_token.clone(), 5));
+        changes.deleted.insert(3, scheduled_tx(nam(), 3));
+
+        assert_eq!(
+            changes.escrow_changes(&nam()),
+            Some((token::Amount::from_u64(30), token::Amount::from_u64(30)))
+        );
+        assert_eq!(
+            changes.escrow_changes(&other_token),
+            Some((token::Amount::from_u64(50), token::Amount::zero()))
+        );
+        assert!(matches!(changes.get(2), Some((_, true))));
+        assert!(matches!(changes.get(3), Some((_, false))));
+        assert!(changes.get(4).is_none());
+    }
+}
@@ -2,7 +2,6 @@
 
 use std::num::TryFromIntError;
 
-use namada_core::ledger::gas::MEMORY_ACCESS_GAS_PER_BYTE;
 use namada_core::types::address::{Address, ESTABLISHED_ADDRESS_BYTES_LEN};
 use namada_core::types::hash::{Hash, HASH_LENGTH};
 use namada_core::types::storage::{
@@ -298,14 +297,19 @@ where
 
 /// Getting the block hash. The height is that of the block to which the
 /// current transaction is being applied.
-pub fn get_tx_code_hash(
+pub fn get_tx_code_hash<DB, H>(
     gas_meter: &mut VpGasMeter,
+    storage: &Storage<DB, H>,
     tx: &Tx,
     sentinel: &mut VpSentinel,
-) -> EnvResult<Option<Hash>> {
+) -> EnvResult<Option<Hash>>
+where
+    DB: storage::DB + for<'iter> storage::DBIter<'iter>,
+    H: StorageHasher,
+{
     add_gas(
         gas_meter,
-        HASH_LENGTH as u64 * MEMORY_ACCESS_GAS_PER_BYTE,
+        HASH_LENGTH as u64 * storage.gas_costs.memory_access_per_byte,
         sentinel,
     )?;
     let hash = tx
@@ -333,14 +337,19 @@ where
 
 /// Getting the block epoch. The epoch is that of the block to which the
 /// current transaction is being applied.
-pub fn get_tx_index(
+pub fn get_tx_index<DB, H>(
     gas_meter: &mut VpGasMeter,
+    storage: &Storage<DB, H>,
     tx_index: &TxIndex,
     sentinel: &mut VpSentinel,
-) -> EnvResult<TxIndex> {
+) -> EnvResult<TxIndex>
+where
+    DB: storage::DB + for<'iter> storage::DBIter<'iter>,
+    H: StorageHasher,
+{
     add_gas(
         gas_meter,
-        TX_INDEX_LENGTH as u64 * MEMORY_ACCESS_GAS_PER_BYTE,
+        TX_INDEX_LENGTH as u64 * storage.gas_costs.memory_access_per_byte,
         sentinel,
     )?;
     Ok(*tx_index)
@@ -358,7 +367,8 @@ where
 {
     add_gas(
         gas_meter,
-        ESTABLISHED_ADDRESS_BYTES_LEN as u64 * MEMORY_ACCESS_GAS_PER_BYTE,
+        ESTABLISHED_ADDRESS_BYTES_LEN as u64
+            * storage.gas_costs.memory_access_per_byte,
         sentinel,
     )?;
     Ok(storage.native_token.clone())
@@ -7,9 +7,7 @@ use std::num::TryFromIntError;
 use borsh::BorshDeserialize;
 use borsh_ext::BorshSerializeExt;
 use masp_primitives::transaction::Transaction;
-use namada_core::ledger::gas::{
-    GasMetering, TxGasMeter, MEMORY_ACCESS_GAS_PER_BYTE,
-};
+use namada_core::ledger::gas::{GasMetering, TxGasMeter, WASM_GAS_SECTION};
 use namada_core::types::address::{ESTABLISHED_ADDRESS_BYTES_LEN, MASP};
 use namada_core::types::internal::KeyVal;
 use namada_core::types::storage::TX_INDEX_LENGTH;
@@ -522,6 +520,70 @@ where
     vp_host_fns::add_gas(gas_meter, used_gas, sentinel)
 }
 
+/// Add a gas cost charged by the transaction wasm code itself, i.e. for its
+/// instructions
+pub fn tx_charge_wasm_gas<MEM, DB, H, CA>(
+    env: &TxVmEnv<MEM, DB, H, CA>,
+    used_gas: u64,
+) -> TxResult<()>
+where
+    MEM: VmMemory,
+    DB: storage::DB + for<'iter> storage::DBIter<'iter>,
+    H: StorageHasher,
+    CA: WasmCacheAccess,
+{
+    tx_enter_section(env, WASM_GAS_SECTION);
+    tx_charge_gas(env, used_gas)
+}
+
+/// Add a gas cost charged by the VP wasm code itself, i.e. for its
+/// instructions
+pub fn vp_charge_wasm_gas<MEM, DB, H, EVAL, CA>(
+    env: &VpVmEnv<MEM, DB, H, EVAL, CA>,
+    used_gas: u64,
+) -> vp_host_fns::EnvResult<()>
+where
+    MEM: VmMemory,
+    DB: storage::DB + for<'iter> storage::DBIter<'iter>,
+    H: StorageHasher,
+    EVAL: VpEvaluator,
+    CA: WasmCacheAccess,
+{
+    vp_enter_section(env, WASM_GAS_SECTION);
+    vp_charge_gas(env, used_gas)
+}
+
+/// Attribute the gas used from now on by a profiled transaction to the given
+/// section, named after the host function that is entered
+fn tx_enter_section<MEM, DB, H, CA>(
+    env: &TxVmEnv<MEM, DB, H, CA>,
+    section: &'static str,
+) where
+    MEM: VmMemory,
+    DB: storage::DB + for<'iter> storage::DBIter<'iter>,
+    H: StorageHasher,
+    CA: WasmCacheAccess,
+{
+    let gas_meter = unsafe { env.ctx.gas_meter.get() };
+    gas_meter.enter_section(section);
+}
+
+/// Attribute the gas used from now on by a profiled VP to the given section,
+/// named after the host function that is entered
+fn vp_enter_section<MEM, DB, H, EVAL, CA>(
+    env: &VpVmEnv<MEM, DB, H, EVAL, CA>,
+    section: &'static str,
+) where
+    MEM: VmMemory,
+    DB: storage::DB + for<'iter> storage::DBIter<'iter>,
+    H: StorageHasher,
+    EVAL: VpEvaluator,
+    CA: WasmCacheAccess,
+{
+    let gas_meter = unsafe { env.ctx.gas_meter.get() };
+    gas_meter.enter_section(section);
+}
+
 /// Storage `has_key` function exposed to the wasm VM Tx environment. It will
 /// try to check the write log first and if no entry found then the storage.
 pub fn tx_has_key<MEM, DB, H, CA>(
@@ -535,6 +597,7 @@ where
     H: StorageHasher,
     CA: WasmCacheAccess,
 {
+    tx_enter_section(env, "tx_has_key");
     let (key, gas) = env
         .memory
         .read_string(key_ptr, key_len as _)
@@ -591,6 +654,7 @@ where
     H: StorageHasher,
     CA: WasmCacheAccess,
 {
+    tx_enter_section(env, "tx_read");
     let (key, gas) = env
         .memory
         .read_string(key_ptr, key_len as _)
@@ -680,6 +744,7 @@ where
     H: StorageHasher,
     CA: WasmCacheAccess,
 {
+    tx_enter_section(env, "tx_result_buffer");
     let result_buffer = unsafe { env.ctx.result_buffer.get() };
     let value = result_buffer.take().unwrap();
     let gas = env
@@ -703,6 +768,7 @@ where
     H: StorageHasher,
     CA: WasmCacheAccess,
 {
+    tx_enter_section(env, "tx_iter_prefix");
     let (prefix, gas) = env
         .memory
         .read_string(prefix_ptr, prefix_len as _)
@@ -739,6 +805,7 @@ where
     H: StorageHasher,
     CA: WasmCacheAccess,
 {
+    tx_enter_section(env, "tx_iter_next");
     tracing::debug!("tx_iter_next iter_id {}", iter_id,);
 
     let write_log = unsafe { env.ctx.write_log.get() };
@@ -818,6 +885,7 @@ where
     H: StorageHasher,
     CA: WasmCacheAccess,
 {
+    tx_enter_section(env, "tx_write");
     let (key, gas) = env
         .memory
         .read_string(key_ptr, key_len as _)
@@ -861,6 +929,7 @@ where
     H: StorageHasher,
     CA: WasmCacheAccess,
 {
+    tx_enter_section(env, "tx_write_temp");
     let (key, gas) = env
         .memory
         .read_string(key_ptr, key_len as _)
@@ -951,6 +1020,7 @@ where
     H: StorageHasher,
     CA: WasmCacheAccess,
 {
+    tx_enter_section(env, "tx_delete");
     let (key, gas) = env
         .memory
         .read_string(key_ptr, key_len as _)
@@ -984,6 +1054,7 @@ where
     H: StorageHasher,
     CA: WasmCacheAccess,
 {
+    tx_enter_section(env, "tx_emit_ibc_event");
     let (event, gas) = env
         .memory
         .read_bytes(event_ptr, event_len as _)
@@ -1008,6 +1079,7 @@ where
     H: StorageHasher,
     CA: WasmCacheAccess,
 {
+    tx_enter_section(env, "tx_get_ibc_events");
     let (event_type, gas) = env
         .memory
         .read_string(event_type_ptr, event_type_len as _)
@@ -1047,6 +1119,7 @@ where
     EVAL: VpEvaluator,
     CA: WasmCacheAccess,
 {
+    vp_enter_section(env, "vp_read_pre");
     let (key, gas) = env
         .memory
         .read_string(key_ptr, key_len as _)
@@ -1100,6 +1173,7 @@ where
     EVAL: VpEvaluator,
     CA: WasmCacheAccess,
 {
+    vp_enter_section(env, "vp_read_post");
     let (key, gas) = env
         .memory
         .read_string(key_ptr, key_len as _)
@@ -1148,6 +1222,7 @@ where
     EVAL: VpEvaluator,
     CA: WasmCacheAccess,
 {
+    vp_enter_section(env, "vp_read_temp");
     let (key, gas) = env
         .memory
         .read_string(key_ptr, key_len as _)
@@ -1196,6 +1271,7 @@ where
     EVAL: VpEvaluator,
     CA: WasmCacheAccess,
 {
+    vp_enter_section(env, "vp_result_buffer");
     let result_buffer = unsafe { env.ctx.result_buffer.get() };
     let value = result_buffer.take().unwrap();
     let gas = env
@@ -1221,6 +1297,7 @@ where
     EVAL: VpEvaluator,
     CA: WasmCacheAccess,
 {
+    vp_enter_section(env, "vp_has_key_pre");
     let (key, gas) = env
         .memory
         .read_string(key_ptr, key_len as _)
@@ -1256,6 +1333,7 @@ where
     EVAL: VpEvaluator,
     CA: WasmCacheAccess,
 {
+    vp_enter_section(env, "vp_has_key_post");
     let (key, gas) = env
         .memory
         .read_string(key_ptr, key_len as _)
@@ -1292,6 +1370,7 @@ where
     EVAL: VpEvaluator,
     CA: WasmCacheAccess,
 {
+    vp_enter_section(env, "vp_iter_prefix_pre");
     let (prefix, gas) = env
         .memory
         .read_string(prefix_ptr, prefix_len as _)
@@ -1331,6 +1410,7 @@ where
     EVAL: VpEvaluator,
     CA: WasmCacheAccess,
 {
+    vp_enter_section(env, "vp_iter_prefix_post");
     let (prefix, gas) = env
         .memory
         .read_string(prefix_ptr, prefix_len as _)
@@ -1370,6 +1450,7 @@ where
     EVAL: VpEvaluator,
     CA: WasmCacheAccess,
 {
+    vp_enter_section(env, "vp_iter_next");
     tracing::debug!("vp_iter_next iter_id {}", iter_id);
 
     let iterators = unsafe { env.ctx.iterators.get() };
@@ -1406,6 +1487,7 @@ where
     H: StorageHasher,
     CA: WasmCacheAccess,
 {
+    tx_enter_section(env, "tx_insert_verifier");
     let (addr, gas) = env
         .memory
         .read_string(addr_ptr, addr_len as _)
@@ -1416,10 +1498,11 @@ where
 
     let addr = Address::decode(&addr).map_err(TxRuntimeError::AddressError)?;
 
+    let storage = unsafe { env.ctx.storage.get() };
     let verifiers = unsafe { env.ctx.verifiers.get() };
     // This is not a storage write, use the same multiplier used for a storage
     // read
-    tx_charge_gas(env, addr_len * MEMORY_ACCESS_GAS_PER_BYTE)?;
+    tx_charge_gas(env, addr_len * storage.gas_costs.memory_access_per_byte)?;
     verifiers.insert(addr);
 
     Ok(())
@@ -1441,6 +1524,7 @@ where
     H: StorageHasher,
     CA: WasmCacheAccess,
 {
+    tx_enter_section(env, "tx_update_validity_predicate");
     let (addr, gas) = env
         .memory
         .read_string(addr_ptr, addr_len as _)
@@ -1489,6 +1573,7 @@ where
     H: StorageHasher,
     CA: WasmCacheAccess,
 {
+    tx_enter_section(env, "tx_init_account");
     let (code_hash, gas) = env
         .memory
         .read_bytes(code_hash_ptr, code_hash_len as _)
@@ -1532,6 +1617,7 @@ where
     H: StorageHasher,
     CA: WasmCacheAccess,
 {
+    tx_enter_section(env, "tx_get_chain_id");
     let storage = unsafe { env.ctx.storage.get() };
     let (chain_id, gas) = storage.get_chain_id();
     tx_charge_gas(env, gas)?;
@@ -1554,6 +1640,7 @@ where
     H: StorageHasher,
     CA: WasmCacheAccess,
 {
+    tx_enter_section(env, "tx_get_block_height");
     let storage = unsafe { env.ctx.storage.get() };
     let (height, gas) = storage.get_block_height();
     tx_charge_gas(env, gas)?;
@@ -1572,7 +1659,12 @@ where
     H: StorageHasher,
     CA: WasmCacheAccess,
 {
-    tx_charge_gas(env, TX_INDEX_LENGTH as u64 * MEMORY_ACCESS_GAS_PER_BYTE)?;
+    tx_enter_section(env, "tx_get_tx_index");
+    let storage = unsafe { env.ctx.storage.get() };
+    tx_charge_gas(
+        env,
+        TX_INDEX_LENGTH as u64 * storage.gas_costs.memory_access_per_byte,
+    )?;
     let tx_index = unsafe { env.ctx.tx_index.get() };
     Ok(tx_index.0)
 }
@@ -1590,10 +1682,13 @@ where
     EVAL: VpEvaluator,
     CA: WasmCacheAccess,
 {
+    vp_enter_section(env, "vp_get_tx_index");
     let gas_meter = unsafe { env.ctx.gas_meter.get() };
     let sentinel = unsafe { env.ctx.sentinel.get() };
+    let storage = unsafe { env.ctx.storage.get() };
     let tx_index = unsafe { env.ctx.tx_index.get() };
-    let tx_idx = vp_host_fns::get_tx_index(gas_meter, tx_index, sentinel)?;
+    let tx_idx =
+        vp_host_fns::get_tx_index(gas_meter, storage, tx_index, sentinel)?;
     Ok(tx_idx.0)
 }
 
@@ -1609,6 +1704,7 @@ where
     H: StorageHasher,
     CA: WasmCacheAccess,
 {
+    tx_enter_section(env, "tx_get_block_hash");
     let storage = unsafe { env.ctx.storage.get() };
     let (hash, gas) = storage.get_block_hash();
     tx_charge_gas(env, gas)?;
@@ -1631,6 +1727,7 @@ where
     H: StorageHasher,
     CA: WasmCacheAccess,
 {
+    tx_enter_section(env, "tx_get_block_epoch");
     let storage = unsafe { env.ctx.storage.get() };
     let (epoch, gas) = storage.get_current_epoch();
     tx_charge_gas(env, gas)?;
@@ -1648,12 +1745,14 @@ where
     H: StorageHasher,
     CA: WasmCacheAccess,
 {
+    tx_enter_section(env, "tx_get_native_token");
+    let storage = unsafe { env.ctx.storage.get() };
     // Gas for getting the native token address from storage
     tx_charge_gas(
         env,
-        ESTABLISHED_ADDRESS_BYTES_LEN as u64 * MEMORY_ACCESS_GAS_PER_BYTE,
+        ESTABLISHED_ADDRESS_BYTES_LEN as u64
+            * storage.gas_costs.memory_access_per_byte,
     )?;
-    let storage = unsafe { env.ctx.storage.get() };
     let native_token = storage.native_token.clone();
     let native_token_string = native_token.encode();
     let gas = env
@@ -1674,6 +1773,7 @@ where
     H: StorageHasher,
     CA: WasmCacheAccess,
 {
+    tx_enter_section(env, "tx_get_block_header");
     let storage = unsafe { env.ctx.storage.get() };
     let (header, gas) = storage
         .get_block_header(Some(BlockHeight(height)))
@@ -1706,6 +1806,7 @@ where
     EVAL: VpEvaluator,
     CA: WasmCacheAccess,
 {
+    vp_enter_section(env, "vp_get_chain_id");
     let gas_meter = unsafe { env.ctx.gas_meter.get() };
     let sentinel = unsafe { env.ctx.sentinel.get() };
     let storage = unsafe { env.ctx.storage.get() };
@@ -1730,6 +1831,7 @@ where
     EVAL: VpEvaluator,
     CA: WasmCacheAccess,
 {
+    vp_enter_section(env, "vp_get_block_height");
     let gas_meter = unsafe { env.ctx.gas_meter.get() };
     let sentinel = unsafe { env.ctx.sentinel.get() };
     let storage = unsafe { env.ctx.storage.get() };
@@ -1749,6 +1851,7 @@ where
     EVAL: VpEvaluator,
     CA: WasmCacheAccess,
 {
+    vp_enter_section(env, "vp_get_block_header");
     let gas_meter = unsafe { env.ctx.gas_meter.get() };
     let sentinel = unsafe { env.ctx.sentinel.get() };
     let storage = unsafe { env.ctx.storage.get() };
@@ -1784,6 +1887,7 @@ where
     EVAL: VpEvaluator,
     CA: WasmCacheAccess,
 {
+    vp_enter_section(env, "vp_get_block_hash");
     let gas_meter = unsafe { env.ctx.gas_meter.get() };
     let sentinel = unsafe { env.ctx.sentinel.get() };
     let storage = unsafe { env.ctx.storage.get() };
@@ -1807,10 +1911,12 @@ where
     EVAL: VpEvaluator,
     CA: WasmCacheAccess,
 {
+    vp_enter_section(env, "vp_get_tx_code_hash");
     let gas_meter = unsafe { env.ctx.gas_meter.get() };
     let sentinel = unsafe { env.ctx.sentinel.get() };
+    let storage = unsafe { env.ctx.storage.get() };
     let tx = unsafe { env.ctx.tx.get() };
-    let hash = vp_host_fns::get_tx_code_hash(gas_meter, tx, sentinel)?;
+    let hash = vp_host_fns::get_tx_code_hash(gas_meter, storage, tx, sentinel)?;
     let mut result_bytes = vec![];
     if let Some(hash) = hash {
         result_bytes.push(1);
@@ -1838,6 +1944,7 @@ where
     EVAL: VpEvaluator,
     CA: WasmCacheAccess,
 {
+    vp_enter_section(env, "vp_get_block_epoch");
     let gas_meter = unsafe { env.ctx.gas_meter.get() };
     let sentinel = unsafe { env.ctx.sentinel.get() };
     let storage = unsafe { env.ctx.storage.get() };
@@ -1858,6 +1965,7 @@ where
     EVAL: VpEvaluator,
     CA: WasmCacheAccess,
 {
+    vp_enter_section(env, "vp_get_ibc_events");
     let (event_type, gas) = env
         .memory
         .read_string(event_type_ptr, event_type_len as _)
@@ -1902,6 +2010,7 @@ where
     EVAL: VpEvaluator,
     CA: WasmCacheAccess,
 {
+    vp_enter_section(env, "vp_verify_tx_section_signature");
     let (hash_list, gas) = env
         .memory
         .read_bytes(hash_list_ptr, hash_list_len as _)
@@ -1940,16 +2049,31 @@ where
     let max_signatures = Option::<u8>::try_from_slice(&max_signatures)
         .map_err(vp_host_fns::RuntimeError::EncodingError)?;
 
+    let storage = unsafe { env.ctx.storage.get() };
     let tx = unsafe { env.ctx.tx.get() };
 
-    match tx.verify_signatures(
-        &hashes,
-        public_keys_map,
-        &Some(signer),
-        threshold,
-        max_signatures,
-        || gas_meter.consume(gas::VERIFY_TX_SIG_GAS),
-    ) {
+    let signer = Some(signer);
+    let verify_sig_gas = storage.gas_costs.verify_tx_sig;
+    let verified = if gas_meter.accepts_dummy_signatures() {
+        tx.verify_dummy_signatures(
+            &hashes,
+            public_keys_map,
+            &signer,
+            threshold,
+            max_signatures,
+            || gas_meter.consume(verify_sig_gas),
+        )
+    } else {
+        tx.verify_signatures(
+            &hashes,
+            public_keys_map,
+            &signer,
+            threshold,
+            max_signatures,
+            || gas_meter.consume(verify_sig_gas),
+        )
+    };
+    match verified {
         Ok(_) => Ok(HostEnvResult::Success.to_i64()),
         Err(err) => match err {
             namada_core::proto::Error::OutOfGas(inner) => {
@@ -1979,6 +2103,7 @@ where
     H: StorageHasher,
     CA: WasmCacheAccess,
 {
+    tx_enter_section(env, "tx_log_string");
     let (str, _gas) = env
         .memory
         .read_string(str_ptr, str_len as _)
@@ -1999,6 +2124,7 @@ where
     H: StorageHasher,
     CA: WasmCacheAccess,
 {
+    tx_enter_section(env, "tx_ibc_execute");
     use std::cell::RefCell;
     use std::rc::Rc;
 
@@ -2087,6 +2213,7 @@ where
     H: StorageHasher,
     CA: WasmCacheAccess,
 {
+    tx_enter_section(env, "tx_set_commitment_sentinel");
     let sentinel = unsafe { env.ctx.sentinel.get() };
     sentinel.set_invalid_commitment();
 }
@@ -2109,6 +2236,7 @@ where
     H: StorageHasher,
     CA: WasmCacheAccess,
 {
+    tx_enter_section(env, "tx_verify_tx_section_signature");
     let (hash_list, gas) = env
         .memory
         .read_bytes(hash_list_ptr, hash_list_len as _)
@@ -2141,16 +2269,30 @@ where
     let max_signatures = Option::<u8>::try_from_slice(&max_signatures)
         .map_err(TxRuntimeError::EncodingError)?;
 
+    let storage = unsafe { env.ctx.storage.get() };
     let tx = unsafe { env.ctx.tx.get() };
 
-    match tx.verify_signatures(
-        &hashes,
-        public_keys_map,
-        &None,
-        threshold,
-        max_signatures,
-        || gas_meter.consume(gas::VERIFY_TX_SIG_GAS),
-    ) {
+    let verify_sig_gas = storage.gas_costs.verify_tx_sig;
+    let verified = if gas_meter.accepts_dummy_signatures() {
+        tx.verify_dummy_signatures(
+            &hashes,
+            public_keys_map,
+            &None,
+            threshold,
+            max_signatures,
+            || gas_meter.consume(verify_sig_gas),
+        )
+    } else {
+        tx.verify_signatures(
+            &hashes,
+            public_keys_map,
+            &None,
+            threshold,
+            max_signatures,
+            || gas_meter.consume(verify_sig_gas),
+        )
+    };
+    match verified {
         Ok(_) => Ok(HostEnvResult::Success.to_i64()),
         Err(err) => match err {
             namada_core::proto::Error::OutOfGas(inner) => {
@@ -2180,6 +2322,7 @@ where
     EVAL: VpEvaluator<Db = DB, H = H, Eval = EVAL, CA = CA>,
     CA: WasmCacheAccess,
 {
+    vp_enter_section(env, "vp_eval");
     let (vp_code_hash, gas) = env
         .memory
         .read_bytes(vp_code_hash_ptr, vp_code_hash_len as _)
@@ -2220,6 +2363,7 @@ where
     EVAL: VpEvaluator,
     CA: WasmCacheAccess,
 {
+    vp_enter_section(env, "vp_get_native_token");
     let gas_meter = unsafe { env.ctx.gas_meter.get() };
     let sentinel = unsafe { env.ctx.sentinel.get() };
     let storage = unsafe { env.ctx.storage.get() };
@@ -2248,6 +2392,7 @@ where
     EVAL: VpEvaluator,
     CA: WasmCacheAccess,
 {
+    vp_enter_section(env, "vp_log_string");
     let (str, _gas) = env
         .memory
         .read_string(str_ptr, str_len as _)
@@ -2409,21 +2554,16 @@ where
     }
 
     fn get_tx_index(&self) -> Result<TxIndex, storage_api::Error> {
+        let storage = unsafe { self.storage.get() };
         let tx_index = unsafe { self.tx_index.get() };
-        ibc_tx_charge_gas(
-            self,
-            crate::vm::host_env::gas::STORAGE_ACCESS_GAS_PER_BYTE,
-        )?;
+        ibc_tx_charge_gas(self, storage.gas_costs.storage_access_per_byte)?;
         Ok(TxIndex(tx_index.0))
     }
 
     fn get_native_token(&self) -> Result<Address, storage_api::Error> {
         let storage = unsafe { self.storage.get() };
         let native_token = storage.native_token.clone();
-        ibc_tx_charge_gas(
-            self,
-            crate::vm::host_env::gas::STORAGE_ACCESS_GAS_PER_BYTE,
-        )?;
+        ibc_tx_charge_gas(self, storage.gas_costs.storage_access_per_byte)?;
         Ok(native_token)
     }
 }
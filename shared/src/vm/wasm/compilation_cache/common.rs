@@ -3,6 +3,11 @@
 //! limit and a file system cache of compiled modules (either to dynamic libs
 //! compiled via the `dylib` module, or serialized modules compiled via the
 //! `universal` module).
+//!
+//! The files are addressed by the hash of the module and of the engine that
+//! compiled it, so they can be shared across restarts and chains, and they
+//! are checked for integrity when loaded. Their total size may be limited by
+//! a disk budget, in which case the least recently used files get evicted.
 
 use std::collections::hash_map::RandomState;
 use std::collections::HashMap;
@@ -12,13 +17,13 @@ use std::num::NonZeroUsize;
 use std::path::{Path, PathBuf};
 use std::sync::{Arc, RwLock};
 use std::thread::sleep;
-use std::time::Duration;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 use clru::{CLruCache, CLruCacheConfig, WeightScale};
 use wasmer::{Module, Store};
-use wasmer_cache::{FileSystemCache, Hash as CacheHash};
 
-use crate::core::types::hash::Hash;
+use crate::core::ledger::gas::GasCostTable;
+use crate::core::types::hash::{Hash, HASH_LENGTH};
 use crate::types::control_flow::time::{ExponentialBackoff, SleepStrategy};
 use crate::vm::wasm::run::untrusted_wasm_store;
 use crate::vm::wasm::{self, memory};
@@ -29,16 +34,27 @@ use crate::vm::{WasmCacheAccess, WasmCacheRoAccess};
 pub struct Cache<N: CacheName, A: WasmCacheAccess> {
     /// Cached files directory
     dir: PathBuf,
+    /// The hash of the engine that compiles the modules
+    engine_hash: Hash,
+    /// Disk usage of the cached files
+    disk: Arc<RwLock<DiskUsage>>,
     /// Compilation progress
     progress: Arc<RwLock<HashMap<Hash, Compilation>>>,
     /// In-memory LRU cache of compiled modules
     in_memory: Arc<RwLock<MemoryCache>>,
+    /// The gas costs injected into the compiled modules
+    gas_costs: GasCostTable,
     /// The cache's name
     name: PhantomData<N>,
     /// Cache access level
     access: PhantomData<A>,
 }
 
+/// The version of the format of the files of the compiled modules. It must be
+/// bumped whenever the modules compiled by the same wasmer version may differ,
+/// e.g. when the store's tunables change.
+const MODULE_FILE_FORMAT_VERSION: u32 = 1;
+
 /// This trait is used to give names to different caches
 pub trait CacheName: Clone + std::fmt::Debug {
     /// Get the name of the cache
@@ -73,6 +89,19 @@ impl<N: CacheName, A: WasmCacheAccess> Cache<N, A> {
     /// # Panics
     /// The `max_bytes` must be non-zero.
     pub fn new(dir: impl Into<PathBuf>, max_bytes: usize) -> Self {
+        Self::new_with_disk_budget(dir, max_bytes, None)
+    }
+
+    /// Create a wasm in-memory cache with a given size limit and a file
+    /// system cache, whose files are limited to `disk_budget` bytes, if any.
+    ///
+    /// # Panics
+    /// The `max_bytes` must be non-zero.
+    pub fn new_with_disk_budget(
+        dir: impl Into<PathBuf>,
+        max_bytes: usize,
+        disk_budget: Option<u64>,
+    ) -> Self {
         let cache = CLruCache::with_config(
             CLruCacheConfig::new(NonZeroUsize::new(max_bytes).unwrap())
                 .with_scale(ModuleCacheScale),
@@ -82,11 +111,15 @@ impl<N: CacheName, A: WasmCacheAccess> Cache<N, A> {
 
         fs::create_dir_all(&dir)
             .expect("Couldn't create the wasm cache directory");
+        let disk = Arc::new(RwLock::new(DiskUsage::scan(&dir, disk_budget)));
 
         Self {
             dir,
+            engine_hash: engine_hash(),
+            disk,
             progress: Default::default(),
             in_memory,
+            gas_costs: GasCostTable::default(),
             name: Default::default(),
             access: Default::default(),
         }
@@ -100,13 +133,53 @@ impl<N: CacheName, A: WasmCacheAccess> Cache<N, A> {
         &mut self,
         code_hash: &Hash,
     ) -> Result<Option<(Module, Store)>, wasm::run::Error> {
+        let hash = self.module_hash(code_hash);
         if A::is_read_write() {
-            self.get(code_hash)
+            self.get(&hash)
         } else {
-            self.peek(code_hash)
+            self.peek(&hash)
         }
     }
 
+    /// Set the gas costs to inject into the modules compiled from now on.
+    /// The modules compiled with different wasm costs are cached separately.
+    pub fn set_gas_costs(&mut self, gas_costs: GasCostTable) {
+        self.gas_costs = gas_costs;
+    }
+
+    /// Get the hash identifying the module compiled from the code with the
+    /// given hash and the current gas costs. Modules compiled with the
+    /// default wasm costs are identified by the code hash alone.
+    fn module_hash(&self, code_hash: &Hash) -> Hash {
+        if !self.gas_costs.has_custom_wasm_rules() {
+            return *code_hash;
+        }
+        let mut bytes = code_hash.0.to_vec();
+        bytes.extend(self.gas_costs.wasm_instruction.to_le_bytes());
+        bytes.extend(self.gas_costs.wasm_memory_page.to_le_bytes());
+        Hash::sha256(bytes)
+    }
+
+    /// Get the name of the file of the module with the given hash, addressed
+    /// by the hash of the module and of the engine that compiled it
+    fn module_file_name(&self, hash: &Hash) -> String {
+        let mut bytes = hash.0.to_vec();
+        bytes.extend(self.engine_hash.0);
+        format!(
+            "{}.{}",
+            Hash::sha256(bytes).to_string().to_lowercase(),
+            file_ext()
+        )
+    }
+
+    fn module_file_exists(&self, hash: &Hash) -> bool {
+        self.dir.join(self.module_file_name(hash)).is_file()
+    }
+
+    fn file_load_module(&self, hash: &Hash) -> Option<(Module, Store)> {
+        file_load_module(&self.dir, &self.disk, &self.module_file_name(hash))
+    }
+
     /// Get the current number of items in the cache
     pub fn get_size(&self) -> usize {
         self.in_memory.read().unwrap().len()
@@ -156,7 +229,17 @@ impl<N: CacheName, A: WasmCacheAccess> Cache<N, A> {
                         return Ok(Some((module.clone(), store())));
                     }
 
-                    let (module, store) = file_load_module(&self.dir, hash);
+                    let (module, store) = match self.file_load_module(hash) {
+                        Some(loaded) => loaded,
+                        None => {
+                            // The file was evicted or invalid, it has to be
+                            // compiled again
+                            drop(in_memory);
+                            let mut progress = self.progress.write().unwrap();
+                            progress.remove(hash);
+                            return Ok(None);
+                        }
+                    };
                     tracing::info!(
                         "{} found {} in file cache.",
                         N::name(),
@@ -180,17 +263,15 @@ impl<N: CacheName, A: WasmCacheAccess> Cache<N, A> {
                 }
                 None => {
                     drop(progress);
-                    let (module, store) = if module_file_exists(&self.dir, hash)
-                    {
-                        tracing::info!(
-                            "Loading {} {} from file.",
-                            N::name(),
-                            hash.to_string()
-                        );
-                        file_load_module(&self.dir, hash)
-                    } else {
-                        return Ok(None);
+                    let (module, store) = match self.file_load_module(hash) {
+                        Some(loaded) => loaded,
+                        None => return Ok(None),
                     };
+                    tracing::info!(
+                        "Loaded {} {} from file.",
+                        N::name(),
+                        hash.to_string()
+                    );
 
                     // Update progress
                     let mut progress = self.progress.write().unwrap();
@@ -246,13 +327,15 @@ impl<N: CacheName, A: WasmCacheAccess> Cache<N, A> {
                         return Ok(Some((module.clone(), store())));
                     }
 
-                    let (module, store) = file_load_module(&self.dir, hash);
-                    tracing::info!(
-                        "{} found {} in file cache.",
-                        N::name(),
-                        hash.to_string()
-                    );
-                    return Ok(Some((module, store)));
+                    let loaded = self.file_load_module(hash);
+                    if loaded.is_some() {
+                        tracing::info!(
+                            "{} found {} in file cache.",
+                            N::name(),
+                            hash.to_string()
+                        );
+                    }
+                    return Ok(loaded);
                 }
                 Some(Compilation::Compiling) => {
                     drop(progress);
@@ -268,16 +351,15 @@ impl<N: CacheName, A: WasmCacheAccess> Cache<N, A> {
                 None => {
                     drop(progress);
 
-                    return if module_file_exists(&self.dir, hash) {
+                    let loaded = self.file_load_module(hash);
+                    if loaded.is_some() {
                         tracing::info!(
-                            "Loading {} {} from file.",
+                            "Loaded {} {} from file.",
                             N::name(),
                             hash.to_string()
                         );
-                        Ok(Some(file_load_module(&self.dir, hash)))
-                    } else {
-                        Ok(None)
-                    };
+                    }
+                    return Ok(loaded);
                 }
             }
         }
@@ -288,35 +370,46 @@ impl<N: CacheName, A: WasmCacheAccess> Cache<N, A> {
         &mut self,
         code: impl AsRef<[u8]>,
     ) -> Result<Option<(Module, Store)>, wasm::run::Error> {
-        let hash = hash_of_code(&code);
+        let hash = self.module_hash(&hash_of_code(&code));
 
         if !A::is_read_write() {
             // It doesn't update the cache and files
             let progress = self.progress.read().unwrap();
-            match progress.get(&hash) {
-                Some(_) => return self.peek(&hash),
-                None => {
-                    let code = wasm::run::prepare_wasm_code(code)?;
-                    return Ok(Some(compile(code)?));
+            let is_known = progress.get(&hash).is_some();
+            drop(progress);
+            if is_known {
+                if let Some(module) = self.peek(&hash)? {
+                    return Ok(Some(module));
                 }
             }
+            let code = wasm::run::prepare_wasm_code(code, &self.gas_costs)?;
+            return Ok(Some(compile(code)?));
         }
 
         let mut progress = self.progress.write().unwrap();
         if progress.get(&hash).is_some() {
             drop(progress);
-            return self.fetch(&hash);
+            if let Some(module) = self.get(&hash)? {
+                return Ok(Some(module));
+            }
+            // The file was evicted or invalid
+            progress = self.progress.write().unwrap();
         }
         progress.insert(hash, Compilation::Compiling);
         drop(progress);
 
         tracing::info!("Compiling {} {}.", N::name(), hash.to_string());
 
-        match wasm::run::prepare_wasm_code(code) {
+        match wasm::run::prepare_wasm_code(code, &self.gas_costs) {
             Ok(code) => match compile(code) {
                 Ok((module, store)) => {
                     // Write the file
-                    file_write_module(&self.dir, &module, &hash);
+                    file_write_module(
+                        &self.dir,
+                        &self.disk,
+                        self.module_file_name(&hash),
+                        &module,
+                    );
 
                     // Update progress
                     let mut progress = self.progress.write().unwrap();
@@ -356,14 +449,14 @@ impl<N: CacheName, A: WasmCacheAccess> Cache<N, A> {
     /// thread and the function returns immediately.
     pub fn pre_compile(&mut self, code: impl AsRef<[u8]>) {
         if A::is_read_write() {
-            let hash = hash_of_code(&code);
+            let hash = self.module_hash(&hash_of_code(&code));
             let mut progress = self.progress.write().unwrap();
             match progress.get(&hash) {
                 Some(_) => {
                     // Already known, do nothing
                 }
                 None => {
-                    if module_file_exists(&self.dir, &hash) {
+                    if self.module_file_exists(&hash) {
                         progress.insert(hash, Compilation::Done);
                         return;
                     }
@@ -372,11 +465,15 @@ impl<N: CacheName, A: WasmCacheAccess> Cache<N, A> {
                     let progress = self.progress.clone();
                     let code = code.as_ref().to_vec();
                     let dir = self.dir.clone();
+                    let disk = self.disk.clone();
+                    let file_name = self.module_file_name(&hash);
+                    let gas_costs = self.gas_costs;
                     std::thread::spawn(move || {
                         tracing::info!("Compiling WASM {}.", hash.to_string());
 
                         let (_module, _store) =
-                            match wasm::run::prepare_wasm_code(code) {
+                            match wasm::run::prepare_wasm_code(code, &gas_costs)
+                            {
                                 Ok(code) => match compile(code) {
                                     Ok((module, store)) => {
                                         let mut progress =
@@ -399,7 +496,9 @@ impl<N: CacheName, A: WasmCacheAccess> Cache<N, A> {
                                                 N::name()
                                             )
                                         }
-                                        file_write_module(&dir, &module, &hash);
+                                        file_write_module(
+                                            &dir, &disk, file_name, &module,
+                                        );
                                         (module, store)
                                     }
                                     Err(err) => {
@@ -439,8 +538,11 @@ impl<N: CacheName, A: WasmCacheAccess> Cache<N, A> {
     pub fn read_only(&self) -> Cache<N, WasmCacheRoAccess> {
         Cache {
             dir: self.dir.clone(),
+            engine_hash: self.engine_hash,
+            disk: self.disk.clone(),
             progress: self.progress.clone(),
             in_memory: self.in_memory.clone(),
+            gas_costs: self.gas_costs,
             name: Default::default(),
             access: Default::default(),
         }
@@ -472,38 +574,208 @@ fn store() -> Store {
     universal::store()
 }
 
-fn file_write_module(dir: impl AsRef<Path>, module: &Module, hash: &Hash) {
-    use wasmer_cache::Cache;
-    let mut fs_cache = fs_cache(dir, hash);
-    fs_cache.store(CacheHash::new(hash.0), module).unwrap();
+/// Write the compiled module to a file, prefixed with the checksum of its
+/// serialized bytes, and remove the least recently used files that no longer
+/// fit into the disk budget.
+fn file_write_module(
+    dir: &Path,
+    disk: &RwLock<DiskUsage>,
+    name: String,
+    module: &Module,
+) {
+    let bytes = match module.serialize() {
+        Ok(bytes) => bytes,
+        Err(err) => {
+            tracing::warn!("Failed to serialize compiled module {name}: {err}");
+            return;
+        }
+    };
+    let mut content = Hash::sha256(&bytes).0.to_vec();
+    content.extend(bytes);
+
+    // Write a temporary file first, so that a partially written file is never
+    // loaded, not even by another process sharing the directory
+    let path = dir.join(&name);
+    let tmp_path = dir.join(format!("{name}.{}.tmp", std::process::id()));
+    if let Err(err) = fs::write(&tmp_path, &content)
+        .and_then(|()| fs::rename(&tmp_path, &path))
+    {
+        tracing::warn!(
+            "Failed to write compiled module {}: {err}",
+            path.to_string_lossy()
+        );
+        let _ = fs::remove_file(&tmp_path);
+        return;
+    }
+
+    let evicted = disk.write().unwrap().insert(name, content.len() as u64);
+    for name in evicted {
+        tracing::info!("Evicting compiled module {name} from file cache.");
+        let _ = fs::remove_file(dir.join(name));
+    }
 }
 
-fn file_load_module(dir: impl AsRef<Path>, hash: &Hash) -> (Module, Store) {
-    use wasmer_cache::Cache;
-    let fs_cache = fs_cache(dir, hash);
+/// Load a compiled module from a file, if it exists. A file that fails the
+/// integrity check or the deserialization is removed, so that the module gets
+/// compiled again.
+fn file_load_module(
+    dir: &Path,
+    disk: &RwLock<DiskUsage>,
+    name: &str,
+) -> Option<(Module, Store)> {
+    let path = dir.join(name);
+    let content = fs::read(&path).ok()?;
     let store = store();
-    let hash = CacheHash::new(hash.0);
-    let module = unsafe { fs_cache.load(&store, hash) }.unwrap();
-    (module, store)
+    let loaded = if content.len() >= HASH_LENGTH
+        && Hash::sha256(&content[HASH_LENGTH..]).0[..] == content[..HASH_LENGTH]
+    {
+        // The content is checked against the checksum written with it, so it
+        // must have been serialized by an engine with the same version and
+        // target, as identified by the file's name
+        unsafe { Module::deserialize(&store, &content[HASH_LENGTH..]) }
+            .map_err(|err| err.to_string())
+    } else {
+        Err("checksum mismatch".to_string())
+    };
+    match loaded {
+        Ok(module) => {
+            disk.write().unwrap().touch(name);
+            Some((module, store))
+        }
+        Err(err) => {
+            tracing::warn!(
+                "Removing invalid compiled module {}: {err}",
+                path.to_string_lossy()
+            );
+            let _ = fs::remove_file(&path);
+            disk.write().unwrap().remove(name);
+            None
+        }
+    }
 }
 
-fn fs_cache(dir: impl AsRef<Path>, hash: &Hash) -> FileSystemCache {
-    let path = dir.as_ref().join(hash.to_string().to_lowercase());
-    let mut fs_cache = FileSystemCache::new(path).unwrap();
-    fs_cache.set_cache_extension(Some(file_ext()));
-    fs_cache
+/// Identifies the engine that compiles the modules, as a compiled module can
+/// only be loaded by the same wasmer version on a host whose target has the
+/// same CPU features
+fn engine_hash() -> Hash {
+    let target = wasmer::Target::default();
+    Hash::sha256(format!(
+        "wasmer-{}/{}/{:?}/{}",
+        wasmer::VERSION,
+        target.triple(),
+        target.cpu_features(),
+        MODULE_FILE_FORMAT_VERSION
+    ))
 }
 
-fn module_file_exists(dir: impl AsRef<Path>, hash: &Hash) -> bool {
-    let file =
-        dir.as_ref()
-            .join(hash.to_string().to_lowercase())
-            .join(format!(
-                "{}.{}",
-                hash.to_string().to_lowercase(),
-                file_ext()
-            ));
-    file.exists()
+/// The disk usage of the files of the compiled modules, used to evict the
+/// least recently used files when the disk budget is exceeded
+#[derive(Debug, Default)]
+struct DiskUsage {
+    /// The maximum number of bytes of all the files, if any
+    budget: Option<u64>,
+    /// The number of bytes of all the files
+    total_bytes: u64,
+    /// A logical clock ordering the uses of the files
+    clock: u64,
+    /// The size and the last use of the files, by file name
+    files: HashMap<String, FileUsage>,
+}
+
+/// The disk usage of a file of a compiled module
+#[derive(Debug, Clone, Copy)]
+struct FileUsage {
+    bytes: u64,
+    last_use: u64,
+}
+
+impl DiskUsage {
+    /// Find the files of the compiled modules in the given directory, removing
+    /// the ones over the budget. The uses of the files aren't persisted, so
+    /// they're initially ordered by the files' modification time.
+    fn scan(dir: &Path, budget: Option<u64>) -> Self {
+        let mut found: Vec<(String, u64, SystemTime)> = fs::read_dir(dir)
+            .into_iter()
+            .flatten()
+            .filter_map(|entry| {
+                let entry = entry.ok()?;
+                let name = entry.file_name().into_string().ok()?;
+                let metadata = entry.metadata().ok()?;
+                if !metadata.is_file()
+                    || !name.ends_with(&format!(".{}", file_ext()))
+                {
+                    return None;
+                }
+                let modified = metadata.modified().unwrap_or(UNIX_EPOCH);
+                Some((name, metadata.len(), modified))
+            })
+            .collect();
+        found.sort_by_key(|(_name, _bytes, modified)| *modified);
+
+        let mut usage = Self {
+            budget,
+            ..Default::default()
+        };
+        for (name, bytes, _modified) in found {
+            for evicted in usage.insert(name, bytes) {
+                let _ = fs::remove_file(dir.join(evicted));
+            }
+        }
+        usage
+    }
+
+    /// Record a use of a file
+    fn touch(&mut self, name: &str) {
+        self.clock += 1;
+        if let Some(file) = self.files.get_mut(name) {
+            file.last_use = self.clock;
+        }
+    }
+
+    /// Record a new file. Returns the names of the least recently used files
+    /// that have to be removed to fit into the budget, which never include
+    /// the new file.
+    fn insert(&mut self, name: String, bytes: u64) -> Vec<String> {
+        self.remove(&name);
+        self.clock += 1;
+        self.total_bytes += bytes;
+        self.files.insert(
+            name.clone(),
+            FileUsage {
+                bytes,
+                last_use: self.clock,
+            },
+        );
+
+        let mut evicted = vec![];
+        let budget = match self.budget {
+            Some(budget) => budget,
+            None => return evicted,
+        };
+        while self.total_bytes > budget {
+            let lru = self
+                .files
+                .iter()
+                .filter(|(file_name, _)| **file_name != name)
+                .min_by_key(|(_, file)| file.last_use)
+                .map(|(file_name, _)| file_name.clone());
+            match lru {
+                Some(lru) => {
+                    self.remove(&lru);
+                    evicted.push(lru);
+                }
+                None => break,
+            }
+        }
+        evicted
+    }
+
+    /// Forget a removed file
+    fn remove(&mut self, name: &str) {
+        if let Some(file) = self.files.remove(name) {
+            self.total_bytes -= file.bytes;
+        }
+    }
 }
 
 /// A universal engine compilation. The module can be serialized to/from bytes.
@@ -643,7 +915,7 @@ mod test {
                 );
 
                 assert!(
-                    module_file_exists(&cache.dir, &tx_read_storage_key.hash),
+                    cache.module_file_exists(&tx_read_storage_key.hash),
                     "The file must be written"
                 );
             }
@@ -680,13 +952,13 @@ mod test {
                 );
 
                 assert!(
-                    module_file_exists(&cache.dir, &tx_no_op.hash),
+                    cache.module_file_exists(&tx_no_op.hash),
                     "The file must be written"
                 );
 
                 // The previous module's file should still exist
                 assert!(
-                    module_file_exists(&cache.dir, &tx_read_storage_key.hash),
+                    cache.module_file_exists(&tx_read_storage_key.hash),
                     "The file must be written"
                 );
                 // But it should not be in-memory
@@ -730,13 +1002,13 @@ mod test {
                 );
 
                 assert!(
-                    module_file_exists(&cache.dir, &tx_read_storage_key.hash),
+                    cache.module_file_exists(&tx_read_storage_key.hash),
                     "The file must be written"
                 );
 
                 // The previous module's file should still exist
                 assert!(
-                    module_file_exists(&cache.dir, &tx_no_op.hash),
+                    cache.module_file_exists(&tx_no_op.hash),
                     "The file must be written"
                 );
                 // But it should not be in-memory
@@ -771,13 +1043,13 @@ mod test {
                 );
 
                 assert!(
-                    module_file_exists(&cache.dir, &tx_read_storage_key.hash),
+                    cache.module_file_exists(&tx_read_storage_key.hash),
                     "The file must be written"
                 );
 
                 // The previous module's file should still exist
                 assert!(
-                    module_file_exists(&cache.dir, &tx_no_op.hash),
+                    cache.module_file_exists(&tx_no_op.hash),
                     "The file must be written"
                 );
                 // But it should not be in-memory
@@ -824,6 +1096,135 @@ mod test {
         }
     }
 
+    #[test]
+    fn test_fetch_or_compile_with_gas_costs() {
+        let tx_no_op = load_wasm(TestWasms::TxNoOp.path());
+        let (mut cache, _tmp_dir) = cache(tx_no_op.size * 2 + 1);
+
+        cache.compile_or_fetch(&tx_no_op.code).unwrap().unwrap();
+        assert!(cache.fetch(&tx_no_op.hash).unwrap().is_some());
+
+        // The module compiled with the default costs must not be used with
+        // other wasm costs
+        cache.set_gas_costs(GasCostTable {
+            wasm_instruction: 1,
+            ..Default::default()
+        });
+        let module_hash = cache.module_hash(&tx_no_op.hash);
+        assert_ne!(module_hash, tx_no_op.hash);
+        assert_matches!(
+            cache.fetch(&tx_no_op.hash).unwrap(),
+            None,
+            "The module must not be compiled with the new costs"
+        );
+
+        cache.compile_or_fetch(&tx_no_op.code).unwrap().unwrap();
+        assert!(
+            cache.module_file_exists(&module_hash),
+            "The file must be written"
+        );
+        assert!(cache.fetch(&tx_no_op.hash).unwrap().is_some());
+
+        // Costs that don't affect the wasm code don't need a recompilation
+        cache.set_gas_costs(GasCostTable {
+            verify_tx_sig: 1,
+            ..Default::default()
+        });
+        assert_eq!(cache.module_hash(&tx_no_op.hash), tx_no_op.hash);
+    }
+
+    #[test]
+    fn test_fetch_corrupted_module_file() {
+        let tx_no_op = load_wasm(TestWasms::TxNoOp.path());
+        let (mut cache, tmp_dir) = cache(tx_no_op.size * 2 + 1);
+        cache.compile_or_fetch(&tx_no_op.code).unwrap().unwrap();
+
+        let path = tmp_dir.path().join(cache.module_file_name(&tx_no_op.hash));
+        let mut content = fs::read(&path).unwrap();
+        let last = content.len() - 1;
+        content[last] ^= 1;
+        fs::write(&path, content).unwrap();
+
+        // A new cache with the same dir, as after a restart
+        let mut cache =
+            Cache::<TestCache, WasmCacheRwAccess>::new(tmp_dir.path(), 1);
+        assert_matches!(
+            cache.fetch(&tx_no_op.hash).unwrap(),
+            None,
+            "The corrupted module must not be loaded"
+        );
+        assert!(
+            !cache.module_file_exists(&tx_no_op.hash),
+            "The corrupted file must be removed"
+        );
+
+        cache.compile_or_fetch(&tx_no_op.code).unwrap().unwrap();
+        let mut cache =
+            Cache::<TestCache, WasmCacheRwAccess>::new(tmp_dir.path(), 1);
+        assert!(
+            cache.fetch(&tx_no_op.hash).unwrap().is_some(),
+            "The recompiled module must be loaded from file"
+        );
+    }
+
+    #[test]
+    fn test_module_files_disk_budget() {
+        let tx_no_op = load_wasm(TestWasms::TxNoOp.path());
+        let tx_read_storage_key = load_wasm(TestWasms::TxReadStorageKey.path());
+
+        // Find the sizes of the module files
+        let (mut cache, tmp_dir) = cache(1);
+        let mut file_bytes = 0;
+        for wasm in [&tx_no_op, &tx_read_storage_key] {
+            cache.compile_or_fetch(&wasm.code).unwrap().unwrap();
+            let path = tmp_dir.path().join(cache.module_file_name(&wasm.hash));
+            file_bytes += fs::metadata(path).unwrap().len();
+        }
+
+        // Only one of the files fits into the budget
+        let dir = tempdir().unwrap();
+        let mut cache =
+            Cache::<TestCache, WasmCacheRwAccess>::new_with_disk_budget(
+                dir.path(),
+                1,
+                Some(file_bytes - 1),
+            );
+        cache.compile_or_fetch(&tx_no_op.code).unwrap().unwrap();
+        cache
+            .compile_or_fetch(&tx_read_storage_key.code)
+            .unwrap()
+            .unwrap();
+        assert!(
+            !cache.module_file_exists(&tx_no_op.hash),
+            "The least recently used file must be evicted"
+        );
+        assert!(cache.module_file_exists(&tx_read_storage_key.hash));
+
+        // The evicted module gets compiled again
+        cache.compile_or_fetch(&tx_no_op.code).unwrap().unwrap();
+        assert!(cache.module_file_exists(&tx_no_op.hash));
+        assert!(!cache.module_file_exists(&tx_read_storage_key.hash));
+    }
+
+    #[test]
+    fn test_disk_usage_evicts_least_recently_used() {
+        let mut usage = DiskUsage {
+            budget: Some(10),
+            ..Default::default()
+        };
+        assert!(usage.insert("a".to_string(), 4).is_empty());
+        assert!(usage.insert("b".to_string(), 4).is_empty());
+        usage.touch("a");
+        assert_eq!(usage.insert("c".to_string(), 4), vec!["b".to_string()]);
+        assert_eq!(usage.total_bytes, 8);
+
+        // A new file over the budget is kept on its own
+        let mut evicted = usage.insert("d".to_string(), 20);
+        evicted.sort();
+        assert_eq!(evicted, vec!["a".to_string(), "c".to_string()]);
+        assert_eq!(usage.total_bytes, 20);
+    }
+
     #[test]
     fn test_fetch_or_compile_invalid_wasm() {
         // Some random bytes
@@ -848,7 +1249,7 @@ mod test {
         assert_matches!(progress.get(&hash), None, "Any progress is removed");
 
         assert!(
-            !module_file_exists(&cache.dir, &hash),
+            !cache.module_file_exists(&hash),
             "The file must not be written"
         );
     }
@@ -906,7 +1307,7 @@ mod test {
                 );
 
                 assert!(
-                    module_file_exists(&cache.dir, &vp_always_true.hash),
+                    cache.module_file_exists(&vp_always_true.hash),
                     "The file must be written"
                 );
             }
@@ -942,13 +1343,13 @@ mod test {
                 );
 
                 assert!(
-                    module_file_exists(&cache.dir, &vp_eval.hash),
+                    cache.module_file_exists(&vp_eval.hash),
                     "The file must be written"
                 );
 
                 // The previous module's file should still exist
                 assert!(
-                    module_file_exists(&cache.dir, &vp_always_true.hash),
+                    cache.module_file_exists(&vp_always_true.hash),
                     "The file must be written"
                 );
                 // But it should not be in-memory
@@ -1003,7 +1404,7 @@ mod test {
             );
 
             assert!(
-                !module_file_exists(&cache.dir, &hash),
+                !cache.module_file_exists(&hash),
                 "The file must not be written"
             );
         }
@@ -61,9 +61,9 @@ where
         "env" => {
             "memory" => initial_memory,
             // Wasm middleware gas injection hook
-            "gas" => Function::new_native_with_env(wasm_store, env.clone(), host_env::tx_charge_gas),
+            "gas" => Function::new_native_with_env(wasm_store, env.clone(), host_env::tx_charge_wasm_gas),
             // Whitelisted gas exposed function, we need two different functions just because of colliding names in the vm_host_env macro to generate implementations
-            "namada_tx_charge_gas" => Function::new_native_with_env(wasm_store, env.clone(), host_env::tx_charge_gas),
+            "namada_tx_charge_gas" => Function::new_native_with_env(wasm_store, env.clone(), host_env::tx_charge_wasm_gas),
             "namada_tx_read" => Function::new_native_with_env(wasm_store, env.clone(), host_env::tx_read),
             "namada_tx_result_buffer" => Function::new_native_with_env(wasm_store, env.clone(), host_env::tx_result_buffer),
             "namada_tx_has_key" => Function::new_native_with_env(wasm_store, env.clone(), host_env::tx_has_key),
@@ -110,9 +110,9 @@ where
         "env" => {
             "memory" => initial_memory,
             // Wasm middleware gas injection hook
-            "gas" => Function::new_native_with_env(wasm_store, env.clone(), host_env::vp_charge_gas),
+            "gas" => Function::new_native_with_env(wasm_store, env.clone(), host_env::vp_charge_wasm_gas),
             // Whitelisted gas exposed function, we need two different functions just because of colliding names in the vm_host_env macro to generate implementations
-            "namada_vp_charge_gas" => Function::new_native_with_env(wasm_store, env.clone(), host_env::vp_charge_gas),
+            "namada_vp_charge_gas" => Function::new_native_with_env(wasm_store, env.clone(), host_env::vp_charge_wasm_gas),
             "namada_vp_read_pre" => Function::new_native_with_env(wasm_store, env.clone(), host_env::vp_read_pre),
             "namada_vp_read_post" => Function::new_native_with_env(wasm_store, env.clone(), host_env::vp_read_post),
             "namada_vp_read_temp" => Function::new_native_with_env(wasm_store, env.clone(), host_env::vp_read_temp),
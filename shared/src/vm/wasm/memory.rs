@@ -6,7 +6,7 @@ use std::str::Utf8Error;
 use std::sync::Arc;
 
 use borsh_ext::BorshSerializeExt;
-use namada_core::ledger::gas::MEMORY_ACCESS_GAS_PER_BYTE;
+use namada_core::ledger::gas::GasCostTable;
 use thiserror::Error;
 use wasmer::{
     vm, BaseTunables, HostEnvInitError, LazyInit, Memory, MemoryError,
@@ -223,9 +223,20 @@ fn write_memory_bytes(
 #[derive(Debug, Clone, Default)]
 pub struct WasmMemory {
     inner: LazyInit<wasmer::Memory>,
+    /// The gas costs charged for the memory accesses
+    gas_costs: GasCostTable,
 }
 
 impl WasmMemory {
+    /// Create a memory, to be initialized from the wasm exports, that charges
+    /// the given gas costs
+    pub fn new(gas_costs: GasCostTable) -> Self {
+        Self {
+            inner: LazyInit::default(),
+            gas_costs,
+        }
+    }
+
     /// Initialize the memory from the given exports, used to implement
     /// [`wasmer::WasmerEnv`].
     pub fn init_env_memory(
@@ -256,7 +267,7 @@ impl VmMemory for WasmMemory {
     fn read_bytes(&self, offset: u64, len: usize) -> Result<(Vec<u8>, u64)> {
         let memory = self.inner.get_ref().ok_or(Error::UninitializedMemory)?;
         let bytes = read_memory_bytes(memory, offset, len)?;
-        let gas = bytes.len() as u64 * MEMORY_ACCESS_GAS_PER_BYTE;
+        let gas = bytes.len() as u64 * self.gas_costs.memory_access_per_byte;
         Ok((bytes, gas))
     }
 
@@ -265,7 +276,8 @@ impl VmMemory for WasmMemory {
         // No need for a separate gas multiplier for writes since we are only
         // writing to memory and we already charge gas for every memory page
         // allocated
-        let gas = bytes.as_ref().len() as u64 * MEMORY_ACCESS_GAS_PER_BYTE;
+        let gas =
+            bytes.as_ref().len() as u64 * self.gas_costs.memory_access_per_byte;
         let memory = self.inner.get_ref().ok_or(Error::UninitializedMemory)?;
         write_memory_bytes(memory, offset, bytes)?;
         Ok(gas)
@@ -4,7 +4,9 @@ use std::collections::BTreeSet;
 use std::marker::PhantomData;
 
 use borsh::BorshDeserialize;
-use namada_core::ledger::gas::{GasMetering, TxGasMeter, WASM_MEMORY_PAGE_GAS};
+use namada_core::ledger::gas::{
+    GasCostTable, GasMetering, SETUP_GAS_SECTION, TxGasMeter,
+};
 use namada_core::ledger::storage::write_log::StorageModification;
 use namada_core::types::transaction::TxSentinel;
 use namada_core::types::validity_predicate::VpSentinel;
@@ -145,6 +147,9 @@ where
         }
     }
 
+    // Loading and compiling the code is attributed to the setup, in case the
+    // execution is profiled
+    gas_meter.enter_section(SETUP_GAS_SECTION);
     let (module, store) = fetch_or_compile(
         tx_wasm_cache,
         &tx_code.code,
@@ -159,7 +164,7 @@ where
 
     let mut sentinel = TxSentinel::default();
     let env = TxVmEnv::new(
-        WasmMemory::default(),
+        WasmMemory::new(storage.gas_costs),
         storage,
         write_log,
         &mut iterators,
@@ -255,7 +260,7 @@ where
 
     let mut sentinel = VpSentinel::default();
     let env = VpVmEnv::new(
-        WasmMemory::default(),
+        WasmMemory::new(storage.gas_costs),
         address,
         storage,
         write_log,
@@ -443,7 +448,7 @@ where
         let storage = unsafe { ctx.storage.get() };
         let gas_meter = unsafe { ctx.gas_meter.get() };
         let env = VpVmEnv {
-            memory: WasmMemory::default(),
+            memory: WasmMemory::new(storage.gas_costs),
             ctx,
         };
 
@@ -484,8 +489,12 @@ pub fn untrusted_wasm_store(limit: Limit<BaseTunables>) -> wasmer::Store {
     )
 }
 
-/// Inject gas counter and stack-height limiter into the given wasm code
-pub fn prepare_wasm_code<T: AsRef<[u8]>>(code: T) -> Result<Vec<u8>> {
+/// Inject gas counter and stack-height limiter into the given wasm code. The
+/// gas counter charges the wasm costs of the given table.
+pub fn prepare_wasm_code<T: AsRef<[u8]>>(
+    code: T,
+    gas_costs: &GasCostTable,
+) -> Result<Vec<u8>> {
     let module: elements::Module = elements::deserialize_buffer(code.as_ref())
         .map_err(Error::DeserializationError)?;
     let module = wasm_instrument::gas_metering::inject(
@@ -493,7 +502,7 @@ pub fn prepare_wasm_code<T: AsRef<[u8]>>(code: T) -> Result<Vec<u8>> {
         wasm_instrument::gas_metering::host_function::Injector::new(
             "env", "gas",
         ),
-        &get_gas_rules(),
+        &get_gas_rules(gas_costs),
     )
     .map_err(|_original_module| Error::GasMeterInjection)?;
     let module =
@@ -517,6 +526,8 @@ where
     CN: 'static + CacheName,
     CA: 'static + WasmCacheAccess,
 {
+    // The compiled modules must meter the wasm costs in force
+    wasm_cache.set_gas_costs(storage.gas_costs);
     match code_or_hash {
         Commitment::Hash(code_hash) => {
             let (module, store, tx_len) = match wasm_cache.fetch(code_hash)? {
@@ -589,22 +600,22 @@ where
             };
 
             gas_meter
-                .add_wasm_load_from_storage_gas(tx_len)
+                .add_wasm_load_from_storage_gas(tx_len, &storage.gas_costs)
                 .map_err(|e| Error::GasError(e.to_string()))?;
             gas_meter
-                .add_compiling_gas(tx_len)
+                .add_compiling_gas(tx_len, &storage.gas_costs)
                 .map_err(|e| Error::GasError(e.to_string()))?;
             Ok((module, store))
         }
         Commitment::Id(code) => {
             let tx_len = code.len() as u64;
             gas_meter
-                .add_wasm_validation_gas(tx_len)
+                .add_wasm_validation_gas(tx_len, &storage.gas_costs)
                 .map_err(|e| Error::GasError(e.to_string()))?;
             validate_untrusted_wasm(code).map_err(Error::ValidationError)?;
 
             gas_meter
-                .add_compiling_gas(tx_len)
+                .add_compiling_gas(tx_len, &storage.gas_costs)
                 .map_err(|e| Error::GasError(e.to_string()))?;
             match wasm_cache.compile_or_fetch(code)? {
                 Some((module, store)) => Ok((module, store)),
@@ -615,11 +626,13 @@ where
 }
 
 /// Get the gas rules used to meter wasm operations
-fn get_gas_rules() -> wasm_instrument::gas_metering::ConstantCostRules {
+fn get_gas_rules(
+    gas_costs: &GasCostTable,
+) -> wasm_instrument::gas_metering::ConstantCostRules {
     // NOTE: costs set to 0 don't actually trigger the injection of a call to
     // the gas host function (no useless instructions are injected)
-    let instruction_cost = 0;
-    let memory_grow_cost = WASM_MEMORY_PAGE_GAS;
+    let instruction_cost = gas_costs.wasm_instruction;
+    let memory_grow_cost = gas_costs.wasm_memory_page;
     let call_per_local_cost = 0;
     wasm_instrument::gas_metering::ConstantCostRules::new(
         instruction_cost,
@@ -26,7 +26,7 @@ use namada_apps::config::ethereum_bridge;
 use namada_apps::config::utils::convert_tm_addr_to_socket_addr;
 use namada_apps::facade::tendermint_config::net::Address as TendermintAddress;
 use namada_core::ledger::governance::cli::onchain::{
-    PgfFunding, PgfFundingTarget, StewardsUpdate,
+    PgfContinousTarget, PgfFunding, PgfFundingTarget, StewardsUpdate,
 };
 use namada_core::types::token::NATIVE_MAX_DECIMAL_PLACES;
 use namada_sdk::masp::fs::FsShieldedUtils;
@@ -2369,9 +2369,13 @@ fn pgf_governance_proposal() -> Result<()> {
     let christel = find_address(&test, CHRISTEL)?;
 
     let pgf_funding = PgfFunding {
-        continous: vec![PgfFundingTarget {
+        continous: vec![PgfContinousTarget {
             amount: token::Amount::from_u64(10),
-            address: bertha.clone(),
+            address: bertha.to_string(),
+            ibc: None,
+            start_epoch: None,
+            end_epoch: None,
+            cap: None,
         }],
         retro: vec![PgfFundingTarget {
             amount: token::Amount::from_u64(5),
@@ -29,7 +29,7 @@ mod tests {
         get_dummy_header as tm_dummy_header, Error as IbcError,
     };
     use namada::ledger::tx_env::TxEnv;
-    use namada::proto::Tx;
+    use namada::proto::{Section, Signature, Signer, Tx};
     use namada::types::hash::Hash;
     use namada::types::key::*;
     use namada::types::storage::{self, BlockHash, BlockHeight, Key, KeySeg};
@@ -498,6 +498,54 @@ mod tests {
                     )
                     .is_err()
             );
+
+            // A dummy signature, made with another key than the one it's
+            // attributed to, only passes the dummy check, which charges the
+            // gas of a verification all the same
+            let mut dummy_signed_tx = signed_tx_data.clone();
+            dummy_signed_tx
+                .sections
+                .retain(|section| !matches!(section, Section::Signature(_)));
+            let mut section = Signature {
+                targets: vec![dummy_signed_tx.raw_header_hash()],
+                signer: Signer::PubKeys(vec![pk.clone()]),
+                signatures: Default::default(),
+            };
+            let hash = section.get_raw_hash();
+            section
+                .signatures
+                .insert(0, common::SigScheme::sign(&other_keypair, hash));
+            dummy_signed_tx.add_section(Section::Signature(section));
+            let pks_map = AccountPublicKeysMap::from_iter(vec![pk.clone()]);
+            assert!(
+                dummy_signed_tx
+                    .verify_signatures(
+                        &[dummy_signed_tx.header_hash()],
+                        pks_map.clone(),
+                        &None,
+                        1,
+                        None,
+                        || Ok(())
+                    )
+                    .is_err()
+            );
+            let mut charged_verifications = 0;
+            assert!(
+                dummy_signed_tx
+                    .verify_dummy_signatures(
+                        &[dummy_signed_tx.header_hash()],
+                        pks_map,
+                        &None,
+                        1,
+                        None,
+                        || {
+                            charged_verifications += 1;
+                            Ok(())
+                        }
+                    )
+                    .is_ok()
+            );
+            assert_eq!(charged_verifications, 1);
         }
     }
 
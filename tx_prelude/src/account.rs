@@ -13,6 +13,9 @@ pub fn init_account(
         &data.public_keys,
         data.threshold,
     )?;
+    if let Some(vesting) = &data.vesting {
+        storage_api::account::init_vesting(ctx, owner, vesting)?;
+    }
 
     Ok(owner.to_owned())
 }
@@ -23,8 +23,8 @@ pub use namada_core::ledger::governance::storage as gov_storage;
 pub use namada_core::ledger::parameters::storage as parameters_storage;
 pub use namada_core::ledger::storage::types::encode;
 pub use namada_core::ledger::storage_api::{
-    self, governance, iter_prefix, iter_prefix_bytes, Error, OptionExt,
-    ResultExt, StorageRead, StorageWrite,
+    self, fee_grant, governance, iter_prefix, iter_prefix_bytes, scheduler,
+    Error, OptionExt, ResultExt, StorageRead, StorageWrite,
 };
 pub use namada_core::ledger::tx_env::TxEnv;
 pub use namada_core::ledger::{eth_bridge, parameters};
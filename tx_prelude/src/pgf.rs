@@ -1,4 +1,6 @@
-use namada_core::types::transaction::pgf::UpdateStewardCommission;
+use namada_core::types::transaction::pgf::{
+    PausePgfFunding, UpdateStewardCommission,
+};
 
 use super::*;
 
@@ -16,3 +18,9 @@ pub fn remove_steward(ctx: &mut Ctx, data: &Address) -> EnvResult<()> {
 
     Ok(())
 }
+
+pub fn pause_funding(ctx: &mut Ctx, data: PausePgfFunding) -> EnvResult<()> {
+    storage_api::pgf::pause_payment(ctx, &data.steward, &data.funding)?;
+
+    Ok(())
+}
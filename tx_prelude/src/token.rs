@@ -28,6 +28,8 @@ pub fn transfer(
         dest_bal.receive(&amount.amount);
         ctx.write(&src_key, src_bal)?;
         ctx.write(&dest_key, dest_bal)?;
+        // The source's VP checks the debit against its spending limits
+        storage_api::account::record_spending(ctx, src, token, amount.amount)?;
     }
     Ok(())
 }
@@ -15,9 +15,11 @@ use std::marker::PhantomData;
 
 pub use borsh::{BorshDeserialize, BorshSerialize};
 use borsh_ext::BorshSerializeExt;
+pub use namada_core::ledger::fee_grant::storage as fee_grant_storage;
 pub use namada_core::ledger::governance::storage as gov_storage;
 pub use namada_core::ledger::parameters;
 pub use namada_core::ledger::pgf::storage as pgf_storage;
+pub use namada_core::ledger::scheduler::storage as scheduler_storage;
 pub use namada_core::ledger::storage_api::{
     self, iter_prefix, iter_prefix_bytes, Error, OptionExt, ResultExt,
     StorageRead,
@@ -81,11 +83,41 @@ pub fn is_proposal_accepted(ctx: &Ctx, proposal_id: u64) -> VpResult {
 
 /// Verify section signatures
 pub fn verify_signatures(ctx: &Ctx, tx: &Tx, owner: &Address) -> VpResult {
+    let public_keys_index_map =
+        storage_api::account::public_keys_index_map(&ctx.pre(), owner)?;
+    verify_signatures_of_keys(ctx, tx, owner, public_keys_index_map)
+}
+
+/// Verify section signatures, counting only the signatures of the owner's
+/// public keys that may authorize the given kind of changes. For a debit,
+/// `spending` holds the token and the amount of it debited in the current
+/// epoch, including this debit.
+pub fn verify_signatures_with_permission(
+    ctx: &Ctx,
+    tx: &Tx,
+    owner: &Address,
+    permission: account::AccountPermission,
+    spending: Option<(&Address, token::Amount)>,
+) -> VpResult {
+    let public_keys_index_map =
+        storage_api::account::permitted_public_keys_index_map(
+            &ctx.pre(),
+            owner,
+            permission,
+            spending,
+        )?;
+    verify_signatures_of_keys(ctx, tx, owner, public_keys_index_map)
+}
+
+fn verify_signatures_of_keys(
+    ctx: &Ctx,
+    tx: &Tx,
+    owner: &Address,
+    public_keys_index_map: account::AccountPublicKeysMap,
+) -> VpResult {
     let max_signatures_per_transaction =
         parameters::max_signatures_per_transaction(&ctx.pre())?;
 
-    let public_keys_index_map =
-        storage_api::account::public_keys_index_map(&ctx.pre(), owner)?;
     let threshold =
         storage_api::account::threshold(&ctx.pre(), owner)?.unwrap_or(1);
 
@@ -129,6 +161,35 @@ pub fn is_valid_tx(ctx: &Ctx, tx_data: &Tx) -> VpResult {
     }
 }
 
+/// The net amount of tokens bonded by the given source in the transaction.
+/// Unbonds and redelegations offset it, so that only the tokens debited from
+/// the source's balance into new bonds are counted.
+pub fn bonded_amount(
+    ctx: &Ctx,
+    source: &Address,
+    keys_changed: &BTreeSet<storage::Key>,
+) -> EnvResult<token::Amount> {
+    let bonded: token::Change = keys_changed
+        .iter()
+        .filter(|key| {
+            matches!(
+                proof_of_stake::storage::is_bond_key(key),
+                Some((bond_id, _)) if &bond_id.source == source
+            )
+        })
+        .map(|key| {
+            let pre: token::Amount = ctx.read_pre(key)?.unwrap_or_default();
+            let post: token::Amount = ctx.read_post(key)?.unwrap_or_default();
+            Ok(post.change() - pre.change())
+        })
+        .sum::<EnvResult<token::Change>>()?;
+    Ok(if bonded.non_negative() {
+        token::Amount::from(bonded)
+    } else {
+        token::Amount::zero()
+    })
+}
+
 /// Format and log a string in a debug build.
 ///
 /// In WASM target debug build, the message will be printed at the
@@ -2,6 +2,8 @@
 pub mod tx_bond;
 #[cfg(feature = "tx_bridge_pool")]
 pub mod tx_bridge_pool;
+#[cfg(feature = "tx_cancel_scheduled_tx")]
+pub mod tx_cancel_scheduled_tx;
 #[cfg(feature = "tx_change_consensus_key")]
 pub mod tx_change_consensus_key;
 #[cfg(feature = "tx_change_validator_commission")]
@@ -12,6 +14,12 @@ pub mod tx_change_validator_metadata;
 pub mod tx_claim_rewards;
 #[cfg(feature = "tx_deactivate_validator")]
 pub mod tx_deactivate_validator;
+#[cfg(feature = "tx_delegate_votes")]
+pub mod tx_delegate_votes;
+#[cfg(feature = "tx_deposit_proposal")]
+pub mod tx_deposit_proposal;
+#[cfg(feature = "tx_grant_fee_allowance")]
+pub mod tx_grant_fee_allowance;
 #[cfg(feature = "tx_ibc")]
 pub mod tx_ibc;
 #[cfg(feature = "tx_init_account")]
@@ -20,6 +28,8 @@ pub mod tx_init_account;
 pub mod tx_init_proposal;
 #[cfg(feature = "tx_init_validator")]
 pub mod tx_init_validator;
+#[cfg(feature = "tx_pause_pgf_funding")]
+pub mod tx_pause_pgf_funding;
 #[cfg(feature = "tx_reactivate_validator")]
 pub mod tx_reactivate_validator;
 #[cfg(feature = "tx_redelegate")]
@@ -28,6 +38,10 @@ pub mod tx_redelegate;
 pub mod tx_resign_steward;
 #[cfg(feature = "tx_reveal_pk")]
 pub mod tx_reveal_pk;
+#[cfg(feature = "tx_revoke_fee_allowance")]
+pub mod tx_revoke_fee_allowance;
+#[cfg(feature = "tx_schedule_tx")]
+pub mod tx_schedule_tx;
 #[cfg(feature = "tx_transfer")]
 pub mod tx_transfer;
 #[cfg(feature = "tx_unbond")]
@@ -49,3 +63,5 @@ pub mod vp_implicit;
 pub mod vp_user;
 #[cfg(feature = "vp_validator")]
 pub mod vp_validator;
+#[cfg(feature = "vp_vesting")]
+pub mod vp_vesting;
@@ -0,0 +1,19 @@
+//! A tx to cancel a scheduled tx and get its escrowed fees refunded
+
+use namada_tx_prelude::*;
+
+#[transaction(gas = 835970)]
+fn apply_tx(ctx: &mut Ctx, tx_data: Tx) -> TxResult {
+    let signed = tx_data;
+    let data = signed.data().ok_or_err_msg("Missing data").map_err(|err| {
+        ctx.set_commitment_sentinel();
+        err
+    })?;
+    let tx_data =
+        transaction::scheduler::CancelScheduledTx::try_from_slice(&data[..])
+            .wrap_err("failed to decode CancelScheduledTx")?;
+
+    debug_log!("apply_tx called to cancel the scheduled tx {}", tx_data.id);
+
+    scheduler::cancel_scheduled_tx(ctx, tx_data.id, &tx_data.owner)
+}
@@ -0,0 +1,22 @@
+//! A tx to delegate the governance votes of an address
+
+use namada_tx_prelude::*;
+
+#[transaction(gas = 840866)]
+fn apply_tx(ctx: &mut Ctx, tx_data: Tx) -> TxResult {
+    let signed = tx_data;
+    let data = signed.data().ok_or_err_msg("Missing data").map_err(|err| {
+        ctx.set_commitment_sentinel();
+        err
+    })?;
+    let tx_data =
+        transaction::governance::DelegateVotes::try_from_slice(&data[..])
+            .wrap_err("failed to decode DelegateVotes")?;
+
+    debug_log!(
+        "apply_tx called to delegate the governance votes of {}",
+        tx_data.delegator
+    );
+
+    governance::delegate_votes(ctx, tx_data)
+}
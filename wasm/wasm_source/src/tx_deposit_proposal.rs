@@ -0,0 +1,23 @@
+//! A tx to top up the deposit of a governance proposal
+
+use namada_tx_prelude::*;
+
+#[transaction(gas = 840866)]
+fn apply_tx(ctx: &mut Ctx, tx_data: Tx) -> TxResult {
+    let signed = tx_data;
+    let data = signed.data().ok_or_err_msg("Missing data").map_err(|err| {
+        ctx.set_commitment_sentinel();
+        err
+    })?;
+    let tx_data =
+        transaction::governance::DepositProposalData::try_from_slice(&data[..])
+            .wrap_err("failed to decode DepositProposalData")?;
+
+    debug_log!(
+        "apply_tx called to deposit {} on the proposal {}",
+        tx_data.amount.to_string_native(),
+        tx_data.id
+    );
+
+    governance::deposit_proposal(ctx, tx_data)
+}
@@ -0,0 +1,22 @@
+//! A tx to authorise an address to pay the wrapper fees of another one
+
+use namada_tx_prelude::*;
+
+#[transaction(gas = 840866)]
+fn apply_tx(ctx: &mut Ctx, tx_data: Tx) -> TxResult {
+    let signed = tx_data;
+    let data = signed.data().ok_or_err_msg("Missing data").map_err(|err| {
+        ctx.set_commitment_sentinel();
+        err
+    })?;
+    let tx_data =
+        transaction::fee_grant::GrantFeeAllowance::try_from_slice(&data[..])
+            .wrap_err("failed to decode GrantFeeAllowance")?;
+
+    debug_log!(
+        "apply_tx called to grant a fee allowance to {}",
+        tx_data.grantee
+    );
+
+    fee_grant::grant_fee_allowance(ctx, tx_data)
+}
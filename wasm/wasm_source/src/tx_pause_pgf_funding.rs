@@ -0,0 +1,19 @@
+//! A tx for a steward to pause a continuous pgf funding
+
+use namada_tx_prelude::transaction::pgf::PausePgfFunding;
+use namada_tx_prelude::*;
+
+#[transaction(gas = 1222239)]
+fn apply_tx(ctx: &mut Ctx, tx_data: Tx) -> TxResult {
+    let signed = tx_data;
+    let data = signed.data().ok_or_err_msg("Missing data").map_err(|err| {
+        ctx.set_commitment_sentinel();
+        err
+    })?;
+    let pause = PausePgfFunding::try_from_slice(&data[..])
+        .wrap_err("failed to decode a PausePgfFunding")?;
+
+    pgf::pause_funding(ctx, pause)?;
+
+    Ok(())
+}
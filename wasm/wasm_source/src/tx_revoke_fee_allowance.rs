@@ -0,0 +1,22 @@
+//! A tx to revoke a fee allowance
+
+use namada_tx_prelude::*;
+
+#[transaction(gas = 835970)]
+fn apply_tx(ctx: &mut Ctx, tx_data: Tx) -> TxResult {
+    let signed = tx_data;
+    let data = signed.data().ok_or_err_msg("Missing data").map_err(|err| {
+        ctx.set_commitment_sentinel();
+        err
+    })?;
+    let tx_data =
+        transaction::fee_grant::RevokeFeeAllowance::try_from_slice(&data[..])
+            .wrap_err("failed to decode RevokeFeeAllowance")?;
+
+    debug_log!(
+        "apply_tx called to revoke the fee allowance of {}",
+        tx_data.grantee
+    );
+
+    fee_grant::revoke_fee_allowance(ctx, &tx_data.granter, &tx_data.grantee)
+}
@@ -0,0 +1,20 @@
+//! A tx to schedule the execution of a signed tx at a future block height or
+//! epoch, escrowing its fees
+
+use namada_tx_prelude::*;
+
+#[transaction(gas = 1004780)]
+fn apply_tx(ctx: &mut Ctx, tx_data: Tx) -> TxResult {
+    let signed = tx_data;
+    let data = signed.data().ok_or_err_msg("Missing data").map_err(|err| {
+        ctx.set_commitment_sentinel();
+        err
+    })?;
+    let tx_data = transaction::scheduler::ScheduleTx::try_from_slice(&data[..])
+        .wrap_err("failed to decode ScheduleTx")?;
+
+    debug_log!("apply_tx called to schedule a tx {}", tx_data.schedule);
+
+    scheduler::schedule_tx(ctx, tx_data)?;
+    Ok(())
+}
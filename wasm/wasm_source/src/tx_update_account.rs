@@ -54,5 +54,9 @@ fn apply_tx(ctx: &mut Ctx, tx: Tx) -> TxResult {
         }
     }
 
+    if let Some(key_policies) = &tx_data.key_policies {
+        storage_api::account::set_key_policies(ctx, owner, key_policies)?;
+    }
+
     Ok(())
 }
@@ -23,6 +23,8 @@ enum KeyType<'a> {
     },
     PoS,
     GovernanceVote(&'a Address),
+    ScheduledTx(&'a Address),
+    FeeAllowance(&'a Address),
     Unknown,
 }
 
@@ -41,6 +43,25 @@ impl<'a> From<&'a storage::Key> for KeyType<'a> {
             } else {
                 Self::Unknown
             }
+        } else if let Some(voter) = gov_storage::keys::is_delegate_vote_key(key)
+        {
+            Self::GovernanceVote(voter)
+        } else if let Some((delegator, _)) =
+            gov_storage::keys::is_vote_delegation_key(key)
+        {
+            Self::GovernanceVote(delegator)
+        } else if let Some((_, delegator, _)) =
+            gov_storage::keys::is_vote_delegator_key(key)
+        {
+            Self::GovernanceVote(delegator)
+        } else if let Some((owner, _)) =
+            scheduler_storage::keys::is_owner_key(key)
+        {
+            Self::ScheduledTx(owner)
+        } else if let Some((granter, _)) =
+            fee_grant_storage::keys::is_allowance_key(key)
+        {
+            Self::FeeAllowance(granter)
         } else {
             Self::Unknown
         }
@@ -163,6 +184,16 @@ fn validate_tx(
                     true
                 }
             }
+            KeyType::ScheduledTx(owner) => {
+                // Scheduling or cancelling a tx of this address must be
+                // signed
+                if owner == &addr { *valid_sig } else { true }
+            }
+            KeyType::FeeAllowance(granter) => {
+                // Granting or revoking a fee allowance paid by this address
+                // must be signed
+                if granter == &addr { *valid_sig } else { true }
+            }
             KeyType::Unknown => {
                 if key.segments.get(0) == Some(&addr.to_db_key()) {
                     // Unknown changes to this address space require a valid
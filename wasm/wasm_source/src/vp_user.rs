@@ -7,25 +7,37 @@
 //! valid signature.
 //!
 //! Any other storage key changes are allowed only with a valid signature.
+//!
+//! The public keys of the account can be restricted by policies. A key with a
+//! policy only counts towards the signature threshold for the kinds of changes
+//! it's permitted, and for the debits that keep the amount of a token debited
+//! in the epoch within its spending limit.
 
+use namada_vp_prelude::account::{AccountPermission, EpochSpending};
 use namada_vp_prelude::storage::KeySeg;
 use namada_vp_prelude::*;
 use once_cell::unsync::Lazy;
 
 enum KeyType<'a> {
-    Token { owner: &'a Address },
+    Token {
+        token: &'a Address,
+        owner: &'a Address,
+    },
     PoS,
     Vp(&'a Address),
     Masp,
     PgfStward(&'a Address),
     GovernanceVote(&'a Address),
+    ScheduledTx(&'a Address),
+    FeeAllowance(&'a Address),
+    Spending(&'a Address),
     Unknown,
 }
 
 impl<'a> From<&'a storage::Key> for KeyType<'a> {
     fn from(key: &'a storage::Key) -> KeyType<'a> {
-        if let Some([_, owner]) = token::is_any_token_balance_key(key) {
-            Self::Token { owner }
+        if let Some([token, owner]) = token::is_any_token_balance_key(key) {
+            Self::Token { token, owner }
         } else if proof_of_stake::storage::is_pos_key(key) {
             Self::PoS
         } else if gov_storage::keys::is_vote_key(key) {
@@ -35,8 +47,33 @@ impl<'a> From<&'a storage::Key> for KeyType<'a> {
             } else {
                 Self::Unknown
             }
+        } else if let Some(voter) = gov_storage::keys::is_delegate_vote_key(key)
+        {
+            Self::GovernanceVote(voter)
+        } else if let Some((delegator, _)) =
+            gov_storage::keys::is_vote_delegation_key(key)
+        {
+            Self::GovernanceVote(delegator)
+        } else if let Some((_, delegator, _)) =
+            gov_storage::keys::is_vote_delegator_key(key)
+        {
+            Self::GovernanceVote(delegator)
+        } else if let Some((owner, _)) =
+            scheduler_storage::keys::is_owner_key(key)
+        {
+            Self::ScheduledTx(owner)
+        } else if let Some((granter, _)) =
+            fee_grant_storage::keys::is_allowance_key(key)
+        {
+            Self::FeeAllowance(granter)
         } else if let Some(address) = pgf_storage::keys::is_stewards_key(key) {
             Self::PgfStward(address)
+        } else if let Some((_, steward)) =
+            pgf_storage::keys::is_funding_pause_key(key)
+        {
+            Self::PgfStward(steward)
+        } else if let Some((owner, _)) = key::is_spending_key(key) {
+            Self::Spending(owner)
         } else if let Some(address) = key.is_validity_predicate() {
             Self::Vp(address)
         } else if token::is_masp_key(key) {
@@ -62,9 +99,19 @@ fn validate_tx(
         verifiers
     );
 
-    let valid_sig = Lazy::new(|| {
-        matches!(verify_signatures(ctx, &tx_data, &addr), Ok(true))
-    });
+    let valid_sig_with = |permission| {
+        matches!(
+            verify_signatures_with_permission(
+                ctx, &tx_data, &addr, permission, None
+            ),
+            Ok(true)
+        )
+    };
+    let valid_sig = Lazy::new(|| valid_sig_with(AccountPermission::Admin));
+    let transfer_sig =
+        Lazy::new(|| valid_sig_with(AccountPermission::Transfer));
+    let pos_sig = Lazy::new(|| valid_sig_with(AccountPermission::PoS));
+    let gov_sig = Lazy::new(|| valid_sig_with(AccountPermission::Governance));
 
     if !is_valid_tx(ctx, &tx_data)? {
         return reject();
@@ -73,21 +120,57 @@ fn validate_tx(
     for key in keys_changed.iter() {
         let key_type: KeyType = key.into();
         let is_valid = match key_type {
-            KeyType::Token { owner, .. } => {
+            KeyType::Token { token, owner } => {
                 if owner == &addr {
                     let pre: token::Amount =
                         ctx.read_pre(key)?.unwrap_or_default();
                     let post: token::Amount =
                         ctx.read_post(key)?.unwrap_or_default();
                     let change = post.change() - pre.change();
-                    // debit has to signed, credit doesn't
-                    let valid = change.non_negative() || *valid_sig;
+                    // debit has to signed by the keys permitted to transfer
+                    // the token, credit doesn't. The tokens bonded in the tx
+                    // are instead authorized by the bond.
+                    let bonded = if change.non_negative() {
+                        token::Amount::zero()
+                    } else if token == &ctx.get_native_token()? {
+                        bonded_amount(ctx, &addr, &keys_changed)?
+                    } else {
+                        token::Amount::zero()
+                    };
+                    let transferred = pre
+                        .checked_sub(post)
+                        .and_then(|debited| debited.checked_sub(bonded))
+                        .unwrap_or_default();
+                    let valid = if transferred.is_zero() {
+                        true
+                    } else if storage_api::account::has_spending_limit(
+                        &ctx.pre(),
+                        &addr,
+                        token,
+                    )? {
+                        match spent_with_debit(ctx, &addr, token, transferred)?
+                        {
+                            Some(spent) => matches!(
+                                verify_signatures_with_permission(
+                                    ctx,
+                                    &tx_data,
+                                    &addr,
+                                    AccountPermission::Transfer,
+                                    Some((token, spent)),
+                                ),
+                                Ok(true)
+                            ),
+                            None => false,
+                        }
+                    } else {
+                        *transfer_sig
+                    };
                     debug_log!(
-                        "token key: {}, change: {:?}, valid_sig: {}, valid \
+                        "token key: {}, change: {:?}, bonded: {}, valid \
                          modification: {}",
                         key,
                         change,
-                        *valid_sig,
+                        bonded.to_string_native(),
                         valid
                     );
                     valid
@@ -115,7 +198,7 @@ fn validate_tx(
                     Some(bond_id) => {
                         // Bonds and unbonds changes for this address
                         // must be signed
-                        bond_id.source != addr || *valid_sig
+                        bond_id.source != addr || *pos_sig
                     }
                     None => {
                         // Any other PoS changes are allowed without signature
@@ -131,11 +214,21 @@ fn validate_tx(
             }
             KeyType::GovernanceVote(voter) => {
                 if voter == &addr {
-                    *valid_sig
+                    *gov_sig
                 } else {
                     true
                 }
             }
+            KeyType::ScheduledTx(owner) => {
+                // Scheduling or cancelling a tx of this address must be
+                // signed
+                if owner == &addr { *valid_sig } else { true }
+            }
+            KeyType::FeeAllowance(granter) => {
+                // Granting or revoking a fee allowance paid by this address
+                // must be signed
+                if granter == &addr { *valid_sig } else { true }
+            }
             KeyType::PgfStward(address) => {
                 if address == &addr {
                     *valid_sig
@@ -143,6 +236,14 @@ fn validate_tx(
                     true
                 }
             }
+            KeyType::Spending(owner) => {
+                if owner == &addr {
+                    // The amount debited in the epoch may only grow
+                    is_spending_increased(ctx, key)? && *transfer_sig
+                } else {
+                    true
+                }
+            }
             KeyType::Vp(owner) => {
                 let has_post: bool = ctx.has_key_post(key)?;
                 if owner == &addr {
@@ -179,8 +280,41 @@ fn validate_tx(
     accept()
 }
 
+/// The amount of the token debited from the owner in the current epoch,
+/// including the given debit. Returns `None` if the tx didn't record the
+/// debit.
+fn spent_with_debit(
+    ctx: &Ctx,
+    owner: &Address,
+    token: &Address,
+    debited: token::Amount,
+) -> EnvResult<Option<token::Amount>> {
+    let key = key::spending_key(owner, token);
+    let epoch = ctx.get_block_epoch()?;
+    let pre: EpochSpending = ctx.read_pre(&key)?.unwrap_or_default();
+    let post: Option<EpochSpending> = ctx.read_post(&key)?;
+    Ok(post.and_then(|post| {
+        let recorded = pre.spent_in(epoch).checked_add(debited)?;
+        (post.epoch == epoch && post.amount >= recorded).then_some(post.amount)
+    }))
+}
+
+/// Check that the record of the amount debited in the current epoch only
+/// grows
+fn is_spending_increased(ctx: &Ctx, key: &storage::Key) -> VpResult {
+    let epoch = ctx.get_block_epoch()?;
+    let pre: EpochSpending = ctx.read_pre(key)?.unwrap_or_default();
+    let post: Option<EpochSpending> = ctx.read_post(key)?;
+    Ok(matches!(
+        post,
+        Some(post) if post.epoch == epoch && post.amount >= pre.spent_in(epoch)
+    ))
+}
+
 #[cfg(test)]
 mod tests {
+    use std::collections::BTreeMap;
+
     use address::testing::arb_non_internal_address;
     use namada::ledger::pos::{GenesisValidator, PosParams};
     use namada::proto::{Code, Data, Signature};
@@ -195,7 +329,7 @@ mod tests {
     use namada_tests::vp::vp_host_env::storage::Key;
     use namada_tests::vp::*;
     use namada_tx_prelude::{StorageWrite, TxEnv};
-    use namada_vp_prelude::account::AccountPublicKeysMap;
+    use namada_vp_prelude::account::{AccountPublicKeysMap, KeyPolicy};
     use namada_vp_prelude::key::RefTo;
     use proptest::prelude::*;
     use storage::testing::arb_account_storage_key_no_vp;
@@ -640,6 +774,211 @@ mod tests {
         }
     }
 
+    /// Run the VP on a signed transfer of the given amount from the VP owner,
+    /// whose only public key is restricted by the given policy
+    fn run_signed_transfer_with_policy(
+        policy: KeyPolicy,
+        amount: token::Amount,
+    ) -> bool {
+        // Initialize a tx environment
+        let mut tx_env = TestTxEnv::default();
+
+        let vp_owner = address::testing::established_address_1();
+        let keypair = key::testing::keypair_1();
+        let public_key = keypair.ref_to();
+        let target = address::testing::established_address_2();
+        let token = address::nam();
+
+        // Spawn the accounts to be able to modify their storage
+        tx_env.spawn_accounts([&vp_owner, &target, &token]);
+        tx_env.init_account_storage(&vp_owner, vec![public_key.clone()], 1);
+        storage_api::account::set_key_policies(
+            &mut tx_env.wl_storage,
+            &vp_owner,
+            &BTreeMap::from([(public_key.clone(), policy)]),
+        )
+        .unwrap();
+
+        // Credit the tokens to the VP owner before running the transaction to
+        // be able to transfer from it
+        tx_env.credit_tokens(&vp_owner, &token, amount);
+        // write the denomination of NAM into storage
+        storage_api::token::write_denom(
+            &mut tx_env.wl_storage,
+            &token,
+            token::NATIVE_MAX_DECIMAL_PLACES.into(),
+        )
+        .unwrap();
+
+        let amount = token::DenominatedAmount {
+            amount,
+            denom: token::NATIVE_MAX_DECIMAL_PLACES.into(),
+        };
+
+        // Initialize VP environment from a transaction
+        vp_host_env::init_from_tx(vp_owner.clone(), tx_env, |address| {
+            // Apply transfer in a transaction, which records the debit
+            tx_host_env::token::transfer(
+                tx::ctx(),
+                address,
+                &target,
+                &token,
+                amount,
+            )
+            .unwrap();
+        });
+
+        let pks_map = AccountPublicKeysMap::from_iter(vec![public_key]);
+
+        let mut vp_env = vp_host_env::take();
+        let mut tx = vp_env.tx.clone();
+        tx.set_data(Data::new(vec![]));
+        tx.set_code(Code::new(vec![], None));
+        tx.add_section(Section::Signature(Signature::new(
+            vec![tx.raw_header_hash()],
+            pks_map.index_secret_keys(vec![keypair]),
+            None,
+        )));
+        let signed_tx = tx.clone();
+        vp_env.tx = signed_tx.clone();
+        let keys_changed: BTreeSet<storage::Key> =
+            vp_env.all_touched_storage_keys();
+        let verifiers: BTreeSet<Address> = BTreeSet::default();
+        vp_host_env::set(vp_env);
+        validate_tx(&CTX, signed_tx, vp_owner, keys_changed, verifiers).unwrap()
+    }
+
+    /// Test that a debit transfer signed by a key without the permission to
+    /// transfer is rejected.
+    #[test]
+    fn test_signed_debit_transfer_without_permission_rejected() {
+        let policy = KeyPolicy {
+            permissions: BTreeSet::from([
+                AccountPermission::PoS,
+                AccountPermission::Governance,
+            ]),
+            ..Default::default()
+        };
+        let amount = token::Amount::from_uint(10_098_123, 0).unwrap();
+        assert!(!run_signed_transfer_with_policy(policy, amount));
+    }
+
+    /// Test that a debit transfer signed by a key with a spending limit is
+    /// only accepted within the limit.
+    #[test]
+    fn test_signed_debit_transfer_spending_limit() {
+        let limit = token::Amount::from_uint(10_098_123, 0).unwrap();
+        let policy = KeyPolicy {
+            permissions: BTreeSet::from([AccountPermission::Transfer]),
+            spending_limits: BTreeMap::from([(address::nam(), limit)]),
+        };
+        assert!(run_signed_transfer_with_policy(policy.clone(), limit));
+
+        let over_limit = limit.checked_add(1.into()).unwrap();
+        assert!(!run_signed_transfer_with_policy(policy, over_limit));
+    }
+
+    /// Test that the tokens bonded with a key that's only permitted to use
+    /// PoS are accepted.
+    #[test]
+    fn test_signed_pos_action_with_permission_accepted() {
+        // Init PoS genesis
+        let pos_params = PosParams::default();
+        let validator = address::testing::established_address_3();
+        let initial_stake = token::Amount::from_uint(10_098_123, 0).unwrap();
+        let consensus_key = key::testing::keypair_2().ref_to();
+        let protocol_key = key::testing::keypair_1().ref_to();
+        let commission_rate = Dec::new(5, 2).unwrap();
+        let max_commission_rate_change = Dec::new(1, 2).unwrap();
+
+        let genesis_validators = [GenesisValidator {
+            address: validator.clone(),
+            tokens: initial_stake,
+            consensus_key,
+            protocol_key,
+            commission_rate,
+            max_commission_rate_change,
+            eth_hot_key: key::common::PublicKey::Secp256k1(
+                key::testing::gen_keypair::<key::secp256k1::SigScheme>()
+                    .ref_to(),
+            ),
+            eth_cold_key: key::common::PublicKey::Secp256k1(
+                key::testing::gen_keypair::<key::secp256k1::SigScheme>()
+                    .ref_to(),
+            ),
+            metadata: Default::default(),
+        }];
+
+        init_pos(&genesis_validators[..], &pos_params, Epoch(0));
+
+        // Initialize a tx environment
+        let mut tx_env = tx_host_env::take();
+
+        let secret_key = key::testing::keypair_1();
+        let public_key = secret_key.ref_to();
+        let vp_owner: Address = address::testing::established_address_2();
+        let target = address::testing::established_address_3();
+        let token = address::nam();
+        let amount = token::Amount::from_uint(10_098_123, 0).unwrap();
+        let bond_amount = token::Amount::from_uint(5_098_123, 0).unwrap();
+
+        // Spawn the accounts to be able to modify their storage
+        tx_env.spawn_accounts([&target, &token]);
+        tx_env.init_account_storage(&vp_owner, vec![public_key.clone()], 1);
+        let policy = KeyPolicy {
+            permissions: BTreeSet::from([AccountPermission::PoS]),
+            ..Default::default()
+        };
+        storage_api::account::set_key_policies(
+            &mut tx_env.wl_storage,
+            &vp_owner,
+            &BTreeMap::from([(public_key.clone(), policy)]),
+        )
+        .unwrap();
+
+        // write the denomination of NAM into storage
+        storage_api::token::write_denom(
+            &mut tx_env.wl_storage,
+            &token,
+            token::NATIVE_MAX_DECIMAL_PLACES.into(),
+        )
+        .unwrap();
+
+        // Credit the tokens to the VP owner before running the transaction to
+        // be able to transfer from it
+        tx_env.credit_tokens(&vp_owner, &token, amount);
+
+        // Initialize VP environment from a transaction
+        vp_host_env::init_from_tx(vp_owner.clone(), tx_env, |_address| {
+            // Bond the tokens, which debits them from the owner
+            tx::ctx()
+                .bond_tokens(Some(&vp_owner), &validator, bond_amount)
+                .unwrap();
+        });
+
+        let pks_map = AccountPublicKeysMap::from_iter(vec![public_key]);
+
+        let mut vp_env = vp_host_env::take();
+        let mut tx = vp_env.tx.clone();
+        tx.set_data(Data::new(vec![]));
+        tx.set_code(Code::new(vec![], None));
+        tx.add_section(Section::Signature(Signature::new(
+            vec![tx.raw_header_hash()],
+            pks_map.index_secret_keys(vec![secret_key]),
+            None,
+        )));
+        let signed_tx = tx.clone();
+        vp_env.tx = signed_tx.clone();
+        let keys_changed: BTreeSet<storage::Key> =
+            vp_env.all_touched_storage_keys();
+        let verifiers: BTreeSet<Address> = BTreeSet::default();
+        vp_host_env::set(vp_env);
+        assert!(
+            validate_tx(&CTX, signed_tx, vp_owner, keys_changed, verifiers)
+                .unwrap()
+        );
+    }
+
     proptest! {
         /// Test that an unsigned tx that performs arbitrary storage writes or
         /// deletes to  the account is rejected.
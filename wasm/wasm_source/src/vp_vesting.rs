@@ -0,0 +1,514 @@
+//! A vesting account VP.
+//!
+//! This VP provides the same signature verification as the basic user VP, but
+//! the balance of the vesting token can't be debited below the amount that is
+//! still locked by the vesting schedule of the account at the time of the
+//! block.
+//!
+//! Locked tokens may still be bonded to PoS, so the tokens bonded by the
+//! account in the same transaction count towards the locked amount.
+//!
+//! The vesting schedule is written when the account is initialized and it
+//! can't be changed afterwards. The VP can only be updated once all the tokens
+//! are vested.
+
+use namada_vp_prelude::storage::KeySeg;
+use namada_vp_prelude::*;
+use once_cell::unsync::Lazy;
+
+enum KeyType<'a> {
+    Token {
+        token: &'a Address,
+        owner: &'a Address,
+    },
+    PoS,
+    Vp(&'a Address),
+    Vesting(&'a Address),
+    Masp,
+    PgfStward(&'a Address),
+    GovernanceVote(&'a Address),
+    ScheduledTx(&'a Address),
+    FeeAllowance(&'a Address),
+    Unknown,
+}
+
+impl<'a> From<&'a storage::Key> for KeyType<'a> {
+    fn from(key: &'a storage::Key) -> KeyType<'a> {
+        if let Some([token, owner]) = token::is_any_token_balance_key(key) {
+            Self::Token { token, owner }
+        } else if proof_of_stake::storage::is_pos_key(key) {
+            Self::PoS
+        } else if gov_storage::keys::is_vote_key(key) {
+            let voter_address = gov_storage::keys::get_voter_address(key);
+            if let Some(address) = voter_address {
+                Self::GovernanceVote(address)
+            } else {
+                Self::Unknown
+            }
+        } else if let Some(voter) = gov_storage::keys::is_delegate_vote_key(key)
+        {
+            Self::GovernanceVote(voter)
+        } else if let Some((delegator, _)) =
+            gov_storage::keys::is_vote_delegation_key(key)
+        {
+            Self::GovernanceVote(delegator)
+        } else if let Some((_, delegator, _)) =
+            gov_storage::keys::is_vote_delegator_key(key)
+        {
+            Self::GovernanceVote(delegator)
+        } else if let Some((owner, _)) =
+            scheduler_storage::keys::is_owner_key(key)
+        {
+            Self::ScheduledTx(owner)
+        } else if let Some((granter, _)) =
+            fee_grant_storage::keys::is_allowance_key(key)
+        {
+            Self::FeeAllowance(granter)
+        } else if let Some(address) = pgf_storage::keys::is_stewards_key(key) {
+            Self::PgfStward(address)
+        } else if let Some((_, steward)) =
+            pgf_storage::keys::is_funding_pause_key(key)
+        {
+            Self::PgfStward(steward)
+        } else if let Some(address) = vesting::is_vesting_key(key) {
+            Self::Vesting(address)
+        } else if let Some(address) = key.is_validity_predicate() {
+            Self::Vp(address)
+        } else if token::is_masp_key(key) {
+            Self::Masp
+        } else {
+            Self::Unknown
+        }
+    }
+}
+
+#[validity_predicate(gas = 152870)]
+fn validate_tx(
+    ctx: &Ctx,
+    tx_data: Tx,
+    addr: Address,
+    keys_changed: BTreeSet<storage::Key>,
+    verifiers: BTreeSet<Address>,
+) -> VpResult {
+    debug_log!(
+        "vp_vesting called with user addr: {}, key_changed: {:?}, verifiers: \
+         {:?}",
+        addr,
+        keys_changed,
+        verifiers
+    );
+
+    let valid_sig = Lazy::new(|| {
+        matches!(verify_signatures(ctx, &tx_data, &addr), Ok(true))
+    });
+
+    if !is_valid_tx(ctx, &tx_data)? {
+        return reject();
+    }
+
+    let vesting: Option<vesting::Vesting> =
+        ctx.read_pre(&vesting::vesting_key(&addr))?;
+
+    for key in keys_changed.iter() {
+        let key_type: KeyType = key.into();
+        let is_valid = match key_type {
+            KeyType::Token { token, owner } => {
+                if owner == &addr {
+                    let pre: token::Amount =
+                        ctx.read_pre(key)?.unwrap_or_default();
+                    let post: token::Amount =
+                        ctx.read_post(key)?.unwrap_or_default();
+                    let change = post.change() - pre.change();
+                    // debit has to signed and it can't dip into the locked
+                    // tokens, credit doesn't
+                    let valid = if change.non_negative() {
+                        true
+                    } else if !*valid_sig {
+                        false
+                    } else {
+                        match &vesting {
+                            Some(vesting) if &vesting.token == token => {
+                                let locked = locked_amount(ctx, vesting)?;
+                                let bonded = if token
+                                    == &ctx.get_native_token()?
+                                {
+                                    bonded_amount(ctx, &addr, &keys_changed)?
+                                } else {
+                                    token::Amount::zero()
+                                };
+                                post.checked_add(bonded)
+                                    .unwrap_or_else(token::Amount::max)
+                                    >= locked
+                            }
+                            _ => true,
+                        }
+                    };
+                    debug_log!(
+                        "token key: {}, change: {:?}, valid_sig: {}, valid \
+                         modification: {}",
+                        key,
+                        change,
+                        *valid_sig,
+                        valid
+                    );
+                    valid
+                } else {
+                    debug_log!(
+                        "This address ({}) is not of owner ({}) of token key: \
+                         {}",
+                        addr,
+                        owner,
+                        key
+                    );
+                    // If this is not the owner, allow any change
+                    true
+                }
+            }
+            KeyType::PoS => {
+                // Allow the account to be used in PoS
+                let bond_id = proof_of_stake::storage::is_bond_key(key)
+                    .map(|(bond_id, _)| bond_id)
+                    .or_else(|| {
+                        proof_of_stake::storage::is_unbond_key(key)
+                            .map(|(bond_id, _, _)| bond_id)
+                    });
+                let valid = match bond_id {
+                    Some(bond_id) => {
+                        // Bonds and unbonds changes for this address
+                        // must be signed
+                        bond_id.source != addr || *valid_sig
+                    }
+                    None => {
+                        // Any other PoS changes are allowed without signature
+                        true
+                    }
+                };
+                debug_log!(
+                    "PoS key {} {}",
+                    key,
+                    if valid { "accepted" } else { "rejected" }
+                );
+                valid
+            }
+            KeyType::GovernanceVote(voter) => {
+                if voter == &addr {
+                    *valid_sig
+                } else {
+                    true
+                }
+            }
+            KeyType::ScheduledTx(owner) => {
+                // Scheduling or cancelling a tx of this address must be
+                // signed
+                if owner == &addr { *valid_sig } else { true }
+            }
+            KeyType::FeeAllowance(granter) => {
+                // Granting or revoking a fee allowance paid by this address
+                // must be signed
+                if granter == &addr { *valid_sig } else { true }
+            }
+            KeyType::PgfStward(address) => {
+                if address == &addr {
+                    *valid_sig
+                } else {
+                    true
+                }
+            }
+            KeyType::Vesting(owner) => {
+                // The vesting schedule is immutable once the account is
+                // initialized
+                owner != &addr
+            }
+            KeyType::Vp(owner) => {
+                let has_post: bool = ctx.has_key_post(key)?;
+                if owner == &addr {
+                    if has_post {
+                        let vp_hash: Vec<u8> =
+                            ctx.read_bytes_post(key)?.unwrap();
+                        let is_vested = match &vesting {
+                            Some(vesting) => {
+                                locked_amount(ctx, vesting)?.is_zero()
+                            }
+                            None => true,
+                        };
+                        *valid_sig
+                            && is_vested
+                            && is_vp_whitelisted(ctx, &vp_hash)?
+                    } else {
+                        false
+                    }
+                } else {
+                    let vp_hash: Vec<u8> = ctx.read_bytes_post(key)?.unwrap();
+                    is_vp_whitelisted(ctx, &vp_hash)?
+                }
+            }
+            KeyType::Masp => true,
+            KeyType::Unknown => {
+                if key.segments.get(0) == Some(&addr.to_db_key()) {
+                    // Unknown changes to this address space require a valid
+                    // signature
+                    *valid_sig
+                } else {
+                    // Unknown changes anywhere else are permitted
+                    true
+                }
+            }
+        };
+        if !is_valid {
+            debug_log!("key {} modification failed vp", key);
+            return reject();
+        }
+    }
+
+    accept()
+}
+
+/// The amount of the vesting token that is still locked at the time of the
+/// current block. Without a block header, the whole amount is locked.
+fn locked_amount(
+    ctx: &Ctx,
+    vesting: &vesting::Vesting,
+) -> EnvResult<token::Amount> {
+    let height = ctx.get_block_height()?;
+    Ok(match ctx.get_block_header(height)? {
+        Some(header) => vesting.locked_at(header.time),
+        None => vesting.amount,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use namada::ledger::pos::{GenesisValidator, PosParams};
+    use namada::proto::{Code, Data, Signature};
+    use namada::types::dec::Dec;
+    use namada::types::hash::Hash;
+    use namada::types::storage::{Epoch, Header};
+    use namada::types::time::{DateTimeUtc, TimeZone, Utc};
+    use namada::types::vesting::{Vesting, VestingSchedule};
+    // Use this as `#[test]` annotation to enable logging
+    use namada_tests::log::test;
+    use namada_tests::native_vp::pos::init_pos;
+    use namada_tests::tx::{self, tx_host_env, TestTxEnv};
+    use namada_tests::vp::*;
+    use namada_vp_prelude::account::AccountPublicKeysMap;
+    use namada_vp_prelude::key::RefTo;
+
+    use super::*;
+
+    fn at(secs: i64) -> DateTimeUtc {
+        Utc.timestamp_opt(secs, 0).single().unwrap().into()
+    }
+
+    /// Init the vesting schedule of the VP owner and set the time of the
+    /// current block
+    fn init_vesting(
+        tx_env: &mut TestTxEnv,
+        vp_owner: &Address,
+        vesting: &Vesting,
+        block_time: DateTimeUtc,
+    ) {
+        storage_api::account::init_vesting(
+            &mut tx_env.wl_storage,
+            vp_owner,
+            vesting,
+        )
+        .unwrap();
+        tx_env.wl_storage.storage.header = Some(Header {
+            hash: Hash::zero(),
+            time: block_time,
+            next_validators_hash: Hash::zero(),
+        });
+    }
+
+    /// Sign the tx of the VP env and run the VP with it
+    fn run_signed_vp(
+        vp_owner: Address,
+        keypair: key::common::SecretKey,
+    ) -> bool {
+        let pks_map = AccountPublicKeysMap::from_iter(vec![keypair.ref_to()]);
+
+        let mut vp_env = vp_host_env::take();
+        let mut tx = vp_env.tx.clone();
+        tx.set_data(Data::new(vec![]));
+        tx.set_code(Code::new(vec![], None));
+        tx.add_section(Section::Signature(Signature::new(
+            vec![tx.raw_header_hash()],
+            pks_map.index_secret_keys(vec![keypair]),
+            None,
+        )));
+        let signed_tx = tx.clone();
+        vp_env.tx = signed_tx.clone();
+        let keys_changed: BTreeSet<storage::Key> =
+            vp_env.all_touched_storage_keys();
+        let verifiers: BTreeSet<Address> = BTreeSet::default();
+        vp_host_env::set(vp_env);
+        validate_tx(&CTX, signed_tx, vp_owner, keys_changed, verifiers).unwrap()
+    }
+
+    /// Test that a signed debit transfer is accepted only up to the vested
+    /// amount.
+    #[test]
+    fn test_signed_debit_transfer_of_vested_tokens() {
+        let vp_owner = address::testing::established_address_1();
+        let keypair = key::testing::keypair_1();
+        let target = address::testing::established_address_2();
+        let token = address::nam();
+        let amount = token::Amount::from_uint(10_000_000, 0).unwrap();
+        let vesting = Vesting {
+            token: token.clone(),
+            amount,
+            schedule: VestingSchedule::Linear {
+                start: at(1_000),
+                end: at(2_000),
+            },
+        };
+
+        // A quarter of the tokens are vested
+        for (transferred, expected) in [(2_500_000, true), (2_500_001, false)] {
+            // Initialize a tx environment
+            let mut tx_env = TestTxEnv::default();
+
+            // Spawn the accounts to be able to modify their storage
+            tx_env.spawn_accounts([&vp_owner, &target, &token]);
+            tx_env.init_account_storage(&vp_owner, vec![keypair.ref_to()], 1);
+            init_vesting(&mut tx_env, &vp_owner, &vesting, at(1_250));
+
+            // Credit the tokens to the VP owner before running the
+            // transaction to be able to transfer from it
+            tx_env.credit_tokens(&vp_owner, &token, amount);
+            // write the denomination of NAM into storage
+            storage_api::token::write_denom(
+                &mut tx_env.wl_storage,
+                &token,
+                token::NATIVE_MAX_DECIMAL_PLACES.into(),
+            )
+            .unwrap();
+
+            let transferred = token::DenominatedAmount {
+                amount: token::Amount::from_uint(transferred, 0).unwrap(),
+                denom: token::NATIVE_MAX_DECIMAL_PLACES.into(),
+            };
+
+            // Initialize VP environment from a transaction
+            vp_host_env::init_from_tx(vp_owner.clone(), tx_env, |address| {
+                // Apply transfer in a transaction
+                tx_host_env::token::transfer(
+                    tx::ctx(),
+                    address,
+                    &target,
+                    &token,
+                    transferred,
+                )
+                .unwrap();
+            });
+
+            assert_eq!(
+                run_signed_vp(vp_owner.clone(), keypair.clone()),
+                expected
+            );
+        }
+    }
+
+    /// Test that locked tokens can be bonded with a valid signature.
+    #[test]
+    fn test_signed_bond_of_locked_tokens_accepted() {
+        // Init PoS genesis
+        let pos_params = PosParams::default();
+        let validator = address::testing::established_address_3();
+        let initial_stake = token::Amount::from_uint(10_098_123, 0).unwrap();
+        let consensus_key = key::testing::keypair_2().ref_to();
+        let protocol_key = key::testing::keypair_1().ref_to();
+        let eth_cold_key = key::testing::keypair_3().ref_to();
+        let eth_hot_key = key::testing::keypair_4().ref_to();
+        let commission_rate = Dec::new(5, 2).unwrap();
+        let max_commission_rate_change = Dec::new(1, 2).unwrap();
+
+        let genesis_validators = [GenesisValidator {
+            address: validator.clone(),
+            tokens: initial_stake,
+            consensus_key,
+            protocol_key,
+            commission_rate,
+            max_commission_rate_change,
+            eth_hot_key,
+            eth_cold_key,
+            metadata: Default::default(),
+        }];
+
+        init_pos(&genesis_validators[..], &pos_params, Epoch(0));
+
+        // Initialize a tx environment
+        let mut tx_env = tx_host_env::take();
+
+        let keypair = key::testing::keypair_1();
+        let vp_owner: Address = address::testing::established_address_2();
+        let token = address::nam();
+        let amount = token::Amount::from_uint(10_098_123, 0).unwrap();
+        let bond_amount = token::Amount::from_uint(5_098_123, 0).unwrap();
+
+        // Spawn the accounts to be able to modify their storage
+        tx_env.spawn_accounts([&token]);
+        tx_env.init_account_storage(&vp_owner, vec![keypair.ref_to()], 1);
+        // All the tokens are locked
+        let vesting = Vesting {
+            token: token.clone(),
+            amount,
+            schedule: VestingSchedule::Cliff { unlock: at(2_000) },
+        };
+        init_vesting(&mut tx_env, &vp_owner, &vesting, at(1_000));
+        // write the denomination of NAM into storage
+        storage_api::token::write_denom(
+            &mut tx_env.wl_storage,
+            &token,
+            token::NATIVE_MAX_DECIMAL_PLACES.into(),
+        )
+        .unwrap();
+
+        // Credit the tokens to the VP owner before running the transaction to
+        // be able to bond them
+        tx_env.credit_tokens(&vp_owner, &token, amount);
+
+        // Initialize VP environment from a transaction
+        vp_host_env::init_from_tx(vp_owner.clone(), tx_env, |_address| {
+            tx::ctx()
+                .bond_tokens(Some(&vp_owner), &validator, bond_amount)
+                .unwrap();
+        });
+
+        assert!(run_signed_vp(vp_owner, keypair));
+    }
+
+    /// Test that the vesting schedule can't be changed, even with a valid
+    /// signature.
+    #[test]
+    fn test_signed_vesting_update_rejected() {
+        // Initialize a tx environment
+        let mut tx_env = TestTxEnv::default();
+
+        let vp_owner = address::testing::established_address_1();
+        let keypair = key::testing::keypair_1();
+        let token = address::nam();
+        let vesting = Vesting {
+            token,
+            amount: token::Amount::from_uint(10_000_000, 0).unwrap(),
+            schedule: VestingSchedule::Cliff { unlock: at(2_000) },
+        };
+
+        // Spawn the accounts to be able to modify their storage
+        tx_env.spawn_accounts([&vp_owner]);
+        tx_env.init_account_storage(&vp_owner, vec![keypair.ref_to()], 1);
+        init_vesting(&mut tx_env, &vp_owner, &vesting, at(1_000));
+
+        // Initialize VP environment from a transaction
+        vp_host_env::init_from_tx(vp_owner.clone(), tx_env, |address| {
+            let vesting = Vesting {
+                schedule: VestingSchedule::Cliff { unlock: at(1_000) },
+                ..vesting.clone()
+            };
+            storage_api::account::init_vesting(tx::ctx(), address, &vesting)
+                .unwrap();
+        });
+
+        assert!(!run_signed_vp(vp_owner, keypair));
+    }
+}